@@ -0,0 +1,14 @@
+from voting_node.utils import decrypt_legacy_secret, encrypt_secret, envelope_decrypt, envelope_encrypt
+
+
+def test_envelope_roundtrip():
+    secrets_key = "EVENT_DB_SECRETS_KEY_THAT_MUST_BE_CHANGED"
+    encrypted, data_key = envelope_encrypt(["ed25519_sk1seckey", "ed25519_sk1netkey"], secrets_key)
+    assert envelope_decrypt(encrypted, data_key, secrets_key) == ["ed25519_sk1seckey", "ed25519_sk1netkey"]
+
+
+def test_legacy_secret_converted_by_migration():
+    password = "SUPER_SECRET_SECRET_THAT_MUST_BE_CHANGED"
+    # the migration converts the stored text to bytes with `convert_to(seckey, 'UTF8')`
+    stored = encrypt_secret("ed25519_sk1seckey", password).encode()
+    assert decrypt_legacy_secret(stored, password) == "ed25519_sk1seckey"
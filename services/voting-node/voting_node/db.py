@@ -7,9 +7,9 @@ from asyncpg import Connection
 from loguru import logger
 from pydantic import BaseModel
 
-from .envvar import SECRET_SECRET
+from .envvar import EVENTDB_SECRETS_KEY, SECRET_SECRET
 from .models import Contribution, Event, HostInfo, LeaderHostInfo, Objective, Proposal, Snapshot, VotePlan, Voter, VotingGroup
-from .utils import LEADER_REGEX, decrypt_secret, encrypt_secret, get_hostname
+from .utils import LEADER_REGEX, decrypt_legacy_secret, envelope_decrypt, envelope_encrypt, get_hostname
 
 
 class EventDb(BaseModel):
@@ -76,10 +76,15 @@ class EventDb(BaseModel):
             case None:
                 raise Exception("failed to fetch leader node info from DB")
             case record:
-                # fetch secret from envvar, fail if not present
-                encrypt_pass = os.environ[SECRET_SECRET]
-                seckey = decrypt_secret(record["seckey"], encrypt_pass)
-                netkey = decrypt_secret(record["netkey"], encrypt_pass)
+                if record["data_key"] is None:
+                    # the row was stored before the secrets were encrypted at rest,
+                    # they are still encrypted with the legacy password
+                    decrypt_pass = os.environ[SECRET_SECRET]
+                    seckey, netkey = (decrypt_legacy_secret(record[key], decrypt_pass) for key in ("seckey", "netkey"))
+                else:
+                    # fetch secret from envvar, fail if not present
+                    secrets_key = os.environ[EVENTDB_SECRETS_KEY]
+                    seckey, netkey = envelope_decrypt([record["seckey"], record["netkey"]], record["data_key"], secrets_key)
                 host_info = HostInfo(
                     hostname=record["hostname"],
                     event=record["event"],
@@ -92,15 +97,14 @@ class EventDb(BaseModel):
     async def insert_leader_host_info(self, host_info: HostInfo):
         """Insert the hostname row into the voting_node table."""
         conn = await self.connect()
-        fields = "hostname, event, seckey, pubkey, netkey"
-        values = "$1, $2, $3, $4, $5"
+        fields = "hostname, event, seckey, pubkey, netkey, data_key"
+        values = "$1, $2, $3, $4, $5, $6"
         query = f"INSERT INTO voting_node({fields}) VALUES({values}) RETURNING *"
         h = host_info
         # fetch secret from envvar, fail if not present
-        encrypt_pass = os.environ[SECRET_SECRET]
+        secrets_key = os.environ[EVENTDB_SECRETS_KEY]
 
-        enc_sk = encrypt_secret(h.seckey, encrypt_pass)
-        enc_nk = encrypt_secret(h.netkey, encrypt_pass)
+        (enc_sk, enc_nk), data_key = envelope_encrypt([h.seckey, h.netkey], secrets_key)
         result = await conn.execute(
             query,
             h.hostname,
@@ -108,6 +112,7 @@ class EventDb(BaseModel):
             enc_sk,
             h.pubkey,
             enc_nk,
+            data_key,
         )
         await conn.close()
 
@@ -43,3 +43,5 @@ The same CRS is shared per committee member, per event.
 """
 SECRET_SECRET: Final = "SECRET_SECRET"
 """Password used for key encryption."""
+EVENTDB_SECRETS_KEY: Final = "EVENT_DB_SECRETS_KEY"
+"""Key encrypting the secrets stored in the EventDB, shared with the other services using the EventDB."""
@@ -11,6 +11,7 @@ from typing import Final, Literal
 import yaml
 from cryptography.fernet import Fernet
 from cryptography.hazmat.primitives import hashes
+from cryptography.hazmat.primitives.ciphers.aead import ChaCha20Poly1305
 from cryptography.hazmat.primitives.kdf.pbkdf2 import PBKDF2HMAC
 from loguru import logger
 from pydantic.dataclasses import dataclass
@@ -35,6 +36,14 @@ SALT_BYTES: Final = 16
 """Size in bytes of encryption salt."""
 KDF_LENGTH: Final = 32
 """Length in bytes uses in key derivation."""
+SYMMETRIC_CIPHER_ITERATIONS: Final = 12983
+"""Hash iterations performed in key derivation by `symmetric-cipher`, used to encrypt the EventDB secrets."""
+SYMMETRIC_CIPHER_BLOCK_BYTES: Final = 64
+"""`symmetric-cipher` only encrypts data which size is a multiple of this."""
+NONCE_BYTES: Final = 12
+"""Size in bytes of the ChaCha20Poly1305 nonce."""
+DATA_KEY_BYTES: Final = 64
+"""Size in bytes of the data key encrypting the secrets of an EventDB row."""
 
 
 def get_hostname() -> str:
@@ -366,3 +375,71 @@ def decrypt_secret(encrypted: str, password: str) -> str:
     f = Fernet(key)
     secret = f.decrypt(encrypted_secret)
     return secret.decode()
+
+
+def decrypt_legacy_secret(encrypted: bytes, password: str) -> str:
+    """Decrypt a secret stored in the EventDB before the secrets were encrypted at rest.
+
+    These secrets were encrypted with `encrypt_secret`, the EventDB migration converted them to bytes.
+    """
+    return decrypt_secret(bytes(encrypted).decode(), password)
+
+
+def _symmetric_encrypt(password: bytes, data: bytes) -> bytes:
+    """Encrypt data in the format of `chain-wallet-libs/symmetric-cipher`."""
+    salt = secrets.token_bytes(SALT_BYTES)
+    nonce = secrets.token_bytes(NONCE_BYTES)
+    kdf = PBKDF2HMAC(
+        algorithm=hashes.SHA512(),
+        length=KDF_LENGTH,
+        salt=salt,
+        iterations=SYMMETRIC_CIPHER_ITERATIONS,
+    )
+    key = kdf.derive(password)
+    # the ciphertext is followed by the authentication tag
+    return bytes([1]) + salt + nonce + ChaCha20Poly1305(key).encrypt(nonce, data, None)
+
+
+def _symmetric_decrypt(password: bytes, encrypted: bytes) -> bytes:
+    """Decrypt data in the format of `chain-wallet-libs/symmetric-cipher`."""
+    if encrypted[:1] != bytes([1]):
+        raise Exception("invalid encrypted secret protocol")
+    salt = encrypted[1 : 1 + SALT_BYTES]
+    nonce = encrypted[1 + SALT_BYTES : 1 + SALT_BYTES + NONCE_BYTES]
+    kdf = PBKDF2HMAC(
+        algorithm=hashes.SHA512(),
+        length=KDF_LENGTH,
+        salt=salt,
+        iterations=SYMMETRIC_CIPHER_ITERATIONS,
+    )
+    key = kdf.derive(password)
+    return ChaCha20Poly1305(key).decrypt(nonce, encrypted[1 + SALT_BYTES + NONCE_BYTES :], None)
+
+
+def envelope_encrypt(secrets_list: list[str], secrets_key: str) -> tuple[list[bytes], bytes]:
+    """Encrypt the secrets of an EventDB row with a new data key.
+
+    Return the encrypted secrets, and the data key encrypted with the secrets key.
+    """
+    data_key = secrets.token_bytes(DATA_KEY_BYTES)
+
+    def pad(secret: str) -> bytes:
+        data = secret.encode()
+        padded = len(data).to_bytes(4, "big") + data
+        return padded + bytes(-len(padded) % SYMMETRIC_CIPHER_BLOCK_BYTES)
+
+    encrypted = [_symmetric_encrypt(data_key, pad(secret)) for secret in secrets_list]
+    return (encrypted, _symmetric_encrypt(secrets_key.encode(), data_key))
+
+
+def envelope_decrypt(encrypted: list[bytes], data_key: bytes | None, secrets_key: str) -> list[str]:
+    """Decrypt the secrets of an EventDB row with its encrypted data key."""
+    if data_key is None:
+        raise Exception("the secrets are still encrypted with the legacy password, run `cat-data-service encrypt-secrets`")
+    key = _symmetric_decrypt(secrets_key.encode(), data_key)
+
+    def unpad(padded: bytes) -> str:
+        length = int.from_bytes(padded[:4], "big")
+        return padded[4 : 4 + length].decode()
+
+    return [unpad(_symmetric_decrypt(key, secret)) for secret in encrypted]
@@ -158,7 +158,9 @@ pub fn find_vote(jormungandr_database: &Path, voting_key: String) -> Result<Vec<
 
                 let choice = match certificate.payload() {
                     chain_impl_mockchain::vote::Payload::Public { choice } => choice.as_byte(),
-                    chain_impl_mockchain::vote::Payload::Private { .. } => {
+                    chain_impl_mockchain::vote::Payload::Private { .. }
+                    | chain_impl_mockchain::vote::Payload::Approval { .. }
+                    | chain_impl_mockchain::vote::Payload::Ranked { .. } => {
                         // zeroing data to enable private voting support
                         // (at least everying exception choice, since it is disabled by design in private vote)
                         0u8
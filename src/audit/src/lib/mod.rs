@@ -2,3 +2,4 @@ pub mod find;
 pub mod offline;
 pub mod recover;
 pub mod tally;
+pub mod verify;
@@ -4,8 +4,8 @@ use bech32::{ToBase32, Variant};
 use chain_crypto::testing::TestCryptoRng;
 use chain_crypto::{Ed25519, SecretKey};
 use chain_vote::committee::MemberSecretKey;
-use chain_vote::tally::batch_decrypt;
-use chain_vote::{EncryptedTally, MemberPublicKey, Tally};
+use chain_vote::tally::{batch_decrypt, batch_decrypt_with_table, ValidatedTally};
+use chain_vote::{EncryptedTally, MemberPublicKey, Tally, TallyOptimizationTable};
 
 use chain_vote::TallyDecryptShare;
 
@@ -13,6 +13,10 @@ use base64::{engine::general_purpose, Engine as _};
 
 use color_eyre::Result;
 use rand_core::SeedableRng;
+use tracing::{info, warn};
+
+use std::num::NonZeroU64;
+use std::path::Path;
 
 /// A Bech32_encoded address consists of 3 parts: A Human-Readable Part (HRP) + Separator + Data:
 const HRP_PK: &str = "ristretto255_memberpk";
@@ -170,6 +174,7 @@ pub fn encode_public_keys(
 pub fn decrypt_tally_with_secret_keys(
     encrypted_tally: EncryptedTally,
     committee_priv_keys: Vec<MemberSecretKey>,
+    optimization_table: Option<&Path>,
 ) -> Result<Vec<Tally>, Box<dyn std::error::Error>> {
     let mut rng = TestCryptoRng::seed_from_u64(0);
 
@@ -185,7 +190,52 @@ pub fn decrypt_tally_with_secret_keys(
 
     let validated_tally = encrypted_tally.validate_partial_decryptions(&public_keys, &shares)?;
 
-    Ok(batch_decrypt([validated_tally])?)
+    decrypt_validated_tallies(vec![validated_tally], optimization_table)
+}
+
+///
+/// Decrypt validated tallies
+/// If a path to an optimization table is given, the table stored there is reused,
+/// or generated and saved there for the next runs if the file does not exist yet
+pub fn decrypt_validated_tallies(
+    validated_tallies: Vec<ValidatedTally>,
+    optimization_table: Option<&Path>,
+) -> Result<Vec<Tally>, Box<dyn std::error::Error>> {
+    let path = match optimization_table {
+        Some(path) => path,
+        None => return Ok(batch_decrypt(validated_tallies)?),
+    };
+    let max_stake = validated_tallies
+        .iter()
+        .map(ValidatedTally::max_stake)
+        .max()
+        .and_then(NonZeroU64::new);
+    let max_stake = match max_stake {
+        Some(max_stake) => max_stake,
+        // nothing to solve, no need for a table
+        None => return Ok(batch_decrypt(validated_tallies)?),
+    };
+
+    let table = if path.exists() {
+        info!("loading optimization table from {}", path.display());
+        let table = TallyOptimizationTable::load(path)?;
+        if table.max_value() < max_stake.get() {
+            warn!(
+                "optimization table computed for a stake of {}, decrypting a stake of {} will be slower",
+                table.max_value(),
+                max_stake
+            );
+        }
+        table
+    } else {
+        info!("generating optimization table for a stake of {}", max_stake);
+        let table = TallyOptimizationTable::generate(max_stake);
+        table.save(path)?;
+        info!("optimization table saved to {}", path.display());
+        table
+    };
+
+    Ok(batch_decrypt_with_table(validated_tallies, &table)?)
 }
 
 #[cfg(test)]
@@ -0,0 +1,516 @@
+//! End-to-end verification of the tally of a fund.
+//!
+//! The vote fragments are replayed on top of block0 to re-aggregate the encrypted
+//! tallies, the decryption shares published in the tally certificates are checked
+//! against the committee public keys and used to decrypt the tallies again, and the
+//! results are compared with the ones certified on chain and the published ones.
+use chain_crypto::{bech32::Bech32, Ed25519, PublicKey, SecretKey, Signature};
+use chain_impl_mockchain::{
+    block::Block,
+    certificate::{DecryptedPrivateTally, DecryptedPrivateTallyProposal},
+    fragment::Fragment,
+};
+use chain_vote::{tally::ValidatedTally, EncryptedTally, MemberPublicKey};
+use color_eyre::{eyre::eyre, Result};
+use jormungandr_lib::interfaces::{PrivateTallyState, Tally, VotePlanStatus};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use std::collections::HashMap;
+use std::num::NonZeroU8;
+use std::path::Path;
+
+use crate::recover::recover_ledger_from_fragments;
+use crate::tally::decrypt_validated_tallies;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error("malformed auditor public key")]
+    InvalidAuditorKey,
+
+    #[error("malformed signature of the verdict of proposal {proposal_index} of vote plan {vote_plan_id}")]
+    MalformedSignature {
+        vote_plan_id: String,
+        proposal_index: u8,
+    },
+
+    #[error(
+        "invalid signature of the verdict of proposal {proposal_index} of vote plan {vote_plan_id}"
+    )]
+    InvalidSignature {
+        vote_plan_id: String,
+        proposal_index: u8,
+    },
+}
+
+/// Outcome of the verification of the tally of a proposal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProposalVerdict {
+    pub vote_plan_id: String,
+    pub proposal_index: u8,
+    pub proposal_id: String,
+    /// Number of votes cast in the replayed ledger
+    pub votes_cast: usize,
+    /// Number of decryption shares in the tally certificate
+    pub decrypt_shares: usize,
+    /// Number of decryption shares whose proof matches a committee member key
+    pub valid_decrypt_shares: usize,
+    /// Result decrypted from the replayed encrypted tally with the valid shares
+    pub recomputed_result: Option<Vec<u64>>,
+    /// Result of the tally certificate
+    pub certified_result: Option<Vec<u64>>,
+    /// Result of the published vote plan status
+    pub published_result: Option<Vec<u64>>,
+    pub verified: bool,
+    /// Reasons for the verification to fail, empty if verified
+    pub failures: Vec<String>,
+}
+
+/// A verdict signed by the auditor.
+///
+/// The signature covers the verdict serialized as compact JSON,
+/// with the fields in their declaration order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedVerdict {
+    pub verdict: ProposalVerdict,
+    /// Hex encoded Ed25519 signature
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    /// Bech32 encoded Ed25519 public key of the auditor
+    pub auditor: String,
+    pub verdicts: Vec<SignedVerdict>,
+}
+
+impl VerificationReport {
+    /// Sign each verdict with the auditor key
+    pub fn sign(
+        verdicts: Vec<ProposalVerdict>,
+        auditor: &SecretKey<Ed25519>,
+    ) -> Result<Self, Error> {
+        let verdicts = verdicts
+            .into_iter()
+            .map(|verdict| {
+                let signature = auditor.sign(&serde_json::to_vec(&verdict)?);
+                Ok(SignedVerdict {
+                    verdict,
+                    signature: signature.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Self {
+            auditor: auditor.to_public().to_bech32_str(),
+            verdicts,
+        })
+    }
+
+    /// Check the signature of every verdict against the auditor public key
+    pub fn check_signatures(&self) -> Result<(), Error> {
+        let auditor = PublicKey::<Ed25519>::try_from_bech32_str(&self.auditor)
+            .map_err(|_| Error::InvalidAuditorKey)?;
+        for SignedVerdict { verdict, signature } in &self.verdicts {
+            let signature: Signature<[u8], Ed25519> =
+                signature.parse().map_err(|_| Error::MalformedSignature {
+                    vote_plan_id: verdict.vote_plan_id.clone(),
+                    proposal_index: verdict.proposal_index,
+                })?;
+            let signed = serde_json::to_vec(verdict)?;
+            if signature.verify(&auditor, signed.as_slice()) != chain_crypto::Verification::Success
+            {
+                return Err(Error::InvalidSignature {
+                    vote_plan_id: verdict.vote_plan_id.clone(),
+                    proposal_index: verdict.proposal_index,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replay the fragments on top of block0 and verify the tally of every private proposal
+/// against the tally certificates found in the fragments and the published vote plans.
+pub fn verify_tallies(
+    block0: &Block,
+    all_fragments: Vec<Fragment>,
+    published: &[VotePlanStatus],
+    optimization_table: Option<&Path>,
+) -> Result<Vec<ProposalVerdict>> {
+    let mut thresholds = HashMap::new();
+    let mut certificates = HashMap::new();
+    for fragment in block0.fragments().chain(all_fragments.iter()) {
+        match fragment {
+            Fragment::VotePlan(tx) => {
+                let vote_plan = tx.as_slice().payload().into_payload();
+                thresholds.insert(
+                    vote_plan.to_id().to_string(),
+                    vote_plan.committee_threshold(),
+                );
+            }
+            Fragment::VoteTally(tx) => {
+                let certificate = tx.as_slice().payload().into_payload();
+                if let Some(decrypted) = certificate.tally_decrypted() {
+                    certificates.insert(certificate.id().to_string(), decrypted.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let published: HashMap<_, _> = published
+        .iter()
+        .flat_map(|vote_plan| {
+            vote_plan.proposals.iter().map(|proposal| {
+                (
+                    (vote_plan.id.to_string(), proposal.index),
+                    (proposal.votes_cast, published_result(&proposal.tally)),
+                )
+            })
+        })
+        .collect();
+
+    // the encrypted tallies are aggregated again from the votes, before any tally certificate
+    let without_tally_fragments = all_fragments
+        .into_iter()
+        .filter(|f| !matches!(f, Fragment::VoteTally(_)));
+    let (ledger, failed) = recover_ledger_from_fragments(block0, without_tally_fragments)?;
+    if !failed.is_empty() {
+        warn!("{} fragments couldn't be properly processed", failed.len());
+    }
+
+    let mut verdicts = vec![];
+    let mut validated_tallies = vec![];
+    for vote_plan in ledger.active_vote_plans() {
+        let vote_plan_id = vote_plan.id.to_string();
+        let certificate = certificates.get(&vote_plan_id);
+        let threshold = thresholds.get(&vote_plan_id).copied().flatten();
+
+        for proposal in &vote_plan.proposals {
+            let encrypted_tally = match proposal.tally.private_encrypted() {
+                Ok(encrypted_tally) => encrypted_tally,
+                Err(_) => {
+                    info!(
+                        "skipping proposal {} of vote plan {}, the tally is not private",
+                        proposal.index, vote_plan_id
+                    );
+                    continue;
+                }
+            };
+
+            let mut verdict = ProposalVerdict {
+                vote_plan_id: vote_plan_id.clone(),
+                proposal_index: proposal.index,
+                proposal_id: proposal.proposal_id.to_string(),
+                votes_cast: proposal.votes.size(),
+                decrypt_shares: 0,
+                valid_decrypt_shares: 0,
+                recomputed_result: None,
+                certified_result: None,
+                published_result: None,
+                verified: false,
+                failures: vec![],
+            };
+
+            match published.get(&(vote_plan_id.clone(), proposal.index)) {
+                Some((votes_cast, result)) => {
+                    if *votes_cast != verdict.votes_cast {
+                        verdict.failures.push(format!(
+                            "{} votes cast in the replayed ledger, {} published",
+                            verdict.votes_cast, votes_cast
+                        ));
+                    }
+                    verdict.published_result = result.clone();
+                }
+                None => verdict
+                    .failures
+                    .push("proposal missing from the published vote plans".to_string()),
+            }
+
+            match certificate.and_then(|c| proposal_certificate(c, proposal.index)) {
+                Some(certified) => {
+                    verdict.certified_result = Some(certified.tally_result.to_vec());
+                    match validate_shares(
+                        &mut verdict,
+                        encrypted_tally,
+                        &vote_plan.committee_public_keys,
+                        threshold,
+                        certified,
+                    ) {
+                        Some(validated) => validated_tallies.push((verdicts.len(), validated)),
+                        None => verdict
+                            .failures
+                            .push("not enough valid decryption shares".to_string()),
+                    }
+                }
+                None => verdict
+                    .failures
+                    .push("no tally certificate for the proposal".to_string()),
+            }
+
+            verdicts.push(verdict);
+        }
+    }
+
+    info!("decrypting {} tallies", validated_tallies.len());
+    let (indices, validated_tallies): (Vec<_>, Vec<_>) = validated_tallies.into_iter().unzip();
+    let tallies = decrypt_validated_tallies(validated_tallies, optimization_table)
+        .map_err(|e| eyre!("unable to decrypt the tallies: {}", e))?;
+    for (index, tally) in indices.into_iter().zip(tallies) {
+        verdicts[index].recomputed_result = Some(tally.votes);
+    }
+
+    verdicts.iter_mut().for_each(check_results);
+
+    Ok(verdicts)
+}
+
+/// Compare the recomputed result with the certified and published ones, and settle the verdict
+fn check_results(verdict: &mut ProposalVerdict) {
+    if let Some(recomputed) = &verdict.recomputed_result {
+        if verdict.certified_result.as_ref() != Some(recomputed) {
+            verdict
+                .failures
+                .push("recomputed result differs from the tally certificate".to_string());
+        }
+        if verdict.published_result.as_ref() != Some(recomputed) {
+            verdict
+                .failures
+                .push("recomputed result differs from the published result".to_string());
+        }
+    }
+    verdict.verified = verdict.failures.is_empty();
+}
+
+/// Tally certificates list the proposals of the vote plan in order
+fn proposal_certificate(
+    certificate: &DecryptedPrivateTally,
+    index: u8,
+) -> Option<&DecryptedPrivateTallyProposal> {
+    certificate.iter().nth(index as usize)
+}
+
+/// Check the proof of each decryption share against the committee member keys,
+/// and validate the decryption of the tally with the valid ones.
+fn validate_shares(
+    verdict: &mut ProposalVerdict,
+    encrypted_tally: &EncryptedTally,
+    committee_public_keys: &[MemberPublicKey],
+    threshold: Option<NonZeroU8>,
+    certified: &DecryptedPrivateTallyProposal,
+) -> Option<ValidatedTally> {
+    verdict.decrypt_shares = certified.decrypt_shares.len();
+    let mut members = vec![];
+    for (i, share) in certified.decrypt_shares.iter().enumerate() {
+        match share.find_member(encrypted_tally, committee_public_keys) {
+            Some(member) if members.iter().any(|(m, _)| *m == member) => verdict.failures.push(
+                format!("decryption share {} duplicates member {}", i, member),
+            ),
+            Some(member) => members.push((member, share.clone())),
+            None => verdict.failures.push(format!(
+                "decryption share {} does not match any committee member key",
+                i
+            )),
+        }
+    }
+    verdict.valid_decrypt_shares = members.len();
+
+    match threshold {
+        Some(threshold) => encrypted_tally
+            .validate_threshold_partial_decryptions(
+                committee_public_keys,
+                threshold.get() as usize,
+                &members,
+            )
+            .ok(),
+        None => {
+            // without a threshold every member must have published its share, in order
+            members.sort_by_key(|(member, _)| *member);
+            if members.len() != committee_public_keys.len() {
+                return None;
+            }
+            let shares: Vec<_> = members.into_iter().map(|(_, share)| share).collect();
+            encrypted_tally
+                .validate_partial_decryptions(committee_public_keys, &shares)
+                .ok()
+        }
+    }
+}
+
+fn published_result(tally: &Tally) -> Option<Vec<u64>> {
+    match tally {
+        Tally::Private {
+            state:
+                PrivateTallyState::Decrypted { result }
+                | PrivateTallyState::DecryptedMultiSelection { result, .. },
+        }
+        | Tally::Public { result } => Some(result.results()),
+        Tally::Private {
+            state: PrivateTallyState::Encrypted { .. },
+        } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tally::extract_decrypt_shares;
+    use chain_vote::{
+        Ballot, Crs, ElectionPublicKey, MemberCommunicationKey, MemberState, TallyDecryptShare,
+        Vote,
+    };
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    fn verdict(verified: bool) -> ProposalVerdict {
+        ProposalVerdict {
+            vote_plan_id: "00".repeat(32),
+            proposal_index: 0,
+            proposal_id: "11".repeat(32),
+            votes_cast: 3,
+            decrypt_shares: 2,
+            valid_decrypt_shares: 2,
+            recomputed_result: Some(vec![1, 2]),
+            certified_result: Some(vec![1, 2]),
+            published_result: Some(vec![1, 2]),
+            verified,
+            failures: vec![],
+        }
+    }
+
+    #[test]
+    fn signed_verdicts_roundtrip() {
+        let auditor = SecretKey::<Ed25519>::generate(ChaCha20Rng::from_seed([0; 32]));
+        let report = VerificationReport::sign(vec![verdict(true)], &auditor).unwrap();
+
+        let report: VerificationReport =
+            serde_json::from_str(&serde_json::to_string_pretty(&report).unwrap()).unwrap();
+        report.check_signatures().unwrap();
+
+        let mut tampered = report;
+        tampered.verdicts[0].verdict = verdict(false);
+        assert!(matches!(
+            tampered.check_signatures(),
+            Err(Error::InvalidSignature { .. })
+        ));
+    }
+
+    /// A committee of three members without threshold and a tally of [5, 3] votes
+    fn encrypted_tally() -> (EncryptedTally, Vec<MemberPublicKey>, Vec<TallyDecryptShare>) {
+        let mut rng = ChaCha20Rng::from_seed([1; 32]);
+        let crs = Crs::from_hash(b"audit verification");
+        let members: Vec<_> = (0..3)
+            .map(|_| {
+                let communication_key = MemberCommunicationKey::new(&mut rng);
+                MemberState::new(&mut rng, 1, &crs, &[communication_key.to_public()], 0)
+            })
+            .collect();
+        let public_keys: Vec<_> = members.iter().map(MemberState::public_key).collect();
+        let election_key = ElectionPublicKey::from_participants(&public_keys);
+
+        let mut encrypted_tally = EncryptedTally::new(2, election_key.clone(), crs.clone());
+        for (choice, weight) in [(0, 1), (1, 3), (0, 4)] {
+            let vote = Vote::new(2, choice).unwrap();
+            let (encrypted, proof) = election_key.encrypt_and_prove_vote(&mut rng, &crs, vote);
+            let ballot =
+                Ballot::try_from_vote_and_proof(encrypted, &proof, &crs, &election_key).unwrap();
+            encrypted_tally.add(&ballot, weight);
+        }
+
+        let shares = extract_decrypt_shares(
+            encrypted_tally.clone(),
+            members.iter().map(MemberState::member_secret_key).collect(),
+        );
+        (encrypted_tally, public_keys, shares)
+    }
+
+    fn certified(shares: Vec<TallyDecryptShare>) -> DecryptedPrivateTallyProposal {
+        DecryptedPrivateTallyProposal {
+            decrypt_shares: shares.into_boxed_slice(),
+            tally_result: vec![5, 3].into_boxed_slice(),
+        }
+    }
+
+    fn recompute(validated: ValidatedTally) -> Vec<u64> {
+        decrypt_validated_tallies(vec![validated], None)
+            .unwrap()
+            .remove(0)
+            .votes
+    }
+
+    #[test]
+    fn valid_shares_are_accepted() {
+        let (encrypted_tally, public_keys, shares) = encrypted_tally();
+        let mut verdict = verdict(false);
+
+        let validated = validate_shares(
+            &mut verdict,
+            &encrypted_tally,
+            &public_keys,
+            None,
+            &certified(shares),
+        )
+        .unwrap();
+        assert_eq!(verdict.decrypt_shares, 3);
+        assert_eq!(verdict.valid_decrypt_shares, 3);
+        assert!(verdict.failures.is_empty());
+
+        verdict.recomputed_result = Some(recompute(validated));
+        verdict.certified_result = Some(vec![5, 3]);
+        verdict.published_result = Some(vec![5, 3]);
+        check_results(&mut verdict);
+        assert!(verdict.verified, "{:?}", verdict.failures);
+    }
+
+    #[test]
+    fn tampered_decryption_share_is_rejected() {
+        let (encrypted_tally, public_keys, mut shares) = encrypted_tally();
+        // swap the decrypted elements of the two options, the proofs no longer match
+        let bytes = shares[1].to_bytes();
+        let (first, second) = bytes.split_at(TallyDecryptShare::bytes_len(1));
+        shares[1] = TallyDecryptShare::from_bytes(&[second, first].concat()).unwrap();
+        let mut verdict = verdict(false);
+
+        let validated = validate_shares(
+            &mut verdict,
+            &encrypted_tally,
+            &public_keys,
+            None,
+            &certified(shares),
+        );
+        assert!(validated.is_none());
+        assert_eq!(verdict.decrypt_shares, 3);
+        assert_eq!(verdict.valid_decrypt_shares, 2);
+        assert_eq!(
+            verdict.failures,
+            vec!["decryption share 1 does not match any committee member key".to_string()]
+        );
+    }
+
+    #[test]
+    fn mismatching_published_result_fails_verification() {
+        let (encrypted_tally, public_keys, shares) = encrypted_tally();
+        let mut verdict = verdict(false);
+        let validated = validate_shares(
+            &mut verdict,
+            &encrypted_tally,
+            &public_keys,
+            None,
+            &certified(shares),
+        )
+        .unwrap();
+
+        verdict.recomputed_result = Some(recompute(validated));
+        verdict.certified_result = Some(vec![5, 3]);
+        verdict.published_result = Some(vec![3, 5]);
+        check_results(&mut verdict);
+        assert!(!verdict.verified);
+        assert_eq!(
+            verdict.failures,
+            vec!["recomputed result differs from the published result".to_string()]
+        );
+    }
+}
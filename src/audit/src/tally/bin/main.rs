@@ -2,10 +2,9 @@
 //! Community Tally Verification Tool
 //!
 
-use chain_vote::tally::batch_decrypt;
 use lib::tally::{
-    decrypt_tally_with_secret_keys, encode_decrypt_shares, encode_public_keys,
-    extract_decrypt_shares, load_decrypt_shares, load_encrypted_tally,
+    decrypt_tally_with_secret_keys, decrypt_validated_tallies, encode_decrypt_shares,
+    encode_public_keys, extract_decrypt_shares, load_decrypt_shares, load_encrypted_tally,
     parse_private_committee_keys, parse_public_committee_keys,
 };
 
@@ -15,6 +14,7 @@ use color_eyre::Result;
 use tracing::{info, Level};
 
 use std::error::Error;
+use std::path::PathBuf;
 
 ///
 /// Args defines and declares CLI behaviour within the context of clap
@@ -56,6 +56,10 @@ pub struct Args {
     /// Show Public keys
     #[clap(short, long, value_delimiter = ' ', num_args = 1..)]
     pub show_public_keys: Option<Vec<String>>,
+    /// Precomputed optimization table used to decrypt the tally. It is generated
+    /// and saved at this path if the file does not exist yet
+    #[clap(long)]
+    pub optimization_table: Option<PathBuf>,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -98,7 +102,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
         println!(
             "Decryption results {:?}",
-            &batch_decrypt([validated_tally])?
+            &decrypt_validated_tallies(vec![validated_tally], args.optimization_table.as_deref())?
         );
     }
 
@@ -129,7 +133,11 @@ fn main() -> Result<(), Box<dyn Error>> {
 
         println!(
             "tally decryption {:?}",
-            decrypt_tally_with_secret_keys(encrypted_tally, priv_keys)
+            decrypt_tally_with_secret_keys(
+                encrypted_tally,
+                priv_keys,
+                args.optimization_table.as_deref()
+            )
         );
     }
 
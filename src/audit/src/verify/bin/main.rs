@@ -0,0 +1,122 @@
+//!
+//! Tool for end-to-end tally verification
+//!
+
+use chain_core::packer::Codec;
+use chain_crypto::{bech32::Bech32, Ed25519, SecretKey};
+use chain_impl_mockchain::block::Block;
+use chain_ser::deser::Deserialize;
+
+use clap::Parser;
+use color_eyre::{
+    eyre::{eyre, Context},
+    Report, Result,
+};
+use jormungandr_lib::interfaces::VotePlanStatus;
+use lib::offline::{extract_fragments_from_storage, json_from_file};
+use lib::verify::{verify_tallies, VerificationReport};
+use tracing::{info, warn, Level};
+
+use std::{fs::File, io::BufWriter, path::PathBuf};
+
+///
+/// Args defines and declares CLI behaviour within the context of clap
+///
+#[derive(Parser, Debug, Clone)]
+#[clap(about, version, author)]
+pub struct Args {
+    /// Path to the jormungandr storage of the fund
+    #[clap(short, long)]
+    pub fragments: PathBuf,
+    /// block0 path
+    #[clap(short, long)]
+    pub block0: PathBuf,
+    /// Published vote plan statuses, in json format
+    #[clap(short, long)]
+    pub published: PathBuf,
+    /// File containing the bech32 encoded ed25519 secret key signing the verdicts
+    #[clap(short, long)]
+    pub auditor_key: PathBuf,
+    /// Path to the tally optimization table, generated there if it does not exist
+    #[clap(short = 't', long)]
+    pub optimization_table: Option<PathBuf>,
+    /// Path to write the signed verdicts to
+    #[clap(short, long, default_value = "/tmp/verify.verdicts.json")]
+    pub output: PathBuf,
+}
+
+fn main() -> Result<(), Report> {
+    color_eyre::install()?;
+
+    let args = Args::parse();
+
+    // Configure a custom event formatter
+    let format = tracing_subscriber::fmt::format()
+        .with_level(true) // don't include levels in formatted output
+        .with_target(true) // don't include targets
+        .with_thread_ids(true) // include the thread ID of the current thread
+        .with_thread_names(true) // include the name of the current thread
+        .compact(); // use the `Compact` formatting style.
+
+    // Create a `fmt` subscriber that uses our custom event format, and set it
+    // as the default.
+    tracing_subscriber::fmt()
+        .event_format(format)
+        .with_max_level(Level::INFO /*DEBUG*/)
+        .init();
+
+    info!("Audit Tool.");
+    info!("Starting Tally Verification");
+
+    let auditor_key = std::fs::read_to_string(&args.auditor_key).context("auditor key loading")?;
+    let auditor_key = SecretKey::<Ed25519>::try_from_bech32_str(auditor_key.trim())
+        .map_err(|e| eyre!("invalid auditor key: {}", e))?;
+
+    let block0 = read_block0(args.block0)?;
+
+    info!("extracting fragments from storage");
+    let all_fragments =
+        extract_fragments_from_storage(&args.fragments).map_err(|e| eyre!(e.to_string()))?;
+
+    let published: Vec<VotePlanStatus> = json_from_file(&args.published)?;
+
+    info!("replaying fragments and verifying tallies ⏳");
+    let verdicts = verify_tallies(
+        &block0,
+        all_fragments,
+        &published,
+        args.optimization_table.as_deref(),
+    )?;
+
+    let verified = verdicts.iter().filter(|verdict| verdict.verified).count();
+    info!("verified: {}/{}", verified, verdicts.len());
+    for verdict in verdicts.iter().filter(|verdict| !verdict.verified) {
+        warn!(
+            "proposal {} of vote plan {} failed verification: {}",
+            verdict.proposal_index,
+            verdict.vote_plan_id,
+            verdict.failures.join(", ")
+        );
+    }
+
+    let report = VerificationReport::sign(verdicts, &auditor_key)?;
+
+    let file = File::options()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&args.output)?;
+    let writer = BufWriter::new(file);
+
+    serde_json::to_writer_pretty(writer, &report)?;
+
+    info!("saved: {} 🚀", args.output.display());
+
+    Ok(())
+}
+
+/// Read block0 from file
+fn read_block0(path: PathBuf) -> Result<Block, Report> {
+    let reader = std::fs::File::open(path)?;
+    Block::deserialize(&mut Codec::new(reader)).context("block0 loading")
+}
@@ -1,5 +1,10 @@
-use crate::{logger, service, settings::Settings, state::State};
+use crate::{
+    logger, service,
+    settings::{SecretsSettings, Settings},
+    state::State,
+};
 use clap::Parser;
+use event_db::{queries::automation::AutomationQueries, secret::SecretsKey};
 use std::sync::Arc;
 
 #[derive(thiserror::Error, Debug)]
@@ -14,6 +19,9 @@ pub enum Error {
 #[clap(rename_all = "kebab-case")]
 pub enum Cli {
     Run(Settings),
+    /// Encrypt the secrets of the event db still encrypted with the legacy password,
+    /// and optionally rotate the key encrypting them
+    EncryptSecrets(SecretsSettings),
 }
 
 impl Cli {
@@ -26,6 +34,26 @@ impl Cli {
                 service::run(&settings.address, &settings.metrics_address, state).await?;
                 Ok(())
             }
+            Self::EncryptSecrets(settings) => {
+                logger::init(settings.log_format, settings.log_level).unwrap();
+
+                let event_db = event_db::establish_connection(Some(&settings.database_url))
+                    .await?
+                    .with_secrets_key(SecretsKey::new(settings.secrets_key));
+
+                if let Some(legacy_password) = settings.legacy_password {
+                    let encrypted = event_db.encrypt_legacy_secrets(&legacy_password).await?;
+                    tracing::info!("Encrypted the secrets of {encrypted} rows");
+                }
+
+                if let Some(new_secrets_key) = settings.new_secrets_key {
+                    let updated = event_db
+                        .rotate_secrets_key(&SecretsKey::new(new_secrets_key))
+                        .await?;
+                    tracing::info!("Rotated the secrets key of {updated} rows");
+                }
+                Ok(())
+            }
         }
     }
 }
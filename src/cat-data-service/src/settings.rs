@@ -26,3 +26,36 @@ pub struct Settings {
     #[clap(long, default_value = LOG_LEVEL_DEFAULT)]
     pub log_level: LogLevel,
 }
+
+#[derive(Args, Clone)]
+pub struct SecretsSettings {
+    /// Url to the postgres event db
+    #[clap(long, env)]
+    pub database_url: String,
+
+    /// Key encrypting the secrets stored in the event db
+    #[clap(long, env = "EVENT_DB_SECRETS_KEY", hide_env_values = true)]
+    pub secrets_key: String,
+
+    /// Password the voting node encrypted the secrets with before they were encrypted at rest.
+    /// Not needed when only rotating the secrets key.
+    #[clap(
+        long,
+        env = "SECRET_SECRET",
+        hide_env_values = true,
+        required_unless_present = "new_secrets_key"
+    )]
+    pub legacy_password: Option<String>,
+
+    /// New key to encrypt the secrets with, if the secrets key is rotated
+    #[clap(long, env = "EVENT_DB_NEW_SECRETS_KEY", hide_env_values = true)]
+    pub new_secrets_key: Option<String>,
+
+    /// Logging format
+    #[clap(long, default_value = LOG_FORMAT_DEFAULT)]
+    pub log_format: LogFormat,
+
+    /// Logging level
+    #[clap(long, default_value = LOG_LEVEL_DEFAULT)]
+    pub log_level: LogLevel,
+}
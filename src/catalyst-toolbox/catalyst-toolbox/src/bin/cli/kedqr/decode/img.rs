@@ -1,6 +1,5 @@
 use bech32::{ToBase32, Variant};
 use catalyst_toolbox::kedqr::KeyQrCode;
-use catalyst_toolbox::kedqr::QrPin;
 use chain_crypto::AsymmetricKey;
 use chain_crypto::Ed25519Extended;
 use chain_crypto::SecretKey;
@@ -10,8 +9,12 @@ use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 
-pub fn save_secret_from_qr(qr: PathBuf, output: Option<PathBuf>, pin: QrPin) -> Result<(), Report> {
-    let sk = secret_from_qr(qr, pin)?;
+pub fn save_secret_from_qr(
+    qr: PathBuf,
+    output: Option<PathBuf>,
+    password: &[u8],
+) -> Result<(), Report> {
+    let sk = secret_from_qr(qr, password)?;
     let hrp = Ed25519Extended::SECRET_BECH32_HRP;
     let secret_key = bech32::encode(hrp, sk.leak_secret().to_base32(), Variant::Bech32)?;
 
@@ -31,9 +34,9 @@ pub fn save_secret_from_qr(qr: PathBuf, output: Option<PathBuf>, pin: QrPin) ->
 
 pub fn secret_from_qr(
     qr: impl AsRef<Path>,
-    pin: QrPin,
+    password: &[u8],
 ) -> Result<SecretKey<Ed25519Extended>, Report> {
     let img = image::open(qr)?;
-    let secret = KeyQrCode::decode(img, &pin.password)?;
+    let secret = KeyQrCode::decode(img, password)?;
     Ok(secret.first().unwrap().clone())
 }
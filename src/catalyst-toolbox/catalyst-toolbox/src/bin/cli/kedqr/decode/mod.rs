@@ -1,8 +1,7 @@
 mod img;
 mod payload;
 
-use crate::cli::kedqr::QrCodeOpts;
-use catalyst_toolbox::kedqr::QrPin;
+use crate::cli::kedqr::{PasswordArgs, QrCodeOpts};
 use clap::Parser;
 use color_eyre::Report;
 pub use img::{save_secret_from_qr, secret_from_qr};
@@ -19,9 +18,8 @@ pub struct DecodeQrCodeCmd {
     /// Path to file to save secret output, if not provided console output will be attempted.
     #[clap(short, long, value_parser = PathBuf::from_str)]
     output: Option<PathBuf>,
-    /// Pin code. 4-digit number is used on Catalyst.
-    #[clap(short, long, value_parser = QrPin::from_str)]
-    pin: QrPin,
+    #[clap(flatten)]
+    password: PasswordArgs,
     /// Set to img or payload
     #[clap(short, long, value_parser = QrCodeOpts::from_str)]
     opts: QrCodeOpts,
@@ -29,9 +27,10 @@ pub struct DecodeQrCodeCmd {
 
 impl DecodeQrCodeCmd {
     pub fn exec(self) -> Result<(), Report> {
+        let password = self.password.to_bytes();
         match self.opts {
-            QrCodeOpts::Payload => decode_payload(self.input, self.output, self.pin),
-            QrCodeOpts::Img => save_secret_from_qr(self.input, self.output, self.pin),
+            QrCodeOpts::Payload => decode_payload(self.input, self.output, &password),
+            QrCodeOpts::Img => save_secret_from_qr(self.input, self.output, &password),
         }
     }
 }
@@ -1,4 +1,4 @@
-use catalyst_toolbox::kedqr::{KeyQrCode, QrPin};
+use catalyst_toolbox::kedqr::{KdfParams, KeyQrCode};
 use chain_crypto::bech32::Bech32;
 use chain_crypto::{Ed25519Extended, SecretKey};
 use color_eyre::Report;
@@ -8,7 +8,12 @@ use std::{
     path::PathBuf,
 };
 
-pub fn generate_qr(input: PathBuf, output: Option<PathBuf>, pin: QrPin) -> Result<(), Report> {
+pub fn generate_qr(
+    input: PathBuf,
+    output: Option<PathBuf>,
+    password: &[u8],
+    kdf: Option<KdfParams>,
+) -> Result<(), Report> {
     // open input key and parse it
     let key_file = OpenOptions::new()
         .create(false)
@@ -27,10 +32,11 @@ pub fn generate_qr(input: PathBuf, output: Option<PathBuf>, pin: QrPin) -> Resul
 
     let secret_key: SecretKey<Ed25519Extended> =
         SecretKey::try_from_bech32_str(&sk).expect("Malformed secret key.");
-    // use parsed pin from args
-    let pwd = pin.password;
-    // generate qrcode with key and parsed pin
-    let qr = KeyQrCode::generate(secret_key, &pwd);
+    // generate qrcode with key and parsed password
+    let qr = match kdf {
+        Some(params) => KeyQrCode::generate_argon2id(secret_key, password, params)?,
+        None => KeyQrCode::generate(secret_key, password),
+    };
     // process output
     match output {
         Some(path) => {
@@ -1,8 +1,8 @@
 mod img;
 mod payload;
 
-use crate::cli::kedqr::QrCodeOpts;
-use catalyst_toolbox::kedqr::QrPin;
+use crate::cli::kedqr::{PasswordArgs, QrCodeOpts};
+use catalyst_toolbox::kedqr::KdfParams;
 use clap::Parser;
 use color_eyre::Report;
 pub use img::generate_qr;
@@ -19,19 +19,37 @@ pub struct EncodeQrCodeCmd {
     /// Path to file to save qr code output, if not provided console output will be attempted.
     #[clap(short, long, value_parser = PathBuf::from_str)]
     output: Option<PathBuf>,
-    /// Pin code. 4-digit number is used on Catalyst.
-    #[clap(short, long, value_parser = QrPin::from_str)]
-    pin: QrPin,
+    #[clap(flatten)]
+    password: PasswordArgs,
     /// Set to img or payload
     #[clap(short, long, value_parser = QrCodeOpts::from_str)]
     opts: QrCodeOpts,
+    /// Protect the key with Argon2id instead of PBKDF2, and store the payload as binary instead
+    /// of hex. The Catalyst voting app does not read this format yet.
+    #[clap(long)]
+    argon2id: bool,
+    /// Argon2id memory size, in KiB.
+    #[clap(long, requires = "argon2id")]
+    kdf_memory_kib: Option<u32>,
+    /// Argon2id number of passes over the memory.
+    #[clap(long, requires = "argon2id")]
+    kdf_iterations: Option<u32>,
 }
 
 impl EncodeQrCodeCmd {
     pub fn exec(self) -> Result<(), Report> {
+        let password = self.password.to_bytes();
+        let kdf = self.argon2id.then(|| {
+            let default = KdfParams::default();
+            KdfParams {
+                memory_kib: self.kdf_memory_kib.unwrap_or(default.memory_kib),
+                iterations: self.kdf_iterations.unwrap_or(default.iterations),
+                ..default
+            }
+        });
         match self.opts {
-            QrCodeOpts::Payload => generate_payload(self.input, self.output, self.pin),
-            QrCodeOpts::Img => generate_qr(self.input, self.output, self.pin),
+            QrCodeOpts::Payload => generate_payload(self.input, self.output, &password, kdf),
+            QrCodeOpts::Img => generate_qr(self.input, self.output, &password, kdf),
         }
     }
 }
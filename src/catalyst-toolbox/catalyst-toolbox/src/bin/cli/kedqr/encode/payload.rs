@@ -1,5 +1,4 @@
-use catalyst_toolbox::kedqr::generate;
-use catalyst_toolbox::kedqr::QrPin;
+use catalyst_toolbox::kedqr::{generate, generate_argon2id, KdfParams};
 use chain_crypto::bech32::Bech32;
 use chain_crypto::{Ed25519Extended, SecretKey};
 use color_eyre::Report;
@@ -11,7 +10,12 @@ use std::{
     path::PathBuf,
 };
 
-pub fn generate_payload(input: PathBuf, output: Option<PathBuf>, pin: QrPin) -> Result<(), Report> {
+pub fn generate_payload(
+    input: PathBuf,
+    output: Option<PathBuf>,
+    password: &[u8],
+    kdf: Option<KdfParams>,
+) -> Result<(), Report> {
     // open input key and parse it
     let key_file = OpenOptions::new()
         .create(false)
@@ -30,20 +34,25 @@ pub fn generate_payload(input: PathBuf, output: Option<PathBuf>, pin: QrPin) ->
 
     let secret_key: SecretKey<Ed25519Extended> =
         SecretKey::try_from_bech32_str(&sk).expect("Malformed secret key.");
-    // use parsed pin from args
-    let pwd = pin.password;
-    // generate qrcode with key and parsed pin
-    let qr = generate(secret_key, &pwd);
+    // generate qrcode with key and parsed password, binary payloads are only hex encoded when
+    // printed
+    let qr = match kdf {
+        Some(params) => generate_argon2id(secret_key, password, params)?,
+        None => generate(secret_key, password).into_bytes(),
+    };
     // process output
     match output {
         Some(path) => {
             // save qr code to file, or print to stdout if it fails
             let mut file = File::create(path)?;
-            file.write_all(qr.as_bytes())?;
+            file.write_all(&qr)?;
         }
         None => {
             // prints qr code to stdout when no path is specified
-            println!("{}", qr);
+            match kdf {
+                Some(_) => println!("{}", hex::encode(qr)),
+                None => println!("{}", String::from_utf8(qr)?),
+            }
         }
     }
     Ok(())
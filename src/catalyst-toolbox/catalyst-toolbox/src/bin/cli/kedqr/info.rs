@@ -1,6 +1,5 @@
-use super::QrCodeOpts;
+use super::{PasswordArgs, QrCodeOpts};
 use crate::cli::kedqr::decode::{secret_from_payload, secret_from_qr};
-use catalyst_toolbox::kedqr::QrPin;
 use chain_addr::{AddressReadable, Discrimination, Kind};
 use chain_core::property::Deserialize;
 use chain_crypto::{Ed25519Extended, SecretKey};
@@ -19,9 +18,8 @@ pub struct InfoForQrCodeCmd {
     /// Path to file containing img or payload.
     #[clap(short, long, value_parser = PathBuf::from_str)]
     input: PathBuf,
-    /// Pin code. 4-digit number is used on Catalyst.
-    #[clap(short, long, value_parser = QrPin::from_str)]
-    pin: QrPin,
+    #[clap(flatten)]
+    password: PasswordArgs,
     /// Blockchain block0. Can be either url of local file path
     #[clap(long = "block0")]
     pub block0: Option<String>,
@@ -35,10 +33,11 @@ pub struct InfoForQrCodeCmd {
 
 impl InfoForQrCodeCmd {
     pub fn exec(self) -> Result<(), Report> {
+        let password = self.password.to_bytes();
         let secret_key: SecretKey<Ed25519Extended> = {
             match self.opts {
-                QrCodeOpts::Payload => secret_from_payload(&self.input, self.pin)?,
-                QrCodeOpts::Img => secret_from_qr(&self.input, self.pin)?,
+                QrCodeOpts::Payload => secret_from_payload(&self.input, &password)?,
+                QrCodeOpts::Img => secret_from_qr(&self.input, &password)?,
             }
         };
         let kind = Kind::Account(secret_key.to_public());
@@ -5,7 +5,8 @@ mod verify;
 
 use std::str::FromStr;
 
-use clap::Parser;
+use catalyst_toolbox::kedqr::{QrPassphrase, QrPin};
+use clap::{Args, Parser};
 use color_eyre::{eyre::bail, Report};
 
 #[derive(Parser)]
@@ -33,6 +34,27 @@ impl QrCodeCmd {
     }
 }
 
+/// Secret protecting the key
+#[derive(Debug, PartialEq, Eq, Args)]
+pub struct PasswordArgs {
+    /// Pin code. 4-digit number is used on Catalyst.
+    #[clap(short, long, value_parser = QrPin::from_str, required_unless_present = "passphrase")]
+    pin: Option<QrPin>,
+    /// Passphrase of at least 8 characters, used instead of the pin code.
+    #[clap(long, value_parser = QrPassphrase::from_str, conflicts_with = "pin")]
+    passphrase: Option<QrPassphrase>,
+}
+
+impl PasswordArgs {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match (&self.pin, &self.passphrase) {
+            (_, Some(passphrase)) => passphrase.as_bytes().to_vec(),
+            (Some(pin), None) => pin.password.to_vec(),
+            (None, None) => unreachable!("either a pin or a passphrase is required"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Parser)]
 #[clap(rename_all = "kebab-case")]
 pub enum QrCodeOpts {
@@ -1,7 +1,7 @@
 use crate::cli::kedqr::decode::secret_from_payload;
 use crate::cli::kedqr::decode::secret_from_qr;
 use crate::cli::kedqr::QrCodeOpts;
-use catalyst_toolbox::kedqr::PinReadMode;
+use catalyst_toolbox::kedqr::{PinReadMode, QrPassphrase};
 use clap::Parser;
 use color_eyre::eyre::Context;
 use color_eyre::Report;
@@ -22,6 +22,10 @@ pub struct VerifyQrCodeCmd {
     #[clap(long = "pin-from-file")]
     pub read_pin_from_filename: bool,
 
+    /// Passphrase used instead of the pin code for every QR code
+    #[clap(long, value_parser = QrPassphrase::from_str, conflicts_with = "read_pin_from_filename")]
+    pub passphrase: Option<QrPassphrase>,
+
     #[clap(short = 's', long = "stop-at-fail")]
     pub stop_at_fail: bool,
 
@@ -45,18 +49,23 @@ impl VerifyQrCodeCmd {
         let mut failed_count = 0;
 
         for (idx, qr_code) in qr_codes.iter().enumerate() {
-            let pin = {
-                if self.read_pin_from_filename {
-                    PinReadMode::FromFileName(qr_code.clone())
-                } else {
-                    PinReadMode::Global(self.pin.to_string())
+            let password = match &self.passphrase {
+                Some(passphrase) => passphrase.as_bytes().to_vec(),
+                None => {
+                    if self.read_pin_from_filename {
+                        PinReadMode::FromFileName(qr_code.clone())
+                    } else {
+                        PinReadMode::Global(self.pin.to_string())
+                    }
                 }
-            }
-            .into_qr_pin()?;
+                .into_qr_pin()?
+                .password
+                .to_vec(),
+            };
 
             let result = match self.opts {
-                QrCodeOpts::Payload => secret_from_payload(qr_code, pin),
-                QrCodeOpts::Img => secret_from_qr(qr_code, pin),
+                QrCodeOpts::Payload => secret_from_payload(qr_code, &password),
+                QrCodeOpts::Img => secret_from_qr(qr_code, &password),
             };
 
             if let Err(err) = result {
@@ -9,6 +9,7 @@ mod recovery;
 mod rewards;
 mod snapshot;
 mod stats;
+mod storage;
 mod sve_snapshot;
 mod vote_check;
 
@@ -61,6 +62,9 @@ pub enum CatalystCommand {
     /// Dump information related to catalyst fund
     #[clap(subcommand)]
     Archive(archive::Archive),
+    /// Export, import, prune and compact Jormungandr databases
+    #[clap(subcommand)]
+    Storage(storage::Storage),
     /// Validate catalyst elections
     VoteCheck(vote_check::VoteCheck),
     /// Prints voting statistics
@@ -101,6 +105,7 @@ impl CatalystCommand {
             Ideascale(ideascale) => ideascale.exec()?,
             Reviews(reviews) => reviews.exec()?,
             Archive(archive) => archive.exec()?,
+            Storage(storage) => storage.exec()?,
             VoteCheck(vote_check) => vote_check.exec()?,
             Stats(stats) => stats.exec()?,
             Snapshot(snapshot) => snapshot.exec()?,
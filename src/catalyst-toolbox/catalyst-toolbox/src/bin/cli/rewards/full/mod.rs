@@ -1,5 +1,5 @@
 use catalyst_toolbox::{
-    rewards::proposers::{OutputFormat, ProposerRewards},
+    rewards::proposers::{FundingStrategy, OutputFormat, ProposerRewards},
     utils::json_from_file,
 };
 use color_eyre::Result;
@@ -89,6 +89,7 @@ pub(super) fn full_rewards(path: &Path) -> Result<()> {
         committee_keys: Some(committee_keys),
         excluded_proposals,
         output_format: OutputFormat::Csv,
+        funding_strategy: FundingStrategy::GreedyFillIn,
     })?;
 
     Ok(())
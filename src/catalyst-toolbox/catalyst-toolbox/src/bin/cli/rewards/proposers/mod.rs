@@ -19,6 +19,7 @@ pub fn rewards(
         total_stake_threshold,
         approval_threshold,
         output_format,
+        funding_strategy,
     }: &ProposerRewards,
 ) -> Result<()> {
     let proposals = json_from_file(proposals)?;
@@ -45,6 +46,7 @@ pub fn rewards(
         committee_keys,
         total_stake_threshold: *total_stake_threshold,
         approval_threshold: *approval_threshold,
+        funding_strategy: *funding_strategy,
     })?;
 
     write_results(output, *output_format, results)?;
@@ -0,0 +1,153 @@
+use super::load_voting_group_rules;
+use catalyst_toolbox::utils::csv::dump_data_to_csv;
+use chain_addr::Discrimination;
+use clap::{Parser, ValueEnum};
+use color_eyre::{eyre::eyre, Report};
+use jormungandr_lib::interfaces::Value;
+use snapshot_lib::{
+    diff::SnapshotDiff,
+    registration::Deregistration,
+    voting_group::{VotingGroupRules, DEFAULT_DIRECT_VOTER_GROUP},
+    voting_power::{AnyVotingPowerTransform, VotingPowerCap},
+    Fraction, RawSnapshot, Snapshot,
+};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::info;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum DiffFormat {
+    Json,
+    Csv,
+}
+
+/// Compare two raw snapshots, usually taken at different slots
+#[derive(Parser)]
+#[clap(rename_all = "kebab-case")]
+pub struct SnapshotDiffCmd {
+    /// Path to the older raw snapshot, in json format
+    #[clap(long, value_parser = PathBuf::from_str)]
+    before: PathBuf,
+
+    /// Path to the newer raw snapshot, in json format
+    #[clap(long, value_parser = PathBuf::from_str)]
+    after: PathBuf,
+
+    /// Registrations voting power threshold for eligibility
+    #[clap(short, long)]
+    min_stake_threshold: Value,
+
+    /// Voting power cap for each account
+    #[clap(short, long, default_value = "100.0")]
+    voting_power_cap: Fraction,
+
+    /// Transformation of the voting power of each account: `cap(<fraction>)`, `quadratic`,
+    /// `logarithmic(<scale>)` or `tiered(<from>:<weight>,...)`.
+    /// Replaces the voting power cap when given.
+    #[clap(long, value_parser = AnyVotingPowerTransform::from_str)]
+    voting_power_transform: Option<AnyVotingPowerTransform>,
+
+    /// Path to the file containing the CIP-36 deregistrations in json format.
+    /// Registrations cancelled by them are dropped from both snapshots.
+    #[clap(long, value_parser = PathBuf::from_str)]
+    deregistrations: Option<PathBuf>,
+
+    /// Path to a TOML or JSON file with the rules assigning the voters to voting groups.
+    /// If not provided all the voters are in the same group.
+    #[clap(long, value_parser = PathBuf::from_str)]
+    voting_group_rules: Option<PathBuf>,
+
+    #[clap(long, value_enum, default_value = "json")]
+    format: DiffFormat,
+
+    /// File to write the json diff to, or directory to write the csv tables to.
+    /// The json diff is printed to stdout if not provided.
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl SnapshotDiffCmd {
+    pub fn exec(self) -> Result<(), Report> {
+        if self.voting_power_cap > Fraction::from(100) || self.voting_power_cap < Fraction::from(0)
+        {
+            return Err(eyre!("Voting power cap (%) must be between 0.0 and 100.0"));
+        }
+        let voting_power_transform = self.voting_power_transform.clone().unwrap_or_else(|| {
+            AnyVotingPowerTransform::Cap(VotingPowerCap::new(self.voting_power_cap / 100.0))
+        });
+
+        let mut raw_before: RawSnapshot =
+            serde_json::from_str(&std::fs::read_to_string(&self.before)?)?;
+        let mut raw_after: RawSnapshot =
+            serde_json::from_str(&std::fs::read_to_string(&self.after)?)?;
+        if let Some(deregistrations) = &self.deregistrations {
+            let deregistrations: Vec<Deregistration> =
+                serde_json::from_str(&std::fs::read_to_string(deregistrations)?)?;
+            let dropped_before = raw_before.drop_deregistered(&deregistrations);
+            let dropped_after = raw_after.drop_deregistered(&deregistrations);
+            info!(
+                "Dropped {} deregistered registrations before, {} after",
+                dropped_before, dropped_after
+            );
+        }
+
+        info!("Processing Snapshots");
+
+        let rules = match &self.voting_group_rules {
+            Some(rules) => load_voting_group_rules(rules)?,
+            None => VotingGroupRules {
+                default_group: DEFAULT_DIRECT_VOTER_GROUP.into(),
+                rules: Vec::new(),
+            },
+        };
+        let process = |raw: RawSnapshot| {
+            Snapshot::from_raw_snapshot(
+                raw,
+                self.min_stake_threshold,
+                &voting_power_transform,
+                &rules,
+                Discrimination::Production,
+                false,
+            )
+        };
+        let diff = SnapshotDiff::new(&raw_before, &raw_after, process)?;
+
+        info!(
+            "{} voters changed, {} registrations superseded, {} delegation shifts",
+            diff.voters.len(),
+            diff.superseded_registrations.len(),
+            diff.delegation_shifts.len()
+        );
+
+        match self.format {
+            DiffFormat::Json => {
+                let content = serde_json::to_string_pretty(&diff)?;
+                match &self.output {
+                    Some(path) => std::fs::write(path, content)?,
+                    None => writeln!(std::io::stdout(), "{}", content)?,
+                }
+            }
+            DiffFormat::Csv => {
+                let dir = self
+                    .output
+                    .as_deref()
+                    .ok_or_else(|| eyre!("an output directory is required for the csv format"))?;
+                write_csv_tables(dir, &diff)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_csv_tables(dir: &Path, diff: &SnapshotDiff) -> Result<(), Report> {
+    std::fs::create_dir_all(dir)?;
+    dump_data_to_csv(&diff.voters, &dir.join("voters.csv"))?;
+    dump_data_to_csv(
+        &diff.superseded_registrations,
+        &dir.join("superseded_registrations.csv"),
+    )?;
+    dump_data_to_csv(&diff.delegation_shifts, &dir.join("delegation_shifts.csv"))?;
+    dump_data_to_csv(&diff.voting_groups, &dir.join("voting_groups.csv"))?;
+    Ok(())
+}
@@ -1,3 +1,5 @@
+mod diff;
+
 use chain_addr::Discrimination;
 use clap::Parser;
 use color_eyre::Report;
@@ -7,27 +9,41 @@ use jormungandr_lib::interfaces::Value;
 
 use serde::Serialize;
 use snapshot_lib::{
-    voting_group::{RepsVotersAssigner, DEFAULT_DIRECT_VOTER_GROUP, DEFAULT_REPRESENTATIVE_GROUP},
-    Snapshot, SnapshotInfo,
+    registration::Deregistration,
+    voting_group::{
+        RepsVotersAssigner, VotingGroupRules, DEFAULT_DIRECT_VOTER_GROUP,
+        DEFAULT_REPRESENTATIVE_GROUP,
+    },
+    voting_power::{AnyVotingPowerTransform, VotingPowerCap, VotingPowerTransform},
+    RawSnapshot, Snapshot, SnapshotInfo, VotingGroup, VotingGroupSummary,
 };
 use snapshot_lib::{Dreps, Fraction};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use tracing::info;
 
 /// Process raw registrations into blockchain initials
 #[derive(Parser)]
-#[clap(rename_all = "kebab-case")]
+#[clap(
+    rename_all = "kebab-case",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 pub struct SnapshotCmd {
+    #[clap(subcommand)]
+    command: Option<SnapshotSubCmd>,
+
     /// Base file to save.  Will also create a <base_file>.summary<.extension> file.
     #[clap(flatten)]
     output: OutputFile,
 
     /// Path to the file containing all CIP-15 compatible registrations in json format.
-    #[clap(short, long, value_parser = PathBuf::from_str)]
-    snapshot: PathBuf,
+    #[clap(short, long, value_parser = PathBuf::from_str, required = true)]
+    snapshot: Option<PathBuf>,
 
     /// Discrimination to use for initial addresses
     #[clap(short, long, default_value = "production")]
@@ -35,13 +51,19 @@ pub struct SnapshotCmd {
 
     // Processing Options
     /// Registrations voting power threshold for eligibility
-    #[clap(short, long)]
-    min_stake_threshold: Value,
+    #[clap(short, long, required = true)]
+    min_stake_threshold: Option<Value>,
 
     /// Voting power cap for each account
     #[clap(short, long, default_value = "100.0")]
     voting_power_cap: Fraction,
 
+    /// Transformation of the voting power of each account: `cap(<fraction>)`, `quadratic`,
+    /// `logarithmic(<scale>)` or `tiered(<from>:<weight>,...)`.
+    /// Replaces the voting power cap when given.
+    #[clap(long, value_parser = AnyVotingPowerTransform::from_str)]
+    voting_power_transform: Option<AnyVotingPowerTransform>,
+
     /// Make a loadtest suitable snapshot.
     #[clap(short, long, default_value = "false")]
     loadtest: bool,
@@ -79,6 +101,11 @@ pub struct SnapshotCmd {
     #[clap(short, default_value = "false")]
     final_snapshot: bool,
 
+    /// Path to the file containing the CIP-36 deregistrations in json format.
+    /// Registrations cancelled by them are dropped from the snapshot.
+    #[clap(long, value_parser = PathBuf::from_str)]
+    deregistrations: Option<PathBuf>,
+
     /// Path to the file containing all dreps information in json format.
     /// Currently Unsupported
     #[clap(long, value_parser = PathBuf::from_str)]
@@ -93,6 +120,17 @@ pub struct SnapshotCmd {
     /// Currently Unsupported
     #[clap(long)]
     representatives_group: Option<String>,
+    /// Path to a TOML or JSON file with the rules assigning the voters to voting groups.
+    /// Takes precedence over the dreps based assignment.
+    #[clap(long, value_parser = PathBuf::from_str)]
+    voting_group_rules: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+#[clap(rename_all = "kebab-case")]
+pub enum SnapshotSubCmd {
+    /// Compare two raw snapshots and report how the voters changed
+    Diff(diff::SnapshotDiffCmd),
 }
 
 fn is_false(b: &bool) -> bool {
@@ -108,8 +146,10 @@ pub struct SnapshotConfig {
     // Parameters we processed with
     discrimination: Discrimination,
     min_stake_threshold: Value,
-    voting_power_cap_pct: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    voting_power_cap_pct: Option<String>,
     voting_power_cap: u64,
+    voting_power_transform: String,
 
     // What the snapshot represents
     deadline_datetime: String,
@@ -129,6 +169,7 @@ pub struct SnapshotConfig {
     total_registered_voting_power: u128,
     total_eligible_voters: u64,
     total_eligible_voting_power: u128,
+    voting_groups: BTreeMap<VotingGroup, VotingGroupSummary>,
 }
 
 #[derive(Serialize)]
@@ -155,6 +196,21 @@ pub struct SnapshotSummaryReport {
 
 impl SnapshotCmd {
     pub fn exec(self) -> Result<(), Report> {
+        if let Some(command) = self.command {
+            return match command {
+                SnapshotSubCmd::Diff(diff) => diff.exec(),
+            };
+        }
+        // Only optional when a subcommand is given
+        let (snapshot, min_stake_threshold) = match (self.snapshot, self.min_stake_threshold) {
+            (Some(snapshot), Some(min_stake_threshold)) => (snapshot, min_stake_threshold),
+            _ => {
+                return Err(color_eyre::eyre::eyre!(
+                    "--snapshot and --min-stake-threshold are required"
+                ))
+            }
+        };
+
         if self.voting_power_cap > Fraction::from(100) {
             return Err(color_eyre::eyre::eyre!(
                 "Voting power cap (%) must be less than 100.0 "
@@ -174,9 +230,15 @@ impl SnapshotCmd {
         // See: https://github.com/serde-rs/json/issues/160
         // serde_json::from_reader took 28 seconds to read the file.
         // Reading to a  string and then converting took 90ms.
-        let raw_snapshot_data = std::fs::read_to_string(&self.snapshot)?;
-
-        let raw_snapshot = serde_json::from_str(&raw_snapshot_data)?;
+        let raw_snapshot_data = std::fs::read_to_string(&snapshot)?;
+
+        let mut raw_snapshot: RawSnapshot = serde_json::from_str(&raw_snapshot_data)?;
+        if let Some(deregistrations) = &self.deregistrations {
+            let deregistrations: Vec<Deregistration> =
+                serde_json::from_str(&std::fs::read_to_string(deregistrations)?)?;
+            let dropped = raw_snapshot.drop_deregistered(&deregistrations);
+            info!("Dropped {} deregistered registrations", dropped);
+        }
         let dreps = if let Some(dreps) = &self.dreps {
             serde_json::from_reader(File::open(dreps)?)?
         } else {
@@ -188,18 +250,49 @@ impl SnapshotCmd {
         let representative = self
             .representatives_group
             .unwrap_or_else(|| DEFAULT_REPRESENTATIVE_GROUP.into());
-        let assigner = RepsVotersAssigner::new(direct_voter, representative, dreps);
+        let voting_power_transform = self
+            .voting_power_transform
+            .clone()
+            .unwrap_or_else(|| AnyVotingPowerTransform::Cap(VotingPowerCap::new(voting_power_cap)));
+        // only reported if the transform applied is a cap, which may not be `--voting-power-cap`
+        let voting_power_cap_pct = match &voting_power_transform {
+            AnyVotingPowerTransform::Cap(cap) => {
+                Some(format!("{:.8}", cap.fraction() * Fraction::from(100)))
+            }
+            _ => None,
+        };
 
         info!("Processing Snapshot");
 
-        let processed_snapshot = Snapshot::from_raw_snapshot(
-            raw_snapshot,
-            self.min_stake_threshold,
-            voting_power_cap,
-            &assigner,
-            self.discrimination,
-            self.loadtest,
-        )?;
+        let processed_snapshot = if let Some(rules) = &self.voting_group_rules {
+            let assigner = load_voting_group_rules(rules)?;
+            Snapshot::from_raw_snapshot(
+                raw_snapshot,
+                min_stake_threshold,
+                &voting_power_transform,
+                &assigner,
+                self.discrimination,
+                self.loadtest,
+            )?
+        } else {
+            let assigner = RepsVotersAssigner::new(direct_voter, representative, dreps);
+            Snapshot::from_raw_snapshot(
+                raw_snapshot,
+                min_stake_threshold,
+                &voting_power_transform,
+                &assigner,
+                self.discrimination,
+                self.loadtest,
+            )?
+        };
+
+        let voting_groups = processed_snapshot.voting_groups_summary();
+        for (group, summary) in &voting_groups {
+            info!(
+                "Voting group {}: {} voters with {} voting power, {} voters under threshold",
+                group, summary.voters, summary.voting_power, summary.underthreshold_voters
+            );
+        }
 
         info!("Generating Report");
 
@@ -208,8 +301,9 @@ impl SnapshotCmd {
                 load_test: self.loadtest,
                 discrimination: self.discrimination,
                 min_stake_threshold: processed_snapshot.stake_threshold,
-                voting_power_cap_pct: format!("{:.8}", self.voting_power_cap),
+                voting_power_cap_pct,
                 voting_power_cap: processed_snapshot.voting_power_cap,
+                voting_power_transform: voting_power_transform.name(),
 
                 deadline_datetime: self.deadline_datetime,
                 slot_no: self.slot_no,
@@ -224,6 +318,7 @@ impl SnapshotCmd {
                 total_registered_voting_power: processed_snapshot.total_registered_voting_power,
                 total_eligible_voters: processed_snapshot.total_eligible_voters,
                 total_eligible_voting_power: processed_snapshot.total_eligible_voting_power,
+                voting_groups,
             },
             voters: processed_snapshot.to_full_snapshot_info(),
         };
@@ -262,3 +357,52 @@ impl SnapshotCmd {
         Ok(())
     }
 }
+
+/// Load the voting group rules, in TOML unless the file has a `.json` extension
+fn load_voting_group_rules(path: &Path) -> Result<VotingGroupRules, Report> {
+    let content = std::fs::read_to_string(path)?;
+    if path.extension().and_then(OsStr::to_str) == Some("json") {
+        Ok(serde_json::from_str(&content)?)
+    } else {
+        Ok(toml::from_str(&content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> SnapshotCmd {
+        let mut all_args = vec!["snapshot", "--snapshot", "raw.json", "-m", "1"];
+        all_args.extend(args);
+        SnapshotCmd::try_parse_from(all_args).unwrap()
+    }
+
+    #[test]
+    fn voting_power_transform_is_optional() {
+        assert_eq!(parse(&[]).voting_power_transform, None);
+    }
+
+    #[test]
+    fn voting_power_transform_is_parsed() {
+        let cmd = parse(&["--voting-power-transform", "tiered(0:1,1000:1/2)"]);
+        assert_eq!(
+            cmd.voting_power_transform.unwrap().name(),
+            "tiered(0:1,1000:1/2)"
+        );
+
+        let cmd = parse(&["--voting-power-transform", "quadratic"]);
+        assert_eq!(cmd.voting_power_transform.unwrap().transform(16, 0), 4);
+
+        assert!(SnapshotCmd::try_parse_from([
+            "snapshot",
+            "--snapshot",
+            "raw.json",
+            "-m",
+            "1",
+            "--voting-power-transform",
+            "linear",
+        ])
+        .is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use chain_impl_mockchain::chaintypes::HeaderId;
+use chain_storage::{verify_archive, ArchiveManifest, BackendKind, BlockStore};
+use clap::{Parser, ValueEnum};
+use color_eyre::{eyre::eyre, Report};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+const MAIN_TAG: &str = "HEAD";
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Backend {
+    Sled,
+    Redb,
+}
+
+impl From<Backend> for BackendKind {
+    fn from(backend: Backend) -> Self {
+        match backend {
+            Backend::Sled => BackendKind::Sled,
+            Backend::Redb => BackendKind::Redb,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+#[clap(rename_all = "kebab-case")]
+pub enum Storage {
+    /// Export the main chain of a Jormungandr database, with its tags, to an archive file
+    Export(Export),
+    /// Import an archive file into a new Jormungandr database
+    Import(Import),
+    /// Check the integrity of an archive file
+    Verify(Verify),
+    /// Remove the branches which are not part of the main chain of a Jormungandr database
+    Prune(Prune),
+    /// Reclaim the unused space of a Jormungandr database
+    Compact(Compact),
+}
+
+#[derive(Debug, Parser)]
+#[clap(rename_all = "kebab-case")]
+pub struct Export {
+    /// The path to the Jormungandr database, the node must not be running
+    jormungandr_database: PathBuf,
+    /// The archive file to write
+    output: PathBuf,
+    /// The database backend of the Jormungandr node
+    #[clap(long, value_enum, default_value = "sled")]
+    backend: Backend,
+}
+
+#[derive(Debug, Parser)]
+#[clap(rename_all = "kebab-case")]
+pub struct Import {
+    /// The archive file to import
+    archive: PathBuf,
+    /// The path to the new Jormungandr database
+    jormungandr_database: PathBuf,
+    /// The database backend of the new database
+    #[clap(long, value_enum, default_value = "sled")]
+    backend: Backend,
+}
+
+#[derive(Debug, Parser)]
+#[clap(rename_all = "kebab-case")]
+pub struct Verify {
+    /// The archive file to check
+    archive: PathBuf,
+}
+
+#[derive(Debug, Parser)]
+#[clap(rename_all = "kebab-case")]
+pub struct Prune {
+    /// The path to the Jormungandr database, the node must not be running
+    jormungandr_database: PathBuf,
+    /// The database backend of the Jormungandr node
+    #[clap(long, value_enum, default_value = "sled")]
+    backend: Backend,
+}
+
+#[derive(Debug, Parser)]
+#[clap(rename_all = "kebab-case")]
+pub struct Compact {
+    /// The path to the Jormungandr database, the node must not be running
+    jormungandr_database: PathBuf,
+    /// The database backend of the Jormungandr node
+    #[clap(long, value_enum, default_value = "sled")]
+    backend: Backend,
+}
+
+impl Storage {
+    pub fn exec(self) -> Result<(), Report> {
+        match self {
+            Storage::Export(export) => export.exec(),
+            Storage::Import(import) => import.exec(),
+            Storage::Verify(verify) => verify.exec(),
+            Storage::Prune(prune) => prune.exec(),
+            Storage::Compact(compact) => compact.exec(),
+        }
+    }
+}
+
+impl Export {
+    fn exec(self) -> Result<(), Report> {
+        let db = open_existing(&self.jormungandr_database, self.backend)?;
+        let tip_id = main_tip(&db)?;
+
+        let output = BufWriter::new(File::create(&self.output)?);
+        let manifest = db.export_archive(tip_id.as_ref(), output)?;
+        print_manifest(&manifest);
+        Ok(())
+    }
+}
+
+impl Import {
+    fn exec(self) -> Result<(), Report> {
+        if self.jormungandr_database.exists() {
+            return Err(eyre!(
+                "{} already exists, the archive must be imported into a new database",
+                self.jormungandr_database.display()
+            ));
+        }
+
+        // check the archive first, to not leave a partial database behind
+        verify_archive(BufReader::new(File::open(&self.archive)?))?;
+
+        let db = BlockStore::file_with_backend(
+            &self.jormungandr_database,
+            root_id(),
+            self.backend.into(),
+        )?;
+        let manifest = db.import_archive(BufReader::new(File::open(&self.archive)?))?;
+        print_manifest(&manifest);
+        Ok(())
+    }
+}
+
+impl Verify {
+    fn exec(self) -> Result<(), Report> {
+        let manifest = verify_archive(BufReader::new(File::open(&self.archive)?))?;
+        print_manifest(&manifest);
+        Ok(())
+    }
+}
+
+impl Prune {
+    fn exec(self) -> Result<(), Report> {
+        let db = open_existing(&self.jormungandr_database, self.backend)?;
+        let tip_id = main_tip(&db)?;
+
+        let pruned = db.prune_side_branches(tip_id.as_ref())?;
+        println!("pruned {} branches", pruned);
+        Ok(())
+    }
+}
+
+impl Compact {
+    fn exec(self) -> Result<(), Report> {
+        BlockStore::compact(&self.jormungandr_database, self.backend.into())?;
+        Ok(())
+    }
+}
+
+fn root_id() -> Vec<u8> {
+    HeaderId::zero_hash().as_bytes().to_owned()
+}
+
+fn open_existing(path: &Path, backend: Backend) -> Result<BlockStore, Report> {
+    if !path.exists() {
+        return Err(eyre!("{} does not exist", path.display()));
+    }
+    Ok(BlockStore::file_with_backend(
+        path,
+        root_id(),
+        backend.into(),
+    )?)
+}
+
+fn main_tip(db: &BlockStore) -> Result<chain_storage::Value, Report> {
+    db.get_tag(MAIN_TAG)?
+        .ok_or_else(|| eyre!("the database has no {} tag", MAIN_TAG))
+}
+
+fn print_manifest(manifest: &ArchiveManifest) {
+    println!("blocks: {}", manifest.blocks);
+    println!("tags: {}", manifest.tags);
+    println!("tip: {}", hex::encode(manifest.tip.as_ref()));
+    println!("checksum: {}", hex::encode(manifest.checksum));
+}
@@ -40,8 +40,6 @@ pub enum QrDecodeError {
     DecodeError(#[from] quircs::DecodeError),
     #[error("couldn't extract QR code")]
     ExtractError(#[from] quircs::ExtractError),
-    #[error("QR code payload is not valid uf8")]
-    NonUtf8Payload,
 }
 
 impl KeyQrCode {
@@ -52,6 +50,19 @@ impl KeyQrCode {
         KeyQrCode { inner }
     }
 
+    /// Same as [`KeyQrCode::generate`], but the key is protected with Argon2id and the payload is
+    /// stored as binary rather than hex, which also makes the QR code smaller
+    pub fn generate_argon2id(
+        key: SecretKey<Ed25519Extended>,
+        password: &[u8],
+        params: payload::KdfParams,
+    ) -> Result<Self, KeyQrCodeError> {
+        let payload = payload::generate_argon2id(key, password, params)?;
+        let inner = QrCode::with_error_correction_level(payload, EcLevel::H).unwrap();
+
+        Ok(KeyQrCode { inner })
+    }
+
     pub fn write_svg(&self, path: impl AsRef<Path>) -> Result<(), KeyQrCodeError> {
         let mut out = File::create(path)?;
         let svg_file = self
@@ -88,10 +99,8 @@ impl KeyQrCode {
                     .map_err(QrDecodeError::ExtractError)
                     .and_then(|c| c.decode().map_err(QrDecodeError::DecodeError))?;
 
-                // TODO: I actually don't know if this can fail
-                let h = std::str::from_utf8(&decoded.payload)
-                    .map_err(|_| QrDecodeError::NonUtf8Payload)?;
-                payload::decode(h, password).map_err(Into::into)
+                // either hex text or binary, depending on the payload version
+                payload::decode(&decoded.payload, password).map_err(Into::into)
             })
             .collect()
     }
@@ -2,18 +2,24 @@ mod img;
 mod payload;
 
 pub use img::{KeyQrCode, KeyQrCodeError};
-pub use payload::{decode, generate, Error as KeyQrCodePayloadError};
+pub use payload::{decode, generate, generate_argon2id, Error as KeyQrCodePayloadError, KdfParams};
+use std::fmt;
 use std::path::PathBuf;
 use std::str::FromStr;
 use thiserror::Error;
 
 pub const PIN_LENGTH: usize = 4;
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct QrPin {
     pub password: [u8; 4],
 }
 
+/// Alternative to a [`QrPin`] for QR codes which are not meant for the Catalyst voting app
+#[derive(Clone, PartialEq, Eq)]
+pub struct QrPassphrase(String);
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error(transparent)]
@@ -30,6 +36,8 @@ pub enum BadPinError {
     InvalidLength(usize),
     #[error("Invalid digit {0}")]
     InvalidDigit(char),
+    #[error("The passphrase must be at least {MIN_PASSPHRASE_LENGTH} characters long, found {0}")]
+    PassphraseTooShort(usize),
     #[error("cannot detect file name from path {0:?} in order to read qr pin from it")]
     UnableToDetectFileName(PathBuf),
 }
@@ -50,6 +58,30 @@ impl FromStr for QrPin {
     }
 }
 
+impl FromStr for QrPassphrase {
+    type Err = BadPinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let length = s.chars().count();
+        if length < MIN_PASSPHRASE_LENGTH {
+            return Err(BadPinError::PassphraseTooShort(length));
+        }
+        Ok(QrPassphrase(s.to_string()))
+    }
+}
+
+impl QrPassphrase {
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl fmt::Debug for QrPassphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("QrPassphrase(..)")
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum PinReadMode {
     Global(String),
@@ -107,6 +139,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn passphrases_must_not_be_too_short() {
+        assert!(QrPassphrase::from_str("1234567").is_err());
+        assert_eq!(
+            QrPassphrase::from_str("12345678").unwrap().as_bytes(),
+            b"12345678"
+        );
+    }
+
     #[test]
     fn pins_that_do_not_satisfy_content_reqs_return_error() {
         for bad_pin in &["    ", " 111", "llll", "000u"] {
@@ -140,4 +181,24 @@ mod tests {
                 .as_ref()
         );
     }
+
+    #[test]
+    fn encode_decode_argon2id() {
+        const PASSWORD: &[u8] = b"a longer passphrase";
+        let sk = SecretKey::generate(rand::thread_rng());
+        let params = KdfParams {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let qr = KeyQrCode::generate_argon2id(sk.clone(), PASSWORD, params).unwrap();
+        let img = qr.to_img();
+        assert_eq!(
+            sk.leak_secret().as_ref(),
+            KeyQrCode::decode(DynamicImage::ImageLuma8(img), PASSWORD).unwrap()[0]
+                .clone()
+                .leak_secret()
+                .as_ref()
+        );
+    }
 }
@@ -1,8 +1,10 @@
 use chain_crypto::{Ed25519Extended, SecretKey, SecretKeyError};
 use std::io;
-use symmetric_cipher::{decrypt, encrypt, Error as SymmetricCipherError};
+use symmetric_cipher::{decrypt, encrypt, encrypt_argon2id, Error as SymmetricCipherError};
 use thiserror::Error;
 
+pub use symmetric_cipher::KdfParams;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("encryption-decryption protocol error")]
@@ -15,23 +17,49 @@ pub enum Error {
     HexDecode(#[from] hex::FromHexError),
 }
 
+/// Hex encoded payload, protected with a PBKDF2 derived key
+///
+/// This is the format read by the Catalyst voting app.
 pub fn generate(key: SecretKey<Ed25519Extended>, password: &[u8]) -> String {
     let secret = key.leak_secret();
     let rng = rand::thread_rng();
     // this won't fail because we already know it's an ed25519extended key,
     // so it is safe to unwrap
     let enc = encrypt(password, secret.as_ref(), rng).unwrap();
-    // Using binary would make the QR codes more compact and probably less
-    // prone to scanning errors.
     hex::encode(enc)
 }
 
-pub fn decode<S: Into<String>>(
-    payload: S,
+/// Binary payload, protected with an Argon2id derived key
+///
+/// The Argon2id parameters are part of the payload, so they can be changed without breaking
+/// [`decode`].
+pub fn generate_argon2id(
+    key: SecretKey<Ed25519Extended>,
+    password: &[u8],
+    params: KdfParams,
+) -> Result<Vec<u8>, Error> {
+    let secret = key.leak_secret();
+    let rng = rand::thread_rng();
+    let enc = encrypt_argon2id(password, secret.as_ref(), params, rng)?;
+    Ok(enc.into_vec())
+}
+
+/// Decodes a payload produced by either [`generate`] or [`generate_argon2id`]
+///
+/// Binary payloads start with the protocol version byte, which is never a hex digit, anything
+/// else is read as hex text.
+pub fn decode<P: AsRef<[u8]>>(
+    payload: P,
     password: &[u8],
 ) -> Result<SecretKey<Ed25519Extended>, Error> {
-    let encrypted_bytes = hex::decode(payload.into())?;
-    let key = decrypt(password, encrypted_bytes)?;
+    let payload = payload.as_ref();
+    let key = match payload.first() {
+        Some(byte) if !byte.is_ascii_hexdigit() => decrypt(password, payload)?,
+        _ => {
+            let encrypted_bytes = hex::decode(String::from_utf8_lossy(payload).trim())?;
+            decrypt(password, encrypted_bytes)?
+        }
+    };
     Ok(SecretKey::from_binary(&key)?)
 }
 
@@ -49,4 +77,27 @@ mod tests {
             decode(hash, PASSWORD).unwrap().leak_secret().as_ref()
         );
     }
+
+    #[test]
+    fn encode_decode_argon2id() {
+        const PASSWORD: &[u8] = b"a longer passphrase";
+        let sk = SecretKey::generate(rand::thread_rng());
+        let params = KdfParams {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let payload = generate_argon2id(sk.clone(), PASSWORD, params).unwrap();
+        assert_eq!(
+            sk.leak_secret().as_ref(),
+            decode(&payload, PASSWORD).unwrap().leak_secret().as_ref()
+        );
+        assert_eq!(
+            sk.leak_secret().as_ref(),
+            decode(hex::encode(payload), PASSWORD)
+                .unwrap()
+                .leak_secret()
+                .as_ref()
+        );
+    }
 }
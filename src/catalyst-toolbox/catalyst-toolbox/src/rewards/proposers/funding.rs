@@ -0,0 +1,228 @@
+use super::FundingStrategy;
+use color_eyre::{eyre::bail, Result};
+
+/// Upper bound on the size in bits of the table of reachable allocations computed
+/// by the knapsack strategy, 128 MiB.
+const MAX_KNAPSACK_TABLE_BITS: u128 = 1 << 30;
+
+/// A proposal competing for the budget of a challenge
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Candidate {
+    pub requested_funds: i64,
+    pub meets_approval_threshold: bool,
+}
+
+/// Select the candidates funded with `fund` according to `strategy`.
+///
+/// Candidates are given in funding order, the returned flags are in the same order.
+pub(super) fn select_funded(
+    strategy: FundingStrategy,
+    candidates: &[Candidate],
+    fund: i64,
+) -> Result<Vec<bool>> {
+    match strategy {
+        FundingStrategy::Greedy => Ok(greedy(candidates, fund, false)),
+        FundingStrategy::GreedyFillIn => Ok(greedy(candidates, fund, true)),
+        FundingStrategy::Knapsack => knapsack(candidates, fund),
+    }
+}
+
+fn greedy(candidates: &[Candidate], fund: i64, fill_in: bool) -> Vec<bool> {
+    let mut depletion = fund;
+    let mut exhausted = false;
+    candidates
+        .iter()
+        .map(|candidate| {
+            if !candidate.meets_approval_threshold || exhausted {
+                return false;
+            }
+            let fits = depletion > 0 && depletion >= candidate.requested_funds;
+            if fits {
+                depletion -= candidate.requested_funds;
+            } else if !fill_in {
+                // the remaining budget is not reconsidered for the next proposals
+                exhausted = true;
+            }
+            fits
+        })
+        .collect()
+}
+
+/// Fund the set of proposals leaving the least of the budget unallocated. Among the sets
+/// allocating the same amount, the proposals earlier in funding order are preferred.
+fn knapsack(candidates: &[Candidate], fund: i64) -> Result<Vec<bool>> {
+    let mut funded = vec![false; candidates.len()];
+    if fund <= 0 {
+        return Ok(funded);
+    }
+
+    // free proposals are always funded, those over the whole budget never are
+    let mut items = Vec::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        if !candidate.meets_approval_threshold || candidate.requested_funds > fund {
+            continue;
+        }
+        if candidate.requested_funds <= 0 {
+            funded[index] = true;
+        } else {
+            items.push((index, candidate.requested_funds));
+        }
+    }
+    if items.is_empty() {
+        return Ok(funded);
+    }
+
+    // requested funds are usually round amounts, the common divisor keeps the table small
+    let divisor = items
+        .iter()
+        .fold(fund, |divisor, (_, cost)| gcd(divisor, *cost));
+    let capacity = (fund / divisor) as usize;
+    let table_bits = (capacity as u128 + 1) * (items.len() as u128 + 1);
+    if table_bits > MAX_KNAPSACK_TABLE_BITS {
+        bail!(
+            "too many proposals to compute the knapsack funding of a budget of {}, use a greedy strategy",
+            fund
+        );
+    }
+
+    // reachable[i] holds the allocations reachable with the first i items
+    let mut reachable = vec![BitSet::new(capacity + 1)];
+    reachable[0].set(0);
+    for (_, cost) in &items {
+        let previous = reachable.last().unwrap();
+        let mut next = previous.clone();
+        next.or_shifted(previous, (*cost / divisor) as usize);
+        reachable.push(next);
+    }
+
+    let mut allocation = (0..=capacity)
+        .rev()
+        .find(|allocation| reachable[items.len()].get(*allocation))
+        .expect("an empty allocation is always reachable");
+    // walking back from the last item, an item is only funded when the
+    // allocation cannot be reached without it
+    for (i, (index, cost)) in items.iter().enumerate().rev() {
+        if !reachable[i].get(allocation) {
+            funded[*index] = true;
+            allocation -= (*cost / divisor) as usize;
+        }
+    }
+
+    Ok(funded)
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[derive(Clone)]
+struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; (len + 63) / 64],
+            len,
+        }
+    }
+
+    fn get(&self, bit: usize) -> bool {
+        self.words[bit / 64] & (1 << (bit % 64)) != 0
+    }
+
+    fn set(&mut self, bit: usize) {
+        self.words[bit / 64] |= 1 << (bit % 64);
+    }
+
+    /// `self |= other << shift`, discarding the bits shifted past the length
+    fn or_shifted(&mut self, other: &BitSet, shift: usize) {
+        let (word_shift, bit_shift) = (shift / 64, shift % 64);
+        for (i, word) in self.words.iter_mut().enumerate().skip(word_shift) {
+            let mut shifted = other.words[i - word_shift] << bit_shift;
+            if bit_shift > 0 && i > word_shift {
+                shifted |= other.words[i - word_shift - 1] >> (64 - bit_shift);
+            }
+            *word |= shifted;
+        }
+        let extra_bits = self.words.len() * 64 - self.len;
+        if extra_bits > 0 {
+            *self.words.last_mut().unwrap() &= u64::MAX >> extra_bits;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(requested_funds: &[i64]) -> Vec<Candidate> {
+        requested_funds
+            .iter()
+            .map(|requested_funds| Candidate {
+                requested_funds: *requested_funds,
+                meets_approval_threshold: true,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn strategies() {
+        let candidates = candidates(&[60, 50, 30, 10]);
+        assert_eq!(
+            select_funded(FundingStrategy::Greedy, &candidates, 100).unwrap(),
+            vec![true, false, false, false]
+        );
+        assert_eq!(
+            select_funded(FundingStrategy::GreedyFillIn, &candidates, 100).unwrap(),
+            vec![true, false, true, true]
+        );
+        assert_eq!(
+            select_funded(FundingStrategy::Knapsack, &candidates, 100).unwrap(),
+            vec![true, false, true, true]
+        );
+        assert_eq!(
+            select_funded(FundingStrategy::Knapsack, &candidates, 90).unwrap(),
+            vec![true, false, true, false]
+        );
+        assert_eq!(
+            select_funded(FundingStrategy::Knapsack, &candidates, 80).unwrap(),
+            vec![false, true, true, false]
+        );
+    }
+
+    #[test]
+    fn proposals_under_the_approval_threshold_are_not_funded() {
+        let mut candidates = candidates(&[10, 10]);
+        candidates[0].meets_approval_threshold = false;
+        for strategy in [
+            FundingStrategy::Greedy,
+            FundingStrategy::GreedyFillIn,
+            FundingStrategy::Knapsack,
+        ] {
+            assert_eq!(
+                select_funded(strategy, &candidates, 100).unwrap(),
+                vec![false, true]
+            );
+        }
+    }
+
+    #[test]
+    fn knapsack_over_word_boundaries() {
+        let candidates = candidates(&[97, 101, 103, 89]);
+        assert_eq!(
+            select_funded(FundingStrategy::Knapsack, &candidates, 293).unwrap(),
+            vec![false, true, true, true]
+        );
+        assert_eq!(
+            select_funded(FundingStrategy::Knapsack, &candidates, 201).unwrap(),
+            vec![true, false, true, false]
+        );
+    }
+}
@@ -92,6 +92,7 @@ mod tests {
             "meets_approval_threshold",
             "requested_dollars",
             "status",
+            "funded_by",
             "fund_depletion",
             "not_funded_reason",
             "link_to_ideascale",
@@ -1,4 +1,8 @@
-use self::{io::vecs_to_maps, types::NotFundedReason};
+use self::{
+    funding::{select_funded, Candidate},
+    io::vecs_to_maps,
+    types::NotFundedReason,
+};
 use crate::types::{challenge::Challenge, proposal::Proposal};
 use chain_impl_mockchain::value::Value;
 use color_eyre::{
@@ -17,6 +21,7 @@ use tracing::debug;
 pub use types::*;
 pub use util::build_path_for_challenge;
 
+mod funding;
 pub mod io;
 mod types;
 mod util;
@@ -30,6 +35,7 @@ pub struct ProposerRewardsInputs {
     pub committee_keys: Vec<Address>,
     pub total_stake_threshold: f64,
     pub approval_threshold: f64,
+    pub funding_strategy: FundingStrategy,
 }
 
 pub fn proposer_rewards(
@@ -42,6 +48,7 @@ pub fn proposer_rewards(
         committee_keys,
         total_stake_threshold,
         approval_threshold,
+        funding_strategy,
     }: ProposerRewardsInputs,
 ) -> Result<Vec<(Challenge, Vec<Calculation>)>> {
     let (proposals, voteplans, challenges) = vecs_to_maps(proposals, voteplans, challenges)?;
@@ -64,6 +71,7 @@ pub fn proposer_rewards(
             challenge.rewards_total,
             approval_threshold,
             total_stake_approval_threshold,
+            funding_strategy,
         )?;
 
         result.push((challenge, calculations));
@@ -78,6 +86,7 @@ pub fn calculate_results(
     fund: i64,
     threshold: f64,
     total_stake_threshold: f64,
+    funding_strategy: FundingStrategy,
 ) -> Result<Vec<Calculation>> {
     debug!("calculating. threshold: {threshold}, total_stake_threshold: {total_stake_threshold}");
     let success_results = calculate_vote_difference_and_threshold_success(
@@ -90,18 +99,25 @@ pub fn calculate_results(
     let mut sorted_ids = success_results.keys().collect_vec();
     sorted_ids.sort_unstable_by_key(|&id| success_results[id].0);
 
+    let candidates = sorted_ids
+        .iter()
+        .map(|&id| Candidate {
+            requested_funds: proposals[id].proposal_funds,
+            meets_approval_threshold: success_results[id].1,
+        })
+        .collect_vec();
+    let selected = select_funded(funding_strategy, &candidates, fund)?;
+
     let mut results = vec![];
     let mut depletion = fund;
 
-    for proposal_id in sorted_ids {
+    for (proposal_id, funded) in sorted_ids.into_iter().zip(selected) {
         debug!("calculating proposal_id: {proposal_id}");
         let proposal = &proposals[proposal_id];
         let voteplan = &voteplans[proposal_id];
         let (total_result, threshold_success) = success_results[proposal_id];
         let (yes, no) = extract_yes_no_votes(proposal, voteplan)?;
 
-        let funded = threshold_success && depletion > 0 && depletion >= proposal.proposal_funds;
-
         let not_funded_reason = match (funded, threshold_success) {
             (true, _) => None,
             (false, true) => Some(NotFundedReason::OverBudget),
@@ -124,6 +140,7 @@ pub fn calculate_results(
             meets_approval_threshold: threshold_success.into(),
             requested_dollars: proposal.proposal_funds,
             status: funded.into(),
+            funded_by: funded.then_some(funding_strategy),
             fund_depletion: depletion as f64,
             not_funded_reason,
             link_to_ideascale: proposal.proposal_url.clone(),
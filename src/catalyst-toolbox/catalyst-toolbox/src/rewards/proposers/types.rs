@@ -36,6 +36,35 @@ pub enum NotFundedReason {
     ApprovalThreshold,
 }
 
+/// How the budget of a challenge is allocated to the proposals meeting the approval threshold,
+/// considered in order of their results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FundingStrategy {
+    /// Fund the proposals until the first one not fitting in the remaining budget
+    Greedy,
+    /// Fund the proposals, skipping the ones not fitting in the remaining budget
+    GreedyFillIn,
+    /// Fund the proposals leaving the least of the budget unallocated,
+    /// preferring the proposals with the best results
+    Knapsack,
+}
+
+impl FromStr for FundingStrategy {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "greedy" => Ok(FundingStrategy::Greedy),
+            "greedy-fill-in" => Ok(FundingStrategy::GreedyFillIn),
+            "knapsack" => Ok(FundingStrategy::Knapsack),
+            s => Err(eyre!(
+                "expected one of `greedy`, `greedy-fill-in` or `knapsack`, found {s}"
+            )),
+        }
+    }
+}
+
 #[derive(Parser)]
 #[clap(rename_all = "kebab-case")]
 pub struct ProposerRewards {
@@ -57,6 +86,11 @@ pub struct ProposerRewards {
     #[clap(long)]
     pub output_format: OutputFormat,
 
+    /// `greedy-fill-in` is the allocation historically used to compute the proposer rewards
+    #[clap(default_value = "greedy-fill-in")]
+    #[clap(long)]
+    pub funding_strategy: FundingStrategy,
+
     #[clap(long = "proposals-path")]
     pub proposals: PathBuf,
     #[clap(long = "excluded-proposals-path")]
@@ -82,6 +116,7 @@ pub struct Calculation {
     pub meets_approval_threshold: YesNo,
     pub requested_dollars: i64,
     pub status: FundedStatus,
+    pub funded_by: Option<FundingStrategy>,
     pub fund_depletion: f64,
     pub not_funded_reason: Option<NotFundedReason>,
     pub link_to_ideascale: String,
@@ -101,6 +136,7 @@ impl Default for Calculation {
             meets_approval_threshold: YesNo::Yes,
             requested_dollars: Default::default(),
             status: FundedStatus::NotFunded,
+            funded_by: None,
             fund_depletion: Default::default(),
             not_funded_reason: None,
             link_to_ideascale: Default::default(),
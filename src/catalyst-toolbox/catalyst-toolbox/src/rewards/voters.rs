@@ -136,6 +136,7 @@ mod tests {
     use crate::utils::assert_are_close;
     use jormungandr_lib::crypto::{account::Identifier, hash::Hash};
     use snapshot_lib::registration::{Delegations, StakeAddress, VotingRegistration};
+    use snapshot_lib::voting_power::VotingPowerCap;
     use snapshot_lib::Snapshot;
     use snapshot_lib::{Fraction, RawSnapshot};
     use test_strategy::proptest;
@@ -148,7 +149,7 @@ mod tests {
         let snapshot = Snapshot::from_raw_snapshot(
             raw_snapshot,
             DEFAULT_SNAPSHOT_THRESHOLD.into(),
-            Fraction::from(1),
+            &VotingPowerCap::none(),
             &|_vk: &Identifier| String::new(),
             Discrimination::Production,
             false,
@@ -182,7 +183,7 @@ mod tests {
         let snapshot = Snapshot::from_raw_snapshot(
             raw_snapshot,
             DEFAULT_SNAPSHOT_THRESHOLD.into(),
-            Fraction::from(1),
+            &VotingPowerCap::none(),
             &|_vk: &Identifier| String::new(),
             Discrimination::Production,
             false,
@@ -207,7 +208,7 @@ mod tests {
         let snapshot = Snapshot::from_raw_snapshot(
             raw_snapshot,
             DEFAULT_SNAPSHOT_THRESHOLD.into(),
-            Fraction::from(1),
+            &VotingPowerCap::none(),
             &|_vk: &Identifier| String::new(),
             Discrimination::Production,
             false,
@@ -322,7 +323,7 @@ mod tests {
         let snapshot = Snapshot::from_raw_snapshot(
             raw_snapshot.into(),
             0.into(),
-            Fraction::from(1u64),
+            &VotingPowerCap::none(),
             &|_voting_key: &Identifier| String::new(),
             Discrimination::Production,
             false,
@@ -371,7 +372,7 @@ mod tests {
         let snapshot = Snapshot::from_raw_snapshot(
             raw_snapshot.into(),
             0.into(),
-            Fraction::new(1u64, 9u64),
+            &VotingPowerCap::new(Fraction::new(1u64, 9u64)),
             &|_vk: &Identifier| String::new(),
             Discrimination::Production,
             false,
@@ -401,7 +402,7 @@ mod tests {
         let snapshot = Snapshot::from_raw_snapshot(
             raw_snapshot,
             DEFAULT_SNAPSHOT_THRESHOLD.into(),
-            Fraction::from(1),
+            &VotingPowerCap::none(),
             &|_vk: &Identifier| String::new(),
             Discrimination::Production,
             false,
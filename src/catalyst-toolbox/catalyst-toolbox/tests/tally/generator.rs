@@ -199,7 +199,7 @@ impl VoteRoundGenerator {
                 let vote_end = manager.plan().vote_end();
 
                 match manager.plan().payload_type() {
-                    PayloadType::Private => {
+                    PayloadType::Private | PayloadType::Approval | PayloadType::Ranked => {
                         let mut results = Vec::new();
                         let mut shares = Vec::new();
                         for proposal in manager.statuses().proposals {
@@ -0,0 +1,367 @@
+//! Differences between two snapshots of the same registrations, usually taken
+//! at different slots, to explain how the voting power of each voter changed.
+use crate::registration::{Delegations, VotingRegistration};
+use crate::{is_catalyst_registration, RawSnapshot, Snapshot, SnapshotInfo, VotingGroup};
+use jormungandr_lib::crypto::account::Identifier;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub voters: Vec<VoterDiff>,
+    pub superseded_registrations: Vec<SupersededRegistration>,
+    pub delegation_shifts: Vec<DelegationShift>,
+    pub voting_groups: Vec<VotingGroupDiff>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VoterChange {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A voter whose voting power or voting group is not the same in both snapshots
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoterDiff {
+    pub voting_key: String,
+    pub change: VoterChange,
+    pub voting_group_before: Option<VotingGroup>,
+    pub voting_group_after: Option<VotingGroup>,
+    pub voting_power_before: u64,
+    pub voting_power_after: u64,
+    pub voting_power_delta: i128,
+}
+
+/// A registration replaced by one with a higher nonce for the same stake key,
+/// either within the same snapshot or between the two snapshots.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SupersededRegistration {
+    pub stake_public_key: String,
+    pub superseded_nonce: u64,
+    pub nonce: u64,
+}
+
+/// Change in the share of the voting power a stake key delegates to a voting key.
+/// The weights are only meaningful relative to the total weight of the registration.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelegationShift {
+    pub stake_public_key: String,
+    pub voting_key: String,
+    pub weight_before: u32,
+    pub total_weight_before: u64,
+    pub weight_after: u32,
+    pub total_weight_after: u64,
+}
+
+/// Change of the voting power of the eligible voters of a voting group
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VotingGroupDiff {
+    pub voting_group: VotingGroup,
+    pub voting_power_before: u128,
+    pub voting_power_after: u128,
+    pub voting_power_delta: i128,
+}
+
+impl SnapshotDiff {
+    /// Compare the registrations of two raw snapshots, and the voters of the
+    /// snapshots `process`ed from the latest registration of each stake key.
+    pub fn new<E>(
+        raw_before: &RawSnapshot,
+        raw_after: &RawSnapshot,
+        process: impl Fn(RawSnapshot) -> Result<Snapshot, E>,
+    ) -> Result<Self, E> {
+        let (registrations_before, mut superseded_registrations) = latest_registrations(raw_before);
+        let (registrations_after, superseded_after) = latest_registrations(raw_after);
+        superseded_registrations.extend(superseded_after);
+
+        let mut delegation_shifts = Vec::new();
+        for (stake_public_key, reg_before) in &registrations_before {
+            let reg_after = match registrations_after.get(stake_public_key) {
+                Some(reg_after) => reg_after,
+                None => continue,
+            };
+            if reg_before.nonce != reg_after.nonce {
+                superseded_registrations.push(SupersededRegistration {
+                    stake_public_key: stake_public_key.clone(),
+                    superseded_nonce: reg_before.nonce.min(reg_after.nonce),
+                    nonce: reg_before.nonce.max(reg_after.nonce),
+                });
+            }
+            delegation_shifts.extend(delegation_shifts_between(
+                stake_public_key,
+                &reg_before.delegations,
+                &reg_after.delegations,
+            ));
+        }
+
+        // the after snapshot usually still holds the registrations superseded between the
+        // two snapshots, as well as the ones already superseded in the before snapshot
+        let mut reported = HashSet::new();
+        superseded_registrations.retain(|superseded| reported.insert(superseded.clone()));
+
+        // a superseded registration does not count towards the voting power of its voting keys
+        let before = process(latest_snapshot(&registrations_before))?;
+        let after = process(latest_snapshot(&registrations_after))?;
+
+        Ok(Self {
+            voters: voters_diff(&before, &after),
+            superseded_registrations,
+            delegation_shifts,
+            voting_groups: voting_groups_diff(&before, &after),
+        })
+    }
+}
+
+/// The registration with the highest nonce of each stake key, alongside the ones it superseded
+fn latest_registrations(
+    raw_snapshot: &RawSnapshot,
+) -> (
+    BTreeMap<String, &VotingRegistration>,
+    Vec<SupersededRegistration>,
+) {
+    let mut latest = BTreeMap::<String, &VotingRegistration>::new();
+    let mut superseded = Vec::new();
+    for reg in raw_snapshot
+        .0
+        .iter()
+        .filter(|reg| is_catalyst_registration(reg))
+    {
+        let stake_public_key = reg.stake_public_key.0.clone();
+        match latest.get_mut(&stake_public_key) {
+            Some(current) => {
+                let (old, new) = if current.nonce < reg.nonce {
+                    (*current, reg)
+                } else {
+                    (reg, *current)
+                };
+                superseded.push(SupersededRegistration {
+                    stake_public_key,
+                    superseded_nonce: old.nonce,
+                    nonce: new.nonce,
+                });
+                *current = new;
+            }
+            None => {
+                latest.insert(stake_public_key, reg);
+            }
+        }
+    }
+    (latest, superseded)
+}
+
+fn latest_snapshot(registrations: &BTreeMap<String, &VotingRegistration>) -> RawSnapshot {
+    RawSnapshot(registrations.values().map(|reg| (*reg).clone()).collect())
+}
+
+fn delegation_weights(delegations: &Delegations) -> BTreeMap<&Identifier, u32> {
+    match delegations {
+        Delegations::Legacy(vk) => BTreeMap::from([(vk, 1)]),
+        Delegations::New(vks) => vks
+            .iter()
+            .fold(BTreeMap::new(), |mut weights, (vk, weight)| {
+                *weights.entry(vk).or_default() += weight;
+                weights
+            }),
+    }
+}
+
+fn delegation_shifts_between(
+    stake_public_key: &str,
+    before: &Delegations,
+    after: &Delegations,
+) -> Vec<DelegationShift> {
+    let weights_before = delegation_weights(before);
+    let weights_after = delegation_weights(after);
+    let total_before: u64 = weights_before.values().map(|w| u64::from(*w)).sum();
+    let total_after: u64 = weights_after.values().map(|w| u64::from(*w)).sum();
+
+    weights_before
+        .keys()
+        .chain(weights_after.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|vk| {
+            let weight_before = weights_before.get(vk).copied().unwrap_or(0);
+            let weight_after = weights_after.get(vk).copied().unwrap_or(0);
+            // compare the shares of the total weight without rounding
+            let unchanged = u128::from(weight_before) * u128::from(total_after)
+                == u128::from(weight_after) * u128::from(total_before);
+            (!unchanged).then(|| DelegationShift {
+                stake_public_key: stake_public_key.to_string(),
+                voting_key: format!("0x{}", vk.to_hex()),
+                weight_before,
+                total_weight_before: total_before,
+                weight_after,
+                total_weight_after: total_after,
+            })
+        })
+        .collect()
+}
+
+fn voters_diff(before: &Snapshot, after: &Snapshot) -> Vec<VoterDiff> {
+    let voting_keys = before
+        .inner
+        .keys()
+        .chain(after.inner.keys())
+        .collect::<BTreeSet<_>>();
+    voting_keys
+        .into_iter()
+        .filter_map(|vk| {
+            let entry_before = before.inner.get(vk);
+            let entry_after = after.inner.get(vk);
+            let change = match (entry_before, entry_after) {
+                (None, Some(_)) => VoterChange::Added,
+                (Some(_), None) => VoterChange::Removed,
+                (Some(entry_before), Some(entry_after))
+                    if entry_before.hir.voting_power != entry_after.hir.voting_power
+                        || entry_before.hir.voting_group != entry_after.hir.voting_group =>
+                {
+                    VoterChange::Changed
+                }
+                _ => return None,
+            };
+            let voting_power = |entry: Option<&SnapshotInfo>| {
+                entry.map_or(0, |entry| entry.hir.voting_power.as_u64())
+            };
+            let voting_power_before = voting_power(entry_before);
+            let voting_power_after = voting_power(entry_after);
+            Some(VoterDiff {
+                voting_key: format!("0x{}", vk.to_hex()),
+                change,
+                voting_group_before: entry_before.map(|entry| entry.hir.voting_group.clone()),
+                voting_group_after: entry_after.map(|entry| entry.hir.voting_group.clone()),
+                voting_power_before,
+                voting_power_after,
+                voting_power_delta: i128::from(voting_power_after)
+                    - i128::from(voting_power_before),
+            })
+        })
+        .collect()
+}
+
+fn voting_groups_diff(before: &Snapshot, after: &Snapshot) -> Vec<VotingGroupDiff> {
+    let summary_before = before.voting_groups_summary();
+    let summary_after = after.voting_groups_summary();
+    summary_before
+        .keys()
+        .chain(summary_after.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|group| {
+            let voting_power_before = summary_before.get(group).map_or(0, |s| s.voting_power);
+            let voting_power_after = summary_after.get(group).map_or(0, |s| s.voting_power);
+            VotingGroupDiff {
+                voting_group: group.clone(),
+                voting_power_before,
+                voting_power_after,
+                voting_power_delta: voting_power_after as i128 - voting_power_before as i128,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registration::{RewardAddress, StakeAddress};
+    use crate::voting_power::VotingPowerCap;
+    use chain_addr::Discrimination;
+
+    fn voting_key(byte: u8) -> Identifier {
+        Identifier::from_hex(&hex::encode([byte; 32])).unwrap()
+    }
+
+    fn registration(
+        stake: &str,
+        voting_power: u64,
+        nonce: u64,
+        delegations: Delegations,
+    ) -> VotingRegistration {
+        VotingRegistration {
+            stake_public_key: StakeAddress(stake.to_string()),
+            voting_power: voting_power.into(),
+            reward_address: RewardAddress(String::new()),
+            delegations,
+            voting_purpose: Some(0),
+            nonce,
+        }
+    }
+
+    fn process(raw: RawSnapshot) -> Result<Snapshot, crate::Error> {
+        Snapshot::from_raw_snapshot(
+            raw,
+            0.into(),
+            &VotingPowerCap::none(),
+            &|vk: &Identifier| {
+                if *vk == voting_key(2) {
+                    "rep".to_string()
+                } else {
+                    "direct".to_string()
+                }
+            },
+            Discrimination::Production,
+            false,
+        )
+    }
+
+    #[test]
+    fn diff() {
+        let raw_before: RawSnapshot = vec![
+            registration("stake_a", 100, 1, Delegations::Legacy(voting_key(0))),
+            registration("stake_b", 50, 1, Delegations::Legacy(voting_key(1))),
+        ]
+        .into();
+        let raw_after: RawSnapshot = vec![
+            registration("stake_a", 100, 1, Delegations::Legacy(voting_key(0))),
+            registration("stake_b", 50, 1, Delegations::Legacy(voting_key(1))),
+            registration(
+                "stake_b",
+                60,
+                5,
+                Delegations::New(vec![(voting_key(1), 1), (voting_key(2), 1)]),
+            ),
+        ]
+        .into();
+
+        let diff = SnapshotDiff::new(&raw_before, &raw_after, process).unwrap();
+
+        // only the latest registration of stake_b counts, its 60 split between
+        // its former voting key and the representative
+        assert_eq!(
+            diff.voters
+                .iter()
+                .map(|voter| (
+                    voter.change,
+                    voter.voting_power_before,
+                    voter.voting_power_after
+                ))
+                .collect::<Vec<_>>(),
+            vec![(VoterChange::Changed, 50, 30), (VoterChange::Added, 0, 30)]
+        );
+        assert_eq!(
+            diff.voters
+                .iter()
+                .map(|voter| voter.voting_power_delta)
+                .collect::<Vec<_>>(),
+            vec![-20, 30]
+        );
+        assert_eq!(
+            diff.superseded_registrations,
+            vec![SupersededRegistration {
+                stake_public_key: "stake_b".to_string(),
+                superseded_nonce: 1,
+                nonce: 5,
+            }]
+        );
+        assert_eq!(diff.delegation_shifts.len(), 2);
+        assert_eq!(
+            diff.voting_groups
+                .iter()
+                .map(|group| (group.voting_group.as_str(), group.voting_power_delta))
+                .collect::<Vec<_>>(),
+            vec![("direct", -20), ("rep", 30)]
+        );
+    }
+}
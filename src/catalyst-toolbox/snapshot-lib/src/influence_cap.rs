@@ -189,6 +189,7 @@ mod tests {
                 .prop_map(|hir| Self {
                     contributions: Vec::new(),
                     hir,
+                    voting_power_transform: None,
                 })
                 .boxed()
         }
@@ -2,27 +2,30 @@ use chain_addr::Discrimination;
 pub use fraction::Fraction;
 use jormungandr_lib::{crypto::account::Identifier, interfaces::Value};
 use registration::{
-    serde_impl::IdentifierDef, Delegations, RewardAddress, StakeAddress, VotingRegistration,
+    serde_impl::IdentifierDef, Delegations, Deregistration, RewardAddress, StakeAddress,
+    VotingRegistration,
 };
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::cmp;
 use std::{
     borrow::Borrow,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     iter::Iterator,
     num::NonZeroU64,
 };
 use thiserror::Error;
 pub use voter_hir::VoterHIR;
 pub use voter_hir::VotingGroup;
-use voting_group::VotingGroupAssigner;
+use voting_group::{DelegationType, VoterInfo, VotingGroupAssigner};
+use voting_power::{AppliedVotingPowerTransform, VotingPowerTransform};
 
 // Wow, this is crazy complex for what it needs to do.
 // mod influence_cap;
+pub mod diff;
 pub mod registration;
 pub mod sve;
 mod voter_hir;
 pub mod voting_group;
+pub mod voting_power;
 
 pub const CATALYST_VOTING_PURPOSE_TAG: u64 = 0;
 
@@ -35,6 +38,31 @@ impl From<Vec<VotingRegistration>> for RawSnapshot {
     }
 }
 
+impl RawSnapshot {
+    /// Drop the registrations cancelled by a deregistration of their stake key.
+    ///
+    /// Returns the number of registrations dropped.
+    pub fn drop_deregistered(&mut self, deregistrations: &[Deregistration]) -> usize {
+        let mut by_stake_key: HashMap<&str, Vec<&Deregistration>> = HashMap::new();
+        for dereg in deregistrations {
+            by_stake_key
+                .entry(dereg.stake_public_key.as_str())
+                .or_default()
+                .push(dereg);
+        }
+
+        let before = self.0.len();
+        self.0.retain(|reg| {
+            !by_stake_key
+                .get(reg.stake_public_key.as_str())
+                .map_or(false, |deregs| {
+                    deregs.iter().any(|dereg| dereg.cancels(reg))
+                })
+        });
+        before - self.0.len()
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Dreps {
     #[serde(
@@ -76,6 +104,10 @@ pub enum Error {
     NotEnoughVoters,
     #[error("voting power overflow")]
     Overflow,
+    #[error("voting power tiers must start from 0 and be sorted by increasing stake")]
+    InvalidVotingPowerTiers,
+    #[error("invalid voting power transform {0:?}, expected cap(<fraction>), quadratic, logarithmic(<scale>) or tiered(<from>:<weight>,...)")]
+    InvalidVotingPowerTransform(String),
 }
 
 /// Contribution to a voting key for some registration
@@ -104,6 +136,9 @@ pub struct SnapshotInfo {
     /// VoterHIR, due to voting power caps or additional transformations.
     pub contributions: Vec<KeyContribution>,
     pub hir: VoterHIR,
+    /// Transformation applied to the voting power of the `hir`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voting_power_transform: Option<AppliedVotingPowerTransform>,
 }
 
 impl SnapshotInfo {
@@ -114,13 +149,18 @@ impl SnapshotInfo {
             .map(|c| c.to_loadtest_snapshot())
             .collect();
         let hir = self.hir.to_loadtest_snapshot();
-        Self { contributions, hir }
+        Self {
+            contributions,
+            hir,
+            voting_power_transform: self.voting_power_transform.clone(),
+        }
     }
 
     pub fn cap_voting_power(&self, cap: u64) -> Self {
         Self {
             contributions: self.contributions.clone(),
             hir: self.hir.cap_voting_power(cap),
+            voting_power_transform: self.voting_power_transform.clone(),
         }
     }
 }
@@ -131,6 +171,8 @@ pub struct Snapshot {
     // a CIP-36 compatible encoding
     inner: BTreeMap<Identifier, SnapshotInfo>,
     pub stake_threshold: Value,
+    /// Maximum voting power of a voter, `u64::MAX` if the voting power
+    /// transformation does not cap it.
     pub voting_power_cap: u64,
 
     pub total_registered_voters: u64,
@@ -139,36 +181,50 @@ pub struct Snapshot {
     pub total_eligible_voting_power: u128,
 }
 
-fn calculate_voting_power_cap(cap: Fraction, total_eligible_voting_power: u128) -> u64 {
-    let numerator = *cap.numer().expect("Numerator must be set.") as u128;
-    let denominator = *cap.denom().expect("Denominator must be set.") as u128;
-
-    cmp::min(
-        total_eligible_voting_power
-            .saturating_mul(numerator)
-            .saturating_div(denominator),
-        u64::MAX as u128,
-    ) as u64
+/// Voters assigned to a voting group
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VotingGroupSummary {
+    /// Number of eligible voters
+    pub voters: u64,
+    /// Total voting power of the eligible voters
+    pub voting_power: u128,
+    /// Number of voters under the stake threshold
+    pub underthreshold_voters: u64,
 }
 
-fn cap_voting_power(
-    cap: Fraction,
-    total_eligible_voting_power: u128,
-    entries: Vec<SnapshotInfo>,
-) -> (BTreeMap<Identifier, SnapshotInfo>, u64) {
-    let voting_cap = calculate_voting_power_cap(cap, total_eligible_voting_power);
+fn is_catalyst_registration(reg: &VotingRegistration) -> bool {
+    reg.voting_purpose.unwrap_or(CATALYST_VOTING_PURPOSE_TAG) == CATALYST_VOTING_PURPOSE_TAG
+}
 
-    // Cap all voting power.
-    (
-        entries
-            .iter()
-            .map(|entry| {
-                let capped_entry = entry.cap_voting_power(voting_cap);
-                (capped_entry.hir.voting_key.clone(), capped_entry)
-            })
-            .collect(),
-        voting_cap,
-    )
+/// Kind of the registrations delegating to each voting key, and the nonce (slot number)
+/// of the oldest of them.
+fn collect_registrations_info(
+    raw_snapshot: &RawSnapshot,
+) -> BTreeMap<Identifier, (DelegationType, u64)> {
+    let mut registrations = BTreeMap::new();
+    for reg in raw_snapshot
+        .0
+        .iter()
+        .filter(|reg| is_catalyst_registration(reg))
+    {
+        let (delegation_type, vks) = match &reg.delegations {
+            Delegations::Legacy(vk) => (DelegationType::Legacy, vec![vk]),
+            Delegations::New(vks) => (
+                DelegationType::Cip36,
+                vks.iter().map(|(vk, _)| vk).collect(),
+            ),
+        };
+        for vk in vks {
+            registrations
+                .entry(vk.clone())
+                .and_modify(|(types, slot): &mut (DelegationType, u64)| {
+                    *types = types.merge(delegation_type);
+                    *slot = (*slot).min(reg.nonce);
+                })
+                .or_insert((delegation_type, reg.nonce));
+        }
+    }
+    registrations
 }
 
 fn collect_raw_contributions(
@@ -178,9 +234,7 @@ fn collect_raw_contributions(
         .0
         .into_iter()
         // Only accept Catalyst Voting Purpose.
-        .filter(|reg| {
-            reg.voting_purpose.unwrap_or(CATALYST_VOTING_PURPOSE_TAG) == CATALYST_VOTING_PURPOSE_TAG
-        })
+        .filter(is_catalyst_registration)
         .fold(BTreeMap::new(), |mut acc: BTreeMap<_, Vec<_>>, reg| {
             let VotingRegistration {
                 reward_address,
@@ -230,11 +284,15 @@ fn collect_raw_contributions(
 }
 
 impl Snapshot {
+    /// Process the registrations of the raw snapshot.
+    ///
+    /// The voting power of the eligible voters is transformed with `voting_power_transform`
+    /// before they are assigned to their voting groups.
     #[allow(clippy::missing_errors_doc)]
     pub fn from_raw_snapshot(
         raw_snapshot: RawSnapshot,
         stake_threshold: Value,
-        cap: Fraction,
+        voting_power_transform: &impl VotingPowerTransform,
         voting_group_assigner: &impl VotingGroupAssigner,
         discrimination: Discrimination,
         loadtest: bool,
@@ -244,11 +302,12 @@ impl Snapshot {
         let mut total_eligible_voters: u64 = 0;
         let mut total_eligible_voting_power: u128 = 0;
 
+        let registrations = collect_registrations_info(&raw_snapshot);
         let raw_contribs = collect_raw_contributions(raw_snapshot);
 
-        let entries: Vec<SnapshotInfo> = raw_contribs
+        let voters: Vec<(Identifier, Vec<KeyContribution>, Value, bool)> = raw_contribs
             .into_iter()
-            .flat_map(|(k, contributions)| {
+            .map(|(k, contributions)| {
                 let voting_power: Value = contributions.iter().map(|c| c.value).sum::<u64>().into();
                 let underthreshold = voting_power < stake_threshold;
 
@@ -273,21 +332,48 @@ impl Snapshot {
                     }
                 }
 
+                (k, contributions, voting_power, underthreshold)
+            })
+            .collect();
+
+        // The voting power is transformed once the total eligible voting power is known.
+        let transform_name = voting_power_transform.name();
+        let voting_power_cap = voting_power_transform.voting_power_cap(total_eligible_voting_power);
+
+        let entries: BTreeMap<Identifier, SnapshotInfo> = voters
+            .into_iter()
+            .flat_map(|(k, contributions, voting_power, underthreshold)| {
+                let transformed = voting_power_transform
+                    .transform(voting_power.as_u64(), total_eligible_voting_power);
+                let overlimit = voting_power_cap.map_or(false, |cap| voting_power.as_u64() > cap);
+
+                let (delegation_type, registration_slot) = registrations[&k];
+                let voting_group = voting_group_assigner.assign(&VoterInfo {
+                    voting_key: &k,
+                    stake: voting_power,
+                    delegation_type,
+                    registration_slot,
+                });
+
                 let snapshot_info = SnapshotInfo {
                     hir: VoterHIR {
-                        voting_group: voting_group_assigner.assign(&k),
+                        voting_group,
                         voting_key: k.clone(),
                         address: chain_addr::Address(
                             discrimination,
                             chain_addr::Kind::Account(k.to_inner().into()),
                         )
                         .into(),
-                        voting_power,
+                        voting_power: transformed.into(),
                         underthreshold,
-                        overlimit: false,  // Don't know yet, so assume not.
+                        overlimit,
                         private_key: None, // Normal snapshot info can't have a private key.
                     },
                     contributions,
+                    voting_power_transform: Some(AppliedVotingPowerTransform {
+                        name: transform_name.clone(),
+                        original_voting_power: voting_power,
+                    }),
                 };
 
                 if loadtest {
@@ -300,16 +386,13 @@ impl Snapshot {
                     vec![snapshot_info]
                 }
             })
+            .map(|entry| (entry.hir.voting_key.clone(), entry))
             .collect();
 
-        // Cap the voting power of all entries.
-        let (capped_entries, voting_power_cap) =
-            cap_voting_power(cap, total_eligible_voting_power, entries);
-
         Ok(Self {
-            inner: capped_entries,
+            inner: entries,
             stake_threshold,
-            voting_power_cap,
+            voting_power_cap: voting_power_cap.unwrap_or(u64::MAX),
             total_registered_voters,
             total_registered_voting_power,
             total_eligible_voters,
@@ -317,6 +400,22 @@ impl Snapshot {
         })
     }
 
+    /// Number of voters and voting power assigned to each voting group
+    #[must_use]
+    pub fn voting_groups_summary(&self) -> BTreeMap<VotingGroup, VotingGroupSummary> {
+        let mut summary = BTreeMap::<VotingGroup, VotingGroupSummary>::new();
+        for entry in self.inner.values() {
+            let group = summary.entry(entry.hir.voting_group.clone()).or_default();
+            if entry.hir.underthreshold {
+                group.underthreshold_voters += 1;
+            } else {
+                group.voters += 1;
+                group.voting_power += entry.hir.voting_power.as_u64() as u128;
+            }
+        }
+        summary
+    }
+
     #[must_use]
     pub fn stake_threshold(&self) -> Value {
         self.stake_threshold
@@ -355,6 +454,7 @@ impl Snapshot {
 #[cfg(any(test, feature = "proptest"))]
 pub mod tests {
     use super::*;
+    use crate::voting_power::{QuadraticVotingPower, VotingPowerCap};
     use chain_addr::{Discrimination, Kind};
     use jormungandr_lib::interfaces::{Address, InitialUTxO};
     use proptest::prelude::*;
@@ -363,7 +463,7 @@ pub mod tests {
     struct DummyAssigner;
 
     impl VotingGroupAssigner for DummyAssigner {
-        fn assign(&self, _vk: &Identifier) -> String {
+        fn assign(&self, _voter: &VoterInfo) -> String {
             String::new()
         }
     }
@@ -406,7 +506,7 @@ pub mod tests {
             Snapshot::from_raw_snapshot(
                 _raw,
                 _stake_threshold.into(),
-                Fraction::from(1u64),
+                &VotingPowerCap::none(),
                 &DummyAssigner,
                 Discrimination::Production,
                 false
@@ -415,7 +515,7 @@ pub mod tests {
                 == Snapshot::from_raw_snapshot(
                     add,
                     _stake_threshold.into(),
-                    Fraction::from(1u64),
+                    &VotingPowerCap::none(),
                     &DummyAssigner,
                     Discrimination::Production,
                     false
@@ -436,7 +536,7 @@ pub mod tests {
                     Self::from_raw_snapshot(
                         raw_snapshot,
                         threshold.into(),
-                        Fraction::from(1),
+                        &VotingPowerCap::none(),
                         &|_vk: &Identifier| String::new(),
                         Discrimination::Production,
                         false,
@@ -454,7 +554,7 @@ pub mod tests {
         let snapshot = Snapshot::from_raw_snapshot(
             vec![_reg.clone()].into(),
             0.into(),
-            Fraction::from(1),
+            &VotingPowerCap::none(),
             &|_vk: &Identifier| String::new(),
             Discrimination::Production,
         )
@@ -476,7 +576,7 @@ pub mod tests {
             Snapshot::from_raw_snapshot(
                 vec![_reg].into(),
                 0.into(),
-                Fraction::from(1u64),
+                &VotingPowerCap::none(),
                 &DummyAssigner,
                 Discrimination::Production,
             )
@@ -484,7 +584,7 @@ pub mod tests {
             Snapshot::from_raw_snapshot(
                 vec![].into(),
                 0.into(),
-                Fraction::from(1u64),
+                &VotingPowerCap::none(),
                 &DummyAssigner,
                 Discrimination::Production,
             )
@@ -518,7 +618,7 @@ pub mod tests {
         let snapshot = Snapshot::from_raw_snapshot(
             raw_snapshot.into(),
             0.into(),
-            Fraction::from(1u64),
+            &VotingPowerCap::none(),
             &DummyAssigner,
             Discrimination::Production,
             false,
@@ -538,6 +638,78 @@ pub mod tests {
         assert_eq!(vp_2 - vp_1, n / 2); // last key get the remainder during distribution
     }
 
+    #[test]
+    fn test_voting_power_transform_is_recorded() {
+        let voting_key = Identifier::from_hex(&hex::encode([0; 32])).unwrap();
+        let raw_snapshot = vec![VotingRegistration {
+            stake_public_key: StakeAddress(String::new()),
+            voting_power: 100.into(),
+            reward_address: RewardAddress(String::new()),
+            delegations: Delegations::Legacy(voting_key.clone()),
+            voting_purpose: Some(0),
+            nonce: 0,
+        }];
+
+        let snapshot = Snapshot::from_raw_snapshot(
+            raw_snapshot.into(),
+            0.into(),
+            &QuadraticVotingPower,
+            &DummyAssigner,
+            Discrimination::Production,
+            false,
+        )
+        .unwrap();
+        let entry = &snapshot.to_full_snapshot_info()[0];
+        assert_eq!(entry.hir.voting_power, 10.into());
+        assert!(!entry.hir.overlimit);
+        assert_eq!(
+            entry.voting_power_transform,
+            Some(AppliedVotingPowerTransform {
+                name: "quadratic".to_string(),
+                original_voting_power: 100.into(),
+            })
+        );
+        assert_eq!(snapshot.voting_power_cap, u64::MAX);
+    }
+
+    #[test]
+    fn test_deregistered_registrations_are_dropped() {
+        let voting_key = Identifier::from_hex(&hex::encode([0; 32])).unwrap();
+        let registration =
+            |stake: &str, nonce: u64, voting_purpose: Option<u64>| VotingRegistration {
+                stake_public_key: StakeAddress(stake.to_string()),
+                voting_power: 100.into(),
+                reward_address: RewardAddress(String::new()),
+                delegations: Delegations::Legacy(voting_key.clone()),
+                voting_purpose,
+                nonce,
+            };
+        let mut raw_snapshot = RawSnapshot::from(vec![
+            registration("0x01", 10, None),
+            // registered again after deregistering
+            registration("0x02", 30, Some(0)),
+            // deregistration is for another voting purpose
+            registration("0x03", 10, Some(1)),
+            registration("0x04", 10, Some(0)),
+        ]);
+        let deregistrations: Vec<Deregistration> = serde_json::from_str(
+            r#"[
+            { "stake_public_key": "0x01", "voting_purpose": 0, "tx_id": 1, "nonce": 20, "slot": 20 },
+            { "stake_public_key": "0x02", "voting_purpose": 0, "tx_id": 2, "nonce": 20, "slot": 20 },
+            { "stake_public_key": "0x03", "voting_purpose": 0, "tx_id": 3, "nonce": 20, "slot": 20 }
+        ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(raw_snapshot.drop_deregistered(&deregistrations), 1);
+        let remaining: Vec<_> = raw_snapshot
+            .0
+            .iter()
+            .map(|reg| reg.stake_public_key.as_str())
+            .collect();
+        assert_eq!(remaining, vec!["0x02", "0x03", "0x04"]);
+    }
+
     #[test]
     fn test_raw_snapshot_parsing() {
         let raw: RawSnapshot = serde_json::from_str(
@@ -556,7 +728,7 @@ pub mod tests {
         let snapshot = Snapshot::from_raw_snapshot(
             raw,
             0.into(),
-            Fraction::from(1u64),
+            &VotingPowerCap::none(),
             &DummyAssigner,
             Discrimination::Production,
             false,
@@ -87,6 +87,35 @@ impl VotingRegistration {
     }
 }
 
+/// A CIP-36 deregistration of a stake key, as reported by the snapshot tool.
+///
+/// Cancels the registrations of the stake key for the same voting purpose
+/// with a nonce not greater than its own.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Deregistration {
+    pub stake_public_key: StakeAddress,
+    /// 0 = Catalyst, assumed 0 if missing
+    #[serde(default)]
+    pub voting_purpose: Option<u64>,
+
+    #[serde(default)]
+    pub nonce: u64,
+}
+
+impl Deregistration {
+    #[must_use]
+    pub fn cancels(&self, registration: &VotingRegistration) -> bool {
+        self.stake_public_key == registration.stake_public_key
+            && self
+                .voting_purpose
+                .unwrap_or(crate::CATALYST_VOTING_PURPOSE_TAG)
+                == registration
+                    .voting_purpose
+                    .unwrap_or(crate::CATALYST_VOTING_PURPOSE_TAG)
+            && registration.nonce <= self.nonce
+    }
+}
+
 /// To allow backward compatibility and avoid requiring existing users to
 /// re-register we still consider valid old CIP-15 registrations, with the
 /// simple correspondence between the two described in CIP-36.
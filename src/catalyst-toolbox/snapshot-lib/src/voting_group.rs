@@ -1,12 +1,49 @@
-use crate::{Dreps, VotingGroup};
+use crate::{deserialize_identifiers, serialize_identifiers, Dreps, VotingGroup};
 use jormungandr_lib::crypto::account::Identifier;
+use jormungandr_lib::interfaces::Value;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 pub const DEFAULT_DIRECT_VOTER_GROUP: &str = "direct";
 pub const DEFAULT_REPRESENTATIVE_GROUP: &str = "rep";
 
+/// What is known about a voter when assigning it to a voting group
+#[derive(Clone, Debug)]
+pub struct VoterInfo<'a> {
+    pub voting_key: &'a Identifier,
+    /// Stake delegated to the voter, before any voting power transformation
+    pub stake: Value,
+    /// Kind of the registrations delegating to the voter
+    pub delegation_type: DelegationType,
+    /// Nonce (slot number) of the oldest registration delegating to the voter
+    pub registration_slot: u64,
+}
+
+/// Kind of the registrations delegating to a voter
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DelegationType {
+    /// Legacy CIP-15 registrations only
+    Legacy,
+    /// CIP-36 registrations only
+    Cip36,
+    /// Both legacy and CIP-36 registrations
+    Mixed,
+}
+
+impl DelegationType {
+    #[must_use]
+    pub fn merge(self, other: Self) -> Self {
+        if self == other {
+            self
+        } else {
+            Self::Mixed
+        }
+    }
+}
+
 pub trait VotingGroupAssigner {
-    fn assign(&self, vk: &Identifier) -> VotingGroup;
+    fn assign(&self, voter: &VoterInfo) -> VotingGroup;
 }
 
 pub struct RepsVotersAssigner {
@@ -26,8 +63,8 @@ impl RepsVotersAssigner {
 }
 
 impl VotingGroupAssigner for RepsVotersAssigner {
-    fn assign(&self, vk: &Identifier) -> VotingGroup {
-        if self.dreps.contains(vk) {
+    fn assign(&self, voter: &VoterInfo) -> VotingGroup {
+        if self.dreps.contains(voter.voting_key) {
             self.reps.clone()
         } else {
             self.direct_voters.clone()
@@ -35,12 +72,157 @@ impl VotingGroupAssigner for RepsVotersAssigner {
     }
 }
 
+/// Declarative voting group assignment, usually loaded from a rule file.
+///
+/// The voters are assigned to the group of the first rule they match, or
+/// to the default group if they do not match any of them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VotingGroupRules {
+    pub default_group: VotingGroup,
+    #[serde(default)]
+    pub rules: Vec<VotingGroupRule>,
+}
+
+/// A voter matches a rule if it satisfies all of its conditions,
+/// a rule without conditions matches every voter.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VotingGroupRule {
+    pub group: VotingGroup,
+    /// Minimum stake of the voter, inclusive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_stake: Option<u64>,
+    /// Maximum stake of the voter, exclusive so that consecutive bands do not overlap
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_stake: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delegation_type: Option<DelegationType>,
+    /// The oldest registration of the voter must be from this slot or later
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registered_since_slot: Option<u64>,
+    /// The oldest registration of the voter must be from before this slot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registered_before_slot: Option<u64>,
+    /// Allow-list of voting keys, ignored if empty
+    #[serde(
+        default,
+        skip_serializing_if = "HashSet::is_empty",
+        serialize_with = "serialize_identifiers",
+        deserialize_with = "deserialize_identifiers"
+    )]
+    pub voting_keys: HashSet<Identifier>,
+}
+
+impl VotingGroupRule {
+    #[must_use]
+    pub fn matches(&self, voter: &VoterInfo) -> bool {
+        let stake = voter.stake.as_u64();
+        self.min_stake.map_or(true, |min| stake >= min)
+            && self.max_stake.map_or(true, |max| stake < max)
+            && self.delegation_type.map_or(true, |delegation_type| {
+                delegation_type == voter.delegation_type
+            })
+            && self
+                .registered_since_slot
+                .map_or(true, |slot| voter.registration_slot >= slot)
+            && self
+                .registered_before_slot
+                .map_or(true, |slot| voter.registration_slot < slot)
+            && (self.voting_keys.is_empty() || self.voting_keys.contains(voter.voting_key))
+    }
+}
+
+impl VotingGroupAssigner for VotingGroupRules {
+    fn assign(&self, voter: &VoterInfo) -> VotingGroup {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(voter))
+            .map_or(&self.default_group, |rule| &rule.group)
+            .clone()
+    }
+}
+
 #[cfg(any(test, feature = "test-api", feature = "proptest"))]
 impl<F> VotingGroupAssigner for F
 where
     F: Fn(&Identifier) -> VotingGroup,
 {
-    fn assign(&self, vk: &Identifier) -> VotingGroup {
-        self(vk)
+    fn assign(&self, voter: &VoterInfo) -> VotingGroup {
+        self(voter.voting_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voter(voting_key: &Identifier, stake: u64, delegation_type: DelegationType) -> VoterInfo {
+        VoterInfo {
+            voting_key,
+            stake: stake.into(),
+            delegation_type,
+            registration_slot: 100,
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rep = Identifier::from_hex(&hex::encode([0; 32])).unwrap();
+        let other = Identifier::from_hex(&hex::encode([1; 32])).unwrap();
+        let rules: VotingGroupRules = serde_json::from_value(serde_json::json!({
+            "default_group": "direct",
+            "rules": [
+                {
+                    "group": "rep",
+                    "voting_keys": [format!("0x{}", rep.to_hex())],
+                },
+                {
+                    "group": "legacy",
+                    "delegation_type": "legacy",
+                },
+                {
+                    "group": "whale",
+                    "min_stake": 1000,
+                    "registered_before_slot": 200,
+                },
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            rules.assign(&voter(&rep, 5000, DelegationType::Legacy)),
+            "rep"
+        );
+        assert_eq!(
+            rules.assign(&voter(&other, 5000, DelegationType::Legacy)),
+            "legacy"
+        );
+        assert_eq!(
+            rules.assign(&voter(&other, 5000, DelegationType::Mixed)),
+            "whale"
+        );
+        assert_eq!(
+            rules.assign(&voter(&other, 999, DelegationType::Cip36)),
+            "direct"
+        );
+        assert_eq!(
+            rules.assign(&VoterInfo {
+                registration_slot: 200,
+                ..voter(&other, 5000, DelegationType::Cip36)
+            }),
+            "direct"
+        );
+    }
+
+    #[test]
+    fn unknown_conditions_are_rejected() {
+        assert!(
+            serde_json::from_value::<VotingGroupRules>(serde_json::json!({
+                "default_group": "direct",
+                "rules": [{ "group": "rep", "stake_above": 10 }],
+            }))
+            .is_err()
+        );
     }
 }
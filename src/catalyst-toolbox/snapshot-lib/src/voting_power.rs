@@ -0,0 +1,384 @@
+use crate::{Error, Fraction};
+use jormungandr_lib::interfaces::Value;
+use serde::{Deserialize, Serialize};
+use std::{cmp, str::FromStr};
+
+/// Transformation of the voting power of the voters, applied when processing a snapshot
+/// before the voters are assigned to their voting groups.
+pub trait VotingPowerTransform {
+    /// Description of the transformation, recorded in the snapshot entries
+    fn name(&self) -> String;
+
+    /// Transform the voting power of a voter. `total_voting_power` is the sum
+    /// of the voting power of all the eligible voters, before any transformation.
+    fn transform(&self, voting_power: u64, total_voting_power: u128) -> u64;
+
+    /// Maximum voting power a voter can get after the transformation, if any.
+    /// The voters whose voting power exceeded it are flagged as overlimit.
+    fn voting_power_cap(&self, _total_voting_power: u128) -> Option<u64> {
+        None
+    }
+}
+
+/// Record of the transformation applied to the voting power of a snapshot entry
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedVotingPowerTransform {
+    /// Name of the transformation, see [`VotingPowerTransform::name`]
+    pub name: String,
+    /// Voting power before the transformation
+    pub original_voting_power: Value,
+}
+
+/// Caps the voting power of each voter to a fraction of the total voting power
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VotingPowerCap(Fraction);
+
+impl VotingPowerCap {
+    pub fn new(cap: Fraction) -> Self {
+        Self(cap)
+    }
+
+    /// A cap of 1 leaves the voting power untouched
+    pub fn none() -> Self {
+        Self(Fraction::from(1u64))
+    }
+
+    /// The fraction of the total voting power a voter can get at most
+    pub fn fraction(&self) -> Fraction {
+        self.0
+    }
+}
+
+impl VotingPowerTransform for VotingPowerCap {
+    fn name(&self) -> String {
+        format!("cap({})", self.0)
+    }
+
+    fn transform(&self, voting_power: u64, total_voting_power: u128) -> u64 {
+        cmp::min(
+            voting_power,
+            calculate_voting_power_cap(self.0, total_voting_power),
+        )
+    }
+
+    fn voting_power_cap(&self, total_voting_power: u128) -> Option<u64> {
+        Some(calculate_voting_power_cap(self.0, total_voting_power))
+    }
+}
+
+fn calculate_voting_power_cap(cap: Fraction, total_eligible_voting_power: u128) -> u64 {
+    let numerator = *cap.numer().expect("Numerator must be set.") as u128;
+    let denominator = *cap.denom().expect("Denominator must be set.") as u128;
+
+    cmp::min(
+        total_eligible_voting_power
+            .saturating_mul(numerator)
+            .saturating_div(denominator),
+        u64::MAX as u128,
+    ) as u64
+}
+
+/// Quadratic voting: the voting power is the square root of the stake
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuadraticVotingPower;
+
+impl VotingPowerTransform for QuadraticVotingPower {
+    fn name(&self) -> String {
+        "quadratic".to_string()
+    }
+
+    fn transform(&self, voting_power: u64, _total_voting_power: u128) -> u64 {
+        integer_sqrt(voting_power)
+    }
+}
+
+/// Largest integer whose square is not greater than `n`
+fn integer_sqrt(n: u64) -> u64 {
+    // the floating point estimate can be off by one for large values
+    let mut root = (n as f64).sqrt() as u64;
+    while root.checked_mul(root).map_or(true, |square| square > n) {
+        root -= 1;
+    }
+    while (root + 1)
+        .checked_mul(root + 1)
+        .map_or(false, |square| square <= n)
+    {
+        root += 1;
+    }
+    root
+}
+
+/// Logarithmic voting: the voting power is `scale * log2(1 + stake)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LogarithmicVotingPower {
+    scale: u64,
+}
+
+impl LogarithmicVotingPower {
+    /// The logarithm of the stake is small, the scale keeps enough precision
+    /// in the resulting voting power to tell voters apart.
+    pub fn new(scale: u64) -> Self {
+        Self { scale }
+    }
+}
+
+impl VotingPowerTransform for LogarithmicVotingPower {
+    fn name(&self) -> String {
+        format!("logarithmic({})", self.scale)
+    }
+
+    fn transform(&self, voting_power: u64, _total_voting_power: u128) -> u64 {
+        (self.scale as f64 * (voting_power as f64 + 1.0).log2()) as u64
+    }
+}
+
+/// A tier of [`TieredVotingPower`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VotingPowerTier {
+    /// stake from which this tier applies
+    pub from: u64,
+    /// weight of the stake within this tier
+    pub weight: Fraction,
+}
+
+/// Tiered voting: the stake is split in brackets, and the stake
+/// within each bracket is counted with the weight of its tier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TieredVotingPower {
+    tiers: Vec<VotingPowerTier>,
+}
+
+impl TieredVotingPower {
+    /// The tiers must be sorted by increasing stake, starting from 0.
+    pub fn new(tiers: Vec<VotingPowerTier>) -> Result<Self, Error> {
+        let starts_at_zero = tiers.first().map_or(false, |tier| tier.from == 0);
+        let sorted = tiers.windows(2).all(|pair| pair[0].from < pair[1].from);
+        let valid_weights = tiers
+            .iter()
+            .all(|tier| tier.weight.numer().is_some() && tier.weight.denom().is_some());
+        if !starts_at_zero || !sorted || !valid_weights {
+            return Err(Error::InvalidVotingPowerTiers);
+        }
+        Ok(Self { tiers })
+    }
+}
+
+impl VotingPowerTransform for TieredVotingPower {
+    fn name(&self) -> String {
+        let tiers = self
+            .tiers
+            .iter()
+            .map(|tier| format!("{}:{}", tier.from, tier.weight))
+            .collect::<Vec<_>>();
+        format!("tiered({})", tiers.join(","))
+    }
+
+    fn transform(&self, voting_power: u64, _total_voting_power: u128) -> u64 {
+        let ends = self
+            .tiers
+            .iter()
+            .skip(1)
+            .map(|tier| tier.from)
+            .chain(std::iter::once(u64::MAX));
+        let weighted: u128 = self
+            .tiers
+            .iter()
+            .zip(ends)
+            .take_while(|(tier, _)| tier.from < voting_power)
+            .map(|(tier, end)| {
+                let stake = (cmp::min(voting_power, end) - tier.from) as u128;
+                let numerator = *tier.weight.numer().unwrap() as u128;
+                let denominator = *tier.weight.denom().unwrap() as u128;
+                stake.saturating_mul(numerator) / denominator
+            })
+            .sum();
+        cmp::min(weighted, u64::MAX as u128) as u64
+    }
+}
+
+/// Any of the voting power transformations, parsed from the names they are recorded with:
+/// `cap(<fraction>)`, `quadratic`, `logarithmic(<scale>)` or `tiered(<from>:<weight>,...)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnyVotingPowerTransform {
+    Cap(VotingPowerCap),
+    Quadratic(QuadraticVotingPower),
+    Logarithmic(LogarithmicVotingPower),
+    Tiered(TieredVotingPower),
+}
+
+impl VotingPowerTransform for AnyVotingPowerTransform {
+    fn name(&self) -> String {
+        match self {
+            Self::Cap(transform) => transform.name(),
+            Self::Quadratic(transform) => transform.name(),
+            Self::Logarithmic(transform) => transform.name(),
+            Self::Tiered(transform) => transform.name(),
+        }
+    }
+
+    fn transform(&self, voting_power: u64, total_voting_power: u128) -> u64 {
+        match self {
+            Self::Cap(transform) => transform.transform(voting_power, total_voting_power),
+            Self::Quadratic(transform) => transform.transform(voting_power, total_voting_power),
+            Self::Logarithmic(transform) => transform.transform(voting_power, total_voting_power),
+            Self::Tiered(transform) => transform.transform(voting_power, total_voting_power),
+        }
+    }
+
+    fn voting_power_cap(&self, total_voting_power: u128) -> Option<u64> {
+        match self {
+            Self::Cap(transform) => transform.voting_power_cap(total_voting_power),
+            Self::Quadratic(transform) => transform.voting_power_cap(total_voting_power),
+            Self::Logarithmic(transform) => transform.voting_power_cap(total_voting_power),
+            Self::Tiered(transform) => transform.voting_power_cap(total_voting_power),
+        }
+    }
+}
+
+impl FromStr for AnyVotingPowerTransform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidVotingPowerTransform(s.to_string());
+        let (name, args) = match s.trim().split_once('(') {
+            Some((name, args)) => (name, Some(args.strip_suffix(')').ok_or_else(invalid)?)),
+            None => (s.trim(), None),
+        };
+        match (name, args) {
+            ("cap", Some(cap)) => parse_fraction(cap)
+                .map(|cap| Self::Cap(VotingPowerCap::new(cap)))
+                .ok_or_else(invalid),
+            ("quadratic", None) => Ok(Self::Quadratic(QuadraticVotingPower)),
+            ("logarithmic", Some(scale)) => scale
+                .trim()
+                .parse()
+                .map(|scale| Self::Logarithmic(LogarithmicVotingPower::new(scale)))
+                .map_err(|_| invalid()),
+            ("tiered", Some(tiers)) => {
+                let tiers = tiers
+                    .split(',')
+                    .map(|tier| {
+                        let (from, weight) = tier.split_once(':')?;
+                        Some(VotingPowerTier {
+                            from: from.trim().parse().ok()?,
+                            weight: parse_fraction(weight)?,
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(invalid)?;
+                TieredVotingPower::new(tiers).map(Self::Tiered)
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Non negative fraction, written as a decimal or as `<numerator>/<denominator>`
+fn parse_fraction(s: &str) -> Option<Fraction> {
+    let s = s.trim();
+    // `Fraction::from_str` panics on a zero denominator
+    if let Some((_, denominator)) = s.split_once('/') {
+        if denominator.parse::<u64>().ok()? == 0 {
+            return None;
+        }
+    }
+    let fraction = Fraction::from_str(s).ok()?;
+    (fraction >= Fraction::from(0u64)).then_some(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cap() {
+        let cap = VotingPowerCap::new(Fraction::new(1u64, 10u64));
+        assert_eq!(cap.transform(5, 100), 5);
+        assert_eq!(cap.transform(50, 100), 10);
+        assert_eq!(cap.voting_power_cap(100), Some(10));
+        assert_eq!(VotingPowerCap::none().transform(50, 100), 50);
+        assert_eq!(cap.name(), "cap(1/10)");
+    }
+
+    #[test]
+    fn quadratic() {
+        let quadratic = QuadraticVotingPower;
+        assert_eq!(quadratic.transform(0, 0), 0);
+        assert_eq!(quadratic.transform(15, 0), 3);
+        assert_eq!(quadratic.transform(16, 0), 4);
+        assert_eq!(quadratic.transform(u64::MAX, 0), u32::MAX as u64);
+    }
+
+    #[test]
+    fn logarithmic() {
+        let logarithmic = LogarithmicVotingPower::new(1000);
+        assert_eq!(logarithmic.transform(0, 0), 0);
+        assert_eq!(logarithmic.transform(1, 0), 1000);
+        assert_eq!(logarithmic.transform(1023, 0), 10000);
+    }
+
+    #[test]
+    fn tiered() {
+        assert!(TieredVotingPower::new(vec![]).is_err());
+        assert!(TieredVotingPower::new(vec![VotingPowerTier {
+            from: 10,
+            weight: Fraction::from(1u64),
+        }])
+        .is_err());
+
+        let tiered = TieredVotingPower::new(vec![
+            VotingPowerTier {
+                from: 0,
+                weight: Fraction::from(1u64),
+            },
+            VotingPowerTier {
+                from: 100,
+                weight: Fraction::new(1u64, 2u64),
+            },
+            VotingPowerTier {
+                from: 1000,
+                weight: Fraction::from(0u64),
+            },
+        ])
+        .unwrap();
+        assert_eq!(tiered.transform(50, 0), 50);
+        assert_eq!(tiered.transform(100, 0), 100);
+        assert_eq!(tiered.transform(300, 0), 200);
+        assert_eq!(tiered.transform(5000, 0), 550);
+        assert_eq!(tiered.name(), "tiered(0:1,100:1/2,1000:0)");
+    }
+
+    #[test]
+    fn parse_transforms() {
+        for name in [
+            "cap(1/10)",
+            "quadratic",
+            "logarithmic(1000)",
+            "tiered(0:1,100:1/2,1000:0)",
+        ] {
+            let transform: AnyVotingPowerTransform = name.parse().unwrap();
+            assert_eq!(transform.name(), name);
+        }
+
+        let cap: AnyVotingPowerTransform = "cap(0.1)".parse().unwrap();
+        assert_eq!(
+            cap,
+            AnyVotingPowerTransform::Cap(VotingPowerCap::new(Fraction::new(1u64, 10u64)))
+        );
+        assert_eq!(cap.voting_power_cap(100), Some(10));
+
+        for invalid in [
+            "",
+            "linear",
+            "cap",
+            "cap(1/0)",
+            "cap(-1)",
+            "quadratic(2)",
+            "logarithmic(x)",
+            "tiered(10:1)",
+            "tiered(0:1,100)",
+        ] {
+            assert!(invalid.parse::<AnyVotingPowerTransform>().is_err());
+        }
+    }
+}
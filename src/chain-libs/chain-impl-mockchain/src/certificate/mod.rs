@@ -16,7 +16,7 @@ use crate::transaction::{Payload, PayloadData, PayloadSlice};
 pub use self::vote_cast::VoteCast;
 pub use self::vote_plan::{
     ExternalProposalDocument, ExternalProposalId, Proposal, Proposals, PushProposal, VoteAction,
-    VotePlan, VotePlanId, VotePlanProof,
+    VotePlan, VotePlanId, VotePlanProof, VOTE_PLAN_EXTENSION_TAG, VOTE_PLAN_EXTENSION_VERSION,
 };
 pub use self::vote_tally::{
     DecryptedPrivateTally, DecryptedPrivateTallyError, DecryptedPrivateTallyProposal, TallyProof,
@@ -10,7 +10,7 @@ use crate::{accounting::account::DelegationType, tokens::identifier::TokenIdenti
 use chain_core::{packer::Codec, property::DeserializeFromSlice};
 use chain_crypto::{testing, Ed25519};
 use chain_time::DurationSeconds;
-use chain_vote::{Crs, EncryptedTally};
+use chain_vote::{committee::dkg::MemberDkg, Crs, EncryptedTally};
 #[cfg(test)]
 use quickcheck::TestResult;
 use quickcheck::{Arbitrary, Gen};
@@ -202,23 +202,35 @@ impl Arbitrary for VotePlan {
         let proposals = Proposals::arbitrary(g);
         let payload_type = vote::PayloadType::arbitrary(g);
 
-        let mut keys = Vec::new();
         // it should have been 256 but is limited for the sake of adequate test times
         let keys_n = g.next_u32() % 15 + 1;
         let mut seed = [0u8; 32];
         g.fill_bytes(&mut seed);
         let mut rng = rand_chacha::ChaCha20Rng::from_seed(seed);
         let h = chain_vote::Crs::from_hash(&seed);
-        for _i in 0..keys_n {
-            let mc = chain_vote::MemberCommunicationKey::new(&mut rng);
-            let threshold = 1;
-            let m1 = chain_vote::MemberState::new(&mut rng, threshold, &h, &[mc.to_public()], 0);
-            keys.push(m1.public_key());
-        }
+        let committee_threshold = if bool::arbitrary(g) {
+            Some((g.next_u32() % keys_n + 1) as u8)
+        } else {
+            None
+        };
+        let keys = match committee_threshold {
+            Some(threshold) => {
+                committee_public_shares(&mut rng, &h, threshold as usize, keys_n as usize)
+            }
+            None => (0..keys_n)
+                .map(|_| {
+                    let mc = chain_vote::MemberCommunicationKey::new(&mut rng);
+                    let threshold = 1;
+                    let m1 =
+                        chain_vote::MemberState::new(&mut rng, threshold, &h, &[mc.to_public()], 0);
+                    m1.public_key()
+                })
+                .collect(),
+        };
 
         let voting_token = TokenIdentifier::arbitrary(g);
 
-        Self::new(
+        let vote_plan = Self::new(
             vote_start,
             vote_end,
             committee_end,
@@ -226,10 +238,43 @@ impl Arbitrary for VotePlan {
             payload_type,
             keys,
             voting_token,
-        )
+        );
+        match committee_threshold {
+            Some(threshold) => vote_plan.with_committee_threshold(threshold.try_into().unwrap()),
+            None => vote_plan,
+        }
     }
 }
 
+/// Public shares of a committee after the distributed key generation, which are
+/// the same for all the members so running it for the first one is enough.
+fn committee_public_shares(
+    rng: &mut rand_chacha::ChaCha20Rng,
+    crs: &Crs,
+    threshold: usize,
+    size: usize,
+) -> Vec<chain_vote::MemberPublicKey> {
+    let comm_keys: Vec<_> = (0..size)
+        .map(|_| chain_vote::MemberCommunicationKey::new(rng))
+        .collect();
+    let comm_pks: Vec<_> = comm_keys.iter().map(|key| key.to_public()).collect();
+    let states: Vec<_> = (0..size)
+        .map(|index| chain_vote::MemberState::new(rng, threshold, crs, &comm_pks, index))
+        .collect();
+    let mut dkg = MemberDkg::new(&states[0], comm_keys[0].clone(), crs, size);
+    let sharings: Vec<_> = states
+        .iter()
+        .map(|state| state.sharing_broadcast())
+        .collect();
+    let coefficients: Vec<_> = states
+        .iter()
+        .map(|state| state.coefficients_broadcast())
+        .collect();
+    assert!(dkg.process_sharings(&sharings).is_empty());
+    assert!(dkg.process_coefficients(&coefficients).is_empty());
+    dkg.finalize().unwrap().public_shares().to_vec()
+}
+
 impl Arbitrary for VotePlanProof {
     fn arbitrary<G: Gen>(g: &mut G) -> Self {
         Self {
@@ -14,7 +14,8 @@ use chain_core::{
     property::{Deserialize, DeserializeFromSlice, ReadError, Serialize, WriteError},
 };
 use chain_crypto::{digest::DigestOf, Blake2b256, Verification};
-use chain_vote::MemberPublicKey;
+use chain_vote::{ElectionPublicKey, MemberPublicKey};
+use std::num::NonZeroU8;
 use std::ops::Deref;
 use typed_bytes::{ByteArray, ByteBuilder};
 
@@ -29,6 +30,15 @@ pub type ExternalProposalId = DigestOf<Blake2b256, ExternalProposalDocument>;
 /// the vote plan identifier on the blockchain
 pub type VotePlanId = DigestOf<Blake2b256, VotePlan>;
 
+/// written in place of the payload type of a serialized vote plan when it is
+/// followed by the versioned extension fields, the payload type coming after them.
+/// It is not a valid payload type, so the vote plans without extension keep their
+/// serialization (and their identifier).
+pub const VOTE_PLAN_EXTENSION_TAG: u8 = 0xff;
+
+/// version of the vote plan extension fields, which holds the committee threshold
+pub const VOTE_PLAN_EXTENSION_VERSION: u8 = 1;
+
 /// a vote plan for the voting system
 ///
 /// A vote plan defines what is being voted, for how long and how long
@@ -50,6 +60,10 @@ pub struct VotePlan {
     payload_type: vote::PayloadType,
     /// encrypting votes public keys
     committee_public_keys: Vec<chain_vote::MemberPublicKey>,
+    /// number of committee members needed to decrypt the tally. When set, the
+    /// committee keys are the public shares of a distributed key generation,
+    /// otherwise every committee member has to take part in the decryption.
+    committee_threshold: Option<NonZeroU8>,
     /// voting token used for weigthing the votes for any proposal in this voteplan
     voting_token: TokenIdentifier,
 }
@@ -143,14 +157,15 @@ impl Proposal {
     }
 
     fn serialize_in(&self, bb: ByteBuilder<VotePlan>) -> ByteBuilder<VotePlan> {
-        bb.bytes(self.external_id.as_ref())
-            .u8(self.options.as_byte())
+        let bb = bb.bytes(self.external_id.as_ref());
+        self.options
+            .serialize_in(bb)
             .sub(|bb| self.action.serialize_in(bb))
     }
 }
 
 impl VoteAction {
-    fn serialize_in(&self, bb: ByteBuilder<VotePlan>) -> ByteBuilder<VotePlan> {
+    pub(crate) fn serialize_in(&self, bb: ByteBuilder<VotePlan>) -> ByteBuilder<VotePlan> {
         match self {
             Self::OffChain => bb.u8(0),
             Self::Treasury { action } => bb.u8(1).sub(|bb| action.serialize_in(bb)),
@@ -207,10 +222,18 @@ impl VotePlan {
             proposals,
             payload_type,
             committee_public_keys,
+            committee_threshold: None,
             voting_token,
         }
     }
 
+    /// set the number of committee members needed to decrypt the tally, making
+    /// the `committee_public_keys` the public shares of the members
+    pub fn with_committee_threshold(mut self, threshold: NonZeroU8) -> Self {
+        self.committee_threshold = Some(threshold);
+        self
+    }
+
     pub fn check_governance(&self, governance: &Governance) -> bool {
         self.proposals()
             .iter()
@@ -259,6 +282,25 @@ impl VotePlan {
         &self.committee_public_keys
     }
 
+    pub fn committee_threshold(&self) -> Option<NonZeroU8> {
+        self.committee_threshold
+    }
+
+    /// the public key the votes of the private vote plans are encrypted to
+    ///
+    /// The committee keys needs to be valid for the committee threshold (see
+    /// `VotePlanLedger::add_vote_plan`), otherwise this function panics.
+    pub fn election_public_key(&self) -> ElectionPublicKey {
+        match self.committee_threshold {
+            Some(threshold) => ElectionPublicKey::from_public_shares(
+                &self.committee_public_keys,
+                threshold.get() as usize,
+            )
+            .expect("committee public shares are not valid for the threshold"),
+            None => ElectionPublicKey::from_participants(&self.committee_public_keys),
+        }
+    }
+
     pub fn voting_token(&self) -> &TokenIdentifier {
         &self.voting_token
     }
@@ -303,13 +345,21 @@ impl VotePlan {
     }
 
     pub fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
-        bb.u32(self.vote_start.epoch)
+        let bb = bb
+            .u32(self.vote_start.epoch)
             .u32(self.vote_start.slot_id)
             .u32(self.vote_end.epoch)
             .u32(self.vote_end.slot_id)
             .u32(self.committee_end.epoch)
-            .u32(self.committee_end.slot_id)
-            .u8(self.payload_type as u8)
+            .u32(self.committee_end.slot_id);
+        let bb = match self.committee_threshold {
+            None => bb,
+            Some(threshold) => bb
+                .u8(VOTE_PLAN_EXTENSION_TAG)
+                .u8(VOTE_PLAN_EXTENSION_VERSION)
+                .u8(threshold.get()),
+        };
+        bb.u8(self.payload_type as u8)
             .iter8(&mut self.proposals.iter(), |bb, proposal| {
                 proposal.serialize_in(bb)
             })
@@ -432,8 +482,22 @@ impl DeserializeFromSlice for VotePlan {
             slot_id: codec.get_be_u32()?,
         };
 
-        let payload_type = codec
-            .get_u8()?
+        let mut payload_type = codec.get_u8()?;
+        let mut committee_threshold = None;
+        if payload_type == VOTE_PLAN_EXTENSION_TAG {
+            let version = codec.get_u8()?;
+            if version != VOTE_PLAN_EXTENSION_VERSION {
+                return Err(ReadError::StructureInvalid(format!(
+                    "unsupported vote plan extension version {}",
+                    version
+                )));
+            }
+            committee_threshold = Some(NonZeroU8::new(codec.get_u8()?).ok_or_else(|| {
+                ReadError::StructureInvalid("committee threshold cannot be zero".to_string())
+            })?);
+            payload_type = codec.get_u8()?;
+        }
+        let payload_type = payload_type
             .try_into()
             .map_err(|e: vote::TryFromIntError| ReadError::StructureInvalid(e.to_string()))?;
 
@@ -443,10 +507,7 @@ impl DeserializeFromSlice for VotePlan {
         };
         for _ in 0..proposal_size {
             let external_id = <[u8; 32]>::deserialize(codec)?.into();
-            let options = codec.get_u8().and_then(|num_choices| {
-                vote::Options::new_length(num_choices)
-                    .map_err(|e| ReadError::StructureInvalid(e.to_string()))
-            })?;
+            let options = vote::Options::read(codec)?;
             let action = VoteAction::deserialize(codec)?;
 
             let proposal = Proposal {
@@ -476,6 +537,7 @@ impl DeserializeFromSlice for VotePlan {
             proposals,
             payload_type,
             committee_public_keys,
+            committee_threshold,
             voting_token,
         })
     }
@@ -502,6 +564,42 @@ mod tests {
         decoded == vote_plan
     }
 
+    #[test]
+    pub fn committee_threshold_is_a_versioned_extension() {
+        let vote_plan = VotePlan::new(
+            BlockDate::from_epoch_slot_id(1, 0),
+            BlockDate::from_epoch_slot_id(2, 0),
+            BlockDate::from_epoch_slot_id(3, 0),
+            VoteTestGen::proposals(1),
+            vote::PayloadType::Private,
+            Vec::new(),
+            TokenIdentifier {
+                policy_hash: PolicyHash::from([0u8; POLICY_HASH_SIZE]),
+                token_name: TokenName::try_from(vec![0u8; TOKEN_NAME_MAX_SIZE]).unwrap(),
+            },
+        );
+        let plain = vote_plan.serialize();
+        let with_threshold = vote_plan
+            .with_committee_threshold(NonZeroU8::new(2).unwrap())
+            .serialize();
+
+        // the dates are followed by the extension, then by the plain vote plan fields
+        let (dates, plain_fields) = plain.as_ref().split_at(24);
+        assert_eq!(plain_fields[0], vote::PayloadType::Private as u8);
+        assert_eq!(&with_threshold.as_ref()[..24], dates);
+        assert_eq!(
+            &with_threshold.as_ref()[24..27],
+            &[VOTE_PLAN_EXTENSION_TAG, VOTE_PLAN_EXTENSION_VERSION, 2]
+        );
+        assert_eq!(&with_threshold.as_ref()[27..], plain_fields);
+
+        let mut unknown_version = with_threshold.as_ref().to_vec();
+        unknown_version[25] = VOTE_PLAN_EXTENSION_VERSION + 1;
+        assert!(
+            VotePlan::deserialize_from_slice(&mut Codec::new(unknown_version.as_slice())).is_err()
+        );
+    }
+
     #[test]
     pub fn proposals_are_full() {
         let mut proposals = VoteTestGen::proposals(Proposals::MAX_LEN);
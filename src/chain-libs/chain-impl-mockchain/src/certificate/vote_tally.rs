@@ -299,6 +299,13 @@ impl DeserializeFromSlice for VoteTally {
                         .map_err(|err| ReadError::InvalidData(err.to_string()))?,
                 }
             }
+            // the encrypted ballots of any kind are tallied with a private tally
+            PayloadType::Approval | PayloadType::Ranked => {
+                return Err(ReadError::StructureInvalid(format!(
+                    "invalid vote tally type {:?}",
+                    payload_type
+                )))
+            }
         };
 
         Ok(Self { id, payload })
@@ -89,6 +89,18 @@ impl ParametersGovernance {
             .unwrap_or_else(|| self.default_acceptance_criteria())
     }
 
+    /// the acceptance criteria set for specific governance actions
+    pub fn acceptance_criteria(
+        &self,
+    ) -> impl Iterator<
+        Item = (
+            &ParametersGovernanceActionType,
+            &GovernanceAcceptanceCriteria,
+        ),
+    > {
+        self.acceptance_criteria_per_action.iter()
+    }
+
     pub fn logs(&self) -> impl Iterator<Item = &ParametersGovernanceAction> {
         self.logs.iter()
     }
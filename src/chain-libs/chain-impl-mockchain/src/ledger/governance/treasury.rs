@@ -86,6 +86,13 @@ impl TreasuryGovernance {
             .lookup(&action)
             .unwrap_or_else(|| self.default_acceptance_criteria())
     }
+
+    /// the acceptance criteria set for specific governance actions
+    pub fn acceptance_criteria(
+        &self,
+    ) -> impl Iterator<Item = (&TreasuryGovernanceActionType, &GovernanceAcceptanceCriteria)> {
+        self.acceptance_criteria_per_action.iter()
+    }
 }
 
 /* Ser/De ******************************************************************* */
@@ -1,14 +1,17 @@
-use super::governance::Governance;
+use super::governance::{Governance, ParametersGovernance, TreasuryGovernance};
 use super::ledger::{Error, Ledger, LedgerStaticParameters};
 use super::pots::{self, Pots};
 use super::LeadersParticipationRecord;
-use crate::certificate::{VotePlan, VotePlanId};
+use crate::certificate::VotePlanId;
+use crate::chaineval::PraosNonce;
 use crate::chaintypes::ChainLength;
 use crate::config::ConfigParam;
 use crate::date::BlockDate;
 use crate::key::Hash;
 use crate::ledger::token_distribution::TokenTotals;
 use crate::stake::PoolsState;
+use crate::tokens::identifier::TokenIdentifier;
+use crate::value::Value;
 use crate::vote::{VotePlanLedger, VotePlanManager};
 use crate::{account, legacy, multisig, setting, update, utxo};
 use chain_addr::Address;
@@ -27,6 +30,7 @@ pub enum Entry<'a> {
         ),
     ),
     ConfigParam(ConfigParam),
+    ConsensusNonce(PraosNonce),
     UpdateProposal(
         (
             &'a crate::certificate::UpdateProposalId,
@@ -47,7 +51,10 @@ pub enum Entry<'a> {
     ),
     StakePool((&'a crate::certificate::PoolId, &'a crate::stake::PoolState)),
     LeaderParticipation((&'a crate::certificate::PoolId, &'a u32)),
-    VotePlan(&'a VotePlan),
+    VotePlan(&'a VotePlanManager),
+    TokenTotal((&'a TokenIdentifier, &'a Value)),
+    TreasuryGovernance(&'a TreasuryGovernance),
+    ParametersGovernance(&'a ParametersGovernance),
 }
 
 #[derive(Clone)]
@@ -63,6 +70,7 @@ pub enum EntryOwned {
         ),
     ),
     ConfigParam(ConfigParam),
+    ConsensusNonce(PraosNonce),
     UpdateProposal(
         (
             crate::certificate::UpdateProposalId,
@@ -78,7 +86,10 @@ pub enum EntryOwned {
     MultisigDeclaration((crate::multisig::Identifier, crate::multisig::Declaration)),
     StakePool((crate::certificate::PoolId, crate::stake::PoolState)),
     LeaderParticipation((crate::certificate::PoolId, u32)),
-    VotePlan(VotePlan),
+    VotePlan(VotePlanManager),
+    TokenTotal((TokenIdentifier, Value)),
+    TreasuryGovernance(TreasuryGovernance),
+    ParametersGovernance(ParametersGovernance),
     StopEntry,
 }
 
@@ -107,6 +118,7 @@ impl EntryOwned {
                 Some(Entry::Account((identifier, account_state)))
             }
             EntryOwned::ConfigParam(config_param) => Some(Entry::ConfigParam(config_param.clone())),
+            EntryOwned::ConsensusNonce(nonce) => Some(Entry::ConsensusNonce(nonce.clone())),
             EntryOwned::UpdateProposal((proposal_id, proposal_state)) => {
                 Some(Entry::UpdateProposal((proposal_id, proposal_state)))
             }
@@ -123,6 +135,13 @@ impl EntryOwned {
                 Some(Entry::LeaderParticipation((pool_id, participation)))
             }
             EntryOwned::VotePlan(vote_plan) => Some(Entry::VotePlan(vote_plan)),
+            EntryOwned::TokenTotal((token, value)) => Some(Entry::TokenTotal((token, value))),
+            EntryOwned::TreasuryGovernance(governance) => {
+                Some(Entry::TreasuryGovernance(governance))
+            }
+            EntryOwned::ParametersGovernance(governance) => {
+                Some(Entry::ParametersGovernance(governance))
+            }
             EntryOwned::StopEntry => None,
         }
     }
@@ -142,6 +161,7 @@ enum IterState<'a> {
     OldUtxo(utxo::Iter<'a, legacy::OldAddress>),
     Accounts(crate::accounting::account::Iter<'a, account::Identifier, ()>),
     ConfigParams(Vec<ConfigParam>),
+    ConsensusNonce,
     UpdateProposals(
         imhamt::HamtIter<
             'a,
@@ -157,6 +177,9 @@ enum IterState<'a> {
     Pots(pots::Entries<'a>),
     LeaderParticipations(imhamt::HamtIter<'a, crate::certificate::PoolId, u32>),
     VotePlan(imhamt::HamtIter<'a, VotePlanId, VotePlanManager>),
+    TokenTotals(imhamt::HamtIter<'a, TokenIdentifier, Value>),
+    TreasuryGovernance,
+    ParametersGovernance,
     Done,
 }
 
@@ -195,7 +218,10 @@ impl<'a> Iterator for LedgerIterator<'a> {
             },
             IterState::Accounts(iter) => match iter.next() {
                 None => {
-                    self.state = IterState::ConfigParams(self.ledger.settings.config_params().0);
+                    // the parameters are popped from the back, keep them in order
+                    let mut params = self.ledger.settings.config_params().0;
+                    params.reverse();
+                    self.state = IterState::ConfigParams(params);
                     self.next()
                 }
                 Some(x) => Some(Entry::Account(x)),
@@ -204,10 +230,16 @@ impl<'a> Iterator for LedgerIterator<'a> {
                 if let Some(param) = params.pop() {
                     Some(Entry::ConfigParam(param))
                 } else {
-                    self.state = IterState::UpdateProposals(self.ledger.updates.proposals.iter());
+                    self.state = IterState::ConsensusNonce;
                     self.next()
                 }
             }
+            IterState::ConsensusNonce => {
+                self.state = IterState::UpdateProposals(self.ledger.updates.proposals.iter());
+                Some(Entry::ConsensusNonce(
+                    self.ledger.settings.consensus_nonce.clone(),
+                ))
+            }
             IterState::UpdateProposals(iter) => match iter.next() {
                 None => {
                     self.state = IterState::MultisigAccounts(self.ledger.multisig.iter_accounts());
@@ -253,11 +285,28 @@ impl<'a> Iterator for LedgerIterator<'a> {
             },
             IterState::VotePlan(iter) => match iter.next() {
                 None => {
-                    self.state = IterState::Done;
+                    self.state = IterState::TokenTotals(self.ledger.token_totals.iter());
                     self.next()
                 }
-                Some((_, plan_manager)) => Some(Entry::VotePlan(plan_manager.plan())),
+                Some((_, plan_manager)) => Some(Entry::VotePlan(plan_manager)),
             },
+            IterState::TokenTotals(iter) => match iter.next() {
+                None => {
+                    self.state = IterState::TreasuryGovernance;
+                    self.next()
+                }
+                Some(x) => Some(Entry::TokenTotal(x)),
+            },
+            IterState::TreasuryGovernance => {
+                self.state = IterState::ParametersGovernance;
+                Some(Entry::TreasuryGovernance(&self.ledger.governance.treasury))
+            }
+            IterState::ParametersGovernance => {
+                self.state = IterState::Done;
+                Some(Entry::ParametersGovernance(
+                    &self.ledger.governance.parameters,
+                ))
+            }
             IterState::Done => None,
         }
     }
@@ -279,17 +328,17 @@ impl<'a> std::iter::FromIterator<Entry<'a>> for Result<Ledger, Error> {
         let mut oldutxos: HashMap<Hash, Vec<_>> = HashMap::new();
         let mut accounts = vec![];
         let mut config_params = crate::fragment::ConfigParams::new();
+        let mut consensus_nonce = None;
         let mut updates = update::UpdateState::new();
         let mut multisig_accounts = vec![];
         let mut multisig_declarations = vec![];
-        let delegation = PoolsState::new();
+        let mut delegation = PoolsState::new();
         let mut globals = None;
         let mut pots = Pots::zero();
         let mut leaders_log = LeadersParticipationRecord::new();
-        // TODO: votes don't have their entry
         let mut votes = VotePlanLedger::new();
-        let governance = Governance::default();
-        let token_totals = TokenTotals::default();
+        let mut governance = Governance::default();
+        let mut token_totals = TokenTotals::default();
 
         for entry in iter {
             match entry {
@@ -315,6 +364,9 @@ impl<'a> std::iter::FromIterator<Entry<'a>> for Result<Ledger, Error> {
                 Entry::ConfigParam(param) => {
                     config_params.push(param.clone());
                 }
+                Entry::ConsensusNonce(nonce) => {
+                    consensus_nonce = Some(nonce);
+                }
                 Entry::UpdateProposal((proposal_id, proposal_state)) => {
                     updates.proposals = updates
                         .proposals
@@ -328,7 +380,7 @@ impl<'a> std::iter::FromIterator<Entry<'a>> for Result<Ledger, Error> {
                     multisig_declarations.push((id.clone(), decl.clone()));
                 }
                 Entry::StakePool((pool_id, pool_state)) => {
-                    let _ = delegation
+                    delegation.stake_pools = delegation
                         .stake_pools
                         .insert(pool_id.clone(), pool_state.clone())
                         .unwrap();
@@ -337,26 +389,35 @@ impl<'a> std::iter::FromIterator<Entry<'a>> for Result<Ledger, Error> {
                 Entry::LeaderParticipation((pool_id, pool_participation)) => leaders_log
                     .set_for(pool_id.clone(), *pool_participation)
                     .unwrap(),
-                Entry::VotePlan(vote_plan) => {
-                    // TODO: don't use default
+                Entry::VotePlan(plan_manager) => {
                     votes.plans = votes
                         .plans
-                        .insert(
-                            vote_plan.to_id(),
-                            VotePlanManager::new(vote_plan.clone(), Default::default()),
-                        )
+                        .insert(plan_manager.id().clone(), plan_manager.clone())
                         .unwrap();
                 }
+                Entry::TokenTotal((token, value)) => {
+                    token_totals = token_totals.add(token.clone(), *value)?;
+                }
+                Entry::TreasuryGovernance(treasury) => {
+                    governance.treasury = treasury.clone();
+                }
+                Entry::ParametersGovernance(parameters) => {
+                    governance.parameters = parameters.clone();
+                }
             }
         }
 
         let globals = globals.ok_or(Error::IncompleteLedger)?;
+        let mut settings = setting::Settings::new().try_apply(&config_params)?;
+        if let Some(consensus_nonce) = consensus_nonce {
+            settings.consensus_nonce = consensus_nonce;
+        }
 
         let ledger = Ledger {
             utxos: utxos.into_iter().collect(),
             oldutxos: oldutxos.into_iter().collect(),
             accounts: accounts.into_iter().collect(),
-            settings: setting::Settings::new().try_apply(&config_params)?,
+            settings,
             updates,
             multisig: multisig::Ledger::restore(multisig_accounts, multisig_declarations),
             delegation,
@@ -468,8 +529,27 @@ mod tests {
                 Entry::LeaderParticipation((pool_id, pool_record)) => {
                     println!("LeaderParticipation {} {}", pool_id, pool_record);
                 }
+                Entry::ConsensusNonce(nonce) => {
+                    println!("ConsensusNonce {:?}", nonce);
+                }
                 Entry::VotePlan(plan) => {
-                    println!("VotePlan {}", plan.to_id());
+                    println!("VotePlan {}", plan.id());
+                }
+                Entry::TokenTotal((token, value)) => {
+                    println!("TokenTotal {:?} {}", token, value);
+                }
+                Entry::TreasuryGovernance(treasury) => {
+                    println!(
+                        "TreasuryGovernance {:?}",
+                        treasury.default_acceptance_criteria()
+                    );
+                }
+                Entry::ParametersGovernance(parameters) => {
+                    println!(
+                        "ParametersGovernance {:?} {:?}",
+                        parameters.default_acceptance_criteria(),
+                        parameters.logs().collect::<Vec<_>>()
+                    );
                 }
             }
         }
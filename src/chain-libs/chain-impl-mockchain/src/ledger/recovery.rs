@@ -42,16 +42,22 @@ use crate::accounting::account::{
 };
 use crate::certificate::{
     PoolId, PoolRegistration, Proposal, Proposals, UpdateProposal, UpdateProposalId, UpdateVoterId,
-    VoteAction, VotePlan,
+    VoteAction, VotePlan, VOTE_PLAN_EXTENSION_TAG, VOTE_PLAN_EXTENSION_VERSION,
 };
+use crate::chaineval::PraosNonce;
 use crate::config::ConfigParam;
 use crate::date::BlockDate;
 use crate::fragment::FragmentId;
 use crate::header::{ChainLength, HeaderId};
 use crate::key::serialize_public_key;
+use crate::ledger::governance::{
+    GovernanceAcceptanceCriteria, ParametersGovernance, ParametersGovernanceAction,
+    ParametersGovernanceActionType, TreasuryGovernance, TreasuryGovernanceActionType,
+};
 use crate::ledger::{Globals, Ledger, LedgerStaticParameters};
 use crate::legacy;
 use crate::multisig::{DeclElement, Declaration};
+use crate::rewards::Ratio;
 use crate::stake::{PoolLastRewards, PoolState};
 use crate::tokens::identifier::TokenIdentifier;
 use crate::tokens::name::TokenName;
@@ -59,7 +65,7 @@ use crate::tokens::policy_hash::{PolicyHash, POLICY_HASH_SIZE};
 use crate::transaction::Output;
 use crate::update::UpdateProposalState;
 use crate::value::Value;
-use crate::vote;
+use crate::vote::{self, CommitteeId, IncrementalTally, TallyResult, VotePlanManager, Weight};
 use crate::{config, key, multisig, utxo};
 use chain_addr::{Address, Discrimination};
 use chain_core::property::WriteError;
@@ -70,8 +76,11 @@ use chain_core::{
 use chain_crypto::digest::{DigestAlg, DigestOf};
 use chain_time::era::{pack_time_era, unpack_time_era};
 use imhamt::Hamt;
+use std::collections::{hash_map::DefaultHasher, HashSet};
 use std::io::Write;
+use std::num::NonZeroU8;
 use std::sync::Arc;
+use typed_bytes::ByteBuilder;
 
 #[cfg(test)]
 use crate::{
@@ -181,6 +190,7 @@ fn pack_account_state<W: std::io::Write>(
     pack_spending_strategy(&account_state.spending, codec)?;
     pack_delegation_type(&account_state.delegation, codec)?;
     codec.put_be_u64(account_state.value.0)?;
+    pack_tokens(&account_state.tokens, codec)?;
     pack_last_rewards(&account_state.last_rewards, codec)?;
     Ok(())
 }
@@ -189,17 +199,45 @@ fn unpack_account_state(codec: &mut Codec<&[u8]>) -> Result<AccountState<()>, Re
     let spending = unpack_spending_strategy(codec)?;
     let delegation = unpack_delegation_type(codec)?;
     let value = codec.get_be_u64()?;
+    let tokens = unpack_tokens(codec)?;
     let last_rewards = unpack_last_rewards(codec)?;
     Ok(AccountState {
         spending,
         delegation,
         value: Value(value),
-        tokens: Hamt::new(),
+        tokens,
         last_rewards,
         extra: (),
     })
 }
 
+fn pack_tokens<W: std::io::Write>(
+    tokens: &Hamt<DefaultHasher, TokenIdentifier, Value>,
+    codec: &mut Codec<W>,
+) -> Result<(), WriteError> {
+    codec.put_be_u64(tokens.size() as u64)?;
+    for (token, value) in tokens.iter() {
+        pack_token_identifier(token, codec)?;
+        codec.put_be_u64(value.0)?;
+    }
+    Ok(())
+}
+
+fn unpack_tokens(
+    codec: &mut Codec<&[u8]>,
+) -> Result<Hamt<DefaultHasher, TokenIdentifier, Value>, ReadError> {
+    let size = codec.get_be_u64()?;
+    let mut tokens = Hamt::new();
+    for _ in 0..size {
+        let token = unpack_token_identifier(codec)?;
+        let value = Value(codec.get_be_u64()?);
+        tokens = tokens
+            .insert(token, value)
+            .map_err(|e| ReadError::InvalidData(e.to_string()))?;
+    }
+    Ok(tokens)
+}
+
 fn pack_delegation_ratio<W: std::io::Write>(
     delegation_ratio: &DelegationRatio,
     codec: &mut Codec<W>,
@@ -810,20 +848,28 @@ fn pack_vote_proposal<W: std::io::Write>(
     codec: &mut Codec<W>,
 ) -> Result<(), WriteError> {
     pack_digestof(proposal.external_id(), codec)?;
-    codec.put_u8(proposal.options().as_byte())?;
+    let options = proposal
+        .options()
+        .serialize_in(ByteBuilder::<Proposal>::new())
+        .finalize_as_vec();
+    codec.put_bytes(&options)?;
+    let action = proposal
+        .action()
+        .serialize_in(ByteBuilder::<VotePlan>::new())
+        .finalize_as_vec();
+    codec.put_bytes(&action)?;
     Ok(())
 }
 
 fn unpack_proposal(codec: &mut Codec<&[u8]>) -> Result<Proposal, ReadError> {
     let external_id = unpack_digestof(codec)?;
-    let options = vote::Options::new_length(codec.get_u8()?)
-        .map_err(|e| ReadError::InvalidData(e.to_string()))?;
+    let options = vote::Options::read(codec)?;
     let action = unpack_vote_action(codec)?;
     Ok(Proposal::new(external_id, options, action))
 }
 
-fn unpack_vote_action(_codec: &mut Codec<&[u8]>) -> Result<VoteAction, ReadError> {
-    todo!()
+fn unpack_vote_action(codec: &mut Codec<&[u8]>) -> Result<VoteAction, ReadError> {
+    VoteAction::deserialize(codec)
 }
 
 fn pack_vote_proposals<W: std::io::Write>(
@@ -846,16 +892,42 @@ fn unpack_proposals(codec: &mut Codec<&[u8]>) -> Result<Proposals, ReadError> {
     Ok(proposals)
 }
 
+// the committee threshold is written in the versioned extension of the vote plan,
+// which comes in place of the payload type as in the vote plan certificate
 fn pack_payload_type<W: std::io::Write>(
     t: vote::PayloadType,
+    committee_threshold: Option<NonZeroU8>,
     codec: &mut Codec<W>,
 ) -> Result<(), WriteError> {
+    if let Some(threshold) = committee_threshold {
+        codec.put_u8(VOTE_PLAN_EXTENSION_TAG)?;
+        codec.put_u8(VOTE_PLAN_EXTENSION_VERSION)?;
+        codec.put_u8(threshold.get())?;
+    }
     codec.put_u8(t as u8)
 }
 
-fn unpack_payload_type(codec: &mut Codec<&[u8]>) -> Result<vote::PayloadType, ReadError> {
-    let byte = codec.get_u8()?;
-    vote::PayloadType::try_from(byte).map_err(|e| ReadError::InvalidData(e.to_string()))
+fn unpack_payload_type(
+    codec: &mut Codec<&[u8]>,
+) -> Result<(vote::PayloadType, Option<NonZeroU8>), ReadError> {
+    let mut byte = codec.get_u8()?;
+    let mut committee_threshold = None;
+    if byte == VOTE_PLAN_EXTENSION_TAG {
+        let version = codec.get_u8()?;
+        if version != VOTE_PLAN_EXTENSION_VERSION {
+            return Err(ReadError::InvalidData(format!(
+                "unsupported vote plan extension version {}",
+                version
+            )));
+        }
+        committee_threshold = Some(NonZeroU8::new(codec.get_u8()?).ok_or_else(|| {
+            ReadError::InvalidData("invalid zero committee threshold in a vote plan".to_string())
+        })?);
+        byte = codec.get_u8()?;
+    }
+    let payload_type =
+        vote::PayloadType::try_from(byte).map_err(|e| ReadError::InvalidData(e.to_string()))?;
+    Ok((payload_type, committee_threshold))
 }
 
 fn pack_committee_public_keys<W: std::io::Write>(
@@ -884,7 +956,7 @@ fn unpack_committee_public_keys(
     Ok(result)
 }
 
-fn pack_voting_token<W: std::io::Write>(
+fn pack_token_identifier<W: std::io::Write>(
     token_identifier: &TokenIdentifier,
     codec: &mut Codec<W>,
 ) -> Result<(), WriteError> {
@@ -893,7 +965,7 @@ fn pack_voting_token<W: std::io::Write>(
     Ok(())
 }
 
-fn unpack_voting_token(codec: &mut Codec<&[u8]>) -> Result<TokenIdentifier, ReadError> {
+fn unpack_token_identifier(codec: &mut Codec<&[u8]>) -> Result<TokenIdentifier, ReadError> {
     let policy_hash_bytes = codec.get_bytes(POLICY_HASH_SIZE)?;
 
     let policy_hash = PolicyHash::try_from(policy_hash_bytes.as_ref())
@@ -918,10 +990,14 @@ fn pack_vote_plan<W: std::io::Write>(
     pack_block_date(vote_plan.vote_start(), codec)?;
     pack_block_date(vote_plan.vote_end(), codec)?;
     pack_block_date(vote_plan.committee_end(), codec)?;
-    pack_payload_type(vote_plan.payload_type(), codec)?;
+    pack_payload_type(
+        vote_plan.payload_type(),
+        vote_plan.committee_threshold(),
+        codec,
+    )?;
     pack_vote_proposals(vote_plan.proposals(), codec)?;
     pack_committee_public_keys(vote_plan.committee_public_keys(), codec)?;
-    pack_voting_token(vote_plan.voting_token(), codec)?;
+    pack_token_identifier(vote_plan.voting_token(), codec)?;
     Ok(())
 }
 
@@ -929,11 +1005,11 @@ fn unpack_vote_plan(codec: &mut Codec<&[u8]>) -> Result<VotePlan, ReadError> {
     let vote_start = unpack_block_date(codec)?;
     let vote_end = unpack_block_date(codec)?;
     let committee_end = unpack_block_date(codec)?;
-    let payload_type = unpack_payload_type(codec)?;
+    let (payload_type, threshold) = unpack_payload_type(codec)?;
     let proposals = unpack_proposals(codec)?;
     let keys = unpack_committee_public_keys(codec)?;
-    let voting_token = unpack_voting_token(codec)?;
-    Ok(VotePlan::new(
+    let voting_token = unpack_token_identifier(codec)?;
+    let vote_plan = VotePlan::new(
         vote_start,
         vote_end,
         committee_end,
@@ -941,7 +1017,276 @@ fn unpack_vote_plan(codec: &mut Codec<&[u8]>) -> Result<VotePlan, ReadError> {
         payload_type,
         keys,
         voting_token,
-    ))
+    );
+    Ok(match threshold {
+        Some(threshold) => vote_plan.with_committee_threshold(threshold),
+        None => vote_plan,
+    })
+}
+
+fn pack_tally_result<W: std::io::Write>(
+    result: &TallyResult,
+    codec: &mut Codec<W>,
+) -> Result<(), WriteError> {
+    // the approval participation is flagged on the second bit, so the results
+    // of the other votes are packed as before
+    let flags = result.is_ranked() as u8 | (result.is_approval() as u8) << 1;
+    codec.put_u8(flags)?;
+    if result.is_approval() {
+        codec.put_be_u64(result.participation().into())?;
+    }
+    let options = result
+        .options()
+        .serialize_in(ByteBuilder::<TallyResult>::new())
+        .finalize_as_vec();
+    codec.put_bytes(&options)?;
+    codec.put_be_u64(result.results().len() as u64)?;
+    for weight in result.results() {
+        codec.put_be_u64((*weight).into())?;
+    }
+    Ok(())
+}
+
+fn unpack_tally_result(codec: &mut Codec<&[u8]>) -> Result<TallyResult, ReadError> {
+    let flags = codec.get_u8()?;
+    let ranked = flags & 1 != 0;
+    let approval_participation = if flags & 2 != 0 {
+        Some(Weight::from(codec.get_be_u64()?))
+    } else {
+        None
+    };
+    let options = vote::Options::read(codec)?;
+    let size = codec.get_be_u64()?;
+    let mut results = Vec::new();
+    for _ in 0..size {
+        results.push(Weight::from(codec.get_be_u64()?));
+    }
+    TallyResult::from_results(results, options, ranked, approval_participation).ok_or_else(|| {
+        ReadError::InvalidData("tally results do not match the proposal options".to_string())
+    })
+}
+
+fn pack_incremental_tally<W: std::io::Write>(
+    tally: &IncrementalTally,
+    codec: &mut Codec<W>,
+) -> Result<(), WriteError> {
+    match tally {
+        IncrementalTally::Public(result) => {
+            codec.put_u8(0)?;
+            pack_tally_result(result, codec)?;
+        }
+        IncrementalTally::Private(encrypted_tally) => {
+            codec.put_u8(1)?;
+            let bytes = encrypted_tally.to_bytes();
+            codec.put_be_u64(bytes.len() as u64)?;
+            codec.put_bytes(&bytes)?;
+        }
+        IncrementalTally::Decrypted(result) => {
+            codec.put_u8(2)?;
+            pack_tally_result(result, codec)?;
+        }
+    }
+    Ok(())
+}
+
+fn unpack_incremental_tally(codec: &mut Codec<&[u8]>) -> Result<IncrementalTally, ReadError> {
+    match codec.get_u8()? {
+        0 => Ok(IncrementalTally::Public(unpack_tally_result(codec)?)),
+        1 => {
+            let size = codec.get_be_u64()? as usize;
+            let bytes = codec.get_slice(size)?;
+            chain_vote::EncryptedTally::from_bytes(bytes)
+                .map(IncrementalTally::Private)
+                .ok_or_else(|| ReadError::InvalidData("invalid encrypted tally".to_string()))
+        }
+        2 => Ok(IncrementalTally::Decrypted(unpack_tally_result(codec)?)),
+        code => Err(ReadError::UnknownTag(code as u32)),
+    }
+}
+
+fn pack_vote_plan_manager<W: std::io::Write>(
+    plan_manager: &VotePlanManager,
+    codec: &mut Codec<W>,
+) -> Result<(), WriteError> {
+    pack_vote_plan(plan_manager.plan(), codec)?;
+    let mut committee: Vec<_> = plan_manager.committee_set().iter().collect();
+    committee.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+    codec.put_be_u64(committee.len() as u64)?;
+    for committee_id in committee {
+        codec.put_bytes(committee_id.as_ref())?;
+    }
+    for (votes_by_voters, tally) in plan_manager.proposal_states() {
+        codec.put_be_u64(votes_by_voters.size() as u64)?;
+        for (identifier, _) in votes_by_voters.iter() {
+            pack_account_identifier(identifier, codec)?;
+        }
+        pack_incremental_tally(tally, codec)?;
+    }
+    Ok(())
+}
+
+fn unpack_vote_plan_manager(codec: &mut Codec<&[u8]>) -> Result<VotePlanManager, ReadError> {
+    let vote_plan = unpack_vote_plan(codec)?;
+    let size = codec.get_be_u64()?;
+    let mut committee = HashSet::new();
+    for _ in 0..size {
+        let bytes = codec.get_slice(CommitteeId::COMMITTEE_ID_SIZE)?;
+        committee.insert(
+            CommitteeId::try_from(bytes).map_err(|e| ReadError::InvalidData(e.to_string()))?,
+        );
+    }
+    let mut proposals = Vec::new();
+    for _ in 0..vote_plan.proposals().len() {
+        let size = codec.get_be_u64()?;
+        let mut votes_by_voters = Hamt::new();
+        for _ in 0..size {
+            votes_by_voters = votes_by_voters
+                .insert(unpack_account_identifier(codec)?, ())
+                .map_err(|e| ReadError::InvalidData(e.to_string()))?;
+        }
+        proposals.push((votes_by_voters, unpack_incremental_tally(codec)?));
+    }
+    VotePlanManager::restore(vote_plan, committee, proposals).ok_or_else(|| {
+        ReadError::InvalidData("vote plan state does not match its proposals".to_string())
+    })
+}
+
+fn pack_optional_ratio<W: std::io::Write>(
+    ratio: &Option<Ratio>,
+    codec: &mut Codec<W>,
+) -> Result<(), WriteError> {
+    match ratio {
+        None => codec.put_u8(0),
+        Some(ratio) => {
+            codec.put_u8(1)?;
+            codec.put_be_u64(ratio.numerator)?;
+            codec.put_be_u64(ratio.denominator.get())
+        }
+    }
+}
+
+fn unpack_optional_ratio(codec: &mut Codec<&[u8]>) -> Result<Option<Ratio>, ReadError> {
+    match codec.get_u8()? {
+        0 => Ok(None),
+        1 => {
+            let numerator = codec.get_be_u64()?;
+            let denominator = std::num::NonZeroU64::new(codec.get_be_u64()?).ok_or_else(|| {
+                ReadError::StructureInvalid("ratio with a zero denominator".to_string())
+            })?;
+            Ok(Some(Ratio {
+                numerator,
+                denominator,
+            }))
+        }
+        code => Err(ReadError::UnknownTag(code as u32)),
+    }
+}
+
+fn pack_acceptance_criteria<W: std::io::Write>(
+    criteria: &GovernanceAcceptanceCriteria,
+    codec: &mut Codec<W>,
+) -> Result<(), WriteError> {
+    pack_optional_ratio(&criteria.minimum_stake_participation, codec)?;
+    pack_optional_ratio(&criteria.minimum_approval, codec)?;
+    codec.put_u8(criteria.blank.as_byte())?;
+    codec.put_u8(criteria.favorable.as_byte())?;
+    codec.put_u8(criteria.rejection.as_byte())?;
+    let options = criteria
+        .options
+        .serialize_in(ByteBuilder::<GovernanceAcceptanceCriteria>::new())
+        .finalize_as_vec();
+    codec.put_bytes(&options)
+}
+
+fn unpack_acceptance_criteria(
+    codec: &mut Codec<&[u8]>,
+) -> Result<GovernanceAcceptanceCriteria, ReadError> {
+    Ok(GovernanceAcceptanceCriteria {
+        minimum_stake_participation: unpack_optional_ratio(codec)?,
+        minimum_approval: unpack_optional_ratio(codec)?,
+        blank: vote::Choice::new(codec.get_u8()?),
+        favorable: vote::Choice::new(codec.get_u8()?),
+        rejection: vote::Choice::new(codec.get_u8()?),
+        options: vote::Options::read(codec)?,
+    })
+}
+
+fn pack_treasury_governance<W: std::io::Write>(
+    governance: &TreasuryGovernance,
+    codec: &mut Codec<W>,
+) -> Result<(), WriteError> {
+    pack_acceptance_criteria(governance.default_acceptance_criteria(), codec)?;
+    let criteria: Vec<_> = governance.acceptance_criteria().collect();
+    codec.put_be_u64(criteria.len() as u64)?;
+    for (action, criteria) in criteria {
+        codec.put_u8(match action {
+            TreasuryGovernanceActionType::NoOp => 0,
+            TreasuryGovernanceActionType::TransferToRewards => 1,
+        })?;
+        pack_acceptance_criteria(criteria, codec)?;
+    }
+    Ok(())
+}
+
+fn unpack_treasury_governance(codec: &mut Codec<&[u8]>) -> Result<TreasuryGovernance, ReadError> {
+    let mut governance = TreasuryGovernance::new();
+    governance.set_default_acceptance_criteria(unpack_acceptance_criteria(codec)?);
+    let size = codec.get_be_u64()?;
+    for _ in 0..size {
+        let action = match codec.get_u8()? {
+            0 => TreasuryGovernanceActionType::NoOp,
+            1 => TreasuryGovernanceActionType::TransferToRewards,
+            code => return Err(ReadError::UnknownTag(code as u32)),
+        };
+        governance.set_acceptance_criteria(action, unpack_acceptance_criteria(codec)?);
+    }
+    Ok(governance)
+}
+
+fn pack_parameters_governance<W: std::io::Write>(
+    governance: &ParametersGovernance,
+    codec: &mut Codec<W>,
+) -> Result<(), WriteError> {
+    pack_acceptance_criteria(governance.default_acceptance_criteria(), codec)?;
+    let criteria: Vec<_> = governance.acceptance_criteria().collect();
+    codec.put_be_u64(criteria.len() as u64)?;
+    for (action, criteria) in criteria {
+        codec.put_u8(match action {
+            ParametersGovernanceActionType::NoOp => 0,
+            ParametersGovernanceActionType::RewardAdd => 1,
+        })?;
+        pack_acceptance_criteria(criteria, codec)?;
+    }
+    let logs: Vec<_> = governance.logs().collect();
+    codec.put_be_u64(logs.len() as u64)?;
+    for action in logs {
+        let action = action
+            .serialize_in(ByteBuilder::<ParametersGovernanceAction>::new())
+            .finalize_as_vec();
+        codec.put_bytes(&action)?;
+    }
+    Ok(())
+}
+
+fn unpack_parameters_governance(
+    codec: &mut Codec<&[u8]>,
+) -> Result<ParametersGovernance, ReadError> {
+    let mut governance = ParametersGovernance::new();
+    governance.set_default_acceptance_criteria(unpack_acceptance_criteria(codec)?);
+    let size = codec.get_be_u64()?;
+    for _ in 0..size {
+        let action = match codec.get_u8()? {
+            0 => ParametersGovernanceActionType::NoOp,
+            1 => ParametersGovernanceActionType::RewardAdd,
+            code => return Err(ReadError::UnknownTag(code as u32)),
+        };
+        governance.set_acceptance_criteria(action, unpack_acceptance_criteria(codec)?);
+    }
+    let size = codec.get_be_u64()?;
+    for _ in 0..size {
+        governance.logs_register(ParametersGovernanceAction::deserialize(codec)?);
+    }
+    Ok(governance)
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -958,6 +1303,10 @@ enum EntrySerializeCode {
     StakePool = 9,
     LeaderParticipation = 10,
     VotePlan = 11,
+    TokenTotal = 12,
+    ParametersGovernance = 13,
+    ConsensusNonce = 14,
+    TreasuryGovernance = 15,
     SerializationEnd = 99,
 }
 
@@ -976,6 +1325,10 @@ impl EntrySerializeCode {
             9 => Some(EntrySerializeCode::StakePool),
             10 => Some(EntrySerializeCode::LeaderParticipation),
             11 => Some(EntrySerializeCode::VotePlan),
+            12 => Some(EntrySerializeCode::TokenTotal),
+            13 => Some(EntrySerializeCode::ParametersGovernance),
+            14 => Some(EntrySerializeCode::ConsensusNonce),
+            15 => Some(EntrySerializeCode::TreasuryGovernance),
             99 => Some(EntrySerializeCode::SerializationEnd),
             _ => None,
         }
@@ -1037,9 +1390,26 @@ fn pack_entry<W: std::io::Write>(
             pack_digestof(pool_id, codec)?;
             codec.put_be_u32(**participation)?;
         }
-        Entry::VotePlan(vote_plan) => {
+        Entry::ConsensusNonce(nonce) => {
+            codec.put_u8(EntrySerializeCode::ConsensusNonce as u8)?;
+            codec.put_bytes(nonce.as_ref())?;
+        }
+        Entry::VotePlan(plan_manager) => {
             codec.put_u8(EntrySerializeCode::VotePlan as u8)?;
-            pack_vote_plan(vote_plan, codec)?;
+            pack_vote_plan_manager(plan_manager, codec)?;
+        }
+        Entry::TokenTotal((token, value)) => {
+            codec.put_u8(EntrySerializeCode::TokenTotal as u8)?;
+            pack_token_identifier(token, codec)?;
+            codec.put_be_u64(value.0)?;
+        }
+        Entry::TreasuryGovernance(governance) => {
+            codec.put_u8(EntrySerializeCode::TreasuryGovernance as u8)?;
+            pack_treasury_governance(governance, codec)?;
+        }
+        Entry::ParametersGovernance(governance) => {
+            codec.put_u8(EntrySerializeCode::ParametersGovernance as u8)?;
+            pack_parameters_governance(governance, codec)?;
         }
     }
     Ok(())
@@ -1096,8 +1466,25 @@ fn unpack_entry_owned(codec: &mut Codec<&[u8]>) -> Result<EntryOwned, ReadError>
             Ok(EntryOwned::LeaderParticipation((pool_id, v)))
         }
         EntrySerializeCode::VotePlan => {
-            let vote_plan = unpack_vote_plan(codec)?;
-            Ok(EntryOwned::VotePlan(vote_plan))
+            let plan_manager = unpack_vote_plan_manager(codec)?;
+            Ok(EntryOwned::VotePlan(plan_manager))
+        }
+        EntrySerializeCode::TokenTotal => {
+            let token = unpack_token_identifier(codec)?;
+            let value = Value(codec.get_be_u64()?);
+            Ok(EntryOwned::TokenTotal((token, value)))
+        }
+        EntrySerializeCode::TreasuryGovernance => Ok(EntryOwned::TreasuryGovernance(
+            unpack_treasury_governance(codec)?,
+        )),
+        EntrySerializeCode::ParametersGovernance => Ok(EntryOwned::ParametersGovernance(
+            unpack_parameters_governance(codec)?,
+        )),
+        EntrySerializeCode::ConsensusNonce => {
+            let nonce = codec.get_bytes(32)?;
+            Ok(EntryOwned::ConsensusNonce(PraosNonce::from_output_array(
+                nonce.try_into().unwrap(),
+            )))
         }
         EntrySerializeCode::SerializationEnd => Ok(EntryOwned::StopEntry),
     }
@@ -1142,7 +1529,12 @@ impl DeserializeFromSlice for Ledger {
 #[cfg(test)]
 pub mod test {
     use super::*;
-    use crate::testing::{ConfigBuilder, LedgerBuilder, StakePoolBuilder};
+    use crate::testing::{
+        decrypt_tally,
+        scenario::{prepare_scenario, proposal, vote_plan, wallet},
+        ConfigBuilder, LedgerBuilder, StakePoolBuilder, VoteTestGen,
+    };
+    use crate::tokens::name::TOKEN_NAME_MAX_SIZE;
     use cardano_legacy_address::Addr;
     use chain_crypto::Blake2b256;
     use quickcheck::{quickcheck, TestResult};
@@ -1272,6 +1664,39 @@ pub mod test {
         assert_eq!(last_rewards, deserialize_last_rewards);
     }
 
+    #[test]
+    pub fn governance_pack_unpack_bijection() {
+        let criteria = GovernanceAcceptanceCriteria {
+            minimum_stake_participation: None,
+            minimum_approval: Some(Ratio {
+                numerator: 2,
+                denominator: std::num::NonZeroU64::new(3).unwrap(),
+            }),
+            blank: vote::Choice::new(2),
+            favorable: vote::Choice::new(0),
+            rejection: vote::Choice::new(1),
+            options: vote::Options::new_length(4).unwrap(),
+        };
+
+        let mut treasury = TreasuryGovernance::new();
+        treasury.set_acceptance_criteria(
+            TreasuryGovernanceActionType::TransferToRewards,
+            criteria.clone(),
+        );
+        let mut parameters = ParametersGovernance::new();
+        parameters.set_default_acceptance_criteria(criteria);
+        parameters.logs_register(ParametersGovernanceAction::RewardAdd { value: Value(10) });
+
+        let mut codec = Codec::new(Vec::new());
+        pack_treasury_governance(&treasury, &mut codec).unwrap();
+        pack_parameters_governance(&parameters, &mut codec).unwrap();
+
+        let inner = codec.into_inner();
+        let mut codec = Codec::new(inner.as_slice());
+        assert!(unpack_treasury_governance(&mut codec).unwrap() == treasury);
+        assert!(unpack_parameters_governance(&mut codec).unwrap() == parameters);
+    }
+
     #[test]
     pub fn pots_entry_pack_unpack_bijection() {
         for entry_value in [
@@ -1379,6 +1804,100 @@ pub mod test {
         assert_eq!(ledger, other_ledger);
     }
 
+    #[test]
+    pub fn ledger_with_votes_serialize_deserialize_bijection() {
+        let mut rng = crate::testing::TestGen::rand();
+        let voting_token = TokenName::try_from(vec![0u8; TOKEN_NAME_MAX_SIZE]).unwrap();
+        let members = VoteTestGen::committee_members_manager(3, 2);
+
+        let (mut test_ledger, controller) = prepare_scenario()
+            .with_config(
+                ConfigBuilder::new()
+                    .with_fee(LinearFee::new(1, 1, 1))
+                    .with_rewards(Value(1000)),
+            )
+            .with_initials(vec![wallet("Alice")
+                .with(1_000)
+                .with_token(voting_token, 1_000)
+                .owns("stake_pool")
+                .committee_member()])
+            .with_vote_plans(vec![
+                vote_plan("public")
+                    .owner("Alice")
+                    .consecutive_epoch_dates()
+                    .with_proposal(
+                        proposal(VoteTestGen::external_proposal_id())
+                            .options(3)
+                            .action_parameters_no_op(),
+                    ),
+                vote_plan("private")
+                    .owner("Alice")
+                    .consecutive_epoch_dates()
+                    .payload_type(vote::PayloadType::Private)
+                    .committee_keys(members.members_keys())
+                    .with_proposal(
+                        proposal(VoteTestGen::external_proposal_id())
+                            .options(3)
+                            .action_off_chain(),
+                    ),
+            ])
+            .build()
+            .unwrap();
+
+        let mut alice = controller.wallet("Alice").unwrap();
+        let public = controller.vote_plan("public").unwrap();
+        let private = controller.vote_plan("private").unwrap();
+        controller
+            .cast_vote_public(
+                &alice,
+                &public,
+                &public.proposal(0).id(),
+                vote::Choice::new(1),
+                &mut test_ledger,
+            )
+            .unwrap();
+        alice.confirm_transaction();
+        controller
+            .cast_vote_private(
+                &alice,
+                &private,
+                &private.proposal(0).id(),
+                vote::Choice::new(1),
+                &mut test_ledger,
+                &mut rng,
+            )
+            .unwrap();
+        alice.confirm_transaction();
+
+        test_ledger.fast_forward_to(BlockDate {
+            epoch: 1,
+            slot_id: 1,
+        });
+        controller
+            .tally_vote_public(&alice, &public, &mut test_ledger)
+            .unwrap();
+        alice.confirm_transaction();
+
+        let private_status = test_ledger
+            .ledger
+            .active_vote_plans()
+            .into_iter()
+            .find(|status| status.id == VotePlan::from(private.clone()).to_id())
+            .unwrap();
+        let shares = decrypt_tally(&private_status, &members).unwrap();
+        controller
+            .tally_vote_private(&alice, &private, shares, &mut test_ledger)
+            .unwrap();
+
+        let ledger: Ledger = test_ledger.into();
+        let mut vec = Vec::new();
+        ledger.serialize(&mut Codec::new(&mut vec)).unwrap();
+
+        let mut codec = Codec::new(vec.as_slice());
+        let other_ledger = Ledger::deserialize_from_slice(&mut codec).unwrap();
+        assert_eq!(ledger, other_ledger);
+    }
+
     #[cfg(test)]
     fn pack_unpack_bijection<T, Pack, Unpack>(
         pack_method: &Pack,
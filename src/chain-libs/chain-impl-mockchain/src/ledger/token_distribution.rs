@@ -4,7 +4,7 @@ use crate::{
     tokens::identifier::TokenIdentifier,
     value::Value,
 };
-use imhamt::Hamt;
+use imhamt::{Hamt, HamtIter};
 use std::collections::hash_map::DefaultHasher;
 
 #[derive(PartialEq, Eq)]
@@ -74,4 +74,8 @@ impl TokenTotals {
     pub fn get_total(&self, token: &TokenIdentifier) -> Option<Value> {
         self.0.lookup(token).copied()
     }
+
+    pub(crate) fn iter(&self) -> HamtIter<'_, TokenIdentifier, Value> {
+        self.0.iter()
+    }
 }
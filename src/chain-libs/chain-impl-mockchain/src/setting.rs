@@ -261,6 +261,12 @@ impl Settings {
             params.push(ConfigParam::AddBftLeader(bft_leader.clone()));
         }
         params.push(ConfigParam::LinearFee(self.linear_fees.clone()));
+        params.push(ConfigParam::PerCertificateFees(
+            self.linear_fees.per_certificate_fees,
+        ));
+        params.push(ConfigParam::PerVoteCertificateFees(
+            self.linear_fees.per_vote_certificate_fees,
+        ));
         params.push(ConfigParam::ProposalExpiration(self.proposal_expiration));
         params.push(ConfigParam::TransactionMaxExpiryEpochs(
             self.transaction_max_expiry_epochs,
@@ -274,8 +280,41 @@ impl Settings {
             Some(p) => params.push(ConfigParam::TreasuryParams(*p)),
             None => (),
         };
+        params.push(ConfigParam::FeesInTreasury(
+            self.fees_goes_to == FeesGoesTo::Treasury,
+        ));
+        match &self.rewards_limit {
+            rewards::Limit::None => params.push(ConfigParam::RewardLimitNone),
+            rewards::Limit::ByStakeAbsolute(ratio) => {
+                params.push(ConfigParam::RewardLimitByAbsoluteStake(*ratio))
+            }
+        };
+        if let Some(capping) = self.pool_participation_capping {
+            params.push(ConfigParam::PoolRewardParticipationCapping(capping));
+        }
+        // BFT leaders are promoted to committee when they are added already
+        let mut leader_committees: Vec<CommitteeId> = self
+            .bft_leaders
+            .iter()
+            .map(|leader| leader.as_public_key().clone().into())
+            .collect();
+        for committee_id in self.committees.iter() {
+            match leader_committees.iter().position(|id| id == committee_id) {
+                Some(index) => {
+                    leader_committees.remove(index);
+                }
+                None => params.push(ConfigParam::AddCommitteeId(*committee_id)),
+            }
+        }
 
-        debug_assert_eq!(self, &Settings::new().try_apply(&params).unwrap());
+        // the consensus nonce is not a configuration parameter
+        debug_assert_eq!(
+            self,
+            &Settings {
+                consensus_nonce: self.consensus_nonce.clone(),
+                ..Settings::new().try_apply(&params).unwrap()
+            }
+        );
 
         params
     }
@@ -331,6 +370,9 @@ impl Default for Settings {
 #[cfg(test)]
 mod tests {
     use super::{FeesGoesTo, Settings};
+    use crate::{
+        config::ConfigParam, fragment::config::ConfigParams, key::BftLeaderId, vote::CommitteeId,
+    };
     use quickcheck::{Arbitrary, Gen};
 
     impl Arbitrary for FeesGoesTo {
@@ -348,4 +390,27 @@ mod tests {
             Settings::new()
         }
     }
+
+    #[test]
+    fn config_params_round_trip_with_bft_leader() {
+        let leader = BftLeaderId::from([1; 32]);
+        let committee = CommitteeId::from([2; CommitteeId::COMMITTEE_ID_SIZE]);
+        let mut params = ConfigParams::new();
+        params.push(ConfigParam::AddBftLeader(leader.clone()));
+        params.push(ConfigParam::AddCommitteeId(committee));
+        let settings = Settings::new().try_apply(&params).unwrap();
+
+        let leader_committee: CommitteeId = leader.as_public_key().clone().into();
+        assert_eq!(&*settings.committees, &[leader_committee, committee]);
+
+        let params = settings.config_params();
+        assert_eq!(
+            params
+                .iter()
+                .filter(|param| matches!(param, ConfigParam::AddCommitteeId(_)))
+                .count(),
+            1
+        );
+        assert_eq!(Settings::new().try_apply(&params).unwrap(), settings);
+    }
 }
@@ -16,7 +16,6 @@ use crate::{
         SingleAccountBindingSignature, TxBuilder, TxBuilderState, Witness,
     },
     value::Value,
-    vote::PayloadType,
 };
 
 use std::iter;
@@ -317,9 +316,10 @@ pub fn tally_sign(
     let auth_data = builder.get_auth_data();
     let signature = SingleAccountBindingSignature::new(&auth_data, |d| key.sign_slice(d.0));
 
-    match payload_type {
-        PayloadType::Public => TallyProof::Public { id, signature },
-        PayloadType::Private => TallyProof::Private { id, signature },
+    if payload_type.is_private() {
+        TallyProof::Private { id, signature }
+    } else {
+        TallyProof::Public { id, signature }
     }
 }
 
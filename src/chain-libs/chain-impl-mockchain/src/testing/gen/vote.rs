@@ -16,7 +16,7 @@ use crate::{
 };
 use chain_core::property::BlockDate as BlockDateProp;
 use chain_crypto::digest::DigestOf;
-use chain_vote::{Crs, ElectionPublicKey, Vote};
+use chain_vote::{Crs, Vote};
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 use rand_core::{CryptoRng, RngCore};
@@ -150,8 +150,7 @@ impl VoteTestGen {
         choice: Choice,
         rng: &mut R,
     ) -> Payload {
-        let encrypting_key =
-            ElectionPublicKey::from_participants(vote_plan.committee_public_keys());
+        let encrypting_key = vote_plan.election_public_key();
 
         let crs = Crs::from_hash(vote_plan.to_id().as_ref());
         let (encrypted_vote, proof) = encrypting_key.encrypt_and_prove_vote(
@@ -234,7 +234,7 @@ impl Controller {
             test_ledger,
             |vote_plan, _proposal| match vote_plan.payload_type() {
                 PayloadType::Public => Payload::Public { choice },
-                PayloadType::Private => panic!("this is a private vote plan"),
+                _ => panic!("this is a private vote plan"),
             },
         )
     }
@@ -261,6 +261,9 @@ impl Controller {
                 PayloadType::Private => {
                     VoteTestGen::private_vote_cast_payload_for(vote_plan, proposal, choice, rng)
                 }
+                PayloadType::Approval | PayloadType::Ranked => {
+                    panic!("this vote plan does not accept single choice ballots")
+                }
             },
         )
     }
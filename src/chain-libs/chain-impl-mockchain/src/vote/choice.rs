@@ -1,5 +1,7 @@
+use chain_core::{packer::Codec, property::ReadError};
 use core::ops::Range;
 use thiserror::Error;
+use typed_bytes::ByteBuilder;
 
 /// error that may occur when creating a new `Options` using
 /// the `new_length` function.
@@ -12,13 +14,30 @@ pub struct InvalidOptionsLength {
     num_choices: u8,
 }
 
+/// error that may occur when creating a new `Options` using
+/// the `new_multi_selection` function.
+///
+/// On top of the constraints of `new_length`, the maximum number of
+/// selections must be between `1` and the number of available choices.
+#[derive(Debug, Error)]
+#[error("Invalid multi selection options: {max_selections} selections among {num_choices} choices")]
+pub struct InvalidMaxSelections {
+    num_choices: u8,
+    max_selections: u8,
+}
+
 /// options for the vote
 ///
 /// currently this is a 4bits structure, allowing up to 16 choices
 /// however we may allow more complex object to be set in
+///
+/// The options also bound the number of choices a voter can make in
+/// a single ballot: the maximum number of approved options of an approval
+/// vote, or the number of ranks of a ranked vote.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Options {
     options_range: Range<u8>,
+    max_selections: u8,
 }
 
 /// a choice
@@ -31,6 +50,10 @@ pub struct Choice(u8);
 impl Options {
     const NUM_CHOICES_MAX: u8 = 0b0001_0000;
 
+    /// set in the serialized number of choices when it is followed by
+    /// the maximum number of selections
+    const MULTI_SELECTION_FLAG: u8 = 0b1000_0000;
+
     /// create a new `Options` with the given number of available choices
     ///
     /// available choices will go from `0` to `num_choices` not included.
@@ -40,17 +63,65 @@ impl Options {
                 start: 0,
                 end: num_choices,
             };
-            Ok(Self { options_range })
+            Ok(Self {
+                options_range,
+                max_selections: 1,
+            })
         } else {
             Err(InvalidOptionsLength { num_choices })
         }
     }
 
+    /// create a new `Options` with the given number of available choices,
+    /// where a ballot may select up to `max_selections` of them.
+    pub fn new_multi_selection(
+        num_choices: u8,
+        max_selections: u8,
+    ) -> Result<Self, InvalidMaxSelections> {
+        match Self::new_length(num_choices) {
+            Ok(options) if max_selections > 0 && max_selections <= num_choices => Ok(Self {
+                max_selections,
+                ..options
+            }),
+            _ => Err(InvalidMaxSelections {
+                num_choices,
+                max_selections,
+            }),
+        }
+    }
+
     /// get the byte representation of the `Options`
     pub(crate) fn as_byte(&self) -> u8 {
         self.options_range.end
     }
 
+    /// the maximum number of choices of a single ballot
+    pub fn max_selections(&self) -> u8 {
+        self.max_selections
+    }
+
+    /// the options with a single selection are serialized as the number of
+    /// choices, so the serialization of the existing vote plans is unchanged
+    pub(crate) fn serialize_in<T>(&self, bb: ByteBuilder<T>) -> ByteBuilder<T> {
+        if self.max_selections == 1 {
+            bb.u8(self.as_byte())
+        } else {
+            bb.u8(self.as_byte() | Self::MULTI_SELECTION_FLAG)
+                .u8(self.max_selections)
+        }
+    }
+
+    pub(crate) fn read(codec: &mut Codec<&[u8]>) -> Result<Self, ReadError> {
+        let num_choices = codec.get_u8()?;
+        if num_choices & Self::MULTI_SELECTION_FLAG == 0 {
+            Self::new_length(num_choices).map_err(|e| ReadError::StructureInvalid(e.to_string()))
+        } else {
+            let max_selections = codec.get_u8()?;
+            Self::new_multi_selection(num_choices & !Self::MULTI_SELECTION_FLAG, max_selections)
+                .map_err(|e| ReadError::StructureInvalid(e.to_string()))
+        }
+    }
+
     /// validate the given `Choice` against the available `Options`
     ///
     /// returns `true` if the choice is valid, `false` otherwise. By _valid_
@@ -82,7 +153,14 @@ mod property {
 
     impl Arbitrary for Options {
         fn arbitrary<G: Gen>(g: &mut G) -> Self {
-            Self::new_length(u8::arbitrary(g)).unwrap_or_else(|_| Options::new_length(1).unwrap())
+            let options = Self::new_length(u8::arbitrary(g))
+                .unwrap_or_else(|_| Options::new_length(1).unwrap());
+            if bool::arbitrary(g) {
+                options
+            } else {
+                let max_selections = u8::arbitrary(g) % options.as_byte() + 1;
+                Self::new_multi_selection(options.as_byte(), max_selections).unwrap()
+            }
         }
     }
 
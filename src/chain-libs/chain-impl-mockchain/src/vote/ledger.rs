@@ -46,6 +46,19 @@ pub enum VotePlanLedgerError {
 
     #[error("Private vote plan must contain at least one committee member key")]
     VotePlanMissingCommitteeMemberKey,
+
+    #[error("Invalid committee threshold {threshold}, the vote plan has {members} committee member keys")]
+    VotePlanInvalidCommitteeThreshold { threshold: u8, members: usize },
+
+    #[error("The committee member keys are not shares of the same election key for threshold {threshold}")]
+    VotePlanInconsistentCommitteeKeys { threshold: u8 },
+
+    #[error("{payload_type:?} vote plan proposal {proposal_index} cannot allow {max_selections} selections per ballot")]
+    VotePlanInvalidMaxSelections {
+        payload_type: PayloadType,
+        proposal_index: usize,
+        max_selections: u8,
+    },
 }
 
 impl VotePlanLedger {
@@ -114,9 +127,43 @@ impl VotePlanLedger {
             });
         }
 
-        if let PayloadType::Private = vote_plan.payload_type() {
-            if vote_plan.committee_public_keys().is_empty() {
-                return Err(VotePlanLedgerError::VotePlanMissingCommitteeMemberKey);
+        if vote_plan.payload_type().is_private() && vote_plan.committee_public_keys().is_empty() {
+            return Err(VotePlanLedgerError::VotePlanMissingCommitteeMemberKey);
+        }
+
+        // only the approval and ranked ballots select several options
+        if let PayloadType::Public | PayloadType::Private = vote_plan.payload_type() {
+            if let Some((proposal_index, proposal)) = vote_plan
+                .proposals()
+                .iter()
+                .enumerate()
+                .find(|(_, proposal)| proposal.options().max_selections() != 1)
+            {
+                return Err(VotePlanLedgerError::VotePlanInvalidMaxSelections {
+                    payload_type: vote_plan.payload_type(),
+                    proposal_index,
+                    max_selections: proposal.options().max_selections(),
+                });
+            }
+        }
+
+        if let Some(threshold) = vote_plan.committee_threshold() {
+            let members = vote_plan.committee_public_keys().len();
+            if threshold.get() as usize > members {
+                return Err(VotePlanLedgerError::VotePlanInvalidCommitteeThreshold {
+                    threshold: threshold.get(),
+                    members,
+                });
+            }
+            if chain_vote::ElectionPublicKey::from_public_shares(
+                vote_plan.committee_public_keys(),
+                threshold.get() as usize,
+            )
+            .is_none()
+            {
+                return Err(VotePlanLedgerError::VotePlanInconsistentCommitteeKeys {
+                    threshold: threshold.get(),
+                });
             }
         }
 
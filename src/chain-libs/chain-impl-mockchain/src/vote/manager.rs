@@ -20,7 +20,7 @@ use imhamt::Hamt;
 use thiserror::Error;
 
 use std::collections::{hash_map::DefaultHasher, HashSet};
-use std::num::NonZeroU64;
+use std::num::{NonZeroU64, NonZeroU8};
 use std::sync::Arc;
 
 use super::{PrivateTallyState, TallyError};
@@ -64,6 +64,7 @@ enum ProposalManagers {
 #[derive(Clone, PartialEq, Eq)]
 struct ProposalManager {
     votes_by_voters: Hamt<DefaultHasher, account::Identifier, ()>,
+    payload_type: PayloadType,
     options: Options,
     tally: IncrementalTally,
     action: VoteAction,
@@ -149,6 +150,7 @@ impl ProposalManager {
 
         Self {
             votes_by_voters: Hamt::new(),
+            payload_type: PayloadType::Public,
             options: proposal.options().clone(),
             tally: IncrementalTally::Public(results),
             action: proposal.action().clone(),
@@ -162,12 +164,23 @@ impl ProposalManager {
     /// of verification in the future about the content of the vote (if
     /// possible : ZK is not necessarily allowing this).
     ///
-    fn new_private(proposal: &Proposal, election_pk: ElectionPublicKey, crs: Crs) -> Self {
-        let tally_size = proposal.options().choice_range().clone().max().unwrap() as usize + 1;
+    /// The ranked votes are tallied with one entry per rank and option.
+    fn new_private(
+        proposal: &Proposal,
+        payload_type: PayloadType,
+        election_pk: ElectionPublicKey,
+        crs: Crs,
+    ) -> Self {
+        let num_choices = proposal.options().choice_range().clone().max().unwrap() as usize + 1;
+        let tally_size = match payload_type {
+            PayloadType::Ranked => num_choices * proposal.options().max_selections() as usize,
+            _ => num_choices,
+        };
 
         let encrypted_tally = EncryptedTally::new(tally_size, election_pk, crs);
         Self {
             votes_by_voters: Hamt::new(),
+            payload_type,
             options: proposal.options().clone(),
             tally: IncrementalTally::Private(encrypted_tally),
             action: proposal.action().clone(),
@@ -222,7 +235,7 @@ impl ProposalManager {
                 (IncrementalTally::Private(_), ValidatedPayload::Public(_)) => {
                     return Err(VoteError::InvalidPayloadType {
                         received: PayloadType::Public,
-                        expected: PayloadType::Private,
+                        expected: self.payload_type,
                     })
                 }
                 (IncrementalTally::Decrypted(_), _) => {
@@ -236,6 +249,7 @@ impl ProposalManager {
         Ok(Self {
             votes_by_voters,
             tally,
+            payload_type: self.payload_type,
             options: self.options.clone(),
             action: self.action.clone(),
         })
@@ -260,8 +274,8 @@ impl ProposalManager {
 
         match payload {
             Payload::Public { choice } => Ok(ValidatedPayload::Public(choice)),
-            Payload::Private { .. } => Err(VoteError::InvalidPayloadType {
-                received: PayloadType::Private,
+            payload => Err(VoteError::InvalidPayloadType {
+                received: payload.payload_type(),
                 expected: PayloadType::Public,
             }),
         }
@@ -278,32 +292,55 @@ impl ProposalManager {
 
         let payload = cast.into_payload();
 
-        match payload {
-            Payload::Public { .. } => Err(VoteError::InvalidPayloadType {
-                received: PayloadType::Public,
-                expected: PayloadType::Private,
-            }),
-            Payload::Private {
-                encrypted_vote,
-                proof,
-            } => {
-                let actual_size = encrypted_vote.as_inner().len();
-                let expected_size = self.options.choice_range().len();
-                if actual_size != expected_size {
-                    Err(VoteError::PrivateVoteInvalidSize {
-                        expected: expected_size,
-                        actual: actual_size,
-                    })
-                } else {
-                    Ok(ValidatedPayload::Private(Ballot::try_from_vote_and_proof(
-                        encrypted_vote.as_inner().clone(),
-                        proof.as_inner(),
-                        crs,
-                        election_pk,
-                    )?))
-                }
+        if payload.payload_type() != self.payload_type {
+            return Err(VoteError::InvalidPayloadType {
+                received: payload.payload_type(),
+                expected: self.payload_type,
+            });
+        }
+
+        let num_choices = self.options.choice_range().len();
+        let max_selections = self.options.max_selections() as usize;
+        let (encrypted_vote, expected_size) = match &payload {
+            Payload::Public { .. } => unreachable!("the payload type was checked above"),
+            Payload::Private { encrypted_vote, .. } | Payload::Approval { encrypted_vote, .. } => {
+                (encrypted_vote.as_inner(), num_choices)
             }
+            Payload::Ranked { encrypted_vote, .. } => {
+                (encrypted_vote.as_inner(), num_choices * max_selections)
+            }
+        };
+        if encrypted_vote.len() != expected_size {
+            return Err(VoteError::PrivateVoteInvalidSize {
+                expected: expected_size,
+                actual: encrypted_vote.len(),
+            });
         }
+
+        let ballot = match &payload {
+            Payload::Public { .. } => unreachable!("the payload type was checked above"),
+            Payload::Private { proof, .. } => Ballot::try_from_vote_and_proof(
+                encrypted_vote.clone(),
+                proof.as_inner(),
+                crs,
+                election_pk,
+            ),
+            Payload::Approval { proof, .. } => Ballot::try_from_approval_vote_and_proof(
+                encrypted_vote.clone(),
+                proof.as_inner(),
+                crs,
+                election_pk,
+                max_selections,
+            ),
+            Payload::Ranked { proof, .. } => Ballot::try_from_ranked_vote_and_proof(
+                encrypted_vote.clone(),
+                proof.as_inner(),
+                crs,
+                election_pk,
+                num_choices,
+            ),
+        }?;
+        Ok(ValidatedPayload::Private(ballot))
     }
 
     #[must_use = "Compute the PublicTally in a new ProposalManager, does not modify self"]
@@ -329,6 +366,7 @@ impl ProposalManager {
 
         Ok(Self {
             votes_by_voters: self.votes_by_voters.clone(),
+            payload_type: self.payload_type,
             options: self.options.clone(),
             tally: self.tally.clone(),
             action: self.action.clone(),
@@ -338,6 +376,7 @@ impl ProposalManager {
     pub fn finalize_private_tally<F>(
         &self,
         committee_pks: &[committee::MemberPublicKey],
+        committee_threshold: Option<NonZeroU8>,
         decrypted_proposal: &DecryptedPrivateTallyProposal,
         governance: &Governance,
         token_distribution: &TokenDistribution<TokenIdentifier>,
@@ -357,18 +396,59 @@ impl ProposalManager {
         let verifiable_tally = chain_vote::Tally {
             votes: decrypted_proposal.tally_result.to_vec(),
         };
-        if !verifiable_tally.verify(
-            encrypted_tally,
-            committee_pks,
-            &decrypted_proposal.decrypt_shares,
-        ) {
+        let verified = match committee_threshold {
+            None => verifiable_tally.verify(
+                encrypted_tally,
+                committee_pks,
+                &decrypted_proposal.decrypt_shares,
+            ),
+            Some(threshold) => {
+                // the tally certificate does not carry the index of the members,
+                // identify them from their decryption shares instead. Shares matching
+                // no member are skipped, any `threshold` valid shares are enough
+                let decrypt_shares: Vec<_> = decrypted_proposal
+                    .decrypt_shares
+                    .iter()
+                    .filter_map(|share| {
+                        share
+                            .find_member(encrypted_tally, committee_pks)
+                            .map(|member| (member, share.clone()))
+                    })
+                    .collect();
+                verifiable_tally.verify_threshold(
+                    encrypted_tally,
+                    committee_pks,
+                    threshold.get() as usize,
+                    &decrypt_shares,
+                )
+            }
+        };
+        if !verified {
             return Err(TallyError::InvalidDecryption);
         }
 
-        let mut result = TallyResult::new(self.options.clone());
-        for (choice, &weight) in decrypted_proposal.tally_result.iter().enumerate() {
+        let mut result = match self.payload_type {
+            PayloadType::Ranked => TallyResult::new_ranked(self.options.clone()),
+            // an approval vote adds its weight to every approved choice, the
+            // participation is the total weight added to the encrypted tally
+            PayloadType::Approval => {
+                TallyResult::new_approval(self.options.clone(), encrypted_tally.max_stake())
+            }
+            _ => TallyResult::new(self.options.clone()),
+        };
+        let num_choices = self.options.choice_range().len();
+        for (index, &weight) in decrypted_proposal.tally_result.iter().enumerate() {
+            let (rank, choice) = if result.is_ranked() {
+                (index / num_choices, index % num_choices)
+            } else {
+                (0, index)
+            };
             result
-                .add_vote(Choice::new(u8::try_from(choice).unwrap()), weight)
+                .add_ranked_vote(
+                    u8::try_from(rank).unwrap(),
+                    Choice::new(u8::try_from(choice).unwrap()),
+                    weight,
+                )
                 .map_err(|error| match error {
                     VoteError::InvalidChoice { options, choice } => {
                         TallyError::InvalidChoice { options, choice }
@@ -383,6 +463,7 @@ impl ProposalManager {
 
         Ok(Self {
             votes_by_voters: self.votes_by_voters.clone(),
+            payload_type: self.payload_type,
             options: self.options.clone(),
             tally: IncrementalTally::Decrypted(result),
             action: self.action.clone(),
@@ -482,11 +563,9 @@ impl ProposalManagers {
                     .collect();
                 Self::Public { managers }
             }
-            PayloadType::Private => {
+            payload_type @ (PayloadType::Private | PayloadType::Approval | PayloadType::Ranked) => {
                 let crs = Arc::new(Crs::from_hash(plan.to_id().as_ref()));
-                let election_pk = Arc::new(ElectionPublicKey::from_participants(
-                    plan.committee_public_keys(),
-                ));
+                let election_pk = Arc::new(plan.election_public_key());
 
                 let managers = plan
                     .proposals()
@@ -494,6 +573,7 @@ impl ProposalManagers {
                     .map(|proposal| {
                         ProposalManager::new_private(
                             proposal,
+                            payload_type,
                             (*election_pk).clone(),
                             (*crs).clone(),
                         )
@@ -614,6 +694,7 @@ impl ProposalManagers {
     pub fn finalize_private_tally<F>(
         &self,
         committee_pks: &[committee::MemberPublicKey],
+        committee_threshold: Option<NonZeroU8>,
         decrypted_tally: &DecryptedPrivateTally,
         governance: &Governance,
         token_distribution: &TokenDistribution<TokenIdentifier>,
@@ -634,6 +715,7 @@ impl ProposalManagers {
                 {
                     proposals.push(proposal_manager.finalize_private_tally(
                         committee_pks,
+                        committee_threshold,
                         decrypted_proposal,
                         governance,
                         token_distribution,
@@ -667,6 +749,53 @@ impl VotePlanManager {
         }
     }
 
+    /// restore the manager of a vote plan with the voters and the tally of each
+    /// of its proposals, given in the order of the proposals of the plan.
+    ///
+    /// Returns `None` if they do not match the proposals of the plan.
+    pub(crate) fn restore(
+        plan: VotePlan,
+        committee: HashSet<CommitteeId>,
+        proposals: Vec<(
+            Hamt<DefaultHasher, account::Identifier, ()>,
+            IncrementalTally,
+        )>,
+    ) -> Option<Self> {
+        let mut manager = Self::new(plan, committee);
+        let managers = manager.proposal_managers.managers_mut();
+        if managers.len() != proposals.len() {
+            return None;
+        }
+
+        for (proposal_manager, (votes_by_voters, tally)) in managers.iter_mut().zip(proposals) {
+            match (&proposal_manager.tally, &tally) {
+                (IncrementalTally::Public(_), IncrementalTally::Public(_))
+                | (IncrementalTally::Private(_), IncrementalTally::Private(_))
+                | (IncrementalTally::Private(_), IncrementalTally::Decrypted(_)) => {}
+                _ => return None,
+            }
+            proposal_manager.votes_by_voters = votes_by_voters;
+            proposal_manager.tally = tally;
+        }
+
+        Some(manager)
+    }
+
+    /// the voters and the tally of each proposal, in the order of the proposals of the plan
+    pub(crate) fn proposal_states(
+        &self,
+    ) -> impl Iterator<
+        Item = (
+            &Hamt<DefaultHasher, account::Identifier, ()>,
+            &IncrementalTally,
+        ),
+    > {
+        self.proposal_managers
+            .managers()
+            .iter()
+            .map(|manager| (&manager.votes_by_voters, &manager.tally))
+    }
+
     pub fn id(&self) -> &VotePlanId {
         &self.id
     }
@@ -867,6 +996,7 @@ impl VotePlanManager {
 
         let proposal_managers = self.proposal_managers.finalize_private_tally(
             committee_pks,
+            self.plan.committee_threshold(),
             decrypted_tally,
             governance,
             &token_distribution.token(self.plan.voting_token()),
@@ -980,10 +1110,14 @@ mod tests {
         let identifier = TestGen::identifier();
 
         let crs = Crs::from_hash(vote_plan.to_id().as_ref());
-        let election_pk = ElectionPublicKey::from_participants(vote_plan.committee_public_keys());
+        let election_pk = vote_plan.election_public_key();
 
-        let proposal_manager =
-            ProposalManager::new_private(vote_plan.proposals().get(0).unwrap(), election_pk, crs);
+        let proposal_manager = ProposalManager::new_private(
+            vote_plan.proposals().get(0).unwrap(),
+            PayloadType::Private,
+            election_pk,
+            crs,
+        );
 
         assert_eq!(
             proposal_manager
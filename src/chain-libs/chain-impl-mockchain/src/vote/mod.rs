@@ -12,13 +12,17 @@ mod privacy;
 mod status;
 mod tally;
 
+pub(crate) use self::manager::IncrementalTally;
 pub use self::{
-    choice::{Choice, Options},
+    choice::{Choice, InvalidMaxSelections, InvalidOptionsLength, Options},
     committee::CommitteeId,
     ledger::{VotePlanLedger, VotePlanLedgerError},
     manager::{ValidatedPayload, VoteError, VotePlanManager},
-    payload::{EncryptedVote, Payload, PayloadType, ProofOfCorrectVote, TryFromIntError},
-    privacy::encrypt_vote,
+    payload::{
+        EncryptedVote, Payload, PayloadType, ProofOfCorrectApprovalVote, ProofOfCorrectRankedVote,
+        ProofOfCorrectVote, TryFromIntError,
+    },
+    privacy::{encrypt_approval_vote, encrypt_ranked_vote, encrypt_vote},
     status::{VotePlanStatus, VoteProposalStatus},
     tally::{PrivateTallyState, Tally, TallyError, TallyResult, Weight},
 };
@@ -11,6 +11,9 @@ use typed_bytes::{ByteArray, ByteBuilder};
 /// this defines how the vote must be published on chain.
 /// Be careful because the default is set to `Public`.
 ///
+/// The `Approval` and `Ranked` ballots are encrypted like the `Private` ones,
+/// and their tally is decrypted by the committee in the same way.
+///
 /// ```
 /// use chain_impl_mockchain::vote::PayloadType;
 /// assert_eq!(PayloadType::Public, PayloadType::default());
@@ -23,6 +26,10 @@ pub enum PayloadType {
     #[default]
     Public = 1,
     Private = 2,
+    /// encrypted ballots approving up to `Options::max_selections` options
+    Approval = 3,
+    /// encrypted ballots ranking `Options::max_selections` options
+    Ranked = 4,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -34,11 +41,25 @@ pub enum Payload {
         encrypted_vote: EncryptedVote,
         proof: ProofOfCorrectVote,
     },
+    Approval {
+        encrypted_vote: EncryptedVote,
+        proof: ProofOfCorrectApprovalVote,
+    },
+    Ranked {
+        encrypted_vote: EncryptedVote,
+        proof: ProofOfCorrectRankedVote,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ProofOfCorrectVote(chain_vote::ProofOfCorrectVote);
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProofOfCorrectApprovalVote(chain_vote::ProofOfCorrectApprovalVote);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProofOfCorrectRankedVote(chain_vote::ProofOfCorrectRankedVote);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EncryptedVote(chain_vote::EncryptedVote);
 
@@ -62,10 +83,26 @@ impl Payload {
         }
     }
 
+    pub fn approval(encrypted_vote: EncryptedVote, proof: ProofOfCorrectApprovalVote) -> Self {
+        Self::Approval {
+            encrypted_vote,
+            proof,
+        }
+    }
+
+    pub fn ranked(encrypted_vote: EncryptedVote, proof: ProofOfCorrectRankedVote) -> Self {
+        Self::Ranked {
+            encrypted_vote,
+            proof,
+        }
+    }
+
     pub fn payload_type(&self) -> PayloadType {
         match self {
             Self::Public { .. } => PayloadType::Public,
             Self::Private { .. } => PayloadType::Private,
+            Self::Approval { .. } => PayloadType::Approval,
+            Self::Ranked { .. } => PayloadType::Ranked,
         }
     }
 
@@ -82,6 +119,18 @@ impl Payload {
             } => bb
                 .sub(|bb| encrypted_vote.serialize_in(bb))
                 .sub(|bb| proof.serialize_in(bb)),
+            Self::Approval {
+                encrypted_vote,
+                proof,
+            } => bb
+                .sub(|bb| encrypted_vote.serialize_in(bb))
+                .sub(|bb| proof.serialize_in(bb)),
+            Self::Ranked {
+                encrypted_vote,
+                proof,
+            } => bb
+                .sub(|bb| encrypted_vote.serialize_in(bb))
+                .sub(|bb| proof.serialize_in(bb)),
         }
     }
 
@@ -101,10 +150,34 @@ impl Payload {
                     proof,
                 })
             }
+            PayloadType::Approval => {
+                let encrypted_vote = EncryptedVote::read(codec)?;
+                let proof = ProofOfCorrectApprovalVote::read(codec)?;
+                Ok(Self::Approval {
+                    encrypted_vote,
+                    proof,
+                })
+            }
+            PayloadType::Ranked => {
+                let encrypted_vote = EncryptedVote::read(codec)?;
+                let proof = ProofOfCorrectRankedVote::read(codec)?;
+                Ok(Self::Ranked {
+                    encrypted_vote,
+                    proof,
+                })
+            }
         }
     }
 }
 
+impl PayloadType {
+    /// tells if the ballots of the vote plan are encrypted, in which case
+    /// the tally needs to be decrypted by the committee
+    pub fn is_private(self) -> bool {
+        !matches!(self, Self::Public)
+    }
+}
+
 impl ProofOfCorrectVote {
     pub(crate) fn from_inner(proof: chain_vote::ProofOfCorrectVote) -> Self {
         assert!(
@@ -136,6 +209,58 @@ impl ProofOfCorrectVote {
     }
 }
 
+impl ProofOfCorrectApprovalVote {
+    pub(crate) fn from_inner(proof: chain_vote::ProofOfCorrectApprovalVote) -> Self {
+        assert!(
+            proof.len() <= u8::MAX as usize,
+            "number of options is too large in an internally obtained proof"
+        );
+        Self(proof)
+    }
+
+    pub(super) fn as_inner(&self) -> &chain_vote::ProofOfCorrectApprovalVote {
+        &self.0
+    }
+
+    pub(crate) fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        bb.bytes(&self.0.to_bytes())
+    }
+
+    pub fn serialize(&self) -> ByteArray<Self> {
+        self.serialize_in(ByteBuilder::new()).finalize()
+    }
+
+    pub(crate) fn read(codec: &mut Codec<&[u8]>) -> Result<Self, ReadError> {
+        chain_vote::ProofOfCorrectApprovalVote::from_buffer(codec).map(Self)
+    }
+}
+
+impl ProofOfCorrectRankedVote {
+    pub(crate) fn from_inner(proof: chain_vote::ProofOfCorrectRankedVote) -> Self {
+        assert!(
+            proof.ranks() <= u8::MAX as usize && proof.options() <= u8::MAX as usize,
+            "number of options is too large in an internally obtained proof"
+        );
+        Self(proof)
+    }
+
+    pub(super) fn as_inner(&self) -> &chain_vote::ProofOfCorrectRankedVote {
+        &self.0
+    }
+
+    pub(crate) fn serialize_in(&self, bb: ByteBuilder<Self>) -> ByteBuilder<Self> {
+        bb.bytes(&self.0.to_bytes())
+    }
+
+    pub fn serialize(&self) -> ByteArray<Self> {
+        self.serialize_in(ByteBuilder::new()).finalize()
+    }
+
+    pub(crate) fn read(codec: &mut Codec<&[u8]>) -> Result<Self, ReadError> {
+        chain_vote::ProofOfCorrectRankedVote::from_buffer(codec).map(Self)
+    }
+}
+
 impl EncryptedVote {
     pub(crate) fn from_inner(vote: chain_vote::EncryptedVote) -> Self {
         Self(vote)
@@ -178,6 +303,8 @@ impl TryFrom<u8> for PayloadType {
             0 => Err(TryFromIntError::Zero),
             1 => Ok(Self::Public),
             2 => Ok(Self::Private),
+            3 => Ok(Self::Approval),
+            4 => Ok(Self::Ranked),
             _ => Err(TryFromIntError::InvalidValue { value }),
         }
     }
@@ -191,33 +318,39 @@ mod tests {
 
     impl Arbitrary for PayloadType {
         fn arbitrary<G: Gen>(g: &mut G) -> Self {
-            if g.next_u32() % 2 == 0 {
-                Self::Public
-            } else {
-                Self::Private
+            match g.next_u32() % 4 {
+                0 => Self::Public,
+                1 => Self::Private,
+                2 => Self::Approval,
+                _ => Self::Ranked,
             }
         }
     }
 
     impl Arbitrary for Payload {
         fn arbitrary<G: Gen>(g: &mut G) -> Self {
-            use chain_vote::{MemberCommunicationKey, MemberState, Vote};
+            use chain_vote::{ApprovalVote, MemberCommunicationKey, MemberState, RankedVote, Vote};
             use rand_core::SeedableRng;
 
-            match PayloadType::arbitrary(g) {
-                PayloadType::Public => Payload::public(Choice::arbitrary(g)),
+            let payload_type = PayloadType::arbitrary(g);
+            if payload_type == PayloadType::Public {
+                return Payload::public(Choice::arbitrary(g));
+            }
+
+            let mut seed = [0u8; 32];
+            g.fill_bytes(&mut seed);
+            let mut gen = rand_chacha::ChaCha20Rng::from_seed(seed);
+            let mc = MemberCommunicationKey::new(&mut gen);
+            let threshold = 1;
+            let h = Crs::from_hash(&seed);
+            let m = MemberState::new(&mut gen, threshold, &h, &[mc.to_public()], 0);
+            let participants = vec![m.public_key()];
+            let ek = ElectionPublicKey::from_participants(&participants);
+            let vote_options = 3;
+            let choice = g.next_u32() % vote_options;
+            match payload_type {
+                PayloadType::Public => unreachable!(),
                 PayloadType::Private => {
-                    let mut seed = [0u8; 32];
-                    g.fill_bytes(&mut seed);
-                    let mut gen = rand_chacha::ChaCha20Rng::from_seed(seed);
-                    let mc = MemberCommunicationKey::new(&mut gen);
-                    let threshold = 1;
-                    let h = Crs::from_hash(&seed);
-                    let m = MemberState::new(&mut gen, threshold, &h, &[mc.to_public()], 0);
-                    let participants = vec![m.public_key()];
-                    let ek = ElectionPublicKey::from_participants(&participants);
-                    let vote_options = 3;
-                    let choice = g.next_u32() % vote_options;
                     let (vote, proof) = ek.encrypt_and_prove_vote(
                         &mut gen,
                         &h,
@@ -228,6 +361,28 @@ mod tests {
                         ProofOfCorrectVote::from_inner(proof),
                     )
                 }
+                PayloadType::Approval => {
+                    let vote =
+                        ApprovalVote::new(vote_options as usize, &[choice as usize]).unwrap();
+                    let (vote, proof) = ek.encrypt_and_prove_approval_vote(&mut gen, &h, &vote, 2);
+                    Payload::approval(
+                        EncryptedVote::from_inner(vote),
+                        ProofOfCorrectApprovalVote::from_inner(proof),
+                    )
+                }
+                PayloadType::Ranked => {
+                    let second = (choice + 1) % vote_options;
+                    let vote = RankedVote::new(
+                        vote_options as usize,
+                        vec![choice as usize, second as usize],
+                    )
+                    .unwrap();
+                    let (vote, proof) = ek.encrypt_and_prove_ranked_vote(&mut gen, &h, &vote);
+                    Payload::ranked(
+                        EncryptedVote::from_inner(vote),
+                        ProofOfCorrectRankedVote::from_inner(proof),
+                    )
+                }
             }
         }
     }
@@ -1,5 +1,7 @@
-use crate::vote::{EncryptedVote, ProofOfCorrectVote};
-use chain_vote::{Crs, ElectionPublicKey, Vote};
+use crate::vote::{
+    EncryptedVote, ProofOfCorrectApprovalVote, ProofOfCorrectRankedVote, ProofOfCorrectVote,
+};
+use chain_vote::{ApprovalVote, Crs, ElectionPublicKey, RankedVote, Vote};
 use rand_core::{CryptoRng, RngCore};
 
 #[allow(dead_code)]
@@ -15,3 +17,33 @@ pub fn encrypt_vote<R: RngCore + CryptoRng>(
         ProofOfCorrectVote::from_inner(proof),
     )
 }
+
+#[allow(dead_code)]
+pub fn encrypt_approval_vote<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    crs: &Crs,
+    public_key: &ElectionPublicKey,
+    vote: &ApprovalVote,
+    max_selections: u8,
+) -> (EncryptedVote, ProofOfCorrectApprovalVote) {
+    let (ev, proof) =
+        public_key.encrypt_and_prove_approval_vote(rng, crs, vote, max_selections as usize);
+    (
+        EncryptedVote::from_inner(ev),
+        ProofOfCorrectApprovalVote::from_inner(proof),
+    )
+}
+
+#[allow(dead_code)]
+pub fn encrypt_ranked_vote<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    crs: &Crs,
+    public_key: &ElectionPublicKey,
+    vote: &RankedVote,
+) -> (EncryptedVote, ProofOfCorrectRankedVote) {
+    let (ev, proof) = public_key.encrypt_and_prove_ranked_vote(rng, crs, vote);
+    (
+        EncryptedVote::from_inner(ev),
+        ProofOfCorrectRankedVote::from_inner(proof),
+    )
+}
@@ -17,11 +17,20 @@ use super::VoteError;
 pub struct Weight(u64);
 
 /// the tally results
+///
+/// For the ranked votes, there are results for each rank, stored one rank
+/// after the other starting from the preferred choices.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TallyResult {
     results: Box<[Weight]>,
 
     options: Options,
+
+    ranked: bool,
+
+    /// total weight of the approval votes, which cannot be derived from the
+    /// results as a vote approves any number of choices
+    approval_participation: Option<Weight>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -61,6 +70,11 @@ impl Weight {
     fn saturating_add(self, other: Self) -> Self {
         Self(self.0.saturating_add(other.0))
     }
+
+    #[must_use = "Does not modify the internal state"]
+    fn saturating_mul(self, factor: u64) -> Self {
+        Self(self.0.saturating_mul(factor))
+    }
 }
 
 impl Tally {
@@ -122,15 +136,108 @@ impl TallyResult {
     pub fn new(options: Options) -> Self {
         let len = options.choice_range().len();
         let results = vec![Weight(0); len].into();
-        Self { results, options }
+        Self {
+            results,
+            options,
+            ranked: false,
+            approval_participation: None,
+        }
+    }
+
+    /// create the results of a ranked vote, with one result per choice
+    /// for each of the `Options::max_selections` ranks
+    pub fn new_ranked(options: Options) -> Self {
+        let len = options.choice_range().len() * options.max_selections() as usize;
+        let results = vec![Weight(0); len].into();
+        Self {
+            results,
+            options,
+            ranked: true,
+            approval_participation: None,
+        }
+    }
+
+    /// create the results of an approval vote, where `participation` is the
+    /// total weight of the votes
+    pub fn new_approval<W>(options: Options, participation: W) -> Self
+    where
+        W: Into<Weight>,
+    {
+        Self {
+            approval_participation: Some(participation.into()),
+            ..Self::new(options)
+        }
+    }
+
+    /// restore the results of a tally, `None` if there is not the expected
+    /// number of results for the options
+    pub(crate) fn from_results(
+        results: Vec<Weight>,
+        options: Options,
+        ranked: bool,
+        approval_participation: Option<Weight>,
+    ) -> Option<Self> {
+        let expected = match (ranked, approval_participation) {
+            (true, None) => Self::new_ranked(options),
+            (false, None) => Self::new(options),
+            (false, Some(participation)) => Self::new_approval(options, participation),
+            (true, Some(_)) => return None,
+        };
+        if results.len() != expected.results.len() {
+            return None;
+        }
+        Some(Self {
+            results: results.into(),
+            ..expected
+        })
     }
 
     pub fn results(&self) -> &[Weight] {
         &self.results
     }
 
+    pub fn is_ranked(&self) -> bool {
+        self.ranked
+    }
+
+    pub fn is_approval(&self) -> bool {
+        self.approval_participation.is_some()
+    }
+
+    /// the results of each rank, starting from the preferred choices.
+    ///
+    /// There is a single rank for the votes that are not ranked.
+    pub fn ranks(&self) -> impl Iterator<Item = &[Weight]> {
+        self.results.chunks(self.options.choice_range().len())
+    }
+
+    /// the Borda count of each choice: a choice gets `n - r` points for
+    /// every vote ranking it at the `r`-th (0-indexed) of the `n` ranks.
+    pub fn borda_scores(&self) -> Vec<Weight> {
+        let ranks = self.results.len() / self.options.choice_range().len();
+        let mut scores = vec![Weight(0); self.options.choice_range().len()];
+        for (rank, results) in self.ranks().enumerate() {
+            for (score, weight) in scores.iter_mut().zip(results.iter()) {
+                *score = score.saturating_add(weight.saturating_mul((ranks - rank) as u64));
+            }
+        }
+        scores
+    }
+
+    /// the total weight of the votes.
+    ///
+    /// For the ranked votes, every vote ranks exactly one preferred choice so
+    /// the participation is computed from the first rank. For the approval
+    /// votes, it is the total weight of the votes given on creation.
     pub fn participation(&self) -> Stake {
-        let s: u64 = self.results.iter().map(|w| w.0).sum();
+        let s: u64 = match self.approval_participation {
+            Some(participation) => participation.0,
+            None => self
+                .ranks()
+                .next()
+                .map(|results| results.iter().map(|w| w.0).sum())
+                .unwrap_or(0),
+        };
         Stake::from_value(Value(s))
     }
 
@@ -146,12 +253,34 @@ impl TallyResult {
     ///
     /// The function will fail if the `choice` is not a valid `Option`
     pub fn add_vote<W>(&mut self, choice: Choice, weight: W) -> Result<(), VoteError>
+    where
+        W: Into<Weight>,
+    {
+        self.add_ranked_vote(0, choice, weight)
+    }
+
+    /// add the weight of a vote ranking `choice` at the given `rank` (0-indexed)
+    ///
+    /// if the vote's weight is null (`0`), nothing will be changed.
+    ///
+    /// # Errors
+    ///
+    /// The function will fail if the `choice` is not a valid `Option`, or
+    /// if the `rank` is out of the ranks of the results
+    pub fn add_ranked_vote<W>(
+        &mut self,
+        rank: u8,
+        choice: Choice,
+        weight: W,
+    ) -> Result<(), VoteError>
     where
         W: Into<Weight>,
     {
         let weight = weight.into();
+        let num_choices = self.options.choice_range().len();
 
-        if !self.options.validate(choice) {
+        if !self.options.validate(choice) || (rank as usize + 1) * num_choices > self.results.len()
+        {
             Err(VoteError::InvalidChoice {
                 options: self.options.clone(),
                 choice,
@@ -164,7 +293,7 @@ impl TallyResult {
             // properly we know that adding a weight of `0` is ignored
             Ok(())
         } else {
-            let index = choice.as_byte() as usize;
+            let index = rank as usize * num_choices + choice.as_byte() as usize;
 
             self.results[index] = self.results[index].saturating_add(weight);
 
@@ -266,6 +395,50 @@ mod tests {
         assert_eq!(*tally_result.options(), options);
     }
 
+    #[test]
+    pub fn tally_result_approval_participation() {
+        let options = Options::new_multi_selection(3, 3).unwrap();
+        let mut tally_result = TallyResult::new_approval(options, 5);
+
+        // a single vote of weight 5 approving every choice
+        for choice in 0..3 {
+            tally_result.add_vote(Choice::new(choice), 5).unwrap();
+        }
+
+        assert_eq!(tally_result.results(), &[Weight(5); 3]);
+        assert_eq!(tally_result.participation(), Stake(5));
+    }
+
+    #[test]
+    pub fn tally_result_ranked_votes() {
+        let options = Options::new_multi_selection(3, 2).unwrap();
+        let mut tally_result = TallyResult::new_ranked(options.clone());
+        assert_eq!(tally_result.results().len(), 6);
+
+        tally_result.add_ranked_vote(0, Choice::new(2), 5).unwrap();
+        tally_result.add_ranked_vote(1, Choice::new(0), 5).unwrap();
+        tally_result.add_ranked_vote(0, Choice::new(0), 3).unwrap();
+        tally_result.add_ranked_vote(1, Choice::new(1), 3).unwrap();
+        assert_eq!(
+            tally_result.add_ranked_vote(2, Choice::new(1), Weight(1)),
+            Err(VoteError::InvalidChoice {
+                options,
+                choice: Choice::new(1)
+            })
+        );
+
+        let ranks: Vec<Vec<u64>> = tally_result
+            .ranks()
+            .map(|rank| rank.iter().map(|w| (*w).into()).collect())
+            .collect();
+        assert_eq!(ranks, vec![vec![3, 0, 5], vec![5, 3, 0]]);
+        assert_eq!(tally_result.participation(), Stake(8));
+        assert_eq!(
+            tally_result.borda_scores(),
+            vec![Weight(11), Weight(3), Weight(10)]
+        );
+    }
+
     #[quickcheck]
     pub fn tally(tally_result: TallyResult) -> TestResult {
         let tally = Tally::new_public(tally_result.clone());
@@ -3,14 +3,21 @@ use rand_core::{OsRng, RngCore};
 
 use chain_storage::{
     test_utils::{Block, BlockId},
-    BlockInfo, BlockStore,
+    BackendKind, BlockInfo, BlockStore,
 };
 
 const BLOCK_DATA_LENGTH: usize = 1024;
 const SEQ_BENCH_N_BLOCKS: u32 = 5210;
 const SEQ_BENCH_FLUSH_POINT: u32 = 4096;
+const BACKENDS: [BackendKind; 2] = [BackendKind::Sled, BackendKind::Redb];
 
 fn basic_benchmark(c: &mut Criterion) {
+    for backend in BACKENDS {
+        basic_benchmark_with_backend(c, backend);
+    }
+}
+
+fn basic_benchmark_with_backend(c: &mut Criterion, backend: BackendKind) {
     let mut rng = OsRng;
     let mut block_data = [0; BLOCK_DATA_LENGTH];
 
@@ -23,7 +30,8 @@ fn basic_benchmark(c: &mut Criterion) {
         path.push("test");
         path
     };
-    let store = BlockStore::file(path, BlockId(0).serialize_as_vec()).unwrap();
+    let store =
+        BlockStore::file_with_backend(path, BlockId(0).serialize_as_vec(), backend).unwrap();
     let genesis_block_info = BlockInfo::new(
         genesis_block.id.serialize_as_vec(),
         genesis_block.parent.serialize_as_vec(),
@@ -35,7 +43,7 @@ fn basic_benchmark(c: &mut Criterion) {
 
     let mut blocks = vec![genesis_block];
 
-    c.bench_function("put_block", |b| {
+    c.bench_function(&format!("put_block/{}", backend), |b| {
         b.iter_batched(
             || {
                 let last_block = blocks.get(rng.next_u32() as usize % blocks.len()).unwrap();
@@ -58,7 +66,7 @@ fn basic_benchmark(c: &mut Criterion) {
         )
     });
 
-    c.bench_function("get_block", |b| {
+    c.bench_function(&format!("get_block/{}", backend), |b| {
         b.iter_batched(
             || {
                 blocks
@@ -74,6 +82,12 @@ fn basic_benchmark(c: &mut Criterion) {
 }
 
 fn seq_read_benchmark(c: &mut Criterion) {
+    for backend in BACKENDS {
+        seq_read_benchmark_with_backend(c, backend);
+    }
+}
+
+fn seq_read_benchmark_with_backend(c: &mut Criterion, backend: BackendKind) {
     let mut rng = OsRng;
     let mut block_data = [0; BLOCK_DATA_LENGTH];
 
@@ -86,7 +100,8 @@ fn seq_read_benchmark(c: &mut Criterion) {
         path.push("test");
         path
     };
-    let store = BlockStore::file(path, BlockId(0).serialize_as_vec()).unwrap();
+    let store =
+        BlockStore::file_with_backend(path, BlockId(0).serialize_as_vec(), backend).unwrap();
     let genesis_block_info = BlockInfo::new(
         genesis_block.id.serialize_as_vec(),
         genesis_block.parent.serialize_as_vec(),
@@ -118,7 +133,7 @@ fn seq_read_benchmark(c: &mut Criterion) {
         .map(|block| block.id.serialize_as_vec())
         .collect();
 
-    c.bench_function("seq_volatile_get_block", |b| {
+    c.bench_function(&format!("seq_volatile_get_block/{}", backend), |b| {
         b.iter(|| {
             for block_id in block_ids.iter() {
                 store.get_block(block_id).unwrap();
@@ -126,7 +141,7 @@ fn seq_read_benchmark(c: &mut Criterion) {
         })
     });
 
-    c.bench_function("seq_volatile_iter", |b| {
+    c.bench_function(&format!("seq_volatile_iter/{}", backend), |b| {
         b.iter(|| {
             for block_res in store
                 .iter(block_ids.last().unwrap().as_ref(), SEQ_BENCH_N_BLOCKS)
@@ -141,7 +156,7 @@ fn seq_read_benchmark(c: &mut Criterion) {
         .flush_to_permanent_store(&block_ids[SEQ_BENCH_FLUSH_POINT as usize], 1)
         .unwrap();
 
-    c.bench_function("seq_mixed_get_block", |b| {
+    c.bench_function(&format!("seq_mixed_get_block/{}", backend), |b| {
         b.iter(|| {
             for block_id in block_ids.iter() {
                 store.get_block(block_id).unwrap();
@@ -149,7 +164,7 @@ fn seq_read_benchmark(c: &mut Criterion) {
         })
     });
 
-    c.bench_function("seq_mixed_iter", |b| {
+    c.bench_function(&format!("seq_mixed_iter/{}", backend), |b| {
         b.iter(|| {
             for block_res in store
                 .iter(block_ids.last().unwrap().as_ref(), SEQ_BENCH_N_BLOCKS)
@@ -164,7 +179,7 @@ fn seq_read_benchmark(c: &mut Criterion) {
         .flush_to_permanent_store(&block_ids[SEQ_BENCH_N_BLOCKS as usize - 1], 1)
         .unwrap();
 
-    c.bench_function("seq_permanent_get_block", |b| {
+    c.bench_function(&format!("seq_permanent_get_block/{}", backend), |b| {
         b.iter(|| {
             for block_id in block_ids.iter() {
                 store.get_block(block_id).unwrap();
@@ -172,7 +187,7 @@ fn seq_read_benchmark(c: &mut Criterion) {
         })
     });
 
-    c.bench_function("seq_permanent_iter", |b| {
+    c.bench_function(&format!("seq_permanent_iter/{}", backend), |b| {
         b.iter(|| {
             for block_res in store
                 .iter(block_ids.last().unwrap().as_ref(), SEQ_BENCH_N_BLOCKS)
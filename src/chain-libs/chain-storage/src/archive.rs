@@ -0,0 +1,312 @@
+//! Portable archive of the main chain of a storage.
+//!
+//! ```text
+//! magic           8 bytes   "CSARCHIV"
+//! version         u16
+//! id length       u16
+//! root id         id length bytes
+//! records         each starting with its kind:
+//!   1 or 2        block, 1 if it was in the permanent storage, 2 otherwise
+//!                 chain length u32, id, parent id, block length u32, block
+//!   3             tag, name length u16, name, block id
+//! end of records  u8        always 0
+//! blocks          u64       number of block records
+//! tags            u64       number of tag records
+//! tip             id length bytes, id of the last block
+//! checksum        32 bytes  blake2b-256 of all the bytes above
+//! ```
+//!
+//! All integers are big endian. Blocks are written from the first block of the
+//! chain to the tip, so an archive can be imported while it is being read.
+use crate::{BlockInfo, Error, Value};
+use cryptoxide::blake2b::Blake2b;
+use cryptoxide::digest::Digest;
+use std::io::{self, Read, Write};
+use thiserror::Error;
+
+const MAGIC: [u8; 8] = *b"CSARCHIV";
+const VERSION: u16 = 1;
+
+const CHECKSUM_LEN: usize = 32;
+
+const RECORD_END: u8 = 0;
+const RECORD_PERMANENT_BLOCK: u8 = 1;
+const RECORD_VOLATILE_BLOCK: u8 = 2;
+const RECORD_TAG: u8 = 3;
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("cannot access the archive")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Storage(#[from] Error),
+    #[error("not a storage archive")]
+    InvalidMagic,
+    #[error("unsupported archive version {0}, expected {VERSION}")]
+    UnsupportedVersion(u16),
+    #[error("the archive was made from a storage with a different root block")]
+    RootMismatch,
+    #[error("the storage already contains blocks")]
+    StoreNotEmpty,
+    #[error("malformed archive: {0}")]
+    Malformed(&'static str),
+    #[error("the archive is corrupted, the checksum does not match")]
+    ChecksumMismatch,
+}
+
+/// Summary of an archive, written at its end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveManifest {
+    /// The number of blocks in the archive
+    pub blocks: u64,
+    /// The number of tags in the archive
+    pub tags: u64,
+    /// The ID of the last block of the archive
+    pub tip: Value,
+    /// blake2b-256 of the content of the archive
+    pub checksum: [u8; CHECKSUM_LEN],
+}
+
+/// Check the integrity of an archive without importing it.
+pub fn verify_archive<R: Read>(reader: R) -> Result<ArchiveManifest, ArchiveError> {
+    let mut reader = ArchiveReader::new(reader)?;
+    loop {
+        if let Entry::End(manifest) = reader.next_entry()? {
+            return Ok(manifest);
+        }
+    }
+}
+
+pub(crate) enum Entry {
+    Block {
+        info: BlockInfo,
+        permanent: bool,
+        block: Vec<u8>,
+    },
+    Tag {
+        name: String,
+        block_id: Vec<u8>,
+    },
+    End(ArchiveManifest),
+}
+
+pub(crate) struct ArchiveWriter<W: Write> {
+    inner: io::BufWriter<W>,
+    hasher: Blake2b,
+    blocks: u64,
+    tags: u64,
+    tip: Option<Value>,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    pub fn new(inner: W, root_id: &[u8]) -> Result<Self, ArchiveError> {
+        let id_length =
+            u16::try_from(root_id.len()).map_err(|_| ArchiveError::Malformed("id too long"))?;
+        let mut writer = Self {
+            inner: io::BufWriter::new(inner),
+            hasher: Blake2b::new(CHECKSUM_LEN),
+            blocks: 0,
+            tags: 0,
+            tip: None,
+        };
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_be_bytes())?;
+        writer.write_all(&id_length.to_be_bytes())?;
+        writer.write_all(root_id)?;
+        Ok(writer)
+    }
+
+    /// Blocks must be written in the order of the chain.
+    pub fn write_block(
+        &mut self,
+        info: &BlockInfo,
+        permanent: bool,
+        block: &[u8],
+    ) -> Result<(), ArchiveError> {
+        let block_length =
+            u32::try_from(block.len()).map_err(|_| ArchiveError::Malformed("block too long"))?;
+        let kind = if permanent {
+            RECORD_PERMANENT_BLOCK
+        } else {
+            RECORD_VOLATILE_BLOCK
+        };
+        self.write_all(&[kind])?;
+        self.write_all(&info.chain_length().to_be_bytes())?;
+        self.write_all(info.id().as_ref())?;
+        self.write_all(info.parent_id().as_ref())?;
+        self.write_all(&block_length.to_be_bytes())?;
+        self.write_all(block)?;
+        self.blocks += 1;
+        self.tip = Some(info.id().clone());
+        Ok(())
+    }
+
+    pub fn write_tag(&mut self, name: &str, block_id: &[u8]) -> Result<(), ArchiveError> {
+        let name_length =
+            u16::try_from(name.len()).map_err(|_| ArchiveError::Malformed("tag too long"))?;
+        self.write_all(&[RECORD_TAG])?;
+        self.write_all(&name_length.to_be_bytes())?;
+        self.write_all(name.as_bytes())?;
+        self.write_all(block_id)?;
+        self.tags += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<ArchiveManifest, ArchiveError> {
+        let tip = self
+            .tip
+            .take()
+            .ok_or(ArchiveError::Malformed("no blocks"))?;
+        self.write_all(&[RECORD_END])?;
+        self.write_all(&self.blocks.to_be_bytes())?;
+        self.write_all(&self.tags.to_be_bytes())?;
+        self.write_all(tip.as_ref())?;
+
+        let mut checksum = [0; CHECKSUM_LEN];
+        self.hasher.result(&mut checksum);
+        self.inner.write_all(&checksum)?;
+        self.inner.flush()?;
+
+        Ok(ArchiveManifest {
+            blocks: self.blocks,
+            tags: self.tags,
+            tip,
+            checksum,
+        })
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.hasher.input(bytes);
+        self.inner.write_all(bytes)
+    }
+}
+
+pub(crate) struct ArchiveReader<R: Read> {
+    inner: io::BufReader<R>,
+    hasher: Blake2b,
+    root_id: Vec<u8>,
+    blocks: u64,
+    tags: u64,
+    // the last block read, the next one must be its child
+    tip: Option<BlockInfo>,
+}
+
+impl<R: Read> ArchiveReader<R> {
+    pub fn new(inner: R) -> Result<Self, ArchiveError> {
+        let mut reader = Self {
+            inner: io::BufReader::new(inner),
+            hasher: Blake2b::new(CHECKSUM_LEN),
+            root_id: Vec::new(),
+            blocks: 0,
+            tags: 0,
+            tip: None,
+        };
+        if reader.read_array::<8>()? != MAGIC {
+            return Err(ArchiveError::InvalidMagic);
+        }
+        let version = u16::from_be_bytes(reader.read_array()?);
+        if version != VERSION {
+            return Err(ArchiveError::UnsupportedVersion(version));
+        }
+        let id_length = u16::from_be_bytes(reader.read_array()?);
+        reader.root_id = reader.read_vec(id_length.into())?;
+        Ok(reader)
+    }
+
+    pub fn root_id(&self) -> &[u8] {
+        &self.root_id
+    }
+
+    pub fn next_entry(&mut self) -> Result<Entry, ArchiveError> {
+        let [kind] = self.read_array()?;
+        match kind {
+            RECORD_PERMANENT_BLOCK | RECORD_VOLATILE_BLOCK => {
+                let chain_length = u32::from_be_bytes(self.read_array()?);
+                let id = self.read_id()?;
+                let parent_id = self.read_id()?;
+                let block_length = u32::from_be_bytes(self.read_array()?);
+                let block = self.read_vec(block_length as usize)?;
+
+                let follows_tip = match &self.tip {
+                    Some(tip) => {
+                        parent_id == tip.id().as_ref()
+                            && Some(chain_length) == tip.chain_length().checked_add(1)
+                    }
+                    None => parent_id == self.root_id,
+                };
+                if !follows_tip {
+                    return Err(ArchiveError::Malformed("blocks do not form a chain"));
+                }
+
+                let info = BlockInfo::new(id, parent_id, chain_length);
+                self.blocks += 1;
+                self.tip = Some(info.clone());
+                Ok(Entry::Block {
+                    info,
+                    permanent: kind == RECORD_PERMANENT_BLOCK,
+                    block,
+                })
+            }
+            RECORD_TAG => {
+                let name_length = u16::from_be_bytes(self.read_array()?);
+                let name = String::from_utf8(self.read_vec(name_length.into())?)
+                    .map_err(|_| ArchiveError::Malformed("tag is not valid UTF-8"))?;
+                let block_id = self.read_id()?;
+                self.tags += 1;
+                Ok(Entry::Tag { name, block_id })
+            }
+            RECORD_END => {
+                let blocks = u64::from_be_bytes(self.read_array()?);
+                let tags = u64::from_be_bytes(self.read_array()?);
+                let tip = self.read_id()?;
+
+                let mut expected = [0; CHECKSUM_LEN];
+                self.hasher.result(&mut expected);
+                let mut checksum = [0; CHECKSUM_LEN];
+                self.inner.read_exact(&mut checksum)?;
+                if checksum != expected {
+                    return Err(ArchiveError::ChecksumMismatch);
+                }
+
+                let last_block = self.tip.as_ref().map(|info| info.id().as_ref());
+                if blocks != self.blocks || tags != self.tags || last_block != Some(&tip[..]) {
+                    return Err(ArchiveError::Malformed(
+                        "manifest does not match the records",
+                    ));
+                }
+
+                Ok(Entry::End(ArchiveManifest {
+                    blocks,
+                    tags,
+                    tip: Value::from(tip),
+                    checksum,
+                }))
+            }
+            _ => Err(ArchiveError::Malformed("unknown record")),
+        }
+    }
+
+    fn read_id(&mut self) -> io::Result<Vec<u8>> {
+        self.read_vec(self.root_id.len())
+    }
+
+    fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut bytes = [0; N];
+        self.inner.read_exact(&mut bytes)?;
+        self.hasher.input(&bytes);
+        Ok(bytes)
+    }
+
+    fn read_vec(&mut self, length: usize) -> io::Result<Vec<u8>> {
+        // do not trust the length to allocate the buffer upfront
+        let mut bytes = Vec::new();
+        (&mut self.inner)
+            .take(length as u64)
+            .read_to_end(&mut bytes)?;
+        if bytes.len() != length {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.hasher.input(&bytes);
+        Ok(bytes)
+    }
+}
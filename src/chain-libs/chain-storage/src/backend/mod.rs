@@ -0,0 +1,134 @@
+//! Backends of the volatile storage.
+//!
+//! The volatile storage, and the index of the blocks of the permanent storage,
+//! are kept in an embedded key-value database. The database is hidden behind
+//! the `VolatileBackend` trait, so the same block store logic runs on top of
+//! any of the supported databases (see `BackendKind`).
+
+mod redb;
+mod sled;
+
+pub(crate) use self::{redb::RedbBackend, sled::SledBackend};
+use crate::{Error, Value};
+use std::{cell::RefCell, fmt};
+
+/// The embedded database holding the volatile storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackendKind {
+    /// `sled`, the historical backend.
+    #[default]
+    Sled,
+    /// `redb`, a copy-on-write B-tree database with a bounded memory usage and
+    /// no background compaction.
+    Redb,
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendKind::Sled => f.write_str("sled"),
+            BackendKind::Redb => f.write_str("redb"),
+        }
+    }
+}
+
+/// Trees (or tables) of the volatile storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Tree {
+    /// Binary data of blocks stored in the volatile storage.
+    Blocks,
+    /// Correspondence between IDs and chain lengths of blocks stored in the
+    /// permanent storage.
+    PermanentStoreBlocks,
+    /// Block information (see `BlockInfo`) for volatile storage.
+    Info,
+    /// Maintains conversion from chain length to block IDs. This tree has empty
+    /// values and keys in the form of `bytes(chain_length) ++ block_id`. Such
+    /// structure allows to get all blocks on the given chain length by using
+    /// prefix `bytes(chain_length)`.
+    ChainLengthIndex,
+    /// Holds references to blocks in the volatile storage that have no
+    /// descendants. This allows to quickly determine which branches should be
+    /// removed.
+    BranchesTips,
+    /// Converts a tag name to a block ID.
+    Tags,
+}
+
+impl Tree {
+    /// All the trees, in the order of their discriminants.
+    pub const ALL: [Tree; 6] = [
+        Tree::Blocks,
+        Tree::PermanentStoreBlocks,
+        Tree::Info,
+        Tree::ChainLengthIndex,
+        Tree::BranchesTips,
+        Tree::Tags,
+    ];
+
+    /// Name of the tree in the database.
+    pub fn name(self) -> &'static str {
+        match self {
+            Tree::Blocks => "blocks",
+            Tree::PermanentStoreBlocks => "permanent_store",
+            Tree::Info => "info",
+            Tree::ChainLengthIndex => "length_to_block_ids",
+            Tree::BranchesTips => "branches_tips",
+            Tree::Tags => "tags",
+        }
+    }
+}
+
+/// Embedded key-value database holding the volatile storage.
+pub(crate) trait VolatileBackend: Send + Sync {
+    fn get(&self, tree: Tree, key: &[u8]) -> Result<Option<Value>, Error>;
+
+    fn contains_key(&self, tree: Tree, key: &[u8]) -> Result<bool, Error> {
+        self.get(tree, key).map(|value| value.is_some())
+    }
+
+    fn insert(&self, tree: Tree, key: &[u8], value: &[u8]) -> Result<(), Error>;
+
+    fn remove(&self, tree: Tree, key: &[u8]) -> Result<(), Error>;
+
+    /// The keys starting with `prefix`, in ascending order. The keys are
+    /// collected, so the prefix should only match a few keys.
+    fn scan_prefix(&self, tree: Tree, prefix: &[u8]) -> Result<Vec<Value>, Error>;
+
+    /// Run `f` atomically over all the trees. `f` may be run several times if
+    /// the transaction conflicts with another one, see `transaction`.
+    fn transaction_dyn(
+        &self,
+        f: &dyn Fn(&mut dyn Transaction) -> Result<(), Error>,
+    ) -> Result<(), Error>;
+}
+
+impl dyn VolatileBackend {
+    /// Run `f` atomically over all the trees and return its result. `f` may be
+    /// run several times, so it must not have effects outside of the
+    /// transaction.
+    pub fn transaction<R>(
+        &self,
+        f: impl Fn(&mut dyn Transaction) -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        let result = RefCell::new(None);
+        self.transaction_dyn(&|tx| {
+            *result.borrow_mut() = Some(f(tx)?);
+            Ok(())
+        })?;
+        Ok(result
+            .into_inner()
+            .expect("the transaction has run successfully"))
+    }
+}
+
+/// Reads and writes within a transaction of a `VolatileBackend`.
+pub(crate) trait Transaction {
+    fn get(&self, tree: Tree, key: &[u8]) -> Result<Option<Value>, Error>;
+
+    /// Returns the previous value of the key
+    fn insert(&mut self, tree: Tree, key: &[u8], value: &[u8]) -> Result<Option<Value>, Error>;
+
+    /// Returns the previous value of the key
+    fn remove(&mut self, tree: Tree, key: &[u8]) -> Result<Option<Value>, Error>;
+}
@@ -0,0 +1,129 @@
+use super::{Transaction, Tree, VolatileBackend};
+use crate::{Error, Value};
+use redb::{backends::InMemoryBackend, Database, ReadableTable, TableDefinition, WriteTransaction};
+use std::path::Path;
+
+pub(crate) struct RedbBackend {
+    db: Database,
+}
+
+fn table(tree: Tree) -> TableDefinition<'static, &'static [u8], &'static [u8]> {
+    TableDefinition::new(tree.name())
+}
+
+fn backend_error(err: impl Into<redb::Error>) -> Error {
+    Error::RedbBackendError(err.into())
+}
+
+impl RedbBackend {
+    pub fn file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::new(Database::create(path).map_err(backend_error)?)
+    }
+
+    pub fn memory() -> Result<Self, Error> {
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .map_err(backend_error)?;
+        Self::new(db)
+    }
+
+    pub fn compact<P: AsRef<Path>>(path: P) -> Result<(), Error> {
+        if !path.as_ref().exists() {
+            return Err(Error::Open(std::io::ErrorKind::NotFound.into()));
+        }
+
+        let mut db = Database::create(path).map_err(backend_error)?;
+        db.compact().map_err(backend_error)?;
+        Ok(())
+    }
+
+    fn new(db: Database) -> Result<Self, Error> {
+        // tables must exist to be opened by read transactions
+        let txn = db.begin_write().map_err(backend_error)?;
+        for tree in Tree::ALL {
+            txn.open_table(table(tree)).map_err(backend_error)?;
+        }
+        txn.commit().map_err(backend_error)?;
+
+        Ok(Self { db })
+    }
+}
+
+impl VolatileBackend for RedbBackend {
+    fn get(&self, tree: Tree, key: &[u8]) -> Result<Option<Value>, Error> {
+        let txn = self.db.begin_read().map_err(backend_error)?;
+        let table = txn.open_table(table(tree)).map_err(backend_error)?;
+        let value = table
+            .get(key)
+            .map_err(backend_error)?
+            .map(|value| Value::from(value.value().to_vec()));
+        Ok(value)
+    }
+
+    fn insert(&self, tree: Tree, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.transaction_dyn(&|tx| tx.insert(tree, key, value).map(|_| ()))
+    }
+
+    fn remove(&self, tree: Tree, key: &[u8]) -> Result<(), Error> {
+        self.transaction_dyn(&|tx| tx.remove(tree, key).map(|_| ()))
+    }
+
+    fn scan_prefix(&self, tree: Tree, prefix: &[u8]) -> Result<Vec<Value>, Error> {
+        let txn = self.db.begin_read().map_err(backend_error)?;
+        let table = txn.open_table(table(tree)).map_err(backend_error)?;
+
+        let mut keys = Vec::new();
+        for entry in table.range(prefix..).map_err(backend_error)? {
+            let (key, _) = entry.map_err(backend_error)?;
+            if !key.value().starts_with(prefix) {
+                break;
+            }
+            keys.push(Value::from(key.value().to_vec()));
+        }
+        Ok(keys)
+    }
+
+    fn transaction_dyn(
+        &self,
+        f: &dyn Fn(&mut dyn Transaction) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        // write transactions are serialized, they never conflict. The
+        // transaction is aborted when dropped without being committed.
+        let txn = self.db.begin_write().map_err(backend_error)?;
+        f(&mut RedbTransaction { txn: &txn })?;
+        txn.commit().map_err(backend_error)
+    }
+}
+
+struct RedbTransaction<'a, 'db> {
+    txn: &'a WriteTransaction<'db>,
+}
+
+impl Transaction for RedbTransaction<'_, '_> {
+    fn get(&self, tree: Tree, key: &[u8]) -> Result<Option<Value>, Error> {
+        let table = self.txn.open_table(table(tree)).map_err(backend_error)?;
+        let value = table
+            .get(key)
+            .map_err(backend_error)?
+            .map(|value| Value::from(value.value().to_vec()));
+        Ok(value)
+    }
+
+    fn insert(&mut self, tree: Tree, key: &[u8], value: &[u8]) -> Result<Option<Value>, Error> {
+        let mut table = self.txn.open_table(table(tree)).map_err(backend_error)?;
+        let old = table
+            .insert(key, value)
+            .map_err(backend_error)?
+            .map(|old| Value::from(old.value().to_vec()));
+        Ok(old)
+    }
+
+    fn remove(&mut self, tree: Tree, key: &[u8]) -> Result<Option<Value>, Error> {
+        let mut table = self.txn.open_table(table(tree)).map_err(backend_error)?;
+        let old = table
+            .remove(key)
+            .map_err(backend_error)?
+            .map(|old| Value::from(old.value().to_vec()));
+        Ok(old)
+    }
+}
@@ -0,0 +1,174 @@
+use super::{Transaction, Tree, VolatileBackend};
+use crate::{Error, Value};
+use sled::transaction::{
+    ConflictableTransactionError, TransactionError, Transactional, TransactionalTree,
+    UnabortableTransactionError,
+};
+use std::path::Path;
+
+pub(crate) struct SledBackend {
+    // indexed by the discriminants of `Tree`
+    trees: Vec<sled::Tree>,
+
+    // needs to be kept so that the database is always closed correctly
+    _db: sled::Db,
+}
+
+impl SledBackend {
+    pub fn file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::new(sled::open(path)?)
+    }
+
+    pub fn memory() -> Result<Self, Error> {
+        let db = sled::Config::new()
+            .temporary(true)
+            .open()
+            .map_err(|err| Error::Open(err.into()))?;
+        Self::new(db)
+    }
+
+    /// Copy the database to a new one, which does not contain the unused
+    /// space, and replace the database with it.
+    pub fn compact<P: AsRef<Path>>(path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let compacted_path = path.with_extension("compacted");
+        let old_path = path.with_extension("old");
+
+        recover_compaction(path, &compacted_path, &old_path)?;
+        if !path.exists() {
+            return Err(Error::Open(std::io::ErrorKind::NotFound.into()));
+        }
+
+        {
+            let db = sled::open(path)?;
+            let compacted = sled::open(&compacted_path)?;
+            compacted.import(db.export());
+            compacted.flush()?;
+        }
+
+        std::fs::rename(path, &old_path).map_err(Error::Compact)?;
+        std::fs::rename(&compacted_path, path).map_err(Error::Compact)?;
+        std::fs::remove_dir_all(&old_path).map_err(Error::Compact)
+    }
+
+    fn new(db: sled::Db) -> Result<Self, Error> {
+        let trees = Tree::ALL
+            .iter()
+            .map(|tree| db.open_tree(tree.name()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { trees, _db: db })
+    }
+
+    fn tree(&self, tree: Tree) -> &sled::Tree {
+        &self.trees[tree as usize]
+    }
+}
+
+impl VolatileBackend for SledBackend {
+    fn get(&self, tree: Tree, key: &[u8]) -> Result<Option<Value>, Error> {
+        self.tree(tree)
+            .get(key)
+            .map(|maybe_value| maybe_value.map(Value::volatile))
+            .map_err(Into::into)
+    }
+
+    fn contains_key(&self, tree: Tree, key: &[u8]) -> Result<bool, Error> {
+        self.tree(tree).contains_key(key).map_err(Into::into)
+    }
+
+    fn insert(&self, tree: Tree, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.tree(tree).insert(key, value)?;
+        Ok(())
+    }
+
+    fn remove(&self, tree: Tree, key: &[u8]) -> Result<(), Error> {
+        self.tree(tree).remove(key)?;
+        Ok(())
+    }
+
+    fn scan_prefix(&self, tree: Tree, prefix: &[u8]) -> Result<Vec<Value>, Error> {
+        self.tree(tree)
+            .scan_prefix(prefix)
+            .map(|entry| entry.map(|(key, _)| Value::volatile(key)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    fn transaction_dyn(
+        &self,
+        f: &dyn Fn(&mut dyn Transaction) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        self.trees[..]
+            .transaction(|trees| {
+                f(&mut SledTransaction { trees }).map_err(|err| match err {
+                    // let sled run the transaction again
+                    Error::TransactionConflict => ConflictableTransactionError::Conflict,
+                    err => ConflictableTransactionError::Abort(err),
+                })
+            })
+            .map_err(|err| match err {
+                TransactionError::Abort(err) => err,
+                TransactionError::Storage(err) => err.into(),
+            })
+    }
+}
+
+struct SledTransaction<'a> {
+    trees: &'a [TransactionalTree],
+}
+
+impl Transaction for SledTransaction<'_> {
+    fn get(&self, tree: Tree, key: &[u8]) -> Result<Option<Value>, Error> {
+        self.trees[tree as usize]
+            .get(key)
+            .map(|maybe_value| maybe_value.map(Value::volatile))
+            .map_err(unabortable)
+    }
+
+    fn insert(&mut self, tree: Tree, key: &[u8], value: &[u8]) -> Result<Option<Value>, Error> {
+        self.trees[tree as usize]
+            .insert(key, value)
+            .map(|maybe_value| maybe_value.map(Value::volatile))
+            .map_err(unabortable)
+    }
+
+    fn remove(&mut self, tree: Tree, key: &[u8]) -> Result<Option<Value>, Error> {
+        self.trees[tree as usize]
+            .remove(key)
+            .map(|maybe_value| maybe_value.map(Value::volatile))
+            .map_err(unabortable)
+    }
+}
+
+/// Finish or undo a compaction that was interrupted, so that `path` holds the
+/// database again and no copy of it is left behind.
+fn recover_compaction(path: &Path, compacted_path: &Path, old_path: &Path) -> Result<(), Error> {
+    if old_path.exists() {
+        if !path.exists() {
+            if compacted_path.exists() {
+                // interrupted between the two renames, the compacted copy has
+                // been flushed before the first one so it is complete
+                std::fs::rename(compacted_path, path).map_err(Error::Compact)?;
+            } else {
+                // the old database is the only copy left
+                return std::fs::rename(old_path, path).map_err(Error::Compact);
+            }
+        }
+        std::fs::remove_dir_all(old_path).map_err(Error::Compact)?;
+    }
+
+    // interrupted while copying, the copy may be incomplete
+    if compacted_path.exists() {
+        std::fs::remove_dir_all(compacted_path).map_err(Error::Compact)?;
+    }
+
+    Ok(())
+}
+
+fn unabortable(err: UnabortableTransactionError) -> Error {
+    match err {
+        UnabortableTransactionError::Conflict => Error::TransactionConflict,
+        UnabortableTransactionError::Storage(err) => err.into(),
+    }
+}
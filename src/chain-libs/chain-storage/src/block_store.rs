@@ -1,13 +1,18 @@
 use crate::{
-    permanent_store::PermanentStore, BlockInfo, ConsistencyFailure, Error, StorageIterator, Value,
+    archive::{verify_archive, ArchiveError, ArchiveManifest, ArchiveReader, ArchiveWriter, Entry},
+    backend::{BackendKind, RedbBackend, SledBackend, Transaction, Tree, VolatileBackend},
+    permanent_store::PermanentStore,
+    BlockInfo, ConsistencyFailure, Error, StorageIterator, Value,
 };
-use sled::{
-    transaction::{
-        ConflictableTransactionError, TransactionError, Transactional, TransactionalTree,
-    },
-    Tree,
+use std::{
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
 };
-use std::path::Path;
+
+/// Number of imported blocks after which the blocks of the permanent storage
+/// are moved there, to not accumulate the whole chain in the volatile storage.
+const IMPORT_FLUSH_INTERVAL: u64 = 1024;
 
 #[derive(Clone)]
 pub struct BlockStore {
@@ -15,14 +20,7 @@ pub struct BlockStore {
     root_id: Value,
     id_length: usize,
 
-    blocks_tree: Tree,
-    info_tree: Tree,
-    chain_length_index_tree: Tree,
-    branches_tips_tree: Tree,
-    tags_tree: Tree,
-
-    // needs to be kept so that the database is always closed correctly
-    _db: sled::Db,
+    volatile: Arc<dyn VolatileBackend>,
 }
 
 enum RemoveTipResult {
@@ -31,54 +29,10 @@ enum RemoveTipResult {
     Done,
 }
 
-impl From<Error> for ConflictableTransactionError<Error> {
-    fn from(from: Error) -> Self {
-        ConflictableTransactionError::Abort(from)
-    }
-}
-
-impl From<ConsistencyFailure> for ConflictableTransactionError<Error> {
-    fn from(from: ConsistencyFailure) -> Self {
-        ConflictableTransactionError::Abort(from.into())
-    }
-}
-
-impl From<TransactionError<Error>> for Error {
-    fn from(from: TransactionError<Error>) -> Self {
-        match from {
-            TransactionError::Abort(err) => err,
-            TransactionError::Storage(err) => err.into(),
-        }
-    }
-}
-
-// Names of trees in `sled` storage. For documentation about trees please refer
-// to `sled` docs.
-mod tree {
-    // Binary data of blocks stored in the volatile storage.
-    pub const BLOCKS: &str = "blocks";
-    // Correspondence between IDs and chain lengths of blocks stored in the
-    // permanent storage.
-    pub const PERMANENT_STORE_BLOCKS: &str = "permanent_store";
-    // Block information (see `BlockInfo`) for volatile storage.
-    pub const INFO: &str = "info";
-    // Maintains conversion from chain length to block IDs. This tree has empty
-    // values and keys in the form of `bytes(chain_length) ++ block_id`. Such
-    // structure allows to get all blocks on the given chain length by using
-    // prefix `bytes(chain_length)`. `sled` allows to iterate over key-value
-    // pairs with the same prefix.
-    pub const CHAIN_LENGTH_INDEX: &str = "length_to_block_ids";
-    // Holds references to blocks in the volatile storage that have no
-    // descendants. This allows to quickly determine which branches should be
-    // removed.
-    pub const BRANCHES_TIPS: &str = "branches_tips";
-    // Converts a tag name to a block ID.
-    pub const TAGS: &str = "tags";
-}
-
 impl BlockStore {
-    /// Create a new storage handle. The path must not exist or should be a
-    /// directory. The directory will be created if it does not exist.
+    /// Create a new storage handle, with the sled backend. The path must not
+    /// exist or should be a directory. The directory will be created if it
+    /// does not exist.
     ///
     /// # Arguments
     ///
@@ -88,65 +42,106 @@ impl BlockStore {
     pub fn file<P: AsRef<Path>, I: Into<Value> + Clone>(
         path: P,
         root_id: I,
+    ) -> Result<Self, Error> {
+        Self::file_with_backend(path, root_id, BackendKind::Sled)
+    }
+
+    /// Create a new storage handle with the given volatile storage backend.
+    /// The path must not exist or should be a directory. The directory will be
+    /// created if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - a path to the storage directory.
+    /// * `root_id` - the ID of the root block which the first block in this
+    ///   block chain should refer to as a parent.
+    /// * `backend` - the database of the volatile storage. It must be the
+    ///   backend the storage was created with.
+    pub fn file_with_backend<P: AsRef<Path>, I: Into<Value> + Clone>(
+        path: P,
+        root_id: I,
+        backend: BackendKind,
     ) -> Result<Self, Error> {
         if !path.as_ref().exists() {
             std::fs::create_dir(path.as_ref()).map_err(Error::Open)?;
         }
 
-        let volatile_path = path.as_ref().join("volatile");
+        let volatile_path = volatile_path(path.as_ref(), backend)?;
         let permanent_path = path.as_ref().join("permanent");
 
-        let volatile = sled::open(volatile_path)?;
+        let volatile: Arc<dyn VolatileBackend> = match backend {
+            BackendKind::Sled => Arc::new(SledBackend::file(volatile_path)?),
+            BackendKind::Redb => Arc::new(RedbBackend::file(volatile_path)?),
+        };
 
-        let block_id_index = volatile.open_tree(tree::PERMANENT_STORE_BLOCKS)?;
-        let permanent = PermanentStore::file(permanent_path, block_id_index, root_id.clone())?;
+        let permanent = PermanentStore::file(permanent_path, volatile.clone(), root_id.clone())?;
 
         Self::new(root_id, volatile, permanent)
     }
 
-    /// Open a temporary in-memory database.
+    /// Open a temporary in-memory database, with the sled backend.
     ///
     /// # Arguments
     ///
     /// * `root_id` - the ID of the root block which the first block in this
     ///   block chain should refer to as a parent.
     pub fn memory<I: Into<Value> + Clone>(root_id: I) -> Result<Self, Error> {
-        let volatile = sled::Config::new()
-            .temporary(true)
-            .open()
-            .map_err(|err| Error::Open(err.into()))?;
-        let block_id_index = volatile.open_tree(tree::PERMANENT_STORE_BLOCKS)?;
-        let permanent = PermanentStore::memory(block_id_index, root_id.clone())?;
+        Self::memory_with_backend(root_id, BackendKind::Sled)
+    }
+
+    /// Open a temporary in-memory database with the given volatile storage
+    /// backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `root_id` - the ID of the root block which the first block in this
+    ///   block chain should refer to as a parent.
+    /// * `backend` - the database of the volatile storage.
+    pub fn memory_with_backend<I: Into<Value> + Clone>(
+        root_id: I,
+        backend: BackendKind,
+    ) -> Result<Self, Error> {
+        let volatile: Arc<dyn VolatileBackend> = match backend {
+            BackendKind::Sled => Arc::new(SledBackend::memory()?),
+            BackendKind::Redb => Arc::new(RedbBackend::memory()?),
+        };
+        let permanent = PermanentStore::memory(volatile.clone(), root_id.clone())?;
 
         Self::new(root_id, volatile, permanent)
     }
 
+    /// Reclaim the unused space of the volatile storage. The storage must not
+    /// be opened while it is compacted. A compaction of the sled backend that
+    /// was interrupted is finished or undone first.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - a path to the storage directory.
+    /// * `backend` - the database of the volatile storage. It must be the
+    ///   backend the storage was created with.
+    pub fn compact<P: AsRef<Path>>(path: P, backend: BackendKind) -> Result<(), Error> {
+        let volatile_path = volatile_path(path.as_ref(), backend)?;
+
+        match backend {
+            BackendKind::Sled => SledBackend::compact(volatile_path),
+            BackendKind::Redb => RedbBackend::compact(volatile_path),
+        }
+    }
+
     fn new<I: Into<Value>>(
         root_id: I,
-        volatile: sled::Db,
+        volatile: Arc<dyn VolatileBackend>,
         permanent: PermanentStore,
     ) -> Result<Self, Error> {
         let root_id = root_id.into();
         let id_length = root_id.as_ref().len();
 
-        let blocks_tree = volatile.open_tree(tree::BLOCKS)?;
-        let info_tree = volatile.open_tree(tree::INFO)?;
-        let chain_length_index_tree = volatile.open_tree(tree::CHAIN_LENGTH_INDEX)?;
-        let branches_tips_tree = volatile.open_tree(tree::BRANCHES_TIPS)?;
-        let tags_tree = volatile.open_tree(tree::TAGS)?;
-
         Ok(Self {
             permanent,
             root_id,
             id_length,
 
-            blocks_tree,
-            info_tree,
-            chain_length_index_tree,
-            branches_tips_tree,
-            tags_tree,
-
-            _db: volatile,
+            volatile,
         })
     }
 
@@ -167,26 +162,16 @@ impl BlockStore {
             .permanent
             .contains_key(block_info.parent_id().as_ref())?;
 
-        (
-            &self.blocks_tree,
-            &self.info_tree,
-            &self.chain_length_index_tree,
-            &self.branches_tips_tree,
-        )
-            .transaction(|(blocks, info, chain_length_to_block_ids, tips)| {
-                put_block_impl(
-                    blocks,
-                    info,
-                    chain_length_to_block_ids,
-                    tips,
-                    block,
-                    &block_info,
-                    self.root_id.as_ref(),
-                    self.id_length,
-                    parent_in_permanent_store,
-                )
-            })
-            .map_err(Into::into)
+        self.volatile.transaction(|tx| {
+            put_block_impl(
+                tx,
+                block,
+                &block_info,
+                self.root_id.as_ref(),
+                self.id_length,
+                parent_in_permanent_store,
+            )
+        })
     }
 
     /// Get a block from the storage.
@@ -199,11 +184,9 @@ impl BlockStore {
             return Ok(block);
         }
 
-        self.blocks_tree
-            .get(block_id)
-            .map_err(Into::into)
+        self.volatile
+            .get(Tree::Blocks, block_id)
             .and_then(|maybe_block| maybe_block.ok_or(Error::BlockNotFound))
-            .map(Value::volatile)
     }
 
     /// Get the `BlockInfo` instance for the requested block.
@@ -220,12 +203,11 @@ impl BlockStore {
     }
 
     fn get_block_info_volatile(&self, block_id: &[u8]) -> Result<BlockInfo, Error> {
-        self.info_tree
-            .get(block_id)
-            .map_err(Into::into)
+        self.volatile
+            .get(Tree::Info, block_id)
             .and_then(|maybe_block| maybe_block.ok_or(Error::BlockNotFound))
             .and_then(|block_info_bin| {
-                let mut block_info_reader: &[u8] = &block_info_bin;
+                let mut block_info_reader: &[u8] = block_info_bin.as_ref();
                 BlockInfo::deserialize(&mut block_info_reader, self.id_length, block_id.to_vec())
             })
     }
@@ -239,93 +221,74 @@ impl BlockStore {
             return Ok(vec![block]);
         }
 
-        self.chain_length_index_tree
-            .scan_prefix(build_chain_length_index_prefix(chain_length))
-            .map(|scan_result| {
-                let (block_id, _) = scan_result?;
-
-                self.blocks_tree
-                    .get(block_id_from_chain_length_index(&block_id))?
+        self.volatile
+            .scan_prefix(
+                Tree::ChainLengthIndex,
+                &build_chain_length_index_prefix(chain_length),
+            )?
+            .into_iter()
+            .map(|block_id| {
+                self.volatile
+                    .get(
+                        Tree::Blocks,
+                        block_id_from_chain_length_index(block_id.as_ref()),
+                    )?
                     .ok_or(Error::Inconsistent(ConsistencyFailure::ChainLength))
-                    .map(Value::volatile)
             })
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(Into::into)
+            .collect()
     }
 
     /// Add a tag for a given block. The block id can be later retrieved by this
     /// tag.
     pub fn put_tag(&self, tag_name: &str, block_id: &[u8]) -> Result<(), Error> {
-        let permanent_store_index = self.permanent.block_id_index();
-
-        (&self.info_tree, &self.tags_tree, permanent_store_index)
-            .transaction(move |(info, tags, permanent_store_index)| {
-                put_tag_impl(
-                    info,
-                    tags,
-                    permanent_store_index,
-                    tag_name,
-                    block_id,
-                    self.id_length,
-                )
-            })
-            .map_err(Into::into)
+        self.volatile
+            .transaction(|tx| put_tag_impl(tx, tag_name, block_id, self.id_length))
     }
 
     /// Get the block ID for the given tag.
     pub fn get_tag(&self, tag_name: &str) -> Result<Option<Value>, Error> {
-        self.tags_tree
-            .get(tag_name)
-            .map(|maybe_id_bin| maybe_id_bin.map(Value::volatile))
-            .map_err(Into::into)
+        self.volatile.get(Tree::Tags, tag_name.as_bytes())
+    }
+
+    /// Get all the tags and the IDs of the blocks they refer to.
+    pub fn get_tags(&self) -> Result<Vec<(String, Value)>, Error> {
+        self.volatile
+            .scan_prefix(Tree::Tags, &[])?
+            .into_iter()
+            .filter_map(|tag_name| {
+                let block_id = match self.volatile.get(Tree::Tags, tag_name.as_ref()) {
+                    Ok(block_id) => block_id?,
+                    Err(err) => return Some(Err(err)),
+                };
+                // tags are only written from `&str`
+                let tag_name = String::from_utf8_lossy(tag_name.as_ref()).into_owned();
+                Some(Ok((tag_name, block_id)))
+            })
+            .collect()
     }
 
     /// Get identifier of all branches tips.
     pub fn get_tips_ids(&self) -> Result<Vec<Value>, Error> {
-        self.branches_tips_tree
-            .iter()
-            .map(|id_result| id_result.map(|(id, _)| Value::volatile(id)))
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(Into::into)
+        self.volatile.scan_prefix(Tree::BranchesTips, &[])
     }
 
     /// Prune a branch with the given tip id from the storage.
     pub fn prune_branch(&self, tip_id: &[u8]) -> Result<(), Error> {
-        if !self.branches_tips_tree.contains_key(tip_id)? {
+        if !self.volatile.contains_key(Tree::BranchesTips, tip_id)? {
             return Err(Error::BranchNotFound);
         }
 
-        let permanent_store_index = self.permanent.block_id_index();
+        let result = self.volatile.transaction(|tx| {
+            let mut result = RemoveTipResult::NextTip {
+                id: Vec::from(tip_id),
+            };
 
-        let result = (
-            &self.blocks_tree,
-            &self.info_tree,
-            &self.chain_length_index_tree,
-            &self.branches_tips_tree,
-            permanent_store_index,
-        )
-            .transaction(
-                |(blocks, info, chain_length_to_block_ids, tips, permanent_store_index)| {
-                    let mut result = RemoveTipResult::NextTip {
-                        id: Vec::from(tip_id),
-                    };
-
-                    while let RemoveTipResult::NextTip { id } = &result {
-                        result = remove_tip_impl(
-                            blocks,
-                            info,
-                            chain_length_to_block_ids,
-                            tips,
-                            permanent_store_index,
-                            id,
-                            self.root_id.as_ref(),
-                            self.id_length,
-                        )?;
-                    }
+            while let RemoveTipResult::NextTip { id } = &result {
+                result = remove_tip_impl(tx, id, self.root_id.as_ref(), self.id_length)?;
+            }
 
-                    Ok(result)
-                },
-            )?;
+            Ok(result)
+        })?;
 
         if let RemoveTipResult::HitPermanentStore { id } = result {
             let block_info = self.get_block_info(&id).map_err(|err| match err {
@@ -335,13 +298,35 @@ impl BlockStore {
             let chain_length = block_info.chain_length() + 1;
 
             if self.get_blocks_by_chain_length(chain_length)?.is_empty() {
-                self.branches_tips_tree.insert(block_info.id(), &[])?;
+                self.volatile
+                    .insert(Tree::BranchesTips, block_info.id().as_ref(), &[])?;
             }
         }
 
         Ok(())
     }
 
+    /// Prune all the branches which do not lead to `main_tip_id`. Branches
+    /// with tagged blocks are only pruned down to the last tagged block.
+    ///
+    /// # Returns
+    ///
+    /// The number of pruned branches.
+    pub fn prune_side_branches(&self, main_tip_id: &[u8]) -> Result<usize, Error> {
+        let mut pruned = 0;
+
+        for tip_id in self.get_tips_ids()? {
+            if self.is_ancestor(tip_id.as_ref(), main_tip_id)?.is_some() {
+                continue;
+            }
+
+            self.prune_branch(tip_id.as_ref())?;
+            pruned += 1;
+        }
+
+        Ok(pruned)
+    }
+
     /// Check if the block with the given id exists.
     pub fn block_exists(&self, block_id: &[u8]) -> Result<bool, Error> {
         if self.permanent.contains_key(block_id)? {
@@ -352,7 +337,7 @@ impl BlockStore {
     }
 
     fn block_exists_volatile(&self, block_id: &[u8]) -> Result<bool, Error> {
-        self.info_tree.contains_key(block_id).map_err(Into::into)
+        self.volatile.contains_key(Tree::Info, block_id)
     }
 
     /// Determine whether block identified by `ancestor_id` is an ancestor of
@@ -396,17 +381,15 @@ impl BlockStore {
             return Ok(Some(1));
         }
 
-        let mut chain_length_iter = self
-            .chain_length_index_tree
-            .scan_prefix(build_chain_length_index_prefix(ancestor.chain_length()));
+        let chain_length_entries = self.volatile.scan_prefix(
+            Tree::ChainLengthIndex,
+            &build_chain_length_index_prefix(ancestor.chain_length()),
+        )?;
 
         // if the target length is in the volatile storage and there is only one
         // block at the given length, this block is an ancestor
-        if let Some(chain_length_res) = chain_length_iter.next() {
-            let _ = chain_length_res?;
-            if chain_length_iter.next().is_none() {
-                return Ok(Some(descendant.chain_length() - ancestor.chain_length()));
-            }
+        if chain_length_entries.len() == 1 {
+            return Ok(Some(descendant.chain_length() - ancestor.chain_length()));
         }
 
         let mut current_block_info = descendant;
@@ -446,18 +429,17 @@ impl BlockStore {
             return Ok(info);
         }
 
-        let mut chain_length_iter = self
-            .chain_length_index_tree
-            .scan_prefix(build_chain_length_index_prefix(target));
+        let chain_length_entries = self.volatile.scan_prefix(
+            Tree::ChainLengthIndex,
+            &build_chain_length_index_prefix(target),
+        )?;
 
         // if the target length is in the volatile storage and there is only one
         // block at the given length, it is an ancestor
-        if let Some(chain_length_res) = chain_length_iter.next() {
-            let (chain_length_index_entry, _) = chain_length_res?;
-            if chain_length_iter.next().is_none() {
-                return self
-                    .get_block_info(block_id_from_chain_length_index(&chain_length_index_entry));
-            }
+        if let [chain_length_index_entry] = chain_length_entries.as_slice() {
+            return self.get_block_info(block_id_from_chain_length_index(
+                chain_length_index_entry.as_ref(),
+            ));
         }
 
         // otherwise just iterate until we find the required ancestor
@@ -579,14 +561,12 @@ impl BlockStore {
             None => return Ok(0),
         };
 
-        match self
-            .chain_length_index_tree
-            .scan_prefix(chain_length_prefix)
-            .next()
+        if self
+            .volatile
+            .scan_prefix(Tree::ChainLengthIndex, &chain_length_prefix)?
+            .is_empty()
         {
-            Some(Ok(_)) => {}
-            Some(Err(err)) => return Err(err.into()),
-            None => return Ok(0),
+            return Ok(0);
         }
 
         let mut block_infos = vec![block_info];
@@ -628,15 +608,134 @@ impl BlockStore {
             let key = block_info.id().as_ref();
             let chain_length = start_chain_length + i as u32;
 
-            self.info_tree.remove(key)?;
-            self.blocks_tree.remove(key)?;
-            self.chain_length_index_tree
-                .remove(build_chain_length_index(chain_length, key))?;
+            self.volatile.remove(Tree::Info, key)?;
+            self.volatile.remove(Tree::Blocks, key)?;
+            self.volatile.remove(
+                Tree::ChainLengthIndex,
+                &build_chain_length_index(chain_length, key),
+            )?;
         }
 
         Ok(block_infos.len())
     }
 
+    /// Write the chain from the first block to `tip_id`, and the tags of its
+    /// blocks, to a portable archive (see `verify_archive`).
+    pub fn export_archive<W: Write>(
+        &self,
+        tip_id: &[u8],
+        writer: W,
+    ) -> Result<ArchiveManifest, ArchiveError> {
+        let tip = self.get_block_info(tip_id)?;
+
+        // only the volatile part of the chain needs to be gathered, the
+        // permanent storage holds a single branch
+        let mut volatile_infos = Vec::new();
+        let mut current_id = tip.id().clone();
+        while !self.permanent.contains_key(current_id.as_ref())?
+            && current_id.as_ref() != self.root_id.as_ref()
+        {
+            let info = self.get_block_info_volatile(current_id.as_ref())?;
+            current_id = info.parent_id().clone();
+            volatile_infos.push(info);
+        }
+
+        let permanent_length = if current_id.as_ref() == self.root_id.as_ref() {
+            0
+        } else {
+            self.get_block_info(current_id.as_ref())?.chain_length() + 1
+        };
+
+        let mut writer = ArchiveWriter::new(writer, self.root_id.as_ref())?;
+
+        for chain_length in 0..permanent_length {
+            let info = self
+                .permanent
+                .get_block_info_by_chain_length(chain_length)?
+                .ok_or(Error::from(ConsistencyFailure::MissingPermanentBlock))?;
+            let block = self
+                .permanent
+                .get_block_by_chain_length(chain_length)
+                .ok_or(Error::from(ConsistencyFailure::MissingPermanentBlock))?;
+            writer.write_block(&info, true, block.as_ref())?;
+        }
+
+        for info in volatile_infos.iter().rev() {
+            let block = self.get_block(info.id().as_ref())?;
+            writer.write_block(info, false, block.as_ref())?;
+        }
+
+        for (tag_name, block_id) in self.get_tags()? {
+            if self.is_ancestor(block_id.as_ref(), tip_id)?.is_some() {
+                writer.write_tag(&tag_name, block_id.as_ref())?;
+            }
+        }
+
+        writer.finish()
+    }
+
+    /// Import an archive written by `export_archive` into this storage, which
+    /// must be empty. The whole archive, with its checksum, is checked before
+    /// anything is written, so a corrupted archive leaves the storage empty.
+    pub fn import_archive<R: Read + Seek>(
+        &self,
+        mut reader: R,
+    ) -> Result<ArchiveManifest, ArchiveError> {
+        if !self.get_tips_ids()?.is_empty() {
+            return Err(ArchiveError::StoreNotEmpty);
+        }
+
+        let start = reader.stream_position()?;
+        let verified = verify_archive(&mut reader)?;
+        reader.seek(SeekFrom::Start(start))?;
+
+        let mut reader = ArchiveReader::new(reader)?;
+        if reader.root_id() != self.root_id.as_ref() {
+            return Err(ArchiveError::RootMismatch);
+        }
+
+        let mut tags = Vec::new();
+        let mut last_permanent = None;
+        let mut imported = 0;
+
+        loop {
+            match reader.next_entry()? {
+                Entry::Block {
+                    info,
+                    permanent,
+                    block,
+                } => {
+                    if permanent {
+                        last_permanent = Some(info.id().clone());
+                    }
+                    self.put_block(&block, info)?;
+
+                    imported += 1;
+                    if imported % IMPORT_FLUSH_INTERVAL == 0 {
+                        if let Some(id) = last_permanent.take() {
+                            self.flush_to_permanent_store(id.as_ref(), 1)?;
+                        }
+                    }
+                }
+                // tags are written once the whole archive is checked
+                Entry::Tag { name, block_id } => tags.push((name, block_id)),
+                Entry::End(manifest) => {
+                    // the archive was changed between the two reads
+                    if manifest != verified {
+                        return Err(ArchiveError::ChecksumMismatch);
+                    }
+                    if let Some(id) = last_permanent {
+                        self.flush_to_permanent_store(id.as_ref(), 1)?;
+                    }
+                    for (name, block_id) in tags {
+                        self.put_tag(&name, &block_id)?;
+                    }
+                    return Ok(manifest);
+                }
+            }
+        }
+    }
+
     /// Iterate to the given block starting from the block at the given
     /// `distance - 1`. `distance == 1` means that only `to_block` will be
     /// iterated. `distance == 0` means empty iterator.
@@ -649,147 +748,155 @@ impl BlockStore {
             Value::from(to_block.to_vec()),
             distance,
             self.permanent.clone(),
-            self.info_tree.clone(),
-            self.blocks_tree.clone(),
+            self.volatile.clone(),
         )
     }
 }
 
+/// Path of the volatile storage with the given backend, checking that the
+/// storage was not created with another backend.
+fn volatile_path(path: &Path, backend: BackendKind) -> Result<PathBuf, Error> {
+    let sled_path = path.join("volatile");
+    let redb_path = path.join("volatile.redb");
+
+    match backend {
+        BackendKind::Sled if redb_path.exists() => Err(Error::BackendMismatch(BackendKind::Redb)),
+        BackendKind::Sled => Ok(sled_path),
+        BackendKind::Redb if sled_path.exists() => Err(Error::BackendMismatch(BackendKind::Sled)),
+        BackendKind::Redb => Ok(redb_path),
+    }
+}
+
 #[inline]
-#[allow(clippy::too_many_arguments)]
 fn put_block_impl(
-    blocks: &TransactionalTree,
-    info: &TransactionalTree,
-    chain_length_to_block_ids: &TransactionalTree,
-    tips: &TransactionalTree,
+    tx: &mut dyn Transaction,
     block: &[u8],
     block_info: &BlockInfo,
     root_id: &[u8],
     id_length: usize,
     parent_external: bool,
-) -> Result<(), ConflictableTransactionError<Error>> {
+) -> Result<(), Error> {
     let parent_in_volatile_store = if parent_external || block_info.parent_id().as_ref() == root_id
     {
         false
-    } else if info.get(block_info.parent_id())?.is_none() {
-        return Err(Error::MissingParent.into());
+    } else if tx
+        .get(Tree::Info, block_info.parent_id().as_ref())?
+        .is_none()
+    {
+        return Err(Error::MissingParent);
     } else {
         true
     };
 
     if parent_in_volatile_store {
-        let parent_block_info_bin = info
-            .get(block_info.parent_id())?
+        let parent_block_info_bin = tx
+            .get(Tree::Info, block_info.parent_id().as_ref())?
             .ok_or(ConsistencyFailure::BlockInfo)?;
-        let mut parent_block_info_reader: &[u8] = &parent_block_info_bin;
+        let mut parent_block_info_reader: &[u8] = parent_block_info_bin.as_ref();
         let mut parent_block_info = BlockInfo::deserialize(
             &mut parent_block_info_reader,
             id_length,
             block_info.parent_id().clone(),
         )?;
         parent_block_info.add_parent_ref();
-        info.insert(
+        tx.insert(
+            Tree::Info,
             parent_block_info.id().as_ref(),
-            parent_block_info.serialize()?,
+            &parent_block_info.serialize()?,
         )?;
     }
 
-    tips.remove(block_info.parent_id().as_ref())?;
-    tips.insert(block_info.id().as_ref(), &[])?;
+    tx.remove(Tree::BranchesTips, block_info.parent_id().as_ref())?;
+    tx.insert(Tree::BranchesTips, block_info.id().as_ref(), &[])?;
 
-    chain_length_to_block_ids.insert(
-        build_chain_length_index(block_info.chain_length(), block_info.id().as_ref()),
+    tx.insert(
+        Tree::ChainLengthIndex,
+        &build_chain_length_index(block_info.chain_length(), block_info.id().as_ref()),
         &[],
     )?;
 
-    blocks.insert(block_info.id().as_ref(), block)?;
+    tx.insert(Tree::Blocks, block_info.id().as_ref(), block)?;
 
-    info.insert(block_info.id().as_ref(), block_info.serialize()?)?;
+    tx.insert(
+        Tree::Info,
+        block_info.id().as_ref(),
+        &block_info.serialize()?,
+    )?;
 
     Ok(())
 }
 
 #[inline]
 fn put_tag_impl(
-    info: &TransactionalTree,
-    tags: &TransactionalTree,
-    permanent_store_index: &TransactionalTree,
+    tx: &mut dyn Transaction,
     tag_name: &str,
     block_id: &[u8],
     id_size: usize,
-) -> Result<(), ConflictableTransactionError<Error>> {
-    if let Some(info_bin) = info.get(block_id)? {
-        let mut block_info = BlockInfo::deserialize(&info_bin[..], id_size, block_id.to_vec())?;
+) -> Result<(), Error> {
+    if let Some(info_bin) = tx.get(Tree::Info, block_id)? {
+        let mut block_info = BlockInfo::deserialize(info_bin.as_ref(), id_size, block_id.to_vec())?;
         block_info.add_tag_ref();
         let info_bin = block_info.serialize()?;
-        info.insert(block_id, info_bin)?;
-    } else if !permanent_store_index
-        .get(block_id)
-        .map(|maybe_block| maybe_block.is_some())?
-    {
-        return Err(Error::BlockNotFound.into());
+        tx.insert(Tree::Info, block_id, &info_bin)?;
+    } else if tx.get(Tree::PermanentStoreBlocks, block_id)?.is_none() {
+        return Err(Error::BlockNotFound);
     }
 
-    let maybe_old_block_id = tags.insert(tag_name, block_id)?;
+    let maybe_old_block_id = tx.insert(Tree::Tags, tag_name.as_bytes(), block_id)?;
 
     if let Some(old_block_id) = maybe_old_block_id {
-        let info_bin = info
-            .get(old_block_id.clone())?
+        let info_bin = tx
+            .get(Tree::Info, old_block_id.as_ref())?
             .ok_or(ConsistencyFailure::TaggedBlock)?;
-        let mut block_info = BlockInfo::deserialize(&info_bin[..], id_size, old_block_id.to_vec())?;
+        let mut block_info =
+            BlockInfo::deserialize(info_bin.as_ref(), id_size, old_block_id.as_ref().to_vec())?;
         block_info.remove_tag_ref();
         let info_bin = block_info.serialize()?;
-        info.insert(block_info.id().as_ref(), info_bin)?;
+        tx.insert(Tree::Info, block_info.id().as_ref(), &info_bin)?;
     }
 
     Ok(())
 }
 
 #[inline]
-#[allow(clippy::too_many_arguments)]
 fn remove_tip_impl(
-    blocks: &TransactionalTree,
-    info: &TransactionalTree,
-    chain_length_to_block_ids: &TransactionalTree,
-    tips: &TransactionalTree,
-    permanent_store_index: &TransactionalTree,
+    tx: &mut dyn Transaction,
     block_id: &[u8],
     root_id: &[u8],
     id_size: usize,
-) -> Result<RemoveTipResult, ConflictableTransactionError<Error>> {
+) -> Result<RemoveTipResult, Error> {
     // Stop when we bump into a block stored in the permanent storage.
-    if permanent_store_index
-        .get(block_id)
-        .map(|maybe_block| maybe_block.is_some())?
-    {
+    if tx.get(Tree::PermanentStoreBlocks, block_id)?.is_some() {
         return Ok(RemoveTipResult::Done);
     }
 
-    let block_info_bin = info.get(block_id)?.ok_or(ConsistencyFailure::BlockInfo)?;
-    let mut block_info_reader: &[u8] = &block_info_bin;
+    let block_info_bin = tx
+        .get(Tree::Info, block_id)?
+        .ok_or(ConsistencyFailure::BlockInfo)?;
+    let mut block_info_reader: &[u8] = block_info_bin.as_ref();
     let block_info = BlockInfo::deserialize(&mut block_info_reader, id_size, block_id.to_vec())?;
 
     if block_info.ref_count() != 0 {
         return Ok(RemoveTipResult::Done);
     }
 
-    info.remove(block_id)?;
-    blocks.remove(block_id)?;
+    tx.remove(Tree::Info, block_id)?;
+    tx.remove(Tree::Blocks, block_id)?;
 
-    chain_length_to_block_ids.remove(build_chain_length_index(
-        block_info.chain_length(),
-        block_info.id().as_ref(),
-    ))?;
+    tx.remove(
+        Tree::ChainLengthIndex,
+        &build_chain_length_index(block_info.chain_length(), block_info.id().as_ref()),
+    )?;
 
-    tips.remove(block_id)?;
+    tx.remove(Tree::BranchesTips, block_id)?;
 
     if block_info.parent_id().as_ref() == root_id {
         return Ok(RemoveTipResult::Done);
     }
 
-    let parent_permanent = permanent_store_index
-        .get(block_info.parent_id().as_ref())
-        .map(|maybe_block| maybe_block.is_some())?;
+    let parent_permanent = tx
+        .get(Tree::PermanentStoreBlocks, block_info.parent_id().as_ref())?
+        .is_some();
 
     if parent_permanent {
         return Ok(RemoveTipResult::HitPermanentStore {
@@ -797,19 +904,20 @@ fn remove_tip_impl(
         });
     }
 
-    let parent_block_info_bin = info
-        .get(block_info.parent_id())?
+    let parent_block_info_bin = tx
+        .get(Tree::Info, block_info.parent_id().as_ref())?
         .ok_or(ConsistencyFailure::MissingParentBlock)?;
-    let mut parent_block_info_reader: &[u8] = &parent_block_info_bin;
+    let mut parent_block_info_reader: &[u8] = parent_block_info_bin.as_ref();
     let mut parent_block_info = BlockInfo::deserialize(
         &mut parent_block_info_reader,
         id_size,
         block_info.parent_id().clone(),
     )?;
     parent_block_info.remove_parent_ref();
-    info.insert(
+    tx.insert(
+        Tree::Info,
         parent_block_info.id().as_ref(),
-        parent_block_info.serialize()?,
+        &parent_block_info.serialize()?,
     )?;
 
     // If the block is inside another branch it cannot be a tip.
@@ -817,7 +925,7 @@ fn remove_tip_impl(
         return Ok(RemoveTipResult::Done);
     }
 
-    tips.insert(block_info.parent_id().as_ref(), &[])?;
+    tx.insert(Tree::BranchesTips, block_info.parent_id().as_ref(), &[])?;
 
     // A referenced block cannot be removed.
     if parent_block_info.ref_count() != 0 {
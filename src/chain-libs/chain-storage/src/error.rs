@@ -1,3 +1,4 @@
+use crate::BackendKind;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -8,6 +9,16 @@ pub enum Error {
     BlockNotFound,
     #[error("volatile store error")]
     VolatileBackendError(#[from] sled::Error),
+    #[error("volatile store error")]
+    RedbBackendError(#[from] redb::Error),
+    /// Conflict with a concurrent transaction of the volatile store, the
+    /// transaction is run again by the backend
+    #[error("volatile store transaction conflict")]
+    TransactionConflict,
+    #[error("the storage was created with the {0} backend")]
+    BackendMismatch(BackendKind),
+    #[error("failed to replace the volatile store with its compacted copy")]
+    Compact(#[source] std::io::Error),
     #[error("permanent store error")]
     PermanentBackendError(#[from] data_pile::Error),
     #[error("Block already present in DB")]
@@ -1,12 +1,15 @@
-use crate::{permanent_store::PermanentStore, BlockInfo, ConsistencyFailure, Error, Value};
-use sled::Tree;
+use crate::{
+    backend::{Tree, VolatileBackend},
+    permanent_store::PermanentStore,
+    BlockInfo, ConsistencyFailure, Error, Value,
+};
+use std::sync::Arc;
 
 /// Iterator over blocks. Starts from n-th ancestor of the given block.
 pub struct StorageIterator {
     state: IteratorState,
     to: Value,
-    block_info: Tree,
-    blocks: Tree,
+    volatile: Arc<dyn VolatileBackend>,
 }
 
 enum IteratorState {
@@ -25,10 +28,9 @@ impl StorageIterator {
         to: Value,
         distance: u32,
         permanent_store: PermanentStore,
-        block_info: Tree,
-        blocks: Tree,
+        volatile: Arc<dyn VolatileBackend>,
     ) -> Result<Self, Error> {
-        let to_info = if let Some(to_info_bin) = block_info.get(to.as_ref())? {
+        let to_info = if let Some(to_info_bin) = volatile.get(Tree::Info, to.as_ref())? {
             BlockInfo::deserialize(to_info_bin.as_ref(), to.as_ref().len(), to.clone())?
         } else {
             permanent_store
@@ -53,15 +55,14 @@ impl StorageIterator {
             }
         } else {
             IteratorState::Volatile {
-                ids: gather_blocks_ids(to.clone(), &block_info, from_length)?,
+                ids: gather_blocks_ids(to.clone(), volatile.as_ref(), from_length)?,
             }
         };
 
         Ok(Self {
             state,
             to,
-            block_info,
-            blocks,
+            volatile,
         })
     }
 }
@@ -85,8 +86,11 @@ impl Iterator for StorageIterator {
                         Some(Ok(Value::permanent(item)))
                     }
                     None => {
-                        match gather_blocks_ids(self.to.clone(), &self.block_info, *current_length)
-                        {
+                        match gather_blocks_ids(
+                            self.to.clone(),
+                            self.volatile.as_ref(),
+                            *current_length,
+                        ) {
                             Ok(ids) => self.state = IteratorState::Volatile { ids },
                             Err(err) => return Some(Err(err)),
                         }
@@ -96,11 +100,7 @@ impl Iterator for StorageIterator {
             }
             IteratorState::Volatile { ids } => {
                 let id = ids.pop()?;
-                self.blocks
-                    .get(id.as_ref())
-                    .map(|maybe_value| maybe_value.map(Value::volatile))
-                    .map_err(Into::into)
-                    .transpose()
+                self.volatile.get(Tree::Blocks, id.as_ref()).transpose()
             }
         }
     }
@@ -108,13 +108,13 @@ impl Iterator for StorageIterator {
 
 fn gather_blocks_ids(
     to: Value,
-    block_info: &Tree,
+    volatile: &dyn VolatileBackend,
     stop_at_length: u32,
 ) -> Result<Vec<Value>, Error> {
     let id_size = to.as_ref().len();
     let mut ids = Vec::new();
 
-    let maybe_block_info = block_info.get(to.as_ref())?;
+    let maybe_block_info = volatile.get(Tree::Info, to.as_ref())?;
 
     let block_info_bin = match maybe_block_info {
         Some(block_info_bin) => block_info_bin,
@@ -131,8 +131,8 @@ fn gather_blocks_ids(
         }
 
         current_info = BlockInfo::deserialize(
-            block_info
-                .get(current_info.parent_id().as_ref())?
+            volatile
+                .get(Tree::Info, current_info.parent_id().as_ref())?
                 .ok_or(ConsistencyFailure::MissingParentBlock)?
                 .as_ref(),
             id_size,
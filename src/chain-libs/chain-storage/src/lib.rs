@@ -132,9 +132,24 @@
 //! store
 //! ├── permanent       - permanent storage directory
 //! │   └── flatfile    - storage file that can be transferred over the network
-//! └── volatile        - volatile storage
+//! ├── volatile        - volatile storage, with the sled backend
+//! └── volatile.redb   - volatile storage, with the redb backend
 //! ```
+//!
+//! # Volatile storage backends
+//!
+//! The volatile storage is kept in an embedded database, selected with
+//! `BackendKind` when opening the store: `sled` (the default) or `redb`. A
+//! storage must always be opened with the backend it was created with.
+//!
+//! # Archives
+//!
+//! `BlockStore::export_archive` writes the main chain, with its tags, to a
+//! single checksummed file, which can be checked with `verify_archive` and
+//! imported into an empty storage with `BlockStore::import_archive`.
 
+mod archive;
+mod backend;
 mod block_info;
 mod block_store;
 mod error;
@@ -146,6 +161,8 @@ pub mod test_utils;
 mod tests;
 mod value;
 
+pub use archive::{verify_archive, ArchiveError, ArchiveManifest};
+pub use backend::BackendKind;
 pub use block_info::BlockInfo;
 pub use block_store::BlockStore;
 pub use error::{ConsistencyFailure, Error};
@@ -1,18 +1,22 @@
-use crate::{BlockInfo, ConsistencyFailure, Error, Value};
-use std::path::Path;
+use crate::{
+    backend::{Tree, VolatileBackend},
+    BlockInfo, ConsistencyFailure, Error, Value,
+};
+use std::{path::Path, sync::Arc};
 
 #[derive(Clone)]
 pub(crate) struct PermanentStore {
     blocks: data_pile::Database,
     chain_length_index: data_pile::Database,
-    block_id_index: sled::Tree,
+    // the index of the block ids is `Tree::PermanentStoreBlocks`
+    volatile: Arc<dyn VolatileBackend>,
     root_id: Value,
 }
 
 impl PermanentStore {
     pub fn file<P: AsRef<Path>, I: Into<Value>>(
         path: P,
-        block_id_index: sled::Tree,
+        volatile: Arc<dyn VolatileBackend>,
         root_id: I,
     ) -> Result<PermanentStore, Error> {
         std::fs::create_dir_all(&path).map_err(Error::Open)?;
@@ -28,13 +32,13 @@ impl PermanentStore {
         Ok(Self {
             blocks,
             chain_length_index,
-            block_id_index,
+            volatile,
             root_id,
         })
     }
 
     pub fn memory<I: Into<Value>>(
-        block_id_index: sled::Tree,
+        volatile: Arc<dyn VolatileBackend>,
         root_id: I,
     ) -> Result<PermanentStore, Error> {
         let blocks = data_pile::Database::memory()?;
@@ -45,7 +49,7 @@ impl PermanentStore {
         Ok(Self {
             blocks,
             chain_length_index,
-            block_id_index,
+            volatile,
             root_id,
         })
     }
@@ -108,10 +112,11 @@ impl PermanentStore {
     }
 
     fn get_chain_length(&self, block_id: &[u8]) -> Result<Option<u32>, Error> {
-        let chain_length_bytes_slice = match self.block_id_index.get(block_id)? {
-            Some(block_id) => block_id,
-            None => return Ok(None),
-        };
+        let chain_length_bytes_slice =
+            match self.volatile.get(Tree::PermanentStoreBlocks, block_id)? {
+                Some(block_id) => block_id,
+                None => return Ok(None),
+            };
 
         let mut chain_length_bytes = [0u8; 4];
         chain_length_bytes.copy_from_slice(chain_length_bytes_slice.as_ref());
@@ -121,9 +126,8 @@ impl PermanentStore {
     }
 
     pub fn contains_key(&self, block_id: &[u8]) -> Result<bool, Error> {
-        self.block_id_index
-            .contains_key(block_id)
-            .map_err(Into::into)
+        self.volatile
+            .contains_key(Tree::PermanentStoreBlocks, block_id)
     }
 
     pub fn put_blocks(
@@ -149,7 +153,8 @@ impl PermanentStore {
         for (i, id) in ids.iter().enumerate() {
             let chain_length = start_chain_length + i as u32;
             let chain_length_bytes = chain_length.to_le_bytes();
-            self.block_id_index.insert(id, &chain_length_bytes[..])?;
+            self.volatile
+                .insert(Tree::PermanentStoreBlocks, id, &chain_length_bytes[..])?;
         }
 
         Ok(())
@@ -160,8 +165,4 @@ impl PermanentStore {
             .iter_from_seqno(chain_length as usize)
             .ok_or(Error::BlockNotFound)
     }
-
-    pub fn block_id_index(&self) -> &sled::Tree {
-        &self.block_id_index
-    }
 }
@@ -1,9 +1,9 @@
 use crate::{
     test_utils::{Block, BlockId},
-    BlockInfo, BlockStore, Error, Value,
+    verify_archive, ArchiveError, BackendKind, BlockInfo, BlockStore, Error, Value,
 };
 use rand_core::{OsRng, RngCore};
-use std::{collections::HashSet, iter::FromIterator};
+use std::{collections::HashSet, io::Cursor, iter::FromIterator};
 
 const SIMULTANEOUS_READ_WRITE_ITERS: usize = 50;
 const BLOCK_NUM_PERMANENT_TEST: usize = 1024;
@@ -661,13 +661,21 @@ fn is_ancestor_only_permanent() {
 }
 
 fn prepare_and_fill_store(n: usize) -> (tempfile::TempDir, BlockStore, Vec<Block>) {
+    prepare_and_fill_store_with_backend(n, BackendKind::Sled)
+}
+
+fn prepare_and_fill_store_with_backend(
+    n: usize,
+    backend: BackendKind,
+) -> (tempfile::TempDir, BlockStore, Vec<Block>) {
     const BLOCK_DATA_LENGTH: usize = 512;
 
     let mut rng = OsRng;
     let mut block_data = [0; BLOCK_DATA_LENGTH];
 
     let file = tempfile::TempDir::new().unwrap();
-    let store = BlockStore::file(file.path(), BlockId(0).serialize_as_vec()).unwrap();
+    let store =
+        BlockStore::file_with_backend(file.path(), BlockId(0).serialize_as_vec(), backend).unwrap();
 
     let mut blocks = vec![];
 
@@ -863,3 +871,293 @@ fn iterator_only_permanent_storage() {
         assert_eq!(blocks[i].serialize_as_value(), block.unwrap());
     }
 }
+
+#[test]
+fn redb_tags_and_chain_length() {
+    let mut rng = OsRng;
+
+    let store =
+        BlockStore::memory_with_backend(BlockId(0).serialize_as_vec(), BackendKind::Redb).unwrap();
+    let blocks = generate_chain(&mut rng, &store);
+
+    assert!(store.get_tag("tip").unwrap().is_none());
+    store
+        .put_tag("tip", &blocks.last().unwrap().id.serialize_as_vec())
+        .unwrap();
+    store
+        .put_tag("tip", &blocks.first().unwrap().id.serialize_as_vec())
+        .unwrap();
+    assert_eq!(
+        store.get_tag("tip").unwrap().unwrap(),
+        blocks.first().unwrap().id.serialize_as_value()
+    );
+
+    let block = blocks.last().unwrap();
+    assert!(store
+        .get_blocks_by_chain_length(block.chain_length)
+        .unwrap()
+        .contains(&block.serialize_as_value()));
+}
+
+#[test]
+fn redb_flush_iterate_and_prune() {
+    const TEST_BLOCK_NUM: usize = 64;
+    const FLUSH_AT: usize = 32;
+
+    let (_file, store, blocks) =
+        prepare_and_fill_store_with_backend(TEST_BLOCK_NUM, BackendKind::Redb);
+
+    store
+        .flush_to_permanent_store(&blocks[FLUSH_AT].id.serialize_as_vec(), 1)
+        .unwrap();
+
+    for (i, block) in store
+        .iter(
+            &blocks[TEST_BLOCK_NUM - 1].id.serialize_as_vec(),
+            TEST_BLOCK_NUM as u32,
+        )
+        .unwrap()
+        .enumerate()
+    {
+        assert_eq!(blocks[i].serialize_as_value(), block.unwrap());
+    }
+
+    store
+        .prune_branch(&blocks.last().unwrap().id.serialize_as_vec())
+        .unwrap();
+
+    for block in blocks.iter().take(FLUSH_AT + 1) {
+        assert!(store.block_exists(&block.id.serialize_as_vec()).unwrap());
+    }
+    for block in blocks.iter().skip(FLUSH_AT + 1) {
+        assert!(!store.block_exists(&block.id.serialize_as_vec()).unwrap());
+    }
+    assert_eq!(
+        vec![blocks[FLUSH_AT].id.serialize_as_value()],
+        store.get_tips_ids().unwrap()
+    );
+}
+
+#[test]
+fn redb_reopen() {
+    const TEST_BLOCK_NUM: usize = 16;
+
+    let (file, store, blocks) =
+        prepare_and_fill_store_with_backend(TEST_BLOCK_NUM, BackendKind::Redb);
+    drop(store);
+
+    let store = BlockStore::file_with_backend(
+        file.path(),
+        BlockId(0).serialize_as_vec(),
+        BackendKind::Redb,
+    )
+    .unwrap();
+
+    for block in blocks.iter() {
+        assert_eq!(
+            block.serialize_as_value(),
+            store.get_block(&block.id.serialize_as_vec()).unwrap()
+        );
+    }
+}
+
+#[test]
+fn backend_mismatch() {
+    let (file, store) = prepare_store();
+    drop(store);
+
+    match BlockStore::file_with_backend(
+        file.path(),
+        BlockId(0).serialize_as_vec(),
+        BackendKind::Redb,
+    ) {
+        Err(Error::BackendMismatch(BackendKind::Sled)) => {}
+        Err(err) => panic!("{:?}", err),
+        Ok(_) => panic!("the storage was opened with the wrong backend"),
+    }
+}
+
+#[test]
+fn archive_export_import() {
+    let (_, store, main_branch, second_branch) =
+        generate_two_branches(MAIN_BRANCH_LEN, SECOND_BRANCH_LEN, BIFURCATION_POINT);
+    let tip_id = main_branch.last().unwrap().id.serialize_as_vec();
+
+    store
+        .flush_to_permanent_store(&main_branch[BIFURCATION_POINT].id.serialize_as_vec(), 1)
+        .unwrap();
+    store.put_tag("tip", &tip_id).unwrap();
+    store
+        .put_tag("side", &second_branch.last().unwrap().id.serialize_as_vec())
+        .unwrap();
+
+    let mut archive = Vec::new();
+    let manifest = store.export_archive(&tip_id, &mut archive).unwrap();
+    assert_eq!(MAIN_BRANCH_LEN as u64, manifest.blocks);
+    assert_eq!(1, manifest.tags);
+    assert_eq!(manifest, verify_archive(&archive[..]).unwrap());
+
+    let imported =
+        BlockStore::memory_with_backend(BlockId(0).serialize_as_vec(), BackendKind::Redb).unwrap();
+    assert_eq!(
+        manifest,
+        imported.import_archive(Cursor::new(&archive[..])).unwrap()
+    );
+
+    assert_eq!(
+        vec![Value::from(tip_id.clone())],
+        imported.get_tips_ids().unwrap()
+    );
+    assert_eq!(
+        Value::from(tip_id.clone()),
+        imported.get_tag("tip").unwrap().unwrap()
+    );
+    assert!(imported.get_tag("side").unwrap().is_none());
+    for (i, block) in imported
+        .iter(&tip_id, MAIN_BRANCH_LEN as u32)
+        .unwrap()
+        .enumerate()
+    {
+        assert_eq!(main_branch[i].serialize_as_value(), block.unwrap());
+    }
+
+    // the blocks of the permanent storage are moved there again
+    assert_eq!(
+        0,
+        imported
+            .flush_to_permanent_store(&main_branch[BIFURCATION_POINT].id.serialize_as_vec(), 1)
+            .unwrap()
+    );
+
+    assert!(matches!(
+        imported.import_archive(Cursor::new(&archive[..])),
+        Err(ArchiveError::StoreNotEmpty)
+    ));
+}
+
+#[test]
+fn archive_corrupted() {
+    let (_file, store, blocks) = prepare_and_fill_store(16);
+
+    let mut archive = Vec::new();
+    store
+        .export_archive(&blocks.last().unwrap().id.serialize_as_vec(), &mut archive)
+        .unwrap();
+    let middle = archive.len() / 2;
+    archive[middle] ^= 1;
+
+    assert!(verify_archive(&archive[..]).is_err());
+
+    let imported = BlockStore::memory(BlockId(0).serialize_as_vec()).unwrap();
+    assert!(imported.import_archive(Cursor::new(&archive[..])).is_err());
+    assert!(imported.get_tips_ids().unwrap().is_empty());
+
+    // only the checksum is wrong, all the records can be read
+    let mut archive = Vec::new();
+    store
+        .export_archive(&blocks.last().unwrap().id.serialize_as_vec(), &mut archive)
+        .unwrap();
+    *archive.last_mut().unwrap() ^= 1;
+
+    assert!(matches!(
+        imported.import_archive(Cursor::new(&archive[..])),
+        Err(ArchiveError::ChecksumMismatch)
+    ));
+    assert!(imported.get_tips_ids().unwrap().is_empty());
+    assert!(imported
+        .get_block(&blocks[0].id.serialize_as_vec())
+        .is_err());
+}
+
+#[test]
+fn prune_side_branches() {
+    let (_, store, main_branch, second_branch) =
+        generate_two_branches(MAIN_BRANCH_LEN, SECOND_BRANCH_LEN, BIFURCATION_POINT);
+    let tip_id = main_branch.last().unwrap().id.serialize_as_vec();
+
+    assert_eq!(1, store.prune_side_branches(&tip_id).unwrap());
+    assert_eq!(
+        vec![Value::from(tip_id.clone())],
+        store.get_tips_ids().unwrap()
+    );
+    assert!(!store
+        .block_exists(&second_branch.last().unwrap().id.serialize_as_vec())
+        .unwrap());
+    assert_eq!(0, store.prune_side_branches(&tip_id).unwrap());
+}
+
+#[test]
+fn compact_recovers_from_interrupted_sled_compaction() {
+    const TEST_BLOCK_NUM: usize = 16;
+
+    let (file, store, blocks) =
+        prepare_and_fill_store_with_backend(TEST_BLOCK_NUM, BackendKind::Sled);
+    drop(store);
+
+    let path = file.path().join("volatile");
+    let compacted_path = file.path().join("volatile.compacted");
+    let old_path = file.path().join("volatile.old");
+
+    let check = || {
+        assert!(!compacted_path.exists());
+        assert!(!old_path.exists());
+        let store = BlockStore::file_with_backend(
+            file.path(),
+            BlockId(0).serialize_as_vec(),
+            BackendKind::Sled,
+        )
+        .unwrap();
+        for block in blocks.iter() {
+            assert_eq!(
+                block.serialize_as_value(),
+                store.get_block(&block.id.serialize_as_vec()).unwrap()
+            );
+        }
+    };
+
+    // interrupted while copying
+    std::fs::create_dir(&compacted_path).unwrap();
+    BlockStore::compact(file.path(), BackendKind::Sled).unwrap();
+    check();
+
+    // interrupted after the database was moved away
+    std::fs::rename(&path, &old_path).unwrap();
+    BlockStore::compact(file.path(), BackendKind::Sled).unwrap();
+    check();
+
+    // interrupted between the two renames
+    std::fs::rename(&path, &compacted_path).unwrap();
+    std::fs::create_dir(&old_path).unwrap();
+    BlockStore::compact(file.path(), BackendKind::Sled).unwrap();
+    check();
+
+    // interrupted before the old database was removed
+    std::fs::create_dir(&old_path).unwrap();
+    BlockStore::compact(file.path(), BackendKind::Sled).unwrap();
+    check();
+}
+
+#[test]
+fn compact() {
+    const TEST_BLOCK_NUM: usize = 64;
+
+    for backend in [BackendKind::Sled, BackendKind::Redb] {
+        let (file, store, blocks) = prepare_and_fill_store_with_backend(TEST_BLOCK_NUM, backend);
+        store
+            .flush_to_permanent_store(&blocks[TEST_BLOCK_NUM / 2].id.serialize_as_vec(), 1)
+            .unwrap();
+        drop(store);
+
+        BlockStore::compact(file.path(), backend).unwrap();
+
+        let store =
+            BlockStore::file_with_backend(file.path(), BlockId(0).serialize_as_vec(), backend)
+                .unwrap();
+        for block in blocks.iter() {
+            assert_eq!(
+                block.serialize_as_value(),
+                store.get_block(&block.id.serialize_as_vec()).unwrap()
+            );
+        }
+    }
+}
@@ -1,6 +1,12 @@
-use crate::cryptography::{Ciphertext, HybridCiphertext, PublicKey, SecretKey};
-use crate::encrypted_vote::{EncryptedVote, ProofOfCorrectVote, Vote};
-use crate::math::polynomial::Polynomial;
+pub mod dkg;
+
+use self::dkg::EncryptedShares;
+use crate::cryptography::{Ciphertext, PublicKey, SecretKey};
+use crate::encrypted_vote::{
+    ApprovalVote, EncryptedVote, ProofOfCorrectApprovalVote, ProofOfCorrectRankedVote,
+    ProofOfCorrectVote, RankedVote, Vote,
+};
+use crate::math::polynomial::{lagrange_coefficients_at, Polynomial};
 use crate::tally::Crs;
 use crate::{GroupElement, Scalar, CURVE_HRP};
 use chain_crypto::bech32::{to_bech32_from_bytes, try_from_bech32_to_bytes, Bech32, Error};
@@ -56,6 +62,65 @@ impl ElectionPublicKey {
         );
         (ciphertexts, proof)
     }
+
+    /// Take an approval vote and encrypt it + provide a proof that at most
+    /// `max_selections` options are approved
+    pub fn encrypt_and_prove_approval_vote<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+        crs: &Crs,
+        vote: &ApprovalVote,
+        max_selections: usize,
+    ) -> (EncryptedVote, ProofOfCorrectApprovalVote) {
+        let encryption_randomness: Vec<Scalar> =
+            (0..vote.len()).map(|_| Scalar::random(rng)).collect();
+        let ciphertexts: Vec<Ciphertext> = encryption_randomness
+            .iter()
+            .zip(vote.iter())
+            .map(|(r, v)| self.as_raw().encrypt_with_r(&Scalar::from(v), r))
+            .collect();
+
+        let proof = ProofOfCorrectApprovalVote::generate(
+            rng,
+            crs,
+            &self.0,
+            vote,
+            max_selections,
+            &encryption_randomness,
+            &ciphertexts,
+        );
+        (ciphertexts, proof)
+    }
+
+    /// Take a ranked vote and encrypt it + provide a proof of correct ranking
+    pub fn encrypt_and_prove_ranked_vote<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+        crs: &Crs,
+        vote: &RankedVote,
+    ) -> (EncryptedVote, ProofOfCorrectRankedVote) {
+        let encryption_randomness: Vec<Scalar> = (0..vote.ranks() * vote.options())
+            .map(|_| Scalar::random(rng))
+            .collect();
+        let ciphertexts: Vec<Ciphertext> = encryption_randomness
+            .iter()
+            .zip(
+                vote.unit_vectors()
+                    .flat_map(|unit_vector| unit_vector.iter()),
+            )
+            .map(|(r, v)| self.as_raw().encrypt_with_r(&Scalar::from(v), r))
+            .collect();
+
+        let proof = ProofOfCorrectRankedVote::generate(
+            rng,
+            crs,
+            &self.0,
+            vote,
+            &encryption_randomness,
+            &ciphertexts,
+        );
+        (ciphertexts, proof)
+    }
     //-------------------------------------------------------------------------------------
     // The encrypt_vote and prove_encrypted_vote methods are not the part of the original ElectionPublicKey trait,
     // they are added only for a more selective benchmarking in this crate
@@ -94,6 +159,39 @@ impl ElectionPublicKey {
         ElectionPublicKey(PublicKey { pk: k })
     }
 
+    /// Create an election public key from the public shares of the committee members,
+    /// as obtained with the distributed key generation, where `threshold` members are
+    /// required to decrypt.
+    ///
+    /// Returns `None` if `threshold` is not within `1..=public_shares.len()`, or if the
+    /// shares are not the evaluations of the same sharing polynomial of degree
+    /// `threshold - 1`, in which case some members could not take part in the decryption.
+    pub fn from_public_shares(public_shares: &[MemberPublicKey], threshold: usize) -> Option<Self> {
+        if threshold == 0 || threshold > public_shares.len() {
+            return None;
+        }
+        let xs: Vec<Scalar> = (0..threshold).map(evaluation_point).collect();
+        let interpolate = |at: &Scalar| {
+            GroupElement::vartime_multiscalar_multiplication(
+                lagrange_coefficients_at(&xs, at),
+                public_shares[..threshold]
+                    .iter()
+                    .map(|share| share.0.pk.clone()),
+            )
+        };
+        // the first `threshold` shares define the polynomial, the others must lie on it
+        let consistent = public_shares
+            .iter()
+            .enumerate()
+            .skip(threshold)
+            .all(|(index, share)| interpolate(&evaluation_point(index)) == share.0.pk);
+        consistent.then(|| {
+            ElectionPublicKey(PublicKey {
+                pk: interpolate(&Scalar::zero()),
+            })
+        })
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         self.0.to_bytes()
     }
@@ -103,6 +201,11 @@ impl ElectionPublicKey {
     }
 }
 
+// committee members are 1-indexed when evaluating the sharing polynomials
+pub(crate) fn evaluation_point(index: usize) -> Scalar {
+    Scalar::from_u64((index + 1) as u64)
+}
+
 impl Bech32 for ElectionPublicKey {
     const BECH32_HRP: &'static str = concatcp!(CURVE_HRP, "_votepk");
     const BYTES_LEN: usize = PublicKey::BYTES_LEN;
@@ -121,33 +224,40 @@ impl Bech32 for ElectionPublicKey {
 
 /// Initial state generated by a Member, which include keys for this election
 #[derive(Clone)]
-// TODO: some of the fields are needed for DKG features which are
-// not yet implemented
-#[allow(dead_code)]
 pub struct MemberState {
     sk: MemberSecretKey,
     owner_index: usize,
     apubs: Vec<GroupElement>,
     es: Vec<GroupElement>,
-    encrypted: Vec<(HybridCiphertext, HybridCiphertext)>,
+    encrypted: Vec<EncryptedShares>,
+    pshek: Polynomial,
+    pcomm: Polynomial,
 }
 
 impl MemberState {
-    /// Generate a new member state from random, where the number
+    /// Generate a new member state from random, where `t` is the number of committee
+    /// members required to recover the election secret, `h` is the commitment key of
+    /// the Pedersen commitments and `my` is the (0-based) index of this member in
+    /// `committee_pks`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `t` is not within `1..=committee_pks.len()`, or if `my` is not a valid index.
     pub fn new<R: RngCore + CryptoRng>(
         rng: &mut R,
         t: usize,
-        h: &Crs, // TODO: document
+        h: &Crs,
         committee_pks: &[MemberCommunicationPublicKey],
         my: usize,
     ) -> MemberState {
         let n = committee_pks.len();
-        assert!(t > 0);
-        assert!(t <= n);
+        assert!(t > 0, "the threshold must be at least 1");
+        assert!(t <= n, "the threshold cannot exceed the committee size");
         assert!(my < n);
 
-        let pcomm = Polynomial::random(rng, t);
-        let pshek = Polynomial::random(rng, t);
+        // a polynomial of degree t - 1 is recovered with t of its evaluations
+        let pcomm = Polynomial::random(rng, t - 1);
+        let pshek = Polynomial::random(rng, t - 1);
 
         let mut apubs = Vec::new();
         let mut es = Vec::new();
@@ -166,7 +276,7 @@ impl MemberState {
             if i == my {
                 continue;
             } else {
-                let idx = Scalar::from_u64((i + 1) as u64);
+                let idx = evaluation_point(i);
                 let share_comm = pcomm.evaluate(&idx);
                 let share_shek = pshek.evaluate(&idx);
 
@@ -175,12 +285,16 @@ impl MemberState {
                 let ecomm = pk.0.hybrid_encrypt(&share_comm.to_bytes(), rng);
                 let eshek = pk.0.hybrid_encrypt(&share_shek.to_bytes(), rng);
 
-                encrypted.push((ecomm, eshek));
+                encrypted.push(EncryptedShares {
+                    recipient: i,
+                    ecomm,
+                    eshek,
+                });
             }
         }
 
-        assert_eq!(apubs.len(), t + 1);
-        assert_eq!(es.len(), t + 1);
+        assert_eq!(apubs.len(), t);
+        assert_eq!(es.len(), t);
         assert_eq!(encrypted.len(), n - 1);
 
         MemberState {
@@ -191,9 +305,21 @@ impl MemberState {
             apubs,
             es,
             encrypted,
+            pshek,
+            pcomm,
         }
     }
 
+    /// Index of this member in the committee, as used when creating the state
+    pub fn index(&self) -> usize {
+        self.owner_index - 1
+    }
+
+    /// Number of members required to recover the election secret
+    pub fn threshold(&self) -> usize {
+        self.es.len()
+    }
+
     pub fn secret_key(&self) -> &MemberSecretKey {
         &self.sk
     }
@@ -0,0 +1,986 @@
+//! Distributed key generation (DKG) for the tally committee.
+//!
+//! The protocol follows the Pedersen-VSS based DKG presented by Gennaro, Jarecki,
+//! Krawczyk and Rabin in
+//! ["Secure Distributed Key Generation for Discrete-Log Based Cryptosystems"](https://link.springer.com/article/10.1007/s00145-006-0347-3).
+//! At the end of the protocol every committee member owns a share of the election
+//! secret key, while no party ever learns the secret key itself.
+//!
+//! Members are identified by their (0-based) index in the list of communication keys used to
+//! create their `MemberState`. The rounds are, for every member:
+//!
+//! 1. broadcast `MemberState::sharing_broadcast`, which contains the Pedersen commitments
+//!    to the member polynomials, and the shares of every other member, encrypted
+//!    under their `MemberCommunicationPublicKey`.
+//! 2. call `MemberDkg::process_sharings` with the sharing broadcasts of the other members.
+//!    Shares that do not match the commitments of their dealer result in a `Complaint`,
+//!    which is broadcast.
+//! 3. answer the complaints raised against self with `MemberState::answer_complaints`,
+//!    and feed every complaint and answer to `MemberDkg::process_sharing_complaints`.
+//!    A dealer which does not answer a complaint with a valid share is disqualified.
+//! 4. broadcast `MemberState::coefficients_broadcast`, and check the coefficients of the
+//!    qualified dealers with `MemberDkg::process_coefficients`, broadcasting the resulting
+//!    complaints.
+//! 5. call `MemberDkg::process_coefficient_complaints`, and broadcast the returned shares, so that
+//!    the secret of the dealers with justified complaints is publicly reconstructed with
+//!    `MemberDkg::reconstruct`.
+//! 6. `MemberDkg::finalize` derives the secret share of the member, and the `ElectionPublicKey`.
+
+use super::{
+    evaluation_point, ElectionPublicKey, MemberCommunicationKey, MemberPublicKey, MemberSecretKey,
+    MemberState,
+};
+use crate::cryptography::{HybridCiphertext, PublicKey, SecretKey};
+use crate::math::polynomial::Polynomial;
+use crate::tally::Crs;
+use crate::{GroupElement, Scalar};
+use chain_core::packer::Codec;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Size of a hybrid encryption of a `Scalar`
+const ENCRYPTED_SCALAR_BYTES_LEN: usize = GroupElement::BYTES_LEN + Scalar::BYTES_LEN;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum DkgError {
+    #[error("no valid share received from qualified dealer {0}")]
+    MissingShare(usize),
+    #[error("no coefficients available for qualified dealer {0}")]
+    MissingCoefficients(usize),
+    #[error("cannot reconstruct secret of dealer {dealer}, got {received} valid shares out of {threshold} needed")]
+    NotEnoughShares {
+        dealer: usize,
+        received: usize,
+        threshold: usize,
+    },
+    #[error("only {qualified} members are qualified, but at least {threshold} are needed")]
+    NotEnoughQualified { qualified: usize, threshold: usize },
+}
+
+/// Shares of a dealer polynomials for a single recipient, encrypted under the
+/// communication key of the recipient.
+#[derive(Clone)]
+pub struct EncryptedShares {
+    pub(crate) recipient: usize,
+    pub(crate) ecomm: HybridCiphertext,
+    pub(crate) eshek: HybridCiphertext,
+}
+
+/// First round broadcast of a dealer: the Pedersen commitments to the coefficients of its
+/// polynomials, and the encrypted shares for every other member.
+#[derive(Clone)]
+pub struct SharingBroadcast {
+    dealer: usize,
+    es: Vec<GroupElement>,
+    encrypted: Vec<EncryptedShares>,
+}
+
+/// Second round broadcast of a dealer: the public coefficients of its secret polynomial.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoefficientsBroadcast {
+    dealer: usize,
+    apubs: Vec<GroupElement>,
+}
+
+/// Share of the polynomials of `dealer` for `recipient`, disclosed publicly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RevealedShare {
+    dealer: usize,
+    recipient: usize,
+    shek: Scalar,
+    comm: Scalar,
+}
+
+/// Complaint broadcast against a dealer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Complaint {
+    /// The share sent by `dealer` to `accuser` is missing, or does not match the
+    /// commitments of the dealer.
+    Sharing { accuser: usize, dealer: usize },
+    /// The share of the recipient does not match the coefficients broadcast by the dealer.
+    /// The share is revealed so that every member can check the complaint.
+    Coefficients(RevealedShare),
+}
+
+/// Result of a successful DKG, from the point of view of a single member
+#[derive(Clone)]
+pub struct DkgOutput {
+    secret_share: MemberSecretKey,
+    election_public_key: ElectionPublicKey,
+    public_shares: Vec<MemberPublicKey>,
+    qualified: Vec<usize>,
+}
+
+/// State of a single member during the rounds of the distributed key generation
+pub struct MemberDkg {
+    index: usize,
+    threshold: usize,
+    committee_size: usize,
+    crs: Crs,
+    comm_key: MemberCommunicationKey,
+    commitments: BTreeMap<usize, Vec<GroupElement>>,
+    shares: BTreeMap<usize, RevealedShare>,
+    coefficients: BTreeMap<usize, Vec<GroupElement>>,
+    disqualified: BTreeSet<usize>,
+    exposed: BTreeSet<usize>,
+}
+
+// Evaluate, in the exponent, the polynomial whose coefficients are committed in `commitments`
+fn evaluate_in_exponent(commitments: &[GroupElement], at: &Scalar) -> GroupElement {
+    GroupElement::vartime_multiscalar_multiplication(
+        at.exp_iter().take(commitments.len()),
+        commitments.iter().cloned(),
+    )
+}
+
+fn read_index(codec: &mut Codec<&[u8]>) -> Option<usize> {
+    codec.get_be_u32().ok().map(|i| i as usize)
+}
+
+fn read_group_elements(codec: &mut Codec<&[u8]>) -> Option<Vec<GroupElement>> {
+    let len = codec.get_be_u32().ok()? as usize;
+    (0..len)
+        .map(|_| GroupElement::from_bytes(codec.get_slice(GroupElement::BYTES_LEN).ok()?))
+        .collect()
+}
+
+fn write_group_elements(out: &mut Vec<u8>, elements: &[GroupElement]) {
+    out.extend_from_slice(&(elements.len() as u32).to_be_bytes());
+    for element in elements {
+        out.extend_from_slice(&element.to_bytes());
+    }
+}
+
+impl MemberState {
+    /// Broadcast of the first round of the DKG
+    pub fn sharing_broadcast(&self) -> SharingBroadcast {
+        SharingBroadcast {
+            dealer: self.index(),
+            es: self.es.clone(),
+            encrypted: self.encrypted.clone(),
+        }
+    }
+
+    /// Broadcast of the second round of the DKG, only sent by qualified dealers
+    pub fn coefficients_broadcast(&self) -> CoefficientsBroadcast {
+        CoefficientsBroadcast {
+            dealer: self.index(),
+            apubs: self.apubs.clone(),
+        }
+    }
+
+    /// Publicly reveal the shares disputed by the sharing complaints raised against this member
+    pub fn answer_complaints(&self, complaints: &[Complaint]) -> Vec<RevealedShare> {
+        complaints
+            .iter()
+            .filter_map(|complaint| match complaint {
+                Complaint::Sharing { accuser, dealer } if *dealer == self.index() => {
+                    Some(self.share_for(*accuser))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn share_for(&self, recipient: usize) -> RevealedShare {
+        let at = evaluation_point(recipient);
+        RevealedShare {
+            dealer: self.index(),
+            recipient,
+            shek: self.pshek.evaluate(&at),
+            comm: self.pcomm.evaluate(&at),
+        }
+    }
+}
+
+impl EncryptedShares {
+    const BYTES_LEN: usize = 4 + 2 * ENCRYPTED_SCALAR_BYTES_LEN;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(Self::BYTES_LEN);
+        out.extend_from_slice(&(self.recipient as u32).to_be_bytes());
+        out.extend_from_slice(&self.ecomm.to_bytes());
+        out.extend_from_slice(&self.eshek.to_bytes());
+        out
+    }
+
+    fn from_buffer(codec: &mut Codec<&[u8]>) -> Option<Self> {
+        let recipient = read_index(codec)?;
+        let ecomm =
+            HybridCiphertext::from_bytes(codec.get_slice(ENCRYPTED_SCALAR_BYTES_LEN).ok()?)?;
+        let eshek =
+            HybridCiphertext::from_bytes(codec.get_slice(ENCRYPTED_SCALAR_BYTES_LEN).ok()?)?;
+        Some(Self {
+            recipient,
+            ecomm,
+            eshek,
+        })
+    }
+}
+
+impl SharingBroadcast {
+    pub fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&(self.dealer as u32).to_be_bytes());
+        write_group_elements(&mut out, &self.es);
+        out.extend_from_slice(&(self.encrypted.len() as u32).to_be_bytes());
+        for shares in &self.encrypted {
+            out.extend_from_slice(&shares.to_bytes());
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut codec = Codec::new(bytes);
+        let dealer = read_index(&mut codec)?;
+        let es = read_group_elements(&mut codec)?;
+        let len = codec.get_be_u32().ok()? as usize;
+        let encrypted = (0..len)
+            .map(|_| EncryptedShares::from_buffer(&mut codec))
+            .collect::<Option<Vec<_>>>()?;
+        if codec.has_bytes_left() {
+            return None;
+        }
+        Some(Self {
+            dealer,
+            es,
+            encrypted,
+        })
+    }
+}
+
+impl CoefficientsBroadcast {
+    pub fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&(self.dealer as u32).to_be_bytes());
+        write_group_elements(&mut out, &self.apubs);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut codec = Codec::new(bytes);
+        let dealer = read_index(&mut codec)?;
+        let apubs = read_group_elements(&mut codec)?;
+        if codec.has_bytes_left() {
+            return None;
+        }
+        Some(Self { dealer, apubs })
+    }
+}
+
+impl RevealedShare {
+    pub const BYTES_LEN: usize = 4 + 4 + 2 * Scalar::BYTES_LEN;
+
+    pub fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    pub fn recipient(&self) -> usize {
+        self.recipient
+    }
+
+    /// Check that the share is consistent with the Pedersen commitments `es` of the dealer
+    fn verify_commitments(&self, es: &[GroupElement], crs: &Crs) -> bool {
+        let committed = GroupElement::generator() * &self.shek + crs * &self.comm;
+        committed == evaluate_in_exponent(es, &evaluation_point(self.recipient))
+    }
+
+    /// Check that the share is consistent with the public coefficients `apubs` of the dealer
+    fn verify_coefficients(&self, apubs: &[GroupElement]) -> bool {
+        GroupElement::generator() * &self.shek
+            == evaluate_in_exponent(apubs, &evaluation_point(self.recipient))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(Self::BYTES_LEN);
+        out.extend_from_slice(&(self.dealer as u32).to_be_bytes());
+        out.extend_from_slice(&(self.recipient as u32).to_be_bytes());
+        out.extend_from_slice(&self.shek.to_bytes());
+        out.extend_from_slice(&self.comm.to_bytes());
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::BYTES_LEN {
+            return None;
+        }
+        let mut codec = Codec::new(bytes);
+        Self::from_buffer(&mut codec)
+    }
+
+    fn from_buffer(codec: &mut Codec<&[u8]>) -> Option<Self> {
+        let dealer = read_index(codec)?;
+        let recipient = read_index(codec)?;
+        let shek = Scalar::from_bytes(codec.get_slice(Scalar::BYTES_LEN).ok()?)?;
+        let comm = Scalar::from_bytes(codec.get_slice(Scalar::BYTES_LEN).ok()?)?;
+        Some(Self {
+            dealer,
+            recipient,
+            shek,
+            comm,
+        })
+    }
+}
+
+impl Complaint {
+    const SHARING_TAG: u8 = 0;
+    const COEFFICIENTS_TAG: u8 = 1;
+
+    /// The dealer this complaint is raised against
+    pub fn dealer(&self) -> usize {
+        match self {
+            Complaint::Sharing { dealer, .. } => *dealer,
+            Complaint::Coefficients(share) => share.dealer,
+        }
+    }
+
+    /// The member that raised this complaint
+    pub fn accuser(&self) -> usize {
+        match self {
+            Complaint::Sharing { accuser, .. } => *accuser,
+            Complaint::Coefficients(share) => share.recipient,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            Complaint::Sharing { accuser, dealer } => {
+                out.push(Self::SHARING_TAG);
+                out.extend_from_slice(&(*accuser as u32).to_be_bytes());
+                out.extend_from_slice(&(*dealer as u32).to_be_bytes());
+            }
+            Complaint::Coefficients(share) => {
+                out.push(Self::COEFFICIENTS_TAG);
+                out.extend_from_slice(&share.to_bytes());
+            }
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut codec = Codec::new(bytes);
+        let complaint = match codec.get_u8().ok()? {
+            Self::SHARING_TAG => Complaint::Sharing {
+                accuser: read_index(&mut codec)?,
+                dealer: read_index(&mut codec)?,
+            },
+            Self::COEFFICIENTS_TAG => {
+                Complaint::Coefficients(RevealedShare::from_buffer(&mut codec)?)
+            }
+            _ => return None,
+        };
+        if codec.has_bytes_left() {
+            return None;
+        }
+        Some(complaint)
+    }
+}
+
+impl DkgOutput {
+    /// Share of the election secret key owned by this member. It is used
+    /// to produce the member's partial decryptions of the tally.
+    pub fn secret_share(&self) -> &MemberSecretKey {
+        &self.secret_share
+    }
+
+    /// Public key every vote is encrypted to
+    pub fn election_public_key(&self) -> &ElectionPublicKey {
+        &self.election_public_key
+    }
+
+    /// Public keys matching the secret shares of every committee member, indexed
+    /// by member. They are used to verify the partial decryptions of the members.
+    pub fn public_shares(&self) -> &[MemberPublicKey] {
+        &self.public_shares
+    }
+
+    /// Indices of the members whose contribution is part of the election key
+    pub fn qualified(&self) -> &[usize] {
+        &self.qualified
+    }
+}
+
+impl MemberDkg {
+    /// Start the DKG for the member owning `state`, where `comm_key` is the communication
+    /// secret key of the member, and `crs` the commitment key used to create `state`.
+    pub fn new(
+        state: &MemberState,
+        comm_key: MemberCommunicationKey,
+        crs: &Crs,
+        committee_size: usize,
+    ) -> Self {
+        let index = state.index();
+        assert!(index < committee_size);
+        assert!(state.threshold() <= committee_size);
+
+        let mut commitments = BTreeMap::new();
+        commitments.insert(index, state.es.clone());
+        let mut shares = BTreeMap::new();
+        shares.insert(index, state.share_for(index));
+
+        MemberDkg {
+            index,
+            threshold: state.threshold(),
+            committee_size,
+            crs: crs.clone(),
+            comm_key,
+            commitments,
+            shares,
+            coefficients: BTreeMap::new(),
+            disqualified: BTreeSet::new(),
+            exposed: BTreeSet::new(),
+        }
+    }
+
+    /// Indices of the members that are not disqualified (yet)
+    pub fn qualified(&self) -> Vec<usize> {
+        (0..self.committee_size)
+            .filter(|i| !self.disqualified.contains(i))
+            .collect()
+    }
+
+    /// Decrypt and verify the shares sent by the other members in their `SharingBroadcast`.
+    /// Dealers that did not broadcast well formed commitments are disqualified, and a
+    /// `Complaint` is returned for every missing or invalid share.
+    pub fn process_sharings(&mut self, broadcasts: &[SharingBroadcast]) -> Vec<Complaint> {
+        let mut complaints = Vec::new();
+        for dealer in 0..self.committee_size {
+            if dealer == self.index {
+                continue;
+            }
+            let broadcast = match broadcasts.iter().find(|b| b.dealer == dealer) {
+                Some(broadcast) if broadcast.es.len() == self.threshold => broadcast,
+                _ => {
+                    self.disqualified.insert(dealer);
+                    continue;
+                }
+            };
+            self.commitments.insert(dealer, broadcast.es.clone());
+            match self.decrypt_share(broadcast) {
+                Some(share) if share.verify_commitments(&broadcast.es, &self.crs) => {
+                    self.shares.insert(dealer, share);
+                }
+                _ => complaints.push(Complaint::Sharing {
+                    accuser: self.index,
+                    dealer,
+                }),
+            }
+        }
+        complaints
+    }
+
+    fn decrypt_share(&self, broadcast: &SharingBroadcast) -> Option<RevealedShare> {
+        let encrypted = broadcast
+            .encrypted
+            .iter()
+            .find(|e| e.recipient == self.index)?;
+        let shek = Scalar::from_bytes(&self.comm_key.0.hybrid_decrypt(&encrypted.eshek))?;
+        let comm = Scalar::from_bytes(&self.comm_key.0.hybrid_decrypt(&encrypted.ecomm))?;
+        Some(RevealedShare {
+            dealer: broadcast.dealer,
+            recipient: self.index,
+            shek,
+            comm,
+        })
+    }
+
+    /// Resolve the complaints of the sharing round, given the `answers` of the accused dealers.
+    /// A dealer that did not reveal a share matching its commitments for every complaint is
+    /// disqualified.
+    pub fn process_sharing_complaints(
+        &mut self,
+        complaints: &[Complaint],
+        answers: &[RevealedShare],
+    ) {
+        for complaint in complaints {
+            let (accuser, dealer) = match complaint {
+                Complaint::Sharing { accuser, dealer } => (*accuser, *dealer),
+                Complaint::Coefficients(_) => continue,
+            };
+            if self.disqualified.contains(&dealer) {
+                continue;
+            }
+            let es = match self.commitments.get(&dealer) {
+                Some(es) => es,
+                None => continue,
+            };
+            let answer = answers.iter().find(|answer| {
+                answer.dealer == dealer
+                    && answer.recipient == accuser
+                    && answer.verify_commitments(es, &self.crs)
+            });
+            match answer {
+                Some(answer) if accuser == self.index => {
+                    self.shares.insert(dealer, answer.clone());
+                }
+                Some(_) => {}
+                None => {
+                    self.disqualified.insert(dealer);
+                }
+            }
+        }
+    }
+
+    /// Verify the share received from every qualified dealer against the coefficients it
+    /// broadcast. A `Complaint`, revealing the share, is returned for every dealer whose
+    /// coefficients are missing or do not match.
+    pub fn process_coefficients(&mut self, broadcasts: &[CoefficientsBroadcast]) -> Vec<Complaint> {
+        let mut complaints = Vec::new();
+        for dealer in self.qualified() {
+            let share = match self.shares.get(&dealer) {
+                Some(share) => share,
+                None => continue,
+            };
+            let apubs = broadcasts
+                .iter()
+                .find(|b| b.dealer == dealer && b.apubs.len() == self.threshold)
+                .map(|b| &b.apubs);
+            match apubs {
+                Some(apubs) => {
+                    if !share.verify_coefficients(apubs) {
+                        complaints.push(Complaint::Coefficients(share.clone()));
+                    }
+                    self.coefficients.insert(dealer, apubs.clone());
+                }
+                None => complaints.push(Complaint::Coefficients(share.clone())),
+            }
+        }
+        complaints
+    }
+
+    /// Check the complaints raised against the coefficients of the qualified dealers. The secret
+    /// of every dealer with a justified complaint is reconstructed publicly: the shares of
+    /// this member for those dealers are returned, and need to be broadcast.
+    pub fn process_coefficient_complaints(
+        &mut self,
+        complaints: &[Complaint],
+    ) -> Vec<RevealedShare> {
+        for complaint in complaints {
+            let share = match complaint {
+                Complaint::Coefficients(share) => share,
+                Complaint::Sharing { .. } => continue,
+            };
+            if self.disqualified.contains(&share.dealer) {
+                continue;
+            }
+            let justified = match self.commitments.get(&share.dealer) {
+                Some(es) => {
+                    share.verify_commitments(es, &self.crs)
+                        && self
+                            .coefficients
+                            .get(&share.dealer)
+                            .map(|apubs| !share.verify_coefficients(apubs))
+                            .unwrap_or(true)
+                }
+                None => false,
+            };
+            if justified {
+                self.exposed.insert(share.dealer);
+            }
+        }
+        self.exposed
+            .iter()
+            .filter_map(|dealer| self.shares.get(dealer).cloned())
+            .collect()
+    }
+
+    /// Reconstruct the coefficients of the dealers with justified coefficient complaints,
+    /// from the shares `revealed` by the other members.
+    pub fn reconstruct(&mut self, revealed: &[RevealedShare]) -> Result<(), DkgError> {
+        for dealer in &self.exposed {
+            let es = &self.commitments[dealer];
+            let mut recipients = BTreeSet::new();
+            let mut points = Vec::with_capacity(self.threshold);
+            for share in revealed.iter().filter(|share| share.dealer == *dealer) {
+                if points.len() == self.threshold {
+                    break;
+                }
+                if !recipients.contains(&share.recipient) && share.verify_commitments(es, &self.crs)
+                {
+                    recipients.insert(share.recipient);
+                    points.push((evaluation_point(share.recipient), share.shek.clone()));
+                }
+            }
+            if points.len() < self.threshold {
+                return Err(DkgError::NotEnoughShares {
+                    dealer: *dealer,
+                    received: points.len(),
+                    threshold: self.threshold,
+                });
+            }
+            let polynomial = Polynomial::interpolate(&points);
+            let apubs = polynomial
+                .get_coefficients()
+                .map(|coefficient| GroupElement::generator() * coefficient)
+                .collect();
+            self.coefficients.insert(*dealer, apubs);
+        }
+        Ok(())
+    }
+
+    /// Derive the secret share of this member, and the public outcome of the DKG.
+    pub fn finalize(&self) -> Result<DkgOutput, DkgError> {
+        let qualified = self.qualified();
+        if qualified.len() < self.threshold {
+            return Err(DkgError::NotEnoughQualified {
+                qualified: qualified.len(),
+                threshold: self.threshold,
+            });
+        }
+
+        let mut secret_share = Scalar::zero();
+        let mut coefficients = Vec::with_capacity(qualified.len());
+        for dealer in &qualified {
+            let share = self
+                .shares
+                .get(dealer)
+                .ok_or(DkgError::MissingShare(*dealer))?;
+            secret_share = secret_share + &share.shek;
+            coefficients.push(
+                self.coefficients
+                    .get(dealer)
+                    .ok_or(DkgError::MissingCoefficients(*dealer))?,
+            );
+        }
+
+        let constant_terms: Vec<GroupElement> =
+            coefficients.iter().map(|apubs| apubs[0].clone()).collect();
+        let election_public_key = ElectionPublicKey(PublicKey {
+            pk: GroupElement::sum(constant_terms.iter()),
+        });
+
+        let public_shares = (0..self.committee_size)
+            .map(|member| {
+                let at = evaluation_point(member);
+                let evaluations: Vec<GroupElement> = coefficients
+                    .iter()
+                    .map(|apubs| evaluate_in_exponent(apubs, &at))
+                    .collect();
+                MemberPublicKey(PublicKey {
+                    pk: GroupElement::sum(evaluations.iter()),
+                })
+            })
+            .collect();
+
+        Ok(DkgOutput {
+            secret_share: MemberSecretKey(SecretKey { sk: secret_share }),
+            election_public_key,
+            public_shares,
+            qualified,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::committee::MemberCommunicationPublicKey;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    struct Committee {
+        states: Vec<MemberState>,
+        dkgs: Vec<MemberDkg>,
+    }
+
+    fn committee(rng: &mut ChaCha20Rng, threshold: usize, size: usize) -> Committee {
+        let crs = Crs::from_hash(b"Example of a shared string. This should be VotePlan.to_id()");
+        let comm_keys: Vec<_> = (0..size)
+            .map(|_| MemberCommunicationKey::new(rng))
+            .collect();
+        let comm_pks: Vec<MemberCommunicationPublicKey> =
+            comm_keys.iter().map(|k| k.to_public()).collect();
+        let states: Vec<_> = (0..size)
+            .map(|i| MemberState::new(rng, threshold, &crs, &comm_pks, i))
+            .collect();
+        let dkgs = states
+            .iter()
+            .zip(comm_keys)
+            .map(|(state, comm_key)| MemberDkg::new(state, comm_key, &crs, size))
+            .collect();
+        Committee { states, dkgs }
+    }
+
+    #[test]
+    #[should_panic(expected = "the threshold must be at least 1")]
+    fn zero_threshold_is_rejected() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        committee(&mut rng, 0, 3);
+    }
+
+    fn run_honest_rounds(committee: &mut Committee) -> Vec<DkgOutput> {
+        let sharings: Vec<_> = committee
+            .states
+            .iter()
+            .map(|s| s.sharing_broadcast())
+            .collect();
+        let complaints: Vec<_> = committee
+            .dkgs
+            .iter_mut()
+            .flat_map(|dkg| dkg.process_sharings(&sharings))
+            .collect();
+        assert!(complaints.is_empty());
+
+        let coefficients: Vec<_> = committee
+            .states
+            .iter()
+            .map(|s| s.coefficients_broadcast())
+            .collect();
+        let complaints: Vec<_> = committee
+            .dkgs
+            .iter_mut()
+            .flat_map(|dkg| dkg.process_coefficients(&coefficients))
+            .collect();
+        assert!(complaints.is_empty());
+
+        committee
+            .dkgs
+            .iter()
+            .map(|dkg| dkg.finalize().unwrap())
+            .collect()
+    }
+
+    fn secret_from_shares(outputs: &[DkgOutput], members: &[usize]) -> Scalar {
+        let points: Vec<_> = members
+            .iter()
+            .map(|i| {
+                (
+                    evaluation_point(*i),
+                    outputs[*i].secret_share().0.sk.clone(),
+                )
+            })
+            .collect();
+        Polynomial::interpolate(&points).at_zero()
+    }
+
+    #[test]
+    fn honest_dkg() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let mut committee = committee(&mut rng, 2, 3);
+        let outputs = run_honest_rounds(&mut committee);
+
+        let election_pk = outputs[0].election_public_key().clone();
+        let participants: Vec<_> = committee.states.iter().map(|s| s.public_key()).collect();
+        assert_eq!(
+            election_pk,
+            ElectionPublicKey::from_participants(&participants)
+        );
+
+        for (i, output) in outputs.iter().enumerate() {
+            assert_eq!(output.election_public_key(), &election_pk);
+            assert_eq!(output.qualified(), &[0, 1, 2]);
+            assert_eq!(output.public_shares(), outputs[0].public_shares());
+            assert_eq!(output.secret_share().to_public(), output.public_shares()[i]);
+        }
+
+        // any `threshold` members recover the election secret
+        for members in [[0, 1], [0, 2], [1, 2]] {
+            let secret = secret_from_shares(&outputs, &members);
+            assert_eq!(GroupElement::generator() * secret, election_pk.0.pk);
+        }
+    }
+
+    #[test]
+    fn cheating_dealer_answering_complaint() {
+        let mut rng = ChaCha20Rng::from_seed([1u8; 32]);
+        let mut committee = committee(&mut rng, 2, 3);
+
+        let mut sharings: Vec<_> = committee
+            .states
+            .iter()
+            .map(|s| s.sharing_broadcast())
+            .collect();
+        // dealer 0 sends the share of member 2 to member 1
+        let wrong = sharings[0].encrypted[1].clone();
+        sharings[0].encrypted[0].ecomm = wrong.ecomm;
+        sharings[0].encrypted[0].eshek = wrong.eshek;
+
+        let complaints: Vec<_> = committee
+            .dkgs
+            .iter_mut()
+            .flat_map(|dkg| dkg.process_sharings(&sharings))
+            .collect();
+        assert_eq!(
+            complaints,
+            vec![Complaint::Sharing {
+                accuser: 1,
+                dealer: 0
+            }]
+        );
+
+        let answers = committee.states[0].answer_complaints(&complaints);
+        for dkg in committee.dkgs.iter_mut() {
+            dkg.process_sharing_complaints(&complaints, &answers);
+            assert_eq!(dkg.qualified(), vec![0, 1, 2]);
+        }
+
+        let coefficients: Vec<_> = committee
+            .states
+            .iter()
+            .map(|s| s.coefficients_broadcast())
+            .collect();
+        for dkg in committee.dkgs.iter_mut() {
+            assert!(dkg.process_coefficients(&coefficients).is_empty());
+        }
+        let outputs: Vec<_> = committee
+            .dkgs
+            .iter()
+            .map(|dkg| dkg.finalize().unwrap())
+            .collect();
+        let secret = secret_from_shares(&outputs, &[1, 2]);
+        assert_eq!(
+            GroupElement::generator() * secret,
+            outputs[1].election_public_key().0.pk
+        );
+    }
+
+    #[test]
+    fn silent_dealer_is_disqualified() {
+        let mut rng = ChaCha20Rng::from_seed([2u8; 32]);
+        let mut committee = committee(&mut rng, 2, 3);
+
+        let mut sharings: Vec<_> = committee
+            .states
+            .iter()
+            .map(|s| s.sharing_broadcast())
+            .collect();
+        sharings[2].encrypted.clear();
+
+        let complaints: Vec<_> = committee
+            .dkgs
+            .iter_mut()
+            .flat_map(|dkg| dkg.process_sharings(&sharings))
+            .collect();
+        assert_eq!(complaints.len(), 2);
+
+        // dealer 2 does not answer
+        for dkg in committee.dkgs.iter_mut() {
+            dkg.process_sharing_complaints(&complaints, &[]);
+            assert_eq!(dkg.qualified(), vec![0, 1]);
+        }
+
+        let coefficients: Vec<_> = committee
+            .states
+            .iter()
+            .map(|s| s.coefficients_broadcast())
+            .collect();
+        for dkg in committee.dkgs.iter_mut() {
+            assert!(dkg.process_coefficients(&coefficients).is_empty());
+        }
+        let outputs: Vec<_> = committee
+            .dkgs
+            .iter()
+            .map(|dkg| dkg.finalize().unwrap())
+            .collect();
+        let participants = vec![
+            committee.states[0].public_key(),
+            committee.states[1].public_key(),
+        ];
+        assert_eq!(
+            outputs[0].election_public_key(),
+            &ElectionPublicKey::from_participants(&participants)
+        );
+        let secret = secret_from_shares(&outputs, &[0, 2]);
+        assert_eq!(
+            GroupElement::generator() * secret,
+            outputs[0].election_public_key().0.pk
+        );
+    }
+
+    #[test]
+    fn wrong_coefficients_are_reconstructed() {
+        let mut rng = ChaCha20Rng::from_seed([3u8; 32]);
+        let mut committee = committee(&mut rng, 2, 3);
+
+        let sharings: Vec<_> = committee
+            .states
+            .iter()
+            .map(|s| s.sharing_broadcast())
+            .collect();
+        for dkg in committee.dkgs.iter_mut() {
+            assert!(dkg.process_sharings(&sharings).is_empty());
+        }
+
+        let mut coefficients: Vec<_> = committee
+            .states
+            .iter()
+            .map(|s| s.coefficients_broadcast())
+            .collect();
+        coefficients[1].apubs[0] = GroupElement::from_hash(b"biased key");
+
+        let complaints: Vec<_> = committee
+            .dkgs
+            .iter_mut()
+            .flat_map(|dkg| dkg.process_coefficients(&coefficients))
+            .collect();
+        assert!(!complaints.is_empty());
+        assert!(complaints.iter().all(|complaint| complaint.dealer() == 1));
+
+        let revealed: Vec<_> = committee
+            .dkgs
+            .iter_mut()
+            .flat_map(|dkg| dkg.process_coefficient_complaints(&complaints))
+            .collect();
+        for dkg in committee.dkgs.iter_mut() {
+            dkg.reconstruct(&revealed).unwrap();
+        }
+
+        let outputs: Vec<_> = committee
+            .dkgs
+            .iter()
+            .map(|dkg| dkg.finalize().unwrap())
+            .collect();
+        let participants: Vec<_> = committee.states.iter().map(|s| s.public_key()).collect();
+        for output in &outputs {
+            assert_eq!(output.qualified(), &[0, 1, 2]);
+            assert_eq!(
+                output.election_public_key(),
+                &ElectionPublicKey::from_participants(&participants)
+            );
+        }
+    }
+
+    #[test]
+    fn messages_serialization() {
+        let mut rng = ChaCha20Rng::from_seed([4u8; 32]);
+        let committee = committee(&mut rng, 2, 3);
+        let state = &committee.states[0];
+
+        let sharing = state.sharing_broadcast();
+        let decoded = SharingBroadcast::from_bytes(&sharing.to_bytes()).unwrap();
+        assert_eq!(decoded.to_bytes(), sharing.to_bytes());
+
+        let coefficients = state.coefficients_broadcast();
+        assert_eq!(
+            CoefficientsBroadcast::from_bytes(&coefficients.to_bytes()),
+            Some(coefficients)
+        );
+
+        let share = state.share_for(1);
+        assert_eq!(
+            RevealedShare::from_bytes(&share.to_bytes()),
+            Some(share.clone())
+        );
+
+        for complaint in [
+            Complaint::Sharing {
+                accuser: 2,
+                dealer: 0,
+            },
+            Complaint::Coefficients(share),
+        ] {
+            assert_eq!(
+                Complaint::from_bytes(&complaint.to_bytes()),
+                Some(complaint)
+            );
+        }
+    }
+}
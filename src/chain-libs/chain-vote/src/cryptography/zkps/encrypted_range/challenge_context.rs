@@ -0,0 +1,45 @@
+use crate::cryptography::{Ciphertext, CommitmentKey, PublicKey};
+use crate::{GroupElement, Scalar};
+use cryptoxide::blake2b::Blake2b;
+use cryptoxide::digest::Digest;
+
+/// Challenge context for the proof of encryption of a value within a range. The statement
+/// consists of the commitment key, the public key, the ciphertext, and the bounds of the range.
+/// The challenge computation takes as input the announcements of every branch of the
+/// disjunction.
+pub struct ChallengeContext(Blake2b);
+
+impl ChallengeContext {
+    /// Initialise the challenge context, by including the full statement
+    pub(crate) fn new(
+        commitment_key: &CommitmentKey,
+        public_key: &PublicKey,
+        ciphertext: &Ciphertext,
+        lower: u64,
+        upper: u64,
+    ) -> Self {
+        let mut ctx = Blake2b::new(64);
+        ctx.input(&commitment_key.to_bytes());
+        ctx.input(&public_key.to_bytes());
+        ctx.input(&ciphertext.to_bytes());
+        ctx.input(&lower.to_be_bytes());
+        ctx.input(&upper.to_be_bytes());
+
+        ChallengeContext(ctx)
+    }
+
+    /// Generation of the `first_challenge`. This challenge is generated after the announcements
+    /// of all the branches are "sent". Hence, we include the latter to the challenge context
+    /// and generate its corresponding scalar.
+    pub(crate) fn first_challenge(
+        &mut self,
+        announcements: &[(GroupElement, GroupElement)],
+    ) -> Scalar {
+        for (a1, a2) in announcements {
+            self.0.input(&a1.to_bytes());
+            self.0.input(&a2.to_bytes());
+        }
+
+        Scalar::hash_to_scalar(&self.0)
+    }
+}
@@ -0,0 +1,256 @@
+//! Non-interactive Zero Knowledge proof that an ElGamal ciphertext encrypts
+//! a value within a (small) public range.
+//!
+//! The proof is the following:
+//!
+//! `NIZK{(ck, pk, C, lower, upper), (v, r): C = Enc_pk(r; v) AND lower <= v <= upper}`
+//!
+//! For each value `j` of the range, `C = Enc_pk(r; j)` translates to the discrete log
+//! equality `e1 = g^r AND (e2 - g^j) = pk^r`. The range statement is the disjunction
+//! of those equalities, which we prove with the composition of Cramer, Damgård and
+//! Schoenmakers: the prover simulates the transcripts of all the branches but the one
+//! of the encrypted value, and the challenges of all the branches must add up to the
+//! challenge of the proof.
+//!
+//! The size of the proof is linear in the size of the range, so it is meant to be used
+//! with ranges of a few values only, e.g. to prove that a ciphertext encrypts a bit.
+//!
+//! The commitment key `ck` is not used by the proof itself, but it is part of the
+//! statement so that a proof is bound to the `Crs` of the election it was made for.
+#![allow(clippy::many_single_char_names)]
+use super::challenge_context::ChallengeContext;
+use crate::cryptography::{Ciphertext, CommitmentKey, PublicKey};
+use crate::tally::Crs;
+use crate::{GroupElement, Scalar};
+use chain_core::packer::Codec;
+use chain_core::property::ReadError;
+use rand_core::{CryptoRng, RngCore};
+
+/// Proof that a ciphertext encrypts a value within a range. It consists of a challenge
+/// and a response for each of the values of the range.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Zkp {
+    challenges: Vec<Scalar>,
+    responses: Vec<Scalar>,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl Zkp {
+    /// Generate a range proof for the `ciphertext` encrypting `value` with the
+    /// `encryption_randomness`, under `public_key` and for the Common Reference String
+    /// `crs`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is not within `lower..=upper`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn generate<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        crs: &Crs,
+        public_key: &PublicKey,
+        ciphertext: &Ciphertext,
+        value: u64,
+        encryption_randomness: &Scalar,
+        lower: u64,
+        upper: u64,
+    ) -> Self {
+        assert!(lower <= value && value <= upper);
+        let len = (upper - lower + 1) as usize;
+        let real = (value - lower) as usize;
+
+        let mut challenges = Vec::with_capacity(len);
+        let mut responses = Vec::with_capacity(len);
+        let mut announcements = Vec::with_capacity(len);
+        let w = Scalar::random(rng);
+        for (j, candidate) in (lower..=upper).enumerate() {
+            if j == real {
+                announcements.push((GroupElement::generator() * &w, &public_key.pk * &w));
+                challenges.push(Scalar::zero());
+                responses.push(Scalar::zero());
+            } else {
+                let challenge = Scalar::random(rng);
+                let response = Scalar::random(rng);
+                announcements.push(simulated_announcement(
+                    public_key, ciphertext, candidate, &challenge, &response,
+                ));
+                challenges.push(challenge);
+                responses.push(response);
+            }
+        }
+
+        let ck = CommitmentKey::from(crs.clone());
+        let mut challenge_context =
+            ChallengeContext::new(&ck, public_key, ciphertext, lower, upper);
+        let challenge = challenge_context.first_challenge(&announcements);
+        let simulated = challenges
+            .iter()
+            .fold(Scalar::zero(), |acc, challenge| acc + challenge);
+        let real_challenge = &challenge - &simulated;
+        responses[real] = &w + &(encryption_randomness * &real_challenge);
+        challenges[real] = real_challenge;
+
+        Zkp {
+            challenges,
+            responses,
+        }
+    }
+
+    /// Verify that `ciphertext` encrypts a value within `lower..=upper` under `public_key`,
+    /// with a proof generated for the Common Reference String `crs`.
+    pub fn verify(
+        &self,
+        crs: &Crs,
+        public_key: &PublicKey,
+        ciphertext: &Ciphertext,
+        lower: u64,
+        upper: u64,
+    ) -> bool {
+        if lower > upper || self.len() as u64 != upper - lower + 1 {
+            return false;
+        }
+
+        let announcements = (lower..=upper)
+            .zip(self.challenges.iter().zip(self.responses.iter()))
+            .map(|(candidate, (challenge, response))| {
+                simulated_announcement(public_key, ciphertext, candidate, challenge, response)
+            })
+            .collect::<Vec<_>>();
+
+        let ck = CommitmentKey::from(crs.clone());
+        let mut challenge_context =
+            ChallengeContext::new(&ck, public_key, ciphertext, lower, upper);
+        let challenge = challenge_context.first_challenge(&announcements);
+        let sum = self
+            .challenges
+            .iter()
+            .fold(Scalar::zero(), |acc, challenge| acc + challenge);
+        // no need for constant time equality because of the hash in challenge()
+        challenge == sum
+    }
+
+    /// Returns the number of values of the range covered by the proof
+    pub fn len(&self) -> usize {
+        self.challenges.len()
+    }
+
+    /// Size in bytes of a proof covering a range of `len` values
+    pub fn bytes_len(len: usize) -> usize {
+        2 * len * Scalar::BYTES_LEN
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut output = Vec::with_capacity(Self::bytes_len(self.len()));
+        for (challenge, response) in self.challenges.iter().zip(self.responses.iter()) {
+            output.extend_from_slice(&challenge.to_bytes());
+            output.extend_from_slice(&response.to_bytes());
+        }
+        output
+    }
+
+    /// Try to read a proof covering a range of `len` values from a buffer
+    pub fn from_buffer(codec: &mut Codec<&[u8]>, len: usize) -> Result<Self, ReadError> {
+        let mut challenges = Vec::with_capacity(len);
+        let mut responses = Vec::with_capacity(len);
+        for _ in 0..len {
+            let challenge =
+                Scalar::from_bytes(codec.get_slice(Scalar::BYTES_LEN)?).ok_or_else(|| {
+                    ReadError::StructureInvalid("Invalid range proof challenge".to_string())
+                })?;
+            let response =
+                Scalar::from_bytes(codec.get_slice(Scalar::BYTES_LEN)?).ok_or_else(|| {
+                    ReadError::StructureInvalid("Invalid range proof response".to_string())
+                })?;
+            challenges.push(challenge);
+            responses.push(response);
+        }
+        Ok(Zkp {
+            challenges,
+            responses,
+        })
+    }
+}
+
+// Computes the announcement of the branch proving that `ciphertext` encrypts `candidate`,
+// from its `challenge` and `response`: (g^z * e1^-c, pk^z * (e2 - g^candidate)^-c)
+fn simulated_announcement(
+    public_key: &PublicKey,
+    ciphertext: &Ciphertext,
+    candidate: u64,
+    challenge: &Scalar,
+    response: &Scalar,
+) -> (GroupElement, GroupElement) {
+    let point_2 = &ciphertext.e2 - &(GroupElement::generator() * &Scalar::from_u64(candidate));
+    (
+        (GroupElement::generator() * response) - (&ciphertext.e1 * challenge),
+        (&public_key.pk * response) - (&point_2 * challenge),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cryptography::Keypair;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn prove_verify() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let public_key = Keypair::generate(&mut rng).public_key;
+        let crs = Crs::from_hash(b"range proof");
+
+        for value in 0..=3u64 {
+            let r = Scalar::random(&mut rng);
+            let ciphertext = public_key.encrypt_with_r(&Scalar::from_u64(value), &r);
+            let proof = Zkp::generate(&mut rng, &crs, &public_key, &ciphertext, value, &r, 0, 3);
+            assert!(proof.verify(&crs, &public_key, &ciphertext, 0, 3));
+            assert!(!proof.verify(&crs, &public_key, &ciphertext, 0, 2));
+            assert!(!proof.verify(&crs, &public_key, &ciphertext, 1, 4));
+        }
+    }
+
+    #[test]
+    fn out_of_range_value_is_rejected() {
+        let mut rng = ChaCha20Rng::from_seed([1u8; 32]);
+        let public_key = Keypair::generate(&mut rng).public_key;
+        let crs = Crs::from_hash(b"range proof");
+
+        // a proof for a bit reused on a ciphertext of the value 2
+        let r = Scalar::random(&mut rng);
+        let bit = public_key.encrypt_with_r(&Scalar::one(), &r);
+        let proof = Zkp::generate(&mut rng, &crs, &public_key, &bit, 1, &r, 0, 1);
+        let two = public_key.encrypt_with_r(&Scalar::from_u64(2), &r);
+        assert!(!proof.verify(&crs, &public_key, &two, 0, 1));
+    }
+
+    #[test]
+    fn serialisation() {
+        let mut rng = ChaCha20Rng::from_seed([2u8; 32]);
+        let public_key = Keypair::generate(&mut rng).public_key;
+        let crs = Crs::from_hash(b"range proof");
+
+        let r = Scalar::random(&mut rng);
+        let ciphertext = public_key.encrypt_with_r(&Scalar::one(), &r);
+        let proof = Zkp::generate(&mut rng, &crs, &public_key, &ciphertext, 1, &r, 0, 1);
+
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), Zkp::bytes_len(2));
+        let decoded = Zkp::from_buffer(&mut Codec::new(bytes.as_slice()), 2).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(decoded.verify(&crs, &public_key, &ciphertext, 0, 1));
+    }
+
+    #[test]
+    fn proof_is_bound_to_the_crs() {
+        let mut rng = ChaCha20Rng::from_seed([3u8; 32]);
+        let public_key = Keypair::generate(&mut rng).public_key;
+        let crs = Crs::from_hash(b"vote plan");
+        let other_crs = Crs::from_hash(b"another vote plan");
+
+        let r = Scalar::random(&mut rng);
+        let ciphertext = public_key.encrypt_with_r(&Scalar::one(), &r);
+        let proof = Zkp::generate(&mut rng, &crs, &public_key, &ciphertext, 1, &r, 0, 1);
+        assert!(proof.verify(&crs, &public_key, &ciphertext, 0, 1));
+        assert!(!proof.verify(&other_crs, &public_key, &ciphertext, 0, 1));
+    }
+}
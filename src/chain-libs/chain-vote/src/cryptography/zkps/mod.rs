@@ -2,9 +2,11 @@ mod correct_decryption;
 mod correct_hybrid_decryption_key;
 mod correct_share_generation;
 mod dl_equality;
+mod encrypted_range;
 mod unit_vector;
 
 pub use correct_decryption::CorrectElGamalDecrZkp;
 pub use correct_hybrid_decryption_key::CorrectHybridDecrKeyZkp;
 pub use correct_share_generation::CorrectShareGenerationZkp;
+pub use encrypted_range::EncryptedRangeZkp;
 pub use unit_vector::UnitVectorZkp;
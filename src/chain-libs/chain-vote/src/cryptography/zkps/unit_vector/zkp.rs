@@ -245,6 +245,22 @@ impl Zkp {
         ) == GroupElement::zero()
     }
 
+    /// Serialize the `Proof` in the format read by `from_buffer`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![self.len() as u8];
+        self.ibas
+            .iter()
+            .for_each(|iba| buf.extend_from_slice(&iba.to_bytes()));
+        self.ds
+            .iter()
+            .for_each(|d| buf.extend_from_slice(&d.to_bytes()));
+        self.zwvs
+            .iter()
+            .for_each(|zwv| buf.extend_from_slice(&zwv.to_bytes()));
+        buf.extend_from_slice(&self.r.to_bytes());
+        buf
+    }
+
     /// Try to generate a `Proof` from a buffer
     pub fn from_buffer(codec: &mut Codec<&[u8]>) -> Result<Self, ReadError> {
         let bits = codec.get_u8()? as usize;
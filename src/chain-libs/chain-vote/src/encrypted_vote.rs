@@ -1,7 +1,10 @@
-use crate::cryptography::{Ciphertext, UnitVectorZkp};
+use crate::cryptography::{Ciphertext, EncryptedRangeZkp, PublicKey, UnitVectorZkp};
 use crate::tally::ElectionFingerprint;
 use crate::Scalar;
 use crate::{Crs, ElectionPublicKey};
+use chain_core::packer::Codec;
+use chain_core::property::ReadError;
+use rand_core::{CryptoRng, RngCore};
 /// A vote is represented by a standard basis unit vector of an N dimensional space
 ///
 /// Effectively each possible vote is represented by an axis, where the actual voted option
@@ -51,6 +54,44 @@ impl Ballot {
         })
     }
 
+    /// Verify an encrypted approval vote, where up to `max_selections` options
+    /// may be selected.
+    pub fn try_from_approval_vote_and_proof(
+        vote: EncryptedVote,
+        proof: &ProofOfCorrectApprovalVote,
+        crs: &Crs,
+        pk: &ElectionPublicKey,
+        max_selections: usize,
+    ) -> Result<Self, BallotVerificationError> {
+        if !proof.verify(crs, &pk.0, &vote, max_selections) {
+            return Err(BallotVerificationError);
+        }
+
+        Ok(Self {
+            vote,
+            fingerprint: (pk, crs).into(),
+        })
+    }
+
+    /// Verify an encrypted ranked vote over `options` options. The ranks are
+    /// concatenated in the vote, so the tally needs one entry per rank and option.
+    pub fn try_from_ranked_vote_and_proof(
+        vote: EncryptedVote,
+        proof: &ProofOfCorrectRankedVote,
+        crs: &Crs,
+        pk: &ElectionPublicKey,
+        options: usize,
+    ) -> Result<Self, BallotVerificationError> {
+        if !proof.verify(crs, &pk.0, &vote, options) {
+            return Err(BallotVerificationError);
+        }
+
+        Ok(Self {
+            vote,
+            fingerprint: (pk, crs).into(),
+        })
+    }
+
     pub fn vote(&self) -> &EncryptedVote {
         &self.vote
     }
@@ -212,9 +253,333 @@ impl Iterator for UnitVectorIter {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MultiSelectionVoteError {
+    #[error("option {option} is out of range, the vote has {options} options")]
+    OptionOutOfRange { option: usize, options: usize },
+    #[error("option {0} is selected more than once")]
+    DuplicatedOption(usize),
+    #[error("at least one option needs to be selected")]
+    Empty,
+}
+
+/// An approval vote selects a subset of the options. It is represented by a vector of bits,
+/// where the approved options are enabled.
+///
+/// E.g.: given 4 possible votes in the 0-indexed set {option 0, .., option 3}, then the vote
+/// "0101" represents the approval of options 1 and 3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalVote {
+    selected: Vec<bool>,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl ApprovalVote {
+    /// Create a new vote among `options` options, approving the `selected` ones
+    pub fn new(options: usize, selected: &[usize]) -> Result<Self, MultiSelectionVoteError> {
+        if selected.is_empty() {
+            return Err(MultiSelectionVoteError::Empty);
+        }
+        let mut vote = vec![false; options];
+        for &option in selected {
+            match vote.get_mut(option) {
+                None => return Err(MultiSelectionVoteError::OptionOutOfRange { option, options }),
+                Some(true) => return Err(MultiSelectionVoteError::DuplicatedOption(option)),
+                Some(bit) => *bit = true,
+            }
+        }
+        Ok(Self { selected: vote })
+    }
+
+    pub fn len(&self) -> usize {
+        self.selected.len()
+    }
+
+    /// Number of approved options
+    pub fn selections(&self) -> usize {
+        self.selected.iter().filter(|b| **b).count()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.selected.iter().copied()
+    }
+}
+
+/// A ranked vote orders some of the options by preference. It is represented by one unit
+/// vector per rank, the first one selecting the preferred option.
+///
+/// E.g.: given 3 possible votes in the 0-indexed set {option 0, option 1, option 2}, then
+/// the vote "001 100" ranks option 2 first and option 0 second.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankedVote {
+    options: usize,
+    ranking: Vec<usize>,
+}
+
+impl RankedVote {
+    /// Create a new vote among `options` options, where `ranking` lists the options from
+    /// the preferred one.
+    pub fn new(options: usize, ranking: Vec<usize>) -> Result<Self, MultiSelectionVoteError> {
+        if ranking.is_empty() {
+            return Err(MultiSelectionVoteError::Empty);
+        }
+        let mut seen = vec![false; options];
+        for &option in &ranking {
+            match seen.get_mut(option) {
+                None => return Err(MultiSelectionVoteError::OptionOutOfRange { option, options }),
+                Some(true) => return Err(MultiSelectionVoteError::DuplicatedOption(option)),
+                Some(seen) => *seen = true,
+            }
+        }
+        Ok(Self { options, ranking })
+    }
+
+    pub fn options(&self) -> usize {
+        self.options
+    }
+
+    /// Number of ranked options
+    pub fn ranks(&self) -> usize {
+        self.ranking.len()
+    }
+
+    /// Ranked options, from the preferred one
+    pub fn ranking(&self) -> &[usize] {
+        &self.ranking
+    }
+
+    /// Unit vectors selecting the option of each rank
+    pub fn unit_vectors(&self) -> impl Iterator<Item = UnitVector> + '_ {
+        self.ranking
+            .iter()
+            .map(move |ith| UnitVector::new(self.options, *ith).expect("validated ranking"))
+    }
+}
+
+/// A proof of correct approval vote encryption, where the voter proves that each entry of
+/// the `EncryptedVote` is a bit, and that the number of approved options is bounded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProofOfCorrectApprovalVote {
+    bits: Vec<EncryptedRangeZkp>,
+    selections: EncryptedRangeZkp,
+}
+
+impl ProofOfCorrectApprovalVote {
+    pub(crate) fn generate<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        crs: &Crs,
+        public_key: &PublicKey,
+        vote: &ApprovalVote,
+        max_selections: usize,
+        encryption_randomness: &[Scalar],
+        ciphertexts: &[Ciphertext],
+    ) -> Self {
+        let bits = vote
+            .iter()
+            .zip(encryption_randomness.iter().zip(ciphertexts.iter()))
+            .map(|(bit, (r, ciphertext))| {
+                EncryptedRangeZkp::generate(rng, crs, public_key, ciphertext, bit as u64, r, 0, 1)
+            })
+            .collect();
+        let selections = EncryptedRangeZkp::generate(
+            rng,
+            crs,
+            public_key,
+            &sum_ciphertexts(ciphertexts),
+            vote.selections() as u64,
+            &Scalar::sum(encryption_randomness.iter().cloned()).unwrap_or_else(Scalar::zero),
+            1,
+            max_selections as u64,
+        );
+        Self { bits, selections }
+    }
+
+    /// Verify that `ciphertexts` encrypt an approval vote with at least one and at most
+    /// `max_selections` approved options.
+    pub fn verify(
+        &self,
+        crs: &Crs,
+        public_key: &PublicKey,
+        ciphertexts: &[Ciphertext],
+        max_selections: usize,
+    ) -> bool {
+        if max_selections == 0 || self.bits.len() != ciphertexts.len() {
+            return false;
+        }
+        self.bits
+            .iter()
+            .zip(ciphertexts.iter())
+            .all(|(proof, ciphertext)| proof.verify(crs, public_key, ciphertext, 0, 1))
+            && self.selections.verify(
+                crs,
+                public_key,
+                &sum_ciphertexts(ciphertexts),
+                1,
+                max_selections as u64,
+            )
+    }
+
+    /// Returns the number of options of the vote
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![self.bits.len() as u8];
+        self.bits
+            .iter()
+            .for_each(|proof| buf.extend_from_slice(&proof.to_bytes()));
+        buf.push(self.selections.len() as u8);
+        buf.extend_from_slice(&self.selections.to_bytes());
+        buf
+    }
+
+    /// Try to read a proof from a buffer
+    pub fn from_buffer(codec: &mut Codec<&[u8]>) -> Result<Self, ReadError> {
+        let len = codec.get_u8()? as usize;
+        let mut bits = Vec::with_capacity(len);
+        for _ in 0..len {
+            bits.push(EncryptedRangeZkp::from_buffer(codec, 2)?);
+        }
+        let max_selections = codec.get_u8()? as usize;
+        let selections = EncryptedRangeZkp::from_buffer(codec, max_selections)?;
+        Ok(Self { bits, selections })
+    }
+}
+
+/// A proof of correct ranked vote encryption, where the voter proves that each rank of
+/// the `EncryptedVote` is a unit vector, and that no option is ranked more than once.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProofOfCorrectRankedVote {
+    ranks: Vec<UnitVectorZkp>,
+    options: Vec<EncryptedRangeZkp>,
+}
+
+impl ProofOfCorrectRankedVote {
+    pub(crate) fn generate<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        crs: &Crs,
+        public_key: &PublicKey,
+        vote: &RankedVote,
+        encryption_randomness: &[Scalar],
+        ciphertexts: &[Ciphertext],
+    ) -> Self {
+        let options = vote.options();
+        let ranks = vote
+            .unit_vectors()
+            .zip(
+                encryption_randomness
+                    .chunks(options)
+                    .zip(ciphertexts.chunks(options)),
+            )
+            .map(|(unit_vector, (randomness, ciphertexts))| {
+                UnitVectorZkp::generate(rng, crs, public_key, &unit_vector, randomness, ciphertexts)
+            })
+            .collect();
+        let options = (0..options)
+            .map(|option| {
+                let column = ciphertexts.iter().skip(option).step_by(options);
+                let randomness = encryption_randomness.iter().skip(option).step_by(options);
+                EncryptedRangeZkp::generate(
+                    rng,
+                    crs,
+                    public_key,
+                    &sum_ciphertexts(column),
+                    vote.ranking().contains(&option) as u64,
+                    &Scalar::sum(randomness.cloned()).unwrap_or_else(Scalar::zero),
+                    0,
+                    1,
+                )
+            })
+            .collect();
+        Self { ranks, options }
+    }
+
+    /// Verify that `ciphertexts` encrypt a ranked vote over `options` options, i.e. a unit
+    /// vector of `options` entries per rank, with every option ranked at most once.
+    pub fn verify(
+        &self,
+        crs: &Crs,
+        public_key: &PublicKey,
+        ciphertexts: &[Ciphertext],
+        options: usize,
+    ) -> bool {
+        if options == 0
+            || ciphertexts.is_empty()
+            || ciphertexts.len() % options != 0
+            || self.ranks.len() != ciphertexts.len() / options
+            || self.ranks.len() > options
+            || self.options.len() != options
+        {
+            return false;
+        }
+        self.ranks
+            .iter()
+            .zip(ciphertexts.chunks(options))
+            .all(|(proof, rank)| proof.verify(crs, public_key, rank))
+            && self.options.iter().enumerate().all(|(option, proof)| {
+                let column = ciphertexts.iter().skip(option).step_by(options);
+                proof.verify(crs, public_key, &sum_ciphertexts(column), 0, 1)
+            })
+    }
+
+    /// Returns the number of ranks of the vote
+    pub fn ranks(&self) -> usize {
+        self.ranks.len()
+    }
+
+    /// Returns the number of options of the vote
+    pub fn options(&self) -> usize {
+        self.options.len()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![self.ranks.len() as u8];
+        self.ranks
+            .iter()
+            .for_each(|proof| buf.extend_from_slice(&proof.to_bytes()));
+        buf.push(self.options.len() as u8);
+        self.options
+            .iter()
+            .for_each(|proof| buf.extend_from_slice(&proof.to_bytes()));
+        buf
+    }
+
+    /// Try to read a proof from a buffer
+    pub fn from_buffer(codec: &mut Codec<&[u8]>) -> Result<Self, ReadError> {
+        let len = codec.get_u8()? as usize;
+        let mut ranks = Vec::with_capacity(len);
+        for _ in 0..len {
+            ranks.push(UnitVectorZkp::from_buffer(codec)?);
+        }
+        let len = codec.get_u8()? as usize;
+        let mut options = Vec::with_capacity(len);
+        for _ in 0..len {
+            options.push(EncryptedRangeZkp::from_buffer(codec, 2)?);
+        }
+        Ok(Self { ranks, options })
+    }
+}
+
+fn sum_ciphertexts<'a, I>(ciphertexts: I) -> Ciphertext
+where
+    I: IntoIterator<Item = &'a Ciphertext>,
+{
+    ciphertexts
+        .into_iter()
+        .fold(Ciphertext::zero(), |acc, ciphertext| &acc + ciphertext)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cryptography::Keypair;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
 
     #[test]
     fn unit_vector() {
@@ -245,4 +610,87 @@ mod tests {
     fn unit_binrep() {
         assert_eq!(binrep(3, 5), &[false, false, false, true, true])
     }
+
+    #[test]
+    fn multi_selection_votes_validation() {
+        assert_eq!(
+            ApprovalVote::new(3, &[]),
+            Err(MultiSelectionVoteError::Empty)
+        );
+        assert_eq!(
+            ApprovalVote::new(3, &[0, 3]),
+            Err(MultiSelectionVoteError::OptionOutOfRange {
+                option: 3,
+                options: 3
+            })
+        );
+        assert_eq!(
+            RankedVote::new(3, vec![1, 1]),
+            Err(MultiSelectionVoteError::DuplicatedOption(1))
+        );
+
+        let vote = ApprovalVote::new(4, &[3, 1]).unwrap();
+        assert_eq!(vote.iter().collect::<Vec<_>>(), [false, true, false, true]);
+        assert_eq!(vote.selections(), 2);
+
+        let vote = RankedVote::new(3, vec![2, 0]).unwrap();
+        assert_eq!(
+            vote.unit_vectors().map(|uv| uv.ith()).collect::<Vec<_>>(),
+            [2, 0]
+        );
+    }
+
+    #[test]
+    fn approval_vote_proof() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let crs = Crs::from_hash(b"approval vote");
+        let ek = ElectionPublicKey(Keypair::generate(&mut rng).public_key);
+
+        let vote = ApprovalVote::new(4, &[0, 1, 3]).unwrap();
+        let (enc, proof) = ek.encrypt_and_prove_approval_vote(&mut rng, &crs, &vote, 3);
+        assert!(proof.verify(&crs, &ek.0, &enc, 3));
+        // the proof is bound to the maximum number of selections
+        assert!(!proof.verify(&crs, &ek.0, &enc, 2));
+        // and to the vote plan it was made for
+        let other_crs = Crs::from_hash(b"another vote plan");
+        assert!(!proof.verify(&other_crs, &ek.0, &enc, 3));
+        assert!(
+            Ballot::try_from_approval_vote_and_proof(enc.clone(), &proof, &other_crs, &ek, 3)
+                .is_err()
+        );
+
+        let decoded =
+            ProofOfCorrectApprovalVote::from_buffer(&mut Codec::new(proof.to_bytes().as_slice()))
+                .unwrap();
+        assert_eq!(decoded, proof);
+
+        // a proof for too many selections cannot be produced
+        let (enc, proof) = ek.encrypt_and_prove_approval_vote(&mut rng, &crs, &vote, 4);
+        assert!(!proof.verify(&crs, &ek.0, &enc, 2));
+    }
+
+    #[test]
+    fn ranked_vote_proof() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let crs = Crs::from_hash(b"ranked vote");
+        let ek = ElectionPublicKey(Keypair::generate(&mut rng).public_key);
+
+        let vote = RankedVote::new(3, vec![2, 0]).unwrap();
+        let (enc, proof) = ek.encrypt_and_prove_ranked_vote(&mut rng, &crs, &vote);
+        assert_eq!(enc.len(), 6);
+        assert!(proof.verify(&crs, &ek.0, &enc, 3));
+        assert!(!proof.verify(&crs, &ek.0, &enc, 2));
+
+        let decoded =
+            ProofOfCorrectRankedVote::from_buffer(&mut Codec::new(proof.to_bytes().as_slice()))
+                .unwrap();
+        assert_eq!(decoded, proof);
+
+        // a ballot ranking the option 2 twice is rejected
+        let r = Scalar::random(&mut rng);
+        let mut forged = enc.clone();
+        forged[3] = ek.0.encrypt_with_r(&Scalar::zero(), &r);
+        forged[5] = ek.0.encrypt_with_r(&Scalar::one(), &r);
+        assert!(!proof.verify(&crs, &ek.0, &forged, 3));
+    }
 }
@@ -24,13 +24,16 @@ const CURVE_HRP: &str = "p256k1";
 #[cfg(crypto_backend = "__internal_ex_backend_ristretto255")]
 const CURVE_HRP: &str = "ristretto255";
 
-pub use math::babystep::BabyStepsTable as TallyOptimizationTable;
+pub use math::babystep::{
+    BabyStepsTable as TallyOptimizationTable, TableFileError as TallyOptimizationTableError,
+};
 
 pub use crate::{
     committee::{ElectionPublicKey, MemberCommunicationKey, MemberPublicKey, MemberState},
     cryptography::Ciphertext, //todo: why this?
     encrypted_vote::{
-        Ballot, BallotVerificationError, EncryptedVote, ProofOfCorrectVote,
+        ApprovalVote, Ballot, BallotVerificationError, EncryptedVote, MultiSelectionVoteError,
+        ProofOfCorrectApprovalVote, ProofOfCorrectRankedVote, ProofOfCorrectVote, RankedVote,
         UnitVectorInitializationError, Vote,
     },
     tally::{Crs, EncryptedTally, Tally, TallyDecryptShare},
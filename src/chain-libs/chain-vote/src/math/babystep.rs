@@ -1,27 +1,85 @@
 //! Implementation of baby steps giant step algorithm to solve the discrete logarithm over
 //! a group of prime order.
+//!
+//! The table of the baby steps can be saved to a file and loaded back later, so that the
+//! decryption of large tallies does not have to precompute it again every time.
+mod file;
+
+pub use self::file::TableFileError;
+
 #[cfg(crypto_backend = "__internal_ex_backend_p256k1")]
 use crate::Coordinate;
 use crate::{GroupElement, Scalar};
+use file::{Header, MappedSteps};
+use memmap2::Mmap;
 use rayon::prelude::*;
-use std::{collections::HashMap, num::NonZeroU64};
+use std::{collections::HashMap, fs::File, io::Write, num::NonZeroU64, path::Path, sync::Arc};
 
 // make steps asymmetric, in order to better use caching of baby steps.
 // balance of 2 means that baby steps are 2 time more than sqrt(max_votes)
 const DEFAULT_BALANCE: u64 = 2;
 
+// number of consecutive steps computed by a single task when generating the table
+const BABY_STEPS_PER_TASK: u64 = 1 << 16;
+
+// number of consecutive giant steps tried by a single task when solving a discrete log
+const GIANT_STEPS_PER_TASK: u64 = 1 << 10;
+
+#[cfg(crypto_backend = "__internal_ex_backend_p256k1")]
+const KEY_LEN: usize = Coordinate::BYTES_LEN;
+#[cfg(crypto_backend = "__internal_ex_backend_ristretto255")]
+const KEY_LEN: usize = GroupElement::BYTES_LEN;
+
+type StepKey = Option<[u8; KEY_LEN]>;
+
 /// Holds precomputed baby steps for the baby-stap giant-step algorithm
 /// for solving discrete log on ECC
 #[derive(Debug, Clone)]
 pub struct BabyStepsTable {
-    #[cfg(crypto_backend = "__internal_ex_backend_p256k1")]
-    table: HashMap<Option<[u8; Coordinate::BYTES_LEN]>, u64>,
-    #[cfg(crypto_backend = "__internal_ex_backend_ristretto255")]
-    table: HashMap<Option<[u8; GroupElement::BYTES_LEN]>, u64>,
+    table: Steps,
+    max_value: u64,
     baby_step_size: u64,
     giant_step: GroupElement,
 }
 
+#[derive(Debug, Clone)]
+enum Steps {
+    Memory(HashMap<StepKey, u64>),
+    Mapped(Arc<MappedSteps>),
+}
+
+impl Steps {
+    fn get(&self, key: &StepKey) -> Option<u64> {
+        match self {
+            Steps::Memory(table) => table.get(key).copied(),
+            Steps::Mapped(table) => table.get(key),
+        }
+    }
+}
+
+// With sec2 curves we can use the property that P and -P share a coordinate
+#[cfg(crypto_backend = "__internal_ex_backend_p256k1")]
+fn step_key(e: &GroupElement) -> StepKey {
+    e.compress().map(|(c, _sign)| c.to_bytes())
+}
+
+// Not with ristretto group. the ristretto group API does not allow to use the x coordinate
+// for security properties (see [here](https://github.com/dalek-cryptography/curve25519-dalek/issues/235))
+#[cfg(crypto_backend = "__internal_ex_backend_ristretto255")]
+fn step_key(e: &GroupElement) -> StepKey {
+    Some(e.to_bytes())
+}
+
+#[cfg(crypto_backend = "__internal_ex_backend_p256k1")]
+fn last_baby_step(baby_step_size: u64) -> u64 {
+    baby_step_size / 2
+}
+
+#[cfg(crypto_backend = "__internal_ex_backend_ristretto255")]
+fn last_baby_step(baby_step_size: u64) -> u64 {
+    baby_step_size
+}
+
 impl BabyStepsTable {
     /// Generate the table with asymmetrical steps,
     /// optimized for multiple reuse of the same table.
@@ -39,73 +97,138 @@ impl BabyStepsTable {
     pub fn generate_with_balance(max_value: NonZeroU64, balance: NonZeroU64) -> Self {
         let sqrt_step_size = (u64::from(max_value) as f64).sqrt().ceil() as u64;
         let baby_step_size = sqrt_step_size * u64::from(balance);
-        let mut bs = HashMap::new();
-        let gen = GroupElement::generator();
-        let mut e = GroupElement::zero();
-
-        // With sec2 curves we can use the property that P and -P share a coordinate
-        #[cfg(crypto_backend = "__internal_ex_backend_p256k1")]
-        for i in 0..=baby_step_size / 2 {
-            bs.insert(e.compress().map(|(c, _sign)| c.to_bytes()), i);
-            e = e + &gen;
-        }
-        // Not with ristretto group. the ristretto group API does not allow to use the x coordinate
-        // for security properties (see [here](https://github.com/dalek-cryptography/curve25519-dalek/issues/235))
-        #[cfg(crypto_backend = "__internal_ex_backend_ristretto255")]
-        for i in 0..=baby_step_size {
-            bs.insert(Some(e.to_bytes()), i);
-            e = e + &gen;
-        }
+        let last = last_baby_step(baby_step_size);
+
+        // every task computes its first step with a multiplication and
+        // the following ones with additions
+        let bs: HashMap<_, _> = (0..=last / BABY_STEPS_PER_TASK)
+            .into_par_iter()
+            .flat_map_iter(|task| {
+                let first = task * BABY_STEPS_PER_TASK;
+                let end = std::cmp::min(first + BABY_STEPS_PER_TASK - 1, last);
+                let gen = GroupElement::generator();
+                let mut e = &gen * first;
+                (first..=end).map(move |i| {
+                    let key = step_key(&e);
+                    e = &e + &gen;
+                    (key, i)
+                })
+            })
+            .collect();
         assert!(!bs.is_empty());
         Self {
-            table: bs,
+            table: Steps::Memory(bs),
+            max_value: max_value.into(),
             baby_step_size,
             giant_step: GroupElement::generator() * Scalar::from_u64(baby_step_size).negate(),
         }
     }
+
+    /// Maximum value this table was generated for. The table can solve the
+    /// discrete log of bigger values too, only less efficiently.
+    pub fn max_value(&self) -> u64 {
+        self.max_value
+    }
+
+    /// Load a table previously written with [`BabyStepsTable::write_to`].
+    ///
+    /// The file is memory mapped instead of being read in memory, it must
+    /// not be modified while the table is in use.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, TableFileError> {
+        let file = File::open(path)?;
+        // SAFETY: the mapped file is only read, and the table files are not
+        // expected to be modified once written
+        let mmap = unsafe { Mmap::map(&file)? };
+        let (header, steps) = MappedSteps::new(mmap)?;
+        if header.steps != last_baby_step(header.baby_step_size) + 1 {
+            return Err(TableFileError::InvalidLength);
+        }
+        Ok(Self {
+            table: Steps::Mapped(Arc::new(steps)),
+            max_value: header.max_value,
+            baby_step_size: header.baby_step_size,
+            giant_step: GroupElement::generator()
+                * Scalar::from_u64(header.baby_step_size).negate(),
+        })
+    }
+
+    /// Write the table, in a versioned format including a checksum of its content
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<(), TableFileError> {
+        let header = Header {
+            max_value: self.max_value,
+            baby_step_size: self.baby_step_size,
+            steps: last_baby_step(self.baby_step_size) + 1,
+        };
+        match &self.table {
+            Steps::Memory(table) => {
+                let mut steps = table
+                    .iter()
+                    .map(|(key, i)| (file::encode_key(key), *i))
+                    .collect::<Vec<_>>();
+                steps.par_sort_unstable();
+                file::write(writer, &header, steps.into_iter())
+            }
+            Steps::Mapped(table) => file::write(writer, &header, table.iter()),
+        }
+    }
+
+    /// Write the table to a new file at `path`, see [`BabyStepsTable::write_to`]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), TableFileError> {
+        self.write_to(File::create(path)?)
+    }
 }
 
 #[derive(Debug)]
 pub struct MaxLogExceeded;
 
 /// Solve the discrete log on ECC using baby step giant step algorithm
+///
+/// The points are solved in parallel, and the giant steps of every point
+/// are tried in parallel too, so that a few large values do not leave
+/// most of the threads idle.
 pub fn baby_step_giant_step(
     points: Vec<GroupElement>,
     max_log: u64,
     table: &BabyStepsTable,
 ) -> Result<Vec<u64>, MaxLogExceeded> {
+    points
+        .into_par_iter()
+        .map(|point| solve(point, max_log, table))
+        .collect()
+}
+
+fn solve(point: GroupElement, max_log: u64, table: &BabyStepsTable) -> Result<u64, MaxLogExceeded> {
     let baby_step_size = table.baby_step_size;
     let giant_step = &table.giant_step;
-    let table = &table.table;
-    points
+    // the search gives up after the first giant step going past max_log
+    let giant_steps = max_log / baby_step_size + 2;
+    (0..(giant_steps + GIANT_STEPS_PER_TASK - 1) / GIANT_STEPS_PER_TASK)
         .into_par_iter()
-        .map(|mut point| {
-            let mut a = 0;
-            loop {
+        .find_map_first(|task| {
+            let first = task * GIANT_STEPS_PER_TASK;
+            let end = std::cmp::min(first + GIANT_STEPS_PER_TASK, giant_steps);
+            let mut point = &point + &(giant_step * first);
+            for a in first..end {
                 #[cfg(crypto_backend = "__internal_ex_backend_p256k1")]
-                if let Some(x) = table.get(&point.compress().map(|(c, _sign)| c.to_bytes())) {
-                    let r = if Scalar::from_u64(*x) * GroupElement::generator() == point {
+                if let Some(x) = table.table.get(&step_key(&point)) {
+                    let r = if Scalar::from_u64(x) * GroupElement::generator() == point {
                         a * baby_step_size + x
                     } else {
                         a * baby_step_size - x
                     };
-                    return Ok(r);
+                    return Some(r);
                 }
 
                 #[cfg(crypto_backend = "__internal_ex_backend_ristretto255")]
-                if let Some(x) = table.get(&Some(point.to_bytes())) {
-                    let r = a * baby_step_size + x;
-                    return Ok(r);
+                if let Some(x) = table.table.get(&step_key(&point)) {
+                    return Some(a * baby_step_size + x);
                 }
 
-                if a * baby_step_size > max_log {
-                    return Err(MaxLogExceeded);
-                }
                 point = point + giant_step;
-                a += 1;
             }
+            None
         })
-        .collect()
+        .ok_or(MaxLogExceeded)
 }
 
 #[cfg(test)]
@@ -137,6 +260,59 @@ mod tests {
         assert_eq!(votes, results);
     }
 
+    #[test]
+    fn save_and_load() {
+        let table = BabyStepsTable::generate_with_balance(nz(1000), nz(2));
+        let file = tempfile::NamedTempFile::new().unwrap();
+        table.save(file.path()).unwrap();
+
+        let loaded = BabyStepsTable::load(file.path()).unwrap();
+        assert_eq!(loaded.max_value(), 1000);
+        let votes = vec![0, 1, 64, 999, 1000, 5000];
+        let points = votes
+            .iter()
+            .map(|k| GroupElement::generator() * Scalar::from_u64(*k))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            baby_step_giant_step(points, 5000, &loaded).unwrap(),
+            votes
+        );
+
+        // a loaded table is written back identically
+        let mut written = Vec::new();
+        loaded.write_to(&mut written).unwrap();
+        assert_eq!(written, std::fs::read(file.path()).unwrap());
+    }
+
+    #[test]
+    fn corrupted_file_is_rejected() {
+        let table = BabyStepsTable::generate_with_balance(nz(100), nz(1));
+        let mut bytes = Vec::new();
+        table.write_to(&mut bytes).unwrap();
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let last = bytes.len() - 33;
+        bytes[last] ^= 1;
+        std::fs::write(file.path(), &bytes).unwrap();
+        assert!(matches!(
+            BabyStepsTable::load(file.path()),
+            Err(TableFileError::ChecksumMismatch)
+        ));
+
+        std::fs::write(file.path(), &bytes[..bytes.len() - 1]).unwrap();
+        assert!(matches!(
+            BabyStepsTable::load(file.path()),
+            Err(TableFileError::InvalidLength)
+        ));
+
+        bytes[0] = b'X';
+        std::fs::write(file.path(), &bytes).unwrap();
+        assert!(matches!(
+            BabyStepsTable::load(file.path()),
+            Err(TableFileError::InvalidMagic)
+        ));
+    }
+
     fn fe_vec_generator() -> BoxGenerator<[(GroupElement, u64); 64]> {
         generator::Array64::new(generator::num::<u16>().map(|a| {
             (
@@ -0,0 +1,204 @@
+//! On-disk format of the precomputed baby steps.
+//!
+//! ```text
+//! magic           8 bytes   "CVBSTEPS"
+//! version         u16
+//! backend         u8        crypto backend the table was computed for
+//! reserved        u8        always 0
+//! max value       u64
+//! baby step size  u64
+//! number of steps u64
+//! steps           (key: 32 bytes, exponent: u64) * number of steps, sorted by key
+//! checksum        32 bytes  blake2b-256 of all the bytes above
+//! ```
+//!
+//! All integers are big endian. Sorting the steps by key allows to look them up
+//! with a binary search directly in the memory mapped file, without having to
+//! rebuild the hash table in memory.
+use super::{StepKey, KEY_LEN};
+use cryptoxide::blake2b::Blake2b;
+use cryptoxide::digest::Digest;
+use memmap2::Mmap;
+use std::io::{self, Write};
+use std::ops::Range;
+use thiserror::Error;
+
+const MAGIC: [u8; 8] = *b"CVBSTEPS";
+const VERSION: u16 = 1;
+
+#[cfg(crypto_backend = "__internal_ex_backend_ristretto255")]
+const BACKEND: u8 = 0;
+#[cfg(crypto_backend = "__internal_ex_backend_p256k1")]
+const BACKEND: u8 = 1;
+
+const HEADER_LEN: usize = 8 + 2 + 1 + 1 + 8 + 8 + 8;
+const STEP_LEN: usize = KEY_LEN + 8;
+const CHECKSUM_LEN: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum TableFileError {
+    #[error("cannot access the table file")]
+    Io(#[from] io::Error),
+    #[error("not a baby steps table file")]
+    InvalidMagic,
+    #[error("unsupported table file version {0}, expected {VERSION}")]
+    UnsupportedVersion(u16),
+    #[error("table computed with the crypto backend {found}, expected {BACKEND}")]
+    BackendMismatch { found: u8 },
+    #[error("table file size does not match its header")]
+    InvalidLength,
+    #[error("table file is corrupted, the checksum does not match")]
+    ChecksumMismatch,
+}
+
+/// Header of a table file
+pub(super) struct Header {
+    pub max_value: u64,
+    pub baby_step_size: u64,
+    pub steps: u64,
+}
+
+/// Baby steps read from a memory mapped table file
+#[derive(Debug)]
+pub(super) struct MappedSteps {
+    mmap: Mmap,
+    steps: Range<usize>,
+}
+
+impl MappedSteps {
+    /// Check the integrity of the file and return its header alongside
+    /// the steps it contains.
+    pub fn new(mmap: Mmap) -> Result<(Header, Self), TableFileError> {
+        if mmap.len() < HEADER_LEN + CHECKSUM_LEN {
+            return Err(TableFileError::InvalidLength);
+        }
+        if mmap[0..8] != MAGIC {
+            return Err(TableFileError::InvalidMagic);
+        }
+        let version = u16::from_be_bytes(mmap[8..10].try_into().unwrap());
+        if version != VERSION {
+            return Err(TableFileError::UnsupportedVersion(version));
+        }
+        if mmap[10] != BACKEND {
+            return Err(TableFileError::BackendMismatch { found: mmap[10] });
+        }
+        let header = Header {
+            max_value: read_u64(&mmap[12..20]),
+            baby_step_size: read_u64(&mmap[20..28]),
+            steps: read_u64(&mmap[28..36]),
+        };
+
+        let steps_len = usize::try_from(header.steps)
+            .ok()
+            .and_then(|steps| steps.checked_mul(STEP_LEN))
+            .ok_or(TableFileError::InvalidLength)?;
+        if mmap.len() != HEADER_LEN + steps_len + CHECKSUM_LEN {
+            return Err(TableFileError::InvalidLength);
+        }
+
+        let (content, checksum) = mmap.split_at(mmap.len() - CHECKSUM_LEN);
+        if checksum != &blake2b_256(content)[..] {
+            return Err(TableFileError::ChecksumMismatch);
+        }
+
+        let steps = HEADER_LEN..HEADER_LEN + steps_len;
+        Ok((header, Self { mmap, steps }))
+    }
+
+    pub fn get(&self, key: &StepKey) -> Option<u64> {
+        let steps = &self.mmap[self.steps.clone()];
+        let key = encode_key(key);
+        let (mut low, mut high) = (0, steps.len() / STEP_LEN);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let step = &steps[middle * STEP_LEN..(middle + 1) * STEP_LEN];
+            match step[..KEY_LEN].cmp(&key[..]) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => return Some(read_u64(&step[KEY_LEN..])),
+            }
+        }
+        None
+    }
+
+    /// Iterate over the encoded steps, already sorted by key
+    pub fn iter(&self) -> impl Iterator<Item = ([u8; KEY_LEN], u64)> + '_ {
+        self.mmap[self.steps.clone()]
+            .chunks_exact(STEP_LEN)
+            .map(|step| {
+                (
+                    step[..KEY_LEN].try_into().unwrap(),
+                    read_u64(&step[KEY_LEN..]),
+                )
+            })
+    }
+}
+
+/// Write a table file from the header and the steps, which must be sorted
+/// by their encoded keys.
+pub(super) fn write<W: Write>(
+    writer: W,
+    header: &Header,
+    sorted_steps: impl Iterator<Item = ([u8; KEY_LEN], u64)>,
+) -> Result<(), TableFileError> {
+    let mut writer = ChecksumWriter::new(writer);
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&VERSION.to_be_bytes())?;
+    writer.write_all(&[BACKEND, 0])?;
+    writer.write_all(&header.max_value.to_be_bytes())?;
+    writer.write_all(&header.baby_step_size.to_be_bytes())?;
+    writer.write_all(&header.steps.to_be_bytes())?;
+    for (key, exponent) in sorted_steps {
+        writer.write_all(&key)?;
+        writer.write_all(&exponent.to_be_bytes())?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Encode the key of a step in the fixed size format of the file.
+///
+/// The only step without a key is the neutral element with the p256k1 backend,
+/// there is no point with a zero coordinate on that curve so it is used instead.
+pub(super) fn encode_key(key: &StepKey) -> [u8; KEY_LEN] {
+    key.unwrap_or([0; KEY_LEN])
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes(bytes.try_into().unwrap())
+}
+
+fn blake2b_256(bytes: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut hasher = Blake2b::new(CHECKSUM_LEN);
+    hasher.input(bytes);
+    let mut checksum = [0; CHECKSUM_LEN];
+    hasher.result(&mut checksum);
+    checksum
+}
+
+/// Writer appending the checksum of everything written through it once finalized
+struct ChecksumWriter<W: Write> {
+    inner: io::BufWriter<W>,
+    hasher: Blake2b,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner: io::BufWriter::new(inner),
+            hasher: Blake2b::new(CHECKSUM_LEN),
+        }
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.hasher.input(bytes);
+        self.inner.write_all(bytes)
+    }
+
+    fn finalize(mut self) -> io::Result<()> {
+        let mut checksum = [0; CHECKSUM_LEN];
+        self.hasher.result(&mut checksum);
+        self.inner.write_all(&checksum)?;
+        self.inner.flush()
+    }
+}
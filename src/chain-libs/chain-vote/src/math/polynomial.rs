@@ -82,6 +82,69 @@ impl Polynomial {
     pub fn get_coefficients(&self) -> std::slice::Iter<Scalar> {
         self.elements.iter()
     }
+
+    /// Recover the unique polynomial of degree `points.len() - 1` going through all the
+    /// given `(x, y)` points, using Lagrange interpolation.
+    ///
+    /// The `x` coordinates of the points must be distinct, otherwise this function panics.
+    pub fn interpolate(points: &[(Scalar, Scalar)]) -> Polynomial {
+        assert!(!points.is_empty());
+        let mut result = Polynomial::new(points.len() - 1);
+        for (i, (xi, yi)) in points.iter().enumerate() {
+            let mut basis = Polynomial::from_vec(vec![Scalar::one()]);
+            let mut denominator = Scalar::one();
+            for (m, (xm, _)) in points.iter().enumerate() {
+                if m == i {
+                    continue;
+                }
+                basis = basis * Polynomial::from_vec(vec![xm.negate(), Scalar::one()]);
+                denominator = &denominator * &(xi - xm);
+            }
+            assert_ne!(
+                denominator,
+                Scalar::zero(),
+                "duplicated interpolation point"
+            );
+            let scale = Polynomial::from_vec(vec![yi * denominator.inverse()]);
+            result = result + basis * scale;
+        }
+        result
+    }
+}
+
+/// Compute the Lagrange coefficients to evaluate at zero the polynomial going through
+/// points with the given `x` coordinates, i.e. `P(0) = sum(coefficient_i * P(x_i))`.
+///
+/// The `x` coordinates of the points must be distinct, otherwise this function panics.
+pub fn lagrange_coefficients_at_zero(xs: &[Scalar]) -> Vec<Scalar> {
+    lagrange_coefficients_at(xs, &Scalar::zero())
+}
+
+/// Compute the Lagrange coefficients to evaluate at `at` the polynomial going through
+/// points with the given `x` coordinates, i.e. `P(at) = sum(coefficient_i * P(x_i))`.
+///
+/// The `x` coordinates of the points must be distinct, otherwise this function panics.
+pub fn lagrange_coefficients_at(xs: &[Scalar], at: &Scalar) -> Vec<Scalar> {
+    xs.iter()
+        .enumerate()
+        .map(|(i, xi)| {
+            let mut numerator = Scalar::one();
+            let mut denominator = Scalar::one();
+            for (m, xm) in xs.iter().enumerate() {
+                if m == i {
+                    continue;
+                }
+                numerator = &numerator * &(xm - at);
+                denominator = &denominator * &(xm - xi);
+            }
+            assert_ne!(
+                denominator,
+                Scalar::zero(),
+                "duplicated interpolation point"
+            );
+            numerator * denominator.inverse()
+        })
+        .collect()
 }
 
 impl std::ops::Add<Polynomial> for Polynomial {
@@ -162,4 +225,37 @@ mod tests {
             assert_eq!(a, b);
         }
     }
+
+    #[test]
+    fn interpolation() {
+        let poly = Polynomial::from_vec(vec![
+            Scalar::from_u64(7),
+            Scalar::from_u64(3),
+            Scalar::from_u64(5),
+        ]);
+        let points: Vec<_> = [2u64, 5, 9]
+            .iter()
+            .map(|x| {
+                let x = Scalar::from_u64(*x);
+                let y = poly.evaluate(&x);
+                (x, y)
+            })
+            .collect();
+
+        let interpolated = Polynomial::interpolate(&points);
+        assert_eq!(interpolated.degree(), poly.degree());
+        for (a, b) in interpolated.get_coefficients().zip(poly.get_coefficients()) {
+            assert_eq!(a, b);
+        }
+
+        let xs: Vec<_> = points.iter().map(|(x, _)| x.clone()).collect();
+        let at_zero = Scalar::sum(
+            lagrange_coefficients_at_zero(&xs)
+                .iter()
+                .zip(points.iter())
+                .map(|(l, (_, y))| l * y),
+        )
+        .unwrap();
+        assert_eq!(at_zero, poly.at_zero());
+    }
 }
@@ -1,17 +1,18 @@
 use std::num::NonZeroU64;
 
-use crate::GroupElement;
 use crate::{
     committee::*,
     cryptography::{Ciphertext, CorrectShareGenerationZkp},
     encrypted_vote::Ballot,
-    math::babystep::baby_step_giant_step,
+    math::{babystep::baby_step_giant_step, polynomial::lagrange_coefficients_at_zero},
     TallyOptimizationTable,
 };
+use crate::{GroupElement, Scalar};
 use base64::{engine::general_purpose, Engine as _};
 use cryptoxide::blake2b::Blake2b;
 use cryptoxide::digest::Digest;
 use rand_core::{CryptoRng, RngCore};
+use rayon::prelude::*;
 
 /// Secret key for opening vote
 pub type OpeningVoteKey = MemberSecretKey;
@@ -53,10 +54,10 @@ pub struct EncryptedTally {
     max_stake: u64,
 }
 
-/// `TallyDecryptShare` contains one decryption share per existing option. All committee
-/// members (todo: this will change once DKG is completed)
-/// need to submit a `TallyDecryptShare` in order to successfully decrypt
-/// the `EncryptedTally`.
+/// `TallyDecryptShare` contains one decryption share per existing option. Either all committee
+/// members, or, when the election key was generated with the distributed key generation,
+/// any `threshold` of them need to submit a `TallyDecryptShare` in order to successfully
+/// decrypt the `EncryptedTally`.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct TallyDecryptShare {
     elements: Vec<ProvenDecryptShare>,
@@ -75,6 +76,9 @@ pub struct TallyDecryptShare {
 pub struct ValidatedTally {
     r: Vec<Ciphertext>,
     decrypt_shares: Vec<TallyDecryptShare>,
+    // weight of every decrypt share when combining them: the Lagrange coefficients of
+    // the members for a threshold decryption, one otherwise.
+    coefficients: Vec<Scalar>,
     max_stake: u64,
 }
 
@@ -161,6 +165,11 @@ impl EncryptedTally {
         self.max_stake += weight;
     }
 
+    /// Upper bound of the decrypted results, the sum of the stake of the votes
+    pub fn max_stake(&self) -> u64 {
+        self.max_stake
+    }
+
     /// Given a single committee member's `secret_key`, returns a partial decryption of
     /// the `EncryptedTally`
     pub fn partial_decrypt<R: RngCore + CryptoRng>(
@@ -204,6 +213,51 @@ impl EncryptedTally {
         Ok(ValidatedTally {
             r: self.r.clone(),
             decrypt_shares: decrypt_shares.to_vec(),
+            coefficients: vec![Scalar::one(); decrypt_shares.len()],
+            max_stake: self.max_stake,
+        })
+    }
+
+    /// Given the public shares `pks` of every committee member, as produced by the distributed
+    /// key generation, and the `decrypt_shares` of at least `threshold` members, each paired
+    /// with the index of its member in `pks`, this function validates the shares and returns a
+    /// `ValidatedTally`, or a `DecryptionError`. Shares that are invalid, or that come from an
+    /// unknown or an already seen member, are skipped, and the first `threshold` valid shares
+    /// are used.
+    pub fn validate_threshold_partial_decryptions(
+        &self,
+        pks: &[MemberPublicKey],
+        threshold: usize,
+        decrypt_shares: &[(usize, TallyDecryptShare)],
+    ) -> Result<ValidatedTally, DecryptionError> {
+        if threshold == 0 || decrypt_shares.len() < threshold {
+            return Err(DecryptionError);
+        }
+
+        let mut points = Vec::with_capacity(threshold);
+        let mut valid_shares = Vec::with_capacity(threshold);
+        for (member, decrypt_share) in decrypt_shares {
+            if valid_shares.len() == threshold {
+                break;
+            }
+            let pk = match pks.get(*member) {
+                Some(pk) => pk,
+                None => continue,
+            };
+            let point = evaluation_point(*member);
+            if points.contains(&point) || !decrypt_share.verify(self, pk) {
+                continue;
+            }
+            points.push(point);
+            valid_shares.push(decrypt_share.clone());
+        }
+        if valid_shares.len() < threshold {
+            return Err(DecryptionError);
+        }
+        Ok(ValidatedTally {
+            r: self.r.clone(),
+            decrypt_shares: valid_shares,
+            coefficients: lagrange_coefficients_at_zero(&points),
             max_stake: self.max_stake,
         })
     }
@@ -260,14 +314,25 @@ impl ValidatedTally {
         self.len() == 0
     }
 
+    /// Upper bound of the decrypted results, the sum of the stake of the votes
+    pub fn max_stake(&self) -> u64 {
+        self.max_stake
+    }
+
     // Given the shares of the committee members, returns the decryption of all the
     // election options in the form of `GroupElements`. To get the final results, one
     // needs to compute the discrete logarithm of these values, which is performed in
     // `decrypt_tally`.
     fn decrypt(&self) -> Vec<GroupElement> {
         let state: Vec<GroupElement> = self.r.iter().map(|c| c.e2.clone()).collect();
-        let ris = (0..state.len())
-            .map(|i| GroupElement::sum(self.decrypt_shares.iter().map(|ds| &ds.elements[i].r1)));
+        let ris = (0..state.len()).map(|i| {
+            GroupElement::vartime_multiscalar_multiplication(
+                self.coefficients.iter().cloned(),
+                self.decrypt_shares
+                    .iter()
+                    .map(|ds| ds.elements[i].r1.clone()),
+            )
+        });
 
         state
             .iter()
@@ -348,6 +413,16 @@ impl TallyDecryptShare {
         true
     }
 
+    /// Find the index, in `pks`, of the committee member that produced this share for
+    /// `encrypted_tally`. Returns `None` if the share is not valid for any of the members.
+    pub fn find_member(
+        &self,
+        encrypted_tally: &EncryptedTally,
+        pks: &[MemberPublicKey],
+    ) -> Option<usize> {
+        pks.iter().position(|pk| self.verify(encrypted_tally, pk))
+    }
+
     /// Number of voting options this tally decrypt share structure is
     /// constructed for.
     pub fn options(&self) -> usize {
@@ -398,12 +473,30 @@ impl Tally {
         pks: &[MemberPublicKey],
         decrypt_shares: &[TallyDecryptShare],
     ) -> bool {
-        let validated_decryptions =
-            match encrypted_tally.validate_partial_decryptions(pks, decrypt_shares) {
-                Ok(dec) => dec,
-                Err(_) => return false,
-            };
+        match encrypted_tally.validate_partial_decryptions(pks, decrypt_shares) {
+            Ok(validated_decryptions) => self.verify_decryption(&validated_decryptions),
+            Err(_) => false,
+        }
+    }
 
+    /// Verifies that the decrypted tally was correctly obtained from the given
+    /// `EncryptedTally` and the `TallyDecryptShare`s of `threshold` committee members,
+    /// paired with the index of the member in the public shares `pks`.
+    pub fn verify_threshold(
+        &self,
+        encrypted_tally: &EncryptedTally,
+        pks: &[MemberPublicKey],
+        threshold: usize,
+        decrypt_shares: &[(usize, TallyDecryptShare)],
+    ) -> bool {
+        match encrypted_tally.validate_threshold_partial_decryptions(pks, threshold, decrypt_shares)
+        {
+            Ok(validated_decryptions) => self.verify_decryption(&validated_decryptions),
+            Err(_) => false,
+        }
+    }
+
+    fn verify_decryption(&self, validated_decryptions: &ValidatedTally) -> bool {
         let r_results = validated_decryptions.decrypt();
         let gen = GroupElement::generator();
         for (i, &w) in self.votes.iter().enumerate() {
@@ -432,14 +525,24 @@ pub fn batch_decrypt(
         Err(_) => Ok(validated_tallies.iter().map(trivial_convert).collect()),
         Ok(absolute_max_stake) => {
             let table = TallyOptimizationTable::generate(absolute_max_stake);
-            validated_tallies
-                .iter()
-                .map(|tally| tally.decrypt_tally(&table))
-                .collect()
+            batch_decrypt_with_table(validated_tallies, &table)
         }
     }
 }
 
+/// Decrypt a slice of `ValidatedTally`s with a precomputed baby-step giant-step table,
+/// for example one loaded from a file with [`TallyOptimizationTable::load`].
+pub fn batch_decrypt_with_table(
+    validated_tallies: impl AsRef<[ValidatedTally]>,
+    table: &TallyOptimizationTable,
+) -> Result<Vec<Tally>, TallyError> {
+    validated_tallies
+        .as_ref()
+        .par_iter()
+        .map(|tally| tally.decrypt_tally(table))
+        .collect()
+}
+
 /// Convert from [`ValidatedTally`] to a [`Tally`] when there are no votes cast
 ///
 /// If `max_stake` is not 0, this function will panic
@@ -453,7 +556,7 @@ fn trivial_convert(validated_tally: &ValidatedTally) -> Tally {
 mod tests {
     use super::*;
     use crate::cryptography::{Keypair, PublicKey};
-    use crate::encrypted_vote::Vote;
+    use crate::encrypted_vote::{ApprovalVote, RankedVote, Vote};
     use crate::Ballot;
     use rand_chacha::ChaCha20Rng;
     use rand_core::{CryptoRng, RngCore, SeedableRng};
@@ -526,6 +629,60 @@ mod tests {
         assert!(tr.verify(&encrypted_tally, &participants, &shares));
     }
 
+    #[test]
+    fn approval_and_ranked_tally() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+
+        let h = Crs::from_hash(b"approval and ranked ballots");
+        let mc1 = MemberCommunicationKey::new(&mut rng);
+        let m1 = MemberState::new(&mut rng, 1, &h, &[mc1.to_public()], 0);
+        let participants = vec![m1.public_key()];
+        let ek = ElectionPublicKey::from_participants(&participants);
+        let table = TallyOptimizationTable::generate_with_balance(
+            20.try_into().unwrap(),
+            1.try_into().unwrap(),
+        );
+
+        let vote_options = 3;
+        let max_selections = 2;
+        let mut encrypted_tally = EncryptedTally::new(vote_options, ek.clone(), h.clone());
+        for (selected, weight) in [(vec![0, 2], 6), (vec![1], 5), (vec![2, 1], 4)] {
+            let vote = ApprovalVote::new(vote_options, &selected).unwrap();
+            let (enc, proof) =
+                ek.encrypt_and_prove_approval_vote(&mut rng, &h, &vote, max_selections);
+            let ballot =
+                Ballot::try_from_approval_vote_and_proof(enc, &proof, &h, &ek, max_selections)
+                    .unwrap();
+            encrypted_tally.add(&ballot, weight);
+        }
+        let shares = vec![encrypted_tally.partial_decrypt(&mut rng, m1.secret_key())];
+        let tr = encrypted_tally
+            .validate_partial_decryptions(&participants, &shares)
+            .unwrap()
+            .decrypt_tally(&table)
+            .unwrap();
+        assert_eq!(tr.votes, vec![6, 9, 10]);
+
+        // two ranks over three options, stored rank after rank
+        let ranks = 2;
+        let mut encrypted_tally = EncryptedTally::new(vote_options * ranks, ek.clone(), h.clone());
+        for (ranking, weight) in [(vec![0, 2], 6), (vec![1, 0], 5), (vec![0, 1], 4)] {
+            let vote = RankedVote::new(vote_options, ranking).unwrap();
+            let (enc, proof) = ek.encrypt_and_prove_ranked_vote(&mut rng, &h, &vote);
+            let ballot =
+                Ballot::try_from_ranked_vote_and_proof(enc, &proof, &h, &ek, vote_options).unwrap();
+            encrypted_tally.add(&ballot, weight);
+        }
+        let shares = vec![encrypted_tally.partial_decrypt(&mut rng, m1.secret_key())];
+        let tr = encrypted_tally
+            .validate_partial_decryptions(&participants, &shares)
+            .unwrap()
+            .decrypt_tally(&table)
+            .unwrap();
+        assert_eq!(tr.votes, vec![10, 5, 0, 5, 4, 6]);
+        assert!(tr.verify(&encrypted_tally, &participants, &shares));
+    }
+
     #[test]
     fn encdec3() {
         let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
@@ -783,6 +940,154 @@ mod tests {
         assert!(tally_from_bytes.is_some());
     }
 
+    fn run_dkg<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        crs: &Crs,
+        threshold: usize,
+        size: usize,
+    ) -> Vec<dkg::DkgOutput> {
+        let comm_keys: Vec<_> = (0..size)
+            .map(|_| MemberCommunicationKey::new(rng))
+            .collect();
+        let comm_pks: Vec<_> = comm_keys.iter().map(|k| k.to_public()).collect();
+        let states: Vec<_> = (0..size)
+            .map(|i| MemberState::new(rng, threshold, crs, &comm_pks, i))
+            .collect();
+        let mut dkgs: Vec<_> = states
+            .iter()
+            .zip(comm_keys)
+            .map(|(state, comm_key)| dkg::MemberDkg::new(state, comm_key, crs, size))
+            .collect();
+
+        let sharings: Vec<_> = states.iter().map(|s| s.sharing_broadcast()).collect();
+        let coefficients: Vec<_> = states.iter().map(|s| s.coefficients_broadcast()).collect();
+        for dkg in dkgs.iter_mut() {
+            assert!(dkg.process_sharings(&sharings).is_empty());
+            assert!(dkg.process_coefficients(&coefficients).is_empty());
+        }
+        dkgs.iter().map(|dkg| dkg.finalize().unwrap()).collect()
+    }
+
+    #[test]
+    fn inconsistent_public_shares_are_rejected() {
+        let mut rng = ChaCha20Rng::from_seed([1u8; 32]);
+        let h = Crs::from_hash(b"inconsistent public shares");
+
+        let threshold = 2;
+        let outputs = run_dkg(&mut rng, &h, threshold, 3);
+        let public_shares = outputs[0].public_shares().to_vec();
+        assert!(ElectionPublicKey::from_public_shares(&public_shares, threshold).is_some());
+        assert!(ElectionPublicKey::from_public_shares(&public_shares, 0).is_none());
+        assert!(ElectionPublicKey::from_public_shares(&public_shares, 4).is_none());
+
+        // a share which is not on the sharing polynomial, e.g. a key of another committee
+        let other = run_dkg(&mut rng, &h, threshold, 3);
+        for index in 0..public_shares.len() {
+            let mut shares = public_shares.clone();
+            shares[index] = other[0].public_shares()[index].clone();
+            assert!(ElectionPublicKey::from_public_shares(&shares, threshold).is_none());
+        }
+        // every set of shares is consistent with a polynomial of the maximal degree
+        let mut shares = public_shares.clone();
+        shares[2] = other[0].public_shares()[2].clone();
+        assert!(ElectionPublicKey::from_public_shares(&shares, 3).is_some());
+    }
+
+    #[test]
+    fn threshold_decryption() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+
+        let shared_string =
+            b"Example of a shared string. This should be VotePlan.to_id()".to_owned();
+        let h = Crs::from_hash(&shared_string);
+
+        let threshold = 2;
+        let outputs = run_dkg(&mut rng, &h, threshold, 3);
+        let public_shares = outputs[0].public_shares().to_vec();
+        let ek = ElectionPublicKey::from_public_shares(&public_shares, threshold).unwrap();
+        assert_eq!(&ek, outputs[0].election_public_key());
+
+        let vote_options = 2;
+        let e1 = get_encrypted_ballot(&mut rng, &ek, &h, Vote::new(vote_options, 0).unwrap());
+        let e2 = get_encrypted_ballot(&mut rng, &ek, &h, Vote::new(vote_options, 1).unwrap());
+        let e3 = get_encrypted_ballot(&mut rng, &ek, &h, Vote::new(vote_options, 0).unwrap());
+
+        let mut encrypted_tally = EncryptedTally::new(vote_options, ek, h);
+        encrypted_tally.add(&e1, 1);
+        encrypted_tally.add(&e2, 3);
+        encrypted_tally.add(&e3, 4);
+
+        let shares: Vec<_> = outputs
+            .iter()
+            .map(|output| encrypted_tally.partial_decrypt(&mut rng, output.secret_share()))
+            .collect();
+        assert_eq!(
+            shares[2].find_member(&encrypted_tally, &public_shares),
+            Some(2)
+        );
+
+        let table = TallyOptimizationTable::generate_with_balance(
+            20.try_into().unwrap(),
+            1.try_into().unwrap(),
+        );
+        for members in [[0, 1], [0, 2], [2, 1]] {
+            let decrypt_shares: Vec<_> = members
+                .iter()
+                .map(|member| (*member, shares[*member].clone()))
+                .collect();
+            let tr = encrypted_tally
+                .validate_threshold_partial_decryptions(&public_shares, threshold, &decrypt_shares)
+                .unwrap()
+                .decrypt_tally(&table)
+                .unwrap();
+            assert_eq!(tr.votes, vec![5, 3]);
+            assert!(tr.verify_threshold(
+                &encrypted_tally,
+                &public_shares,
+                threshold,
+                &decrypt_shares
+            ));
+        }
+
+        // not enough shares
+        assert!(encrypted_tally
+            .validate_threshold_partial_decryptions(
+                &public_shares,
+                threshold,
+                &[(1, shares[1].clone())]
+            )
+            .is_err());
+        // the same member twice
+        assert!(encrypted_tally
+            .validate_threshold_partial_decryptions(
+                &public_shares,
+                threshold,
+                &[(1, shares[1].clone()), (1, shares[1].clone())]
+            )
+            .is_err());
+        // share attributed to the wrong member
+        assert!(encrypted_tally
+            .validate_threshold_partial_decryptions(
+                &public_shares,
+                threshold,
+                &[(0, shares[1].clone()), (2, shares[2].clone())]
+            )
+            .is_err());
+        // invalid and unknown shares are skipped as long as enough valid ones follow
+        let decrypt_shares = [
+            (0, shares[1].clone()),
+            (7, shares[0].clone()),
+            (2, shares[2].clone()),
+            (1, shares[1].clone()),
+        ];
+        let tr = encrypted_tally
+            .validate_threshold_partial_decryptions(&public_shares, threshold, &decrypt_shares)
+            .unwrap()
+            .decrypt_tally(&table)
+            .unwrap();
+        assert_eq!(tr.votes, vec![5, 3]);
+    }
+
     #[test]
     fn batch_decrypt_empty_slice() {
         assert_eq!(batch_decrypt(&[]).unwrap(), []);
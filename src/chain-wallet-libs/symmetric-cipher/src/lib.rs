@@ -1,3 +1,4 @@
+use argon2::{Algorithm, Argon2, Params, Version};
 use cryptoxide::chacha20poly1305::ChaCha20Poly1305;
 use cryptoxide::hmac::Hmac;
 use cryptoxide::pbkdf2::pbkdf2;
@@ -20,30 +21,144 @@ pub enum Error {
     IoError(#[from] std::io::Error),
     #[error("wrong password")]
     AuthenticationFailed,
+    #[error("invalid key derivation parameters")]
+    InvalidKdfParameters,
 }
 
-struct View<T: AsRef<[u8]>>(pub T);
+struct View<T: AsRef<[u8]>> {
+    inner: T,
+    header_size: usize,
+}
+
+/// Key derived with PBKDF2 HMAC SHA512
+pub const PROTOCOL_PBKDF2: u8 = 1;
+/// Key derived with Argon2id, with the parameters following the protocol byte
+pub const PROTOCOL_ARGON2ID: u8 = 2;
 
 const ITERS: u32 = 12983;
 const PROTOCOL_SIZE: usize = 1;
+const KDF_PARAMS_SIZE: usize = 12;
 const SALT_SIZE: usize = 16;
 const NONCE_SIZE: usize = 12;
 const TAG_SIZE: usize = 16;
 
+/// Upper bounds on the Argon2id parameters, four times the [`KdfParams::default`] ones, so that a
+/// crafted payload cannot make the decryption use more than 256 MiB or run for long on a phone
+const MAX_MEMORY_KIB: u32 = 4 * 64 * 1024;
+const MAX_ITERATIONS: u32 = 4 * 3;
+const MAX_PARALLELISM: u32 = 4;
+
+/// Argon2id parameters, recorded in the header of the encrypted data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory size, in KiB
+    pub memory_kib: u32,
+    /// Number of passes over the memory
+    pub iterations: u32,
+    /// Number of lanes
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// 64 MiB and 3 passes over a single lane, which takes a fraction of a second on a phone
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    fn to_bytes(self) -> [u8; KDF_PARAMS_SIZE] {
+        let mut bytes = [0u8; KDF_PARAMS_SIZE];
+        bytes[0..4].copy_from_slice(&self.memory_kib.to_be_bytes());
+        bytes[4..8].copy_from_slice(&self.iterations.to_be_bytes());
+        bytes[8..12].copy_from_slice(&self.parallelism.to_be_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let word = |i: usize| u32::from_be_bytes(bytes[i..i + 4].try_into().unwrap());
+        Self {
+            memory_kib: word(0),
+            iterations: word(4),
+            parallelism: word(8),
+        }
+    }
+
+    fn to_argon2(self) -> Result<Argon2<'static>, Error> {
+        if self.memory_kib > MAX_MEMORY_KIB
+            || self.iterations > MAX_ITERATIONS
+            || self.parallelism > MAX_PARALLELISM
+        {
+            return Err(Error::InvalidKdfParameters);
+        }
+
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, None)
+            .map_err(|_| Error::InvalidKdfParameters)?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+/// Encrypts `data` with a key derived from `password` with PBKDF2 (protocol 1)
 pub fn encrypt<G: rand::Rng + rand::CryptoRng>(
     password: impl AsRef<[u8]>,
     data: impl AsRef<[u8]>,
-    mut random: G,
+    random: G,
 ) -> Result<Box<[u8]>, Error> {
-    if data.as_ref().is_empty() {
+    check_plaintext(data.as_ref())?;
+
+    seal(&[PROTOCOL_PBKDF2], data, random, |salt| {
+        Ok(derive_symmetric_key(password, salt))
+    })
+}
+
+/// Encrypts `data` with a key derived from `password` with Argon2id (protocol 2)
+///
+/// Unlike PBKDF2, Argon2id is memory-hard, which makes brute forcing short passwords with
+/// dedicated hardware much more expensive.
+pub fn encrypt_argon2id<G: rand::Rng + rand::CryptoRng>(
+    password: impl AsRef<[u8]>,
+    data: impl AsRef<[u8]>,
+    params: KdfParams,
+    random: G,
+) -> Result<Box<[u8]>, Error> {
+    check_plaintext(data.as_ref())?;
+    let argon2 = params.to_argon2()?;
+
+    let mut header = [0u8; PROTOCOL_SIZE + KDF_PARAMS_SIZE];
+    header[0] = PROTOCOL_ARGON2ID;
+    header[PROTOCOL_SIZE..].copy_from_slice(&params.to_bytes());
+
+    seal(&header, data, random, |salt| {
+        derive_argon2id_key(&argon2, password, salt)
+    })
+}
+
+fn check_plaintext(data: &[u8]) -> Result<(), Error> {
+    if data.is_empty() {
         return Err(Error::EmptyPayload);
     }
 
-    if data.as_ref().len() % 64 != 0 {
+    if data.len() % 64 != 0 {
         return Err(Error::InvalidDataLength);
     }
 
-    let aad = [];
+    Ok(())
+}
+
+/// Produces `header || salt || nonce || ciphertext || tag`
+///
+/// The header is authenticated, except for protocol 1 which predates this.
+fn seal<G: rand::Rng + rand::CryptoRng>(
+    header: &[u8],
+    data: impl AsRef<[u8]>,
+    mut random: G,
+    derive_key: impl FnOnce([u8; SALT_SIZE]) -> Result<Zeroizing<[u8; 32]>, Error>,
+) -> Result<Box<[u8]>, Error> {
+    let aad = aad(header);
 
     let salt = {
         let mut salt = [0u8; SALT_SIZE];
@@ -57,9 +172,9 @@ pub fn encrypt<G: rand::Rng + rand::CryptoRng>(
         nonce
     };
 
-    let symmetric_key = derive_symmetric_key(password, salt);
+    let symmetric_key = derive_key(salt)?;
 
-    let mut chacha20 = ChaCha20Poly1305::new(&*symmetric_key, &nonce, &aad);
+    let mut chacha20 = ChaCha20Poly1305::new(&*symmetric_key, &nonce, aad);
 
     let (ciphertext, tag) = {
         let mut ciphertext = vec![0u8; data.as_ref().len()];
@@ -69,10 +184,9 @@ pub fn encrypt<G: rand::Rng + rand::CryptoRng>(
         (ciphertext.into_boxed_slice(), tag)
     };
 
-    let mut buffer =
-        vec![0u8; PROTOCOL_SIZE + SALT_SIZE + NONCE_SIZE + ciphertext.len() + TAG_SIZE];
+    let mut buffer = vec![0u8; header.len() + SALT_SIZE + NONCE_SIZE + ciphertext.len() + TAG_SIZE];
 
-    let parts: [&[u8]; 5] = [&[1u8], &salt, &nonce, &ciphertext, &tag];
+    let parts: [&[u8]; 5] = [header, &salt, &nonce, &ciphertext, &tag];
 
     let mut low = 0;
 
@@ -84,18 +198,17 @@ pub fn encrypt<G: rand::Rng + rand::CryptoRng>(
     Ok(buffer.into_boxed_slice())
 }
 
+/// Decrypts data produced by either [`encrypt`] or [`encrypt_argon2id`]
 pub fn decrypt<T: AsRef<[u8]>>(password: impl AsRef<[u8]>, data: T) -> Result<Box<[u8]>, Error> {
     let data = View::new(data)?;
 
-    let aad = [];
-
-    if data.protocol() != 0x1 {
-        return Err(Error::InvalidProtocol);
-    }
-
-    let key = derive_symmetric_key(password, data.salt().try_into().unwrap());
+    let salt = data.salt().try_into().unwrap();
+    let key = match data.protocol() {
+        PROTOCOL_PBKDF2 => derive_symmetric_key(password, salt),
+        _ => derive_argon2id_key(&data.kdf_params().to_argon2()?, password, salt)?,
+    };
 
-    let mut chacha20 = ChaCha20Poly1305::new(&*key, data.nonce(), &aad);
+    let mut chacha20 = ChaCha20Poly1305::new(&*key, data.nonce(), aad(data.header()));
 
     let mut plaintext = vec![0u8; data.encrypted_data().len()];
 
@@ -106,12 +219,27 @@ pub fn decrypt<T: AsRef<[u8]>>(password: impl AsRef<[u8]>, data: T) -> Result<Bo
     }
 }
 
+fn aad(header: &[u8]) -> &[u8] {
+    if header == [PROTOCOL_PBKDF2] {
+        &[]
+    } else {
+        header
+    }
+}
+
 impl<T: AsRef<[u8]>> View<T> {
     fn new(inner: T) -> Result<View<T>, Error> {
-        if inner.as_ref().len() <= PROTOCOL_SIZE + SALT_SIZE + NONCE_SIZE + TAG_SIZE {
+        let header_size = match inner.as_ref().first() {
+            Some(&PROTOCOL_PBKDF2) => PROTOCOL_SIZE,
+            Some(&PROTOCOL_ARGON2ID) => PROTOCOL_SIZE + KDF_PARAMS_SIZE,
+            Some(_) => return Err(Error::InvalidProtocol),
+            None => return Err(Error::MalformedInput),
+        };
+
+        if inner.as_ref().len() <= header_size + SALT_SIZE + NONCE_SIZE + TAG_SIZE {
             Err(Error::MalformedInput)
         } else {
-            let data = Self(inner);
+            let data = Self { inner, header_size };
 
             if data.encrypted_data().is_empty() {
                 Err(Error::EmptyPayload)
@@ -124,32 +252,41 @@ impl<T: AsRef<[u8]>> View<T> {
     }
 
     fn protocol(&self) -> u8 {
-        self.0.as_ref()[0]
+        self.inner.as_ref()[0]
+    }
+
+    fn header(&self) -> &[u8] {
+        &self.inner.as_ref()[..self.header_size]
+    }
+
+    fn kdf_params(&self) -> KdfParams {
+        KdfParams::from_bytes(&self.header()[PROTOCOL_SIZE..])
     }
 
     fn salt(&self) -> &[u8] {
-        &self.0.as_ref()[PROTOCOL_SIZE..PROTOCOL_SIZE + SALT_SIZE]
+        &self.inner.as_ref()[self.header_size..self.header_size + SALT_SIZE]
     }
 
     fn nonce(&self) -> &[u8] {
-        &self.0.as_ref()[PROTOCOL_SIZE + SALT_SIZE..PROTOCOL_SIZE + SALT_SIZE + NONCE_SIZE]
+        let start = self.header_size + SALT_SIZE;
+        &self.inner.as_ref()[start..start + NONCE_SIZE]
     }
 
     fn encrypted_data(&self) -> &[u8] {
         let data_len = self
-            .0
+            .inner
             .as_ref()
             .len()
-            .checked_sub(PROTOCOL_SIZE + SALT_SIZE + NONCE_SIZE + TAG_SIZE)
+            .checked_sub(self.header_size + SALT_SIZE + NONCE_SIZE + TAG_SIZE)
             .unwrap();
 
-        let starting_pos = PROTOCOL_SIZE + SALT_SIZE + NONCE_SIZE;
-        &self.0.as_ref()[starting_pos..starting_pos + data_len]
+        let starting_pos = self.header_size + SALT_SIZE + NONCE_SIZE;
+        &self.inner.as_ref()[starting_pos..starting_pos + data_len]
     }
 
     fn tag(&self) -> &[u8] {
-        let start = self.0.as_ref().len().checked_sub(TAG_SIZE).unwrap();
-        &self.0.as_ref()[start..]
+        let start = self.inner.as_ref().len().checked_sub(TAG_SIZE).unwrap();
+        &self.inner.as_ref()[start..]
     }
 }
 
@@ -162,6 +299,20 @@ fn derive_symmetric_key(password: impl AsRef<[u8]>, salt: [u8; SALT_SIZE]) -> Ze
     Zeroizing::new(symmetric_key)
 }
 
+fn derive_argon2id_key(
+    argon2: &Argon2,
+    password: impl AsRef<[u8]>,
+    salt: [u8; SALT_SIZE],
+) -> Result<Zeroizing<[u8; 32]>, Error> {
+    let mut symmetric_key = Zeroizing::new([0u8; 32]);
+
+    argon2
+        .hash_password_into(password.as_ref(), &salt, &mut *symmetric_key)
+        .map_err(|_| Error::InvalidKdfParameters)?;
+
+    Ok(symmetric_key)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -236,6 +387,138 @@ mod tests {
         assert_eq!(&decrypt(password, slice).unwrap()[..], &bytes[..]);
     }
 
+    fn fast_kdf_params() -> KdfParams {
+        KdfParams {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+
+    #[test]
+    fn encrypt_decrypt_argon2id() {
+        let bytes = [7u8; 64 * 2];
+        let password = b"correct horse battery staple";
+
+        let slice =
+            encrypt_argon2id(password, &bytes[..], fast_kdf_params(), get_random_gen()).unwrap();
+
+        assert_eq!(slice[0], PROTOCOL_ARGON2ID);
+        assert_eq!(&decrypt(password, slice).unwrap()[..], &bytes[..]);
+    }
+
+    #[test]
+    fn argon2id_wrong_password() {
+        let bytes = [7u8; 64];
+
+        let slice = encrypt_argon2id(
+            [1u8, 2, 3, 4],
+            &bytes[..],
+            fast_kdf_params(),
+            get_random_gen(),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            decrypt([5u8, 6, 7, 8], slice),
+            Err(Error::AuthenticationFailed)
+        ));
+    }
+
+    #[test]
+    fn argon2id_parameters_are_authenticated() {
+        let bytes = [7u8; 64];
+        let password = [1u8, 2, 3, 4];
+
+        let mut slice =
+            encrypt_argon2id(password, &bytes[..], fast_kdf_params(), get_random_gen()).unwrap();
+        // one more pass
+        slice[PROTOCOL_SIZE + 7] += 1;
+
+        assert!(matches!(
+            decrypt(password, slice),
+            Err(Error::AuthenticationFailed)
+        ));
+    }
+
+    #[test]
+    fn argon2id_parameters_are_bounded() {
+        let bytes = [7u8; 64];
+        let password = [1u8, 2, 3, 4];
+        let params = KdfParams {
+            memory_kib: MAX_MEMORY_KIB + 1,
+            ..fast_kdf_params()
+        };
+
+        assert!(matches!(
+            encrypt_argon2id(password, &bytes[..], params, get_random_gen()),
+            Err(Error::InvalidKdfParameters)
+        ));
+
+        let mut slice =
+            encrypt_argon2id(password, &bytes[..], fast_kdf_params(), get_random_gen()).unwrap();
+        slice[PROTOCOL_SIZE..PROTOCOL_SIZE + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+
+        assert!(matches!(
+            decrypt(password, slice),
+            Err(Error::InvalidKdfParameters)
+        ));
+    }
+
+    #[test]
+    fn argon2id_header_above_the_cap_is_rejected() {
+        let bytes = [7u8; 64];
+        let password = [1u8, 2, 3, 4];
+
+        let slice =
+            encrypt_argon2id(password, &bytes[..], fast_kdf_params(), get_random_gen()).unwrap();
+
+        let above_cap = [
+            KdfParams {
+                memory_kib: MAX_MEMORY_KIB + 1,
+                ..fast_kdf_params()
+            },
+            KdfParams {
+                iterations: MAX_ITERATIONS + 1,
+                ..fast_kdf_params()
+            },
+            KdfParams {
+                parallelism: MAX_PARALLELISM + 1,
+                ..fast_kdf_params()
+            },
+        ];
+        for params in above_cap {
+            let mut slice = slice.clone();
+            slice[PROTOCOL_SIZE..PROTOCOL_SIZE + KDF_PARAMS_SIZE]
+                .copy_from_slice(&params.to_bytes());
+
+            assert!(matches!(
+                decrypt(password, slice),
+                Err(Error::InvalidKdfParameters)
+            ));
+        }
+
+        let at_cap = KdfParams {
+            memory_kib: MAX_MEMORY_KIB,
+            ..fast_kdf_params()
+        };
+        assert!(at_cap.to_argon2().is_ok());
+    }
+
+    #[test]
+    fn unknown_protocol() {
+        let bytes = [7u8; 64];
+        let password = [1u8, 2, 3, 4];
+
+        let mut slice = encrypt(password, &bytes[..], get_random_gen()).unwrap();
+        slice[0] = 3;
+
+        assert!(matches!(
+            decrypt(password, slice),
+            Err(Error::InvalidProtocol)
+        ));
+    }
+
     #[test]
     fn wrong_password() {
         let mut bytes: Vec<u8> = vec![];
@@ -0,0 +1,35 @@
+-- Catalyst Event Database
+
+-- Encrypt the secret keys at rest.
+-- Each row gets its own random data key, wrapped with the secrets key supplied to the services.
+-- The secret columns hold the secrets encrypted with the data key of their row.
+
+-- Rows existing before this migration keep their secrets encrypted by the voting node with the `SECRET_SECRET`
+-- password, as bytes, and a NULL `data_key`, until they are decrypted and encrypted again with a data key
+-- by the services holding both keys (`cat-data-service encrypt-secrets`).
+-- The secrets of rows with a NULL `data_key` are never returned by the event-db queries.
+
+ALTER TABLE voting_node
+    ALTER COLUMN seckey TYPE BYTEA USING convert_to(seckey, 'UTF8'),
+    ALTER COLUMN netkey TYPE BYTEA USING convert_to(netkey, 'UTF8'),
+    ADD COLUMN data_key BYTEA NULL;
+
+COMMENT ON COLUMN voting_node.seckey IS 'Encrypted secret key from Ed25519 pair for the node. Used as the node secret.
+Encrypted with the data key of the row.';
+COMMENT ON COLUMN voting_node.netkey IS 'Encrypted Ed25519 secret key for the node. Used as the node p2p topology key.
+Encrypted with the data key of the row.';
+COMMENT ON COLUMN voting_node.data_key IS 'Random data key encrypting the secrets of the row, itself encrypted with the secrets key.
+NULL if the secrets of the row are still encrypted with the legacy password.';
+
+
+ALTER TABLE committee_member
+    ALTER COLUMN comm_sk TYPE BYTEA USING convert_to(comm_sk, 'UTF8'),
+    ALTER COLUMN member_sk TYPE BYTEA USING convert_to(member_sk, 'UTF8'),
+    ADD COLUMN data_key BYTEA NULL;
+
+COMMENT ON COLUMN committee_member.comm_sk IS 'Encrypted committee member communication secret key.
+Encrypted with the data key of the row.';
+COMMENT ON COLUMN committee_member.member_sk IS 'Encrypted committee member secret key.
+Encrypted with the data key of the row.';
+COMMENT ON COLUMN committee_member.data_key IS 'Random data key encrypting the secrets of the row, itself encrypted with the secrets key.
+NULL if the secrets of the row are still encrypted with the legacy password.';
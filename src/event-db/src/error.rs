@@ -13,6 +13,16 @@ pub enum Error {
     Unknown(String),
     #[error(transparent)]
     VarError(#[from] VarError),
+    #[error("the secrets key is not set, cannot access the secrets")]
+    MissingSecretsKey,
+    #[error("cannot encrypt the secret")]
+    SecretEncryption,
+    #[error("cannot decrypt the secret, the secrets key may be wrong")]
+    SecretDecryption,
+    #[error("cannot decrypt the legacy secret, the legacy password may be wrong")]
+    LegacySecretDecryption,
+    #[error("the secrets are still encrypted with the legacy password, error: {0}")]
+    UnencryptedSecret(String),
 }
 
 impl From<RunError<tokio_postgres::Error>> for Error {
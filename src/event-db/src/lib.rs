@@ -4,6 +4,7 @@ use bb8_postgres::PostgresConnectionManager;
 use dotenvy::dotenv;
 use error::Error;
 use schema_check::SchemaVersion;
+use secret::SecretsKey;
 use std::str::FromStr;
 use tokio_postgres::NoTls;
 
@@ -11,6 +12,7 @@ mod config_table;
 pub mod error;
 pub mod queries;
 pub mod schema_check;
+pub mod secret;
 pub mod types;
 
 /// Database URL Environment Variable name.
@@ -19,7 +21,7 @@ const DATABASE_URL_ENVVAR: &str = "EVENT_DB_URL";
 
 /// Database version this crate matches.
 /// Must equal the last Migrations Version Number.
-pub const DATABASE_SCHEMA_VERSION: i32 = 9;
+pub const DATABASE_SCHEMA_VERSION: i32 = 10;
 
 #[allow(unused)]
 /// Connection to the Election Database
@@ -28,6 +30,22 @@ pub struct EventDB {
     // All database operations (queries, inserts, etc) should be constrained
     // to this crate and should be exported with a clean data access api.
     pool: Pool<PostgresConnectionManager<NoTls>>,
+    // Key encrypting the secrets stored in the database, if supplied.
+    secrets_key: Option<SecretsKey>,
+}
+
+impl EventDB {
+    /// Use `secrets_key` to encrypt and decrypt the secrets stored in the database,
+    /// instead of the one from the "`EVENT_DB_SECRETS_KEY`" env var.
+    #[must_use]
+    pub fn with_secrets_key(mut self, secrets_key: SecretsKey) -> Self {
+        self.secrets_key = Some(secrets_key);
+        self
+    }
+
+    fn secrets_key(&self) -> Result<&SecretsKey, Error> {
+        self.secrets_key.as_ref().ok_or(Error::MissingSecretsKey)
+    }
 }
 
 /// Establish a connection to the database, and check the schema is up-to-date.
@@ -51,6 +69,10 @@ pub struct EventDB {
 ///
 /// The env var "`DATABASE_URL`" can be set directly as an anv var, or in a
 /// `.env` file.
+///
+/// The key encrypting the secrets stored in the database is read from the
+/// env var "`EVENT_DB_SECRETS_KEY`" if it is set, see [`EventDB::with_secrets_key`].
+/// Without it, the queries reading or writing secrets return an error.
 pub async fn establish_connection(url: Option<&str>) -> Result<EventDB, Error> {
     // Support env vars in a `.env` file,  doesn't need to exist.
     dotenv().ok();
@@ -67,7 +89,10 @@ pub async fn establish_connection(url: Option<&str>) -> Result<EventDB, Error> {
 
     let pool = Pool::builder().build(pg_mgr).await?;
 
-    let db = EventDB { pool };
+    let db = EventDB {
+        pool,
+        secrets_key: SecretsKey::from_env(),
+    };
 
     db.schema_version_check().await?;
 
@@ -1,4 +1,5 @@
 use self::{
+    automation::AutomationQueries,
     event::{
         ballot::BallotQueries, objective::ObjectiveQueries, proposal::ProposalQueries,
         review::ReviewQueries, EventQueries,
@@ -8,6 +9,7 @@ use self::{
 };
 use crate::EventDB;
 
+pub mod automation;
 pub mod event;
 pub mod registration;
 pub mod search;
@@ -23,6 +25,7 @@ pub trait EventDbQueries:
     + SearchQueries
     + BallotQueries
     + vit_ss::fund::VitSSFundQueries
+    + AutomationQueries
 {
 }
 
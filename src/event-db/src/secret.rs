@@ -0,0 +1,227 @@
+//! Envelope encryption of the secrets stored in the event database.
+//!
+//! Each row holding secrets has its own random data key, which encrypts the secrets of the row.
+//! The data key is stored alongside, encrypted with the secrets key supplied to the services.
+//! Rotating the secrets key only requires to re-encrypt the data keys.
+//!
+//! Before that, the voting node encrypted each secret with the `SECRET_SECRET` password,
+//! these legacy secrets are only decrypted to encrypt them again with a data key.
+use crate::error::Error;
+use base64::{engine::general_purpose::URL_SAFE, Engine as _};
+use hmac::Hmac;
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// Secrets Key Environment Variable name.
+pub(crate) const SECRETS_KEY_ENVVAR: &str = "EVENT_DB_SECRETS_KEY";
+
+/// `symmetric-cipher` only encrypts data which is a multiple of this size
+const BLOCK_SIZE: usize = 64;
+const LENGTH_SIZE: usize = 4;
+const DATA_KEY_SIZE: usize = 64;
+
+/// Key derivation parameters of the legacy secrets, see `voting_node.utils.encrypt_secret`
+const LEGACY_SALT_SIZE: usize = 16;
+const LEGACY_KEY_SIZE: usize = 32;
+const LEGACY_ITERATIONS: u32 = 480_000;
+
+/// A secret read from, or to be written to, the event database.
+///
+/// The secret is never printed by `Debug`, and is wiped from memory when dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(Zeroizing<String>);
+
+impl Secret {
+    pub fn new(secret: String) -> Self {
+        Self(Zeroizing::new(secret))
+    }
+
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(<redacted>)")
+    }
+}
+
+/// Key encrypting the data keys of the rows holding secrets.
+#[derive(Clone)]
+pub struct SecretsKey(Zeroizing<Vec<u8>>);
+
+impl SecretsKey {
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        Self(Zeroizing::new(key.into()))
+    }
+
+    /// Read the secrets key from the "`EVENT_DB_SECRETS_KEY`" env var, if set.
+    pub fn from_env() -> Option<Self> {
+        std::env::var(SECRETS_KEY_ENVVAR).ok().map(Self::new)
+    }
+}
+
+impl fmt::Debug for SecretsKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretsKey(<redacted>)")
+    }
+}
+
+/// Random key encrypting the secrets of a single row.
+pub(crate) struct DataKey(Zeroizing<Vec<u8>>);
+
+impl DataKey {
+    pub(crate) fn generate() -> Self {
+        let mut key = Zeroizing::new(vec![0; DATA_KEY_SIZE]);
+        OsRng.fill_bytes(&mut key);
+        Self(key)
+    }
+
+    /// Decrypt a data key stored in the database
+    pub(crate) fn unwrap(secrets_key: &SecretsKey, wrapped: &[u8]) -> Result<Self, Error> {
+        let key = symmetric_cipher::decrypt(&*secrets_key.0, wrapped)
+            .map_err(|_| Error::SecretDecryption)?;
+        Ok(Self(Zeroizing::new(key.into_vec())))
+    }
+
+    /// Encrypt the data key to store it in the database
+    pub(crate) fn wrap(&self, secrets_key: &SecretsKey) -> Result<Vec<u8>, Error> {
+        symmetric_cipher::encrypt(&*secrets_key.0, &*self.0, OsRng)
+            .map(Vec::from)
+            .map_err(|_| Error::SecretEncryption)
+    }
+
+    pub(crate) fn encrypt(&self, secret: &Secret) -> Result<Vec<u8>, Error> {
+        symmetric_cipher::encrypt(&*self.0, &*pad(secret.expose_secret().as_bytes()), OsRng)
+            .map(Vec::from)
+            .map_err(|_| Error::SecretEncryption)
+    }
+
+    pub(crate) fn decrypt(&self, encrypted: &[u8]) -> Result<Secret, Error> {
+        let padded = Zeroizing::new(
+            symmetric_cipher::decrypt(&*self.0, encrypted)
+                .map_err(|_| Error::SecretDecryption)?
+                .into_vec(),
+        );
+        let secret = unpad(&padded).ok_or(Error::SecretDecryption)?;
+        let secret = std::str::from_utf8(secret).map_err(|_| Error::SecretDecryption)?;
+        Ok(Secret::new(secret.to_string()))
+    }
+}
+
+/// Decrypt a secret encrypted by the voting node with the legacy password.
+///
+/// The legacy format is the URL safe base64 encoding of a random salt followed by a Fernet token,
+/// the Fernet key being derived from the password and the salt with PBKDF2-HMAC-SHA256.
+pub(crate) fn decrypt_legacy(encrypted: &[u8], password: &str) -> Result<Secret, Error> {
+    let decoded = URL_SAFE
+        .decode(encrypted)
+        .map_err(|_| Error::LegacySecretDecryption)?;
+    if decoded.len() < LEGACY_SALT_SIZE {
+        return Err(Error::LegacySecretDecryption);
+    }
+    let (salt, token) = decoded.split_at(LEGACY_SALT_SIZE);
+    let token = std::str::from_utf8(token).map_err(|_| Error::LegacySecretDecryption)?;
+
+    let mut key = Zeroizing::new([0; LEGACY_KEY_SIZE]);
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt, LEGACY_ITERATIONS, &mut *key);
+    let fernet =
+        fernet::Fernet::new(&URL_SAFE.encode(*key)).ok_or(Error::LegacySecretDecryption)?;
+
+    let secret = Zeroizing::new(
+        fernet
+            .decrypt(token)
+            .map_err(|_| Error::LegacySecretDecryption)?,
+    );
+    let secret = std::str::from_utf8(&secret).map_err(|_| Error::LegacySecretDecryption)?;
+    Ok(Secret::new(secret.to_string()))
+}
+
+/// Prefix the data with its length and pad it with zeros to a multiple of the block size
+fn pad(data: &[u8]) -> Zeroizing<Vec<u8>> {
+    let len = LENGTH_SIZE + data.len();
+    let mut padded = Zeroizing::new(vec![0; (len + BLOCK_SIZE - 1) / BLOCK_SIZE * BLOCK_SIZE]);
+    padded[..LENGTH_SIZE].copy_from_slice(&(data.len() as u32).to_be_bytes());
+    padded[LENGTH_SIZE..len].copy_from_slice(data);
+    padded
+}
+
+fn unpad(padded: &[u8]) -> Option<&[u8]> {
+    let len = u32::from_be_bytes(padded.get(..LENGTH_SIZE)?.try_into().ok()?) as usize;
+    padded.get(LENGTH_SIZE..LENGTH_SIZE + len)
+}
+
+/// Legacy secrets of a voting node, as stored by the voting node with `SECRET_SECRET` set to the password
+#[cfg(test)]
+pub(crate) mod legacy_test_data {
+    pub(crate) const LEGACY_PASSWORD: &str = "SUPER_SECRET_SECRET_THAT_MUST_BE_CHANGED";
+    pub(crate) const LEGACY_SECKEY: &str = "AAECAwQFBgcICQoLDA0OD2dBQUFBQUJxMUdLR2c3SWZGVHAxVTNWdU9hblY2VmZBYW5BOEhSb1FPU1h3NFZMMEdBTDBOWlQyeVZWbzFnS3dNbDR4dElmeXd3UUFoMGlZNDJsQWVSd3hXSWlHa0x2akRBb1o3Q0pUVkRYaDB5ZG1JdTdSRzRVPQ==";
+    pub(crate) const LEGACY_NETKEY: &str = "AAECAwQFBgcICQoLDA0OD2dBQUFBQUJxMUdLR0ZJcWxHaUV2Uy1JTnlTNGV4bjVlSjlkcW04a3V3dFhGMm5WWk5YUGItVjdWLUFnbWV2U2tFUlotUHg1TEoxZ1JXVVVXMDNpYXB5bmJReGF2UThkNlNDXzlEdW9xSG9NTVFDXzhfNFl0T2xJPQ==";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::legacy_test_data::*;
+    use super::*;
+
+    #[test]
+    fn envelope_roundtrip() {
+        let secrets_key = SecretsKey::new("secrets key");
+        let secret =
+            Secret::new("ed25519_sk1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq".to_string());
+
+        let data_key = DataKey::generate();
+        let wrapped = data_key.wrap(&secrets_key).unwrap();
+        let encrypted = data_key.encrypt(&secret).unwrap();
+        assert!(!encrypted
+            .windows(secret.expose_secret().len())
+            .any(|w| w == secret.expose_secret().as_bytes()));
+
+        let data_key = DataKey::unwrap(&secrets_key, &wrapped).unwrap();
+        assert_eq!(data_key.decrypt(&encrypted).unwrap(), secret);
+
+        assert_eq!(
+            DataKey::unwrap(&SecretsKey::new("another key"), &wrapped).err(),
+            Some(Error::SecretDecryption)
+        );
+    }
+
+    #[test]
+    fn padding() {
+        for len in [0, 1, 59, 60, 61, 200] {
+            let data = vec![7; len];
+            let padded = pad(&data);
+            assert_eq!(padded.len() % BLOCK_SIZE, 0);
+            assert_eq!(unpad(&padded), Some(data.as_slice()));
+        }
+    }
+
+    #[test]
+    fn legacy_secret() {
+        // encrypted by `voting_node.utils.encrypt_secret`
+        let encrypted = LEGACY_SECKEY.as_bytes();
+
+        assert_eq!(
+            decrypt_legacy(encrypted, LEGACY_PASSWORD).unwrap(),
+            Secret::new("ed25519_sk1legacysecretkey".to_string())
+        );
+        assert_eq!(
+            decrypt_legacy(encrypted, "another password").err(),
+            Some(Error::LegacySecretDecryption)
+        );
+        assert_eq!(
+            decrypt_legacy(b"ed25519_sk1legacysecretkey", LEGACY_PASSWORD).err(),
+            Some(Error::LegacySecretDecryption)
+        );
+    }
+
+    #[test]
+    fn secrets_are_not_printed() {
+        let secret = Secret::new("plaintext".to_string());
+        assert!(!format!("{:?}", secret).contains("plaintext"));
+        assert!(!format!("{:?}", SecretsKey::new("plaintext")).contains("plaintext"));
+    }
+}
@@ -0,0 +1,24 @@
+use super::event::EventId;
+use crate::secret::Secret;
+
+/// A node of the voting network of an event, with the keys it bootstraps from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VotingNode {
+    pub hostname: String,
+    pub event: EventId,
+    pub pubkey: String,
+    pub seckey: Secret,
+    pub netkey: Secret,
+}
+
+/// A member of the tally committee of an event, with its keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitteeMember {
+    pub committee: i32,
+    pub member_index: i32,
+    pub threshold: i32,
+    pub comm_pk: String,
+    pub comm_sk: Secret,
+    pub member_pk: String,
+    pub member_sk: Secret,
+}
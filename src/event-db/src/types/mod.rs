@@ -1,3 +1,4 @@
+pub mod automation;
 pub mod ballot;
 pub mod event;
 pub mod objective;
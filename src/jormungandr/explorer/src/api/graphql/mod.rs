@@ -13,7 +13,7 @@ use self::{
     scalars::{
         BlockCount, ChainLength, EpochNumber, ExternalProposalId, IndexCursor, NonZero,
         PayloadType, PoolCount, PoolId, PublicKey, Slot, TransactionCount, Value, VoteOptionRange,
-        VotePlanId, VotePlanStatusCount, Weight,
+        VotePlanId, VotePlanPhase, VotePlanStatusCount, Weight,
     },
 };
 use crate::db::{
@@ -21,7 +21,7 @@ use crate::db::{
         BlockProducer, EpochData, ExplorerAddress, ExplorerBlock, ExplorerTransaction,
         ExplorerVote, ExplorerVotePlan, ExplorerVoteProposal, ExplorerVoteTally, StakePoolData,
     },
-    persistent_sequence::PersistentSequence,
+    stable_storage::StoredSequence,
     tally::compute_public_tally,
     ExplorerDb, Settings as ChainSettings,
 };
@@ -37,7 +37,7 @@ use chain_impl_mockchain::{
     fragment::FragmentId,
     key::BftLeaderId,
     stake::StakeControl,
-    vote::{EncryptedVote, PayloadType as OtherPayloadType, ProofOfCorrectVote},
+    vote::PayloadType as OtherPayloadType,
 };
 use std::{
     convert::{TryFrom, TryInto},
@@ -63,6 +63,14 @@ impl Branch {
     fn from_id_and_state(id: HeaderHash, state: crate::db::Ref) -> Branch {
         Branch { state, id }
     }
+
+    fn vote_plan_data(&self, vote_plan_id: &VotePlanId) -> FieldResult<Arc<ExplorerVotePlan>> {
+        let id = parse_vote_plan_id(vote_plan_id)?;
+        self.state
+            .state()
+            .get_vote_plan(&id)
+            .ok_or_else(|| ApiError::NotFound(format!("vote plan {}", id)).into())
+    }
 }
 
 #[Object]
@@ -72,9 +80,7 @@ impl Branch {
     }
 
     pub async fn block(&self) -> Block {
-        Block::from_contents(Arc::clone(
-            self.state.state().blocks.lookup(&self.id).unwrap(),
-        ))
+        Block::from_contents(self.state.state().get_block(&self.id).unwrap())
     }
 
     pub async fn blocks(
@@ -86,7 +92,7 @@ impl Branch {
     ) -> FieldResult<Connection<IndexCursor, Block, ConnectionFields<BlockCount>, EmptyFields>>
     {
         let block0 = 0u32;
-        let chain_length = self.state.state().blocks.size();
+        let chain_length = self.state.state().block_count();
 
         query(
             after,
@@ -157,13 +163,9 @@ impl Branch {
             .or_else(|_| OldAddress::from_str(&address_bech32).map(ExplorerAddress::Old))
             .map_err(|_| ApiError::InvalidAddress(address_bech32.to_string()))?;
 
-        let transactions = self
-            .state
-            .state()
-            .transactions_by_address(&address)
-            .unwrap_or_else(PersistentSequence::<FragmentId>::new);
+        let transactions = self.state.state().transactions_by_address(&address);
 
-        let len = transactions.len();
+        let len = transactions.as_ref().map_or(0, StoredSequence::len);
 
         query(
             after,
@@ -200,7 +202,12 @@ impl Branch {
                 let edges = match range {
                     PaginationInterval::Empty => vec![],
                     PaginationInterval::Inclusive(range) => (range.lower_bound..=range.upper_bound)
-                        .filter_map(|i| transactions.get(i).map(|h| (HeaderHash::clone(h), i)))
+                        .filter_map(|i| {
+                            transactions
+                                .as_ref()
+                                .and_then(|transactions| transactions.get(i))
+                                .map(|h| (h, i))
+                        })
                         .collect(),
                 };
 
@@ -225,7 +232,7 @@ impl Branch {
     > {
         let mut vote_plans = self.state.state().get_vote_plans();
 
-        vote_plans.sort_unstable_by_key(|(id, _data)| id.clone());
+        vote_plans.sort_unstable();
 
         query(
             after,
@@ -270,12 +277,12 @@ impl Branch {
                         let to = range.upper_bound;
 
                         (from..=to)
-                            .map(|i: u32| {
-                                let (_pool_id, vote_plan_data) =
-                                    &vote_plans[usize::try_from(i).unwrap()];
-                                (
-                                    VotePlanStatus::vote_plan_from_data(Arc::clone(vote_plan_data)),
-                                    i,
+                            .filter_map(|i: u32| {
+                                let vote_plan_id = &vote_plans[usize::try_from(i).unwrap()];
+                                self.state.state().get_vote_plan(vote_plan_id).map(
+                                    |vote_plan_data| {
+                                        (VotePlanStatus::vote_plan_from_data(vote_plan_data), i)
+                                    },
                                 )
                             })
                             .collect::<Vec<(VotePlanStatus, u32)>>()
@@ -294,6 +301,190 @@ impl Branch {
         .await
     }
 
+    /// the votes cast by an account on all the vote plans, sorted by vote plan
+    /// and proposal index
+    pub async fn vote_casts_by_address(
+        &self,
+        address_bech32: String,
+        first: Option<i32>,
+        last: Option<i32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> FieldResult<Connection<IndexCursor, VoteCastStatus, ConnectionFields<u64>, EmptyFields>>
+    {
+        let address = Address::from_bech32(&address_bech32)?.id;
+        let votes = self.state.state().get_votes_by_address(&address);
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let boundaries = if !votes.is_empty() {
+                    PaginationInterval::Inclusive(InclusivePaginationInterval {
+                        lower_bound: 0u32,
+                        upper_bound: votes
+                            .len()
+                            .checked_sub(1)
+                            .unwrap()
+                            .try_into()
+                            .expect("tried to paginate more than 2^32 elements"),
+                    })
+                } else {
+                    PaginationInterval::Empty
+                };
+
+                let pagination_arguments = ValidatedPaginationArguments {
+                    first,
+                    last,
+                    before: before.map(u32::try_from).transpose()?,
+                    after: after.map(u32::try_from).transpose()?,
+                };
+
+                let (range, page_meta) = compute_interval(boundaries, pagination_arguments)?;
+                let mut connection = Connection::with_additional_fields(
+                    page_meta.has_previous_page,
+                    page_meta.has_next_page,
+                    ConnectionFields {
+                        total_count: page_meta.total_count,
+                    },
+                );
+
+                let edges = match range {
+                    PaginationInterval::Empty => vec![],
+                    PaginationInterval::Inclusive(range) => (range.lower_bound..=range.upper_bound)
+                        .map(|i: u32| {
+                            let (vote_plan_id, proposal_index, vote) = &votes[i as usize];
+                            (
+                                VoteCastStatus::new(
+                                    vote_plan_id.clone(),
+                                    *proposal_index,
+                                    &address,
+                                    vote,
+                                ),
+                                i,
+                            )
+                        })
+                        .collect::<Vec<(VoteCastStatus, u32)>>(),
+                };
+
+                connection.edges.extend(
+                    edges
+                        .into_iter()
+                        .map(|(vcs, cursor)| Edge::new(IndexCursor::from(cursor), vcs)),
+                );
+
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// the votes cast on a proposal of a vote plan
+    pub async fn proposal_votes(
+        &self,
+        vote_plan_id: VotePlanId,
+        proposal_index: i32,
+        first: Option<i32>,
+        last: Option<i32>,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> FieldResult<Connection<IndexCursor, VoteStatus, ConnectionFields<u64>, EmptyFields>> {
+        let vote_plan = self.vote_plan_data(&vote_plan_id)?;
+        let proposal = usize::try_from(proposal_index)
+            .ok()
+            .and_then(|index| vote_plan.proposals.get(index))
+            .ok_or_else(|| {
+                ApiError::NotFound(format!(
+                    "proposal {} of vote plan {}",
+                    proposal_index, vote_plan_id.0
+                ))
+            })?;
+
+        // only the votes of the requested page are converted
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let boundaries = if !proposal.votes.is_empty() {
+                    PaginationInterval::Inclusive(InclusivePaginationInterval {
+                        lower_bound: 0u32,
+                        upper_bound: proposal
+                            .votes
+                            .size()
+                            .checked_sub(1)
+                            .unwrap()
+                            .try_into()
+                            .expect("tried to paginate more than 2^32 elements"),
+                    })
+                } else {
+                    PaginationInterval::Empty
+                };
+
+                let pagination_arguments = ValidatedPaginationArguments {
+                    first,
+                    last,
+                    before: before.map(u32::try_from).transpose()?,
+                    after: after.map(u32::try_from).transpose()?,
+                };
+
+                let (range, page_meta) = compute_interval(boundaries, pagination_arguments)?;
+                let mut connection = Connection::with_additional_fields(
+                    page_meta.has_previous_page,
+                    page_meta.has_next_page,
+                    ConnectionFields {
+                        total_count: page_meta.total_count,
+                    },
+                );
+
+                if let PaginationInterval::Inclusive(range) = range {
+                    let from = range.lower_bound;
+                    let to = range.upper_bound;
+
+                    connection.edges.extend(
+                        proposal
+                            .votes
+                            .iter()
+                            .skip(from as usize)
+                            .zip(from..=to)
+                            .map(|((address, vote), i)| {
+                                Edge::new(
+                                    IndexCursor::from(i),
+                                    VoteStatus::from_vote(address, vote),
+                                )
+                            }),
+                    );
+                }
+
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// the phase of a vote plan and the number of votes and the tally of its
+    /// proposals, at the date of this branch
+    pub async fn vote_plan_progress(
+        &self,
+        vote_plan_id: VotePlanId,
+    ) -> FieldResult<VotePlanProgress> {
+        let vote_plan = self.vote_plan_data(&vote_plan_id)?;
+        // the tip of a branch is always indexed, unless the storage cannot be read
+        let date = self
+            .state
+            .state()
+            .get_block(&self.id)
+            .ok_or_else(|| {
+                ApiError::InternalError("Couldn't find block in the explorer".to_owned())
+            })?
+            .date;
+
+        Ok(VotePlanProgress::from_data(&vote_plan, date))
+    }
+
     pub async fn all_stake_pools(
         &self,
         first: Option<i32>,
@@ -407,16 +598,14 @@ impl Branch {
                     let epoch_lower_bound = self
                         .state
                         .state()
-                        .blocks
-                        .lookup(&epoch_data.first_block)
+                        .get_block(&epoch_data.first_block)
                         .map(|block| u32::from(block.chain_length))
                         .expect("Epoch lower bound");
 
                     let epoch_upper_bound = self
                         .state
                         .state()
-                        .blocks
-                        .lookup(&epoch_data.last_block)
+                        .get_block(&epoch_data.last_block)
                         .map(|block| u32::from(block.chain_length))
                         .expect("Epoch upper bound");
 
@@ -1009,7 +1198,7 @@ impl Proposal {
 pub struct Pool {
     id: certificate::PoolId,
     data: Option<Arc<StakePoolData>>,
-    blocks: Option<Arc<PersistentSequence<HeaderHash>>>,
+    blocks: Option<StoredSequence>,
 }
 
 impl Pool {
@@ -1106,7 +1295,7 @@ impl Pool {
                 let edges = match range {
                     PaginationInterval::Empty => vec![],
                     PaginationInterval::Inclusive(range) => (range.lower_bound..=range.upper_bound)
-                        .filter_map(|i| blocks.get(i).map(|h| (*h.as_ref(), i)))
+                        .filter_map(|i| blocks.get(i).map(|h| (h, i)))
                         .collect(),
                 };
 
@@ -1259,8 +1448,8 @@ pub struct VotePayloadPublicStatus {
 
 #[derive(Clone)]
 pub struct VotePayloadPrivateStatus {
-    proof: ProofOfCorrectVote,
-    encrypted_vote: EncryptedVote,
+    proof: Vec<u8>,
+    encrypted_vote: Vec<u8>,
 }
 
 #[Object]
@@ -1273,13 +1462,11 @@ impl VotePayloadPublicStatus {
 #[Object]
 impl VotePayloadPrivateStatus {
     pub async fn proof(&self, _context: &Context<'_>) -> String {
-        let bytes_proof = self.proof.serialize();
-        base64::encode_config(bytes_proof, base64::URL_SAFE)
+        base64::encode_config(&self.proof, base64::URL_SAFE)
     }
 
     pub async fn encrypted_vote(&self, _context: &Context<'_>) -> String {
-        let encrypted_bote_bytes = self.encrypted_vote.serialize();
-        base64::encode_config(encrypted_bote_bytes, base64::URL_SAFE)
+        base64::encode_config(&self.encrypted_vote, base64::URL_SAFE)
     }
 }
 
@@ -1354,55 +1541,25 @@ impl VotePlanStatus {
             payload_type: PayloadType::from(payload_type),
             proposals: proposals
                 .into_iter()
-                .map(|proposal| VoteProposalStatus {
-                    proposal_id: ExternalProposalId::from(proposal.proposal_id.clone()),
-                    options: VoteOptionRange::from(proposal.options.clone()),
-                    tally: proposal.tally.clone().map_or(
-                        Some(generic_tally_status(
-                            ExplorerVoteProposal {
-                                proposal_id: proposal.proposal_id,
-                                options: proposal.options,
-                                tally: proposal.tally,
-                                votes: proposal.votes.clone(),
-                            },
-                            payload_type,
-                        )),
-                        |tally| Some(TallyStatus::from(tally)),
-                    ),
-                    votes: proposal
-                        .votes
-                        .iter()
-                        .map(|(key, vote)| match vote.as_ref() {
-                            ExplorerVote::Public(choice) => VoteStatus {
-                                address: key.into(),
-                                payload: VotePayloadStatus::Public(VotePayloadPublicStatus {
-                                    choice: choice.as_byte().into(),
-                                }),
-                            },
-                            ExplorerVote::Private {
-                                proof,
-                                encrypted_vote,
-                            } => VoteStatus {
-                                address: key.into(),
-                                payload: VotePayloadStatus::Private(VotePayloadPrivateStatus {
-                                    proof: proof.clone(),
-                                    encrypted_vote: encrypted_vote.clone(),
-                                }),
-                            },
-                        })
-                        .collect(),
-                })
+                .map(|proposal| VoteProposalStatus::from_data(proposal, payload_type))
                 .collect(),
         }
     }
 }
 
+fn tally_status(proposal: &ExplorerVoteProposal, payload: OtherPayloadType) -> TallyStatus {
+    match &proposal.tally {
+        Some(tally) => TallyStatus::from(tally.clone()),
+        None => generic_tally_status(proposal.clone(), payload),
+    }
+}
+
 // if the tally is None, convert to generic tally result as per rest api requirements
 pub fn generic_tally_status(p: ExplorerVoteProposal, payload: OtherPayloadType) -> TallyStatus {
     match payload {
         OtherPayloadType::Public => {
             let s = StakeControl::default();
-            match compute_public_tally(&p, &s) {
+            match compute_public_tally(&p, std::iter::empty(), &s) {
                 ExplorerVoteTally::Public { results, options } => {
                     TallyStatus::Public(TallyPublicStatus {
                         results: results.iter().map(Into::into).collect(),
@@ -1417,10 +1574,20 @@ pub fn generic_tally_status(p: ExplorerVoteProposal, payload: OtherPayloadType)
                 }
             }
         }
-        OtherPayloadType::Private => TallyStatus::Private(TallyPrivateStatus {
+        OtherPayloadType::Private | OtherPayloadType::Approval => {
+            TallyStatus::Private(TallyPrivateStatus {
+                results: Some(vec![
+                    Weight("0".to_string());
+                    p.options.choice_range().end as usize
+                ]),
+                options: p.options.into(),
+            })
+        }
+        OtherPayloadType::Ranked => TallyStatus::Private(TallyPrivateStatus {
             results: Some(vec![
                 Weight("0".to_string());
                 p.options.choice_range().end as usize
+                    * p.options.max_selections() as usize
             ]),
             options: p.options.into(),
         }),
@@ -1452,6 +1619,42 @@ pub struct VoteStatus {
     payload: VotePayloadStatus,
 }
 
+impl VoteStatus {
+    fn from_vote(address: &ExplorerAddress, vote: &ExplorerVote) -> Self {
+        let payload = match vote {
+            ExplorerVote::Public(choice) => VotePayloadStatus::Public(VotePayloadPublicStatus {
+                choice: choice.as_byte().into(),
+            }),
+            ExplorerVote::Private {
+                proof,
+                encrypted_vote,
+            } => VotePayloadStatus::Private(VotePayloadPrivateStatus {
+                proof: proof.serialize().into(),
+                encrypted_vote: encrypted_vote.serialize().into(),
+            }),
+            ExplorerVote::Approval {
+                proof,
+                encrypted_vote,
+            } => VotePayloadStatus::Private(VotePayloadPrivateStatus {
+                proof: proof.serialize().into(),
+                encrypted_vote: encrypted_vote.serialize().into(),
+            }),
+            ExplorerVote::Ranked {
+                proof,
+                encrypted_vote,
+            } => VotePayloadStatus::Private(VotePayloadPrivateStatus {
+                proof: proof.serialize().into(),
+                encrypted_vote: encrypted_vote.serialize().into(),
+            }),
+        };
+
+        VoteStatus {
+            address: address.into(),
+            payload,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct VoteProposalStatus {
     proposal_id: ExternalProposalId,
@@ -1460,6 +1663,21 @@ pub struct VoteProposalStatus {
     votes: Vec<VoteStatus>,
 }
 
+impl VoteProposalStatus {
+    fn from_data(proposal: ExplorerVoteProposal, payload_type: OtherPayloadType) -> Self {
+        VoteProposalStatus {
+            proposal_id: ExternalProposalId::from(proposal.proposal_id.clone()),
+            options: VoteOptionRange::from(proposal.options.clone()),
+            tally: Some(tally_status(&proposal, payload_type)),
+            votes: proposal
+                .votes
+                .iter()
+                .map(|(key, vote)| VoteStatus::from_vote(key, vote))
+                .collect(),
+        }
+    }
+}
+
 #[Object]
 impl VoteProposalStatus {
     pub async fn proposal_id(&self) -> &ExternalProposalId {
@@ -1543,6 +1761,100 @@ impl VoteProposalStatus {
     }
 }
 
+/// A vote cast on a proposal of a vote plan
+#[derive(Clone, SimpleObject)]
+pub struct VoteCastStatus {
+    vote_plan: VotePlanId,
+    proposal_index: i32,
+    address: Address,
+    payload: VotePayloadStatus,
+}
+
+impl VoteCastStatus {
+    fn new(
+        vote_plan_id: certificate::VotePlanId,
+        proposal_index: u8,
+        address: &ExplorerAddress,
+        vote: &ExplorerVote,
+    ) -> Self {
+        let VoteStatus { address, payload } = VoteStatus::from_vote(address, vote);
+        VoteCastStatus {
+            vote_plan: vote_plan_id.into(),
+            proposal_index: proposal_index.into(),
+            address,
+            payload,
+        }
+    }
+}
+
+#[derive(Clone, SimpleObject)]
+pub struct ProposalProgress {
+    proposal_id: ExternalProposalId,
+    votes_cast: u64,
+    tally: TallyStatus,
+}
+
+/// The state of the vote of a vote plan at the date of a block
+#[derive(Clone, SimpleObject)]
+pub struct VotePlanProgress {
+    id: VotePlanId,
+    phase: VotePlanPhase,
+    /// the number of votes cast on all the proposals
+    votes_cast: u64,
+    proposals: Vec<ProposalProgress>,
+}
+
+impl VotePlanProgress {
+    fn from_data(vote_plan: &ExplorerVotePlan, date: InternalBlockDate) -> Self {
+        let phase = if date < vote_plan.vote_start {
+            VotePlanPhase::Pending
+        } else if date < vote_plan.vote_end {
+            VotePlanPhase::Voting
+        } else if date < vote_plan.committee_end {
+            VotePlanPhase::Tallying
+        } else {
+            VotePlanPhase::Finished
+        };
+
+        let proposals: Vec<_> = vote_plan
+            .proposals
+            .iter()
+            .map(|proposal| ProposalProgress {
+                proposal_id: ExternalProposalId::from(proposal.proposal_id.clone()),
+                votes_cast: proposal.votes.size() as u64,
+                tally: tally_status(proposal, vote_plan.payload_type),
+            })
+            .collect();
+
+        VotePlanProgress {
+            id: VotePlanId::from(vote_plan.id.clone()),
+            phase,
+            votes_cast: proposals.iter().map(|proposal| proposal.votes_cast).sum(),
+            proposals,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct VoteCastEvent {
+    block: Block,
+    transaction: Transaction,
+    vote_cast: VoteCastStatus,
+}
+
+#[derive(SimpleObject)]
+pub struct VoteTallyEvent {
+    block: Block,
+    transaction: Transaction,
+    vote_plan: VotePlanProgress,
+}
+
+#[derive(SimpleObject)]
+pub struct VotePlanEvent {
+    block: Block,
+    vote_plan: VotePlanProgress,
+}
+
 pub struct Query;
 
 #[Object]
@@ -1639,6 +1951,164 @@ impl Subscription {
                     .map(|(hash, state)| Branch::from_id_and_state(hash, state))
             })
     }
+
+    /// the votes cast as the blocks are indexed, optionally only the ones on
+    /// the given vote plan. The blocks may be part of branches that will never
+    /// be selected by the node.
+    async fn vote_casts(
+        &self,
+        context: &Context<'_>,
+        vote_plan_id: Option<VotePlanId>,
+    ) -> FieldResult<impl futures::Stream<Item = VoteCastEvent>> {
+        use futures::StreamExt;
+        let vote_plan_id = vote_plan_id.as_ref().map(parse_vote_plan_id).transpose()?;
+
+        Ok(
+            certificate_subscription(context).flat_map(move |(block, state, transactions)| {
+                let events: Vec<_> = transactions
+                    .into_iter()
+                    .filter_map(|tx| match &tx.certificate {
+                        Some(certificate::Certificate::VoteCast(vote_cast))
+                            if vote_plan_id
+                                .as_ref()
+                                .map_or(true, |id| id == vote_cast.vote_plan()) =>
+                        {
+                            let voter = &tx.inputs.first()?.address;
+                            let vote = state.state().get_vote(
+                                vote_cast.vote_plan(),
+                                vote_cast.proposal_index(),
+                                voter,
+                            )?;
+
+                            Some(VoteCastEvent {
+                                block: Block::from_contents(Arc::clone(&block)),
+                                vote_cast: VoteCastStatus::new(
+                                    vote_cast.vote_plan().clone(),
+                                    vote_cast.proposal_index(),
+                                    voter,
+                                    &vote,
+                                ),
+                                transaction: Transaction::from_contents(tx),
+                            })
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                futures::stream::iter(events)
+            }),
+        )
+    }
+
+    /// the tally certificates as the blocks are indexed, with the tallied vote
+    /// plan, optionally only the ones of the given vote plan
+    async fn vote_tallies(
+        &self,
+        context: &Context<'_>,
+        vote_plan_id: Option<VotePlanId>,
+    ) -> FieldResult<impl futures::Stream<Item = VoteTallyEvent>> {
+        use futures::StreamExt;
+        let vote_plan_id = vote_plan_id.as_ref().map(parse_vote_plan_id).transpose()?;
+
+        Ok(
+            certificate_subscription(context).flat_map(move |(block, state, transactions)| {
+                let events: Vec<_> = transactions
+                    .into_iter()
+                    .filter_map(|tx| match &tx.certificate {
+                        Some(certificate::Certificate::VoteTally(vote_tally))
+                            if vote_plan_id
+                                .as_ref()
+                                .map_or(true, |id| id == vote_tally.id()) =>
+                        {
+                            let vote_plan = state.state().get_vote_plan(vote_tally.id())?;
+
+                            Some(VoteTallyEvent {
+                                block: Block::from_contents(Arc::clone(&block)),
+                                vote_plan: VotePlanProgress::from_data(&vote_plan, block.date),
+                                transaction: Transaction::from_contents(tx),
+                            })
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                futures::stream::iter(events)
+            }),
+        )
+    }
+
+    /// the vote plans created, voted on or tallied as the blocks are indexed,
+    /// optionally only the given vote plan
+    async fn vote_plan_updates(
+        &self,
+        context: &Context<'_>,
+        vote_plan_id: Option<VotePlanId>,
+    ) -> FieldResult<impl futures::Stream<Item = VotePlanEvent>> {
+        use futures::StreamExt;
+        let vote_plan_id = vote_plan_id.as_ref().map(parse_vote_plan_id).transpose()?;
+
+        Ok(
+            certificate_subscription(context).flat_map(move |(block, state, transactions)| {
+                let mut updated = Vec::new();
+                for tx in transactions {
+                    let id = match &tx.certificate {
+                        Some(certificate::Certificate::VotePlan(vote_plan)) => vote_plan.to_id(),
+                        Some(certificate::Certificate::VoteCast(vote_cast)) => {
+                            vote_cast.vote_plan().clone()
+                        }
+                        Some(certificate::Certificate::VoteTally(vote_tally)) => {
+                            vote_tally.id().clone()
+                        }
+                        _ => continue,
+                    };
+                    if vote_plan_id.as_ref().map_or(true, |filter| *filter == id)
+                        && !updated.contains(&id)
+                    {
+                        updated.push(id);
+                    }
+                }
+
+                let events: Vec<_> = updated
+                    .iter()
+                    .filter_map(|id| state.state().get_vote_plan(id))
+                    .map(|vote_plan| VotePlanEvent {
+                        block: Block::from_contents(Arc::clone(&block)),
+                        vote_plan: VotePlanProgress::from_data(&vote_plan, block.date),
+                    })
+                    .collect();
+                futures::stream::iter(events)
+            }),
+        )
+    }
+}
+
+/// The indexed blocks with the state obtained by applying them, and their
+/// transactions with a certificate, in the order of the block
+fn certificate_subscription(
+    context: &Context<'_>,
+) -> impl futures::Stream<Item = (Arc<ExplorerBlock>, crate::db::Ref, Vec<ExplorerTransaction>)> {
+    use futures::StreamExt;
+    extract_context(context)
+        .db
+        .block_subscription()
+        // like for the tips, a lagging subscriber skips the blocks it missed
+        .filter_map(|block| async move {
+            let (hash, state) = block.ok()?;
+            let block = state.state().get_block(&hash)?;
+
+            let mut transactions: Vec<_> = block
+                .transactions
+                .values()
+                .filter(|tx| tx.certificate.is_some())
+                .cloned()
+                .collect();
+            transactions.sort_by_key(|tx| tx.offset_in_block);
+
+            Some((block, state, transactions))
+        })
+}
+
+fn parse_vote_plan_id(vote_plan_id: &VotePlanId) -> FieldResult<certificate::VotePlanId> {
+    certificate::VotePlanId::from_str(&vote_plan_id.0)
+        .map_err(|err| ApiError::ArgumentError(err.to_string()).into())
 }
 
 pub type Schema = async_graphql::Schema<Query, EmptyMutation, Subscription>;
@@ -203,6 +203,21 @@ impl ScalarType for ExternalProposalId {
 pub enum PayloadType {
     Public,
     Private,
+    Approval,
+    Ranked,
+}
+
+/// The phase of a vote plan at a given block date
+#[derive(Clone, Copy, PartialEq, Eq, Enum)]
+pub enum VotePlanPhase {
+    /// before the start of the vote
+    Pending,
+    /// votes can be cast
+    Voting,
+    /// the committee can tally the votes
+    Tallying,
+    /// the committee period is over
+    Finished,
 }
 
 #[derive(Clone)]
@@ -336,6 +351,8 @@ impl From<vote::PayloadType> for PayloadType {
         match payload_type {
             vote::PayloadType::Public => Self::Public,
             vote::PayloadType::Private => Self::Private,
+            vote::PayloadType::Approval => Self::Approval,
+            vote::PayloadType::Ranked => Self::Ranked,
         }
     }
 }
@@ -26,6 +26,10 @@ pub enum ExplorerError {
     TxCalculationFailure,
     #[error("unable to transmute to explorer API representation")]
     ExplorerTransmuteFail,
+    #[error(transparent)]
+    StorageError(#[from] sled::Error),
+    #[error("the explorer storage is corrupted: {0}")]
+    CorruptedStorage(String),
 }
 
 #[derive(Debug, Error, Clone)]
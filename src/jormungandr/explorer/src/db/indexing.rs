@@ -1,4 +1,4 @@
-use super::{error, persistent_sequence::PersistentSequence};
+use super::{error, persistent_sequence::PersistentSequence, stable_storage::StableStorage};
 use cardano_legacy_address::Addr as OldAddress;
 use chain_addr::{Address, Discrimination};
 use chain_core::property::{Block as _, Fragment as _};
@@ -13,7 +13,10 @@ use chain_impl_mockchain::{
     key::{BftLeaderId, Hash},
     transaction::{InputEnum, TransactionSlice, Witness},
     value::Value,
-    vote::{Choice, EncryptedVote, Options, PayloadType, ProofOfCorrectVote, Weight},
+    vote::{
+        Choice, EncryptedVote, Options, PayloadType, ProofOfCorrectApprovalVote,
+        ProofOfCorrectRankedVote, ProofOfCorrectVote, Weight,
+    },
 };
 use error::ExplorerError as Error;
 use std::{
@@ -130,6 +133,14 @@ pub enum ExplorerVote {
         proof: ProofOfCorrectVote,
         encrypted_vote: EncryptedVote,
     },
+    Approval {
+        proof: ProofOfCorrectApprovalVote,
+        encrypted_vote: EncryptedVote,
+    },
+    Ranked {
+        proof: ProofOfCorrectRankedVote,
+        encrypted_vote: EncryptedVote,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -158,6 +169,8 @@ pub struct ExplorerBlockBuildingContext<'a> {
     pub discrimination: Discrimination,
     pub prev_transactions: &'a Transactions,
     pub prev_blocks: &'a Blocks,
+    /// Used to resolve the utxo inputs spending outputs of stable blocks
+    pub stable_storage: &'a StableStorage,
 }
 
 impl ExplorerBlock {
@@ -533,31 +546,31 @@ impl ExplorerTransaction {
                     let tx = utxo_pointer.transaction_id;
                     let index = utxo_pointer.output_index;
 
-                    let output = context
-                        .prev_transactions
-                        .lookup(&tx)
-                        .and_then(|block_id| {
-                            context
-                                .prev_blocks
-                                .lookup(block_id)
-                                .map(|block| &block.transactions[&tx].outputs[index as usize])
-                        })
-                        .or_else(|| {
-                            match transactions_in_current_block
-                                .get(&tx)
-                                .map(|fragment| &fragment.outputs[index as usize])
-                            {
-                                Some(tx) => Some(tx),
-                                None => {
-                                    error!(error = %tx,"transaction not found for utxo input");
-                                    None
-                                }
+                    let output = match context.prev_transactions.lookup(&tx).and_then(|block_id| {
+                        context
+                            .prev_blocks
+                            .lookup(block_id)
+                            .map(|block| block.transactions[&tx].outputs[index as usize].clone())
+                    }) {
+                        Some(output) => Some(output),
+                        None => context.stable_storage.output(&tx, index)?,
+                    }
+                    .or_else(|| {
+                        match transactions_in_current_block
+                            .get(&tx)
+                            .map(|fragment| fragment.outputs[index as usize].clone())
+                        {
+                            Some(tx) => Some(tx),
+                            None => {
+                                error!(error = %tx,"transaction not found for utxo input");
+                                None
                             }
-                        });
+                        }
+                    });
 
                     match output {
                         Some(output) => new_inputs.push(ExplorerInput {
-                            address: output.address.clone(),
+                            address: output.address,
                             value: output.value,
                         }),
                         None => return Err(Error::ExplorerTransmuteFail),
@@ -2,16 +2,18 @@ pub mod error;
 pub mod indexing;
 pub mod multiverse;
 pub mod persistent_sequence;
+pub mod stable_storage;
 pub(crate) mod tally;
 
 use self::{
     error::{BlockNotFound, ExplorerError as Error},
     indexing::{
         Addresses, Blocks, ChainLengths, EpochData, Epochs, ExplorerAddress, ExplorerBlock,
-        ExplorerVote, ExplorerVotePlan, ExplorerVoteProposal, StakePool, StakePoolBlocks,
-        StakePoolData, Transactions, VotePlans,
+        ExplorerBlockBuildingContext, ExplorerVote, ExplorerVotePlan, ExplorerVoteProposal,
+        ExplorerVoteTally, StakePool, StakePoolBlocks, StakePoolData, Transactions, VotePlans,
     },
     persistent_sequence::PersistentSequence,
+    stable_storage::{StableStorage, StoredSequence},
 };
 use crate::db::tally::{compute_private_tally, compute_public_tally};
 use chain_addr::Discrimination;
@@ -27,18 +29,25 @@ use chain_impl_mockchain::{
     vote::PayloadType,
 };
 use futures::prelude::*;
+use imhamt::UpdateError;
 use multiverse::Multiverse;
 pub use multiverse::Ref;
 use std::{
+    collections::{BTreeMap, HashMap},
     convert::Infallible,
     sync::{
         atomic::{AtomicU32, Ordering},
         Arc,
     },
 };
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, Mutex, RwLock};
 use tracing::error;
 
+/// Minimum number of stable blocks kept in memory before they are moved to the
+/// stable storage. Moving them requires to apply again the unstable blocks, so
+/// it is never done more often than every `epoch_stability_depth` blocks.
+const FLUSH_INTERVAL: u32 = 1000;
+
 #[derive(Clone)]
 pub struct Explorer {
     pub db: ExplorerDb,
@@ -60,6 +69,13 @@ pub struct ExplorerDb {
     pub blockchain_config: BlockchainConfig,
     stable_store: StableIndex,
     tip_broadcast: tokio::sync::broadcast::Sender<(HeaderHash, multiverse::Ref)>,
+    block_broadcast: tokio::sync::broadcast::Sender<(HeaderHash, multiverse::Ref)>,
+    /// Indexes of the blocks that can't be rolled back anymore, all the states of
+    /// the multiverse only keep the indexes of the blocks applied after them.
+    stable_storage: StableStorage,
+    /// Locked while a block is applied or while the stable blocks are moved to
+    /// the stable storage
+    unstable: Arc<Mutex<Unstable>>,
 }
 
 #[derive(Clone)]
@@ -67,6 +83,16 @@ pub struct StableIndex {
     confirmed_block_chain_length: Arc<AtomicU32>,
 }
 
+struct Unstable {
+    /// State of the last block moved to the stable storage, which is the root of
+    /// the multiverse
+    root: multiverse::Ref,
+    root_chain_length: ChainLength,
+    /// Blocks applied since, they are kept in the stable storage until they
+    /// are either stable or abandoned
+    blocks: HashMap<HeaderHash, ChainLength>,
+}
+
 #[derive(Clone)]
 pub struct BlockchainConfig {
     /// Used to construct `Address` from `AccountIndentifier` when processing transaction
@@ -80,10 +106,13 @@ pub struct BlockchainConfig {
 /// Inmutable data structure used to represent the explorer's state at a given Block
 /// A new state can be obtained to from a Block and it's previous state, getting two
 /// independent states but with memory sharing to minimize resource utilization
+///
+/// The indexes only contain the blocks applied after the root of the multiverse,
+/// the queries fall back to the stable storage for the older blocks.
 #[derive(Clone)]
 pub struct State {
-    pub transactions: Transactions,
-    pub blocks: Blocks,
+    transactions: Transactions,
+    blocks: Blocks,
     addresses: Addresses,
     epochs: Epochs,
     chain_lengths: ChainLengths,
@@ -91,6 +120,7 @@ pub struct State {
     stake_pool_blocks: StakePoolBlocks,
     vote_plans: VotePlans,
     stake_control: StakeControl,
+    stable_storage: StableStorage,
 }
 
 #[derive(Clone)]
@@ -106,67 +136,93 @@ pub struct Settings {
 }
 
 impl ExplorerDb {
-    pub fn bootstrap(block0: Block) -> Result<Self, Error> {
-        let blockchain_config = BlockchainConfig::from_config_params(
-            block0
-                .contents()
-                .iter()
-                .find_map(|fragment| match fragment {
-                    Fragment::Initial(config_params) => Some(config_params),
-                    _ => None,
-                })
-                .expect("the Initial fragment to be present in the genesis block"),
-        );
+    /// Index the genesis block in an empty stable storage
+    pub fn bootstrap(block0: Block, stable_storage: StableStorage) -> Result<Self, Error> {
+        let blockchain_config = BlockchainConfig::from_block0(&block0);
+        let stable_storage = stable_storage.with_discrimination(blockchain_config.discrimination);
 
+        let empty_state = State::new(StakeControl::new(), stable_storage.clone());
         let block = ExplorerBlock::resolve_from(
             &block0,
-            indexing::ExplorerBlockBuildingContext {
-                discrimination: blockchain_config.discrimination,
-                prev_transactions: &Transactions::new(),
-                prev_blocks: &Blocks::new(),
-            },
+            empty_state.building_context(blockchain_config.discrimination),
+        )?;
+        let block0_state = empty_state.apply_block(&block)?;
+
+        stable_storage.put_block(&block0)?;
+        stable_storage.flush(
+            block0.id(),
+            block0.chain_length(),
+            &block0_state,
+            std::iter::empty(),
         )?;
 
-        let blocks = apply_block_to_blocks(Blocks::new(), &block)?;
-        let epochs = apply_block_to_epochs(Epochs::new(), &block);
-        let chain_lengths = apply_block_to_chain_lengths(ChainLengths::new(), &block)?;
-        let transactions = apply_block_to_transactions(Transactions::new(), &block)?;
-        let addresses = apply_block_to_addresses(Addresses::new(), &block);
-        let (stake_pool_data, stake_pool_blocks) =
-            apply_block_to_stake_pools(StakePool::new(), StakePoolBlocks::new(), &block)?;
-        let stake_control = apply_block_to_stake_control(StakeControl::new(), &block);
-        let vote_plans = apply_block_to_vote_plans(VotePlans::new(), &block, &stake_control)?;
-
-        let initial_state = State {
-            transactions,
-            blocks,
-            addresses,
-            epochs,
-            chain_lengths,
-            stake_pool_data,
-            stake_pool_blocks,
-            vote_plans,
-            stake_control,
-        };
+        Ok(Self::new(
+            block0.id(),
+            block0.chain_length(),
+            blockchain_config,
+            stable_storage,
+            block0_state.stake_control,
+        ))
+    }
 
-        let block0_id = block0.id();
-        let (_, multiverse) = Multiverse::new(block0.chain_length(), block0_id, initial_state);
+    /// Resume from the last stable block of a storage filled by a previous run
+    pub fn restore(stable_storage: StableStorage) -> Result<Self, Error> {
+        let (root, root_chain_length) = stable_storage
+            .tip()?
+            .ok_or_else(|| Error::BootstrapError("the storage is empty".to_owned()))?;
+
+        let block0 = stable_storage
+            .block_id(ChainLength::from(0))?
+            .map(|block0_id| stable_storage.raw_block(&block0_id))
+            .transpose()?
+            .flatten()
+            .ok_or_else(|| Error::CorruptedStorage("genesis block not found".to_owned()))?;
+
+        let blockchain_config = BlockchainConfig::from_block0(&block0);
+        let stable_storage = stable_storage.with_discrimination(blockchain_config.discrimination);
+
+        // the node will send them again
+        stable_storage.remove_unstable_blocks()?;
+        let stake_control = stable_storage.stake_control()?;
+
+        Ok(Self::new(
+            root,
+            root_chain_length,
+            blockchain_config,
+            stable_storage,
+            stake_control,
+        ))
+    }
 
-        let block0_id = block0.id();
+    fn new(
+        root: HeaderHash,
+        root_chain_length: ChainLength,
+        blockchain_config: BlockchainConfig,
+        stable_storage: StableStorage,
+        stake_control: StakeControl,
+    ) -> Self {
+        let root_state = State::new(stake_control, stable_storage.clone());
+        let (root_ref, multiverse) = Multiverse::new(root_chain_length, root, root_state);
 
         let (tx, _) = broadcast::channel(10);
+        let (block_tx, _) = broadcast::channel(10);
 
-        let bootstraped_db = ExplorerDb {
+        ExplorerDb {
             multiverse,
-            longest_chain_tip: Tip::new(block0_id),
+            longest_chain_tip: Tip::new(root),
             blockchain_config,
             stable_store: StableIndex {
-                confirmed_block_chain_length: Arc::new(AtomicU32::default()),
+                confirmed_block_chain_length: Arc::new(AtomicU32::new(root_chain_length.into())),
             },
             tip_broadcast: tx,
-        };
-
-        Ok(bootstraped_db)
+            block_broadcast: block_tx,
+            stable_storage,
+            unstable: Arc::new(Mutex::new(Unstable {
+                root: root_ref,
+                root_chain_length,
+                blocks: HashMap::new(),
+            })),
+        }
     }
 
     /// Try to add a new block to the indexes, this can fail if the parent of the block is
@@ -180,63 +236,35 @@ impl ExplorerDb {
         let multiverse = self.multiverse.clone();
         let discrimination = self.blockchain_config.discrimination;
 
+        let mut unstable = self.unstable.lock().await;
+
         let previous_state = multiverse
             .get_ref(&previous_block)
             .await
             .ok_or_else(|| Error::AncestorNotFound(block.id()))?;
-        let State {
-            transactions,
-            blocks,
-            addresses,
-            epochs,
-            chain_lengths,
-            stake_pool_data,
-            stake_pool_blocks,
-            vote_plans,
-            stake_control,
-        } = previous_state.state().clone();
 
         let explorer_block = ExplorerBlock::resolve_from(
             &block,
-            indexing::ExplorerBlockBuildingContext {
-                discrimination,
-                prev_transactions: &transactions,
-                prev_blocks: &blocks,
-            },
+            previous_state.state().building_context(discrimination),
         )?;
-        let (stake_pool_data, stake_pool_blocks) =
-            apply_block_to_stake_pools(stake_pool_data, stake_pool_blocks, &explorer_block)?;
-
-        let stake_control = apply_block_to_stake_control(stake_control, &explorer_block);
+        let state = previous_state.state().apply_block(&explorer_block)?;
 
-        let vote_plans = apply_block_to_vote_plans(vote_plans, &explorer_block, &stake_control)?;
+        self.stable_storage.put_block(&block)?;
+        unstable.blocks.insert(block_id, chain_length);
 
         let state_ref = multiverse
-            .insert(
-                chain_length,
-                block.parent_id(),
-                block_id,
-                State {
-                    transactions: apply_block_to_transactions(transactions, &explorer_block)?,
-                    blocks: apply_block_to_blocks(blocks, &explorer_block)?,
-                    addresses: apply_block_to_addresses(addresses, &explorer_block),
-                    epochs: apply_block_to_epochs(epochs, &explorer_block),
-                    chain_lengths: apply_block_to_chain_lengths(chain_lengths, &explorer_block)?,
-                    stake_pool_data,
-                    stake_pool_blocks,
-                    vote_plans,
-                    stake_control,
-                },
-            )
+            .insert(chain_length, block.parent_id(), block_id, state)
             .await;
 
+        let _ = self.block_broadcast.send((block_id, state_ref.clone()));
+
         Ok(state_ref)
     }
 
     pub async fn get_block(&self, block_id: &HeaderHash) -> Option<Arc<ExplorerBlock>> {
         for (_hash, state_ref) in self.multiverse.tips().await.iter() {
-            if let Some(b) = state_ref.state().blocks.lookup(block_id) {
-                return Some(Arc::clone(b));
+            if let Some(b) = state_ref.state().get_block(block_id) {
+                return Some(b);
             }
         }
 
@@ -247,15 +275,11 @@ impl ExplorerDb {
         // the tip changes which means now a block is confirmed (at least after
         // the initial epoch_stability_depth blocks).
 
-        let state_ref = if let Some(state_ref) = self.multiverse.get_ref(&hash).await {
-            state_ref
-        } else {
-            return Err(BlockNotFound { hash });
+        let block = match self.multiverse.get_ref(&hash).await {
+            Some(state_ref) => state_ref.state().get_block(&hash).unwrap(),
+            None => return Err(BlockNotFound { hash }),
         };
 
-        let state = state_ref.state();
-        let block = Arc::clone(state.blocks.lookup(&hash).unwrap());
-
         if let Some(confirmed_block_chain_length) = block
             .chain_length()
             .nth_ancestor(self.blockchain_config.epoch_stability_depth)
@@ -275,8 +299,19 @@ impl ExplorerDb {
             self.multiverse
                 .gc(self.blockchain_config.epoch_stability_depth)
                 .await;
+
+            if let Err(error) = self.flush(hash, confirmed_block_chain_length).await {
+                error!(%error, "cannot move the stable blocks to the storage");
+            }
         }
 
+        // the state may have been rebuilt by the flush
+        let state_ref = self
+            .multiverse
+            .get_ref(&hash)
+            .await
+            .ok_or(BlockNotFound { hash })?;
+
         let mut guard = self.longest_chain_tip.0.write().await;
 
         *guard = hash;
@@ -286,6 +321,111 @@ impl ExplorerDb {
         Ok(())
     }
 
+    /// Move the indexes of the confirmed blocks of the branch of `tip` to the
+    /// stable storage, once there are enough of them.
+    ///
+    /// The multiverse is rebuilt on top of the new stable block, applying again
+    /// the blocks of the branches descending from it, the other branches are
+    /// abandoned. The states obtained before may see some blocks both in the
+    /// storage and in their indexes, which only matters for the sequences
+    /// (transactions of an address and blocks of a pool) until they are dropped.
+    async fn flush(&self, tip: HeaderHash, confirmed: ChainLength) -> Result<(), Error> {
+        let mut unstable = self.unstable.lock().await;
+
+        let interval = FLUSH_INTERVAL.max(self.blockchain_config.epoch_stability_depth);
+        if u32::from(confirmed) < u32::from(unstable.root_chain_length).saturating_add(interval) {
+            return Ok(());
+        }
+
+        let tip_state = match self.multiverse.get_ref(&tip).await {
+            Some(state_ref) => state_ref,
+            None => return Ok(()),
+        };
+
+        // the root of the multiverse is not in the indexes of the states
+        let mut stable_blocks = Vec::new();
+        let mut current = tip_state.state().blocks.lookup(&tip).cloned();
+        while let Some(block) = current {
+            if block.chain_length <= confirmed {
+                stable_blocks.push(Arc::clone(&block));
+            }
+            current = tip_state.state().blocks.lookup(&block.parent_hash).cloned();
+        }
+        stable_blocks.reverse();
+
+        let new_root = match stable_blocks.last() {
+            Some(block) => block.id(),
+            None => return Ok(()),
+        };
+
+        let mut root_state = unstable.root.state().clone();
+        for block in stable_blocks.iter() {
+            root_state = root_state.apply_block(block)?;
+        }
+
+        let mut unstable_blocks = BTreeMap::new();
+        for (tip, state_ref) in self.multiverse.tips().await {
+            let mut branch = Vec::new();
+            let mut current = state_ref.state().blocks.lookup(&tip).cloned();
+            while let Some(block) = current {
+                if block.chain_length <= confirmed {
+                    if block.id() == new_root {
+                        unstable_blocks.extend(branch);
+                    }
+                    break;
+                }
+                if unstable_blocks.contains_key(&(block.chain_length, block.id())) {
+                    unstable_blocks.extend(branch);
+                    break;
+                }
+                current = state_ref.state().blocks.lookup(&block.parent_hash).cloned();
+                branch.push(((block.chain_length, block.id()), block));
+            }
+        }
+
+        let abandoned: Vec<_> = unstable
+            .blocks
+            .iter()
+            .filter(|(id, chain_length)| {
+                !root_state.blocks.contains_key(*id)
+                    && !unstable_blocks.contains_key(&(**chain_length, **id))
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        self.stable_storage
+            .flush(new_root, confirmed, &root_state, abandoned)?;
+
+        let (root, multiverse) = Multiverse::new(
+            confirmed,
+            new_root,
+            State::new(root_state.stake_control, self.stable_storage.clone()),
+        );
+        for ((chain_length, id), block) in unstable_blocks.iter() {
+            let parent = multiverse
+                .get_ref(&block.parent_hash)
+                .await
+                .ok_or(Error::AncestorNotFound(*id))?;
+            let state = parent.state().apply_block(block)?;
+            multiverse
+                .insert(*chain_length, block.parent_hash, *id, state)
+                .await;
+        }
+
+        self.multiverse.replace(multiverse).await;
+        self.multiverse
+            .gc(self.blockchain_config.epoch_stability_depth)
+            .await;
+
+        unstable.root = root;
+        unstable.root_chain_length = confirmed;
+        unstable
+            .blocks
+            .retain(|id, chain_length| unstable_blocks.contains_key(&(*chain_length, *id)));
+
+        Ok(())
+    }
+
     pub async fn get_block_with_branches(
         &self,
         block_id: &HeaderHash,
@@ -294,8 +434,8 @@ impl ExplorerDb {
         let mut tips = Vec::new();
 
         for (hash, state_ref) in self.multiverse.tips().await.drain(..) {
-            if let Some(b) = state_ref.state().blocks.lookup(block_id) {
-                block = block.or_else(|| Some(Arc::clone(b)));
+            if let Some(b) = state_ref.state().get_block(block_id) {
+                block = block.or(Some(b));
                 tips.push((hash, state_ref));
             }
         }
@@ -307,11 +447,7 @@ impl ExplorerDb {
         let tips = self.multiverse.tips().await;
         let (_, state_ref) = &tips[0];
 
-        state_ref
-            .state()
-            .epochs
-            .lookup(&epoch)
-            .map(|e| e.as_ref().clone())
+        state_ref.state().get_epoch(epoch)
     }
 
     pub async fn is_block_confirmed(&self, block_id: &HeaderHash) -> bool {
@@ -321,7 +457,7 @@ impl ExplorerDb {
             .await
             .unwrap();
 
-        if let Some(block) = current_branch.state().blocks.lookup(block_id) {
+        if let Some(block) = current_branch.state().get_block(block_id) {
             let confirmed_block_chain_length: ChainLength = self
                 .stable_store
                 .confirmed_block_chain_length
@@ -337,8 +473,8 @@ impl ExplorerDb {
         let mut hashes = Vec::new();
 
         for (_hash, state_ref) in self.multiverse.tips().await.iter() {
-            if let Some(hash) = state_ref.state().chain_lengths.lookup(&chain_length) {
-                hashes.push(**hash);
+            if let Some(hash) = state_ref.state().get_block_hash(chain_length) {
+                hashes.push(hash);
             }
         }
 
@@ -355,11 +491,7 @@ impl ExplorerDb {
             .await
             .iter()
             .filter_map(|(_tip_hash, state_ref)| {
-                state_ref
-                    .state()
-                    .transactions
-                    .lookup(transaction_id)
-                    .map(|arc| *arc.clone())
+                state_ref.state().get_transaction_block(transaction_id)
             })
             .collect();
 
@@ -369,12 +501,7 @@ impl ExplorerDb {
         txs
     }
 
-    pub async fn get_stake_pool_blocks(
-        &self,
-        pool: &PoolId,
-    ) -> Option<Arc<PersistentSequence<HeaderHash>>> {
-        let pool = pool.clone();
-
+    pub async fn get_stake_pool_blocks(&self, pool: &PoolId) -> Option<StoredSequence> {
         // this is a tricky query, one option would be to take a hash and return
         // only the blocks from a particular branch, but it's not like a stake
         // pool would produce inconsistent branches itself, although there may
@@ -387,17 +514,14 @@ impl ExplorerDb {
             .tips()
             .await
             .iter()
-            .filter_map(|(_hash, state_ref)| state_ref.state().stake_pool_blocks.lookup(&pool))
+            .filter_map(|(_hash, state_ref)| state_ref.state().get_stake_pool_blocks(pool))
             .max_by_key(|seq| seq.len())
-            .map(Arc::clone)
     }
 
     pub async fn get_stake_pool_data(&self, pool: &PoolId) -> Option<Arc<StakePoolData>> {
-        let pool = pool.clone();
-
         for (_hash, state_ref) in self.multiverse.tips().await.iter() {
-            if let Some(b) = state_ref.state().stake_pool_data.lookup(&pool) {
-                return Some(Arc::clone(b));
+            if let Some(b) = state_ref.state().get_stake_pool_data(pool) {
+                return Some(b);
             }
         }
 
@@ -409,8 +533,8 @@ impl ExplorerDb {
         vote_plan_id: &VotePlanId,
     ) -> Option<Arc<ExplorerVotePlan>> {
         for (_hash, state_ref) in self.multiverse.tips().await.iter() {
-            if let Some(b) = state_ref.state().vote_plans.lookup(vote_plan_id) {
-                return Some(Arc::clone(b));
+            if let Some(b) = state_ref.state().get_vote_plan(vote_plan_id) {
+                return Some(b);
             }
         }
 
@@ -440,6 +564,20 @@ impl ExplorerDb {
     > {
         tokio_stream::wrappers::BroadcastStream::new(self.tip_broadcast.subscribe())
     }
+
+    /// Blocks with the state obtained by applying them, as they are indexed.
+    /// This includes the blocks of the branches that may never be part of the
+    /// main chain.
+    pub fn block_subscription(
+        &self,
+    ) -> impl Stream<
+        Item = Result<
+            (HeaderHash, multiverse::Ref),
+            tokio_stream::wrappers::errors::BroadcastStreamRecvError,
+        >,
+    > {
+        tokio_stream::wrappers::BroadcastStream::new(self.block_broadcast.subscribe())
+    }
 }
 
 fn apply_block_to_transactions(
@@ -495,11 +633,24 @@ fn apply_block_to_addresses(mut addresses: Addresses, block: &ExplorerBlock) ->
     addresses
 }
 
-fn apply_block_to_epochs(epochs: Epochs, block: &ExplorerBlock) -> Epochs {
+fn apply_block_to_epochs(
+    epochs: Epochs,
+    block: &ExplorerBlock,
+    stable_storage: &StableStorage,
+) -> Result<Epochs, Error> {
     let epoch_id = block.date().epoch;
     let block_id = block.id();
 
-    epochs.insert_or_update_simple(
+    // the epoch may have started in a stable block
+    let epochs = match epochs.contains_key(&epoch_id) {
+        true => epochs,
+        false => match stable_storage.epoch(epoch_id)? {
+            Some(data) => epochs.insert(epoch_id, Arc::new(data)).unwrap(),
+            None => epochs,
+        },
+    };
+
+    Ok(epochs.insert_or_update_simple(
         epoch_id,
         Arc::new(EpochData {
             first_block: block_id,
@@ -513,7 +664,7 @@ fn apply_block_to_epochs(epochs: Epochs, block: &ExplorerBlock) -> Epochs {
                 total_blocks: data.total_blocks + 1,
             }))
         },
-    )
+    ))
 }
 
 fn apply_block_to_chain_lengths(
@@ -534,16 +685,28 @@ fn apply_block_to_stake_pools(
     data: StakePool,
     blocks: StakePoolBlocks,
     block: &ExplorerBlock,
+    stable_storage: &StableStorage,
 ) -> Result<(StakePool, StakePoolBlocks), Error> {
     let mut blocks = match &block.producer() {
-        indexing::BlockProducer::StakePool(id) => blocks
-            .update(
-                id,
-                |array: &Arc<PersistentSequence<HeaderHash>>| -> Result<_, Infallible> {
-                    Ok(Some(Arc::new(array.append(block.id()))))
-                },
-            )
-            .expect("block to be created by registered stake pool"),
+        indexing::BlockProducer::StakePool(id) => {
+            // the pool may have been registered in a stable block
+            let blocks = if !blocks.contains_key(id) && stable_storage.stake_pool(id)?.is_some() {
+                blocks
+                    .insert(id.clone(), Arc::new(PersistentSequence::new()))
+                    .unwrap()
+            } else {
+                blocks
+            };
+
+            blocks
+                .update(
+                    id,
+                    |array: &Arc<PersistentSequence<HeaderHash>>| -> Result<_, Infallible> {
+                        Ok(Some(Arc::new(array.append(block.id()))))
+                    },
+                )
+                .expect("block to be created by registered stake pool")
+        }
         indexing::BlockProducer::BftLeader(_) => blocks,
         indexing::BlockProducer::None => blocks,
     };
@@ -565,6 +728,9 @@ fn apply_block_to_stake_pools(
 
                 _ => blocks,
             };
+            if let Certificate::PoolRetirement(retirement) = cert {
+                data = load_stake_pool(data, &retirement.pool_id, stable_storage)?;
+            }
             data = match cert {
                 Certificate::PoolRegistration(registration) => match data.insert(
                     registration.to_id(),
@@ -607,9 +773,19 @@ fn apply_block_to_vote_plans(
     mut vote_plans: VotePlans,
     block: &ExplorerBlock,
     stake: &StakeControl,
+    stable_storage: &StableStorage,
 ) -> Result<VotePlans, Error> {
     for tx in block.transactions.values() {
         if let Some(cert) = &tx.certificate {
+            match cert {
+                Certificate::VoteCast(vote_cast) => {
+                    vote_plans = load_vote_plan(vote_plans, vote_cast.vote_plan(), stable_storage)?;
+                }
+                Certificate::VoteTally(vote_tally) => {
+                    vote_plans = load_vote_plan(vote_plans, vote_tally.id(), stable_storage)?;
+                }
+                _ => (),
+            }
             vote_plans = match cert {
                 Certificate::VotePlan(vote_plan) => vote_plans
                     .insert(
@@ -656,39 +832,54 @@ fn apply_block_to_vote_plans(
 
                                 // update the tally every time a vote is cast
                                 let p = &mut proposals[vote_cast.proposal_index() as usize];
-                                p.tally = Some(compute_public_tally(p, stake));
+                                p.tally = Some(public_tally(
+                                    vote_cast.vote_plan(),
+                                    vote_cast.proposal_index(),
+                                    p,
+                                    stake,
+                                    stable_storage,
+                                )?);
 
                                 let vote_plan = ExplorerVotePlan {
                                     proposals,
                                     ..(**vote_plan).clone()
                                 };
-                                Ok::<_, std::convert::Infallible>(Some(Arc::new(vote_plan)))
+                                Ok::<_, Error>(Some(Arc::new(vote_plan)))
                             })
-                            .unwrap(),
-                        Payload::Private {
-                            proof,
-                            encrypted_vote,
-                        } => vote_plans
+                            .map_err(vote_plan_update_error)?,
+                        payload => vote_plans
                             .update(vote_cast.vote_plan(), |vote_plan| {
+                                let vote = Arc::new(match payload {
+                                    Payload::Private {
+                                        proof,
+                                        encrypted_vote,
+                                    } => ExplorerVote::Private {
+                                        proof: proof.clone(),
+                                        encrypted_vote: encrypted_vote.clone(),
+                                    },
+                                    Payload::Approval {
+                                        proof,
+                                        encrypted_vote,
+                                    } => ExplorerVote::Approval {
+                                        proof: proof.clone(),
+                                        encrypted_vote: encrypted_vote.clone(),
+                                    },
+                                    Payload::Ranked {
+                                        proof,
+                                        encrypted_vote,
+                                    } => ExplorerVote::Ranked {
+                                        proof: proof.clone(),
+                                        encrypted_vote: encrypted_vote.clone(),
+                                    },
+                                    Payload::Public { .. } => unreachable!(),
+                                });
                                 let mut proposals = vote_plan.proposals.clone();
                                 proposals[vote_cast.proposal_index() as usize].votes = proposals
                                     [vote_cast.proposal_index() as usize]
                                     .votes
-                                    .insert_or_update(
-                                        voter,
-                                        Arc::new(ExplorerVote::Private {
-                                            proof: proof.clone(),
-                                            encrypted_vote: encrypted_vote.clone(),
-                                        }),
-                                        |_| {
-                                            Ok::<_, std::convert::Infallible>(Some(Arc::new(
-                                                ExplorerVote::Private {
-                                                    proof: proof.clone(),
-                                                    encrypted_vote: encrypted_vote.clone(),
-                                                },
-                                            )))
-                                        },
-                                    )
+                                    .insert_or_update(voter, Arc::clone(&vote), |_| {
+                                        Ok::<_, std::convert::Infallible>(Some(Arc::clone(&vote)))
+                                    })
                                     .unwrap();
 
                                 let vote_plan = ExplorerVotePlan {
@@ -707,13 +898,20 @@ fn apply_block_to_vote_plans(
                                 .proposals
                                 .clone()
                                 .into_iter()
-                                .map(|mut proposal| {
-                                    proposal.tally = Some(compute_public_tally(&proposal, stake));
-
-                                    proposal
+                                .enumerate()
+                                .map(|(index, mut proposal)| {
+                                    proposal.tally = Some(public_tally(
+                                        vote_tally.id(),
+                                        index as u8,
+                                        &proposal,
+                                        stake,
+                                        stable_storage,
+                                    )?);
+
+                                    Ok(proposal)
                                 })
-                                .collect(),
-                            PayloadType::Private => {
+                                .collect::<Result<_, Error>>()?,
+                            PayloadType::Private | PayloadType::Approval | PayloadType::Ranked => {
                                 if let Some(decrypted_tally) = vote_tally.tally_decrypted() {
                                     vote_plan
                                         .proposals
@@ -743,7 +941,7 @@ fn apply_block_to_vote_plans(
 
                         Ok(Some(Arc::new(vote_plan)))
                     })
-                    .unwrap(),
+                    .map_err(vote_plan_update_error)?,
                 _ => vote_plans,
             }
         }
@@ -752,6 +950,63 @@ fn apply_block_to_vote_plans(
     Ok(vote_plans)
 }
 
+/// Copy a stake pool from the stable storage to the indexes, to update it
+fn load_stake_pool(
+    data: StakePool,
+    pool: &PoolId,
+    stable_storage: &StableStorage,
+) -> Result<StakePool, Error> {
+    if data.contains_key(pool) {
+        return Ok(data);
+    }
+
+    Ok(match stable_storage.stake_pool(pool)? {
+        Some(pool_data) => data.insert(pool.clone(), Arc::new(pool_data)).unwrap(),
+        None => data,
+    })
+}
+
+/// Copy a vote plan from the stable storage to the indexes, to update it.
+/// Its votes stay in the storage, the indexes only get the ones cast since.
+fn load_vote_plan(
+    vote_plans: VotePlans,
+    id: &VotePlanId,
+    stable_storage: &StableStorage,
+) -> Result<VotePlans, Error> {
+    if vote_plans.contains_key(id) {
+        return Ok(vote_plans);
+    }
+
+    Ok(match stable_storage.vote_plan_metadata(id)? {
+        Some(vote_plan) => vote_plans.insert(id.clone(), Arc::new(vote_plan)).unwrap(),
+        None => vote_plans,
+    })
+}
+
+/// Tally of a public proposal, with both the votes of the indexes and the
+/// ones in the stable storage
+fn public_tally(
+    id: &VotePlanId,
+    index: u8,
+    proposal: &ExplorerVoteProposal,
+    stake: &StakeControl,
+    stable_storage: &StableStorage,
+) -> Result<ExplorerVoteTally, Error> {
+    let mut error = None;
+    let stable_votes = stable_storage
+        .proposal_votes(id, index)
+        .map_while(|vote| vote.map_err(|e| error = Some(e)).ok());
+    let tally = compute_public_tally(proposal, stable_votes, stake);
+    error.map_or(Ok(tally), Err)
+}
+
+fn vote_plan_update_error(error: UpdateError<Error>) -> Error {
+    match error {
+        UpdateError::ValueCallbackError(error) => error,
+        UpdateError::KeyNotFound => Error::CannotApplyBlock,
+    }
+}
+
 fn apply_block_to_stake_control(
     mut stake_control: StakeControl,
     block: &ExplorerBlock,
@@ -796,6 +1051,19 @@ fn apply_block_to_stake_control(
 }
 
 impl BlockchainConfig {
+    fn from_block0(block0: &Block) -> BlockchainConfig {
+        BlockchainConfig::from_config_params(
+            block0
+                .contents()
+                .iter()
+                .find_map(|fragment| match fragment {
+                    Fragment::Initial(config_params) => Some(config_params),
+                    _ => None,
+                })
+                .expect("the Initial fragment to be present in the genesis block"),
+        )
+    }
+
     fn from_config_params(params: &ConfigParams) -> BlockchainConfig {
         let mut discrimination: Option<Discrimination> = None;
         let mut consensus_version: Option<ConsensusVersion> = None;
@@ -842,27 +1110,242 @@ impl Tip {
 }
 
 impl State {
-    pub fn get_vote_plans(&self) -> Vec<(VotePlanId, Arc<ExplorerVotePlan>)> {
-        self.vote_plans
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect()
+    /// State without any block, on top of the stable storage
+    fn new(stake_control: StakeControl, stable_storage: StableStorage) -> Self {
+        State {
+            transactions: Transactions::new(),
+            blocks: Blocks::new(),
+            addresses: Addresses::new(),
+            epochs: Epochs::new(),
+            chain_lengths: ChainLengths::new(),
+            stake_pool_data: StakePool::new(),
+            stake_pool_blocks: StakePoolBlocks::new(),
+            vote_plans: VotePlans::new(),
+            stake_control,
+            stable_storage,
+        }
+    }
+
+    fn building_context(&self, discrimination: Discrimination) -> ExplorerBlockBuildingContext<'_> {
+        ExplorerBlockBuildingContext {
+            discrimination,
+            prev_transactions: &self.transactions,
+            prev_blocks: &self.blocks,
+            stable_storage: &self.stable_storage,
+        }
+    }
+
+    fn apply_block(&self, block: &ExplorerBlock) -> Result<State, Error> {
+        let (stake_pool_data, stake_pool_blocks) = apply_block_to_stake_pools(
+            self.stake_pool_data.clone(),
+            self.stake_pool_blocks.clone(),
+            block,
+            &self.stable_storage,
+        )?;
+
+        let stake_control = apply_block_to_stake_control(self.stake_control.clone(), block);
+
+        let vote_plans = apply_block_to_vote_plans(
+            self.vote_plans.clone(),
+            block,
+            &stake_control,
+            &self.stable_storage,
+        )?;
+
+        Ok(State {
+            transactions: apply_block_to_transactions(self.transactions.clone(), block)?,
+            blocks: apply_block_to_blocks(self.blocks.clone(), block)?,
+            addresses: apply_block_to_addresses(self.addresses.clone(), block),
+            epochs: apply_block_to_epochs(self.epochs.clone(), block, &self.stable_storage)?,
+            chain_lengths: apply_block_to_chain_lengths(self.chain_lengths.clone(), block)?,
+            stake_pool_data,
+            stake_pool_blocks,
+            vote_plans,
+            stake_control,
+            stable_storage: self.stable_storage.clone(),
+        })
+    }
+
+    pub fn get_block(&self, block_id: &HeaderHash) -> Option<Arc<ExplorerBlock>> {
+        match self.blocks.lookup(block_id) {
+            Some(block) => Some(Arc::clone(block)),
+            None => read_stable(self.stable_storage.block(block_id)).map(Arc::new),
+        }
+    }
+
+    /// Number of blocks of the branch, including the genesis block
+    pub fn block_count(&self) -> usize {
+        let stable_blocks = read_stable(self.stable_storage.tip())
+            .map_or(0, |(_, chain_length)| u32::from(chain_length) as usize + 1);
+        stable_blocks + self.blocks.size()
+    }
+
+    pub fn get_block_hash(&self, chain_length: ChainLength) -> Option<HeaderHash> {
+        match self.chain_lengths.lookup(&chain_length) {
+            Some(hash) => Some(**hash),
+            None => read_stable(self.stable_storage.block_id(chain_length)),
+        }
+    }
+
+    pub fn get_transaction_block(&self, transaction_id: &FragmentId) -> Option<HeaderHash> {
+        match self.transactions.lookup(transaction_id) {
+            Some(hash) => Some(**hash),
+            None => read_stable(self.stable_storage.transaction_block(transaction_id)),
+        }
+    }
+
+    pub fn get_epoch(&self, epoch: Epoch) -> Option<EpochData> {
+        match self.epochs.lookup(&epoch) {
+            Some(data) => Some(data.as_ref().clone()),
+            None => read_stable(self.stable_storage.epoch(epoch)),
+        }
+    }
+
+    pub fn get_stake_pool_data(&self, pool: &PoolId) -> Option<Arc<StakePoolData>> {
+        match self.stake_pool_data.lookup(pool) {
+            Some(data) => Some(Arc::clone(data)),
+            None => read_stable(self.stable_storage.stake_pool(pool)).map(Arc::new),
+        }
+    }
+
+    pub fn get_stake_pool_blocks(&self, pool: &PoolId) -> Option<StoredSequence> {
+        let unstable = match self.stake_pool_blocks.lookup(pool) {
+            Some(blocks) => PersistentSequence::clone(blocks),
+            None => {
+                read_stable(self.stable_storage.stake_pool(pool))?;
+                PersistentSequence::new()
+            }
+        };
+
+        read_stable(
+            self.stable_storage
+                .stake_pool_blocks(pool, unstable)
+                .map(Some),
+        )
+    }
+
+    pub fn get_vote_plan(&self, vote_plan_id: &VotePlanId) -> Option<Arc<ExplorerVotePlan>> {
+        let stable = read_stable(self.stable_storage.vote_plan(vote_plan_id));
+        match (self.vote_plans.lookup(vote_plan_id), stable) {
+            // only the votes cast since it was loaded are in the indexes
+            (Some(vote_plan), Some(mut stable)) => {
+                for (proposal, stored) in
+                    vote_plan.proposals.iter().zip(stable.proposals.iter_mut())
+                {
+                    for (voter, vote) in proposal.votes.iter() {
+                        stored.votes = stored
+                            .votes
+                            .insert_or_update(voter.clone(), Arc::clone(vote), |_| {
+                                Ok::<_, Infallible>(Some(Arc::clone(vote)))
+                            })
+                            .unwrap();
+                    }
+                    stored.tally = proposal.tally.clone();
+                }
+                Some(Arc::new(stable))
+            }
+            (Some(vote_plan), None) => Some(Arc::clone(vote_plan)),
+            (None, stable) => stable.map(Arc::new),
+        }
+    }
+
+    /// Vote cast by `voter` on a proposal, without reading all the votes of
+    /// the vote plan
+    pub fn get_vote(
+        &self,
+        vote_plan_id: &VotePlanId,
+        proposal_index: u8,
+        voter: &ExplorerAddress,
+    ) -> Option<Arc<ExplorerVote>> {
+        let vote = self
+            .vote_plans
+            .lookup(vote_plan_id)
+            .and_then(|vote_plan| vote_plan.proposals.get(usize::from(proposal_index)))
+            .and_then(|proposal| proposal.votes.lookup(voter));
+        match vote {
+            Some(vote) => Some(Arc::clone(vote)),
+            None => read_stable(
+                self.stable_storage
+                    .vote(vote_plan_id, proposal_index, voter),
+            )
+            .map(Arc::new),
+        }
+    }
+
+    pub fn get_vote_plans(&self) -> Vec<VotePlanId> {
+        let mut vote_plans: Vec<_> = self.vote_plans.iter().map(|(k, _)| k.clone()).collect();
+        vote_plans.extend(
+            read_stable(self.stable_storage.vote_plan_ids().map(Some))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|id| !self.vote_plans.contains_key(id)),
+        );
+        vote_plans
+    }
+
+    /// Votes cast by `address` on all the vote plans, sorted by vote plan and
+    /// proposal index
+    pub fn get_votes_by_address(
+        &self,
+        address: &ExplorerAddress,
+    ) -> Vec<(VotePlanId, u8, Arc<ExplorerVote>)> {
+        let mut vote_plan_ids = self.get_vote_plans();
+        vote_plan_ids.sort_unstable();
+
+        let mut votes = Vec::new();
+        for id in vote_plan_ids {
+            let mut stable: BTreeMap<_, _> =
+                read_stable(self.stable_storage.votes_by_address(&id, address).map(Some))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(index, vote)| (index, Arc::new(vote)))
+                    .collect();
+            // the votes cast since the vote plan was loaded in the indexes
+            if let Some(vote_plan) = self.vote_plans.lookup(&id) {
+                for (index, proposal) in vote_plan.proposals.iter().enumerate() {
+                    if let Some(vote) = proposal.votes.lookup(address) {
+                        stable.insert(index as u8, Arc::clone(vote));
+                    }
+                }
+            }
+            votes.extend(
+                stable
+                    .into_iter()
+                    .map(|(index, vote)| (id.clone(), index, vote)),
+            );
+        }
+        votes
     }
 
     pub fn get_stake_pools(&self) -> Vec<(PoolId, Arc<StakePoolData>)> {
-        self.stake_pool_data
+        let mut stake_pools: Vec<_> = self
+            .stake_pool_data
             .iter()
             .map(|(k, v)| (k.clone(), v.clone()))
-            .collect()
+            .collect();
+        stake_pools.extend(
+            read_stable(self.stable_storage.stake_pools().map(Some))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(id, _)| !self.stake_pool_data.contains_key(id))
+                .map(|(id, data)| (id, Arc::new(data))),
+        );
+        stake_pools
     }
 
-    pub fn transactions_by_address(
-        &self,
-        address: &ExplorerAddress,
-    ) -> Option<PersistentSequence<FragmentId>> {
-        self.addresses
+    pub fn transactions_by_address(&self, address: &ExplorerAddress) -> Option<StoredSequence> {
+        let unstable = self
+            .addresses
             .lookup(address)
             .map(|txs| PersistentSequence::clone(txs))
+            .unwrap_or_default();
+
+        read_stable(
+            self.stable_storage
+                .transactions_by_address(address, unstable)
+                .map(Some),
+        )
+        .filter(|txs| !txs.is_empty())
     }
 
     // Get the hashes of all blocks in the range [from, to)
@@ -877,11 +1360,16 @@ impl State {
         let to = u32::from(to);
 
         (from..to)
-            .filter_map(|i| {
-                self.chain_lengths
-                    .lookup(&i.into())
-                    .map(|b| (*b.as_ref(), i.into()))
-            })
+            .filter_map(|i| self.get_block_hash(i.into()).map(|b| (b, i.into())))
             .collect()
     }
 }
+
+/// The queries don't fail, an error of the stable storage is logged and the
+/// data is considered as not found
+fn read_stable<T>(result: Result<Option<T>, Error>) -> Option<T> {
+    result.unwrap_or_else(|error| {
+        error!(%error, "cannot read the explorer storage");
+        None
+    })
+}
@@ -66,6 +66,14 @@ impl Multiverse {
         guard.multiverse.gc(depth)
     }
 
+    /// replace all the states by the ones of `other`, used once the oldest
+    /// states were moved to the stable storage
+    pub(super) async fn replace(&self, other: Multiverse) {
+        let mut guard = self.inner.write().await;
+        let mut other_guard = other.inner.write().await;
+        std::mem::swap(&mut *guard, &mut *other_guard);
+    }
+
     /// get all the branches this block is in, None here means the block was never added
     /// or it was moved to stable storage
     pub(super) async fn tips(&self) -> Vec<(HeaderHash, multiverse::Ref<State>)> {
@@ -0,0 +1,964 @@
+//! On-disk part of the explorer index.
+//!
+//! Blocks deeper than the epoch stability depth cannot be rolled back anymore,
+//! so their indexes are moved out of the multiverse into a sled database. The
+//! states of the multiverse only hold the indexes of the blocks which are not
+//! stable yet, and fall back to this storage for everything else.
+//!
+//! All the indexes live in a single tree, with keys prefixed by the kind of
+//! the entry, so moving a batch of blocks to the storage is atomic.
+//!
+//! ```text
+//! B  block id                          block, as sent by the node
+//! U  block id                          blocks stored but not stable yet
+//! L  chain length                      id of the stable block
+//! T  fragment id                       id of the block of the transaction
+//! O  fragment id                       outputs of the transaction
+//! a  address                           number of transactions of the address
+//! A  address, index                    id of the transaction
+//! E  epoch                             first block, last block, number of blocks
+//! P  pool id                           registration and retirement certificates
+//! k  pool id                           number of blocks of the pool
+//! K  pool id, index                    id of the block
+//! V  vote plan id                      vote plan
+//! W  vote plan id, proposal, address   vote cast certificate
+//! Y  vote plan id, proposal            tally
+//! S  account id                        stake of the account
+//! M                                    id and chain length of the last stable block
+//! ```
+//!
+//! Integers are big endian, so the entries of a sequence are sorted.
+use super::{
+    error::ExplorerError as Error,
+    indexing::{
+        EpochData, ExplorerAddress, ExplorerBlock, ExplorerBlockBuildingContext, ExplorerOutput,
+        ExplorerVote, ExplorerVotePlan, ExplorerVoteProposal, ExplorerVoteTally, StakePoolData,
+    },
+    persistent_sequence::PersistentSequence,
+    State,
+};
+use cardano_legacy_address::Addr as OldAddress;
+use chain_addr::{Address, Discrimination};
+use chain_core::{
+    packer::Codec,
+    property::{Deserialize, DeserializeFromSlice, Serialize},
+};
+use chain_impl_mockchain::{
+    account::Identifier,
+    block::{Block, BlockDate, ChainLength, Epoch, HeaderId as HeaderHash},
+    certificate::{PoolId, PoolRegistration, PoolRetirement, VoteCast, VotePlanId},
+    fragment::FragmentId,
+    key::Hash,
+    stake::{Stake, StakeControl},
+    value::Value,
+    vote::{Options, Payload, PayloadType},
+};
+use std::{collections::HashSet, convert::TryFrom, path::Path, sync::Arc};
+
+const BLOCK: u8 = b'B';
+const UNSTABLE_BLOCK: u8 = b'U';
+const CHAIN_LENGTH: u8 = b'L';
+const TRANSACTION: u8 = b'T';
+const OUTPUTS: u8 = b'O';
+const ADDRESS_TRANSACTION_COUNT: u8 = b'a';
+const ADDRESS_TRANSACTION: u8 = b'A';
+const EPOCH: u8 = b'E';
+const STAKE_POOL: u8 = b'P';
+const STAKE_POOL_BLOCK_COUNT: u8 = b'k';
+const STAKE_POOL_BLOCK: u8 = b'K';
+const VOTE_PLAN: u8 = b'V';
+const VOTE: u8 = b'W';
+const TALLY: u8 = b'Y';
+const STAKE: u8 = b'S';
+const TIP: &[u8] = b"M";
+
+const ADDRESS_NEW: u8 = 0;
+const ADDRESS_OLD: u8 = 1;
+
+const TALLY_PUBLIC: u8 = 0;
+const TALLY_PRIVATE_PENDING: u8 = 1;
+const TALLY_PRIVATE: u8 = 2;
+
+#[derive(Clone)]
+pub struct StableStorage {
+    db: sled::Db,
+    /// Used to resolve the account inputs of the blocks read back from the storage
+    discrimination: Discrimination,
+}
+
+// the content of the database would be printed otherwise
+impl std::fmt::Debug for StableStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StableStorage").finish_non_exhaustive()
+    }
+}
+
+/// Sequence of hashes whose first elements are in the stable storage, and the
+/// last ones in the state of a branch.
+#[derive(Clone)]
+pub struct StoredSequence {
+    storage: StableStorage,
+    prefix: Vec<u8>,
+    stable_len: u64,
+    unstable: PersistentSequence<Hash>,
+}
+
+impl StableStorage {
+    /// Open the storage in the given directory, or in a temporary one which is
+    /// removed when the explorer stops.
+    pub fn open(path: Option<&Path>) -> Result<Self, Error> {
+        let db = match path {
+            Some(path) => sled::open(path)?,
+            None => sled::Config::new().temporary(true).open()?,
+        };
+
+        Ok(StableStorage {
+            db,
+            discrimination: Discrimination::Production,
+        })
+    }
+
+    pub(super) fn with_discrimination(self, discrimination: Discrimination) -> Self {
+        StableStorage {
+            discrimination,
+            ..self
+        }
+    }
+
+    /// The id and chain length of the last block moved to the storage, `None`
+    /// if the storage is empty.
+    pub fn tip(&self) -> Result<Option<(HeaderHash, ChainLength)>, Error> {
+        self.db
+            .get(TIP)?
+            .map(|bytes| {
+                let mut codec = Codec::new(bytes.as_ref());
+                let id = read_hash(&mut codec)?;
+                let chain_length = codec.get_be_u32().map_err(corrupted)?;
+                Ok((id, chain_length.into()))
+            })
+            .transpose()
+    }
+
+    /// Remove everything from the storage, to index a chain from scratch.
+    pub fn clear(&self) -> Result<(), Error> {
+        self.db.clear()?;
+        self.db.flush()?;
+
+        Ok(())
+    }
+
+    /// Keep the block until it is either stable or abandoned, this is
+    /// the only copy of it, the states keep only its indexes.
+    pub(super) fn put_block(&self, block: &Block) -> Result<(), Error> {
+        let id = block.header().hash();
+        let bytes = block.serialize_as_vec().map_err(corrupted)?;
+
+        let mut batch = sled::Batch::default();
+        batch.insert(key(BLOCK, id.as_ref()), bytes);
+        batch.insert(key(UNSTABLE_BLOCK, id.as_ref()), Vec::new());
+        self.db.apply_batch(batch)?;
+
+        Ok(())
+    }
+
+    /// Remove the blocks which were stored but never became stable, after a
+    /// restart they will be sent again by the node.
+    pub(super) fn remove_unstable_blocks(&self) -> Result<(), Error> {
+        let mut batch = sled::Batch::default();
+        for entry in self.db.scan_prefix([UNSTABLE_BLOCK]) {
+            let (k, _) = entry?;
+            batch.remove(key(BLOCK, &k[1..]));
+            batch.remove(k);
+        }
+        self.db.apply_batch(batch)?;
+
+        Ok(())
+    }
+
+    pub(super) fn raw_block(&self, id: &HeaderHash) -> Result<Option<Block>, Error> {
+        self.db
+            .get(key(BLOCK, id.as_ref()))?
+            .map(|bytes| Block::deserialize(&mut Codec::new(bytes.as_ref())).map_err(corrupted))
+            .transpose()
+    }
+
+    /// Get a stable block, blocks which are only kept until they are stable
+    /// are ignored
+    pub(super) fn block(&self, id: &HeaderHash) -> Result<Option<ExplorerBlock>, Error> {
+        let block = match self.raw_block(id)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+
+        if self.block_id(block.header().chain_length())? != Some(*id) {
+            return Ok(None);
+        }
+
+        ExplorerBlock::resolve_from(
+            &block,
+            ExplorerBlockBuildingContext {
+                discrimination: self.discrimination,
+                prev_transactions: &Default::default(),
+                prev_blocks: &Default::default(),
+                stable_storage: self,
+            },
+        )
+        .map(Some)
+    }
+
+    pub(super) fn block_id(&self, chain_length: ChainLength) -> Result<Option<HeaderHash>, Error> {
+        self.db
+            .get(key(CHAIN_LENGTH, &u32::from(chain_length).to_be_bytes()))?
+            .map(|bytes| read_hash(&mut Codec::new(bytes.as_ref())))
+            .transpose()
+    }
+
+    pub(super) fn transaction_block(&self, id: &FragmentId) -> Result<Option<HeaderHash>, Error> {
+        self.db
+            .get(key(TRANSACTION, id.as_ref()))?
+            .map(|bytes| read_hash(&mut Codec::new(bytes.as_ref())))
+            .transpose()
+    }
+
+    /// Get an output of a stable transaction, to resolve utxo inputs
+    pub(super) fn output(
+        &self,
+        id: &FragmentId,
+        index: u8,
+    ) -> Result<Option<ExplorerOutput>, Error> {
+        let bytes = match self.db.get(key(OUTPUTS, id.as_ref()))? {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+
+        let mut codec = Codec::new(bytes.as_ref());
+        let count = codec.get_be_u32().map_err(corrupted)?;
+        for i in 0..count {
+            let address = read_address(&mut codec)?;
+            let value = Value(codec.get_be_u64().map_err(corrupted)?);
+            if i == u32::from(index) {
+                return Ok(Some(ExplorerOutput { address, value }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub(super) fn epoch(&self, epoch: Epoch) -> Result<Option<EpochData>, Error> {
+        self.db
+            .get(key(EPOCH, &epoch.to_be_bytes()))?
+            .map(|bytes| {
+                let mut codec = Codec::new(bytes.as_ref());
+                Ok(EpochData {
+                    first_block: read_hash(&mut codec)?,
+                    last_block: read_hash(&mut codec)?,
+                    total_blocks: codec.get_be_u32().map_err(corrupted)?,
+                })
+            })
+            .transpose()
+    }
+
+    /// The part of the sequence in the storage, the unstable part is appended
+    /// to it.
+    pub(super) fn transactions_by_address(
+        &self,
+        address: &ExplorerAddress,
+        unstable: PersistentSequence<Hash>,
+    ) -> Result<StoredSequence, Error> {
+        let address = address_key(address);
+        self.sequence(
+            key(ADDRESS_TRANSACTION_COUNT, &address),
+            key(ADDRESS_TRANSACTION, &address),
+            unstable,
+        )
+    }
+
+    pub(super) fn stake_pool_blocks(
+        &self,
+        pool: &PoolId,
+        unstable: PersistentSequence<Hash>,
+    ) -> Result<StoredSequence, Error> {
+        self.sequence(
+            key(STAKE_POOL_BLOCK_COUNT, pool.as_ref()),
+            key(STAKE_POOL_BLOCK, pool.as_ref()),
+            unstable,
+        )
+    }
+
+    fn sequence(
+        &self,
+        count_key: Vec<u8>,
+        prefix: Vec<u8>,
+        unstable: PersistentSequence<Hash>,
+    ) -> Result<StoredSequence, Error> {
+        Ok(StoredSequence {
+            storage: self.clone(),
+            prefix,
+            stable_len: self.count(&count_key)?,
+            unstable,
+        })
+    }
+
+    fn count(&self, count_key: &[u8]) -> Result<u64, Error> {
+        self.db
+            .get(count_key)?
+            .map(|bytes| Codec::new(bytes.as_ref()).get_be_u64().map_err(corrupted))
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    pub(super) fn stake_pool(&self, pool: &PoolId) -> Result<Option<StakePoolData>, Error> {
+        self.db
+            .get(key(STAKE_POOL, pool.as_ref()))?
+            .map(|bytes| read_stake_pool(&bytes))
+            .transpose()
+    }
+
+    pub(super) fn stake_pools(&self) -> Result<Vec<(PoolId, StakePoolData)>, Error> {
+        self.db
+            .scan_prefix([STAKE_POOL])
+            .map(|entry| {
+                let (k, v) = entry?;
+                let id = PoolId::try_from(&k[1..]).map_err(corrupted)?;
+                Ok((id, read_stake_pool(&v)?))
+            })
+            .collect()
+    }
+
+    /// A stable vote plan with all its votes
+    pub(super) fn vote_plan(&self, id: &VotePlanId) -> Result<Option<ExplorerVotePlan>, Error> {
+        let mut vote_plan = match self.vote_plan_metadata(id)? {
+            Some(vote_plan) => vote_plan,
+            None => return Ok(None),
+        };
+
+        let prefix = key(VOTE, id.as_ref());
+        for entry in self.db.scan_prefix(&prefix) {
+            let (k, v) = entry?;
+            let mut codec = Codec::new(&k[prefix.len()..]);
+            let index = codec.get_u8().map_err(corrupted)?;
+            let voter = read_address(&mut codec)?;
+            let vote = read_vote(&v)?;
+
+            let proposal = vote_plan
+                .proposals
+                .get_mut(usize::from(index))
+                .ok_or_else(|| corrupted("vote for an unknown proposal"))?;
+            proposal.votes = proposal
+                .votes
+                .insert(voter, Arc::new(vote))
+                .map_err(corrupted)?;
+        }
+
+        Ok(Some(vote_plan))
+    }
+
+    /// A stable vote plan with the tallies of its proposals but none of their
+    /// votes, which are read with `proposal_votes`
+    pub(super) fn vote_plan_metadata(
+        &self,
+        id: &VotePlanId,
+    ) -> Result<Option<ExplorerVotePlan>, Error> {
+        let bytes = match self.db.get(key(VOTE_PLAN, id.as_ref()))? {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+
+        let mut codec = Codec::new(bytes.as_ref());
+        let vote_start = read_date(&mut codec)?;
+        let vote_end = read_date(&mut codec)?;
+        let committee_end = read_date(&mut codec)?;
+        let payload_type =
+            PayloadType::try_from(codec.get_u8().map_err(corrupted)?).map_err(corrupted)?;
+        let proposal_count = codec.get_be_u16().map_err(corrupted)?;
+
+        let mut proposals = Vec::with_capacity(proposal_count.into());
+        for index in 0..proposal_count {
+            let proposal_id = codec
+                .get_slice(32)
+                .map_err(corrupted)
+                .and_then(|bytes| TryFrom::try_from(bytes).map_err(corrupted))?;
+            let options = read_options(&mut codec)?;
+            let tally_key = [key(TALLY, id.as_ref()), vec![index as u8]].concat();
+            let tally = self
+                .db
+                .get(tally_key)?
+                .map(|bytes| read_tally(&bytes, &options))
+                .transpose()?;
+
+            proposals.push(ExplorerVoteProposal {
+                proposal_id,
+                options,
+                tally,
+                votes: Default::default(),
+            });
+        }
+
+        Ok(Some(ExplorerVotePlan {
+            id: id.clone(),
+            vote_start,
+            vote_end,
+            committee_end,
+            payload_type,
+            proposals,
+        }))
+    }
+
+    /// Stable votes of a proposal, read one at a time so the proposal does not
+    /// need to fit in memory
+    pub(super) fn proposal_votes(
+        &self,
+        id: &VotePlanId,
+        index: u8,
+    ) -> impl Iterator<Item = Result<(ExplorerAddress, ExplorerVote), Error>> {
+        let prefix = [key(VOTE, id.as_ref()), vec![index]].concat();
+        self.db.scan_prefix(&prefix).map(move |entry| {
+            let (k, v) = entry?;
+            let voter = read_address(&mut Codec::new(&k[prefix.len()..]))?;
+            Ok((voter, read_vote(&v)?))
+        })
+    }
+
+    /// Stable vote cast by `voter` on a proposal
+    pub(super) fn vote(
+        &self,
+        id: &VotePlanId,
+        index: u8,
+        voter: &ExplorerAddress,
+    ) -> Result<Option<ExplorerVote>, Error> {
+        let vote_key = [key(VOTE, id.as_ref()), vec![index], address_key(voter)].concat();
+        self.db
+            .get(vote_key)?
+            .map(|bytes| read_vote(&bytes))
+            .transpose()
+    }
+
+    /// Votes cast by `voter` on the proposals of a stable vote plan, with the
+    /// index of the proposal
+    pub(super) fn votes_by_address(
+        &self,
+        id: &VotePlanId,
+        voter: &ExplorerAddress,
+    ) -> Result<Vec<(u8, ExplorerVote)>, Error> {
+        let bytes = match self.db.get(key(VOTE_PLAN, id.as_ref()))? {
+            Some(bytes) => bytes,
+            None => return Ok(Vec::new()),
+        };
+
+        // skip the dates and the payload type to read the number of proposals
+        let mut codec = Codec::new(bytes.as_ref());
+        for _ in 0..3 {
+            read_date(&mut codec)?;
+        }
+        codec.get_u8().map_err(corrupted)?;
+        let proposal_count = codec.get_be_u16().map_err(corrupted)?;
+
+        let mut votes = Vec::new();
+        for index in 0..proposal_count {
+            let index = index as u8;
+            if let Some(vote) = self.vote(id, index, voter)? {
+                votes.push((index, vote));
+            }
+        }
+        Ok(votes)
+    }
+
+    pub(super) fn vote_plan_ids(&self) -> Result<Vec<VotePlanId>, Error> {
+        self.db
+            .scan_prefix([VOTE_PLAN])
+            .keys()
+            .map(|k| VotePlanId::try_from(&k?[1..]).map_err(corrupted))
+            .collect()
+    }
+
+    pub(super) fn stake_control(&self) -> Result<StakeControl, Error> {
+        self.db
+            .scan_prefix([STAKE])
+            .try_fold(StakeControl::new(), |stake_control, entry| {
+                let (k, v) = entry?;
+                let id = Identifier::deserialize_from_slice(&mut Codec::new(&k[1..]))
+                    .map_err(corrupted)?;
+                let stake = Codec::new(v.as_ref()).get_be_u64().map_err(corrupted)?;
+                Ok(stake_control.add_to(id, Stake(stake)))
+            })
+    }
+
+    /// Move the indexes of `state` to the storage, `state` must be the state of
+    /// the block `id`, built on top of the last stable block. `abandoned` are
+    /// blocks which will never become stable.
+    pub(super) fn flush(
+        &self,
+        id: HeaderHash,
+        chain_length: ChainLength,
+        state: &State,
+        abandoned: impl IntoIterator<Item = HeaderHash>,
+    ) -> Result<(), Error> {
+        let mut batch = sled::Batch::default();
+        let mut accounts = HashSet::new();
+
+        for (block_id, block) in state.blocks.iter() {
+            batch.remove(key(UNSTABLE_BLOCK, block_id.as_ref()));
+            batch.insert(
+                key(CHAIN_LENGTH, &u32::from(block.chain_length).to_be_bytes()),
+                block_id.as_ref(),
+            );
+
+            for (tx_id, tx) in block.transactions.iter() {
+                batch.insert(key(TRANSACTION, tx_id.as_ref()), block_id.as_ref());
+
+                let mut outputs = (tx.outputs.len() as u32).to_be_bytes().to_vec();
+                for output in tx.outputs.iter() {
+                    outputs.extend(address_key(&output.address));
+                    outputs.extend(output.value.0.to_be_bytes());
+                }
+                batch.insert(key(OUTPUTS, tx_id.as_ref()), outputs);
+
+                accounts.extend(
+                    tx.inputs
+                        .iter()
+                        .map(|input| &input.address)
+                        .chain(tx.outputs.iter().map(|output| &output.address))
+                        .filter_map(account_of),
+                );
+            }
+        }
+
+        for (address, transactions) in state.addresses.iter() {
+            let address = address_key(address);
+            self.append(
+                &mut batch,
+                key(ADDRESS_TRANSACTION_COUNT, &address),
+                key(ADDRESS_TRANSACTION, &address),
+                transactions,
+            )?;
+        }
+
+        for (epoch, data) in state.epochs.iter() {
+            let mut bytes = data.first_block.as_ref().to_vec();
+            bytes.extend(data.last_block.as_ref());
+            bytes.extend(data.total_blocks.to_be_bytes());
+            batch.insert(key(EPOCH, &epoch.to_be_bytes()), bytes);
+        }
+
+        for (pool, data) in state.stake_pool_data.iter() {
+            batch.insert(key(STAKE_POOL, pool.as_ref()), write_stake_pool(data)?);
+        }
+
+        for (pool, blocks) in state.stake_pool_blocks.iter() {
+            self.append(
+                &mut batch,
+                key(STAKE_POOL_BLOCK_COUNT, pool.as_ref()),
+                key(STAKE_POOL_BLOCK, pool.as_ref()),
+                blocks,
+            )?;
+        }
+
+        // the vote plans loaded from the storage only hold the votes cast in the
+        // last blocks, the others are already stored
+        for (id, vote_plan) in state.vote_plans.iter() {
+            self.write_vote_plan(&mut batch, id, vote_plan)?;
+        }
+
+        for account in accounts {
+            let k = key(STAKE, &account.serialize_as_vec().map_err(corrupted)?);
+            match state.stake_control.by(&account) {
+                Some(stake) => batch.insert(k, &u64::from(stake).to_be_bytes()[..]),
+                None => batch.remove(k),
+            }
+        }
+
+        for abandoned in abandoned {
+            batch.remove(key(BLOCK, abandoned.as_ref()));
+            batch.remove(key(UNSTABLE_BLOCK, abandoned.as_ref()));
+        }
+
+        let mut tip = id.as_ref().to_vec();
+        tip.extend(u32::from(chain_length).to_be_bytes());
+        batch.insert(TIP, tip);
+
+        self.db.apply_batch(batch)?;
+        self.db.flush()?;
+
+        Ok(())
+    }
+
+    fn append(
+        &self,
+        batch: &mut sled::Batch,
+        count_key: Vec<u8>,
+        prefix: Vec<u8>,
+        sequence: &PersistentSequence<Hash>,
+    ) -> Result<(), Error> {
+        let stable_len = self.count(&count_key)?;
+        for i in 0..sequence.len() {
+            let hash = sequence.get(i).expect("sequence to be contiguous");
+            let index = (stable_len + i).to_be_bytes();
+            batch.insert([prefix.as_slice(), &index].concat(), hash.as_ref().as_ref());
+        }
+        batch.insert(count_key, &(stable_len + sequence.len()).to_be_bytes()[..]);
+
+        Ok(())
+    }
+
+    fn write_vote_plan(
+        &self,
+        batch: &mut sled::Batch,
+        id: &VotePlanId,
+        vote_plan: &ExplorerVotePlan,
+    ) -> Result<(), Error> {
+        let mut bytes = Vec::new();
+        write_date(&mut bytes, vote_plan.vote_start);
+        write_date(&mut bytes, vote_plan.vote_end);
+        write_date(&mut bytes, vote_plan.committee_end);
+        bytes.push(vote_plan.payload_type as u8);
+        bytes.extend((vote_plan.proposals.len() as u16).to_be_bytes());
+
+        for (index, proposal) in vote_plan.proposals.iter().enumerate() {
+            let index = index as u8;
+            bytes.extend(proposal.proposal_id.as_ref());
+            bytes.push(proposal.options.choice_range().end);
+            bytes.push(proposal.options.max_selections());
+
+            if let Some(tally) = &proposal.tally {
+                let tally_key = [key(TALLY, id.as_ref()), vec![index]].concat();
+                let tally = write_tally(tally);
+                if self.db.get(&tally_key)?.as_deref() != Some(&tally[..]) {
+                    batch.insert(tally_key, tally);
+                }
+            }
+
+            for (voter, vote) in proposal.votes.iter() {
+                let payload = match vote.as_ref().clone() {
+                    ExplorerVote::Public(choice) => Payload::Public { choice },
+                    ExplorerVote::Private {
+                        proof,
+                        encrypted_vote,
+                    } => Payload::Private {
+                        encrypted_vote,
+                        proof,
+                    },
+                    ExplorerVote::Approval {
+                        proof,
+                        encrypted_vote,
+                    } => Payload::Approval {
+                        encrypted_vote,
+                        proof,
+                    },
+                    ExplorerVote::Ranked {
+                        proof,
+                        encrypted_vote,
+                    } => Payload::Ranked {
+                        encrypted_vote,
+                        proof,
+                    },
+                };
+                let vote_cast = VoteCast::new(id.clone(), index, payload);
+                let vote_key = [key(VOTE, id.as_ref()), vec![index], address_key(voter)].concat();
+                batch.insert(vote_key, vote_cast.serialize_as_vec().map_err(corrupted)?);
+            }
+        }
+
+        // the metadata of a vote plan never changes once it is stored
+        let vote_plan_key = key(VOTE_PLAN, id.as_ref());
+        if !self.db.contains_key(&vote_plan_key)? {
+            batch.insert(vote_plan_key, bytes);
+        }
+
+        Ok(())
+    }
+}
+
+impl StoredSequence {
+    pub fn len(&self) -> u64 {
+        self.stable_len + self.unstable.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get<I: Into<u64>>(&self, i: I) -> Option<Hash> {
+        let i = i.into();
+        if i >= self.stable_len {
+            return self.unstable.get(i - self.stable_len).map(|hash| **hash);
+        }
+
+        let k = [self.prefix.as_slice(), &i.to_be_bytes()].concat();
+        match self.storage.db.get(k) {
+            Ok(bytes) => bytes.and_then(|bytes| read_hash(&mut Codec::new(bytes.as_ref())).ok()),
+            Err(error) => {
+                tracing::error!(%error, "cannot read the explorer storage");
+                None
+            }
+        }
+    }
+}
+
+fn key(prefix: u8, bytes: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(bytes.len() + 1);
+    key.push(prefix);
+    key.extend_from_slice(bytes);
+    key
+}
+
+fn corrupted(error: impl std::fmt::Display) -> Error {
+    Error::CorruptedStorage(error.to_string())
+}
+
+fn read_hash(codec: &mut Codec<&[u8]>) -> Result<Hash, Error> {
+    let bytes = codec.get_slice(32).map_err(corrupted)?;
+    Ok(Hash::from_bytes(bytes.try_into().map_err(corrupted)?))
+}
+
+/// The kind of the address and its bytes, prefixed by their length
+fn read_vote(bytes: &[u8]) -> Result<ExplorerVote, Error> {
+    let vote_cast = VoteCast::deserialize_from_slice(&mut Codec::new(bytes)).map_err(corrupted)?;
+    Ok(match vote_cast.payload().clone() {
+        Payload::Public { choice } => ExplorerVote::Public(choice),
+        Payload::Private {
+            encrypted_vote,
+            proof,
+        } => ExplorerVote::Private {
+            proof,
+            encrypted_vote,
+        },
+        Payload::Approval {
+            encrypted_vote,
+            proof,
+        } => ExplorerVote::Approval {
+            proof,
+            encrypted_vote,
+        },
+        Payload::Ranked {
+            encrypted_vote,
+            proof,
+        } => ExplorerVote::Ranked {
+            proof,
+            encrypted_vote,
+        },
+    })
+}
+
+fn address_key(address: &ExplorerAddress) -> Vec<u8> {
+    let (kind, bytes) = match address {
+        ExplorerAddress::New(address) => (ADDRESS_NEW, address.to_bytes()),
+        ExplorerAddress::Old(address) => (ADDRESS_OLD, address.as_ref().to_vec()),
+    };
+
+    let mut key = Vec::with_capacity(bytes.len() + 3);
+    key.push(kind);
+    key.extend((bytes.len() as u16).to_be_bytes());
+    key.extend(bytes);
+    key
+}
+
+fn read_address(codec: &mut Codec<&[u8]>) -> Result<ExplorerAddress, Error> {
+    let kind = codec.get_u8().map_err(corrupted)?;
+    let len = codec.get_be_u16().map_err(corrupted)?;
+    let bytes = codec.get_slice(len.into()).map_err(corrupted)?;
+    match kind {
+        ADDRESS_NEW => Address::from_bytes(bytes)
+            .map(ExplorerAddress::New)
+            .map_err(corrupted),
+        ADDRESS_OLD => OldAddress::try_from(bytes)
+            .map(ExplorerAddress::Old)
+            .map_err(corrupted),
+        _ => Err(corrupted("unknown address kind")),
+    }
+}
+
+fn account_of(address: &ExplorerAddress) -> Option<Identifier> {
+    match address {
+        ExplorerAddress::New(address) => match address.kind() {
+            chain_addr::Kind::Group(_, id) | chain_addr::Kind::Account(id) => {
+                Some(id.clone().into())
+            }
+            _ => None,
+        },
+        ExplorerAddress::Old(_) => None,
+    }
+}
+
+fn write_date(bytes: &mut Vec<u8>, date: BlockDate) {
+    bytes.extend(date.epoch.to_be_bytes());
+    bytes.extend(date.slot_id.to_be_bytes());
+}
+
+fn read_date(codec: &mut Codec<&[u8]>) -> Result<BlockDate, Error> {
+    Ok(BlockDate {
+        epoch: codec.get_be_u32().map_err(corrupted)?,
+        slot_id: codec.get_be_u32().map_err(corrupted)?,
+    })
+}
+
+fn read_options(codec: &mut Codec<&[u8]>) -> Result<Options, Error> {
+    let num_choices = codec.get_u8().map_err(corrupted)?;
+    let max_selections = codec.get_u8().map_err(corrupted)?;
+    if max_selections == 1 {
+        Options::new_length(num_choices).map_err(corrupted)
+    } else {
+        Options::new_multi_selection(num_choices, max_selections).map_err(corrupted)
+    }
+}
+
+fn write_tally(tally: &ExplorerVoteTally) -> Vec<u8> {
+    let (kind, results) = match tally {
+        ExplorerVoteTally::Public { results, .. } => (TALLY_PUBLIC, Some(&results[..])),
+        ExplorerVoteTally::Private { results: None, .. } => (TALLY_PRIVATE_PENDING, None),
+        ExplorerVoteTally::Private {
+            results: Some(results),
+            ..
+        } => (TALLY_PRIVATE, Some(&results[..])),
+    };
+
+    let mut bytes = vec![kind];
+    if let Some(results) = results {
+        bytes.extend((results.len() as u32).to_be_bytes());
+        for weight in results {
+            bytes.extend(u64::from(*weight).to_be_bytes());
+        }
+    }
+    bytes
+}
+
+fn read_tally(bytes: &[u8], options: &Options) -> Result<ExplorerVoteTally, Error> {
+    let mut codec = Codec::new(bytes);
+    let kind = codec.get_u8().map_err(corrupted)?;
+    let mut read_results = || -> Result<Vec<_>, Error> {
+        let len = codec.get_be_u32().map_err(corrupted)?;
+        (0..len)
+            .map(|_| Ok(codec.get_be_u64().map_err(corrupted)?.into()))
+            .collect()
+    };
+
+    match kind {
+        TALLY_PUBLIC => Ok(ExplorerVoteTally::Public {
+            results: read_results()?.into_boxed_slice(),
+            options: options.clone(),
+        }),
+        TALLY_PRIVATE_PENDING => Ok(ExplorerVoteTally::Private {
+            results: None,
+            options: options.clone(),
+        }),
+        TALLY_PRIVATE => Ok(ExplorerVoteTally::Private {
+            results: Some(read_results()?),
+            options: options.clone(),
+        }),
+        _ => Err(corrupted("unknown tally kind")),
+    }
+}
+
+fn write_stake_pool(data: &StakePoolData) -> Result<Vec<u8>, Error> {
+    let registration = data.registration.serialize_as_vec().map_err(corrupted)?;
+    let mut bytes = (registration.len() as u32).to_be_bytes().to_vec();
+    bytes.extend(registration);
+    if let Some(retirement) = &data.retirement {
+        bytes.extend(retirement.serialize_as_vec().map_err(corrupted)?);
+    }
+    Ok(bytes)
+}
+
+fn read_stake_pool(bytes: &[u8]) -> Result<StakePoolData, Error> {
+    let mut codec = Codec::new(bytes);
+    let len = codec.get_be_u32().map_err(corrupted)?;
+    let registration = codec.get_slice(len as usize).map_err(corrupted)?;
+    let registration = PoolRegistration::deserialize_from_slice(&mut Codec::new(registration))
+        .map_err(corrupted)?;
+    let retirement = if codec.has_bytes_left() {
+        Some(PoolRetirement::deserialize(&mut codec).map_err(corrupted)?)
+    } else {
+        None
+    };
+
+    Ok(StakePoolData {
+        registration,
+        retirement,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ExplorerDb;
+    use assert_fs::TempDir;
+    use chain_core::property::Block as _;
+    use jormungandr_automation::jormungandr::Block0ConfigurationBuilder;
+
+    fn block0() -> Block {
+        Block0ConfigurationBuilder::minimal_setup()
+            .build()
+            .to_block()
+    }
+
+    #[test]
+    fn bootstrap_is_reloaded() {
+        let dir = TempDir::new().unwrap();
+        let block0 = block0();
+
+        let storage = StableStorage::open(Some(dir.path())).unwrap();
+        assert!(storage.tip().unwrap().is_none());
+        // sled keeps the directory locked until every handle is dropped
+        drop(ExplorerDb::bootstrap(block0.clone(), storage).unwrap());
+
+        let storage = StableStorage::open(Some(dir.path())).unwrap();
+        assert_eq!(
+            Some((block0.id(), ChainLength::from(0))),
+            storage.tip().unwrap()
+        );
+        assert_eq!(
+            Some(block0.id()),
+            storage.block_id(ChainLength::from(0)).unwrap()
+        );
+        assert_eq!(
+            Some(block0.id()),
+            storage
+                .raw_block(&block0.id())
+                .unwrap()
+                .map(|block| block.id())
+        );
+
+        let db = ExplorerDb::restore(storage.clone()).unwrap();
+        let storage = storage.with_discrimination(db.blockchain_config.discrimination);
+        let block = storage.block(&block0.id()).unwrap().unwrap();
+        assert_eq!(ChainLength::from(0), block.chain_length);
+        assert!(!block.transactions.is_empty());
+        for (tx_id, tx) in block.transactions.iter() {
+            assert_eq!(Some(block0.id()), storage.transaction_block(tx_id).unwrap());
+            for (index, output) in tx.outputs.iter().enumerate() {
+                let stored = storage.output(tx_id, index as u8).unwrap().unwrap();
+                assert_eq!(output.address, stored.address);
+                assert_eq!(output.value, stored.value);
+            }
+        }
+    }
+
+    #[test]
+    fn unstable_blocks_are_not_reloaded() {
+        let dir = TempDir::new().unwrap();
+        let block0 = block0();
+
+        let storage = StableStorage::open(Some(dir.path())).unwrap();
+        storage.put_block(&block0).unwrap();
+        drop(storage);
+
+        let storage = StableStorage::open(Some(dir.path())).unwrap();
+        assert!(storage.raw_block(&block0.id()).unwrap().is_some());
+        storage.remove_unstable_blocks().unwrap();
+        assert!(storage.raw_block(&block0.id()).unwrap().is_none());
+    }
+
+    #[test]
+    fn cleared_storage_is_empty() {
+        let dir = TempDir::new().unwrap();
+        let block0 = block0();
+
+        let storage = StableStorage::open(Some(dir.path())).unwrap();
+        drop(ExplorerDb::bootstrap(block0.clone(), storage.clone()).unwrap());
+        storage.clear().unwrap();
+        drop(storage);
+
+        let storage = StableStorage::open(Some(dir.path())).unwrap();
+        assert!(storage.tip().unwrap().is_none());
+        assert!(storage.raw_block(&block0.id()).unwrap().is_none());
+    }
+}
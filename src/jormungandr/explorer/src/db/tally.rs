@@ -1,5 +1,5 @@
 use super::indexing::ExplorerVoteProposal;
-use crate::db::indexing::{ExplorerVote, ExplorerVoteTally};
+use crate::db::indexing::{ExplorerAddress, ExplorerVote, ExplorerVoteTally};
 use chain_impl_mockchain::{certificate::DecryptedPrivateTallyProposal, stake::StakeControl};
 
 pub fn compute_private_tally(
@@ -12,24 +12,32 @@ pub fn compute_private_tally(
     }
 }
 
+/// `stable_votes` are the votes of the proposal which are in the stable storage,
+/// they are ignored for the voters who voted again since
 pub fn compute_public_tally(
     proposal: &ExplorerVoteProposal,
+    stable_votes: impl Iterator<Item = (ExplorerAddress, ExplorerVote)>,
     stake: &StakeControl,
 ) -> ExplorerVoteTally {
     let mut results = vec![0u64; proposal.options.choice_range().end as usize];
 
-    for (address, vote) in proposal.votes.iter() {
+    let stable_votes = stable_votes.filter(|(address, _)| !proposal.votes.contains_key(address));
+    let votes = proposal
+        .votes
+        .iter()
+        .map(|(address, vote)| (address.clone(), vote.as_ref().clone()))
+        .chain(stable_votes);
+    for (address, vote) in votes {
         if let Some(account_id) = address.to_identifier() {
             if let Some(stake) = stake.by(&account_id) {
-                match vote.as_ref() {
+                match vote {
                     ExplorerVote::Public(choice) => {
                         let index = choice.as_byte() as usize;
                         results[index] = results[index].saturating_add(stake.into());
                     }
-                    ExplorerVote::Private {
-                        proof: _,
-                        encrypted_vote: _,
-                    } => {
+                    ExplorerVote::Private { .. }
+                    | ExplorerVote::Approval { .. }
+                    | ExplorerVote::Ranked { .. } => {
                         unreachable!("internal error: found private vote when computing tally for public proposal")
                     }
                 }
@@ -7,11 +7,12 @@ mod settings;
 use crate::indexer::Indexer;
 use anyhow::Context;
 use chain_core::{packer::Codec, property::Deserialize};
-use chain_impl_mockchain::block::Block;
-use chain_network::grpc::watch::client::{
-    BlockSubscription, Client, SyncMultiverseStream, TipSubscription,
+use chain_impl_mockchain::block::{Block, ChainLength};
+use chain_network::{
+    data::BlockId,
+    grpc::watch::client::{BlockSubscription, Client, SyncMultiverseStream, TipSubscription},
 };
-use db::ExplorerDb;
+use db::{stable_storage::StableStorage, ExplorerDb};
 use futures::stream::StreamExt;
 use futures_util::{future, pin_mut, FutureExt, TryFutureExt};
 use settings::Settings;
@@ -82,8 +83,23 @@ async fn main() -> Result<(), Error> {
             .context("Couldn't establish connection with node")
             .map_err(Error::UnrecoverableError)?;
 
+        let stable_storage = StableStorage::open(settings.storage_dir.as_deref())
+            .map_err(BootstrapError::DbError)?;
+
+        // resume from the last stable block indexed by a previous run, if any
+        let checkpoint = stable_storage
+            .tip()
+            .map_err(BootstrapError::DbError)?
+            .map(|(id, _chain_length)| BlockId::try_from(id.as_bytes()).unwrap());
+
+        let db = checkpoint
+            .as_ref()
+            .map(|_| ExplorerDb::restore(stable_storage.clone()))
+            .transpose()
+            .map_err(BootstrapError::DbError)?;
+
         let sync_stream = client
-            .sync_multiverse(vec![])
+            .sync_multiverse(checkpoint.iter())
             .await
             .context("Failed to establish bootstrap stream")
             .map_err(Error::UnrecoverableError)?;
@@ -105,7 +121,7 @@ async fn main() -> Result<(), Error> {
 
             tokio::spawn(
                 async move {
-                    let db = bootstrap(sync_stream).await?;
+                    let db = bootstrap(sync_stream, db, stable_storage).await?;
 
                     let msg = GlobalState::Ready(Indexer::new(db));
 
@@ -210,10 +226,14 @@ async fn main() -> Result<(), Error> {
     Ok(())
 }
 
-async fn bootstrap(mut sync_stream: SyncMultiverseStream) -> Result<ExplorerDb, Error> {
+async fn bootstrap(
+    mut sync_stream: SyncMultiverseStream,
+    mut db: Option<ExplorerDb>,
+    stable_storage: StableStorage,
+) -> Result<ExplorerDb, Error> {
     tracing::info!("starting bootstrap process");
 
-    let mut db: Option<ExplorerDb> = None;
+    let mut longest_chain_length: Option<ChainLength> = None;
 
     // TODO: technically, blocks with the same length can be applied in parallel
     // but it is simpler to do it serially for now at least
@@ -228,17 +248,39 @@ async fn bootstrap(mut sync_stream: SyncMultiverseStream) -> Result<ExplorerDb,
             .context("failed to decode Block received through bootstrap subscription")
             .map_err(Error::UnrecoverableError)?;
 
-        if let Some(ref db) = db {
-            tracing::trace!(
-                "applying block {:?} {:?}",
-                block.header().hash(),
-                block.header().chain_length()
+        // the node sends the genesis block when it doesn't know the checkpoint,
+        // after it was reset for instance, so the chain is indexed from scratch
+        if db.is_some() && block.header().chain_length() == ChainLength::from(0) {
+            tracing::warn!(
+                "the node doesn't know the last block indexed in the storage, starting a full sync"
             );
+            db = None;
+            longest_chain_length = None;
+            stable_storage.clear().map_err(BootstrapError::DbError)?;
+        }
+
+        if let Some(ref db) = db {
+            let block_id = block.header().hash();
+            let chain_length = block.header().chain_length();
+
+            tracing::trace!("applying block {:?} {:?}", block_id, chain_length);
             db.apply_block(block)
                 .await
                 .map_err(BootstrapError::DbError)?;
+
+            // move the confirmed blocks to the storage as we go, instead of
+            // keeping the whole chain in memory until the first tip event
+            if longest_chain_length.map_or(true, |longest| chain_length > longest) {
+                longest_chain_length = Some(chain_length);
+                db.set_tip(block_id)
+                    .await
+                    .map_err(|e| BootstrapError::DbError(e.into()))?;
+            }
         } else {
-            db = Some(ExplorerDb::bootstrap(block).map_err(BootstrapError::DbError)?)
+            db = Some(
+                ExplorerDb::bootstrap(block, stable_storage.clone())
+                    .map_err(BootstrapError::DbError)?,
+            )
         }
     }
 
@@ -47,6 +47,7 @@ pub struct Settings {
     pub query_complexity_limit: usize,
     pub tls: Option<Tls>,
     pub cors: Option<Cors>,
+    pub storage_dir: Option<PathBuf>,
     pub log_settings: Option<LogSettings>,
 }
 
@@ -89,6 +90,8 @@ impl Settings {
             .or(file.query_complexity_limit)
             .unwrap_or(DEFAULT_QUERY_COMPLEXITY_LIMIT);
 
+        let storage_dir = cmd.storage_dir.clone().or(file.storage_dir);
+
         let log_settings = Some(Self::log_settings(&cmd, &file));
 
         let tls = file.tls;
@@ -102,6 +105,7 @@ impl Settings {
             query_complexity_limit,
             tls,
             cors,
+            storage_dir,
             log_settings,
         })
     }
@@ -158,6 +162,10 @@ struct CommandLine {
     pub query_depth_limit: Option<usize>,
     #[clap(long)]
     pub query_complexity_limit: Option<usize>,
+    /// Directory of the explorer index, to resume indexing after a restart.
+    /// If not configured anywhere, the index is kept in a temporary directory.
+    #[clap(long)]
+    pub storage_dir: Option<PathBuf>,
 
     pub config: Option<PathBuf>,
     /// Set log messages minimum severity. If not configured anywhere, defaults to "info".
@@ -193,6 +201,7 @@ pub struct Config {
     pub address_bech32_prefix: Option<String>,
     pub query_depth_limit: Option<usize>,
     pub query_complexity_limit: Option<usize>,
+    pub storage_dir: Option<PathBuf>,
     pub logs: Option<ConfigLogSettings>,
 }
 
@@ -108,6 +108,12 @@ pub enum Error {
     InvalidJson(#[from] serde_json::Error),
     #[error("private vote plans `committee_public_keys` cannot be empty")]
     InvalidPrivateVotePlanCommitteeKeys,
+    #[error("committee threshold {threshold} exceeds the {members} committee member keys")]
+    InvalidVotePlanCommitteeThreshold { threshold: u8, members: usize },
+    #[error(
+        "committee member keys are not shares of the same election key for threshold {threshold}"
+    )]
+    InconsistentVotePlanCommitteeKeys { threshold: u8 },
     #[error(transparent)]
     VotePlanError(#[from] VotePlanError),
     #[error(transparent)]
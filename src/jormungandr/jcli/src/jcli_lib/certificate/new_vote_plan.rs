@@ -8,7 +8,7 @@ use chain_impl_mockchain::{
 };
 use clap::Parser;
 use jormungandr_lib::interfaces::VotePlan;
-use std::path::PathBuf;
+use std::{num::NonZeroU8, path::PathBuf};
 
 /// create a vote plan certificate
 ///
@@ -23,26 +23,54 @@ pub struct VotePlanRegistration {
     /// write the output to the given file or print it to the standard output if not defined
     #[clap(long = "output")]
     pub output: Option<PathBuf>,
+
+    /// number of committee members needed to decrypt the tally, when the committee
+    /// member keys are the public shares of a distributed key generation. Overrides
+    /// the `committee_threshold` of the configuration
+    #[clap(long = "committee-threshold")]
+    pub committee_threshold: Option<NonZeroU8>,
 }
 
 fn validate_voteplan(voteplan: &certificate::VotePlan) -> Result<(), Error> {
     // if voteplan is private committee member keys should be filled
     match voteplan.payload_type() {
         PayloadType::Public => {}
-        PayloadType::Private => {
+        PayloadType::Private | PayloadType::Approval | PayloadType::Ranked => {
             if voteplan.committee_public_keys().is_empty() {
                 return Err(Error::InvalidPrivateVotePlanCommitteeKeys);
             }
         }
     }
+    if let Some(threshold) = voteplan.committee_threshold() {
+        let members = voteplan.committee_public_keys().len();
+        if threshold.get() as usize > members {
+            return Err(Error::InvalidVotePlanCommitteeThreshold {
+                threshold: threshold.get(),
+                members,
+            });
+        }
+        if chain_vote::ElectionPublicKey::from_public_shares(
+            voteplan.committee_public_keys(),
+            threshold.get() as usize,
+        )
+        .is_none()
+        {
+            return Err(Error::InconsistentVotePlanCommitteeKeys {
+                threshold: threshold.get(),
+            });
+        }
+    }
     Ok(())
 }
 
 impl VotePlanRegistration {
     pub fn exec(self) -> Result<(), Error> {
         let configuration = io::open_file_read(&self.input)?;
-        let vpc: VotePlan =
+        let mut vpc: VotePlan =
             serde_yaml::from_reader(configuration).map_err(Error::VotePlanConfig)?;
+        if let Some(threshold) = self.committee_threshold {
+            vpc.committee_threshold = Some(threshold);
+        }
         let vpc: certificate::VotePlan = vpc.into();
         validate_voteplan(&vpc)?;
         let cert = Certificate::VotePlan(vpc);
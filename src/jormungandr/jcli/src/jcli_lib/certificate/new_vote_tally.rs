@@ -92,7 +92,9 @@ impl PrivateTally {
             .zip(shares)
             .map(|(prop, shares)| match prop.tally {
                 Tally::Private {
-                    state: PrivateTallyState::Decrypted { result, .. },
+                    state:
+                        PrivateTallyState::Decrypted { result }
+                        | PrivateTallyState::DecryptedMultiSelection { result, .. },
                 } => Ok(DecryptedPrivateTallyProposal {
                     decrypt_shares: shares.into_boxed_slice(),
                     tally_result: result.results().into_boxed_slice(),
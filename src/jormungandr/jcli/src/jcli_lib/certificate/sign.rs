@@ -156,7 +156,9 @@ pub(crate) fn committee_vote_tally_sign(
 
     let proof = match vote_tally.tally_type() {
         PayloadType::Public => TallyProof::Public { id, signature },
-        PayloadType::Private => TallyProof::Private { id, signature },
+        PayloadType::Private | PayloadType::Approval | PayloadType::Ranked => {
+            TallyProof::Private { id, signature }
+        }
     };
     Ok(SignedCertificate::VoteTally(vote_tally, proof))
 }
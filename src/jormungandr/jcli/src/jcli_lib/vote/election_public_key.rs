@@ -15,14 +15,30 @@ pub struct ElectionPublicKey {
     )]
     member_keys: Vec<chain_vote::committee::MemberPublicKey>,
 
+    /// Number of committee members required to decrypt the tally, when the
+    /// keys are the public shares produced by a distributed key generation
+    #[clap(long = "threshold")]
+    threshold: Option<usize>,
+
     #[clap(flatten)]
     output_file: OutputFile,
 }
 
 impl ElectionPublicKey {
     pub fn exec(&self) -> Result<(), Error> {
-        let election_public_key =
-            chain_vote::ElectionPublicKey::from_participants(&self.member_keys);
+        let election_public_key = match self.threshold {
+            Some(threshold) => {
+                if threshold == 0 || threshold > self.member_keys.len() {
+                    return Err(Error::InvalidThreshold {
+                        threshold,
+                        committee_members: self.member_keys.len(),
+                    });
+                }
+                chain_vote::ElectionPublicKey::from_public_shares(&self.member_keys, threshold)
+                    .ok_or(Error::InconsistentPublicShares { threshold })?
+            }
+            None => chain_vote::ElectionPublicKey::from_participants(&self.member_keys),
+        };
 
         let mut output = self.output_file.open()?;
         writeln!(output, "{}", election_public_key.to_bech32_str()).map_err(Error::from)
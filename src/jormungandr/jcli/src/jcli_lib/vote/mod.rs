@@ -43,6 +43,10 @@ pub enum Error {
         threshold: usize,
         committee_members: usize,
     },
+    #[error(
+        "committee member keys are not shares of the same election key for threshold {threshold}"
+    )]
+    InconsistentPublicShares { threshold: usize },
     #[error("invalid committee member index")]
     InvalidCommitteMemberIndex,
     #[error("failed to read encrypted tally bytes")]
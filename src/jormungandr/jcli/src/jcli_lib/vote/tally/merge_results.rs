@@ -17,6 +17,8 @@ pub enum Error {
     VotePlanEncrypted,
     #[error("voteplans have different privacy type")]
     PrivacyMismatch,
+    #[error("voteplans have different ballot types")]
+    BallotMismatch,
 }
 
 #[derive(Parser)]
@@ -125,6 +127,51 @@ fn merge_voteplans(voteplans: Vec<VotePlanStatus>) -> Result<Vec<MergedVotePlan>
                                 result: result1.merge(result2),
                             },
                         },
+                        (
+                            Tally::Private {
+                                state:
+                                    PrivateTallyState::DecryptedMultiSelection {
+                                        result: result1,
+                                        max_selections: max_selections1,
+                                        ranked: ranked1,
+                                        approval_participation: participation1,
+                                    },
+                            },
+                            Tally::Private {
+                                state:
+                                    PrivateTallyState::DecryptedMultiSelection {
+                                        result: result2,
+                                        max_selections: max_selections2,
+                                        ranked: ranked2,
+                                        approval_participation: participation2,
+                                    },
+                            },
+                        ) if (max_selections1, ranked1, participation1.is_some())
+                            == (max_selections2, ranked2, participation2.is_some()) =>
+                        {
+                            Tally::Private {
+                                state: PrivateTallyState::DecryptedMultiSelection {
+                                    result: result1.merge(result2),
+                                    max_selections: *max_selections1,
+                                    ranked: *ranked1,
+                                    approval_participation: participation1
+                                        .zip(*participation2)
+                                        .map(|(l, r)| l + r),
+                                },
+                            }
+                        }
+                        (
+                            Tally::Private {
+                                state:
+                                    PrivateTallyState::Decrypted { .. }
+                                    | PrivateTallyState::DecryptedMultiSelection { .. },
+                            },
+                            Tally::Private {
+                                state:
+                                    PrivateTallyState::Decrypted { .. }
+                                    | PrivateTallyState::DecryptedMultiSelection { .. },
+                            },
+                        ) => return Err(Error::BallotMismatch),
                         (Tally::Public { result: _ }, Tally::Private { state: _ })
                         | (Tally::Private { state: _ }, Tally::Public { result: _ }) => {
                             return Err(Error::PrivacyMismatch);
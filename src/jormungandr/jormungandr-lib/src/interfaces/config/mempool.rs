@@ -12,6 +12,24 @@ pub struct PersistentLog {
     pub dir: PathBuf,
 }
 
+/// The order in which the fragments of the mempool are selected for a block.
+/// When the mempool is full, the fragments are evicted in the same order
+/// from the other end: the fragments which would be selected last go first,
+/// except with `oldest_first` which evicts the oldest fragments.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FragmentSelectionStrategy {
+    /// the fragments in the order they were received
+    #[default]
+    OldestFirst,
+    /// the fragments paying the highest fee first
+    HighestFeeFirst,
+    /// one fragment of each account in turn
+    AccountFairness,
+    /// the fragments with the highest priority type first, see `FragmentPriority`
+    TypePriority,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct Mempool {
@@ -24,6 +42,9 @@ pub struct Mempool {
     /// path to the persistent log of all incoming fragments
     #[serde(default)]
     pub persistent_log: Option<PersistentLog>,
+    /// the order of selection, and eviction, of the fragments
+    #[serde(default)]
+    pub selection_strategy: FragmentSelectionStrategy,
 }
 
 impl Default for PoolMaxEntries {
@@ -4,9 +4,11 @@ mod node;
 mod secret;
 
 pub use log::{Log, LogEntry, LogOutput};
-pub use mempool::{LogMaxEntries, Mempool, PersistentLog, PoolMaxEntries};
+pub use mempool::{
+    FragmentSelectionStrategy, LogMaxEntries, Mempool, PersistentLog, PoolMaxEntries,
+};
 pub use node::{
     Bootstrap, Connection, Cors, CorsOrigin, JRpc, LayersConfig, NodeConfig, NodeId, P2p, Policy,
-    PreferredListConfig, Rest, Tls, TopicsOfInterest, TrustedPeer,
+    PreferredListConfig, Rest, RestAdmin, Tls, TopicsOfInterest, TrustedPeer,
 };
 pub use secret::{Bft, GenesisPraos, NodeSecret};
@@ -20,6 +20,21 @@ pub struct Rest {
     /// Enables CORS if provided
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cors: Option<Cors>,
+    /// Serves the node management endpoints on a separate listener if provided,
+    /// they are then no longer served on `listen`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin: Option<RestAdmin>,
+}
+
+/// Listener of the node management endpoints, such as shutdown, settings,
+/// leaders and network statistics
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RestAdmin {
+    pub listen: SocketAddr,
+    /// Enables TLS and disables plain HTTP if provided
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<Tls>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -34,6 +49,11 @@ pub struct Tls {
     pub cert_file: String,
     /// Path to server private key file, must be PKCS8 with single PEM-encoded, unencrypted key
     pub priv_key_file: String,
+    /// Path to a PEM-encoded file with the certificates of the authorities trusted to sign
+    /// client certificates. Enables client authentication if provided, the clients must then
+    /// present a certificate signed by one of them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ca_file: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
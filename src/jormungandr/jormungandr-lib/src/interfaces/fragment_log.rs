@@ -33,6 +33,42 @@ pub enum FragmentStatus {
     InABlock { date: BlockDate, block: Hash },
 }
 
+/// the type priority of a fragment, from the highest to the lowest
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FragmentPriority {
+    /// vote plans, tallies and update proposals or votes
+    Governance,
+    /// stake pool and delegation certificates, token minting and evm mappings
+    Certificate,
+    /// transactions and evm transactions
+    Transaction,
+    /// votes cast
+    Vote,
+}
+
+/// why a fragment was selected for a block by the mempool
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "strategy")]
+pub enum FragmentSelectionReason {
+    /// the fragment was the oldest one in the pool
+    OldestFirst,
+    /// the fragment paid the highest fee in the pool
+    HighestFeeFirst { fee: u64 },
+    /// it was the turn of the account of the fragment, `turn` is the number
+    /// of fragments of this account selected in the block so far, this one included
+    AccountFairness { turn: u32 },
+    /// the fragment had the highest type priority in the pool
+    TypePriority { priority: FragmentPriority },
+}
+
+/// a change of the status of a fragment, as streamed by the node
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FragmentStatusUpdate {
+    pub fragment_id: Hash,
+    pub status: FragmentStatus,
+}
+
 /// the log associated to a given fragment
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct FragmentLog {
@@ -41,6 +77,8 @@ pub struct FragmentLog {
     received_at: SystemTime,
     last_updated_at: SystemTime,
     status: FragmentStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    selection_reason: Option<FragmentSelectionReason>,
 }
 
 impl FragmentStatus {
@@ -70,6 +108,7 @@ impl FragmentLog {
             received_at: SystemTime::now(),
             last_updated_at: SystemTime::now(),
             status: FragmentStatus::Pending,
+            selection_reason: None,
         }
     }
 
@@ -104,6 +143,13 @@ impl FragmentLog {
         true
     }
 
+    /// Record why the fragment was selected for a block
+    #[inline]
+    pub fn set_selection_reason(&mut self, reason: FragmentSelectionReason) {
+        self.selection_reason = Some(reason);
+        self.last_updated_at = SystemTime::now();
+    }
+
     #[inline]
     pub fn fragment_id(&self) -> &Hash {
         &self.fragment_id
@@ -128,4 +174,10 @@ impl FragmentLog {
     pub fn status(&self) -> &FragmentStatus {
         &self.status
     }
+
+    /// why the fragment was selected for a block, if it was
+    #[inline]
+    pub fn selection_reason(&self) -> Option<&FragmentSelectionReason> {
+        self.selection_reason.as_ref()
+    }
 }
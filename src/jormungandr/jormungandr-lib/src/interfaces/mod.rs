@@ -55,7 +55,10 @@ pub use self::{
     },
     evm_transaction::EvmTransaction,
     fragment::FragmentDef,
-    fragment_log::{FragmentLog, FragmentOrigin, FragmentStatus},
+    fragment_log::{
+        FragmentLog, FragmentOrigin, FragmentPriority, FragmentSelectionReason, FragmentStatus,
+        FragmentStatusUpdate,
+    },
     fragment_log_persistent::{
         load_persistent_fragments_logs_from_folder_path,
         read_persistent_fragment_logs_from_file_path,
@@ -14,6 +14,7 @@ use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     convert::TryInto,
     fmt,
+    num::NonZeroU8,
     ops::Range,
     str::{self, FromStr},
 };
@@ -27,10 +28,12 @@ pub struct VotePrivacy(#[serde(with = "PayloadTypeDef")] pub vote::PayloadType);
 enum PayloadTypeDef {
     Public,
     Private,
+    Approval,
+    Ranked,
 }
 
 #[derive(Debug, thiserror::Error)]
-#[error("Invalid vote privacy, expected \"public\" or \"private\".")]
+#[error("Invalid vote privacy, expected \"public\", \"private\", \"approval\" or \"ranked\".")]
 pub struct VotePrivacyFromStrError;
 
 impl FromStr for VotePrivacy {
@@ -39,6 +42,8 @@ impl FromStr for VotePrivacy {
         match s {
             "public" => Ok(VotePrivacy(vote::PayloadType::Public)),
             "private" => Ok(VotePrivacy(vote::PayloadType::Private)),
+            "approval" => Ok(VotePrivacy(vote::PayloadType::Approval)),
+            "ranked" => Ok(VotePrivacy(vote::PayloadType::Ranked)),
             _ => Err(VotePrivacyFromStrError),
         }
     }
@@ -49,6 +54,8 @@ impl fmt::Display for VotePrivacy {
         let s = match self.0 {
             vote::PayloadType::Public => "public",
             vote::PayloadType::Private => "private",
+            vote::PayloadType::Approval => "approval",
+            vote::PayloadType::Ranked => "ranked",
         };
         s.fmt(f)
     }
@@ -157,6 +164,10 @@ pub struct VotePlan {
     pub proposals: Proposals,
     #[serde(with = "serde_committee_member_public_keys", default = "Vec::new")]
     pub committee_member_public_keys: Vec<chain_vote::MemberPublicKey>,
+    /// number of committee members needed to decrypt the tally, when the committee
+    /// member public keys are the public shares of a distributed key generation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub committee_threshold: Option<NonZeroU8>,
     pub voting_token: TokenIdentifier,
 }
 
@@ -214,6 +225,7 @@ impl From<certificate::VotePlan> for VotePlan {
             proposals: vp.proposals().clone(),
             payload_type: vp.payload_type().into(),
             committee_member_public_keys: vp.committee_public_keys().to_vec(),
+            committee_threshold: vp.committee_threshold(),
             voting_token: vp.voting_token().clone().into(),
         }
     }
@@ -221,7 +233,7 @@ impl From<certificate::VotePlan> for VotePlan {
 
 impl From<VotePlan> for certificate::VotePlan {
     fn from(vpd: VotePlan) -> Self {
-        certificate::VotePlan::new(
+        let vote_plan = certificate::VotePlan::new(
             vpd.vote_start.into(),
             vpd.vote_end.into(),
             vpd.committee_end.into(),
@@ -229,7 +241,11 @@ impl From<VotePlan> for certificate::VotePlan {
             vpd.payload_type.into(),
             vpd.committee_member_public_keys,
             vpd.voting_token.into(),
-        )
+        );
+        match vpd.committee_threshold {
+            Some(threshold) => vote_plan.with_committee_threshold(threshold),
+            None => vote_plan,
+        }
     }
 }
 
@@ -354,7 +370,11 @@ pub mod serde_external_proposal_id {
 
 pub mod serde_choices {
     use super::*;
-    use serde::{Deserializer, Serialize, Serializer};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// In human readable formats, the single selection options are a plain number of
+    /// choices and the others a map with the maximum number of selections. The other
+    /// formats are not self describing, they always hold both numbers.
     pub fn deserialize<'de, D>(deserializer: D) -> Result<Options, D::Error>
     where
         D: Deserializer<'de>,
@@ -365,7 +385,9 @@ pub mod serde_choices {
             type Value = Options;
 
             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("a number of options from 0 to 255")
+                formatter.write_str(
+                    "a number of options from 0 to 255, or the number of options and the maximum number of selections",
+                )
             }
 
             fn visit_u64<E>(self, value: u64) -> Result<Options, E>
@@ -377,17 +399,53 @@ pub mod serde_choices {
                 }
                 Options::new_length(value as u8).map_err(serde::de::Error::custom)
             }
+
+            fn visit_map<A>(self, map: A) -> Result<Options, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let MultiSelection {
+                    choices,
+                    max_selections,
+                } = MultiSelection::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+                Options::new_multi_selection(choices, max_selections)
+                    .map_err(serde::de::Error::custom)
+            }
         }
 
-        deserializer.deserialize_u64(OptionsVisitor)
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(OptionsVisitor)
+        } else {
+            let (choices, max_selections) = <(u8, u8)>::deserialize(deserializer)?;
+            Options::new_multi_selection(choices, max_selections).map_err(serde::de::Error::custom)
+        }
     }
 
     pub fn serialize<S>(options: &Options, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let v = options.choice_range().end as u64;
-        v.serialize(serializer)
+        let choices = options.choice_range().end;
+        if !serializer.is_human_readable() {
+            return (choices, options.max_selections()).serialize(serializer);
+        }
+        match options.max_selections() {
+            1 => (choices as u64).serialize(serializer),
+            max_selections => MultiSelection {
+                choices,
+                max_selections,
+            }
+            .serialize(serializer),
+        }
+    }
+
+    /// options of the ballots allowing more than one selection, the
+    /// single selection options are kept as a plain number of choices
+    #[derive(Deserialize, Serialize)]
+    #[serde(deny_unknown_fields)]
+    struct MultiSelection {
+        choices: u8,
+        max_selections: u8,
     }
 }
 
@@ -576,8 +634,25 @@ impl From<EncryptedTally> for chain_vote::EncryptedTally {
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PrivateTallyState {
-    Encrypted { encrypted_tally: EncryptedTally },
-    Decrypted { result: TallyResult },
+    Encrypted {
+        encrypted_tally: EncryptedTally,
+    },
+    Decrypted {
+        result: TallyResult,
+    },
+    /// the decrypted results of the approval and ranked votes, which select
+    /// several choices
+    DecryptedMultiSelection {
+        result: TallyResult,
+        /// maximum number of choices selected by a vote
+        max_selections: u8,
+        /// whether the results hold the results of every rank one after the other
+        #[serde(default)]
+        ranked: bool,
+        /// total weight of the approval votes, which approve any number of choices
+        #[serde(default)]
+        approval_participation: Option<u64>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -591,6 +666,18 @@ pub enum VotePayload {
         #[serde(with = "serde_base64_bytes")]
         proof: Vec<u8>,
     },
+    Approval {
+        #[serde(with = "serde_base64_bytes")]
+        encrypted_vote: Vec<u8>,
+        #[serde(with = "serde_base64_bytes")]
+        proof: Vec<u8>,
+    },
+    Ranked {
+        #[serde(with = "serde_base64_bytes")]
+        encrypted_vote: Vec<u8>,
+        #[serde(with = "serde_base64_bytes")]
+        proof: Vec<u8>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -615,6 +702,20 @@ impl From<vote::Payload> for VotePayload {
                 encrypted_vote: encrypted_vote.serialize().into(),
                 proof: proof.serialize().into(),
             },
+            vote::Payload::Approval {
+                encrypted_vote,
+                proof,
+            } => Self::Approval {
+                encrypted_vote: encrypted_vote.serialize().into(),
+                proof: proof.serialize().into(),
+            },
+            vote::Payload::Ranked {
+                encrypted_vote,
+                proof,
+            } => Self::Ranked {
+                encrypted_vote: encrypted_vote.serialize().into(),
+                proof: proof.serialize().into(),
+            },
         }
     }
 }
@@ -623,7 +724,9 @@ impl VotePayload {
     pub fn choice(&self) -> Option<u8> {
         match self {
             VotePayload::Public { choice } => Some(*choice),
-            VotePayload::Private { .. } => None,
+            VotePayload::Private { .. }
+            | VotePayload::Approval { .. }
+            | VotePayload::Ranked { .. } => None,
         }
     }
 }
@@ -660,6 +763,46 @@ impl From<TallyResult> for vote::TallyResult {
     }
 }
 
+fn decrypted_state(result: vote::TallyResult) -> PrivateTallyState {
+    if !result.is_ranked() && !result.is_approval() {
+        return PrivateTallyState::Decrypted {
+            result: result.into(),
+        };
+    }
+
+    PrivateTallyState::DecryptedMultiSelection {
+        max_selections: result.options().max_selections(),
+        ranked: result.is_ranked(),
+        approval_participation: result.is_approval().then(|| result.participation().into()),
+        result: result.into(),
+    }
+}
+
+fn multi_selection_result(
+    result: TallyResult,
+    max_selections: u8,
+    ranked: bool,
+    approval_participation: Option<u64>,
+) -> vote::TallyResult {
+    let choices = result.options.end - result.options.start;
+    let options = Options::new_multi_selection(choices, max_selections).unwrap();
+    let mut tally_result = match (ranked, approval_participation) {
+        (true, _) => vote::TallyResult::new_ranked(options),
+        (false, Some(participation)) => vote::TallyResult::new_approval(options, participation),
+        (false, None) => vote::TallyResult::new(options),
+    };
+    // the ranked results hold the results of every rank one after the other
+    let num_choices = choices.max(1) as usize;
+    for (idx, value) in result.results().iter().enumerate() {
+        let weight: Weight = (*value).into();
+        let (rank, choice) = (idx / num_choices, idx % num_choices);
+        tally_result
+            .add_ranked_vote(rank as u8, Choice::new(choice as u8), weight)
+            .unwrap()
+    }
+    tally_result
+}
+
 impl From<vote::Tally> for Tally {
     fn from(this: vote::Tally) -> Self {
         match this {
@@ -673,9 +816,7 @@ impl From<vote::Tally> for Tally {
                             encrypted_tally: EncryptedTally(encrypted_tally.to_bytes()),
                         }
                     }
-                    vote::PrivateTallyState::Decrypted { result } => PrivateTallyState::Decrypted {
-                        result: result.into(),
-                    },
+                    vote::PrivateTallyState::Decrypted { result } => decrypted_state(result),
                 },
             },
         }
@@ -698,6 +839,19 @@ impl From<Tally> for vote::Tally {
                     PrivateTallyState::Decrypted { result } => vote::PrivateTallyState::Decrypted {
                         result: result.into(),
                     },
+                    PrivateTallyState::DecryptedMultiSelection {
+                        result,
+                        max_selections,
+                        ranked,
+                        approval_participation,
+                    } => vote::PrivateTallyState::Decrypted {
+                        result: multi_selection_result(
+                            result,
+                            max_selections,
+                            ranked,
+                            approval_participation,
+                        ),
+                    },
                 },
             },
         }
@@ -827,4 +981,155 @@ mod test {
         let a = serde_json::to_string(&vote_plan).unwrap();
         assert_eq!(vote_plan, serde_json::from_str(&a).unwrap());
     }
+
+    #[test]
+    fn vote_plan_committee_threshold_round_trip() {
+        let mut rng = rand_chacha::ChaChaRng::from_entropy();
+        let crs = chain_vote::Crs::from_hash("Dummy shared string".as_bytes());
+        let comm_keys: Vec<_> = (0..3)
+            .map(|_| chain_vote::MemberCommunicationKey::new(&mut rng).to_public())
+            .collect();
+        let member_keys = (0..3)
+            .map(|i| chain_vote::MemberState::new(&mut rng, 2, &crs, &comm_keys, i).public_key())
+            .collect();
+        let mut proposals = Proposals::new();
+        let _ = proposals.push(Proposal::new(
+            ExternalProposalId::from([0; 32]),
+            Options::new_length(1).unwrap(),
+            VoteAction::OffChain,
+        ));
+        let voting_token = identifier::TokenIdentifier::from_str(
+            "00000000000000000000000000000000000000000000000000000000.00000000",
+        )
+        .unwrap();
+        let vote_plan = certificate::VotePlan::new(
+            "42.12".parse::<BlockDate>().unwrap(),
+            "42.13".parse::<BlockDate>().unwrap(),
+            "42.14".parse::<BlockDate>().unwrap(),
+            proposals,
+            vote::PayloadType::Private,
+            member_keys,
+            voting_token,
+        )
+        .with_committee_threshold(NonZeroU8::new(2).unwrap());
+
+        let json = serde_json::to_string(&VotePlan::from(vote_plan.clone())).unwrap();
+        let restored: certificate::VotePlan =
+            serde_json::from_str::<VotePlan>(&json).unwrap().into();
+
+        assert_eq!(
+            restored.committee_threshold(),
+            vote_plan.committee_threshold()
+        );
+        assert_eq!(restored.to_id(), vote_plan.to_id());
+    }
+
+    #[test]
+    fn test_serde_multi_selection_options() {
+        let single = Options::new_length(4).unwrap();
+        let mut serializer = serde_json::Serializer::new(Vec::new());
+        serde_choices::serialize(&single, &mut serializer).unwrap();
+        assert_eq!(serializer.into_inner(), b"4");
+
+        let multi = Options::new_multi_selection(4, 2).unwrap();
+        let mut serializer = serde_json::Serializer::new(Vec::new());
+        serde_choices::serialize(&multi, &mut serializer).unwrap();
+        let json = String::from_utf8(serializer.into_inner()).unwrap();
+        let mut json_deserializer = serde_json::Deserializer::from_str(&json);
+        assert_eq!(
+            serde_choices::deserialize(&mut json_deserializer).unwrap(),
+            multi
+        );
+
+        let mut json_deserializer =
+            serde_json::Deserializer::from_str(r#"{"choices":2,"max_selections":3}"#);
+        assert!(serde_choices::deserialize(&mut json_deserializer).is_err());
+
+        #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+        struct Wrapper(#[serde(with = "serde_choices")] Options);
+        for options in [single, multi] {
+            let bytes = bincode::serialize(&Wrapper(options.clone())).unwrap();
+            let decoded: Wrapper = bincode::deserialize(&bytes).unwrap();
+            assert_eq!(decoded.0, options);
+        }
+    }
+
+    fn decrypted_tally_round_trip(result: vote::TallyResult) -> Tally {
+        let tally = Tally::from(vote::Tally::Private {
+            state: vote::PrivateTallyState::Decrypted {
+                result: result.clone(),
+            },
+        });
+
+        let json = serde_json::to_string(&tally).unwrap();
+        let decoded: Tally = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, tally);
+        match vote::Tally::from(decoded) {
+            vote::Tally::Private {
+                state: vote::PrivateTallyState::Decrypted { result: converted },
+            } => {
+                assert_eq!(converted, result);
+                assert_eq!(converted.participation(), result.participation());
+            }
+            _ => panic!("expected a decrypted private tally"),
+        }
+        tally
+    }
+
+    #[test]
+    fn test_single_selection_tally_result_conversion() {
+        let mut result = vote::TallyResult::new(Options::new_length(3).unwrap());
+        result.add_vote(Choice::new(1), Weight::from(5)).unwrap();
+
+        let tally = decrypted_tally_round_trip(result);
+        assert!(matches!(
+            tally,
+            Tally::Private {
+                state: PrivateTallyState::Decrypted { .. }
+            }
+        ));
+    }
+
+    #[test]
+    fn test_ranked_tally_result_conversion() {
+        let options = Options::new_multi_selection(3, 2).unwrap();
+        let mut result = vote::TallyResult::new_ranked(options);
+        result
+            .add_ranked_vote(1, Choice::new(2), Weight::from(5))
+            .unwrap();
+
+        let tally = decrypted_tally_round_trip(result);
+        assert!(matches!(
+            tally,
+            Tally::Private {
+                state: PrivateTallyState::DecryptedMultiSelection {
+                    max_selections: 2,
+                    ranked: true,
+                    approval_participation: None,
+                    ..
+                }
+            }
+        ));
+    }
+
+    #[test]
+    fn test_approval_tally_result_conversion() {
+        let options = Options::new_multi_selection(3, 3).unwrap();
+        let mut result = vote::TallyResult::new_approval(options, 5);
+        result.add_vote(Choice::new(0), Weight::from(5)).unwrap();
+        result.add_vote(Choice::new(2), Weight::from(5)).unwrap();
+
+        let tally = decrypted_tally_round_trip(result);
+        assert!(matches!(
+            tally,
+            Tally::Private {
+                state: PrivateTallyState::DecryptedMultiSelection {
+                    max_selections: 3,
+                    ranked: false,
+                    approval_participation: Some(5),
+                    ..
+                }
+            }
+        ));
+    }
 }
@@ -50,7 +50,10 @@ See Internal documentation for more details: doc/internal_design.md
 */
 #![allow(clippy::large_enum_variant)]
 #![allow(clippy::result_large_err)]
-use super::reference_cache::RefCache;
+use super::{
+    ledger_snapshots::{self, LedgerSnapshot, LedgerSnapshots},
+    reference_cache::RefCache,
+};
 use crate::{
     blockcfg::{
         Block, Block0Error, BlockDate, ChainLength, Epoch, EpochRewardsInfo, Header, HeaderDesc,
@@ -106,6 +109,12 @@ pub enum Error {
 
     #[error("block cannot be applied on top of the previous block's ledger state")]
     CannotApplyBlock(#[source] ledger::Error),
+
+    #[error(transparent)]
+    LedgerSnapshot(#[from] ledger_snapshots::Error),
+
+    #[error("block `{0}` of the ledger snapshot is not in the main branch")]
+    LedgerSnapshotNotInBranch(HeaderHash),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -177,6 +186,8 @@ pub struct Blockchain {
     block0: HeaderHash,
 
     rewards_report_all: bool,
+
+    ledger_snapshots: Option<LedgerSnapshots>,
 }
 
 pub enum PreCheckedHeader {
@@ -269,6 +280,7 @@ impl Blockchain {
         storage: Storage,
         cache_capacity: usize,
         rewards_report_all: bool,
+        ledger_snapshots: Option<LedgerSnapshots>,
     ) -> Self {
         Blockchain {
             ref_cache: RefCache::new(cache_capacity),
@@ -276,6 +288,7 @@ impl Blockchain {
             storage,
             block0,
             rewards_report_all,
+            ledger_snapshots,
         }
     }
 
@@ -305,6 +318,45 @@ impl Blockchain {
         Ok(())
     }
 
+    /// snapshot the ledger at the end of the last epoch, once it is deeper
+    /// than the epoch stability depth from the tip
+    pub async fn snapshot_ledger(&self, tip: Arc<Ref>) -> Result<()> {
+        let ledger_snapshots = match &self.ledger_snapshots {
+            Some(ledger_snapshots) => ledger_snapshots.clone(),
+            None => return Ok(()),
+        };
+        let epoch_end = match tip.last_ref_previous_epoch() {
+            Some(epoch_end) => Arc::clone(epoch_end),
+            None => return Ok(()),
+        };
+        let depth = tip.ledger().settings().epoch_stability_depth;
+        let is_stable = tip
+            .chain_length()
+            .nth_ancestor(depth)
+            .map_or(false, |stable| epoch_end.chain_length() <= stable);
+        if !is_stable || ledger_snapshots.contains(&epoch_end) {
+            return Ok(());
+        }
+
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            if let Some(snapshot) = LedgerSnapshot::from_ref(&epoch_end) {
+                ledger_snapshots.write(&snapshot)?;
+                tracing::info!(
+                    "ledger snapshot written at {}",
+                    epoch_end.header().description()
+                );
+            } else {
+                tracing::debug!(
+                    "the states needed to snapshot the ledger at {} are not available",
+                    epoch_end.header().description()
+                );
+            }
+            Ok(())
+        })
+        .await
+        .expect("ledger snapshot task panicked")
+    }
+
     /// create and store a reference of this leader to the new
     #[allow(clippy::too_many_arguments)]
     async fn create_and_store_reference(
@@ -614,17 +666,7 @@ impl Blockchain {
         let block0_id = block0.header().hash();
         let block0_date = block0.header().block_date();
 
-        let time_frame = {
-            use crate::blockcfg::Block0DataSource as _;
-
-            let start_time = block0.start_time().map_err(Error::Block0)?;
-            let slot_duration = block0.slot_duration().map_err(Error::Block0)?;
-
-            TimeFrame::new(
-                chain_time::Timeline::new(start_time),
-                chain_time::SlotDuration::from_secs(slot_duration.as_secs() as u32),
-            )
-        };
+        let time_frame = block0_time_frame(block0)?;
 
         // we lift the creation of the ledger in the future type
         // this allow chaining of the operation and lifting the error handling
@@ -713,6 +755,11 @@ impl Blockchain {
     /// from the block0 to the `Head` of the storage (the last known block which
     /// made consensus).
     ///
+    /// If a ledger snapshot of the main branch is available, the states are
+    /// restored from the newest one and only the following blocks are applied.
+    /// Otherwise, or if the blocks cannot be applied on top of the snapshot,
+    /// all the blocks are applied from the block0.
+    ///
     /// The Future will returns a branch pointing to the `Head`.
     ///
     /// # Errors
@@ -738,7 +785,30 @@ impl Blockchain {
             return Err(Error::NoTag(MAIN_BRANCH_TAG.to_owned()));
         };
 
-        let mut last_ref = self.apply_block0(&block0).await?.get_ref();
+        if let Some(snapshot_ref) = self.restore_ledger_snapshot(&block0, head_hash).await {
+            match self
+                .apply_blocks_from_storage(snapshot_ref, head_hash)
+                .await
+            {
+                Ok(tip) => return Ok(tip),
+                Err(error) => tracing::warn!(
+                    reason = %error,
+                    "cannot resume from the ledger snapshot, loading from the block0"
+                ),
+            }
+        }
+
+        let block0_ref = self.apply_block0(&block0).await?.get_ref();
+        self.apply_blocks_from_storage(block0_ref, head_hash).await
+    }
+
+    /// apply the blocks of the storage following `from_ref` up to `head_hash`
+    async fn apply_blocks_from_storage(
+        &self,
+        from_ref: Arc<Ref>,
+        head_hash: HeaderHash,
+    ) -> Result<Tip> {
+        let mut last_ref = from_ref;
         let mut reporter = StreamReporter::new(|stream_info| {
             let elapsed = stream_info
                 .last_reported
@@ -754,7 +824,7 @@ impl Blockchain {
 
         let mut block_stream = self
             .storage
-            .stream_from_to(block0_id, head_hash)
+            .stream_from_to(last_ref.hash(), head_hash)
             .map(Box::pin)?;
 
         while let Some(block) = block_stream.next().await.transpose()? {
@@ -768,11 +838,131 @@ impl Blockchain {
         Ok(Tip::new(Branch::new(last_ref)))
     }
 
+    /// restore the states from the newest valid ledger snapshot of the main branch
+    async fn restore_ledger_snapshot(
+        &self,
+        block0: &Block,
+        head_hash: HeaderHash,
+    ) -> Option<Arc<Ref>> {
+        let ledger_snapshots = self.ledger_snapshots.as_ref()?;
+        let snapshots = match ledger_snapshots.list() {
+            Ok(snapshots) => snapshots,
+            Err(error) => {
+                tracing::warn!(reason = %error, "cannot list the ledger snapshots");
+                return None;
+            }
+        };
+
+        for (_, path) in snapshots {
+            let restored = match LedgerSnapshots::read(&path) {
+                Ok(snapshot) => {
+                    self.restore_ledger_snapshot_refs(block0, head_hash, snapshot)
+                        .await
+                }
+                Err(error) => Err(error.into()),
+            };
+            match restored {
+                Ok(snapshot_ref) => {
+                    tracing::info!(
+                        "resuming from the ledger snapshot at {}",
+                        snapshot_ref.header().description()
+                    );
+                    return Some(snapshot_ref);
+                }
+                Err(error) => tracing::warn!(
+                    reason = %error,
+                    "skipping the ledger snapshot {}",
+                    path.display()
+                ),
+            }
+        }
+        None
+    }
+
+    async fn restore_ledger_snapshot_refs(
+        &self,
+        block0: &Block,
+        head_hash: HeaderHash,
+        snapshot: LedgerSnapshot,
+    ) -> Result<Arc<Ref>> {
+        let LedgerSnapshot {
+            block,
+            chain_length: _,
+            ledger,
+            previous_epoch,
+            leadership_ledger,
+        } = snapshot;
+        if !self.storage.is_ancestor(block, head_hash) {
+            return Err(Error::LedgerSnapshotNotInBranch(block));
+        }
+        let time_frame = Arc::new(block0_time_frame(block0)?);
+
+        let previous_epoch_state = match previous_epoch {
+            Some((previous_block, previous_ledger)) => {
+                let header = self.stored_header(previous_block)?;
+                // the leadership of the previous epoch is no longer used to
+                // verify the blocks, it only gives the era of the `Ref`
+                let leadership = Leadership::new(header.block_date().epoch, &previous_ledger);
+                let previous_ref = self
+                    .create_and_store_reference(
+                        previous_block,
+                        header,
+                        previous_ledger,
+                        Arc::clone(&time_frame),
+                        Arc::new(leadership),
+                        None,
+                        None,
+                    )
+                    .await;
+                Some(previous_ref)
+            }
+            None => None,
+        };
+
+        let header = self.stored_header(block)?;
+        // with BFT the leadership only depends on the settings,
+        // which do not change during the epoch
+        let leadership = Leadership::new(
+            header.block_date().epoch,
+            leadership_ledger.as_ref().unwrap_or(&ledger),
+        );
+        Ok(self
+            .create_and_store_reference(
+                block,
+                header,
+                ledger,
+                time_frame,
+                Arc::new(leadership),
+                None,
+                previous_epoch_state,
+            )
+            .await)
+    }
+
+    fn stored_header(&self, header_hash: HeaderHash) -> Result<Header> {
+        self.storage
+            .get(header_hash)?
+            .map(|block| block.header().clone())
+            .ok_or(Error::MissingParentBlock(header_hash))
+    }
+
     pub fn get_checkpoints(&self, branch: &Branch) -> Checkpoints {
         Checkpoints::new_from(branch.get_ref())
     }
 }
 
+fn block0_time_frame(block0: &Block) -> Result<TimeFrame> {
+    use crate::blockcfg::Block0DataSource as _;
+
+    let start_time = block0.start_time().map_err(Error::Block0)?;
+    let slot_duration = block0.slot_duration().map_err(Error::Block0)?;
+
+    Ok(TimeFrame::new(
+        chain_time::Timeline::new(start_time),
+        chain_time::SlotDuration::from_secs(slot_duration.as_secs() as u32),
+    ))
+}
+
 fn write_reward_info(
     epoch: Epoch,
     parent_hash: HeaderHash,
@@ -0,0 +1,336 @@
+//! Periodic snapshots of the ledger, allowing the node to resume from the
+//! newest snapshot instead of replaying all the blocks since the block0.
+//!
+//! A snapshot is taken of the last block of an epoch, once this block is deeper
+//! than the epoch stability depth of the main branch. Along with the ledger of the
+//! block, the snapshot holds the states needed to rebuild the leadership of the
+//! following epochs: with genesis praos the leadership of an epoch is computed
+//! from the stake distribution of the epoch before.
+//!
+//! Each snapshot is stored in its own file, prefixed with the format version and
+//! the checksum of its content, so corrupted snapshots are detected and skipped.
+
+use crate::{
+    blockcfg::{ChainLength, ConsensusVersion, HeaderHash, Ledger},
+    blockchain::Ref,
+};
+use chain_core::{
+    packer::Codec,
+    property::{DeserializeFromSlice, ReadError, Serialize, WriteError},
+};
+use chain_crypto::Blake2b256;
+use std::{
+    fs, io,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+};
+
+const VERSION: u8 = 1;
+const CHECKSUM_SIZE: usize = 32;
+const EXTENSION: &str = "ledger";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("I/O error with the ledger snapshot `{}`", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("cannot serialize the ledger snapshot")]
+    Serialize(#[from] WriteError),
+    #[error("cannot deserialize the ledger snapshot")]
+    Deserialize(#[from] ReadError),
+    #[error("unsupported ledger snapshot version {0}")]
+    UnsupportedVersion(u8),
+    #[error("the checksum of the ledger snapshot does not match its content")]
+    ChecksumMismatch,
+    #[error("the ledger snapshot does not restore the same ledger")]
+    Lossy,
+}
+
+/// The state of the ledger at the last block of an epoch
+pub struct LedgerSnapshot {
+    pub block: HeaderHash,
+    pub chain_length: ChainLength,
+    pub ledger: Ledger,
+    /// the last block of the epoch before and its ledger, only kept with genesis praos
+    pub previous_epoch: Option<(HeaderHash, Ledger)>,
+    /// the ledger the leadership of the epoch was computed from, only kept with genesis praos
+    pub leadership_ledger: Option<Ledger>,
+}
+
+impl LedgerSnapshot {
+    /// Snapshot of the given block, `None` if the states needed to resume
+    /// from this block are not available in memory
+    pub fn from_ref(block_ref: &Ref) -> Option<Self> {
+        let ledger = block_ref.ledger().as_ref().clone();
+        let (previous_epoch, leadership_ledger) = match ledger.consensus_version() {
+            ConsensusVersion::Bft => (None, None),
+            ConsensusVersion::GenesisPraos => {
+                let previous = block_ref.last_ref_previous_epoch()?;
+                // see `new_epoch_leadership_from`, the leadership falls back on
+                // the previous epoch state only for the first epochs
+                let leadership_ledger = match previous.last_ref_previous_epoch() {
+                    Some(before) => before.ledger(),
+                    None if previous.block_date().epoch == 0 => previous.ledger(),
+                    None => return None,
+                };
+                (
+                    Some((previous.hash(), previous.ledger().as_ref().clone())),
+                    Some(leadership_ledger.as_ref().clone()),
+                )
+            }
+        };
+        Some(LedgerSnapshot {
+            block: block_ref.hash(),
+            chain_length: block_ref.chain_length(),
+            ledger,
+            previous_epoch,
+            leadership_ledger,
+        })
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, Error> {
+        let mut codec = Codec::new(Vec::new());
+        codec.put_bytes(self.block.as_bytes())?;
+        codec.put_be_u32(self.chain_length.into())?;
+        self.ledger.serialize(&mut codec)?;
+        match &self.previous_epoch {
+            None => codec.put_u8(0)?,
+            Some((block, ledger)) => {
+                codec.put_u8(1)?;
+                codec.put_bytes(block.as_bytes())?;
+                ledger.serialize(&mut codec)?;
+            }
+        }
+        match &self.leadership_ledger {
+            None => codec.put_u8(0)?,
+            Some(ledger) => {
+                codec.put_u8(1)?;
+                ledger.serialize(&mut codec)?;
+            }
+        }
+
+        let payload = codec.into_inner();
+        let mut bytes = Vec::with_capacity(1 + CHECKSUM_SIZE + payload.len());
+        bytes.push(VERSION);
+        bytes.extend_from_slice(Blake2b256::new(&payload).as_ref());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        let mut codec = Codec::new(bytes);
+        let version = codec.get_u8()?;
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        let checksum = codec.get_slice(CHECKSUM_SIZE)?;
+        let payload = &bytes[1 + CHECKSUM_SIZE..];
+        if checksum != Blake2b256::new(payload).as_ref() {
+            return Err(Error::ChecksumMismatch);
+        }
+
+        let mut codec = Codec::new(payload);
+        let block = get_header_hash(&mut codec)?;
+        let chain_length = codec.get_be_u32()?.into();
+        let ledger = Ledger::deserialize_from_slice(&mut codec)?;
+        let previous_epoch = match codec.get_u8()? {
+            0 => None,
+            1 => {
+                let block = get_header_hash(&mut codec)?;
+                Some((block, Ledger::deserialize_from_slice(&mut codec)?))
+            }
+            tag => return Err(ReadError::UnknownTag(tag as u32).into()),
+        };
+        let leadership_ledger = match codec.get_u8()? {
+            0 => None,
+            1 => Some(Ledger::deserialize_from_slice(&mut codec)?),
+            tag => return Err(ReadError::UnknownTag(tag as u32).into()),
+        };
+        if codec.has_bytes_left() {
+            return Err(ReadError::UnconsumedData(codec.bytes_left()).into());
+        }
+
+        Ok(LedgerSnapshot {
+            block,
+            chain_length,
+            ledger,
+            previous_epoch,
+            leadership_ledger,
+        })
+    }
+
+    /// Checks the ledgers restored from the snapshot are the ones snapshotted
+    fn check_restored(&self, restored: &Self) -> Result<(), Error> {
+        if self.block == restored.block
+            && self.chain_length == restored.chain_length
+            && self.ledger == restored.ledger
+            && self.previous_epoch == restored.previous_epoch
+            && self.leadership_ledger == restored.leadership_ledger
+        {
+            Ok(())
+        } else {
+            Err(Error::Lossy)
+        }
+    }
+}
+
+fn get_header_hash(codec: &mut Codec<&[u8]>) -> Result<HeaderHash, ReadError> {
+    let mut bytes = [0; 32];
+    codec.copy_to_slice(&mut bytes)?;
+    Ok(HeaderHash::from_bytes(bytes))
+}
+
+/// The directory holding the ledger snapshots
+#[derive(Clone)]
+pub struct LedgerSnapshots {
+    dir: PathBuf,
+    keep: NonZeroUsize,
+}
+
+impl LedgerSnapshots {
+    /// Ledger snapshots stored in `dir`, keeping only the `keep` newest ones
+    pub fn new(dir: PathBuf, keep: NonZeroUsize) -> Self {
+        LedgerSnapshots { dir, keep }
+    }
+
+    fn path(&self, chain_length: ChainLength, block: HeaderHash) -> PathBuf {
+        self.dir.join(format!(
+            "{}-{}.{}",
+            u32::from(chain_length),
+            block,
+            EXTENSION
+        ))
+    }
+
+    pub fn contains(&self, block_ref: &Ref) -> bool {
+        self.path(block_ref.chain_length(), block_ref.hash())
+            .exists()
+    }
+
+    /// Write the snapshot, after checking the ledgers can be restored from it,
+    /// and remove the oldest snapshots
+    pub fn write(&self, snapshot: &LedgerSnapshot) -> Result<(), Error> {
+        let bytes = snapshot.serialize()?;
+        snapshot.check_restored(&LedgerSnapshot::deserialize(&bytes)?)?;
+
+        let path = self.path(snapshot.chain_length, snapshot.block);
+        let path_tmp = path.with_extension("tmp");
+        fs::create_dir_all(&self.dir).map_err(|source| Error::Io {
+            path: self.dir.clone(),
+            source,
+        })?;
+        fs::write(&path_tmp, bytes).map_err(|source| Error::Io {
+            path: path_tmp.clone(),
+            source,
+        })?;
+        fs::rename(&path_tmp, &path).map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?;
+
+        for (_, old_path) in self.list()?.into_iter().skip(self.keep.get()) {
+            fs::remove_file(&old_path).map_err(|source| Error::Io {
+                path: old_path,
+                source,
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<LedgerSnapshot, Error> {
+        let bytes = fs::read(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        LedgerSnapshot::deserialize(&bytes)
+    }
+
+    /// The paths of the snapshots, the newest first
+    pub fn list(&self) -> Result<Vec<(ChainLength, PathBuf)>, Error> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => {
+                return Err(Error::Io {
+                    path: self.dir.clone(),
+                    source,
+                })
+            }
+        };
+
+        let mut snapshots = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|source| Error::Io {
+                    path: self.dir.clone(),
+                    source,
+                })?
+                .path();
+            if path
+                .extension()
+                .map_or(true, |extension| extension != EXTENSION)
+            {
+                continue;
+            }
+            let chain_length = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.split_once('-'))
+                .and_then(|(chain_length, _)| chain_length.parse::<u32>().ok());
+            if let Some(chain_length) = chain_length {
+                snapshots.push((chain_length.into(), path));
+            }
+        }
+        snapshots.sort_by(|(a, _), (b, _)| b.cmp(a));
+        Ok(snapshots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_impl_mockchain::testing::{ConfigBuilder, LedgerBuilder};
+
+    fn snapshot() -> LedgerSnapshot {
+        let ledger = LedgerBuilder::from_config(ConfigBuilder::new())
+            .faucet_value(1_000.into())
+            .build()
+            .unwrap()
+            .ledger;
+        LedgerSnapshot {
+            block: HeaderHash::from_bytes([1; 32]),
+            chain_length: 10.into(),
+            ledger: ledger.clone(),
+            previous_epoch: Some((HeaderHash::from_bytes([2; 32]), ledger.clone())),
+            leadership_ledger: Some(ledger),
+        }
+    }
+
+    #[test]
+    fn snapshot_roundtrip() {
+        let snapshot = snapshot();
+        let bytes = snapshot.serialize().unwrap();
+        let restored = LedgerSnapshot::deserialize(&bytes).unwrap();
+        snapshot.check_restored(&restored).unwrap();
+    }
+
+    #[test]
+    fn corrupted_snapshot_is_rejected() {
+        let mut bytes = snapshot().serialize().unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        assert!(matches!(
+            LedgerSnapshot::deserialize(&bytes),
+            Err(Error::ChecksumMismatch)
+        ));
+
+        bytes[0] = VERSION + 1;
+        assert!(matches!(
+            LedgerSnapshot::deserialize(&bytes),
+            Err(Error::UnsupportedVersion(_))
+        ));
+    }
+}
@@ -4,6 +4,7 @@ mod candidate;
 mod chain;
 mod chain_selection;
 mod checkpoints;
+mod ledger_snapshots;
 mod multiverse;
 mod process;
 mod reference;
@@ -34,6 +35,7 @@ pub use self::{
     },
     chain_selection::{compare_against, ComparisonResult},
     checkpoints::Checkpoints,
+    ledger_snapshots::LedgerSnapshots,
     multiverse::Multiverse,
     process::{start, TaskData},
     reference::Ref,
@@ -175,6 +175,7 @@ impl Process {
         tip_update_queue: MessageQueue<Arc<Ref>>,
     ) {
         self.start_garbage_collector(&self.service_info);
+        self.start_ledger_snapshots(&self.service_info);
 
         let mut tip_updater = TipUpdater::new(
             self.blockchain_tip.clone(),
@@ -312,6 +313,21 @@ impl Process {
             move || blockchain_gc(blockchain.clone(), tip.clone()),
         )
     }
+
+    fn start_ledger_snapshots(&self, info: &TokioServiceInfo) {
+        let blockchain = self.blockchain.clone();
+        let tip = self.blockchain_tip.clone();
+
+        async fn snapshot_ledger(blockchain: Blockchain, tip: Tip) -> chain::Result<()> {
+            blockchain.snapshot_ledger(tip.get_ref().await).await
+        }
+
+        info.run_periodic_fallible(
+            "snapshot the ledger",
+            self.garbage_collection_interval,
+            move || snapshot_ledger(blockchain.clone(), tip.clone()),
+        )
+    }
 }
 
 async fn process_and_propagate_new_ref(
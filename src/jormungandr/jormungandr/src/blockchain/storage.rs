@@ -6,7 +6,7 @@ use chain_core::{
     packer::Codec,
     property::{Deserialize, ReadError, Serialize, WriteError},
 };
-use chain_storage::{BlockInfo, BlockStore, Error as StorageError};
+use chain_storage::{BackendKind, BlockInfo, BlockStore, Error as StorageError};
 use futures::prelude::*;
 use std::{convert::identity, path::Path};
 use thiserror::Error;
@@ -63,8 +63,12 @@ enum StreamingError {
 }
 
 impl Storage {
-    pub fn file<P: AsRef<Path>>(path: P, span: Span) -> Result<Self, Error> {
-        let storage = BlockStore::file(path, HeaderHash::zero_hash().as_bytes().to_vec())?;
+    pub fn file<P: AsRef<Path>>(path: P, backend: BackendKind, span: Span) -> Result<Self, Error> {
+        let storage = BlockStore::file_with_backend(
+            path,
+            HeaderHash::zero_hash().as_bytes().to_vec(),
+            backend,
+        )?;
         Ok(Storage { storage, span })
     }
 
@@ -1,22 +1,54 @@
+use super::pool::internal::Account;
 use crate::{fragment::FragmentId, network::retrieve_local_ip};
 use jormungandr_lib::{
     crypto::hash::Hash,
-    interfaces::{BlockDate, FragmentLog, FragmentOrigin, FragmentStatus},
+    interfaces::{BlockDate, FragmentLog, FragmentOrigin, FragmentSelectionReason, FragmentStatus},
 };
 use lru::LruCache;
 use std::collections::HashMap;
+use tokio::sync::broadcast;
+
+// updates not yet received by a subscriber when this many more are sent are
+// dropped for this subscriber
+const STATUS_UPDATES_CAPACITY: usize = 4096;
+
+/// A change of the status of a logged fragment
+#[derive(Clone, Debug)]
+pub struct StatusUpdate {
+    pub fragment_id: FragmentId,
+    /// the first account the fragment spends from, if it is known
+    pub account: Account,
+    pub status: FragmentStatus,
+}
 
 pub struct Logs {
-    entries: LruCache<Hash, (FragmentLog, Option<BlockDate>)>,
+    entries: LruCache<Hash, (FragmentLog, Option<BlockDate>, Account)>,
+    status_updates: broadcast::Sender<StatusUpdate>,
 }
 
 impl Logs {
     pub fn new(max_entries: usize) -> Self {
+        let (status_updates, _) = broadcast::channel(STATUS_UPDATES_CAPACITY);
         Logs {
             entries: LruCache::new(max_entries),
+            status_updates,
         }
     }
 
+    /// Receive the status changes of the fragments logged from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<StatusUpdate> {
+        self.status_updates.subscribe()
+    }
+
+    fn notify(&self, fragment_id: FragmentId, account: Account, status: FragmentStatus) {
+        // fails only if there are no subscribers
+        let _ = self.status_updates.send(StatusUpdate {
+            fragment_id,
+            account,
+            status,
+        });
+    }
+
     pub fn exists(&self, fragment_id: FragmentId) -> bool {
         let fragment_id: Hash = fragment_id.into();
         self.entries.contains(&fragment_id)
@@ -30,21 +62,25 @@ impl Logs {
     }
 
     /// Returns true if fragment was registered
-    pub fn insert_pending(&mut self, log: FragmentLog) -> bool {
+    pub fn insert_pending(&mut self, log: FragmentLog, account: Account) -> bool {
         assert!(log.is_pending());
         let fragment_id = *log.fragment_id();
         if self.entries.contains(&fragment_id) {
             false
         } else {
-            self.entries.put(fragment_id, (log, None));
+            self.entries.put(fragment_id, (log, None, account));
+            self.notify(fragment_id.into_hash(), account, FragmentStatus::Pending);
             true
         }
     }
 
     /// Returns number of registered fragments
-    pub fn insert_all_pending(&mut self, logs: impl IntoIterator<Item = FragmentLog>) -> usize {
+    pub fn insert_all_pending(
+        &mut self,
+        logs: impl IntoIterator<Item = (FragmentLog, Account)>,
+    ) -> usize {
         logs.into_iter()
-            .map(|log| self.insert_pending(log))
+            .map(|(log, account)| self.insert_pending(log, account))
             .filter(|was_modified| *was_modified)
             .count()
     }
@@ -57,11 +93,13 @@ impl Logs {
     ) {
         let fragment_id: Hash = fragment_id.into();
         match self.entries.get_mut(&fragment_id) {
-            Some((entry, date)) => {
-                if !entry.modify(status) {
+            Some((entry, date, account)) => {
+                if !entry.modify(status.clone()) {
                     tracing::debug!("the fragment log update was refused: cannot mark the fragment as invalid if it was already committed to a block");
                 } else {
                     *date = Some(ledger_date);
+                    let account = *account;
+                    self.notify(fragment_id.into_hash(), account, status);
                 }
             }
             None => {
@@ -82,12 +120,26 @@ impl Logs {
                         addr: retrieve_local_ip(),
                     },
                 );
-                entry.modify(status);
-                self.entries.put(fragment_id, (entry, Some(ledger_date)));
+                entry.modify(status.clone());
+                self.entries
+                    .put(fragment_id, (entry, Some(ledger_date), None));
+                self.notify(fragment_id.into_hash(), None, status);
             }
         }
     }
 
+    /// Record why the fragment was selected for a block, ignored if the fragment is not logged
+    pub fn set_selection_reason(
+        &mut self,
+        fragment_id: FragmentId,
+        reason: FragmentSelectionReason,
+    ) {
+        let fragment_id: Hash = fragment_id.into();
+        if let Some((entry, _, _)) = self.entries.get_mut(&fragment_id) {
+            entry.set_selection_reason(reason);
+        }
+    }
+
     pub fn modify_all(
         &mut self,
         fragment_ids: impl IntoIterator<Item = FragmentId>,
@@ -110,24 +162,27 @@ impl Logs {
                 let key: Hash = fragment_id.into();
                 self.entries.peek(&key).map(|log| (fragment_id, log))
             })
-            .for_each(|(k, (log, _date))| {
+            .for_each(|(k, (log, _date, _account))| {
                 result.insert(k, log);
             });
         result
     }
 
     pub fn logs(&self) -> impl Iterator<Item = &FragmentLog> {
-        self.entries.iter().map(|(_, (log, _date))| log)
+        self.entries.iter().map(|(_, (log, _date, _account))| log)
     }
 
+    /// Forget the fragments confirmed or rejected after `target_date`, e.g. on
+    /// the branch that was switched away from. Subscribers are told they are
+    /// pending again.
     pub fn remove_logs_after_date(&mut self, target_date: BlockDate) {
         let mut to_remove = Vec::new();
-        for (_, (log, date)) in self.entries.iter() {
+        for (_, (log, date, account)) in self.entries.iter() {
             match log.status() {
                 FragmentStatus::InABlock { .. } | FragmentStatus::Rejected { .. } => {
                     // date is always present for non pending statuses.
                     if date.unwrap() > target_date {
-                        to_remove.push(*log.fragment_id());
+                        to_remove.push((*log.fragment_id(), *account));
                     } else {
                         // iterating in most-recently used order (i.e. most recently added to block)
                         break;
@@ -137,8 +192,88 @@ impl Logs {
             }
         }
 
-        for fragment in to_remove {
-            self.entries.pop(&fragment);
+        for (fragment_id, account) in to_remove {
+            self.entries.pop(&fragment_id);
+            self.notify(fragment_id.into_hash(), account, FragmentStatus::Pending);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_changes_are_sent_to_subscribers() {
+        let mut logs = Logs::new(10);
+        let mut updates = logs.subscribe();
+
+        let fragment_id = FragmentId::hash_bytes(&[1]);
+        let account = Some([2; 32]);
+        assert!(logs.insert_pending(FragmentLog::new(fragment_id, FragmentOrigin::Rest), account));
+        // already logged, nothing changes
+        assert!(!logs.insert_pending(FragmentLog::new(fragment_id, FragmentOrigin::Rest), None));
+
+        let date = BlockDate::new(1, 0);
+        let in_a_block = FragmentStatus::InABlock {
+            date,
+            block: FragmentId::hash_bytes(&[3]).into(),
+        };
+        logs.modify(fragment_id, in_a_block.clone(), date);
+        // a fragment in a block cannot be rejected anymore
+        logs.modify(
+            fragment_id,
+            FragmentStatus::Rejected {
+                reason: "rejected".to_string(),
+            },
+            date,
+        );
+
+        let update = updates.try_recv().unwrap();
+        assert_eq!(update.fragment_id, fragment_id);
+        assert_eq!(update.account, account);
+        assert_eq!(update.status, FragmentStatus::Pending);
+
+        let update = updates.try_recv().unwrap();
+        assert_eq!(update.fragment_id, fragment_id);
+        assert_eq!(update.account, account);
+        assert_eq!(update.status, in_a_block);
+
+        assert!(updates.try_recv().is_err());
+    }
+
+    #[test]
+    fn removed_logs_are_pending_again_for_subscribers() {
+        let mut logs = Logs::new(10);
+
+        let date = BlockDate::new(1, 0);
+        let later = BlockDate::new(2, 0);
+        let kept = FragmentId::hash_bytes(&[1]);
+        let removed = FragmentId::hash_bytes(&[2]);
+        let account = Some([3; 32]);
+        for (fragment_id, date) in [(kept, date), (removed, later)] {
+            logs.insert_pending(FragmentLog::new(fragment_id, FragmentOrigin::Rest), account);
+            logs.modify(
+                fragment_id,
+                FragmentStatus::InABlock {
+                    date,
+                    block: FragmentId::hash_bytes(&[4]).into(),
+                },
+                date,
+            );
         }
+
+        let mut updates = logs.subscribe();
+        logs.remove_logs_after_date(date);
+
+        assert!(logs.exists(kept));
+        assert!(!logs.exists(removed));
+
+        let update = updates.try_recv().unwrap();
+        assert_eq!(update.fragment_id, removed);
+        assert_eq!(update.account, account);
+        assert_eq!(update.status, FragmentStatus::Pending);
+
+        assert!(updates.try_recv().is_err());
     }
 }
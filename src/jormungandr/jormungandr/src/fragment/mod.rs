@@ -4,5 +4,10 @@ mod pool;
 mod process;
 pub mod selection;
 
-pub use self::{entry::PoolEntry, logs::Logs, pool::Pool, process::Process};
+pub use self::{
+    entry::PoolEntry,
+    logs::{Logs, StatusUpdate},
+    pool::Pool,
+    process::Process,
+};
 pub use crate::blockcfg::{Fragment, FragmentId};
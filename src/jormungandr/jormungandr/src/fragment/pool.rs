@@ -2,8 +2,8 @@ use crate::{
     blockcfg::ApplyBlockLedger,
     fragment::{
         selection::{
-            FragmentSelectionAlgorithm, FragmentSelectionAlgorithmParams, FragmentSelectionResult,
-            OldestFirst,
+            fragment_fee_and_account, FragmentSelectionAlgorithm, FragmentSelectionAlgorithmParams,
+            FragmentSelectionResult, InPoolOrder,
         },
         Fragment, FragmentId, Logs,
     },
@@ -52,6 +52,7 @@ pub enum Error {
 impl Pool {
     pub fn new(
         max_entries: usize,
+        selection_alg: FragmentSelectionAlgorithmParams,
         logs: Logs,
         network_msg_box: MessageBox<NetworkMsg>,
         persistent_log: Option<File>,
@@ -59,7 +60,7 @@ impl Pool {
     ) -> Self {
         Pool {
             logs,
-            pool: internal::Pool::new(max_entries),
+            pool: internal::Pool::new(max_entries, selection_alg),
             network_msg_box,
             persistent_log: persistent_log
                 .map(|file| BufWriter::with_capacity(DEFAULT_BUF_SIZE, file)),
@@ -190,7 +191,10 @@ impl Pool {
         );
         let fragment_logs: Vec<_> = new_fragments
             .iter()
-            .map(move |(_, id)| FragmentLog::new(*id, origin))
+            .map(move |(fragment, id)| {
+                let (_, account) = fragment_fee_and_account(fragment);
+                (FragmentLog::new(*id, origin), account)
+            })
             .collect();
         self.logs.insert_all_pending(fragment_logs);
 
@@ -230,10 +234,11 @@ impl Pool {
         self.update_metrics();
     }
 
+    /// Select the fragments for a block, in the order of the selection
+    /// algorithm of the pool
     pub async fn select(
         &mut self,
         ledger: ApplyBlockLedger,
-        selection_alg: FragmentSelectionAlgorithmParams,
         soft_deadline_future: futures::channel::oneshot::Receiver<()>,
         hard_deadline_future: futures::channel::oneshot::Receiver<()>,
     ) -> (Contents, ApplyBlockLedger) {
@@ -242,20 +247,15 @@ impl Pool {
             contents,
             ledger,
             rejected_fragments_cnt,
-        } = match selection_alg {
-            FragmentSelectionAlgorithmParams::OldestFirst => {
-                let mut selection_alg = OldestFirst::new();
-                selection_alg
-                    .select(
-                        ledger,
-                        logs,
-                        pool,
-                        soft_deadline_future,
-                        hard_deadline_future,
-                    )
-                    .await
-            }
-        };
+        } = InPoolOrder::new()
+            .select(
+                ledger,
+                logs,
+                pool,
+                soft_deadline_future,
+                hard_deadline_future,
+            )
+            .await;
         self.metrics.add_tx_rejected_cnt(rejected_fragments_cnt);
         self.update_metrics();
         (contents, ledger)
@@ -311,9 +311,15 @@ fn is_transaction_valid<E>(tx: &Transaction<E>) -> bool {
 
 pub(super) mod internal {
     use super::*;
+    use crate::{
+        blockcfg::Value,
+        fragment::selection::{fragment_fee_and_account, fragment_priority},
+    };
+    use chain_impl_mockchain::transaction::INPUT_PTR_SIZE;
+    use jormungandr_lib::interfaces::FragmentPriority;
     use std::{
-        cmp::Ordering,
-        collections::HashMap,
+        cmp::{Ordering, Reverse},
+        collections::{BTreeMap, BTreeSet, HashMap},
         hash::{Hash, Hasher},
         ptr,
     };
@@ -375,6 +381,28 @@ pub(super) mod internal {
             }
         }
 
+        fn push_back(&mut self, key: K, value: V) {
+            let mut entry = Box::new(IndexedQueueEntry {
+                key,
+                value,
+                prev: self.tail,
+                next: ptr::null_mut(),
+            });
+            if let Some(tail) = unsafe { self.tail.as_mut() } {
+                tail.next = &mut *entry;
+            } else {
+                self.head = &mut *entry;
+            }
+            self.tail = &mut *entry;
+            if self
+                .index
+                .insert(IndexedQueueKeyRef(&entry.key), entry)
+                .is_some()
+            {
+                panic!("inserted an already existing key");
+            }
+        }
+
         fn pop_back(&mut self) -> Option<(K, V)> {
             let tail = unsafe { self.tail.as_mut() }?;
             self.tail = tail.prev;
@@ -453,16 +481,270 @@ pub(super) mod internal {
         }
     }
 
+    /// The first account a fragment spends from, if any
+    pub type Account = Option<[u8; INPUT_PTR_SIZE]>;
+
+    /// The arrival order of a fragment in the pool, kept when the fragment is put back
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Arrival(u64);
+
+    /// The rank of a fragment in the order of the pool
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Rank {
+        Oldest,
+        Fee(Value),
+        Account(Account),
+        Priority(FragmentPriority),
+    }
+
+    /// Fragments ranked by a key, the highest key first, the oldest fragment
+    /// first among the fragments with the same key.
+    ///
+    /// Only the oldest fragment of each account is ranked, the next fragments of
+    /// the account wait for their turn: selecting them before would make them fail
+    /// the check of the spending counter.
+    struct RankedOrder<K> {
+        ranks: BTreeSet<(K, Reverse<Arrival>, FragmentId)>,
+        by_id: HashMap<FragmentId, (K, Reverse<Arrival>, Account)>,
+        accounts: HashMap<Account, BTreeMap<Arrival, FragmentId>>,
+    }
+
+    impl<K: Ord + Copy> RankedOrder<K> {
+        fn new() -> Self {
+            RankedOrder {
+                ranks: BTreeSet::new(),
+                by_id: HashMap::new(),
+                accounts: HashMap::new(),
+            }
+        }
+
+        fn insert(&mut self, id: FragmentId, key: K, account: Account, arrival: Arrival) {
+            self.by_id.insert(id, (key, Reverse(arrival), account));
+            if account.is_none() {
+                self.ranks.insert((key, Reverse(arrival), id));
+                return;
+            }
+            let fragments = self.accounts.entry(account).or_default();
+            let previous = fragments.values().next().copied();
+            fragments.insert(arrival, id);
+            if fragments.keys().next() == Some(&arrival) {
+                if let Some(previous) = previous {
+                    self.unrank(&previous);
+                }
+                self.ranks.insert((key, Reverse(arrival), id));
+            }
+        }
+
+        fn remove(&mut self, id: &FragmentId) -> Option<K> {
+            let (key, arrival, account) = self.by_id.remove(id)?;
+            let ranked = self.ranks.remove(&(key, arrival, *id));
+            if account.is_some() {
+                let fragments = self.accounts.get_mut(&account)?;
+                fragments.remove(&arrival.0);
+                match fragments.values().next().copied() {
+                    Some(next) if ranked => self.rank(&next),
+                    Some(_) => (),
+                    None => {
+                        self.accounts.remove(&account);
+                    }
+                }
+            }
+            Some(key)
+        }
+
+        fn rank(&mut self, id: &FragmentId) {
+            let (key, arrival, _) = self.by_id[id];
+            self.ranks.insert((key, arrival, *id));
+        }
+
+        fn unrank(&mut self, id: &FragmentId) {
+            let (key, arrival, _) = self.by_id[id];
+            self.ranks.remove(&(key, arrival, *id));
+        }
+
+        fn first(&self) -> Option<FragmentId> {
+            self.ranks.iter().next_back().map(|(_, _, id)| *id)
+        }
+
+        /// The account ranked last loses its newest fragment first, the other
+        /// fragments of the account would not be valid without their predecessor
+        fn last(&self) -> Option<FragmentId> {
+            let (_, _, id) = self.ranks.iter().next()?;
+            let (_, _, account) = &self.by_id[id];
+            match self.accounts.get(account) {
+                Some(fragments) => fragments.values().next_back().copied(),
+                None => Some(*id),
+            }
+        }
+    }
+
+    /// Round robin between the accounts: the oldest fragment of each account in turn.
+    /// The account with the most fragments loses its newest fragment first.
+    struct FairOrder {
+        accounts: HashMap<Account, (u64, BTreeMap<Arrival, FragmentId>)>,
+        by_id: HashMap<FragmentId, (Account, Arrival)>,
+        turns: BTreeSet<(u64, Account)>,
+        sizes: BTreeSet<(usize, Account)>,
+        next_turn: u64,
+        /// The turn the account of a fragment had when the fragment was taken by
+        /// `pop_first`, given back to the account if the fragment is put back
+        taken: HashMap<FragmentId, u64>,
+    }
+
+    impl FairOrder {
+        fn new() -> Self {
+            FairOrder {
+                accounts: HashMap::new(),
+                by_id: HashMap::new(),
+                turns: BTreeSet::new(),
+                sizes: BTreeSet::new(),
+                next_turn: 0,
+                taken: HashMap::new(),
+            }
+        }
+
+        fn insert(&mut self, id: FragmentId, account: Account, arrival: Arrival) {
+            let taken_turn = self.taken.remove(&id);
+            match self.accounts.get_mut(&account) {
+                Some((turn, fragments)) => {
+                    self.sizes.remove(&(fragments.len(), account));
+                    fragments.insert(arrival, id);
+                    self.sizes.insert((fragments.len(), account));
+                    if let Some(taken_turn) = taken_turn.filter(|taken_turn| taken_turn < turn) {
+                        self.set_turn(account, taken_turn);
+                    }
+                }
+                None => {
+                    let turn = taken_turn.unwrap_or_else(|| self.new_turn());
+                    self.accounts
+                        .insert(account, (turn, BTreeMap::from([(arrival, id)])));
+                    self.turns.insert((turn, account));
+                    self.sizes.insert((1, account));
+                }
+            }
+            self.by_id.insert(id, (account, arrival));
+        }
+
+        fn remove(&mut self, id: &FragmentId) -> Option<Account> {
+            let (account, arrival) = self.by_id.remove(id)?;
+            let (turn, fragments) = self.accounts.get_mut(&account)?;
+            self.sizes.remove(&(fragments.len(), account));
+            fragments.remove(&arrival);
+            if fragments.is_empty() {
+                self.turns.remove(&(*turn, account));
+                self.accounts.remove(&account);
+            } else {
+                self.sizes.insert((fragments.len(), account));
+            }
+            Some(account)
+        }
+
+        fn new_turn(&mut self) -> u64 {
+            let turn = self.next_turn;
+            self.next_turn += 1;
+            turn
+        }
+
+        fn set_turn(&mut self, account: Account, new_turn: u64) {
+            if let Some((turn, _)) = self.accounts.get_mut(&account) {
+                self.turns.remove(&(*turn, account));
+                self.turns.insert((new_turn, account));
+                *turn = new_turn;
+            }
+        }
+
+        fn first(&self) -> Option<FragmentId> {
+            let (_, account) = self.turns.iter().next()?;
+            self.accounts[account].1.values().next().copied()
+        }
+
+        /// Removes the oldest fragment of the account whose turn it is,
+        /// the account takes its next turn after all the other accounts
+        fn pop_first(&mut self) -> Option<(FragmentId, Account)> {
+            let (turn, account) = *self.turns.iter().next()?;
+            let id = self.first()?;
+            self.remove(&id)?;
+            self.taken.insert(id, turn);
+            let next_turn = self.new_turn();
+            self.set_turn(account, next_turn);
+            Some((id, account))
+        }
+
+        fn last(&self) -> Option<FragmentId> {
+            let (_, account) = self.sizes.iter().next_back()?;
+            self.accounts[account].1.values().next_back().copied()
+        }
+    }
+
+    /// The order of the fragments on top of their arrival order
+    enum Order {
+        OldestFirst,
+        HighestFeeFirst(RankedOrder<Value>),
+        AccountFairness(FairOrder),
+        TypePriority(RankedOrder<Reverse<FragmentPriority>>),
+    }
+
+    impl Order {
+        fn new(selection_alg: FragmentSelectionAlgorithmParams) -> Self {
+            match selection_alg {
+                FragmentSelectionAlgorithmParams::OldestFirst => Order::OldestFirst,
+                FragmentSelectionAlgorithmParams::HighestFeeFirst => {
+                    Order::HighestFeeFirst(RankedOrder::new())
+                }
+                FragmentSelectionAlgorithmParams::AccountFairness => {
+                    Order::AccountFairness(FairOrder::new())
+                }
+                FragmentSelectionAlgorithmParams::TypePriority => {
+                    Order::TypePriority(RankedOrder::new())
+                }
+            }
+        }
+
+        fn insert(&mut self, id: FragmentId, fragment: &Fragment, arrival: Arrival) {
+            match self {
+                Order::OldestFirst => (),
+                Order::HighestFeeFirst(order) => {
+                    let (fee, account) = fragment_fee_and_account(fragment);
+                    order.insert(id, fee, account, arrival)
+                }
+                Order::AccountFairness(order) => {
+                    order.insert(id, fragment_fee_and_account(fragment).1, arrival)
+                }
+                Order::TypePriority(order) => order.insert(
+                    id,
+                    Reverse(fragment_priority(fragment)),
+                    fragment_fee_and_account(fragment).1,
+                    arrival,
+                ),
+            }
+        }
+
+        fn remove(&mut self, id: &FragmentId) -> Option<Rank> {
+            match self {
+                Order::OldestFirst => Some(Rank::Oldest),
+                Order::HighestFeeFirst(order) => order.remove(id).map(Rank::Fee),
+                Order::AccountFairness(order) => order.remove(id).map(Rank::Account),
+                Order::TypePriority(order) => order
+                    .remove(id)
+                    .map(|Reverse(priority)| Rank::Priority(priority)),
+            }
+        }
+    }
+
     pub struct Pool {
-        entries: IndexedQueue<FragmentId, Fragment>,
+        entries: IndexedQueue<FragmentId, (Fragment, Arrival)>,
+        order: Order,
         max_entries: usize,
+        next_arrival: u64,
     }
 
     impl Pool {
-        pub fn new(max_entries: usize) -> Self {
+        pub fn new(max_entries: usize, selection_alg: FragmentSelectionAlgorithmParams) -> Self {
             Pool {
                 entries: IndexedQueue::new(),
+                order: Order::new(selection_alg),
                 max_entries,
+                next_arrival: 0,
             }
         }
 
@@ -471,33 +753,106 @@ pub(super) mod internal {
             &mut self,
             fragments: impl IntoIterator<Item = (Fragment, FragmentId)>,
         ) -> Vec<(Fragment, FragmentId)> {
-            fragments
+            let inserted: Vec<_> = fragments
                 .into_iter()
                 .filter(|(fragment, id)| {
                     if self.entries.contains(id) {
                         false
                     } else {
-                        if self.entries.len() >= self.max_entries && self.entries.len() != 0 {
-                            // Remove an oldest entry from the pool
-                            self.entries.pop_back().expect("entry must exist");
+                        let arrival = Arrival(self.next_arrival);
+                        self.next_arrival += 1;
+                        self.entries.push_front(*id, (fragment.clone(), arrival));
+                        self.order.insert(*id, fragment, arrival);
+                        // Evict the last fragment in the order of the pool,
+                        // which may be the one just inserted
+                        let mut inserted = true;
+                        while self.entries.len() > self.max_entries {
+                            let evicted = self.evict().expect("entry must exist");
+                            inserted &= evicted != *id;
                         }
-                        self.entries.push_front(*id, fragment.clone());
-                        true
+                        inserted
                     }
                 })
                 // Truncate overflowing fragments
                 .take(self.max_entries)
+                .collect();
+            // a fragment may have been evicted by a later fragment of the same batch
+            inserted
+                .into_iter()
+                .filter(|(_, id)| self.entries.contains(id))
                 .collect()
         }
 
+        /// Put back the fragments returned by `remove_next` which were not selected,
+        /// in their original place in the order of the pool
+        pub fn put_back_all(
+            &mut self,
+            fragments: impl IntoIterator<Item = (Fragment, FragmentId, Arrival)>,
+        ) {
+            let mut fragments: Vec<_> = fragments.into_iter().collect();
+            // the fragments left in the pool are newer than the ones removed before them,
+            // the oldest fragment put back must end up at the back of the queue
+            fragments.sort_by_key(|(_, _, arrival)| Reverse(*arrival));
+            for (fragment, id, arrival) in fragments {
+                if self.entries.contains(&id) {
+                    continue;
+                }
+                self.order.insert(id, &fragment, arrival);
+                self.entries.push_back(id, (fragment, arrival));
+            }
+            // the other fragments taken from the pool were selected
+            if let Order::AccountFairness(order) = &mut self.order {
+                order.taken.clear();
+            }
+            while self.entries.len() > self.max_entries {
+                self.evict().expect("entry must exist");
+            }
+        }
+
         pub fn remove_all<'a>(&mut self, fragment_ids: impl IntoIterator<Item = &'a FragmentId>) {
             for fragment_id in fragment_ids {
-                self.entries.remove(fragment_id);
+                self.remove(fragment_id);
             }
         }
 
-        pub fn remove_oldest(&mut self) -> Option<(Fragment, FragmentId)> {
-            self.entries.pop_back().map(|(id, value)| (value, id))
+        fn remove(&mut self, id: &FragmentId) -> Option<(Fragment, Rank, Arrival)> {
+            let (fragment, arrival) = self.entries.remove(id)?;
+            let rank = self.order.remove(id)?;
+            Some((fragment, rank, arrival))
+        }
+
+        /// Remove the fragment that would be selected last, the oldest one with `OldestFirst`
+        fn evict(&mut self) -> Option<FragmentId> {
+            let id = match &self.order {
+                Order::OldestFirst => return self.entries.pop_back().map(|(id, _)| id),
+                Order::HighestFeeFirst(order) => order.last(),
+                Order::AccountFairness(order) => order.last(),
+                Order::TypePriority(order) => order.last(),
+            }?;
+            self.remove(&id);
+            Some(id)
+        }
+
+        /// Remove the next fragment to select, the rank it was selected with,
+        /// and its arrival to put it back if it is not selected
+        pub fn remove_next(&mut self) -> Option<(Fragment, FragmentId, Rank, Arrival)> {
+            let id = match &mut self.order {
+                Order::OldestFirst => {
+                    return self
+                        .entries
+                        .pop_back()
+                        .map(|(id, (fragment, arrival))| (fragment, id, Rank::Oldest, arrival))
+                }
+                Order::HighestFeeFirst(order) => order.first(),
+                Order::TypePriority(order) => order.first(),
+                Order::AccountFairness(order) => {
+                    let (id, account) = order.pop_first()?;
+                    let (fragment, arrival) = self.entries.remove(&id).expect("entry must exist");
+                    return Some((fragment, id, Rank::Account(account), arrival));
+                }
+            }?;
+            self.remove(&id)
+                .map(|(fragment, rank, arrival)| (fragment, id, rank, arrival))
         }
 
         pub fn len(&self) -> usize {
@@ -560,14 +915,153 @@ pub(super) mod internal {
                 (fragments2_in.2.clone(), fragments2_in.2.id()),
                 (fragments2_in.3.clone(), fragments2_in.3.id()),
             ];
-            let mut pool = Pool::new(4);
+            let mut pool = Pool::new(4, FragmentSelectionAlgorithmParams::OldestFirst);
             assert_eq!(fragments1, pool.insert_all(fragments1.clone()));
 
             assert_eq!(fragments2_expected, pool.insert_all(fragments2));
             for expected in final_expected.into_iter() {
-                assert_eq!(expected, pool.remove_oldest().unwrap());
+                let (fragment, id, _, _) = pool.remove_next().unwrap();
+                assert_eq!(expected, (fragment, id));
+            }
+            TestResult::from_bool(pool.remove_next().is_none())
+        }
+
+        fn distinct(fragments: Vec<Fragment>) -> Option<Vec<(Fragment, FragmentId)>> {
+            let fragments: Vec<_> = fragments
+                .into_iter()
+                .map(|fragment| {
+                    let id = fragment.id();
+                    (fragment, id)
+                })
+                .collect();
+            let ids: HashSet<_> = fragments.iter().map(|(_, id)| id).collect();
+            (ids.len() == fragments.len()).then_some(fragments)
+        }
+
+        #[quickcheck]
+        fn inserted_fragments_are_in_the_pool(fragments: Vec<Fragment>) -> TestResult {
+            let fragments = match distinct(fragments) {
+                Some(fragments) => fragments,
+                None => return TestResult::discard(),
+            };
+            let mut pool = Pool::new(2, FragmentSelectionAlgorithmParams::TypePriority);
+            let inserted = pool.insert_all(fragments);
+            TestResult::from_bool(
+                inserted.len() <= pool.len()
+                    && inserted.iter().all(|(_, id)| pool.entries.contains(id)),
+            )
+        }
+
+        #[quickcheck]
+        fn fragments_put_back_keep_their_place(
+            fragments: Vec<Fragment>,
+            taken: usize,
+        ) -> TestResult {
+            let fragments = match distinct(fragments) {
+                Some(fragments) => fragments,
+                None => return TestResult::discard(),
+            };
+            let taken = taken % (fragments.len() + 1);
+            for selection_alg in [
+                FragmentSelectionAlgorithmParams::OldestFirst,
+                FragmentSelectionAlgorithmParams::HighestFeeFirst,
+                FragmentSelectionAlgorithmParams::AccountFairness,
+                FragmentSelectionAlgorithmParams::TypePriority,
+            ] {
+                let mut pool = Pool::new(fragments.len(), selection_alg);
+                pool.insert_all(fragments.clone());
+                let mut put_back = Vec::new();
+                for _ in 0..taken {
+                    let (fragment, id, _, arrival) = pool.remove_next().unwrap();
+                    put_back.push((fragment, id, arrival));
+                }
+                let expected_order: Vec<_> = {
+                    let mut pool = Pool::new(fragments.len(), selection_alg);
+                    pool.insert_all(fragments.clone());
+                    std::iter::from_fn(|| pool.remove_next().map(|(_, id, _, _)| id)).collect()
+                };
+                put_back.reverse();
+                pool.put_back_all(put_back);
+                let order: Vec<_> =
+                    std::iter::from_fn(|| pool.remove_next().map(|(_, id, _, _)| id)).collect();
+                if order != expected_order {
+                    return TestResult::failed();
+                }
             }
-            TestResult::from_bool(pool.remove_oldest().is_none())
+            TestResult::passed()
+        }
+
+        fn id(n: u8) -> FragmentId {
+            FragmentId::from_bytes([n; 32])
+        }
+
+        #[test]
+        fn ranked_order_selects_highest_key_then_oldest() {
+            let mut order = RankedOrder::new();
+            order.insert(id(1), 10u64, None, Arrival(1));
+            order.insert(id(2), 30, None, Arrival(2));
+            order.insert(id(3), 30, None, Arrival(3));
+            order.insert(id(4), 10, None, Arrival(4));
+
+            assert_eq!(order.first(), Some(id(2)));
+            assert_eq!(order.last(), Some(id(4)));
+
+            assert_eq!(order.remove(&id(2)), Some(30));
+            assert_eq!(order.first(), Some(id(3)));
+            assert_eq!(order.remove(&id(2)), None);
+
+            order.remove(&id(4));
+            assert_eq!(order.last(), Some(id(1)));
+        }
+
+        #[test]
+        fn ranked_order_keeps_the_order_of_an_account() {
+            let alice = Some([1; INPUT_PTR_SIZE]);
+            let bob = Some([2; INPUT_PTR_SIZE]);
+            let mut order = RankedOrder::new();
+            // the fees of alice increase with the spending counter
+            order.insert(id(1), 10u64, alice, Arrival(1));
+            order.insert(id(2), 30, alice, Arrival(2));
+            order.insert(id(3), 20, bob, Arrival(3));
+
+            // alice is ranked last with its oldest fragment, and loses its newest one
+            assert_eq!(order.last(), Some(id(2)));
+
+            let selected: Vec<_> = std::iter::from_fn(|| {
+                let id = order.first()?;
+                order.remove(&id).map(|fee| (id, fee))
+            })
+            .collect();
+            assert_eq!(selected, vec![(id(3), 20), (id(1), 10), (id(2), 30)]);
+            assert!(order.accounts.is_empty());
+        }
+
+        #[test]
+        fn fair_order_takes_turns_between_accounts() {
+            let alice = Some([1; INPUT_PTR_SIZE]);
+            let bob = Some([2; INPUT_PTR_SIZE]);
+            let mut order = FairOrder::new();
+            order.insert(id(1), alice, Arrival(1));
+            order.insert(id(2), alice, Arrival(2));
+            order.insert(id(3), alice, Arrival(3));
+            order.insert(id(4), bob, Arrival(4));
+            order.insert(id(5), None, Arrival(5));
+
+            // the account with the most fragments loses its newest fragment
+            assert_eq!(order.last(), Some(id(3)));
+
+            let selected: Vec<_> = std::iter::from_fn(|| order.pop_first()).collect();
+            assert_eq!(
+                selected,
+                vec![
+                    (id(1), alice),
+                    (id(4), bob),
+                    (id(5), None),
+                    (id(2), alice),
+                    (id(3), alice),
+                ]
+            );
+            assert_eq!(order.last(), None);
         }
     }
 }
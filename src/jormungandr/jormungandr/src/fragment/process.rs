@@ -1,5 +1,5 @@
 use crate::{
-    fragment::{Logs, Pool},
+    fragment::{selection::FragmentSelectionAlgorithmParams, Logs, Pool},
     intercom::{NetworkMsg, TransactionMsg},
     metrics::{Metrics, MetricsBackend},
     utils::{
@@ -24,6 +24,7 @@ use tracing_futures::Instrument;
 pub struct Process {
     pool_max_entries: usize,
     logs_max_entries: usize,
+    selection_alg: FragmentSelectionAlgorithmParams,
     network_msg_box: MessageBox<NetworkMsg>,
 }
 
@@ -39,11 +40,13 @@ impl Process {
     pub fn new(
         pool_max_entries: usize,
         logs_max_entries: usize,
+        selection_alg: FragmentSelectionAlgorithmParams,
         network_msg_box: MessageBox<NetworkMsg>,
     ) -> Self {
         Process {
             pool_max_entries,
             logs_max_entries,
+            selection_alg,
             network_msg_box,
         }
     }
@@ -106,6 +109,7 @@ impl Process {
 
             let mut pool = Pool::new(
                 self.pool_max_entries,
+                self.selection_alg,
                 logs,
                 self.network_msg_box,
                 persistent_log,
@@ -168,13 +172,24 @@ impl Process {
                                     );
                                     reply_handle.reply_ok(statuses);
                                 }
+                                TransactionMsg::SubscribeStatuses(fragment_ids, reply_handle) => {
+                                    let statuses = pool
+                                        .logs()
+                                        .logs_by_ids(fragment_ids)
+                                        .into_iter()
+                                        .map(|(fragment_id, log)| {
+                                            (fragment_id, log.status().clone())
+                                        })
+                                        .collect();
+                                    let updates = pool.logs().subscribe();
+                                    reply_handle.reply_ok((statuses, updates));
+                                }
                                 TransactionMsg::BranchSwitch(fork_date) => {
                                     tracing::debug!(%fork_date, "pruning logs after branch switch");
                                     pool.prune_after_ledger_branch(fork_date);
                                 }
                                 TransactionMsg::SelectTransactions {
                                     ledger,
-                                    selection_alg,
                                     reply_handle,
                                     soft_deadline_future,
                                     hard_deadline_future,
@@ -182,13 +197,12 @@ impl Process {
                                     let span = span!(
                                         Level::DEBUG,
                                         "fragment_selection",
-                                        kind = "older_first",
+                                        kind = ?self.selection_alg,
                                     );
                                     async {
                                         let contents = pool
                                         .select(
                                             ledger,
-                                            selection_alg,
                                             soft_deadline_future,
                                             hard_deadline_future,
                                         )
@@ -1,13 +1,19 @@
-use super::{logs::Logs, pool::internal::Pool};
+use super::{
+    logs::Logs,
+    pool::internal::{Account, Pool, Rank},
+};
 use crate::{
-    blockcfg::{ApplyBlockLedger, Contents, ContentsBuilder},
+    blockcfg::{ApplyBlockLedger, Contents, ContentsBuilder, Value},
     fragment::{Fragment, FragmentId},
 };
 use async_trait::async_trait;
 use chain_core::property::Serialize;
+use chain_impl_mockchain::transaction::{InputEnum, Transaction};
 use futures::{channel::oneshot::Receiver, future::Shared, prelude::*};
-use jormungandr_lib::interfaces::{BlockDate, FragmentStatus};
-use std::{error::Error, iter};
+use jormungandr_lib::interfaces::{
+    BlockDate, FragmentPriority, FragmentSelectionReason, FragmentSelectionStrategy, FragmentStatus,
+};
+use std::{collections::HashMap, error::Error, iter};
 use tracing::{debug_span, Instrument};
 
 pub enum SelectionOutput {
@@ -35,25 +41,101 @@ pub struct FragmentSelectionResult {
     pub rejected_fragments_cnt: usize,
 }
 
-#[derive(Debug)]
+/// The order of the fragments in the pool, used both to select the fragments
+/// for a block and to evict fragments from a full pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FragmentSelectionAlgorithmParams {
     OldestFirst,
+    HighestFeeFirst,
+    AccountFairness,
+    TypePriority,
+}
+
+impl From<FragmentSelectionStrategy> for FragmentSelectionAlgorithmParams {
+    fn from(strategy: FragmentSelectionStrategy) -> Self {
+        match strategy {
+            FragmentSelectionStrategy::OldestFirst => Self::OldestFirst,
+            FragmentSelectionStrategy::HighestFeeFirst => Self::HighestFeeFirst,
+            FragmentSelectionStrategy::AccountFairness => Self::AccountFairness,
+            FragmentSelectionStrategy::TypePriority => Self::TypePriority,
+        }
+    }
 }
 
-pub struct OldestFirst;
+/// Selects the fragments in the order of the pool
+pub struct InPoolOrder;
 
-impl OldestFirst {
+impl InPoolOrder {
     pub fn new() -> Self {
-        OldestFirst
+        InPoolOrder
     }
 }
 
-impl Default for OldestFirst {
+impl Default for InPoolOrder {
     fn default() -> Self {
         Self::new()
     }
 }
 
+pub fn fragment_priority(fragment: &Fragment) -> FragmentPriority {
+    match fragment {
+        Fragment::VotePlan(_)
+        | Fragment::VoteTally(_)
+        | Fragment::UpdateProposal(_)
+        | Fragment::UpdateVote(_) => FragmentPriority::Governance,
+        Fragment::PoolRegistration(_)
+        | Fragment::PoolRetirement(_)
+        | Fragment::PoolUpdate(_)
+        | Fragment::StakeDelegation(_)
+        | Fragment::OwnerStakeDelegation(_)
+        | Fragment::MintToken(_)
+        | Fragment::EvmMapping(_) => FragmentPriority::Certificate,
+        Fragment::Initial(_)
+        | Fragment::OldUtxoDeclaration(_)
+        | Fragment::Transaction(_)
+        | Fragment::Evm(_) => FragmentPriority::Transaction,
+        Fragment::VoteCast(_) => FragmentPriority::Vote,
+    }
+}
+
+/// The fee paid by the fragment and the first account it spends from
+pub fn fragment_fee_and_account(fragment: &Fragment) -> (Value, Account) {
+    match fragment {
+        Fragment::Initial(_) | Fragment::OldUtxoDeclaration(_) | Fragment::Evm(_) => {
+            (Value::zero(), None)
+        }
+        Fragment::Transaction(ref tx) => transaction_fee_and_account(tx),
+        Fragment::StakeDelegation(ref tx) => transaction_fee_and_account(tx),
+        Fragment::OwnerStakeDelegation(ref tx) => transaction_fee_and_account(tx),
+        Fragment::PoolRegistration(ref tx) => transaction_fee_and_account(tx),
+        Fragment::PoolRetirement(ref tx) => transaction_fee_and_account(tx),
+        Fragment::PoolUpdate(ref tx) => transaction_fee_and_account(tx),
+        Fragment::UpdateProposal(ref tx) => transaction_fee_and_account(tx),
+        Fragment::UpdateVote(ref tx) => transaction_fee_and_account(tx),
+        Fragment::VotePlan(ref tx) => transaction_fee_and_account(tx),
+        Fragment::VoteCast(ref tx) => transaction_fee_and_account(tx),
+        Fragment::VoteTally(ref tx) => transaction_fee_and_account(tx),
+        Fragment::MintToken(ref tx) => transaction_fee_and_account(tx),
+        Fragment::EvmMapping(ref tx) => transaction_fee_and_account(tx),
+    }
+}
+
+fn transaction_fee_and_account<E>(tx: &Transaction<E>) -> (Value, Account) {
+    let fee = tx
+        .total_input()
+        .and_then(|input| input.checked_sub(tx.total_output()?))
+        .unwrap_or_else(|_| Value::zero());
+    let account = tx
+        .as_slice()
+        .inputs()
+        .iter()
+        .find_map(|input| match input.to_enum() {
+            InputEnum::AccountInput(account, _) => Some(account.into()),
+            InputEnum::UtxoInput(_) => None,
+        });
+    (fee, account)
+}
+
 enum ApplyFragmentError {
     DoesNotFit,
     SoftDeadlineReached,
@@ -137,7 +219,7 @@ async fn try_apply_fragment(
 }
 
 #[async_trait]
-impl FragmentSelectionAlgorithm for OldestFirst {
+impl FragmentSelectionAlgorithm for InPoolOrder {
     async fn select(
         &mut self,
         mut ledger: ApplyBlockLedger,
@@ -151,10 +233,11 @@ impl FragmentSelectionAlgorithm for OldestFirst {
         let mut contents_builder = ContentsBuilder::new();
         let mut return_to_pool = Vec::new();
         let mut rejected_fragments_cnt = 0;
+        let mut account_turns = HashMap::new();
 
         let soft_deadline_future = soft_deadline_future.shared();
         let hard_deadline_future = hard_deadline_future.shared();
-        while let Some((fragment, id)) = pool.remove_oldest() {
+        while let Some((fragment, id, rank, arrival)) = pool.remove_next() {
             let span = debug_span!("fragment", hash=%id.to_string());
 
             async {
@@ -173,12 +256,27 @@ impl FragmentSelectionAlgorithm for OldestFirst {
                     }) => {
                         contents_builder.push(fragment);
                         ledger = ledger_new;
-                        tracing::debug!("successfully applied and committed the fragment");
+                        let reason = match rank {
+                            Rank::Oldest => FragmentSelectionReason::OldestFirst,
+                            Rank::Fee(fee) => {
+                                FragmentSelectionReason::HighestFeeFirst { fee: fee.0 }
+                            }
+                            Rank::Account(account) => {
+                                let turn = account_turns.entry(account).or_insert(0);
+                                *turn += 1;
+                                FragmentSelectionReason::AccountFairness { turn: *turn }
+                            }
+                            Rank::Priority(priority) => {
+                                FragmentSelectionReason::TypePriority { priority }
+                            }
+                        };
+                        tracing::debug!(?reason, "successfully applied and committed the fragment");
+                        logs.set_selection_reason(id, reason);
                         space_left = space_left_new;
                     }
                     Err(ApplyFragmentError::DoesNotFit)
                     | Err(ApplyFragmentError::SoftDeadlineReached) => {
-                        return_to_pool.push((fragment, id));
+                        return_to_pool.push((fragment, id, arrival));
                     }
                     Err(ApplyFragmentError::Rejected(reason)) => {
                         tracing::debug!(%reason, "fragment is rejected");
@@ -200,7 +298,7 @@ impl FragmentSelectionAlgorithm for OldestFirst {
             "finished block creation with {} fragments left in the pool",
             pool.len()
         );
-        pool.insert_all(return_to_pool);
+        pool.put_back_all(return_to_pool);
 
         FragmentSelectionResult {
             contents: contents_builder.into(),
@@ -1,7 +1,7 @@
 use crate::{
     blockcfg::{ApplyBlockLedger, Block, Fragment, FragmentId, Header, HeaderHash},
     blockchain::{Checkpoints, LeadershipBlock, StorageError},
-    fragment::selection::FragmentSelectionAlgorithmParams,
+    fragment::StatusUpdate,
     network::p2p::comm::PeerInfo,
     topology::{Gossips, NodeId, Peer, PeerInfo as TopologyPeerInfo, View},
     utils::async_msg::{self, MessageBox, MessageQueue},
@@ -25,6 +25,7 @@ use std::{
     pin::Pin,
     task::{Context, Poll},
 };
+use tokio::sync::broadcast;
 
 /// The error values passed via intercom messages.
 #[derive(Debug)]
@@ -515,9 +516,17 @@ pub enum TransactionMsg {
         Vec<FragmentId>,
         ReplyHandle<HashMap<FragmentId, FragmentStatus>>,
     ),
+    /// The statuses of the given fragments, with the changes of the statuses
+    /// of all the logged fragments from then on
+    SubscribeStatuses(
+        Vec<FragmentId>,
+        ReplyHandle<(
+            HashMap<FragmentId, FragmentStatus>,
+            broadcast::Receiver<StatusUpdate>,
+        )>,
+    ),
     SelectTransactions {
         ledger: ApplyBlockLedger,
-        selection_alg: FragmentSelectionAlgorithmParams,
         reply_handle: ReplyHandle<(FragmentContents, ApplyBlockLedger)>,
         soft_deadline_future: futures::channel::oneshot::Receiver<()>,
         hard_deadline_future: futures::channel::oneshot::Receiver<()>,
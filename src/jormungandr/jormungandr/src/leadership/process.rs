@@ -695,13 +695,10 @@ async fn prepare_block(
     soft_deadline_future: futures::channel::oneshot::Receiver<()>,
     hard_deadline_future: futures::channel::oneshot::Receiver<()>,
 ) -> Result<(Contents, ApplyBlockLedger), LeadershipError> {
-    use crate::fragment::selection::FragmentSelectionAlgorithmParams;
-
     let (reply_handle, reply_future) = unary_reply();
 
     let msg = TransactionMsg::SelectTransactions {
         ledger,
-        selection_alg: FragmentSelectionAlgorithmParams::OldestFirst,
         reply_handle,
         soft_deadline_future,
         hard_deadline_future,
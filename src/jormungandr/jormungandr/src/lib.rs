@@ -293,6 +293,7 @@ fn start_services(bootstrapped_node: BootstrappedNode) -> Result<(), start_up::E
         let process = fragment::Process::new(
             bootstrapped_node.settings.mempool.pool_max_entries.into(),
             bootstrapped_node.settings.mempool.log_max_entries.into(),
+            bootstrapped_node.settings.mempool.selection_strategy.into(),
             network_msgbox.clone(),
         );
         let fragment_log_dir = bootstrapped_node
@@ -437,9 +438,16 @@ async fn bootstrap_internal(
 
     let cache_capacity = 102_400;
 
-    let (blockchain, blockchain_tip) =
-        start_up::load_blockchain(block0, storage, cache_capacity, settings.rewards_report_all)
-            .await?;
+    let ledger_snapshots = start_up::prepare_ledger_snapshots(&settings);
+
+    let (blockchain, blockchain_tip) = start_up::load_blockchain(
+        block0,
+        storage,
+        ledger_snapshots,
+        cache_capacity,
+        settings.rewards_report_all,
+    )
+    .await?;
 
     if let Some(context) = &context {
         let mut context = context.write().await;
@@ -631,6 +639,7 @@ fn initialize_node() -> Result<InitializedNode, start_up::Error> {
                 listen: rest_config.listen,
                 tls: rest_config.tls,
                 cors: rest_config.cors,
+                admin: rest_config.admin,
                 #[cfg(feature = "prometheus-metrics")]
                 enable_prometheus: settings.prometheus,
             };
@@ -5,58 +5,88 @@ pub mod v0;
 mod v1;
 
 use crate::context::{Context, ContextLock, ServerStopper};
-use futures::{channel::mpsc, prelude::*};
-use jormungandr_lib::interfaces::{Cors, Tls};
+use futures::{channel::mpsc, future, prelude::*};
+use jormungandr_lib::interfaces::{Cors, RestAdmin, Tls};
 use std::{error::Error, net::SocketAddr, time::Duration};
-use warp::Filter;
+use warp::{
+    filters::trace::{Info, Trace},
+    Filter,
+};
 
 pub struct Config {
     pub listen: SocketAddr,
     pub tls: Option<Tls>,
     pub cors: Option<Cors>,
+    pub admin: Option<RestAdmin>,
     #[cfg(feature = "prometheus-metrics")]
     pub enable_prometheus: bool,
 }
 
-pub async fn start_rest_server(config: Config, context: ContextLock) {
+pub async fn start_rest_server(mut config: Config, context: ContextLock) {
     let (stopper_tx, stopper_rx) = mpsc::channel::<()>(0);
-    let stopper_rx = stopper_rx.into_future().map(|_| ());
+    let stopper_rx = stopper_rx.into_future().map(|_| ()).shared();
     context
         .write()
         .await
         .set_rest_server_stopper(ServerStopper::new(stopper_tx));
-    let api = v0::filter(context.clone()).or(v1::filter(context.clone()));
-
-    let api = warp::path!("api" / ..)
-        .and(api)
-        .with(warp::filters::trace::trace(|info| {
-            use http_zipkin::get_trace_context;
-            use tracing::field::Empty;
-            let span = tracing::span!(
-                tracing::Level::DEBUG,
-                "rest_api_request",
-                method = %info.method(),
-                path = info.path(),
-                version = ?info.version(),
-                remote_addr = Empty,
-                trace_id = Empty,
-                span_id = Empty,
-                parent_span_id = Empty,
+
+    match config.admin.take() {
+        Some(admin) => {
+            let api = v0::filter(context.clone()).or(v1::filter(context.clone()));
+            let api = warp::path!("api" / ..).and(api).with(trace_requests());
+
+            let admin_api = warp::path!("api" / ..)
+                .and(v0::admin_filter(context.clone()))
+                .with(trace_requests());
+
+            tracing::info!(
+                listen_address = %admin.listen,
+                "listening for REST API management requests"
             );
-            if let Some(remote_addr) = info.remote_addr() {
-                span.record("remote_addr", remote_addr.to_string().as_str());
-            }
-            if let Some(trace_context) = get_trace_context(info.request_headers()) {
-                span.record("trace_id", trace_context.trace_id().to_string().as_str());
-                span.record("span_id", trace_context.span_id().to_string().as_str());
-                if let Some(parent_span_id) = trace_context.parent_id() {
-                    span.record("parent_span_id", parent_span_id.to_string().as_str());
-                }
-            }
-            span
-        }));
+            future::join(
+                setup_prometheus(api, config, context, stopper_rx.clone()),
+                run_server_with_app(admin_api, admin.listen, admin.tls, stopper_rx),
+            )
+            .await;
+        }
+        None => {
+            let api = v0::filter(context.clone())
+                .or(v0::admin_filter(context.clone()))
+                .or(v1::filter(context.clone()));
+            let api = warp::path!("api" / ..).and(api).with(trace_requests());
+
+            setup_prometheus(api, config, context, stopper_rx).await;
+        }
+    }
+}
 
-    setup_prometheus(api, config, context, stopper_rx).await;
+fn trace_requests() -> Trace<impl Fn(Info) -> tracing::Span + Clone> {
+    warp::filters::trace::trace(|info| {
+        use http_zipkin::get_trace_context;
+        use tracing::field::Empty;
+        let span = tracing::span!(
+            tracing::Level::DEBUG,
+            "rest_api_request",
+            method = %info.method(),
+            path = info.path(),
+            version = ?info.version(),
+            remote_addr = Empty,
+            trace_id = Empty,
+            span_id = Empty,
+            parent_span_id = Empty,
+        );
+        if let Some(remote_addr) = info.remote_addr() {
+            span.record("remote_addr", remote_addr.to_string().as_str());
+        }
+        if let Some(trace_context) = get_trace_context(info.request_headers()) {
+            span.record("trace_id", trace_context.trace_id().to_string().as_str());
+            span.record("span_id", trace_context.span_id().to_string().as_str());
+            if let Some(parent_span_id) = trace_context.parent_id() {
+                span.record("parent_span_id", parent_span_id.to_string().as_str());
+            }
+        }
+        span
+    })
 }
 
 #[cfg(feature = "prometheus-metrics")]
@@ -144,11 +174,15 @@ async fn run_server_with_app<App>(
 {
     let server = warp::serve(app);
     if let Some(tls_config) = tls_config {
-        let (_, server_fut) = server
+        let server = server
             .tls()
             .cert_path(tls_config.cert_file)
-            .key_path(tls_config.priv_key_file)
-            .bind_with_graceful_shutdown(listen_addr, shutdown_signal);
+            .key_path(tls_config.priv_key_file);
+        let server = match tls_config.client_ca_file {
+            Some(client_ca_file) => server.client_auth_required_path(client_ca_file),
+            None => server,
+        };
+        let (_, server_fut) = server.bind_with_graceful_shutdown(listen_addr, shutdown_signal);
         server_fut.await;
     } else {
         let (_, server_fut) = server.bind_with_graceful_shutdown(listen_addr, shutdown_signal);
@@ -4,18 +4,13 @@ pub mod logic;
 use crate::rest::{display_internal_server_error, ContextLock};
 use warp::{http::StatusCode, Filter, Rejection, Reply};
 
+/// The endpoints which do not need to be restricted
 pub fn filter(
     context: ContextLock,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     let with_context = warp::any().map(move || context.clone());
     let root = warp::path!("v0" / ..);
 
-    let shutdown = warp::path!("shutdown")
-        .and(warp::get().or(warp::post()))
-        .and(with_context.clone())
-        .and_then(|_, context| handlers::shutdown(context))
-        .boxed();
-
     let account = warp::path!("account" / String)
         .and(warp::get())
         .and(with_context.clone())
@@ -53,79 +48,6 @@ pub fn filter(
         root.and(logs).boxed()
     };
 
-    let leaders = {
-        let root = warp::path!("leaders" / ..).boxed();
-
-        let logs = warp::path!("logs")
-            .and(warp::get())
-            .and(with_context.clone())
-            .and_then(handlers::get_leaders_logs)
-            .boxed();
-
-        root.and(logs).boxed()
-    };
-
-    let p2p = {
-        let root = warp::path!("p2p" / ..);
-
-        let quarantined = warp::path!("quarantined")
-            .and(warp::get())
-            .and(with_context.clone())
-            .and_then(handlers::get_network_p2p_quarantined)
-            .boxed();
-
-        let non_public = warp::path!("non_public")
-            .and(warp::get())
-            .and(with_context.clone())
-            .and_then(handlers::get_network_p2p_non_public)
-            .boxed();
-
-        let available = warp::path!("available")
-            .and(warp::get())
-            .and(with_context.clone())
-            .and_then(handlers::get_network_p2p_available)
-            .boxed();
-
-        let view = {
-            let root = warp::path!("view" / ..);
-
-            let view = warp::path::end()
-                .and(warp::get())
-                .and(with_context.clone())
-                .and_then(handlers::get_network_p2p_view)
-                .boxed();
-
-            let view_topic = warp::path!(String)
-                .and(warp::get())
-                .and(with_context.clone())
-                .and_then(handlers::get_network_p2p_view_topic)
-                .boxed();
-
-            root.and(view.or(view_topic)).boxed()
-        };
-
-        root.and(quarantined.or(non_public).or(available).or(view))
-            .boxed()
-    };
-
-    let network = {
-        let root = warp::path!("network" / ..);
-
-        let stats = warp::path!("stats")
-            .and(warp::get())
-            .and(with_context.clone())
-            .and_then(handlers::get_network_stats)
-            .boxed();
-
-        root.and(stats.or(p2p)).boxed()
-    };
-
-    let settings = warp::path!("settings")
-        .and(warp::get())
-        .and(with_context.clone())
-        .and_then(handlers::get_settings)
-        .boxed();
-
     let stake = {
         let root = warp::path!("stake" / ..);
 
@@ -205,12 +127,6 @@ pub fn filter(
         .and_then(handlers::get_utxo)
         .boxed();
 
-    let diagnostic = warp::path!("diagnostic")
-        .and(warp::get())
-        .and(with_context.clone())
-        .and_then(handlers::get_diagnostic)
-        .boxed();
-
     let updates = warp::path!("updates" / "active")
         .and(warp::get())
         .and(with_context.clone())
@@ -233,13 +149,9 @@ pub fn filter(
         root.and(committees.or(vote_plans)).boxed()
     };
 
-    let routes = shutdown
-        .or(account)
+    let routes = account
         .or(block)
         .or(fragment)
-        .or(leaders)
-        .or(network)
-        .or(settings)
         .or(stake)
         .or(stake_pools)
         .or(stake_pool)
@@ -248,13 +160,109 @@ pub fn filter(
         .or(tip)
         .or(rewards)
         .or(utxo)
-        .or(diagnostic)
         .or(updates)
         .or(votes);
 
     root.and(routes.boxed()).recover(handle_rejection).boxed()
 }
 
+/// The node management endpoints
+pub fn admin_filter(
+    context: ContextLock,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let with_context = warp::any().map(move || context.clone());
+    let root = warp::path!("v0" / ..);
+
+    let shutdown = warp::path!("shutdown")
+        .and(warp::get().or(warp::post()))
+        .and(with_context.clone())
+        .and_then(|_, context| handlers::shutdown(context))
+        .boxed();
+
+    let leaders = {
+        let root = warp::path!("leaders" / ..).boxed();
+
+        let logs = warp::path!("logs")
+            .and(warp::get())
+            .and(with_context.clone())
+            .and_then(handlers::get_leaders_logs)
+            .boxed();
+
+        root.and(logs).boxed()
+    };
+
+    let p2p = {
+        let root = warp::path!("p2p" / ..);
+
+        let quarantined = warp::path!("quarantined")
+            .and(warp::get())
+            .and(with_context.clone())
+            .and_then(handlers::get_network_p2p_quarantined)
+            .boxed();
+
+        let non_public = warp::path!("non_public")
+            .and(warp::get())
+            .and(with_context.clone())
+            .and_then(handlers::get_network_p2p_non_public)
+            .boxed();
+
+        let available = warp::path!("available")
+            .and(warp::get())
+            .and(with_context.clone())
+            .and_then(handlers::get_network_p2p_available)
+            .boxed();
+
+        let view = {
+            let root = warp::path!("view" / ..);
+
+            let view = warp::path::end()
+                .and(warp::get())
+                .and(with_context.clone())
+                .and_then(handlers::get_network_p2p_view)
+                .boxed();
+
+            let view_topic = warp::path!(String)
+                .and(warp::get())
+                .and(with_context.clone())
+                .and_then(handlers::get_network_p2p_view_topic)
+                .boxed();
+
+            root.and(view.or(view_topic)).boxed()
+        };
+
+        root.and(quarantined.or(non_public).or(available).or(view))
+            .boxed()
+    };
+
+    let network = {
+        let root = warp::path!("network" / ..);
+
+        let stats = warp::path!("stats")
+            .and(warp::get())
+            .and(with_context.clone())
+            .and_then(handlers::get_network_stats)
+            .boxed();
+
+        root.and(stats.or(p2p)).boxed()
+    };
+
+    let settings = warp::path!("settings")
+        .and(warp::get())
+        .and(with_context.clone())
+        .and_then(handlers::get_settings)
+        .boxed();
+
+    let diagnostic = warp::path!("diagnostic")
+        .and(warp::get())
+        .and(with_context)
+        .and_then(handlers::get_diagnostic)
+        .boxed();
+
+    let routes = shutdown.or(leaders).or(network).or(settings).or(diagnostic);
+
+    root.and(routes.boxed()).recover(handle_rejection).boxed()
+}
+
 /// Convert rejections to actual HTTP errors
 async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
     if let Some(err) = err.find::<logic::Error>() {
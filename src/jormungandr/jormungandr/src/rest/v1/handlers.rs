@@ -1,6 +1,9 @@
 use crate::rest::{v1::logic, ContextLock};
+use futures::StreamExt;
 use jormungandr_lib::interfaces::{FragmentsBatch, VotePlanId};
-use warp::{reject::Reject, Rejection, Reply};
+use std::convert::Infallible;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use warp::{reject::Reject, sse::Event, Rejection, Reply};
 
 impl Reject for logic::Error {}
 
@@ -32,6 +35,38 @@ pub async fn get_fragment_statuses(
         .map(|r| warp::reply::json(&r))
 }
 
+#[derive(Deserialize)]
+pub struct StreamFragmentStatusesQuery {
+    #[serde(default)]
+    fragment_ids: String,
+    #[serde(default)]
+    accounts: String,
+}
+
+pub async fn stream_fragment_statuses(
+    query: StreamFragmentStatusesQuery,
+    context: ContextLock,
+) -> Result<impl Reply, Rejection> {
+    let context = context.read().await;
+    let fragment_ids = query.fragment_ids.split(',').filter(|id| !id.is_empty());
+    let accounts = query.accounts.split(',').filter(|id| !id.is_empty());
+    let updates = logic::subscribe_fragment_statuses(&context, fragment_ids, accounts)
+        .await
+        .map_err(warp::reject::custom)?;
+
+    let events = updates.map(|update| {
+        let event = match update {
+            Ok(update) => Event::default().event("status").json_data(update).unwrap(),
+            // the client missed some updates and has to query the statuses again
+            Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                Event::default().event("lagged").data(missed.to_string())
+            }
+        };
+        Ok::<_, Infallible>(event)
+    });
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(events)))
+}
+
 pub async fn get_fragment_logs(context: ContextLock) -> Result<impl Reply, Rejection> {
     let context = context.read().await;
     logic::get_fragment_logs(&context)
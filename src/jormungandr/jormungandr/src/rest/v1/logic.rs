@@ -9,19 +9,25 @@ use chain_crypto::{
 use chain_impl_mockchain::{
     account::{AccountAlg, Identifier},
     fragment::FragmentId,
-    transaction::UnspecifiedAccountIdentifier,
+    transaction::{UnspecifiedAccountIdentifier, INPUT_PTR_SIZE},
     value::ValueError,
 };
 use futures::{
     channel::mpsc::{SendError, TrySendError},
     prelude::*,
+    stream::{self, BoxStream},
 };
 use hex::ToHex;
 use jormungandr_lib::interfaces::{
-    AccountVotes, FragmentLog, FragmentOrigin, FragmentStatus, FragmentsBatch,
-    FragmentsProcessingSummary, VotePlanId,
+    AccountVotes, FragmentLog, FragmentOrigin, FragmentStatus, FragmentStatusUpdate,
+    FragmentsBatch, FragmentsProcessingSummary, VotePlanId,
 };
-use std::{collections::HashMap, convert::TryInto, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+    str::FromStr,
+};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
 use tracing::{span, Level};
 use tracing_futures::Instrument;
 
@@ -91,6 +97,72 @@ pub async fn get_fragment_statuses<'a>(
     .await
 }
 
+/// The current statuses of the given fragments followed by their changes,
+/// and the changes of the statuses of the fragments spending from the given
+/// accounts. All the changes are streamed if no fragment or account is given.
+pub async fn subscribe_fragment_statuses<'a>(
+    context: &Context,
+    ids: impl IntoIterator<Item = &'a str>,
+    accounts: impl IntoIterator<Item = &'a str>,
+) -> Result<BoxStream<'static, Result<FragmentStatusUpdate, BroadcastStreamRecvError>>, Error> {
+    let ids = ids
+        .into_iter()
+        .map(FragmentId::from_str)
+        .collect::<Result<HashSet<_>, _>>()?;
+    let accounts = accounts
+        .into_iter()
+        .map(|id_hex| {
+            parse_account_id(id_hex)
+                .map(|id| UnspecifiedAccountIdentifier::from_single_account(id).into())
+        })
+        .collect::<Result<HashSet<[u8; INPUT_PTR_SIZE]>, _>>()?;
+    let span = span!(parent: context.span()?, Level::TRACE, "fragment_statuses", request = "subscribe_statuses");
+    let (statuses, updates) = async {
+        let (reply_handle, reply_future) = intercom::unary_reply();
+        let mut mbox = context.try_full()?.transaction_task.clone();
+        mbox.send(TransactionMsg::SubscribeStatuses(
+            ids.iter().cloned().collect(),
+            reply_handle,
+        ))
+        .await
+        .map_err(|e| {
+            tracing::debug!(reason = %e, "error subscribing to fragment statuses");
+            Error::MsgSend(e)
+        })?;
+        reply_future.await.map_err(Error::from)
+    }
+    .instrument(span)
+    .await?;
+
+    let subscribe_all = ids.is_empty() && accounts.is_empty();
+    let statuses = statuses.into_iter().map(|(fragment_id, status)| {
+        Ok(FragmentStatusUpdate {
+            fragment_id: fragment_id.into(),
+            status,
+        })
+    });
+    let updates = BroadcastStream::new(updates).filter_map(move |update| {
+        future::ready(match update {
+            Ok(update)
+                if subscribe_all
+                    || ids.contains(&update.fragment_id)
+                    || update
+                        .account
+                        .map_or(false, |account| accounts.contains(&account)) =>
+            {
+                Some(Ok(FragmentStatusUpdate {
+                    fragment_id: update.fragment_id.into(),
+                    status: update.status,
+                }))
+            }
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+    });
+
+    Ok(stream::iter(statuses).chain(updates).boxed())
+}
+
 pub async fn post_fragments(
     context: &Context,
     batch: FragmentsBatch,
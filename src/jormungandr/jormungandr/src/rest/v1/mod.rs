@@ -28,13 +28,20 @@ pub fn filter(
             .and_then(handlers::get_fragment_statuses)
             .boxed();
 
+        let status_stream = warp::path!("statuses" / "stream")
+            .and(warp::get())
+            .and(warp::query())
+            .and(with_context.clone())
+            .and_then(handlers::stream_fragment_statuses)
+            .boxed();
+
         let logs = warp::path!("logs")
             .and(warp::get())
             .and(with_context.clone())
             .and_then(handlers::get_fragment_logs)
             .boxed();
 
-        root.and(post.or(status).or(logs)).boxed()
+        root.and(post.or(status).or(status_stream).or(logs)).boxed()
     };
 
     let votes_with_plan = warp::path!("votes" / "plan" / VotePlanId / "account-votes" / String)
@@ -10,7 +10,7 @@ pub use jormungandr_lib::interfaces::{Cors, JRpc, LayersConfig, Rest, Tls, Trust
 use jormungandr_lib::{interfaces::Mempool, time::Duration};
 use multiaddr::Multiaddr;
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
-use std::path::PathBuf;
+use std::{num::NonZeroUsize, path::PathBuf};
 use tracing::level_filters::LevelFilter;
 
 #[derive(Debug, Deserialize)]
@@ -18,6 +18,9 @@ use tracing::level_filters::LevelFilter;
 pub struct Config {
     pub secret_file: Option<PathBuf>,
     pub storage: Option<PathBuf>,
+    /// the database holding the volatile part of the block storage
+    #[serde(default)]
+    pub storage_backend: StorageBackend,
     pub log: Option<ConfigLogSettings>,
 
     /// setting of the mempool, fragment logs and related data
@@ -51,6 +54,11 @@ pub struct Config {
     pub skip_bootstrap: bool,
 
     pub block_hard_deadline: Option<u32>,
+
+    /// periodic snapshots of the ledger, to resume the node without
+    /// applying all the blocks of the storage again
+    #[serde(default)]
+    pub ledger_snapshots: LedgerSnapshotsConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -165,12 +173,39 @@ pub struct Leadership {
     pub logs_capacity: usize,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    #[default]
+    Sled,
+    Redb,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LedgerSnapshotsConfig {
+    /// write a snapshot of the ledger at the end of every epoch, once it is
+    /// deeper than the epoch stability depth. Only with a persistent storage.
+    pub enabled: bool,
+    /// the number of snapshots kept, the oldest ones are removed
+    pub keep: NonZeroUsize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct Prometheus {
     pub enabled: bool,
 }
 
+impl From<StorageBackend> for chain_storage::BackendKind {
+    fn from(backend: StorageBackend) -> Self {
+        match backend {
+            StorageBackend::Sled => chain_storage::BackendKind::Sled,
+            StorageBackend::Redb => chain_storage::BackendKind::Redb,
+        }
+    }
+}
+
 impl Default for Leadership {
     fn default() -> Self {
         Leadership {
@@ -179,6 +214,15 @@ impl Default for Leadership {
     }
 }
 
+impl Default for LedgerSnapshotsConfig {
+    fn default() -> Self {
+        LedgerSnapshotsConfig {
+            enabled: true,
+            keep: NonZeroUsize::new(2).unwrap(),
+        }
+    }
+}
+
 mod filter_level_opt_serde {
     use super::*;
 
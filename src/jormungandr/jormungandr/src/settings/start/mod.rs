@@ -2,7 +2,7 @@ pub mod config;
 pub mod network;
 
 use self::{
-    config::{Config, Leadership},
+    config::{Config, Leadership, LedgerSnapshotsConfig, StorageBackend},
     network::{Protocol, TrustedPeer},
 };
 use crate::{
@@ -38,6 +38,7 @@ pub enum Error {
 pub struct Settings {
     pub network: network::Configuration,
     pub storage: Option<PathBuf>,
+    pub storage_backend: StorageBackend,
     pub block_0: Block0Info,
     pub secret: Option<PathBuf>,
     pub rest: Option<Rest>,
@@ -49,6 +50,7 @@ pub struct Settings {
     pub prometheus: bool,
     pub no_blockchain_updates_warning_interval: std::time::Duration,
     pub block_hard_deadline: u32,
+    pub ledger_snapshots: LedgerSnapshotsConfig,
 }
 
 pub struct RawSettings {
@@ -120,6 +122,7 @@ impl RawSettings {
                 listen: cmd_listen,
                 tls: None,
                 cors: None,
+                admin: None,
             }),
             (None, None) => None,
         }
@@ -190,6 +193,9 @@ impl RawSettings {
 
         Ok(Settings {
             storage,
+            storage_backend: config
+                .as_ref()
+                .map_or(StorageBackend::default(), |cfg| cfg.storage_backend),
             block_0,
             network,
             secret,
@@ -215,6 +221,11 @@ impl RawSettings {
                 .as_ref()
                 .and_then(|config| config.block_hard_deadline)
                 .unwrap_or(DEFAULT_BLOCK_HARD_DEADLINE),
+            ledger_snapshots: config
+                .as_ref()
+                .map_or(LedgerSnapshotsConfig::default(), |cfg| {
+                    cfg.ledger_snapshots.clone()
+                }),
         })
     }
 }
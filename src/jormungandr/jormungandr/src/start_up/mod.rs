@@ -3,13 +3,16 @@ mod error;
 pub use self::error::{Error, ErrorKind};
 use crate::{
     blockcfg::{Block, HeaderId},
-    blockchain::{Blockchain, Error as BlockchainError, Storage, Tip},
+    blockchain::{Blockchain, Error as BlockchainError, LedgerSnapshots, Storage, Tip},
     network,
     settings::start::Settings,
 };
 use chain_core::packer::Codec;
 use tracing::{span, Level};
 
+/// name of the directory of the ledger snapshots, in the storage directory
+const LEDGER_SNAPSHOTS_DIRECTORY: &str = "ledger-snapshots";
+
 /// prepare the block storage from the given settings
 pub fn prepare_storage(setting: &Settings) -> Result<Storage, Error> {
     let span = span!(Level::TRACE, "sub_task", kind = "storage");
@@ -21,14 +24,31 @@ pub fn prepare_storage(setting: &Settings) -> Result<Storage, Error> {
             reason: ErrorKind::BlockStorage,
         })?;
 
-        tracing::info!("storing blockchain in '{:?}'", dir);
+        tracing::info!(
+            "storing blockchain in '{:?}' with the {:?} backend",
+            dir,
+            setting.storage_backend
+        );
 
-        Storage::file(dir, storage_span).map_err(Into::into)
+        Storage::file(dir, setting.storage_backend.into(), storage_span).map_err(Into::into)
     } else {
         Storage::memory(storage_span).map_err(Into::into)
     }
 }
 
+/// prepare the ledger snapshots, stored along the block storage
+pub fn prepare_ledger_snapshots(setting: &Settings) -> Option<LedgerSnapshots> {
+    if !setting.ledger_snapshots.enabled {
+        return None;
+    }
+    setting.storage.as_ref().map(|dir| {
+        LedgerSnapshots::new(
+            dir.join(LEDGER_SNAPSHOTS_DIRECTORY),
+            setting.ledger_snapshots.keep,
+        )
+    })
+}
+
 /// Try to fetch the block0_id from the HTTP base URL (services) in the array
 ///
 /// The HTTP url is expecting to be of the form: `URL/<hash-id>.block0`
@@ -150,6 +170,7 @@ pub async fn prepare_block_0(settings: &Settings, storage: &Storage) -> Result<B
 pub async fn load_blockchain(
     block0: Block,
     storage: Storage,
+    ledger_snapshots: Option<LedgerSnapshots>,
     cache_capacity: usize,
     rewards_report_all: bool,
 ) -> Result<(Blockchain, Tip), Error> {
@@ -158,6 +179,7 @@ pub async fn load_blockchain(
         storage,
         cache_capacity,
         rewards_report_all,
+        ledger_snapshots,
     );
 
     let tip = match blockchain.load_from_block0(block0.clone()).await {
@@ -49,6 +49,7 @@ pub fn generate_vote_plans(
             committee_end: template.committee_end,
             proposals: template.proposals.clone(),
             committee_member_public_keys: vec![],
+            committee_threshold: None,
             voting_token: template.voting_token.clone(),
         };
 
@@ -238,5 +238,7 @@ fn payload_type_to_string(payload_type: PayloadType) -> &'static str {
     match payload_type {
         PayloadType::Public => "public",
         PayloadType::Private => "private",
+        PayloadType::Approval => "approval",
+        PayloadType::Ranked => "ranked",
     }
 }
@@ -4,7 +4,7 @@ use crate::jormungandr::get_available_port;
 use jormungandr_lib::{
     interfaces::{
         Bootstrap, Connection, Cors, JRpc, LayersConfig, Log, LogEntry, LogOutput, Mempool,
-        NodeConfig, P2p, Policy, Rest, Tls, TopicsOfInterest, TrustedPeer,
+        NodeConfig, P2p, Policy, Rest, RestAdmin, Tls, TopicsOfInterest, TrustedPeer,
     },
     time::Duration,
 };
@@ -56,6 +56,7 @@ impl Default for NodeConfigBuilder {
                 listen: format!("{}:{}", DEFAULT_HOST, rest_port).parse().unwrap(),
                 tls: None,
                 cors: None,
+                admin: None,
             },
             jrpc: JRpc {
                 listen: format!("{}:{}", DEFAULT_HOST, jrpc_port).parse().unwrap(),
@@ -136,6 +137,11 @@ impl NodeConfigBuilder {
         self
     }
 
+    pub fn with_rest_admin_config(mut self, admin: RestAdmin) -> Self {
+        self.rest.admin = Some(admin);
+        self
+    }
+
     pub fn with_mempool(mut self, mempool: Mempool) -> Self {
         self.mempool = Some(mempool);
         self
@@ -17,6 +17,11 @@ impl ExplorerConfigurationBuilder {
         self
     }
 
+    pub fn storage_dir(mut self, p: Option<PathBuf>) -> Self {
+        self.config.storage_dir = p;
+        self
+    }
+
     pub fn params(mut self, params: ExplorerParams) -> Self {
         self.config.params = params;
         self
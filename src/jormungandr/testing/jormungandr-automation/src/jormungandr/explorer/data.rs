@@ -132,3 +132,19 @@ pub struct BlockById;
     response_derives = "Debug,Clone"
 )]
 pub struct VotePlanById;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "resources/explorer/graphql/vote_casts_by_address.graphql",
+    schema_path = "resources/explorer/graphql/schema.graphql",
+    response_derives = "Debug,Clone"
+)]
+pub struct VoteCastsByAddress;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    query_path = "resources/explorer/graphql/vote_plan_progress.graphql",
+    schema_path = "resources/explorer/graphql/schema.graphql",
+    response_derives = "Debug,Clone"
+)]
+pub struct VotePlanProgress;
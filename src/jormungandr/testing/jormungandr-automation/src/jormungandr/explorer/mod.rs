@@ -3,10 +3,11 @@ use self::{
     data::{
         address, all_blocks, all_stake_pools, all_vote_plans, block, block_by_id,
         blocks_by_chain_length, epoch, last_block, settings, stake_pool, transaction_by_id,
-        transaction_by_id_certificates, transactions_by_address, vote_plan_by_id, Address,
-        AllBlocks, AllStakePools, AllVotePlans, Block, BlockById, BlocksByChainLength, Epoch,
-        LastBlock, Settings, StakePool, TransactionById, TransactionByIdCertificates,
-        TransactionsByAddress, VotePlanById,
+        transaction_by_id_certificates, transactions_by_address, vote_casts_by_address,
+        vote_plan_by_id, vote_plan_progress, Address, AllBlocks, AllStakePools, AllVotePlans,
+        Block, BlockById, BlocksByChainLength, Epoch, LastBlock, Settings, StakePool,
+        TransactionById, TransactionByIdCertificates, TransactionsByAddress, VoteCastsByAddress,
+        VotePlanById, VotePlanProgress,
     },
 };
 use crate::testing::configuration::get_explorer_app;
@@ -389,6 +390,32 @@ impl Explorer {
         Ok(response_body)
     }
 
+    pub fn vote_plan_progress(
+        &self,
+        id: String,
+    ) -> Result<Response<vote_plan_progress::ResponseData>, ExplorerError> {
+        let query = VotePlanProgress::build_query(vote_plan_progress::Variables { id });
+        self.print_request(&query);
+        let response = self.client.run(query).map_err(ExplorerError::ClientError)?;
+        let response_body: Response<vote_plan_progress::ResponseData> = response.json()?;
+        self.print_log(&response_body);
+        Ok(response_body)
+    }
+
+    pub fn vote_casts_by_address<S: Into<String>>(
+        &self,
+        bech32_address: S,
+    ) -> Result<Response<vote_casts_by_address::ResponseData>, ExplorerError> {
+        let query = VoteCastsByAddress::build_query(vote_casts_by_address::Variables {
+            bech32: bech32_address.into(),
+        });
+        self.print_request(&query);
+        let response = self.client.run(query).map_err(ExplorerError::ClientError)?;
+        let response_body: Response<vote_casts_by_address::ResponseData> = response.json()?;
+        self.print_log(&response_body);
+        Ok(response_body)
+    }
+
     pub fn transaction(
         &self,
         hash: Hash,
@@ -53,6 +53,14 @@ impl ExplorerVerifier {
                             vote_plan_status.payload,
                             vote::PayloadType::Private
                         )),
+                        all_vote_plans::PayloadType::APPROVAL => assert!(matches!(
+                            vote_plan_status.payload,
+                            vote::PayloadType::Approval
+                        )),
+                        all_vote_plans::PayloadType::RANKED => assert!(matches!(
+                            vote_plan_status.payload,
+                            vote::PayloadType::Ranked
+                        )),
                         all_vote_plans::PayloadType::Other(_) => panic!("Wrong payload type"),
                     }
 
@@ -145,6 +153,19 @@ impl ExplorerVerifier {
                                                 explorer_tally_status.options.end as u8
                                             );
                                         }
+                                        PrivateTallyState::DecryptedMultiSelection {
+                                            result,
+                                            ..
+                                        } => {
+                                            // the ranked results hold the results of every
+                                            // rank one after the other
+                                            let results: Vec<String> =
+                                                result.results.iter().map(u64::to_string).collect();
+                                            assert_eq!(
+                                                &results,
+                                                explorer_tally_status.results.as_ref().unwrap()
+                                            );
+                                        }
                                     }
                                 } else {
                                     panic!("Wrong tally status. Expected Private")
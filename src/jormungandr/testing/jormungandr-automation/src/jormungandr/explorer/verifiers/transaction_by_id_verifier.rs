@@ -489,6 +489,13 @@ impl ExplorerVerifier {
                 explorer_cert.payload_type,
                 expPayloadType::PRIVATE
             )),
+            PayloadType::Approval => assert!(matches!(
+                explorer_cert.payload_type,
+                expPayloadType::APPROVAL
+            )),
+            PayloadType::Ranked => {
+                assert!(matches!(explorer_cert.payload_type, expPayloadType::RANKED))
+            }
         }
 
         assert_eq!(
@@ -47,6 +47,14 @@ impl ExplorerVerifier {
                 vote_plan_status.payload,
                 vote::PayloadType::Private
             )),
+            vote_plan_by_id::PayloadType::APPROVAL => assert!(matches!(
+                vote_plan_status.payload,
+                vote::PayloadType::Approval
+            )),
+            vote_plan_by_id::PayloadType::RANKED => assert!(matches!(
+                vote_plan_status.payload,
+                vote::PayloadType::Ranked
+            )),
             vote_plan_by_id::PayloadType::Other(_) => panic!("Wrong payload type"),
         }
 
@@ -123,6 +131,13 @@ impl ExplorerVerifier {
                                     explorer_tally_status.options.end as u8
                                 );
                             }
+                            PrivateTallyState::DecryptedMultiSelection { result, .. } => {
+                                // the ranked results hold the results of every rank one
+                                // after the other
+                                let results: Vec<String> =
+                                    result.results.iter().map(u64::to_string).collect();
+                                assert_eq!(results, explorer_tally_status.results.unwrap());
+                            }
                         }
                     } else {
                         panic!("Wrong tally status. Expected Private")
@@ -128,6 +128,7 @@ impl LegacyNodeConfigConverter {
                 listen: source.rest.listen,
                 cors: None,
                 tls: None,
+                admin: None,
             },
             jrpc: source.jrpc.clone(),
             p2p: P2p {
@@ -170,6 +171,7 @@ impl LegacyNodeConfigConverter {
                 listen: source.rest.listen,
                 cors: None,
                 tls: None,
+                admin: None,
             },
             jrpc: source.jrpc.clone(),
             p2p: P2p {
@@ -228,6 +230,7 @@ impl LegacyNodeConfigConverter {
                 listen: source.rest.listen,
                 cors: None,
                 tls: None,
+                admin: None,
             },
             jrpc: source.jrpc.clone(),
             p2p: P2p {
@@ -26,7 +26,7 @@ use jortestkit::prelude::NamedProcess;
 use std::{
     collections::HashMap,
     net::SocketAddr,
-    path::Path,
+    path::{Path, PathBuf},
     process::{Child, ExitStatus},
     str::FromStr,
     time::{Duration, Instant},
@@ -274,6 +274,25 @@ impl JormungandrProcess {
     }
 
     pub fn explorer(&self, params: ExplorerParams) -> Result<ExplorerProcess, ExplorerError> {
+        ExplorerProcess::new(self.explorer_configuration().params(params).build())
+    }
+
+    /// Start an explorer keeping its index in `storage_dir`, so it can be
+    /// restarted without indexing the chain from the genesis block again
+    pub fn explorer_with_storage(
+        &self,
+        params: ExplorerParams,
+        storage_dir: PathBuf,
+    ) -> Result<ExplorerProcess, ExplorerError> {
+        ExplorerProcess::new(
+            self.explorer_configuration()
+                .params(params)
+                .storage_dir(Some(storage_dir))
+                .build(),
+        )
+    }
+
+    fn explorer_configuration(&self) -> ExplorerConfigurationBuilder {
         let mut p2p_public_address = self.p2p_public_address.clone();
         let port = match p2p_public_address.pop().unwrap() {
             multiaddr::Protocol::Tcp(port) => port,
@@ -285,12 +304,7 @@ impl JormungandrProcess {
             _ => todo!("only ipv4 supported for now"),
         };
 
-        ExplorerProcess::new(
-            ExplorerConfigurationBuilder::default()
-                .address(format!("http://{}:{}/", address, port))
-                .params(params)
-                .build(),
-        )
+        ExplorerConfigurationBuilder::default().address(format!("http://{}:{}/", address, port))
     }
 
     pub fn to_trusted_peer(&self) -> TrustedPeer {
@@ -27,7 +27,8 @@ impl VotePlanStatusAssert for Vec<VotePlanStatus> {
                 PrivateTallyState::Encrypted { .. } => {
                     panic!("expected decrypted private tally state")
                 }
-                PrivateTallyState::Decrypted { result, .. } => {
+                PrivateTallyState::Decrypted { result, .. }
+                | PrivateTallyState::DecryptedMultiSelection { result, .. } => {
                     assert_eq!(expected, result.results())
                 }
             },
@@ -18,6 +18,8 @@ impl VotePlanExtension for VotePlan {
         let payload = match self.payload_type() {
             PayloadType::Public => "public",
             PayloadType::Private => "private",
+            PayloadType::Approval => "approval",
+            PayloadType::Ranked => "ranked",
         };
 
         data["payload_type"] = json::JsonValue::String(payload.to_owned());
@@ -30,6 +30,7 @@ pub fn test_mempool_pool_max_entries_limit() {
         pool_max_entries: 1.into(),
         log_max_entries: 100.into(),
         persistent_log: None,
+        ..Default::default()
     });
 
     let jormungandr = SingleNodeTestBootstrapper::default()
@@ -104,6 +105,7 @@ pub fn test_mempool_pool_max_entries_equal_0() {
         pool_max_entries: 0.into(),
         log_max_entries: 100.into(),
         persistent_log: None,
+        ..Default::default()
     });
 
     let jormungandr = SingleNodeTestBootstrapper::default()
@@ -164,6 +166,7 @@ pub fn test_mempool_log_max_entries_only_one_fragment() {
         pool_max_entries: 1.into(),
         log_max_entries: 1.into(),
         persistent_log: None,
+        ..Default::default()
     });
 
     let jormungandr = SingleNodeTestBootstrapper::default()
@@ -244,6 +247,7 @@ pub fn test_mempool_log_max_entries_equals_0() {
         pool_max_entries: 0.into(),
         log_max_entries: 0.into(),
         persistent_log: None,
+        ..Default::default()
     });
 
     let jormungandr = SingleNodeTestBootstrapper::default()
@@ -305,6 +309,7 @@ pub fn test_mempool_pool_max_entries_overrides_log_max_entries() {
         pool_max_entries: 2.into(),
         log_max_entries: 0.into(),
         persistent_log: None,
+        ..Default::default()
     });
 
     let jormungandr = SingleNodeTestBootstrapper::default()
@@ -36,6 +36,7 @@ pub fn test_mempool_pool_max_entries_limit() {
         pool_max_entries: 1.into(),
         log_max_entries: 100.into(),
         persistent_log: None,
+        ..Default::default()
     });
 
     let jormungandr = SingleNodeTestBootstrapper::default()
@@ -125,6 +126,7 @@ pub fn test_mempool_pool_max_entries_equal_0() {
         pool_max_entries: 0.into(),
         log_max_entries: 100.into(),
         persistent_log: None,
+        ..Default::default()
     });
 
     let jormungandr = SingleNodeTestBootstrapper::default()
@@ -204,6 +206,7 @@ pub fn test_mempool_log_max_entries_only_one_fragment() {
         pool_max_entries: 1.into(),
         log_max_entries: 1.into(),
         persistent_log: None,
+        ..Default::default()
     });
 
     let jormungandr = SingleNodeTestBootstrapper::default()
@@ -293,6 +296,7 @@ pub fn test_mempool_log_max_entries_equals_0() {
         pool_max_entries: 0.into(),
         log_max_entries: 0.into(),
         persistent_log: None,
+        ..Default::default()
     });
 
     let jormungandr = SingleNodeTestBootstrapper::default()
@@ -373,6 +377,7 @@ pub fn test_mempool_pool_max_entries_overrides_log_max_entries() {
         pool_max_entries: 2.into(),
         log_max_entries: 0.into(),
         persistent_log: None,
+        ..Default::default()
     });
 
     let jormungandr = SingleNodeTestBootstrapper::default()
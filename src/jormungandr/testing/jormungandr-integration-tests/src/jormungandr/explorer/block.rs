@@ -65,6 +65,7 @@ pub fn explorer_block_test() {
             pool_max_entries: 1_000_000usize.into(),
             log_max_entries: 1_000_000usize.into(),
             persistent_log: None,
+            ..Default::default()
         }))
         .build()
         .start_node(temp_dir)
@@ -245,6 +246,7 @@ pub fn explorer_last_block_test() {
             pool_max_entries: 1_000_000usize.into(),
             log_max_entries: 1_000_000usize.into(),
             persistent_log: None,
+            ..Default::default()
         }))
         .build()
         .start_node(temp_dir)
@@ -349,6 +351,7 @@ pub fn explorer_all_blocks_test() {
             pool_max_entries: 1_000_000usize.into(),
             log_max_entries: 1_000_000usize.into(),
             persistent_log: None,
+            ..Default::default()
         }))
         .build();
 
@@ -463,6 +466,7 @@ pub fn explorer_block_by_chain_length_test() {
             pool_max_entries: 1_000_000usize.into(),
             log_max_entries: 1_000_000usize.into(),
             persistent_log: None,
+            ..Default::default()
         }))
         .build()
         .start_node(temp_dir)
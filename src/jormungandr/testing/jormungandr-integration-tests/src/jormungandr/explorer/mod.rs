@@ -4,5 +4,6 @@ pub mod certificates;
 pub mod explorer_sanity;
 pub mod settings;
 pub mod stake_pool;
+pub mod storage;
 pub mod transaction;
 pub mod vote_plan;
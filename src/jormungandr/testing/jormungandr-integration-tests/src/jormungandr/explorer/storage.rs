@@ -0,0 +1,85 @@
+use crate::startup;
+use assert_fs::{fixture::PathChild, TempDir};
+use chain_impl_mockchain::{block::BlockDate, key::Hash};
+use jormungandr_automation::{
+    jcli::JCli,
+    jormungandr::{explorer::configuration::ExplorerParams, Block0ConfigurationBuilder, Explorer},
+};
+use jormungandr_lib::interfaces::ActiveSlotCoefficient;
+use jortestkit::process::Wait;
+use std::{str::FromStr, time::Duration};
+
+#[test]
+pub fn explorer_restart_from_storage_test() {
+    let jcli: JCli = Default::default();
+    let sender = thor::Wallet::default();
+    let receiver = thor::Wallet::default();
+    let query_complexity_limit = 150;
+    let attempts_number = 20;
+    let temp_dir = TempDir::new().unwrap();
+    let storage_dir = temp_dir.child("explorer");
+
+    let config = Block0ConfigurationBuilder::default()
+        .with_consensus_genesis_praos_active_slot_coeff(ActiveSlotCoefficient::MAXIMUM);
+
+    let (jormungandr, _initial_stake_pools) =
+        startup::start_stake_pool(&[sender.clone()], &[], config, Default::default()).unwrap();
+
+    let params = ExplorerParams::new(query_complexity_limit, None, None);
+    let explorer_process = jormungandr
+        .explorer_with_storage(params.clone(), storage_dir.path().to_path_buf())
+        .unwrap();
+
+    let transaction = thor::FragmentBuilder::from_settings(
+        &jormungandr.rest().settings().unwrap(),
+        BlockDate::first().next_epoch(),
+    )
+    .transaction(&sender, receiver.address(), 1_000.into())
+    .unwrap()
+    .encode();
+
+    let wait = Wait::new(Duration::from_secs(3), attempts_number);
+    let fragment_id = jcli
+        .fragment_sender(&jormungandr)
+        .send(&transaction)
+        .assert_in_block_with_wait(&wait);
+
+    let blocks_before_restart = block_ids(explorer_process.client());
+    explorer_process.shutdown();
+
+    let explorer_process = jormungandr
+        .explorer_with_storage(params, storage_dir.path().to_path_buf())
+        .unwrap();
+    let explorer = explorer_process.client();
+
+    let explorer_transaction = explorer
+        .transaction(fragment_id.into())
+        .expect("Non existing transaction");
+    assert_eq!(
+        fragment_id,
+        Hash::from_str(&explorer_transaction.data.unwrap().transaction.id).unwrap(),
+        "Incorrect fragment id after restart"
+    );
+
+    let blocks_after_restart = block_ids(explorer);
+    assert!(
+        blocks_before_restart
+            .iter()
+            .all(|block| blocks_after_restart.contains(block)),
+        "blocks indexed before the restart are missing"
+    );
+}
+
+fn block_ids(explorer: &Explorer) -> Vec<Hash> {
+    explorer
+        .blocks(1000)
+        .unwrap()
+        .data
+        .unwrap()
+        .tip
+        .blocks
+        .edges
+        .iter()
+        .map(|edge| Hash::from_str(&edge.node.id).unwrap())
+        .collect()
+}
@@ -14,7 +14,9 @@ use chain_impl_mockchain::{
 };
 use jormungandr_automation::{
     jormungandr::{
-        explorer::{configuration::ExplorerParams, verifiers::ExplorerVerifier},
+        explorer::{
+            configuration::ExplorerParams, data::vote_plan_progress, verifiers::ExplorerVerifier,
+        },
         Block0ConfigurationBuilder,
     },
     testing::{
@@ -395,6 +397,148 @@ pub fn explorer_vote_plan_public_flow_test() {
     );
 }
 
+#[test]
+pub fn explorer_vote_casts_by_address_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let alice = Wallet::default();
+    let bob = Wallet::default();
+    let mut voters = vec![alice, bob];
+    let proposals = vec![VOTE_FOR_MARIO, VOTE_FOR_LUIGI, VOTE_FOR_ANTONIO];
+    let yes_choice = Choice::new(1);
+    let no_choice = Choice::new(0);
+
+    let vote_plan = VotePlanBuilder::new()
+        .proposals_count(proposals.len())
+        .vote_start(BlockDate::from_epoch_slot_id(0, 0))
+        .tally_start(BlockDate::from_epoch_slot_id(1, 0))
+        .tally_end(BlockDate::from_epoch_slot_id(1, 10))
+        .public()
+        .build();
+
+    let vote_plan_cert = Initial::Cert(
+        vote_plan_cert(
+            &voters[0],
+            BlockDate {
+                epoch: 1,
+                slot_id: 0,
+            },
+            &vote_plan,
+        )
+        .into(),
+    );
+
+    let config = Block0ConfigurationBuilder::default()
+        .with_utxos(vec![
+            voters[0].to_initial_fund(INITIAL_FUND_PER_WALLET_1),
+            voters[1].to_initial_fund(INITIAL_FUND_PER_WALLET_2),
+        ])
+        .with_token(InitialToken {
+            token_id: vote_plan.voting_token().clone().into(),
+            policy: MintingPolicy::new().into(),
+            to: vec![
+                voters[0].to_initial_token(INITIAL_TOKEN_PER_WALLET_1),
+                voters[1].to_initial_token(INITIAL_TOKEN_PER_WALLET_2),
+            ],
+        })
+        .with_committees(&[voters[0].to_committee_id()])
+        .with_slots_per_epoch(SLOTS_PER_EPOCH.try_into().unwrap())
+        .with_certs(vec![vote_plan_cert])
+        .with_treasury(INITIAL_TREASURY.into());
+
+    let jormungandr = SingleNodeTestBootstrapper::default()
+        .as_bft_leader()
+        .with_block0_config(config)
+        .build()
+        .start_node(temp_dir)
+        .unwrap();
+
+    let transaction_sender = FragmentSender::from_settings(
+        &jormungandr.rest().settings().unwrap(),
+        BlockDate {
+            epoch: 3,
+            slot_id: 0,
+        }
+        .into(),
+        FragmentSenderSetup::resend_3_times(),
+    );
+
+    let params = ExplorerParams::new(
+        VOTE_PLAN_QUERY_COMPLEXITY_LIMIT,
+        VOTE_PLAN_QUERY_DEPTH_LIMIT,
+        None,
+    );
+    let explorer_process = jormungandr.explorer(params).unwrap();
+    let explorer = explorer_process.client();
+
+    for (proposal, choice) in [
+        (VOTE_FOR_ANTONIO, &yes_choice),
+        (VOTE_FOR_MARIO, &no_choice),
+    ] {
+        transaction_sender
+            .send_vote_cast(&mut voters[0], &vote_plan, proposal, choice, &jormungandr)
+            .unwrap();
+    }
+
+    transaction_sender
+        .send_vote_cast(
+            &mut voters[1],
+            &vote_plan,
+            VOTE_FOR_LUIGI,
+            &yes_choice,
+            &jormungandr,
+        )
+        .unwrap();
+
+    let query_response = explorer
+        .vote_casts_by_address(voters[0].address().to_string())
+        .unwrap();
+
+    assert!(
+        query_response.errors.is_none(),
+        "{:?}",
+        query_response.errors.unwrap()
+    );
+
+    let vote_casts = query_response.data.unwrap().tip.vote_casts_by_address;
+    assert_eq!(vote_casts.total_count, 2);
+
+    let mut proposal_indexes: Vec<i64> = vote_casts
+        .edges
+        .iter()
+        .map(|edge| {
+            assert_eq!(edge.node.vote_plan, vote_plan.to_id().to_string());
+            edge.node.proposal_index
+        })
+        .collect();
+    proposal_indexes.sort_unstable();
+    assert_eq!(
+        proposal_indexes,
+        vec![VOTE_FOR_MARIO as i64, VOTE_FOR_ANTONIO as i64]
+    );
+
+    let query_response = explorer
+        .vote_plan_progress(vote_plan.to_id().to_string())
+        .unwrap();
+
+    assert!(
+        query_response.errors.is_none(),
+        "{:?}",
+        query_response.errors.unwrap()
+    );
+
+    let progress = query_response.data.unwrap().tip.vote_plan_progress;
+    assert!(
+        matches!(progress.phase, vote_plan_progress::VotePlanPhase::VOTING),
+        "{:?}",
+        progress.phase
+    );
+    assert_eq!(progress.votes_cast, 3);
+    assert!(progress
+        .proposals
+        .iter()
+        .all(|proposal| proposal.votes_cast == 1));
+}
+
 #[test]
 pub fn explorer_vote_plan_private_flow_test() {
     let temp_dir = TempDir::new().unwrap().into_persistent();
@@ -51,6 +51,7 @@ pub fn send_all_fragments() {
             pool_max_entries: 1_000_000usize.into(),
             log_max_entries: 1_000_000usize.into(),
             persistent_log: None,
+            ..Default::default()
         }))
         .build()
         .start_node(temp_dir)
@@ -61,6 +61,7 @@ pub fn dump_send_correct_fragments() {
             persistent_log: Some(PersistentLog {
                 dir: persistent_log_path.path().to_path_buf(),
             }),
+            ..Default::default()
         }))
         .build()
         .start_node(temp_dir)
@@ -125,6 +126,7 @@ pub fn dump_send_invalid_fragments() {
             persistent_log: Some(PersistentLog {
                 dir: persistent_log_path.path().to_path_buf(),
             }),
+            ..Default::default()
         }))
         .build()
         .start_node(temp_dir)
@@ -177,6 +179,7 @@ pub fn non_existing_folder() {
             persistent_log: Some(PersistentLog {
                 dir: persistent_log_path.path().to_path_buf(),
             }),
+            ..Default::default()
         }))
         .build()
         .start_node(temp_dir)
@@ -203,6 +206,7 @@ pub fn invalid_folder() {
         persistent_log: Some(PersistentLog {
             dir: persistent_log_path.path().to_path_buf(),
         }),
+        ..Default::default()
     });
 
     SingleNodeTestBootstrapper::default()
@@ -237,6 +241,7 @@ pub fn fragment_which_reached_mempool_should_be_persisted() {
             persistent_log: Some(PersistentLog {
                 dir: persistent_log_path.path().to_path_buf(),
             }),
+            ..Default::default()
         }))
         .build()
         .start_node(temp_dir)
@@ -280,6 +285,7 @@ pub fn fragment_which_is_not_in_fragment_log_should_be_persisted() {
             persistent_log: Some(PersistentLog {
                 dir: persistent_log_path.path().to_path_buf(),
             }),
+            ..Default::default()
         }))
         .build()
         .start_node(temp_dir)
@@ -323,6 +329,7 @@ pub fn pending_fragment_should_be_persisted() {
             persistent_log: Some(PersistentLog {
                 dir: persistent_log_path.path().to_path_buf(),
             }),
+            ..Default::default()
         }))
         .build()
         .start_node(temp_dir)
@@ -382,6 +389,7 @@ pub fn node_should_pickup_log_after_restart() {
             persistent_log: Some(PersistentLog {
                 dir: persistent_log_path.path().to_path_buf(),
             }),
+            ..Default::default()
         }))
         .as_bft_leader()
         .build();
@@ -446,6 +454,7 @@ fn pending_transaction_stats() {
                     pool_max_entries: mempool_max_entries.into(),
                     log_max_entries: mempool_max_entries.into(),
                     persistent_log: None,
+                    ..Default::default()
                 })
                 .with_log_level("debug".to_string()),
         )
@@ -0,0 +1,46 @@
+use crate::startup::SingleNodeTestBootstrapper;
+use assert_fs::TempDir;
+use jormungandr_automation::jormungandr::{
+    get_available_port, uri_from_socket_addr, JormungandrRest, NodeConfigBuilder,
+};
+use jormungandr_lib::interfaces::RestAdmin;
+use reqwest::StatusCode;
+use std::net::SocketAddr;
+
+#[test]
+pub fn admin_endpoints_served_on_admin_listener() {
+    let temp_dir = TempDir::new().unwrap();
+    let admin_listen: SocketAddr = format!("127.0.0.1:{}", get_available_port())
+        .parse()
+        .unwrap();
+
+    let config = NodeConfigBuilder::default().with_rest_admin_config(RestAdmin {
+        listen: admin_listen,
+        tls: None,
+    });
+
+    let jormungandr = SingleNodeTestBootstrapper::default()
+        .with_node_config(config)
+        .as_bft_leader()
+        .build()
+        .start_node(temp_dir)
+        .unwrap();
+
+    let public_rest = jormungandr.rest();
+    assert!(public_rest.raw().stats().unwrap().status().is_success());
+    assert_eq!(
+        public_rest.raw().settings().unwrap().status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        public_rest.raw().shutdown().unwrap().status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let admin_rest = JormungandrRest::new(uri_from_socket_addr(admin_listen));
+    assert!(admin_rest.settings().is_ok());
+    assert_eq!(
+        admin_rest.raw().stats().unwrap().status(),
+        StatusCode::NOT_FOUND
+    );
+}
@@ -17,6 +17,7 @@ pub fn test_rest_tls_config() {
     let config = NodeConfigBuilder::default().with_rest_tls_config(Tls {
         cert_file: server_crt_file.as_os_str().to_str().unwrap().to_owned(),
         priv_key_file: prv_key_file.as_os_str().to_str().unwrap().to_owned(),
+        client_ca_file: None,
     });
 
     let jormungandr = SingleNodeTestBootstrapper::default()
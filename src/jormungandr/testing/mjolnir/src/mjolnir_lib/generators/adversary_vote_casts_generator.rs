@@ -72,7 +72,7 @@ impl<'a, S: SyncNode + Send> AdversaryVoteCastsGenerator<'a, S> {
                 FragmentBuilder::new(&block0_hash, &fees, self.expiry_generator.block_date())
                     .public_vote_cast(&self.voter, &vote_plan, 0, &Choice::new(0))
             }
-            PayloadType::Private => {
+            PayloadType::Private | PayloadType::Approval | PayloadType::Ranked => {
                 FragmentBuilder::new(&block0_hash, &fees, self.expiry_generator.block_date())
                     .private_vote_cast(&self.voter, &vote_plan, 0, &Choice::new(0))
             }
@@ -93,7 +93,7 @@ impl<'a, S: SyncNode + Send> AdversaryVoteCastsGenerator<'a, S> {
                 FragmentBuilder::new(&block0_hash, &fees, self.expiry_generator.block_date())
                     .public_vote_cast(&self.voter, vote_plan, 255, &Choice::new(0))
             }
-            PayloadType::Private => {
+            PayloadType::Private | PayloadType::Approval | PayloadType::Ranked => {
                 FragmentBuilder::new(&block0_hash, &fees, self.expiry_generator.block_date())
                     .private_vote_cast(&self.voter, vote_plan, 255, &Choice::new(0))
             }
@@ -110,7 +110,7 @@ impl<'a, S: SyncNode + Send> AdversaryVoteCastsGenerator<'a, S> {
                 FragmentBuilder::new(&block0_hash, &fees, self.expiry_generator.block_date())
                     .private_vote_cast(&self.voter, vote_plan, 0, &Choice::new(0))
             }
-            PayloadType::Private => {
+            PayloadType::Private | PayloadType::Approval | PayloadType::Ranked => {
                 FragmentBuilder::new(&block0_hash, &fees, self.expiry_generator.block_date())
                     .public_vote_cast(&self.voter, vote_plan, 0, &Choice::new(0))
             }
@@ -128,7 +128,7 @@ impl<'a, S: SyncNode + Send> AdversaryVoteCastsGenerator<'a, S> {
                 FragmentBuilder::new(&block0_hash, &fees, self.expiry_generator.block_date())
                     .public_vote_cast(&self.voter, vote_plan, 0, &Choice::new(options))
             }
-            PayloadType::Private => {
+            PayloadType::Private | PayloadType::Approval | PayloadType::Ranked => {
                 FragmentBuilder::new(&block0_hash, &fees, self.expiry_generator.block_date())
                     .private_vote_cast(&self.voter, vote_plan, 0, &Choice::new(options))
             }
@@ -276,6 +276,12 @@ impl FragmentBuilder {
             PayloadType::Private => {
                 self.private_vote_cast(wallet, vote_plan, proposal_index, choice)
             }
+            PayloadType::Approval => {
+                self.approval_vote_cast(wallet, vote_plan, proposal_index, &[*choice])
+            }
+            PayloadType::Ranked => {
+                self.ranked_vote_cast(wallet, vote_plan, proposal_index, &[*choice])
+            }
         }
     }
 
@@ -301,8 +307,7 @@ impl FragmentBuilder {
     ) -> Fragment {
         let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
 
-        let election_key =
-            chain_vote::ElectionPublicKey::from_participants(vote_plan.committee_public_keys());
+        let election_key = vote_plan.election_public_key();
 
         let options = vote_plan
             .proposals()
@@ -338,6 +343,83 @@ impl FragmentBuilder {
             .vote_cast(self.valid_until, &inner_wallet, vote_cast)
     }
 
+    pub fn approval_vote_cast(
+        &self,
+        wallet: &Wallet,
+        vote_plan: &VotePlan,
+        proposal_index: u8,
+        choices: &[Choice],
+    ) -> Fragment {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+
+        let election_key = vote_plan.election_public_key();
+        let options = vote_plan.proposals()[proposal_index as usize].options();
+        let start = options.choice_range().start;
+        let length = options.choice_range().end.checked_sub(start).unwrap();
+
+        let selected = choices
+            .iter()
+            .map(|choice| (choice.as_byte() - start) as usize)
+            .collect::<Vec<_>>();
+        let vote = chain_vote::ApprovalVote::new(length as usize, &selected).unwrap();
+        let crs = chain_vote::Crs::from_hash(vote_plan.to_id().as_ref());
+        let (encrypted_vote, proof) = chain_impl_mockchain::vote::encrypt_approval_vote(
+            &mut rng,
+            &crs,
+            &election_key,
+            &vote,
+            options.max_selections(),
+        );
+
+        let vote_cast = VoteCast::new(
+            vote_plan.to_id(),
+            proposal_index,
+            Payload::approval(encrypted_vote, proof),
+        );
+
+        let inner_wallet = wallet.clone().into();
+
+        self.fragment_factory
+            .vote_cast(self.valid_until, &inner_wallet, vote_cast)
+    }
+
+    /// the `ranking` holds the choices from the most preferred one and
+    /// must rank as many choices as the maximum selections of the proposal
+    pub fn ranked_vote_cast(
+        &self,
+        wallet: &Wallet,
+        vote_plan: &VotePlan,
+        proposal_index: u8,
+        ranking: &[Choice],
+    ) -> Fragment {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+
+        let election_key = vote_plan.election_public_key();
+        let options = vote_plan.proposals()[proposal_index as usize].options();
+        let start = options.choice_range().start;
+        let length = options.choice_range().end.checked_sub(start).unwrap();
+
+        let ranking = ranking
+            .iter()
+            .map(|choice| (choice.as_byte() - start) as usize)
+            .collect();
+        let vote = chain_vote::RankedVote::new(length as usize, ranking).unwrap();
+        let crs = chain_vote::Crs::from_hash(vote_plan.to_id().as_ref());
+        let (encrypted_vote, proof) =
+            chain_impl_mockchain::vote::encrypt_ranked_vote(&mut rng, &crs, &election_key, &vote);
+
+        let vote_cast = VoteCast::new(
+            vote_plan.to_id(),
+            proposal_index,
+            Payload::ranked(encrypted_vote, proof),
+        );
+
+        let inner_wallet = wallet.clone().into();
+
+        self.fragment_factory
+            .vote_cast(self.valid_until, &inner_wallet, vote_cast)
+    }
+
     pub fn vote_tally(
         &self,
         wallet: &Wallet,
@@ -45,6 +45,8 @@ impl ValidVotePlanGenerator {
                 let payload_type = match vote_plan.payload_type() {
                     chain_impl_mockchain::vote::PayloadType::Public => "public",
                     chain_impl_mockchain::vote::PayloadType::Private => "private",
+                    chain_impl_mockchain::vote::PayloadType::Approval => "approval",
+                    chain_impl_mockchain::vote::PayloadType::Ranked => "ranked",
                 };
 
                 Voteplan {
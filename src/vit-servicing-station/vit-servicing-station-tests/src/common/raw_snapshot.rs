@@ -6,7 +6,9 @@ use jormungandr_lib::interfaces::Value;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use snapshot_lib::registration::{Delegations, RewardAddress, StakeAddress, VotingRegistration};
-use snapshot_lib::{voting_group::RepsVotersAssigner, Error, Snapshot};
+use snapshot_lib::{
+    voting_group::RepsVotersAssigner, voting_power::VotingPowerCap, Error, Snapshot,
+};
 use snapshot_lib::{
     voting_group::{DEFAULT_DIRECT_VOTER_GROUP, DEFAULT_REPRESENTATIVE_GROUP},
     CATALYST_VOTING_PURPOSE_TAG,
@@ -59,7 +61,7 @@ impl RawSnapshotExtension for RawSnapshot {
         Ok(Snapshot::from_raw_snapshot(
             self.content.snapshot,
             self.content.min_stake_threshold,
-            self.content.voting_power_cap,
+            &VotingPowerCap::new(self.content.voting_power_cap),
             assigner,
             Discrimination::Production,
             false,
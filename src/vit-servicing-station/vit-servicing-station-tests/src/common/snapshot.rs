@@ -118,6 +118,7 @@ impl SnapshotBuilder {
                                 underthreshold: false,
                             }
                         },
+                        voting_power_transform: None,
                     })
                 })
                 .take(voters_count)
@@ -7,6 +7,7 @@ use jormungandr_lib::interfaces::InitialUTxO;
 use jormungandr_lib::interfaces::Value;
 use snapshot_lib::registration::VotingRegistration;
 use snapshot_lib::voting_group::VotingGroupAssigner;
+use snapshot_lib::voting_power::VotingPowerCap;
 use snapshot_lib::{RawSnapshot, Snapshot, VoterHIR};
 use std::collections::HashSet;
 
@@ -78,7 +79,7 @@ impl SnapshotFilter {
             snapshot: Snapshot::from_raw_snapshot(
                 RawSnapshot::from(voting_registrations),
                 voting_threshold,
-                cap,
+                &VotingPowerCap::new(cap),
                 voting_group_assigner,
                 Discrimination::Production,
                 false,
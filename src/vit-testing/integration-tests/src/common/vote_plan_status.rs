@@ -94,7 +94,9 @@ impl VotePlanStatusProvider for Block0Configuration {
                                     PayloadType::Public => Tally::Public {
                                         result: TallyResultLib::new(p.options().clone()).into(),
                                     },
-                                    PayloadType::Private => Tally::Private {
+                                    PayloadType::Private
+                                    | PayloadType::Approval
+                                    | PayloadType::Ranked => Tally::Private {
                                         state: PrivateTallyState::Decrypted {
                                             result: TallyResultLib::new(p.options().clone()).into(),
                                         },
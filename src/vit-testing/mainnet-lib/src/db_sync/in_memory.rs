@@ -241,6 +241,12 @@ impl InMemoryDbSync {
     }
 }
 
+impl From<&InMemoryDbSync> for voting_tools_rs::InMemoryDbSyncProvider {
+    fn from(db_sync: &InMemoryDbSync) -> Self {
+        Self::new(&db_sync.blocks, &db_sync.transactions)
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Settings {
     pub db_name: String,
@@ -292,6 +298,7 @@ mod tests {
     use assert_fs::TempDir;
     use cardano_serialization_lib::utils::BigNum;
     use std::time::Duration;
+    use voting_tools_rs::{voting_power, InMemoryDbSyncProvider, VotingPowerArgs};
 
     #[tokio::test]
     async fn restore_persist_bijection_direct() {
@@ -310,6 +317,26 @@ mod tests {
         assert_eq!(before, db_sync.metadata());
     }
 
+    #[tokio::test]
+    async fn voting_power_from_in_memory_db_sync() {
+        let alice = CardanoWallet::new(1_000);
+
+        let (db_sync, _node, _reps) = MainnetNetworkBuilder::default()
+            .with(alice.as_direct_voter())
+            .build();
+
+        let (snapshot, invalids, _unregistered, deregistered) = voting_power(
+            &InMemoryDbSyncProvider::from(&db_sync),
+            VotingPowerArgs::default(),
+        )
+        .unwrap();
+
+        assert!(invalids.is_empty());
+        assert!(deregistered.is_empty());
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].voting_power, 1_000);
+    }
+
     #[tokio::test]
     pub async fn dbsync_observer_test() {
         let mut node = InMemoryNode::start(Block0::default());
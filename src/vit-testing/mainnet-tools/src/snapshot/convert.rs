@@ -17,9 +17,8 @@ pub trait MainnetWalletStateExtension {
 impl MainnetWalletStateExtension for Vec<MainnetWalletState> {
     fn try_into_raw_snapshot_request(
         self,
-        _parameters: SnapshotParameters,
+        parameters: SnapshotParameters,
     ) -> Result<RawSnapshotRequest, Error> {
-        /*
         let (db_sync, _, _) = self
             .into_iter()
             .fold(
@@ -27,12 +26,13 @@ impl MainnetWalletStateExtension for Vec<MainnetWalletState> {
                 MainnetNetworkBuilder::with,
             )
             .build();
-        let _db = MockDbProvider::from(db_sync);
-        */
-        //let (outputs, _errs) =
-        //    voting_tools_rs::voting_power(&db, VotingPowerArgs::default()).unwrap();
-        //outputs.try_into_raw_snapshot_request(parameters)
-        Ok(RawSnapshotRequest::default())
+        // mock wallets can delegate to representatives
+        let mut args = VotingPowerArgs::default();
+        args.cip_36_multidelegations = true;
+        let (outputs, _invalids, _unregistered, _deregistered) =
+            voting_tools_rs::voting_power(&InMemoryDbSyncProvider::from(&db_sync), args)
+                .map_err(|e| Error::VotingTools(e.to_string()))?;
+        outputs.try_into_raw_snapshot_request(parameters)
     }
 }
 
@@ -81,13 +81,15 @@ impl OutputsExtension for Vec<SnapshotEntry> {
 
 use jormungandr_lib::crypto::account::Identifier;
 use mainnet_lib::wallet_state::{MainnetWalletState, TemplateError};
-use mainnet_lib::SnapshotParameters;
+use mainnet_lib::{MainnetNetworkBuilder, SnapshotParameters};
 use num_traits::ToPrimitive;
 use snapshot_lib::registration::{
     Delegations as VotingDelegations, RewardAddress, StakeAddress, VotingRegistration,
 };
 use vit_servicing_station_lib::v0::endpoints::snapshot::RawSnapshotInput;
-use voting_tools_rs::{SnapshotEntry, VotingKey, VotingPurpose};
+use voting_tools_rs::{
+    InMemoryDbSyncProvider, SnapshotEntry, VotingKey, VotingPowerArgs, VotingPurpose,
+};
 
 /// Extensions for voting tools `Output` struct
 pub trait OutputExtension {
@@ -152,4 +154,7 @@ pub enum Error {
     /// Error related to building mock snapshot
     #[error(transparent)]
     Template(#[from] TemplateError),
+    /// Error related to calculating voting power of mock snapshot
+    #[error("cannot calculate voting power: {0}")]
+    VotingTools(String),
 }
@@ -2,7 +2,7 @@ use chain_addr::Discrimination;
 use jormungandr_lib::crypto::account::Identifier;
 use mainnet_tools::snapshot::MainnetWalletStateExtension;
 use proptest::{arbitrary::Arbitrary, prelude::*, strategy::BoxedStrategy};
-use snapshot_lib::{Snapshot, VoterHIR};
+use snapshot_lib::{voting_power::VotingPowerCap, Snapshot, VoterHIR};
 use std::collections::BTreeMap;
 use vit_servicing_station_lib::db::models::snapshot::{Contribution, Voter};
 
@@ -34,7 +34,7 @@ impl VoterSnapshot {
             let snapshot = Snapshot::from_raw_snapshot(
                 snapshot.content.snapshot,
                 parameters.min_stake_threshold,
-                parameters.voting_power_cap,
+                &VotingPowerCap::new(parameters.voting_power_cap),
                 &|_vk: &Identifier| String::new(),
                 Discrimination::Production,
                 false,
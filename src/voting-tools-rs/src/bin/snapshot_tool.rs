@@ -11,8 +11,8 @@ use tracing::{debug, info, Level};
 
 use voting_tools_rs::{
     verify::{prefix_hex, Unregistered},
-    voting_power, Args, DbConfig, DryRunCommand, InvalidRegistration, SnapshotEntry,
-    VotingPowerArgs,
+    voting_power, Args, DbConfig, DeregistrationEntry, DryRunCommand, InMemoryDbSyncProvider,
+    InvalidRegistration, PostgresDataProvider, SnapshotEntry, VotingPowerArgs,
 };
 
 fn main() -> Result<()> {
@@ -48,6 +48,9 @@ fn main() -> Result<()> {
         network_id,
         expected_voting_purpose,
         enable_cip36_multiple_delegations,
+        include_reward_balances,
+        stake_state,
+        verify_stake_state,
         ..
     } = Args::parse();
 
@@ -68,17 +71,18 @@ fn main() -> Result<()> {
     args.network_id = network_id;
     args.expected_voting_purpose = expected_voting_purpose;
     args.cip_36_multidelegations = enable_cip36_multiple_delegations;
+    args.include_reward_balances = include_reward_balances;
+    args.stake_state = stake_state;
+    args.verify_stake_state = verify_stake_state;
 
-    let db_client_registrations = db_conn(db_config.clone())?;
-    let db_client_stakes = db_conn(db_config)?;
-
-    let (valids, invalids, unregistered) =
-        load(dry_run, args, db_client_stakes, db_client_registrations)?;
+    let (valids, invalids, unregistered, deregistered) = load(dry_run, args, db_config)?;
 
     handle_invalids(&out_file, &invalids)?;
 
     handle_unregistered(&out_file, unregistered)?;
 
+    handle_deregistered(&out_file, &deregistered)?;
+
     info!(
         "calculated {} valids invalids {}",
         valids.len(),
@@ -117,14 +121,21 @@ fn db_conn(db_config: DbConfig) -> Result<Client, postgres::Error> {
 fn load(
     dry_run: Option<DryRunCommand>,
     args: VotingPowerArgs,
-    db_client_stakes: Client,
-    db_client_registrations: Client,
-) -> Result<(Vec<SnapshotEntry>, Vec<InvalidRegistration>, Unregistered)> {
+    db_config: DbConfig,
+) -> Result<(
+    Vec<SnapshotEntry>,
+    Vec<InvalidRegistration>,
+    Unregistered,
+    Vec<DeregistrationEntry>,
+)> {
     if let Some(DryRunCommand::DryRun { mock_json_file }) = dry_run {
         info!("Using dryrun file: {}", mock_json_file.to_string_lossy());
-        voting_power(db_client_stakes, db_client_registrations, args)
+        voting_power(&InMemoryDbSyncProvider::restore(mock_json_file)?, args)
     } else {
-        voting_power(db_client_stakes, db_client_registrations, args)
+        let db_client_registrations = db_conn(db_config.clone())?;
+        let db_client_stakes = db_conn(db_config)?;
+        let provider = PostgresDataProvider::new(db_client_registrations, db_client_stakes);
+        voting_power(&provider, args)
     }
 }
 
@@ -181,3 +192,29 @@ fn handle_unregistered(path: &Path, unregistered: Unregistered) -> Result<()> {
 
     Ok(())
 }
+
+/// Handle stake addresses that were deregistered
+fn handle_deregistered(path: &Path, deregistered: &[DeregistrationEntry]) -> Result<()> {
+    info!("handling deregistered");
+    if deregistered.is_empty() {
+        return Ok(());
+    }
+
+    let path = path.with_extension("deregistered.json");
+
+    tracing::warn!(
+        "found deregistered stake addresses: writing to {}",
+        path.to_string_lossy()
+    );
+
+    let file = File::options()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    let writer = BufWriter::new(file);
+
+    serde_json::to_writer_pretty(writer, deregistered)?;
+
+    Ok(())
+}
@@ -60,6 +60,19 @@ pub struct Args {
     /// Enable Multiple delegations in CIP-36 registrations
     #[clap(long)]
     pub enable_cip36_multiple_delegations: bool,
+
+    /// Count reward account balances (rewards earned minus withdrawals) toward voting power
+    #[clap(long)]
+    pub include_reward_balances: bool,
+
+    /// File keeping the staked ada as of the last processed slot, so that later runs with a higher
+    /// `--max-slot` only process the changes since. Requires `--max-slot`
+    #[clap(long, requires = "max_slot")]
+    pub stake_state: Option<PathBuf>,
+
+    /// Check the stake computed from `--stake-state` against a full recomputation
+    #[clap(long, requires = "stake_state")]
+    pub verify_stake_state: bool,
 }
 
 /// Sub command for internal testing or dry runs
@@ -142,6 +155,10 @@ mod tests {
             "--network-id",
             "mainnet",
             "--enable-cip36-multiple-delegations",
+            "--include-reward-balances",
+            "--stake-state",
+            "some/state.json",
+            "--verify-stake-state",
         ]);
 
         assert_eq!(
@@ -158,6 +175,9 @@ mod tests {
                 network_id: NetworkId::Mainnet,
                 expected_voting_purpose: VotingPurpose::CATALYST,
                 enable_cip36_multiple_delegations: true,
+                include_reward_balances: true,
+                stake_state: Some("some/state.json".into()),
+                verify_stake_state: true,
             }
         );
     }
@@ -167,5 +187,19 @@ mod tests {
         let args = Args::parse_from(["binary_name", "-o", "some/path"]);
 
         assert_eq!(args.out_file, PathBuf::from("some/path"));
+        assert!(!args.include_reward_balances);
+    }
+
+    #[test]
+    fn stake_state_requires_max_slot() {
+        let args = Args::try_parse_from([
+            "binary_name",
+            "-o",
+            "some/path",
+            "--stake-state",
+            "some/state.json",
+        ]);
+
+        assert!(args.is_err());
     }
 }
@@ -42,8 +42,14 @@ const WEIGHT: usize = 1;
 const KEY_61285: usize = 0;
 const WITNESS: usize = 0;
 
+// 61286 entries
+const KEY_61286: usize = 0;
+const DEREG_STAKE_ADDRESS: usize = 0;
+const DEREG_NONCE: usize = 1;
+const DEREG_VOTE_PURPOSE: usize = 2;
+
 use crate::{
-    verify::{validate_reg_cddl, validate_sig_cddl, CddlConfig},
+    verify::{validate_dereg_cddl, validate_reg_cddl, validate_sig_cddl, CddlConfig},
     RegistrationError,
 };
 
@@ -165,18 +171,7 @@ impl SignedRegistration {
     ///  - blake2b-256 hashing those bytes
     ///  - signing the hash with the private key used to generate the stake key
     pub fn validate_signature_bin(&self, bin_reg: Vec<u8>) -> Result<(), RegistrationError> {
-        let bytes = bin_reg;
-        let hash_bytes = hash(&bytes);
-
-        let pub_key = Ed25519::public_from_binary(self.registration.stake_key.as_ref())
-            .map_err(|e| RegistrationError::StakePublicKeyError { err: e.to_string() })?;
-        let sig = Ed25519::signature_from_bytes(self.signature.inner.as_ref())
-            .map_err(|e| RegistrationError::SignatureError { err: e.to_string() })?;
-
-        match Ed25519::verify_bytes(&pub_key, &sig, &hash_bytes) {
-            Verification::Success => Ok(()),
-            Verification::Failed => Err(RegistrationError::MismatchedSignature { hash_bytes }),
-        }
+        validate_stake_signature(&self.registration.stake_key, &self.signature, &bin_reg)
     }
 
     pub fn validate_multi_delegation(
@@ -197,6 +192,72 @@ impl SignedRegistration {
     }
 }
 
+fn validate_stake_signature(
+    stake_key: &StakeKeyHex,
+    signature: &Signature,
+    bytes: &[u8],
+) -> Result<(), RegistrationError> {
+    let hash_bytes = hash(bytes);
+
+    let pub_key = Ed25519::public_from_binary(stake_key.as_ref())
+        .map_err(|e| RegistrationError::StakePublicKeyError { err: e.to_string() })?;
+    let sig = Ed25519::signature_from_bytes(signature.inner.as_ref())
+        .map_err(|e| RegistrationError::SignatureError { err: e.to_string() })?;
+
+    match Ed25519::verify_bytes(&pub_key, &sig, &hash_bytes) {
+        Verification::Success => Ok(()),
+        Verification::Failed => Err(RegistrationError::MismatchedSignature { hash_bytes }),
+    }
+}
+
+/// A catalyst deregistration on Cardano as defined in CIP-36
+///
+/// Cancels the registrations of the same stake key for the same voting purpose with a nonce not
+/// greater than its own
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Deregistration {
+    #[serde(rename = "1")]
+    pub stake_key: StakeKeyHex,
+    #[serde(rename = "2")]
+    pub nonce: Nonce,
+    #[serde(rename = "3")]
+    pub voting_purpose: Option<VotingPurpose>,
+}
+
+/// A Catalyst deregistration, along with its signature
+///
+/// The signature is generated the same way as for registrations, over the CBOR encoding of
+/// a single entry map with a key of `61286` and a value of the deregistration
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SignedDeregistration {
+    /// The deregistration
+    #[serde(rename = "61286")]
+    pub deregistration: Deregistration,
+    /// The signature
+    #[serde(rename = "61285")]
+    pub signature: Signature,
+
+    /// Stake Key Hash
+    #[serde(serialize_with = "ox_hex")]
+    pub stake_key_hash: StakeKeyHash,
+
+    /// The id of the transaction that created this deregistration
+    pub tx_id: TxId,
+
+    /// The slot the deregistration was found in.
+    pub slot: u64,
+}
+
+impl SignedDeregistration {
+    /// The signature is generated by:
+    ///  - CBOR encoding the deregistration
+    ///  - blake2b-256 hashing those bytes
+    ///  - signing the hash with the private key used to generate the stake key
+    pub fn validate_signature_bin(&self, bin_dereg: Vec<u8>) -> Result<(), RegistrationError> {
+        validate_stake_signature(&self.deregistration.stake_key, &self.signature, &bin_dereg)
+    }
+}
+
 /// A Raw Registration from the `DB.arbitrary_signed_registration`
 ///
 #[derive(Debug, Clone)]
@@ -221,6 +282,11 @@ pub struct RawRegistration {
 }
 
 impl RawRegistration {
+    /// Validates the raw binaries against the CIP-36 CDDL specs and decodes them
+    ///
+    /// # Errors
+    ///
+    /// If the binaries do not match the specs or cannot be decoded
     pub fn to_signed(
         &self,
         cddl_config: &CddlConfig,
@@ -235,7 +301,7 @@ impl RawRegistration {
 
         let registration = self.raw_reg_conversion(network_id, slot_no)?;
 
-        let signature = self.raw_sig_conversion()?;
+        let signature = raw_sig_conversion(&self.bin_sig)?;
 
         Ok(SignedRegistration {
             registration: registration.clone(),
@@ -312,31 +378,137 @@ impl RawRegistration {
             voting_purpose,
         })
     }
+}
+
+fn raw_sig_conversion(bin_sig: &[u8]) -> Result<Signature, Box<dyn Error>> {
+    let decoded: ciborium::value::Value = ciborium::de::from_reader(Cursor::new(bin_sig))?;
+
+    let spec_61285 = match inspect_cip36_sig(decoded) {
+        Ok(value) => value,
+        Err(value) => return value,
+    };
+
+    let metamap = match inspect_metamap_sig(&spec_61285) {
+        Ok(value) => value,
+        Err(value) => return value,
+    };
+
+    // ED25119 signature
+    let sig = match inspect_witness(metamap) {
+        Ok(value) => value,
+        Err(value) => return value,
+    };
+
+    Ok(Signature { inner: Sig(sig) })
+}
+
+/// A Raw Deregistration from the DB
+///
+#[derive(Debug, Clone)]
+pub struct RawDeregistration {
+    /// cip 36: 61286 raw binary
+    pub bin_dereg: Vec<u8>,
+
+    /// cip 36: 61285 raw binary
+    pub bin_sig: Vec<u8>,
+
+    /// deregistration tx_id
+    pub tx_id: TxId,
+
+    /// The slot the deregistration was found in.
+    pub slot: u64,
+}
+
+impl RawDeregistration {
+    /// Validates the raw binaries against the CIP-36 CDDL specs and decodes them
+    ///
+    /// # Errors
+    ///
+    /// If the binaries do not match the specs or cannot be decoded
+    pub fn to_signed(
+        &self,
+        cddl_config: &CddlConfig,
+        network_id: NetworkId,
+        slot_no: SlotNo,
+    ) -> Result<SignedDeregistration, Box<dyn Error>> {
+        // validate cddl: 61286
+        validate_dereg_cddl(&self.bin_dereg, cddl_config)?;
+
+        // validate cddl: 61285
+        validate_sig_cddl(&self.bin_sig, cddl_config)?;
+
+        let deregistration = self.raw_dereg_conversion(slot_no)?;
+
+        let signature = raw_sig_conversion(&self.bin_sig)?;
+
+        Ok(SignedDeregistration {
+            stake_key_hash: stake_key_hash(&deregistration.stake_key, network_id),
+            deregistration,
+            signature,
+            tx_id: self.tx_id,
+            slot: self.slot,
+        })
+    }
 
-    fn raw_sig_conversion(&self) -> Result<Signature, Box<dyn Error>> {
+    fn raw_dereg_conversion(&self, slot_no: SlotNo) -> Result<Deregistration, Box<dyn Error>> {
         let decoded: ciborium::value::Value =
-            ciborium::de::from_reader(Cursor::new(&self.bin_sig))?;
+            ciborium::de::from_reader(Cursor::new(&self.bin_dereg))?;
+
+        // CBOR representation of a map containing a single entry with key 61286. See CIP-36 for context.
+        let spec_61286 = match decoded {
+            Value::Map(m) => m.into_iter().map(|entry| entry.1).collect::<Vec<_>>(),
+            _ => {
+                return Err(dereg_failure(format!(
+                    "Not congruent with CIP-36 {decoded:?}"
+                )))
+            }
+        };
 
-        let spec_61285 = match inspect_cip36_sig(decoded) {
-            Ok(value) => value,
-            Err(value) => return value,
+        // 2 entries inside metadata map with one optional entry for the voting purpose
+        let metamap = match spec_61286.get(KEY_61286) {
+            Some(Value::Map(metamap)) => metamap,
+            _ => return Err(dereg_failure("Unable to obtain metadata map".to_string())),
         };
 
-        let metamap = match inspect_metamap_sig(&spec_61285) {
-            Ok(value) => value,
-            Err(value) => return value,
+        // The stake key whose registrations are cancelled
+        let stake_key = match metamap.get(DEREG_STAKE_ADDRESS) {
+            Some((Value::Integer(_one), Value::Bytes(stake_key))) => {
+                StakeKeyHex(PubKey(stake_key.clone()))
+            }
+            _ => return Err(dereg_failure("Unable to extract stake key".to_string())),
         };
 
-        // ED25119 signature
-        let sig = match inspect_witness(metamap) {
-            Ok(value) => value,
-            Err(value) => return value,
+        // Only registrations with a nonce not greater than this one are cancelled
+        let nonce = match metamap.get(DEREG_NONCE) {
+            Some((Value::Integer(_two), Value::Integer(nonce))) => Nonce(u64::try_from(*nonce)?),
+            _ => return Err(dereg_failure("Unable to extract Nonce".to_string())),
+        };
+        let nonce = if nonce.0 < slot_no.0 {
+            nonce
+        } else {
+            // Don't allow nonce > slot number
+            Nonce(slot_no.0)
         };
 
-        Ok(Signature { inner: Sig(sig) })
+        let voting_purpose = match metamap.get(DEREG_VOTE_PURPOSE) {
+            Some((Value::Integer(_three), Value::Integer(purpose))) => {
+                Some(VotingPurpose(u64::try_from(*purpose)?))
+            }
+            _ => None,
+        };
+
+        Ok(Deregistration {
+            stake_key,
+            nonce,
+            voting_purpose,
+        })
     }
 }
 
+fn dereg_failure(err: String) -> Box<dyn Error> {
+    Box::new(RegistrationError::RawBinCborDeregistrationFailure { err })
+}
+
 ///
 /// To produce the witness field in the case of a staking public key,
 /// the CBOR representation of a map containing a single entry with key 61284
@@ -605,6 +777,16 @@ pub struct SnapshotEntry {
     /// This is computed from `voting_key`
     pub voting_power: u128,
 
+    /// Unspent UTXO value held by the stake credential
+    #[serde(default)]
+    pub utxo_stake: u128,
+
+    /// Reward account balance of the stake credential (rewards earned minus withdrawals)
+    ///
+    /// Only part of `voting_power` if reward balances were included in the snapshot
+    #[serde(default)]
+    pub reward_balance: u128,
+
     /// Voting purpose
     ///
     /// Catalyst expects the voting purpose is set to `0`
@@ -617,6 +799,28 @@ pub struct SnapshotEntry {
     pub nonce: u64,
 }
 
+/// Stake key deregistered in a snapshot
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeregistrationEntry {
+    /// Stake public key
+    #[serde(rename = "stake_public_key")]
+    pub stake_key: StakeKeyHex,
+
+    /// Voting purpose
+    ///
+    /// Catalyst expects the voting purpose is set to `0`
+    pub voting_purpose: Option<VotingPurpose>,
+
+    /// Deregistration transaction id
+    pub tx_id: TxId,
+
+    /// Deregistration Nonce
+    pub nonce: u64,
+
+    /// The slot the deregistration was found in
+    pub slot: u64,
+}
+
 fn ox_hex_<T, S>(v: &T, serializer: S) -> Result<S::Ok, S::Error>
 where
     T: AsRef<[u8]>,
@@ -0,0 +1,85 @@
+use std::sync::Mutex;
+
+use color_eyre::eyre::{eyre, Report, Result};
+use dashmap::DashMap;
+use postgres::Client;
+
+use super::DataProvider;
+use crate::data::{RawDeregistration, RawRegistration, SlotNo};
+use crate::db::queries::{
+    reward_balance::reward_balance,
+    staked_utxo_ada::{staked_utxo_ada, staked_utxo_ada_delta},
+    vote_registrations::{vote_deregistrations, vote_registrations},
+};
+use crate::verify::StakeKeyHash;
+
+/// Chain data queried from a `cardano-db-sync` instance
+///
+/// Registrations and stakes are queried over separate connections, so they can run at the same
+/// time.
+pub struct PostgresDataProvider {
+    registrations: Mutex<Client>,
+    stakes: Mutex<Client>,
+}
+
+impl PostgresDataProvider {
+    /// Creates a provider out of two connections to the same db-sync database
+    #[must_use]
+    pub fn new(registrations: Client, stakes: Client) -> Self {
+        Self {
+            registrations: Mutex::new(registrations),
+            stakes: Mutex::new(stakes),
+        }
+    }
+}
+
+impl DataProvider for PostgresDataProvider {
+    fn vote_registrations(
+        &self,
+        min_slot: SlotNo,
+        max_slot: SlotNo,
+    ) -> Result<Vec<RawRegistration>> {
+        let mut client = self.registrations.lock().unwrap();
+        vote_registrations(into_i64(min_slot)?, into_i64(max_slot)?, &mut client).map_err(db_error)
+    }
+
+    fn vote_deregistrations(
+        &self,
+        min_slot: SlotNo,
+        max_slot: SlotNo,
+    ) -> Result<Vec<RawDeregistration>> {
+        let mut client = self.registrations.lock().unwrap();
+        vote_deregistrations(into_i64(min_slot)?, into_i64(max_slot)?, &mut client)
+            .map_err(db_error)
+    }
+
+    fn staked_utxo_ada(&self, max_slot: SlotNo) -> Result<DashMap<StakeKeyHash, u128>> {
+        let mut client = self.stakes.lock().unwrap();
+        staked_utxo_ada(into_i64(max_slot)?, &mut client).map_err(db_error)
+    }
+
+    fn staked_utxo_ada_delta(
+        &self,
+        from_slot: SlotNo,
+        to_slot: SlotNo,
+    ) -> Result<DashMap<StakeKeyHash, i128>> {
+        let mut client = self.stakes.lock().unwrap();
+        staked_utxo_ada_delta(into_i64(from_slot)?, into_i64(to_slot)?, &mut client)
+            .map_err(db_error)
+    }
+
+    fn reward_balance(&self, max_slot: SlotNo) -> Result<DashMap<StakeKeyHash, u128>> {
+        let mut client = self.stakes.lock().unwrap();
+        reward_balance(into_i64(max_slot)?, &mut client).map_err(db_error)
+    }
+}
+
+fn into_i64(slot: SlotNo) -> Result<i64> {
+    slot.into_i64()
+        .ok_or_else(|| eyre!("slot {} doesn't fit in an i64", slot.0))
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn db_error(err: Box<dyn std::error::Error>) -> Report {
+    eyre!("db-sync query failed: {err}")
+}
@@ -0,0 +1,295 @@
+use std::{collections::HashMap, fs::File, path::Path, str::FromStr};
+
+use cardano_serialization_lib::{
+    address::{Address, BaseAddress, RewardAddress},
+    metadata::{GeneralTransactionMetadata, TransactionMetadatum},
+    utils::{hash_transaction, BigNum},
+    Block, Transaction,
+};
+use color_eyre::eyre::Result;
+use dashmap::DashMap;
+use serde::Deserialize;
+
+use super::DataProvider;
+use crate::data::{RawDeregistration, RawRegistration, SlotNo, TxId};
+use crate::verify::StakeKeyHash;
+
+/// Metadata keys
+const REGISTRATION_KEY: u32 = 61284;
+const SIGNATURE_KEY: u32 = 61285;
+const DEREGISTRATION_KEY: u32 = 61286;
+
+/// Chain data held in memory, built out of the content of `mainnet_lib::InMemoryDbSync`
+///
+/// The mocked db-sync does not track rewards, so every reward account balance is zero, unless
+/// set by unit tests.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryDbSyncProvider {
+    registrations: Vec<RawRegistration>,
+    deregistrations: Vec<RawDeregistration>,
+    outputs: Vec<StakedOutput>,
+    rewards: HashMap<StakeKeyHash, u128>,
+}
+
+/// Transaction output paying to a stake credential
+#[derive(Debug, Clone)]
+struct StakedOutput {
+    stake_credential: StakeKeyHash,
+    value: u128,
+    created_slot: u64,
+    spent_slot: Option<u64>,
+}
+
+impl StakedOutput {
+    fn is_unspent_at(&self, slot: SlotNo) -> bool {
+        self.created_slot <= slot.0 && self.spent_slot.map_or(true, |spent| spent > slot.0)
+    }
+}
+
+/// Layout of a persisted `mainnet_lib::InMemoryDbSync`, only the fields needed here
+#[derive(Deserialize)]
+struct PersistedDbSync {
+    transactions: HashMap<u32, Vec<Transaction>>,
+    blocks: Vec<Block>,
+}
+
+impl InMemoryDbSyncProvider {
+    /// Replays the transactions of each block, in block number order
+    ///
+    /// Blocks missing from `blocks` are assumed to have a slot number equal to their block
+    /// number, which is what `InMemoryDbSync` uses when querying by slot.
+    #[must_use]
+    pub fn new(blocks: &[Block], transactions: &HashMap<u32, Vec<Transaction>>) -> Self {
+        let slots: HashMap<u32, u64> = blocks
+            .iter()
+            .map(|block| {
+                let header = block.header().header_body();
+                (header.block_number(), to_u64(&header.slot_bignum()))
+            })
+            .collect();
+
+        let mut block_numbers: Vec<_> = transactions.keys().copied().collect();
+        block_numbers.sort_unstable();
+
+        let mut provider = Self::default();
+        let mut unspent = HashMap::new();
+        let mut tx_id = 0;
+
+        for block_number in block_numbers {
+            let slot = slots
+                .get(&block_number)
+                .copied()
+                .unwrap_or_else(|| u64::from(block_number));
+
+            for transaction in &transactions[&block_number] {
+                tx_id += 1;
+                let body = transaction.body();
+
+                let inputs = body.inputs();
+                for i in 0..inputs.len() {
+                    let input = inputs.get(i);
+                    let spent = (input.transaction_id().to_bytes(), input.index());
+                    if let Some(output) = unspent.remove(&spent) {
+                        let output: &mut StakedOutput = &mut provider.outputs[output];
+                        output.spent_slot = Some(slot);
+                    }
+                }
+
+                let tx_hash = hash_transaction(&body).to_bytes();
+                let outputs = body.outputs();
+                for i in 0..outputs.len() {
+                    let output = outputs.get(i);
+                    if let Some(stake_credential) = stake_credential(&output.address()) {
+                        let index = u32::try_from(i).expect("output index fits in a u32");
+                        unspent.insert((tx_hash.clone(), index), provider.outputs.len());
+                        provider.outputs.push(StakedOutput {
+                            stake_credential,
+                            value: u128::from(to_u64(&output.amount().coin())),
+                            created_slot: slot,
+                            spent_slot: None,
+                        });
+                    }
+                }
+
+                if let Some(metadata) = transaction
+                    .auxiliary_data()
+                    .and_then(|auxiliary_data| auxiliary_data.metadata())
+                {
+                    provider.add_metadata(&metadata, TxId(tx_id), slot);
+                }
+            }
+        }
+
+        provider
+    }
+
+    /// Restores the content of a db-sync persisted with `InMemoryDbSync::persist`
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be opened or its content cannot be deserialized
+    pub fn restore(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+        let db_sync: PersistedDbSync = serde_json::from_reader(file)?;
+        Ok(Self::new(&db_sync.blocks, &db_sync.transactions))
+    }
+
+    fn add_metadata(&mut self, metadata: &GeneralTransactionMetadata, tx_id: TxId, slot: u64) {
+        let Some(signature) = metadata.get(&BigNum::from(SIGNATURE_KEY)) else {
+            return;
+        };
+        let bin_sig = single_entry_metadata(SIGNATURE_KEY, &signature);
+
+        if let Some(registration) = metadata.get(&BigNum::from(REGISTRATION_KEY)) {
+            // db-sync json representation is not needed to validate the registration
+            self.registrations.push(RawRegistration {
+                json_reg: serde_json::Value::Null,
+                json_sig: serde_json::Value::Null,
+                bin_reg: single_entry_metadata(REGISTRATION_KEY, &registration),
+                bin_sig: bin_sig.clone(),
+                tx_id,
+                slot,
+            });
+        }
+
+        if let Some(deregistration) = metadata.get(&BigNum::from(DEREGISTRATION_KEY)) {
+            self.deregistrations.push(RawDeregistration {
+                bin_dereg: single_entry_metadata(DEREGISTRATION_KEY, &deregistration),
+                bin_sig,
+                tx_id,
+                slot,
+            });
+        }
+    }
+}
+
+/// Shortcuts to the chain data, for unit tests which do not go through transactions
+#[cfg(test)]
+impl InMemoryDbSyncProvider {
+    pub(crate) fn with_registration(mut self, registration: RawRegistration) -> Self {
+        self.registrations.push(registration);
+        self
+    }
+
+    /// Unspent output created at slot 0
+    pub(crate) fn with_stake(mut self, stake_credential: StakeKeyHash, value: u128) -> Self {
+        self.outputs.push(StakedOutput {
+            stake_credential,
+            value,
+            created_slot: 0,
+            spent_slot: None,
+        });
+        self
+    }
+
+    pub(crate) fn with_reward_balance(
+        mut self,
+        stake_credential: StakeKeyHash,
+        balance: u128,
+    ) -> Self {
+        self.rewards.insert(stake_credential, balance);
+        self
+    }
+}
+
+impl DataProvider for InMemoryDbSyncProvider {
+    fn vote_registrations(
+        &self,
+        min_slot: SlotNo,
+        max_slot: SlotNo,
+    ) -> Result<Vec<RawRegistration>> {
+        // same order as the db-sync query
+        Ok(self
+            .registrations
+            .iter()
+            .rev()
+            .filter(|reg| min_slot.0 <= reg.slot && reg.slot <= max_slot.0)
+            .cloned()
+            .collect())
+    }
+
+    fn vote_deregistrations(
+        &self,
+        min_slot: SlotNo,
+        max_slot: SlotNo,
+    ) -> Result<Vec<RawDeregistration>> {
+        Ok(self
+            .deregistrations
+            .iter()
+            .rev()
+            .filter(|dereg| min_slot.0 <= dereg.slot && dereg.slot <= max_slot.0)
+            .cloned()
+            .collect())
+    }
+
+    fn staked_utxo_ada(&self, max_slot: SlotNo) -> Result<DashMap<StakeKeyHash, u128>> {
+        let result = DashMap::new();
+        for output in self.outputs.iter().filter(|o| o.is_unspent_at(max_slot)) {
+            *result
+                .entry(output.stake_credential.clone())
+                .or_insert_with(|| 0) += output.value;
+        }
+        Ok(result)
+    }
+
+    fn staked_utxo_ada_delta(
+        &self,
+        from_slot: SlotNo,
+        to_slot: SlotNo,
+    ) -> Result<DashMap<StakeKeyHash, i128>> {
+        let in_range = |slot: u64| from_slot.0 < slot && slot <= to_slot.0;
+
+        let result = DashMap::new();
+        for output in &self.outputs {
+            let value = i128::try_from(output.value)?;
+            if in_range(output.created_slot) {
+                *result
+                    .entry(output.stake_credential.clone())
+                    .or_insert_with(|| 0) += value;
+            }
+            if output.spent_slot.map_or(false, in_range) {
+                *result
+                    .entry(output.stake_credential.clone())
+                    .or_insert_with(|| 0) -= value;
+            }
+        }
+        Ok(result)
+    }
+
+    fn reward_balance(&self, _max_slot: SlotNo) -> Result<DashMap<StakeKeyHash, u128>> {
+        Ok(self
+            .rewards
+            .iter()
+            .map(|(stake_credential, balance)| (stake_credential.clone(), *balance))
+            .collect())
+    }
+}
+
+/// Raw stake address (header byte followed by the stake credential) an output is staked with,
+/// which is how db-sync identifies stake addresses
+fn stake_credential(address: &Address) -> Option<StakeKeyHash> {
+    // the mock node pays the stake of its wallets straight to their stake address
+    if let Some(reward_address) = RewardAddress::from_address(address) {
+        return Some(reward_address.to_address().to_bytes());
+    }
+
+    let base_address = BaseAddress::from_address(address)?;
+    let network_id = address.network_id().ok()?;
+    Some(
+        RewardAddress::new(network_id, &base_address.stake_cred())
+            .to_address()
+            .to_bytes(),
+    )
+}
+
+/// CBOR encoding of a metadata map with a single entry, which is how db-sync stores each
+/// metadata key of a transaction
+fn single_entry_metadata(key: u32, value: &TransactionMetadatum) -> Vec<u8> {
+    let mut metadata = GeneralTransactionMetadata::new();
+    metadata.insert(&BigNum::from(key), value);
+    metadata.to_bytes()
+}
+
+fn to_u64(value: &BigNum) -> u64 {
+    u64::from_str(&value.to_str()).expect("BigNum is always a valid u64")
+}
@@ -0,0 +1,73 @@
+//! Sources of the chain data a voting power snapshot is computed from
+//!
+//! [`PostgresDataProvider`] queries a `cardano-db-sync` instance, while [`InMemoryDbSyncProvider`]
+//! serves the content of a mocked db-sync, so that snapshots can be computed in tests and offline
+//! rehearsals.
+
+use color_eyre::eyre::Result;
+use dashmap::DashMap;
+
+use crate::data::{RawDeregistration, RawRegistration, SlotNo};
+use crate::verify::StakeKeyHash;
+
+mod db_sync;
+mod in_memory;
+
+pub use db_sync::PostgresDataProvider;
+pub use in_memory::InMemoryDbSyncProvider;
+
+/// Chain data required to compute a voting power snapshot
+///
+/// Registrations and stakes are fetched concurrently, hence the `Sync` bound.
+pub trait DataProvider: Sync {
+    /// All the registration transactions with their signature, in the inclusive slot range
+    ///
+    /// # Errors
+    ///
+    /// Any errors produced by the underlying storage
+    fn vote_registrations(
+        &self,
+        min_slot: SlotNo,
+        max_slot: SlotNo,
+    ) -> Result<Vec<RawRegistration>>;
+
+    /// All the deregistration transactions with their signature, in the inclusive slot range
+    ///
+    /// # Errors
+    ///
+    /// Any errors produced by the underlying storage
+    fn vote_deregistrations(
+        &self,
+        min_slot: SlotNo,
+        max_slot: SlotNo,
+    ) -> Result<Vec<RawDeregistration>>;
+
+    /// Unspent UTXO value of every stake credential as of `max_slot`
+    ///
+    /// # Errors
+    ///
+    /// Any errors produced by the underlying storage
+    fn staked_utxo_ada(&self, max_slot: SlotNo) -> Result<DashMap<StakeKeyHash, u128>>;
+
+    /// Change in the unspent UTXO value of every stake credential after `from_slot`, up to and
+    /// including `to_slot`
+    ///
+    /// Adding it to `staked_utxo_ada(from_slot)` gives `staked_utxo_ada(to_slot)`. Stake
+    /// credentials whose value did not change may be left out.
+    ///
+    /// # Errors
+    ///
+    /// Any errors produced by the underlying storage
+    fn staked_utxo_ada_delta(
+        &self,
+        from_slot: SlotNo,
+        to_slot: SlotNo,
+    ) -> Result<DashMap<StakeKeyHash, i128>>;
+
+    /// Reward account balance of every stake credential as of `max_slot`
+    ///
+    /// # Errors
+    ///
+    /// Any errors produced by the underlying storage
+    fn reward_balance(&self, max_slot: SlotNo) -> Result<DashMap<StakeKeyHash, u128>>;
+}
@@ -1 +1,3 @@
+pub(crate) mod reward_balance;
 pub(crate) mod staked_utxo_ada;
+pub(crate) mod vote_registrations;
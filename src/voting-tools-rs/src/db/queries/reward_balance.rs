@@ -0,0 +1,80 @@
+use rust_decimal::Decimal;
+
+use dashmap::DashMap;
+use postgres::{fallible_iterator::FallibleIterator, Client};
+
+/// DB columns
+const STAKE_CREDENTIAL: usize = 0; // BYTEA
+const REWARDS: usize = 1; // NUMERIC
+const WITHDRAWALS: usize = 2; // NUMERIC
+
+///
+/// Get the reward account balance for all possible Stake Addresses.
+/// Given a maximum slot number
+///
+/// The balance is every reward which became spendable in or before the epoch containing
+/// `max_slot`, minus every withdrawal made in a block up to and including `max_slot`.
+///
+/// # Errors
+///
+/// Any errors produced by the DB get returned.
+///
+pub fn reward_balance(
+    max_slot: i64,
+    client: &mut Client,
+) -> Result<DashMap<Vec<u8>, u128>, Box<dyn std::error::Error>> {
+    info!("executing reward balance statement");
+
+    let reward_balance = format!(
+        "WITH snapshot_epoch AS (
+            SELECT MAX(block.epoch_no) AS epoch_no FROM block
+            WHERE block.slot_no <= {max_slot}),
+        rewards AS (
+            SELECT reward.addr_id, SUM(reward.amount) AS amount FROM reward, snapshot_epoch
+            WHERE reward.spendable_epoch <= snapshot_epoch.epoch_no
+            GROUP BY reward.addr_id),
+        withdrawals AS (
+            SELECT withdrawal.addr_id, SUM(withdrawal.amount) AS amount FROM withdrawal
+                  INNER JOIN tx ON withdrawal.tx_id = tx.id
+                  INNER JOIN block ON tx.block_id = block.id
+            WHERE block.slot_no <= {max_slot}
+            GROUP BY withdrawal.addr_id)
+        SELECT stake_address.hash_raw AS stake_credential,
+               rewards.amount AS rewards,
+               COALESCE(withdrawals.amount, 0) AS withdrawals FROM rewards
+        INNER JOIN stake_address ON stake_address.id = rewards.addr_id
+        LEFT OUTER JOIN withdrawals ON withdrawals.addr_id = rewards.addr_id;"
+    );
+
+    let params: [String; 0] = [];
+    let mut results = client.query_raw(&reward_balance, params)?;
+
+    let result = DashMap::new();
+
+    let mut processing_record = 0;
+    while let Some(row) = results.next()? {
+        let stake_hash: Vec<u8> = row.get(STAKE_CREDENTIAL);
+
+        // https://github.com/sfackler/rust-postgres/issues/119
+        let rewards: Decimal = row.get(REWARDS);
+        let withdrawals: Decimal = row.get(WITHDRAWALS);
+
+        let rewards = rust_decimal::prelude::ToPrimitive::to_u128(&rewards).unwrap();
+        let withdrawals = rust_decimal::prelude::ToPrimitive::to_u128(&withdrawals).unwrap();
+
+        // withdrawals can also draw on instant rewards (`reward_rest` in newer db-sync versions),
+        // so never go below zero
+        let balance = rewards.saturating_sub(withdrawals);
+
+        if balance > 0 {
+            *result.entry(stake_hash).or_insert_with(|| 0) += balance;
+        }
+
+        if processing_record % 1000 == 0 {
+            info!("{:?} reward records processed", processing_record);
+        }
+        processing_record += 1;
+    }
+
+    Ok(result)
+}
@@ -6,6 +6,8 @@ use postgres::{fallible_iterator::FallibleIterator, Client};
 /// DB columns
 const STAKE_CREDENTIAL: usize = 0; // BYTEA
 const STAKED_ADA: usize = 1; // NUMERIC
+const CREATED_ADA: usize = 1; // NUMERIC
+const SPENT_ADA: usize = 2; // NUMERIC
 
 ///
 /// Get ALL UTXO for all possible Stake Addresses.
@@ -81,3 +83,74 @@ pub fn staked_utxo_ada(
 
     Ok(result)
 }
+
+///
+/// Get the change in UTXO value for all possible Stake Addresses.
+/// Given a slot range, exclusive of `from_slot` and inclusive of `to_slot`
+///
+/// Outputs created in a block within the range count positively, outputs spent by an input of a
+/// block within the range count negatively. Adding the result to `staked_utxo_ada(from_slot)`
+/// gives `staked_utxo_ada(to_slot)`.
+///
+/// # Errors
+///
+/// Any errors produced by the DB get returned.
+///
+pub fn staked_utxo_ada_delta(
+    from_slot: i64,
+    to_slot: i64,
+    client: &mut Client,
+) -> Result<DashMap<Vec<u8>, i128>, Box<dyn std::error::Error>> {
+    info!("executing tx out delta statement");
+
+    let utxo_delta = format!(
+        "WITH created AS (
+            SELECT stake_address.hash_raw AS stake_credential, SUM(tx_out.value) AS value FROM tx_out
+                  INNER JOIN tx ON tx_out.tx_id = tx.id
+                  INNER JOIN block ON tx.block_id = block.id
+                  INNER JOIN stake_address ON stake_address.id = tx_out.stake_address_id
+            WHERE block.slot_no > {from_slot} AND block.slot_no <= {to_slot}
+            GROUP BY stake_address.hash_raw),
+        spent AS (
+            SELECT stake_address.hash_raw AS stake_credential, SUM(tx_out.value) AS value FROM tx_in
+                  INNER JOIN tx ON tx_in.tx_in_id = tx.id
+                  INNER JOIN block ON tx.block_id = block.id
+                  INNER JOIN tx_out ON
+                      tx_out.tx_id = tx_in.tx_out_id AND tx_out.index = tx_in.tx_out_index
+                  INNER JOIN stake_address ON stake_address.id = tx_out.stake_address_id
+            WHERE block.slot_no > {from_slot} AND block.slot_no <= {to_slot}
+            GROUP BY stake_address.hash_raw)
+        SELECT COALESCE(created.stake_credential, spent.stake_credential) AS stake_credential,
+               COALESCE(created.value, 0) AS created,
+               COALESCE(spent.value, 0) AS spent FROM created
+        FULL OUTER JOIN spent ON spent.stake_credential = created.stake_credential;"
+    );
+
+    let params: [String; 0] = [];
+    let mut results = client.query_raw(&utxo_delta, params)?;
+
+    let result = DashMap::new();
+
+    let mut processing_record = 0;
+    while let Some(row) = results.next()? {
+        let stake_hash: Vec<u8> = row.get(STAKE_CREDENTIAL);
+
+        // https://github.com/sfackler/rust-postgres/issues/119
+        let created: Decimal = row.get(CREATED_ADA);
+        let spent: Decimal = row.get(SPENT_ADA);
+
+        let created = rust_decimal::prelude::ToPrimitive::to_i128(&created).unwrap();
+        let spent = rust_decimal::prelude::ToPrimitive::to_i128(&spent).unwrap();
+
+        if created != spent {
+            *result.entry(stake_hash).or_insert_with(|| 0) += created - spent;
+        }
+
+        if processing_record % 1000 == 0 {
+            info!("{:?} delta records processed", processing_record);
+        }
+        processing_record += 1;
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,109 @@
+use postgres::{fallible_iterator::FallibleIterator, Client};
+
+use crate::data::{RawDeregistration, RawRegistration, TxId};
+
+/// DB columns
+const TX_ID: usize = 0; // BIGINT
+const SLOT_NO: usize = 1; // BIGINT
+const META_JSON: usize = 2; // JSONB
+const META_BIN: usize = 3; // BYTEA
+const SIG_JSON: usize = 4; // JSONB
+const SIG_BIN: usize = 5; // BYTEA
+
+/// Metadata keys
+const REGISTRATION_KEY: i64 = 61284;
+const DEREGISTRATION_KEY: i64 = 61286;
+
+///
+/// Get ALL registration transactions, along with their signature.
+/// Given a minimum and maximum slot number
+///
+/// # Errors
+///
+/// Any errors produced by the DB get returned.
+///
+pub fn vote_registrations(
+    min_slot: i64,
+    max_slot: i64,
+    client: &mut Client,
+) -> Result<Vec<RawRegistration>, Box<dyn std::error::Error>> {
+    info!("executing vote registrations statement");
+
+    let rows = signed_metadata(REGISTRATION_KEY, min_slot, max_slot, client)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| RawRegistration {
+            json_reg: row.get(META_JSON),
+            json_sig: row.get(SIG_JSON),
+            bin_reg: row.get(META_BIN),
+            bin_sig: row.get(SIG_BIN),
+            tx_id: TxId(row.get::<_, i64>(TX_ID) as u64),
+            slot: row.get::<_, i64>(SLOT_NO) as u64,
+        })
+        .collect())
+}
+
+///
+/// Get ALL deregistration transactions, along with their signature.
+/// Given a minimum and maximum slot number
+///
+/// # Errors
+///
+/// Any errors produced by the DB get returned.
+///
+pub fn vote_deregistrations(
+    min_slot: i64,
+    max_slot: i64,
+    client: &mut Client,
+) -> Result<Vec<RawDeregistration>, Box<dyn std::error::Error>> {
+    info!("executing vote deregistrations statement");
+
+    let rows = signed_metadata(DEREGISTRATION_KEY, min_slot, max_slot, client)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| RawDeregistration {
+            bin_dereg: row.get(META_BIN),
+            bin_sig: row.get(SIG_BIN),
+            tx_id: TxId(row.get::<_, i64>(TX_ID) as u64),
+            slot: row.get::<_, i64>(SLOT_NO) as u64,
+        })
+        .collect())
+}
+
+/// Metadata under `key`, along with the 61285 signature of the same transaction
+fn signed_metadata(
+    key: i64,
+    min_slot: i64,
+    max_slot: i64,
+    client: &mut Client,
+) -> Result<Vec<postgres::Row>, Box<dyn std::error::Error>> {
+    let signed_metadata = format!(
+        "
+        SELECT meta_table.tx_id,
+        block.slot_no,
+        meta_table.json as meta_json,
+        meta_table.bytes as meta_bytes,
+        sig_table.json as sig_json,
+        sig_table.bytes as sig_bytes
+     FROM (((tx_metadata AS meta_table INNER JOIN tx
+        ON (tx.id = meta_table.tx_id)) INNER JOIN tx_metadata AS sig_table
+       ON (sig_table.tx_id = meta_table.tx_id)) INNER JOIN block ON (block.id = tx.block_id))
+     WHERE ((((meta_table.key = {key}) AND (sig_table.key = 61285))) AND
+        ((block.slot_no >= $1) AND (block.slot_no <= $2))) ORDER BY meta_table.tx_id DESC;
+    "
+    );
+
+    let mut results = client.query_raw(&signed_metadata, &[&min_slot, &max_slot])?;
+
+    let mut rows = vec![];
+    while let Some(row) = results.next()? {
+        if rows.len() % 1000 == 0 {
+            info!("{:?} metadata records fetched", rows.len());
+        }
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
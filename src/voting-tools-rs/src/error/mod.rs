@@ -5,7 +5,7 @@ use serde::Serialize;
 use serde_json::Value;
 use thiserror::Error;
 
-use crate::data::{NetworkId, SignedRegistration, TxId, VotingPurpose};
+use crate::data::{NetworkId, SignedDeregistration, SignedRegistration, TxId, VotingPurpose};
 
 /// An error encountered during parsing and validation of a Catalyst registration
 #[derive(Debug, Error, PartialEq, Eq, Serialize)]
@@ -65,6 +65,9 @@ pub enum RegistrationError {
     #[error("Obsolete registration")]
     ObsoleteRegistration,
 
+    #[error("Registration cancelled by a later deregistration")]
+    Deregistered,
+
     #[error("Cddl parsing failed {err}")]
     CddlParsingFailed { err: String },
 
@@ -74,6 +77,9 @@ pub enum RegistrationError {
     #[error("Raw binary conversion of cbor to Registration failure {err}")]
     RawBinCborRegistrationFailure { err: String },
 
+    #[error("Raw binary conversion of cbor to Deregistration failure {err}")]
+    RawBinCborDeregistrationFailure { err: String },
+
     #[error("Raw binary conversion of cbor to Signature failure {err}")]
     RawBinCborSignatureFailure { err: String },
 
@@ -87,11 +93,18 @@ pub enum RegistrationError {
 /// rejected
 ///
 /// `registration` is an `Option` because some errors prevent us from even generating it
+///
+/// Invalid deregistrations are reported the same way, with `spec_61286` and `deregistration`
+/// set instead of `spec_61284` and `registration`
 #[derive(Debug, Serialize)]
 pub struct InvalidRegistration {
     pub spec_61284: Option<String>,
     pub spec_61285: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spec_61286: Option<String>,
     pub registration: Option<SignedRegistration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deregistration: Option<SignedDeregistration>,
     pub registration_bad_bin: Option<RegistrationCorruptedBin>,
     pub errors: NonEmpty<RegistrationError>,
 }
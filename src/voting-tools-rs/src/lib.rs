@@ -31,7 +31,7 @@ extern crate tracing;
 
 mod cli;
 mod data;
-// mod data_provider;
+mod data_provider;
 mod db;
 mod error;
 mod logic;
@@ -42,7 +42,11 @@ pub mod verification;
 pub use exports::*;
 mod exports {
     pub use crate::cli::{show_error_warning, Args, DryRunCommand};
-    pub use crate::data::{Sig, Signature, SlotNo, SnapshotEntry, VotingKey, VotingPurpose};
+    pub use crate::data::{
+        DeregistrationEntry, RawDeregistration, RawRegistration, Sig, Signature, SlotNo,
+        SnapshotEntry, VotingKey, VotingPurpose,
+    };
+    pub use crate::data_provider::{DataProvider, InMemoryDbSyncProvider, PostgresDataProvider};
     pub use crate::db::DbConfig;
     pub use crate::error::*;
     pub use crate::logic::{voting_power, VotingPowerArgs};
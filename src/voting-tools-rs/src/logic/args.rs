@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use crate::{
     data::{NetworkId, VotingPurpose},
     SlotNo,
@@ -17,6 +19,15 @@ pub struct VotingPowerArgs {
     pub expected_voting_purpose: VotingPurpose,
     /// Are Multiple Delegations supported or not.
     pub cip_36_multidelegations: bool,
+    /// Whether reward account balances count toward voting power
+    pub include_reward_balances: bool,
+    /// File persisting the staked UTXO value as of the last processed slot
+    ///
+    /// If set, the stake is brought forward from that slot instead of being computed from the
+    /// whole chain history, and the file is updated to `max_slot`, which must then be set.
+    pub stake_state: Option<PathBuf>,
+    /// Check the stake brought forward from `stake_state` against a full recomputation
+    pub verify_stake_state: bool,
 }
 
 impl Default for VotingPowerArgs {
@@ -27,6 +38,9 @@ impl Default for VotingPowerArgs {
             network_id: NetworkId::Mainnet,
             expected_voting_purpose: VotingPurpose::CATALYST,
             cip_36_multidelegations: false,
+            include_reward_balances: false,
+            stake_state: None,
+            verify_stake_state: false,
         }
     }
 }
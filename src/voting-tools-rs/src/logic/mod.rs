@@ -3,26 +3,26 @@
 use std::thread;
 
 use crate::{
-    data::{Registration, SignedRegistration, SlotNo},
-    db::queries::staked_utxo_ada::staked_utxo_ada,
+    data::{DeregistrationEntry, Registration, SignedDeregistration, SignedRegistration, SlotNo},
+    data_provider::DataProvider,
     error::InvalidRegistration,
     verify::{filter_registrations, StakeKeyHash},
     SnapshotEntry,
 };
 
 use crate::verify::Unregistered;
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{bail, Result};
 use dashmap::DashMap;
 
-use postgres::Client;
-
 mod args;
+mod stake_state;
 pub use args::VotingPowerArgs;
 
-/// Calculate voting power info by querying a db-sync instance
+/// Calculate voting power info out of the chain data served by `provider`
 ///
 /// Returns a tuple containing the successful snapshot entries, as well as any registrations which
-/// failed verification in some way (along with some reason why they failed).
+/// failed verification in some way (along with some reason why they failed), the stake of
+/// unregistered stake keys and the stake keys which were deregistered.
 ///
 /// If provided, `min_slot` and `max_slot` can  be used to constrain the time period to query. If
 /// `None` they default to:
@@ -31,61 +31,129 @@ pub use args::VotingPowerArgs;
 ///
 /// Together they form an inclusive range (i.e. blocks with values equal to `min_slot` or `max_slot` are included)
 ///
+/// Reward account balances as of `max_slot` are always reported on each entry, but only count
+/// toward voting power (and the unregistered stake) if `include_reward_balances` is set.
+///
+/// If `stake_state` is set, the staked UTXO value is computed incrementally from the state
+/// persisted there, see [`VotingPowerArgs::stake_state`].
+///
 /// # Errors
 ///
-/// Returns an error if either of `lower` or `upper` doesn't fit in an `i64`
+/// Returns an error if the provider fails to serve the chain data, e.g. if either of `lower` or
+/// `upper` doesn't fit in an `i64` for db-sync, or if the stake state cannot be used
 pub fn voting_power(
-    mut db_client_stakes: Client,
-    db_client_registrations: Client,
+    provider: &impl DataProvider,
     VotingPowerArgs {
         min_slot,
         max_slot,
         network_id,
         expected_voting_purpose: _,
         cip_36_multidelegations,
+        include_reward_balances,
+        stake_state,
+        verify_stake_state,
     }: VotingPowerArgs,
-) -> Result<(Vec<SnapshotEntry>, Vec<InvalidRegistration>, Unregistered)> {
+) -> Result<(
+    Vec<SnapshotEntry>,
+    Vec<InvalidRegistration>,
+    Unregistered,
+    Vec<DeregistrationEntry>,
+)> {
     const ABS_MIN_SLOT: SlotNo = SlotNo(0);
     const ABS_MAX_SLOT: SlotNo = SlotNo(i64::MAX as u64);
 
+    // the stake state would be saved at whatever the chain tip was, with no way to tell later on
+    if stake_state.is_some() && max_slot.is_none() {
+        bail!("a stake state can only be used with an explicit max slot");
+    }
+
     let min_slot = min_slot.unwrap_or(ABS_MIN_SLOT);
     let max_slot = max_slot.unwrap_or(ABS_MAX_SLOT);
 
-    info!("starting stakes job");
-    let stakes = thread::spawn(move || {
-        staked_utxo_ada(i64::try_from(max_slot.0).unwrap(), &mut db_client_stakes).unwrap()
-    });
+    let (registrations, stakes) = thread::scope(|scope| {
+        info!("starting stakes job");
+        let stakes = scope.spawn(|| -> Result<_> {
+            let staked_ada = match &stake_state {
+                Some(path) => {
+                    stake_state::staked_utxo_ada(provider, path, max_slot, verify_stake_state)?
+                }
+                None => provider.staked_utxo_ada(max_slot)?,
+            };
+            let rewards = provider.reward_balance(max_slot)?;
+            Ok((staked_ada, rewards))
+        });
 
-    info!("starting registrations job");
-    let registrations = thread::spawn(move || {
-        filter_registrations(
-            min_slot,
-            max_slot,
-            db_client_registrations,
-            network_id,
-            cip_36_multidelegations,
-        )
-        .unwrap()
+        info!("starting registrations job");
+        let registrations = scope.spawn(|| -> Result<_> {
+            let registrations = provider.vote_registrations(min_slot, max_slot)?;
+            let deregistrations = provider.vote_deregistrations(min_slot, max_slot)?;
+            Ok(filter_registrations(
+                registrations,
+                deregistrations,
+                network_id,
+                cip_36_multidelegations,
+            ))
+        });
+
+        (registrations.join().unwrap(), stakes.join().unwrap())
     });
 
-    let (valids, invalids) = registrations.join().unwrap();
+    let (valids, invalids, deregistered) = registrations?;
     info!("finished processing registrations");
 
     // UTXOs for all possible Stake Addresses
-    let staked_ada_records = stakes.join().unwrap();
+    let (staked_ada_records, reward_balance_records) = stakes?;
     info!("finished processing stakes");
 
     let snapshot = valids
         .into_iter()
-        .map(|reg| convert_to_snapshot_entry(reg, &staked_ada_records))
+        .map(|reg| {
+            convert_to_snapshot_entry(
+                reg,
+                &staked_ada_records,
+                &reward_balance_records,
+                include_reward_balances,
+            )
+        })
         .collect::<Result<_, _>>()?;
 
-    Ok((snapshot, invalids, staked_ada_records))
+    if include_reward_balances {
+        // what is left are reward accounts of stake addresses that are not registered
+        for (stake_key_hash, reward_balance) in reward_balance_records {
+            *staked_ada_records.entry(stake_key_hash).or_insert(0) += reward_balance;
+        }
+    }
+
+    let deregistered = deregistered
+        .into_iter()
+        .map(convert_to_deregistration_entry)
+        .collect();
+
+    Ok((snapshot, invalids, staked_ada_records, deregistered))
+}
+
+fn convert_to_deregistration_entry(deregistration: SignedDeregistration) -> DeregistrationEntry {
+    let SignedDeregistration {
+        deregistration,
+        tx_id,
+        slot,
+        ..
+    } = deregistration;
+
+    DeregistrationEntry {
+        stake_key: deregistration.stake_key,
+        voting_purpose: deregistration.voting_purpose,
+        tx_id,
+        nonce: deregistration.nonce.0,
+        slot,
+    }
 }
 
 fn convert_to_snapshot_entry(
     registration: SignedRegistration,
     stakes: &DashMap<StakeKeyHash, u128>,
+    rewards: &DashMap<StakeKeyHash, u128>,
+    include_reward_balances: bool,
 ) -> Result<SnapshotEntry> {
     let SignedRegistration {
         registration:
@@ -102,23 +170,136 @@ fn convert_to_snapshot_entry(
     } = registration;
 
     // look up stake key hash of valid registration in stakes map to obtain staked ada associated with the key
-    let voting_power = if let Some(voting_power) = stakes.get(&stake_key_hash) {
-        *voting_power
+    let utxo_stake = if let Some(utxo_stake) = stakes.get(&stake_key_hash) {
+        *utxo_stake
     } else {
         // Registrations with no staked ada. No UTXO's.
         0
     };
 
+    // Registrations which never earned rewards have no entry
+    let reward_balance = rewards.get(&stake_key_hash).map_or(0, |balance| *balance);
+
+    let voting_power = if include_reward_balances {
+        utxo_stake + reward_balance
+    } else {
+        utxo_stake
+    };
+
     // remove registered, what is left (difference) are stake addresses that are not registered
     stakes.remove(&stake_key_hash);
+    rewards.remove(&stake_key_hash);
 
     Ok(SnapshotEntry {
         voting_key,
         rewards_address,
         stake_key,
         voting_power,
+        utxo_stake,
+        reward_balance,
         voting_purpose,
         tx_id,
         nonce: nonce.0,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{NetworkId, PubKey, StakeKeyHex};
+    use crate::data_provider::InMemoryDbSyncProvider;
+    use crate::verify::{
+        stake_key_hash,
+        tests::{registration, stake_key},
+    };
+    use cardano_serialization_lib::crypto::PrivateKey;
+
+    fn stake_credential(key: &PrivateKey) -> StakeKeyHash {
+        stake_key_hash(
+            &StakeKeyHex(PubKey(key.to_public().as_bytes())),
+            NetworkId::Testnet,
+        )
+    }
+
+    fn args(include_reward_balances: bool) -> VotingPowerArgs {
+        VotingPowerArgs {
+            network_id: NetworkId::Testnet,
+            include_reward_balances,
+            ..VotingPowerArgs::default()
+        }
+    }
+
+    /// Alice is registered, Bob is not, both have stake and rewards
+    fn provider() -> InMemoryDbSyncProvider {
+        let alice = stake_key(1);
+        let bob = stake_key(2);
+
+        InMemoryDbSyncProvider::default()
+            .with_registration(registration(&alice, 10, None, 1))
+            .with_stake(stake_credential(&alice), 1_000)
+            .with_stake(stake_credential(&alice), 500)
+            .with_reward_balance(stake_credential(&alice), 200)
+            .with_stake(stake_credential(&bob), 3_000)
+            .with_reward_balance(stake_credential(&bob), 40)
+    }
+
+    #[test]
+    fn utxo_stake_and_reward_balance_are_reported() {
+        let (snapshot, invalids, _, _) = voting_power(&provider(), args(false)).unwrap();
+
+        assert!(invalids.is_empty());
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].utxo_stake, 1_500);
+        assert_eq!(snapshot[0].reward_balance, 200);
+    }
+
+    #[test]
+    fn reward_balances_count_toward_voting_power_only_if_included() {
+        let (snapshot, _, _, _) = voting_power(&provider(), args(false)).unwrap();
+        assert_eq!(snapshot[0].voting_power, 1_500);
+
+        let (snapshot, _, _, _) = voting_power(&provider(), args(true)).unwrap();
+        assert_eq!(snapshot[0].voting_power, 1_700);
+        assert_eq!(snapshot[0].utxo_stake, 1_500);
+        assert_eq!(snapshot[0].reward_balance, 200);
+    }
+
+    #[test]
+    fn unregistered_reward_balances_are_merged_if_included() {
+        let alice = stake_credential(&stake_key(1));
+        let bob = stake_credential(&stake_key(2));
+        // rewards of a stake credential without any UTXO
+        let carol = stake_credential(&stake_key(3));
+        let provider = provider().with_reward_balance(carol.clone(), 7);
+
+        let (_, _, unregistered, _) = voting_power(&provider, args(false)).unwrap();
+        assert!(!unregistered.contains_key(&alice));
+        assert_eq!(*unregistered.get(&bob).unwrap(), 3_000);
+        assert!(!unregistered.contains_key(&carol));
+
+        let (_, _, unregistered, _) = voting_power(&provider, args(true)).unwrap();
+        assert!(!unregistered.contains_key(&alice));
+        assert_eq!(*unregistered.get(&bob).unwrap(), 3_040);
+        assert_eq!(*unregistered.get(&carol).unwrap(), 7);
+    }
+
+    #[test]
+    fn registrations_without_stake_have_no_voting_power() {
+        let stakes = DashMap::new();
+        let rewards = DashMap::new();
+        let key = stake_key(1);
+        let signed = filter_registrations(
+            vec![registration(&key, 10, None, 1)],
+            Vec::new(),
+            NetworkId::Testnet,
+            false,
+        )
+        .0
+        .remove(0);
+
+        let entry = convert_to_snapshot_entry(signed, &stakes, &rewards, true).unwrap();
+        assert_eq!(entry.voting_power, 0);
+        assert_eq!(entry.utxo_stake, 0);
+        assert_eq!(entry.reward_balance, 0);
+    }
+}
@@ -0,0 +1,229 @@
+//! Incremental computation of the staked UTXO value
+//!
+//! Computing the unspent UTXO value of every stake credential walks the whole chain history. To
+//! avoid doing so on every run, the result is persisted to a local file along with the slot it
+//! was computed at, and a later run with a higher `max_slot` only applies the outputs created and
+//! spent since then.
+//!
+//! db-sync rolls back the most recent blocks on chain switches, so the persisted slot should be
+//! old enough to be immutable. `verify` catches a state that no longer matches the chain.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use color_eyre::eyre::{bail, eyre, Result};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{data::SlotNo, data_provider::DataProvider, verify::StakeKeyHash};
+
+/// Content of the stake state file
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct StakeState {
+    /// Last processed slot, inclusive
+    slot: u64,
+    /// Unspent UTXO value of every stake credential, keyed by hex encoded stake address
+    stakes: BTreeMap<String, u128>,
+}
+
+impl StakeState {
+    fn new(slot: SlotNo, stakes: &DashMap<StakeKeyHash, u128>) -> Self {
+        Self {
+            slot: slot.0,
+            stakes: stakes
+                .iter()
+                .map(|entry| (hex::encode(entry.key()), *entry.value()))
+                .collect(),
+        }
+    }
+
+    fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file = File::open(path)?;
+        Ok(Some(serde_json::from_reader(BufReader::new(file))?))
+    }
+
+    /// Writes to a temporary file first, so an interrupted run doesn't leave a corrupted state
+    fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    fn into_stakes(self) -> Result<DashMap<StakeKeyHash, u128>> {
+        self.stakes
+            .into_iter()
+            .map(|(stake_key_hash, stake)| Ok((hex::decode(stake_key_hash)?, stake)))
+            .collect()
+    }
+}
+
+/// Unspent UTXO value of every stake credential as of `max_slot`, using and updating the stake
+/// state persisted in `path`
+///
+/// The state is brought forward from its slot when it is behind `max_slot`, and recomputed from
+/// scratch when there is none or it is ahead of `max_slot`. If `verify` is set, the stake is
+/// always recomputed from scratch as well and the two must match.
+pub(crate) fn staked_utxo_ada(
+    provider: &impl DataProvider,
+    path: &Path,
+    max_slot: SlotNo,
+    verify: bool,
+) -> Result<DashMap<StakeKeyHash, u128>> {
+    let incremental = match StakeState::load(path)? {
+        Some(state) if state.slot <= max_slot.0 => {
+            info!(
+                "updating stake state from slot {} to {}",
+                state.slot, max_slot.0
+            );
+            let from_slot = SlotNo(state.slot);
+            let stakes = state.into_stakes()?;
+            if from_slot < max_slot {
+                apply_delta(
+                    &stakes,
+                    provider.staked_utxo_ada_delta(from_slot, max_slot)?,
+                )?;
+            }
+            Some(stakes)
+        }
+        Some(state) => {
+            info!(
+                "stake state is at slot {}, after {}, recomputing it",
+                state.slot, max_slot.0
+            );
+            None
+        }
+        None => {
+            info!("no stake state found at {}", path.to_string_lossy());
+            None
+        }
+    };
+
+    let stakes = match incremental {
+        Some(stakes) if verify => {
+            info!("verifying stake state against a full recomputation");
+            let expected = provider.staked_utxo_ada(max_slot)?;
+            let mismatches = count_mismatches(&stakes, &expected);
+            if mismatches > 0 {
+                bail!(
+                    "stake state differs from a full recomputation for {mismatches} stake \
+                    credentials, remove {} to start over",
+                    path.to_string_lossy()
+                );
+            }
+            stakes
+        }
+        Some(stakes) => stakes,
+        None => provider.staked_utxo_ada(max_slot)?,
+    };
+
+    StakeState::new(max_slot, &stakes).save(path)?;
+    info!("stake state saved at slot {}", max_slot.0);
+
+    Ok(stakes)
+}
+
+/// Stake credentials left without any unspent output are dropped, as they would be by a full
+/// recomputation
+fn apply_delta(
+    stakes: &DashMap<StakeKeyHash, u128>,
+    delta: DashMap<StakeKeyHash, i128>,
+) -> Result<()> {
+    for (stake_key_hash, change) in delta {
+        let current = stakes.get(&stake_key_hash).map_or(0, |stake| *stake);
+        let updated = i128::try_from(current)?
+            .checked_add(change)
+            .ok_or_else(|| eyre!("stake overflow"))?;
+
+        match u128::try_from(updated) {
+            Ok(0) => {
+                stakes.remove(&stake_key_hash);
+            }
+            Ok(updated) => {
+                stakes.insert(stake_key_hash, updated);
+            }
+            Err(_) => bail!(
+                "stake of {} would become negative, the stake state doesn't match the chain",
+                hex::encode(stake_key_hash)
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn count_mismatches(
+    actual: &DashMap<StakeKeyHash, u128>,
+    expected: &DashMap<StakeKeyHash, u128>,
+) -> usize {
+    let missing = expected
+        .iter()
+        .filter(|entry| !actual.contains_key(entry.key()))
+        .count();
+    let different = actual
+        .iter()
+        .filter(|entry| expected.get(entry.key()).map(|stake| *stake) != Some(*entry.value()))
+        .count();
+    missing + different
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stakes(entries: &[(u8, u128)]) -> DashMap<StakeKeyHash, u128> {
+        entries
+            .iter()
+            .map(|(key, stake)| (vec![*key], *stake))
+            .collect()
+    }
+
+    #[test]
+    fn delta_updates_stakes_and_drops_emptied_credentials() {
+        let current = stakes(&[(1, 100), (2, 50)]);
+        let delta = [(vec![1], -30), (vec![2], -50), (vec![3], 20)]
+            .into_iter()
+            .collect();
+
+        apply_delta(&current, delta).unwrap();
+
+        assert_eq!(count_mismatches(&current, &stakes(&[(1, 70), (3, 20)])), 0);
+        assert!(!current.contains_key(&vec![2]));
+    }
+
+    #[test]
+    fn delta_cannot_make_stake_negative() {
+        let current = stakes(&[(1, 100)]);
+        let delta = [(vec![1], -101)].into_iter().collect();
+
+        assert!(apply_delta(&current, delta).is_err());
+    }
+
+    #[test]
+    fn mismatches_are_counted_both_ways() {
+        let actual = stakes(&[(1, 100), (2, 50), (4, 1)]);
+        let expected = stakes(&[(1, 100), (2, 40), (3, 10)]);
+
+        assert_eq!(count_mismatches(&actual, &expected), 3);
+    }
+
+    #[test]
+    fn state_round_trips() {
+        let state = StakeState::new(SlotNo(42), &stakes(&[(1, u128::from(u64::MAX) + 1)]));
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: StakeState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored, state);
+        let restored = restored.into_stakes().unwrap();
+        assert_eq!(*restored.get(&vec![1]).unwrap(), u128::from(u64::MAX) + 1);
+    }
+}
@@ -1,29 +1,26 @@
 use std::collections::HashMap;
 
 use dashmap::DashMap;
-use postgres::fallible_iterator::FallibleIterator;
-use postgres::Client;
 
-use crate::data::{NetworkId, RawRegistration, SignedRegistration, StakeKeyHex, TxId};
+use crate::data::{
+    Deregistration, NetworkId, RawDeregistration, RawRegistration, Registration,
+    SignedDeregistration, SignedRegistration, StakeKeyHex, VotingPurpose,
+};
 use crate::{InvalidRegistration, RegistrationCorruptedBin, RegistrationError, SlotNo};
 use cryptoxide::{blake2b::Blake2b, digest::Digest};
 
 use nonempty::nonempty;
 
-/// DB columns
-const REG_TX_ID: usize = 2;
-const REG_SLOT_NO: usize = 4;
-const REG_JSON: usize = 6;
-const REG_BIN: usize = 7;
-const SIG_JSON: usize = 9;
-const SIG_BIN: usize = 10;
-
 /// Contains the most recent registration for each public stake address
 pub type Valids = Vec<SignedRegistration>;
 
 /// Registrations which failed cddl and or sig checks
 pub type Invalids = Vec<InvalidRegistration>;
 
+/// Contains the most recent deregistration for each public stake address, which is not
+/// superseded by a later registration
+pub type Deregistered = Vec<SignedDeregistration>;
+
 /// `Network_id` + Blake2b-224( Stake Public Key )
 pub type StakeKeyHash = Vec<u8>;
 
@@ -31,51 +28,22 @@ pub type StakeKeyHash = Vec<u8>;
 pub type Unregistered = DashMap<Vec<u8>, u128>;
 
 ///
-/// Query gathers all possible registration transactions
-/// Each registration is screened and marked: valid or invalid
-///
-/// # Errors
-///
-/// Any errors produced by the DB get returned.
+/// Screens all the registration and deregistration transactions gathered from the chain
+/// Each one is marked: valid or invalid
+/// Registrations cancelled by a later deregistration are dropped from the valids
 ///
 pub fn filter_registrations(
-    min_slot: SlotNo,
-    max_slot: SlotNo,
-    mut client: Client,
+    registrations: Vec<RawRegistration>,
+    deregistrations: Vec<RawDeregistration>,
     network_id: NetworkId,
     cip_36_multidelegations: bool,
-) -> Result<(Valids, Invalids), Box<dyn std::error::Error>> {
+) -> (Valids, Invalids, Deregistered) {
     let mut valids: Valids = vec![];
     let mut invalids: Invalids = vec![];
 
     let cddl = CddlConfig::new();
 
-    let mut results = client.query_raw(
-        "
-        SELECT meta_table.id as reg_id,
-        sig_table.id as sig_id,
-        meta_table.tx_id as reg_tx_id,
-        sig_table.tx_id as sig_tx_id,
-        block.slot_no,
-        meta_table.key as reg_key,
-        meta_table.json as reg_json,
-        meta_table.bytes as reg_bytes,
-        sig_table.key as sig_key,
-        sig_table.json as sig_json,
-        sig_table.bytes as sig_bytes
-     FROM (((tx_metadata AS meta_table INNER JOIN tx
-        ON (tx.id = meta_table.tx_id)) INNER JOIN tx_metadata AS sig_table
-       ON (sig_table.tx_id = meta_table.tx_id)) INNER JOIN block ON (block.id = tx.block_id))
-     WHERE ((((meta_table.key = 61284) AND (sig_table.key = 61285))) AND
-        ((block.slot_no >= $1) AND (block.slot_no <= $2))) ORDER BY meta_table.tx_id DESC;
-    ",
-        &[
-            &i64::try_from(min_slot.0).unwrap(),
-            &i64::try_from(max_slot.0).unwrap(),
-        ],
-    )?;
-
-    while let Some(row) = results.next()? {
+    for rawreg in registrations {
         // Here we can use a threadpool with a size == number of cores.
         // We can process each row in parallel using this pool.
         if valids.len() % 1000 == 0 {
@@ -85,33 +53,21 @@ pub fn filter_registrations(
             );
         }
 
-        // registration tx_id
-        let tx_id: i64 = row.get(REG_TX_ID);
-        let slot: i64 = row.get(REG_SLOT_NO);
-
-        // The raw registration data from the database.
-        let rawreg = RawRegistration {
-            json_reg: row.get(REG_JSON),
-            json_sig: row.get(SIG_JSON),
-            bin_reg: row.get(REG_BIN),
-            bin_sig: row.get(SIG_BIN),
-            tx_id: TxId(tx_id as u64),
-            slot: slot as u64,
-        };
-
         // deserialize the raw Binary CBOR.
-        let reg = match rawreg.to_signed(&cddl, network_id, SlotNo(slot as u64)) {
+        let reg = match rawreg.to_signed(&cddl, network_id, SlotNo(rawreg.slot)) {
             Err(err) => {
                 invalids.push(InvalidRegistration {
                     spec_61284: Some(prefix_hex(&rawreg.bin_reg)),
                     spec_61285: Some(prefix_hex(&rawreg.bin_sig)),
+                    spec_61286: None,
                     registration: None,
+                    deregistration: None,
                     errors: nonempty![RegistrationError::CborDeserializationFailed {
                         err: format!("Failed to deserialize Registration CBOR: {err}"),
                     }],
                     registration_bad_bin: Some(RegistrationCorruptedBin {
-                        tx_id: TxId(tx_id as u64),
-                        slot: slot as u64,
+                        tx_id: rawreg.tx_id,
+                        slot: rawreg.slot,
                     }),
                 });
                 continue;
@@ -125,7 +81,9 @@ pub fn filter_registrations(
                 invalids.push(InvalidRegistration {
                     spec_61284: Some(prefix_hex(&rawreg.bin_reg)),
                     spec_61285: Some(prefix_hex(&rawreg.bin_sig)),
+                    spec_61286: None,
                     registration: Some(reg),
+                    deregistration: None,
                     errors: nonempty![RegistrationError::SignatureError {
                         err: format!("Signature validation failure: {err}"),
                     }],
@@ -141,7 +99,9 @@ pub fn filter_registrations(
                 invalids.push(InvalidRegistration {
                     spec_61284: Some(prefix_hex(&rawreg.bin_reg)),
                     spec_61285: Some(prefix_hex(&rawreg.bin_sig)),
+                    spec_61286: None,
                     registration: Some(reg),
+                    deregistration: None,
                     errors: nonempty![err],
                     registration_bad_bin: None,
                 });
@@ -152,11 +112,82 @@ pub fn filter_registrations(
         valids.push(reg);
     }
 
-    Ok((latest_registrations(&valids, &mut invalids), invalids))
+    let deregistrations = filter_deregistrations(deregistrations, network_id, &cddl, &mut invalids);
+
+    let (valids, deregistered) = latest_registrations(&valids, &deregistrations, &mut invalids);
+
+    (valids, invalids, deregistered)
+}
+
+///
+/// Screens all the deregistration transactions gathered from the chain
+/// Invalid ones are added to `invalids`
+///
+fn filter_deregistrations(
+    deregistrations: Vec<RawDeregistration>,
+    network_id: NetworkId,
+    cddl: &CddlConfig,
+    invalids: &mut Invalids,
+) -> Vec<SignedDeregistration> {
+    let mut valids = vec![];
+
+    for rawdereg in deregistrations {
+        // deserialize the raw Binary CBOR.
+        let dereg = match rawdereg.to_signed(cddl, network_id, SlotNo(rawdereg.slot)) {
+            Err(err) => {
+                invalids.push(InvalidRegistration {
+                    spec_61284: None,
+                    spec_61285: Some(prefix_hex(&rawdereg.bin_sig)),
+                    spec_61286: Some(prefix_hex(&rawdereg.bin_dereg)),
+                    registration: None,
+                    deregistration: None,
+                    errors: nonempty![RegistrationError::CborDeserializationFailed {
+                        err: format!("Failed to deserialize Deregistration CBOR: {err}"),
+                    }],
+                    registration_bad_bin: Some(RegistrationCorruptedBin {
+                        tx_id: rawdereg.tx_id,
+                        slot: rawdereg.slot,
+                    }),
+                });
+                continue;
+            }
+            Ok(dereg) => dereg,
+        };
+
+        if let Err(err) = dereg.validate_signature_bin(rawdereg.bin_dereg.clone()) {
+            invalids.push(InvalidRegistration {
+                spec_61284: None,
+                spec_61285: Some(prefix_hex(&rawdereg.bin_sig)),
+                spec_61286: Some(prefix_hex(&rawdereg.bin_dereg)),
+                registration: None,
+                deregistration: Some(dereg),
+                errors: nonempty![RegistrationError::SignatureError {
+                    err: format!("Signature validation failure: {err}"),
+                }],
+                registration_bad_bin: None,
+            });
+            continue;
+        }
+
+        valids.push(dereg);
+    }
+
+    info!("deregistrations processed {:?}", valids.len());
+
+    valids
 }
 
 /// Each stake key can have multiple registrations, the latest must be identified and the rest partitioned
-pub fn latest_registrations(valids: &Valids, invalids: &mut Invalids) -> Valids {
+///
+/// The latest deregistration of a stake key, for each voting purpose, cancels its latest
+/// registration if they have the same voting purpose and the nonce of the registration is not
+/// greater, the rule of `Deregistration::cancels` in the snapshot library. Deregistrations
+/// superseded by a later registration have no effect and are not reported.
+pub fn latest_registrations(
+    valids: &Valids,
+    deregistrations: &[SignedDeregistration],
+    invalids: &mut Invalids,
+) -> (Valids, Deregistered) {
     let mut latest: HashMap<StakeKeyHex, SignedRegistration> = HashMap::new();
 
     for valid in valids {
@@ -168,7 +199,9 @@ pub fn latest_registrations(valids: &Valids, invalids: &mut Invalids) -> Valids
                 invalids.push(InvalidRegistration {
                     spec_61284: None,
                     spec_61285: None,
+                    spec_61286: None,
                     registration: Some(current.clone()),
+                    deregistration: None,
                     errors: nonempty![RegistrationError::ObsoleteRegistration {}],
                     registration_bad_bin: None,
                 });
@@ -177,7 +210,9 @@ pub fn latest_registrations(valids: &Valids, invalids: &mut Invalids) -> Valids
                 invalids.push(InvalidRegistration {
                     spec_61284: None,
                     spec_61285: None,
+                    spec_61286: None,
                     registration: Some(valid.clone()),
+                    deregistration: None,
                     errors: nonempty![RegistrationError::ObsoleteRegistration {}],
                     registration_bad_bin: None,
                 });
@@ -187,7 +222,62 @@ pub fn latest_registrations(valids: &Valids, invalids: &mut Invalids) -> Valids
         }
     }
 
-    latest.values().cloned().collect()
+    let mut latest_deregistrations: HashMap<(&StakeKeyHex, u64), &SignedDeregistration> =
+        HashMap::new();
+
+    for dereg in deregistrations {
+        let purpose = voting_purpose(dereg.deregistration.voting_purpose);
+        latest_deregistrations
+            .entry((&dereg.deregistration.stake_key, purpose.0))
+            .and_modify(|current| {
+                if dereg.deregistration.nonce > current.deregistration.nonce {
+                    *current = dereg;
+                }
+            })
+            .or_insert(dereg);
+    }
+
+    let mut deregistered = Deregistered::new();
+
+    for ((stake_key, purpose), dereg) in latest_deregistrations {
+        match latest.get(stake_key) {
+            Some(current) if cancels(&dereg.deregistration, &current.registration) => {
+                let cancelled = latest
+                    .remove(stake_key)
+                    .expect("registration was just found");
+                invalids.push(InvalidRegistration {
+                    spec_61284: None,
+                    spec_61285: None,
+                    spec_61286: None,
+                    registration: Some(cancelled),
+                    deregistration: Some(dereg.clone()),
+                    errors: nonempty![RegistrationError::Deregistered {}],
+                    registration_bad_bin: None,
+                });
+                deregistered.push(dereg.clone());
+            }
+            Some(current) if voting_purpose(current.registration.voting_purpose).0 == purpose => {
+                // registered again after deregistering
+            }
+            _ => deregistered.push(dereg.clone()),
+        }
+    }
+
+    (latest.values().cloned().collect(), deregistered)
+}
+
+/// A deregistration cancels the registrations of its stake key for the same voting purpose with
+/// a nonce not greater than its own
+fn cancels(deregistration: &Deregistration, registration: &Registration) -> bool {
+    deregistration.stake_key == registration.stake_key
+        && voting_purpose(deregistration.voting_purpose)
+            == voting_purpose(registration.voting_purpose)
+        && registration.nonce <= deregistration.nonce
+}
+
+/// Registrations and deregistrations without a voting purpose are for Catalyst
+fn voting_purpose(purpose: Option<VotingPurpose>) -> VotingPurpose {
+    purpose.unwrap_or(VotingPurpose::CATALYST)
 }
 
 /// The registration has a 32 byte "Stake Public Key".  This is the raw ED25519 public key of the stake address.
@@ -260,6 +350,24 @@ pub fn validate_reg_cddl(
     Ok(())
 }
 
+/// Validate raw deregistration binary against 61286 CDDL spec
+///
+/// # Errors
+///
+/// Failure will occur if parsed keys do not match CDDL spec
+pub fn validate_dereg_cddl(
+    bin_dereg: &[u8],
+    cddl_config: &CddlConfig,
+) -> Result<(), RegistrationError> {
+    cddl::validate_cbor_from_slice(&cddl_config.spec_61286, bin_dereg, None).map_err(|err| {
+        RegistrationError::CddlParsingFailed {
+            err: format!("dereg bytes does not match 61286 spec: {err}"),
+        }
+    })?;
+
+    Ok(())
+}
+
 /// Validate raw signature binary against 61285 CDDL spec
 ///
 /// # Errors
@@ -283,6 +391,7 @@ pub fn validate_sig_cddl(
 pub struct CddlConfig {
     spec_61284: String,
     spec_61285: String,
+    spec_61286: String,
 }
 
 impl CddlConfig {
@@ -290,10 +399,12 @@ impl CddlConfig {
     pub fn new() -> Self {
         let cddl_61284: String = include_str!("61284.cddl").to_string();
         let cddl_61285: String = include_str!("61285.cddl").to_string();
+        let cddl_61286: String = include_str!("61286.cddl").to_string();
 
         CddlConfig {
             spec_61284: cddl_61284,
             spec_61285: cddl_61285,
+            spec_61286: cddl_61286,
         }
     }
 }
@@ -311,8 +422,11 @@ pub fn prefix_hex(b: &[u8]) -> String {
 }
 
 #[cfg(test)]
-mod tests {
-    use crate::{data::NetworkId, verify::is_valid_rewards_address};
+pub(crate) mod tests {
+    use super::*;
+    use crate::data::{Nonce, PubKey, TxId};
+    use cardano_serialization_lib::crypto::PrivateKey;
+    use ciborium::value::Value;
 
     #[test]
     pub fn test_rewards_addr_permuations() {
@@ -342,4 +456,248 @@ mod tests {
             assert!(!is_valid_rewards_address(&test_addr, NetworkId::Mainnet));
         }
     }
+
+    // nonces are capped to the slot of the transaction
+    const SLOT: u64 = 1_000_000;
+
+    fn int(i: u64) -> Value {
+        Value::Integer(i.into())
+    }
+
+    fn cbor(value: &Value) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(value, &mut bytes).unwrap();
+        bytes
+    }
+
+    pub(crate) fn stake_key(seed: u8) -> PrivateKey {
+        PrivateKey::from_normal_bytes(&[seed; 32]).unwrap()
+    }
+
+    /// The metadata under `label`, and the 61285 witness signed with `key`
+    fn signed(key: &PrivateKey, label: u64, metadata: Vec<(Value, Value)>) -> (Vec<u8>, Vec<u8>) {
+        let bin = cbor(&Value::Map(vec![(int(label), Value::Map(metadata))]));
+
+        let mut digest = [0u8; 32];
+        let mut context = Blake2b::new(32);
+        context.input(&bin);
+        context.result(&mut digest);
+
+        let witness = key.sign(&digest).to_bytes();
+        let bin_sig = cbor(&Value::Map(vec![(
+            int(61285),
+            Value::Map(vec![(int(1), Value::Bytes(witness))]),
+        )]));
+
+        (bin, bin_sig)
+    }
+
+    /// Valid registration of `key` on testnet, with a legacy voting key
+    pub(crate) fn registration(
+        key: &PrivateKey,
+        nonce: u64,
+        purpose: Option<u64>,
+        tx_id: u64,
+    ) -> RawRegistration {
+        let mut metadata = vec![
+            (int(1), Value::Bytes(vec![1; 32])),
+            (int(2), Value::Bytes(key.to_public().as_bytes())),
+            (int(3), Value::Bytes([vec![0xe0], vec![2; 28]].concat())),
+            (int(4), int(nonce)),
+        ];
+        metadata.extend(purpose.map(|purpose| (int(5), int(purpose))));
+        let (bin_reg, bin_sig) = signed(key, 61284, metadata);
+
+        RawRegistration {
+            json_reg: serde_json::Value::Null,
+            json_sig: serde_json::Value::Null,
+            bin_reg,
+            bin_sig,
+            tx_id: TxId(tx_id),
+            slot: SLOT,
+        }
+    }
+
+    fn deregistration_metadata(
+        key: &PrivateKey,
+        nonce: u64,
+        purpose: Option<u64>,
+    ) -> Vec<(Value, Value)> {
+        let mut metadata = vec![
+            (int(1), Value::Bytes(key.to_public().as_bytes())),
+            (int(2), int(nonce)),
+        ];
+        metadata.extend(purpose.map(|purpose| (int(3), int(purpose))));
+        metadata
+    }
+
+    fn deregistration(
+        key: &PrivateKey,
+        nonce: u64,
+        purpose: Option<u64>,
+        tx_id: u64,
+    ) -> RawDeregistration {
+        let (bin_dereg, bin_sig) = signed(key, 61286, deregistration_metadata(key, nonce, purpose));
+
+        RawDeregistration {
+            bin_dereg,
+            bin_sig,
+            tx_id: TxId(tx_id),
+            slot: SLOT,
+        }
+    }
+
+    fn filter(
+        registrations: Vec<RawRegistration>,
+        deregistrations: Vec<RawDeregistration>,
+    ) -> (Valids, Invalids, Deregistered) {
+        filter_registrations(registrations, deregistrations, NetworkId::Testnet, false)
+    }
+
+    #[test]
+    fn deregistration_matches_61286_spec() {
+        let cddl = CddlConfig::new();
+        let key = stake_key(1);
+
+        for purpose in [None, Some(0), Some(1)] {
+            let dereg = deregistration(&key, 10, purpose, 1);
+            assert!(validate_dereg_cddl(&dereg.bin_dereg, &cddl).is_ok());
+            assert!(validate_sig_cddl(&dereg.bin_sig, &cddl).is_ok());
+        }
+
+        let mut without_nonce = deregistration_metadata(&key, 10, None);
+        without_nonce.remove(1);
+        let (bin_dereg, _) = signed(&key, 61286, without_nonce);
+        assert!(validate_dereg_cddl(&bin_dereg, &cddl).is_err());
+
+        let short_key = vec![(int(1), Value::Bytes(vec![1; 31])), (int(2), int(10))];
+        let (bin_dereg, _) = signed(&key, 61286, short_key);
+        assert!(validate_dereg_cddl(&bin_dereg, &cddl).is_err());
+
+        let (registration, _) = signed(&key, 61284, deregistration_metadata(&key, 10, None));
+        assert!(validate_dereg_cddl(&registration, &cddl).is_err());
+    }
+
+    #[test]
+    fn deregistration_signature_is_checked() {
+        let cddl = CddlConfig::new();
+        let key = stake_key(1);
+
+        let raw = deregistration(&key, 10, Some(0), 1);
+        let dereg = raw
+            .to_signed(&cddl, NetworkId::Testnet, SlotNo(SLOT))
+            .unwrap();
+        assert_eq!(
+            StakeKeyHex(PubKey(key.to_public().as_bytes())),
+            dereg.deregistration.stake_key
+        );
+        assert_eq!(Nonce(10), dereg.deregistration.nonce);
+        assert_eq!(Some(VotingPurpose(0)), dereg.deregistration.voting_purpose);
+        assert!(dereg.validate_signature_bin(raw.bin_dereg.clone()).is_ok());
+
+        // signed with another key than the one deregistered
+        let (bin_dereg, bin_sig) = signed(
+            &stake_key(2),
+            61286,
+            deregistration_metadata(&key, 20, None),
+        );
+        let forged = RawDeregistration {
+            bin_dereg,
+            bin_sig,
+            tx_id: TxId(2),
+            slot: SLOT,
+        };
+        let dereg = forged
+            .to_signed(&cddl, NetworkId::Testnet, SlotNo(SLOT))
+            .unwrap();
+        assert!(dereg
+            .validate_signature_bin(forged.bin_dereg.clone())
+            .is_err());
+
+        let (valids, invalids, deregistered) =
+            filter(vec![registration(&key, 10, None, 3)], vec![forged]);
+        assert_eq!(1, valids.len());
+        assert!(deregistered.is_empty());
+        assert_eq!(1, invalids.len());
+        assert!(matches!(
+            invalids[0].errors.head,
+            RegistrationError::SignatureError { .. }
+        ));
+    }
+
+    #[test]
+    fn deregistration_cancels_registrations_with_a_nonce_not_greater() {
+        let key = stake_key(1);
+
+        // same nonce: the deregistration wins
+        let (valids, invalids, deregistered) = filter(
+            vec![registration(&key, 10, None, 1)],
+            vec![deregistration(&key, 10, None, 2)],
+        );
+        assert!(valids.is_empty());
+        assert_eq!(1, deregistered.len());
+        assert_eq!(1, invalids.len());
+        assert!(matches!(
+            invalids[0].errors.head,
+            RegistrationError::Deregistered
+        ));
+
+        // registered again after deregistering
+        let (valids, invalids, deregistered) = filter(
+            vec![
+                registration(&key, 10, None, 1),
+                registration(&key, 30, None, 3),
+            ],
+            vec![deregistration(&key, 20, None, 2)],
+        );
+        assert_eq!(1, valids.len());
+        assert_eq!(Nonce(30), valids[0].registration.nonce);
+        assert!(deregistered.is_empty());
+        assert_eq!(1, invalids.len());
+        assert!(matches!(
+            invalids[0].errors.head,
+            RegistrationError::ObsoleteRegistration
+        ));
+
+        // the latest deregistration is the one with the highest nonce, whatever its slot
+        let (valids, _, deregistered) = filter(
+            vec![registration(&key, 15, None, 1)],
+            vec![
+                deregistration(&key, 20, None, 2),
+                deregistration(&key, 10, None, 3),
+            ],
+        );
+        assert!(valids.is_empty());
+        assert_eq!(1, deregistered.len());
+        assert_eq!(Nonce(20), deregistered[0].deregistration.nonce);
+    }
+
+    #[test]
+    fn deregistration_only_cancels_its_voting_purpose() {
+        let key = stake_key(1);
+
+        let (valids, invalids, deregistered) = filter(
+            vec![registration(&key, 10, None, 1)],
+            vec![deregistration(&key, 20, Some(1), 2)],
+        );
+        assert_eq!(1, valids.len());
+        assert!(invalids.is_empty());
+        assert_eq!(1, deregistered.len());
+
+        // no voting purpose is the catalyst voting purpose
+        let (valids, invalids, deregistered) = filter(
+            vec![registration(&key, 10, None, 1)],
+            vec![
+                deregistration(&key, 20, Some(0), 2),
+                deregistration(&key, 30, Some(1), 3),
+            ],
+        );
+        assert!(valids.is_empty());
+        assert_eq!(2, deregistered.len());
+        assert_eq!(1, invalids.len());
+        assert!(matches!(
+            invalids[0].errors.head,
+            RegistrationError::Deregistered
+        ));
+    }
 }